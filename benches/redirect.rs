@@ -0,0 +1,259 @@
+// benches/redirect.rs - Benchmarks for the redirect hot path
+//
+// Run with `cargo bench --bench redirect`. The lookup group needs a migrated
+// database and is skipped unless DATABASE_URL is set.
+
+use std::hint::black_box;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use chrono::{Duration, Utc};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_json::json;
+use tokio::runtime::Runtime;
+use uuid::Uuid;
+
+use url_shortener::cache::{AccessBacklog, LinkCache};
+use url_shortener::config::Config;
+use url_shortener::db::Database;
+use url_shortener::errors::AppError;
+use url_shortener::events::EventBus;
+use url_shortener::models::shortened_url::{
+    CreateShortenedUrlDto, LinkBehavior, LinkStatus, OrderDirection, ShortenedUrl,
+    ShortenedUrlQueryParams, SortField,
+};
+use url_shortener::repositories::ShortenedUrlRepository;
+use url_shortener::services::{ShortenedUrlService, ShortenedUrlServiceTrait};
+use url_shortener::utils::id_generator::{
+    generate_short_id, BASE62_ALPHABET, UNAMBIGUOUS_ALPHABET,
+};
+use url_shortener::utils::url_rewrite::{append_path, merge_query};
+
+const BENCH_ALIAS: &str = "bench-redirect";
+
+fn code_generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("code_generation");
+    group.throughput(Throughput::Elements(1));
+
+    for (name, alphabet) in [
+        ("base62", BASE62_ALPHABET),
+        ("unambiguous", UNAMBIGUOUS_ALPHABET),
+    ] {
+        for length in [6, 12] {
+            group.bench_with_input(BenchmarkId::new(name, length), &length, |b, &length| {
+                b.iter(|| generate_short_id(black_box(length), black_box(alphabet)))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+fn query_builder(c: &mut Criterion) {
+    let mut group = c.benchmark_group("query_builder");
+
+    let defaults = ShortenedUrlQueryParams::default();
+    group.bench_function("defaults", |b| {
+        b.iter(|| ShortenedUrlRepository::find_query(black_box(&defaults)).into_sql())
+    });
+
+    let filtered = ShortenedUrlQueryParams {
+        limit: Some(50),
+        offset: Some(100),
+        is_expired: Some(false),
+        is_active: Some(true),
+        is_custom_code: Some(true),
+        short_code: Some("promo".to_string()),
+        namespace: Some("marketing".to_string()),
+        order_by: Some(SortField::AccessCount.into()),
+        original_url: Some("example.com".to_string()),
+        min_access_count: Some(10),
+        created_after: Some(Utc::now() - Duration::days(30)),
+        created_before: Some(Utc::now()),
+        order_direction: Some(OrderDirection::Desc),
+        ..Default::default()
+    };
+    group.bench_function("all_filters", |b| {
+        b.iter(|| ShortenedUrlRepository::find_query(black_box(&filtered)).into_sql())
+    });
+
+    group.finish();
+}
+
+fn redirect_checks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("redirect_checks");
+
+    // A row as loaded from the database, spelled out since links are only built by inserting
+    let open = ShortenedUrl {
+        id: Uuid::new_v4(),
+        original_url: "https://example.com/landing?utm_source=short".to_string(),
+        short_code: "landing".to_string(),
+        namespace: None,
+        tenant_id: None,
+        created_at: Utc::now(),
+        last_accessed: None,
+        access_count: 0,
+        expires_at: None,
+        created_by: None,
+        is_custom_code: false,
+        is_active: true,
+        metadata: None,
+        throttled_until: None,
+        status: LinkStatus::Active,
+        allowed_cidrs: Vec::new(),
+        denied_cidrs: Vec::new(),
+        single_use: false,
+        consumed_at: None,
+        forward_path: false,
+        forward_query: false,
+        fallback_url: None,
+        max_idle_days: None,
+        upgrade_https: None,
+        last_checked_at: None,
+        check_status: None,
+        is_broken: false,
+        behavior: LinkBehavior::Redirect,
+        app_url: None,
+        og_title: None,
+        og_description: None,
+        og_image: None,
+        max_redirects_per_minute: None,
+        notes: None,
+        archived_at: None,
+        version: 1,
+    };
+    let restricted = ShortenedUrl {
+        allowed_cidrs: vec![
+            "10.0.0.0/8".parse().unwrap(),
+            "192.168.0.0/16".parse().unwrap(),
+        ],
+        denied_cidrs: vec!["10.1.0.0/16".parse().unwrap()],
+        ..open.clone()
+    };
+    let ip: IpAddr = "10.2.3.4".parse().unwrap();
+
+    group.bench_function("is_valid", |b| b.iter(|| black_box(&open).is_valid()));
+    group.bench_function("allows_ip_open", |b| {
+        b.iter(|| black_box(&open).allows_ip(black_box(Some(ip))))
+    });
+    group.bench_function("allows_ip_restricted", |b| {
+        b.iter(|| black_box(&restricted).allows_ip(black_box(Some(ip))))
+    });
+    group.bench_function("append_path", |b| {
+        b.iter(|| {
+            append_path(
+                black_box(&open.original_url),
+                black_box("docs/guide"),
+                black_box("page=2"),
+            )
+        })
+    });
+    group.bench_function("merge_query", |b| {
+        b.iter(|| {
+            merge_query(
+                black_box(&open.original_url),
+                black_box("utm_source=mail&ref=abc"),
+            )
+        })
+    });
+
+    group.finish();
+}
+
+fn lookup(c: &mut Criterion) {
+    if std::env::var("DATABASE_URL").is_err() {
+        eprintln!("DATABASE_URL is not set, skipping the lookup benchmarks");
+        return;
+    }
+
+    let runtime = Runtime::new().expect("Failed to start the tokio runtime");
+    let config = Config::load().expect("Failed to load configuration");
+    let db = runtime
+        .block_on(Database::connect(&config.db))
+        .expect("Failed to connect to the database");
+    let repository = Arc::new(ShortenedUrlRepository::new(db));
+    let service = ShortenedUrlService::new(
+        repository.clone(),
+        config.short_codes.clone(),
+        config.pagination.clone(),
+        config.milestones.clone(),
+        Arc::new(LinkCache::disabled()),
+        Arc::new(AccessBacklog::new(0)),
+        Arc::new(EventBus::disabled()),
+    );
+    let cached = ShortenedUrlService::new(
+        repository,
+        config.short_codes.clone(),
+        config.pagination.clone(),
+        config.milestones.clone(),
+        Arc::new(LinkCache::new(
+            &config.link_cache,
+            config.short_codes.case_insensitive,
+        )),
+        Arc::new(AccessBacklog::new(0)),
+        Arc::new(EventBus::disabled()),
+    );
+
+    // Make sure there is a link to look up
+    runtime.block_on(async {
+        match service.get_by_code(None, None, BENCH_ALIAS).await {
+            Ok(_) => {}
+            Err(AppError::NotFound(_)) => {
+                let dto: CreateShortenedUrlDto = serde_json::from_value(json!({
+                    "original_url": "https://example.com/benchmark",
+                    "custom_alias": BENCH_ALIAS,
+                }))
+                .unwrap();
+                service
+                    .create(dto, None, None)
+                    .await
+                    .expect("Failed to create the benchmark link");
+            }
+            Err(e) => panic!("Failed to look up the benchmark link: {}", e),
+        }
+    });
+
+    let mut group = c.benchmark_group("lookup");
+    group.bench_function("get_by_code_hit", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(service.get_by_code(None, None, black_box(BENCH_ALIAS)))
+                .unwrap()
+        })
+    });
+    group.bench_function("get_by_code_hit_cached", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(cached.get_by_code(None, None, black_box(BENCH_ALIAS)))
+                .unwrap()
+        })
+    });
+    group.bench_function("get_by_code_miss", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(service.get_by_code(None, None, black_box("bench-missing")))
+                .unwrap_err()
+        })
+    });
+    group.bench_function("get_by_path_nested", |b| {
+        b.iter(|| {
+            runtime
+                .block_on(service.get_by_path(
+                    None,
+                    black_box(BENCH_ALIAS),
+                    black_box("docs/guide"),
+                ))
+                .unwrap_err()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    code_generation,
+    query_builder,
+    redirect_checks,
+    lookup
+);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls DROP CONSTRAINT IF EXISTS shortened_urls_short_code_check;
+ALTER TABLE shortened_urls ALTER COLUMN short_code TYPE VARCHAR(10);
+ALTER TABLE shortened_urls ADD CONSTRAINT shortened_urls_short_code_check
+    CHECK (short_code ~ '^[a-zA-Z0-9]+$');
+
+COMMENT ON COLUMN shortened_urls.short_code IS 'Unique alphanumeric shortcode used in the shortened URL (max 10 chars)';
+
+COMMIT;
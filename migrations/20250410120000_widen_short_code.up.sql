@@ -0,0 +1,12 @@
+-- Add up migration script here
+BEGIN;
+
+-- Custom aliases may be up to 100 chars and include hyphens/underscores
+ALTER TABLE shortened_urls DROP CONSTRAINT IF EXISTS shortened_urls_short_code_check;
+ALTER TABLE shortened_urls ALTER COLUMN short_code TYPE VARCHAR(100);
+ALTER TABLE shortened_urls ADD CONSTRAINT shortened_urls_short_code_check
+    CHECK (short_code ~ '^[a-zA-Z0-9_-]+$');
+
+COMMENT ON COLUMN shortened_urls.short_code IS 'Unique shortcode used in the shortened URL (max 100 chars, alphanumeric, hyphens and underscores)';
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Add down migration script here
+BEGIN;
+
+DROP TABLE IF EXISTS url_visit_daily;
+DROP TABLE IF EXISTS url_visits;
+
+COMMIT;
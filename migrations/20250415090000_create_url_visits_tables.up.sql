@@ -0,0 +1,30 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE url_visits (
+    id BIGSERIAL PRIMARY KEY,
+    url_id UUID NOT NULL REFERENCES shortened_urls(id) ON DELETE CASCADE,
+    visited_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    ip_address TEXT,
+    user_agent TEXT,
+    referer TEXT
+);
+
+CREATE TABLE url_visit_daily (
+    url_id UUID NOT NULL REFERENCES shortened_urls(id) ON DELETE CASCADE,
+    day DATE NOT NULL,
+    visits BIGINT NOT NULL DEFAULT 0,
+    PRIMARY KEY (url_id, day)
+);
+
+-- Create indices for performance optimization
+CREATE INDEX idx_url_visits_url_id_visited_at ON url_visits(url_id, visited_at);
+CREATE INDEX idx_url_visits_visited_at ON url_visits(visited_at);
+
+-- Add table and column descriptions
+COMMENT ON TABLE url_visits IS 'Raw per-redirect visit events, rolled up into url_visit_daily once past retention';
+COMMENT ON COLUMN url_visits.ip_address IS 'Resolved client IP of the visitor';
+COMMENT ON TABLE url_visit_daily IS 'Daily visit counts per URL for events older than the raw retention window';
+COMMENT ON COLUMN url_visit_daily.day IS 'UTC calendar day the visits happened on';
+
+COMMIT;
@@ -0,0 +1,9 @@
+-- Add down migration script here
+BEGIN;
+
+DROP TABLE IF EXISTS account_exports;
+ALTER TABLE shortened_urls DROP COLUMN IF EXISTS created_by;
+DROP TABLE IF EXISTS api_keys;
+DROP TABLE IF EXISTS users;
+
+COMMIT;
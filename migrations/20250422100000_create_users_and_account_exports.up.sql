@@ -0,0 +1,47 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE users (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    email TEXT NOT NULL UNIQUE CHECK (LENGTH(email) <= 320),
+    display_name TEXT,
+    settings JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE api_keys (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    key_hash TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+ALTER TABLE shortened_urls
+    ADD COLUMN created_by UUID REFERENCES users(id) ON DELETE SET NULL;
+
+CREATE TABLE account_exports (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'running', 'completed', 'failed')),
+    archive BYTEA,
+    error TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Create indices for performance optimization
+CREATE INDEX idx_api_keys_user_id ON api_keys(user_id);
+CREATE INDEX idx_shortened_urls_created_by ON shortened_urls(created_by) WHERE created_by IS NOT NULL;
+CREATE INDEX idx_account_exports_user_id ON account_exports(user_id, created_at);
+
+-- Add table and column descriptions
+COMMENT ON TABLE users IS 'Accounts that own shortened URLs';
+COMMENT ON COLUMN users.settings IS 'User preferences in JSON format';
+COMMENT ON TABLE api_keys IS 'Bearer credentials for the API, stored as SHA-256 hashes';
+COMMENT ON COLUMN shortened_urls.created_by IS 'Owner of the URL, NULL for anonymous links';
+COMMENT ON TABLE account_exports IS 'Asynchronous data export requests and their gzip-compressed JSON archive';
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Add down migration script here
+BEGIN;
+
+DROP TABLE IF EXISTS expiry_notifications;
+DROP TABLE IF EXISTS notification_preferences;
+
+COMMIT;
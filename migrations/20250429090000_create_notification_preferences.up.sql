@@ -0,0 +1,23 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE notification_preferences (
+    user_id UUID PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    expiry_emails BOOLEAN NOT NULL DEFAULT TRUE,
+    expiry_notice_days INTEGER NOT NULL CHECK (expiry_notice_days BETWEEN 1 AND 30),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE expiry_notifications (
+    url_id UUID NOT NULL REFERENCES shortened_urls(id) ON DELETE CASCADE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    sent_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (url_id, expires_at)
+);
+
+-- Add table and column descriptions
+COMMENT ON TABLE notification_preferences IS 'Per-user notification settings, users without a row get the configured defaults';
+COMMENT ON COLUMN notification_preferences.expiry_notice_days IS 'How many days before a link expires its owner is emailed';
+COMMENT ON TABLE expiry_notifications IS 'Expiry emails already sent, keyed by expiry so extending a link re-arms the notice';
+
+COMMIT;
@@ -0,0 +1,21 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE notification_channels (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    kind TEXT NOT NULL CHECK (kind IN ('slack', 'discord', 'webhook')),
+    target_url TEXT NOT NULL CHECK (LENGTH(target_url) <= 2048),
+    events TEXT[] NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create indices for performance optimization
+CREATE INDEX idx_notification_channels_user_id ON notification_channels(user_id);
+
+-- Add table and column descriptions
+COMMENT ON TABLE notification_channels IS 'Team channels (Slack, Discord, generic webhooks) that receive a user''s events';
+COMMENT ON COLUMN notification_channels.events IS 'Event kinds delivered to the channel, empty for all events';
+
+COMMIT;
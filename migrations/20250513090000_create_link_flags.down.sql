@@ -0,0 +1,7 @@
+-- Add down migration script here
+BEGIN;
+
+DROP TABLE IF EXISTS link_flags;
+ALTER TABLE shortened_urls DROP COLUMN IF EXISTS throttled_until;
+
+COMMIT;
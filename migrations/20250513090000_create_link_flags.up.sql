@@ -0,0 +1,26 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN throttled_until TIMESTAMP WITH TIME ZONE;
+
+CREATE TABLE link_flags (
+    id BIGSERIAL PRIMARY KEY,
+    url_id UUID NOT NULL REFERENCES shortened_urls(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL,
+    action TEXT NOT NULL CHECK (action IN ('notify', 'throttle')),
+    clicks BIGINT NOT NULL,
+    distinct_ips BIGINT NOT NULL,
+    window_seconds INTEGER NOT NULL,
+    flagged_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Create indices for performance optimization
+CREATE UNIQUE INDEX idx_link_flags_open ON link_flags(url_id) WHERE resolved_at IS NULL;
+
+-- Add table and column descriptions
+COMMENT ON COLUMN shortened_urls.throttled_until IS 'Redirects are refused until this time after a click anomaly';
+COMMENT ON TABLE link_flags IS 'Links flagged by the anomaly detector, at most one open flag per link';
+
+COMMIT;
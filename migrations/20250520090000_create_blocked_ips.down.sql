@@ -0,0 +1,6 @@
+-- Add down migration script here
+BEGIN;
+
+DROP TABLE IF EXISTS blocked_ips;
+
+COMMIT;
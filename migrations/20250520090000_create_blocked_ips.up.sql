@@ -0,0 +1,16 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE blocked_ips (
+    ip TEXT PRIMARY KEY,
+    reason TEXT NOT NULL,
+    source TEXT NOT NULL CHECK (source IN ('auto', 'manual')),
+    blocked_until TIMESTAMP WITH TIME ZONE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Add table and column descriptions
+COMMENT ON TABLE blocked_ips IS 'Client addresses refused by the scanner guard, blocked automatically or by an admin';
+COMMENT ON COLUMN blocked_ips.blocked_until IS 'End of the block, NULL for a permanent block';
+
+COMMIT;
@@ -0,0 +1,8 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    DROP COLUMN IF EXISTS allowed_cidrs,
+    DROP COLUMN IF EXISTS denied_cidrs;
+
+COMMIT;
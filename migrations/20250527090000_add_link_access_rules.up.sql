@@ -0,0 +1,12 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN allowed_cidrs CIDR[] NOT NULL DEFAULT '{}',
+    ADD COLUMN denied_cidrs CIDR[] NOT NULL DEFAULT '{}';
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.allowed_cidrs IS 'When not empty, only clients in these ranges may follow the link';
+COMMENT ON COLUMN shortened_urls.denied_cidrs IS 'Clients in these ranges are refused, even if also allowed';
+
+COMMIT;
@@ -0,0 +1,8 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    DROP COLUMN IF EXISTS single_use,
+    DROP COLUMN IF EXISTS consumed_at;
+
+COMMIT;
@@ -0,0 +1,12 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN single_use BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN consumed_at TIMESTAMP WITH TIME ZONE;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.single_use IS 'The link can be followed only once';
+COMMENT ON COLUMN shortened_urls.consumed_at IS 'When a single-use link was followed, NULL while still unused';
+
+COMMIT;
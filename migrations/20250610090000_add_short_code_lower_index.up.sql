@@ -0,0 +1,7 @@
+-- Add up migration script here
+BEGIN;
+
+-- Serves case-insensitive code lookups
+CREATE INDEX IF NOT EXISTS idx_shortened_urls_short_code_lower ON shortened_urls (LOWER(short_code));
+
+COMMIT;
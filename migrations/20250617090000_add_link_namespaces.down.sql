@@ -0,0 +1,11 @@
+-- Add down migration script here
+BEGIN;
+
+-- Namespaced links would collide once the namespace is gone
+DELETE FROM shortened_urls WHERE namespace IS NOT NULL;
+
+DROP INDEX IF EXISTS idx_shortened_urls_namespace_code;
+ALTER TABLE shortened_urls ADD CONSTRAINT shortened_urls_short_code_key UNIQUE (short_code);
+ALTER TABLE shortened_urls DROP COLUMN IF EXISTS namespace;
+
+COMMIT;
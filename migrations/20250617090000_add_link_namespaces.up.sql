@@ -0,0 +1,15 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN namespace VARCHAR(50) CHECK (namespace ~ '^[a-zA-Z0-9_-]+$');
+
+-- Codes only need to be unique within their namespace
+ALTER TABLE shortened_urls DROP CONSTRAINT IF EXISTS shortened_urls_short_code_key;
+CREATE UNIQUE INDEX idx_shortened_urls_namespace_code
+    ON shortened_urls (COALESCE(namespace, ''), short_code);
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.namespace IS 'Optional first path segment, the link is served at /{namespace}/{short_code}';
+
+COMMIT;
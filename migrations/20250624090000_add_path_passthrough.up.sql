@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN forward_path BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.forward_path IS 'Extra path and query after the code are appended to the original URL';
+
+COMMIT;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN forward_query BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.forward_query IS 'Query parameters of the short link request are merged into the original URL';
+
+COMMIT;
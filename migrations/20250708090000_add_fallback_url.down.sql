@@ -0,0 +1,7 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    DROP COLUMN IF EXISTS fallback_url;
+
+COMMIT;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN fallback_url TEXT;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.fallback_url IS 'Where visitors are sent once the link is expired or deactivated, NULL for the default';
+
+COMMIT;
@@ -0,0 +1,15 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN max_idle_days INTEGER CHECK (max_idle_days > 0);
+
+-- Finds links due for deactivation without scanning the whole table
+CREATE INDEX IF NOT EXISTS idx_shortened_urls_max_idle_days
+    ON shortened_urls(max_idle_days)
+    WHERE max_idle_days IS NOT NULL AND is_active;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.max_idle_days IS 'The link is deactivated after this many days without access, NULL to keep it regardless';
+
+COMMIT;
@@ -0,0 +1,20 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE url_revisions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    url_id UUID NOT NULL REFERENCES shortened_urls(id) ON DELETE CASCADE,
+    revision INTEGER NOT NULL,
+    original_url TEXT NOT NULL,
+    changed_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    changed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (url_id, revision)
+);
+
+-- Add table and column descriptions
+COMMENT ON TABLE url_revisions IS 'Previous destinations of shortened URLs, one row per change';
+COMMENT ON COLUMN url_revisions.revision IS 'Sequence number of the change, starting at 1 for each URL';
+COMMENT ON COLUMN url_revisions.original_url IS 'The destination before the change';
+COMMENT ON COLUMN url_revisions.changed_by IS 'The user who made the change, NULL if anonymous or since deleted';
+
+COMMIT;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.version IS 'Incremented on every update, so concurrent edits can detect each other';
+
+COMMIT;
@@ -0,0 +1,9 @@
+-- Add up migration script here
+BEGIN;
+
+-- Serves the most-accessed active links used to warm the link cache at startup
+CREATE INDEX IF NOT EXISTS idx_shortened_urls_access_count_active
+    ON shortened_urls(access_count DESC)
+    WHERE is_active;
+
+COMMIT;
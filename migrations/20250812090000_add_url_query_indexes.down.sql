@@ -0,0 +1,12 @@
+-- Add down migration script here
+BEGIN;
+
+DROP INDEX IF EXISTS idx_shortened_urls_last_accessed;
+DROP INDEX IF EXISTS idx_shortened_urls_custom_created_at;
+DROP INDEX IF EXISTS idx_shortened_urls_inactive_created_at;
+DROP INDEX IF EXISTS idx_shortened_urls_namespace_created_at;
+DROP INDEX IF EXISTS idx_shortened_urls_original_url_trgm;
+
+-- pg_trgm is left installed, other objects may depend on it
+
+COMMIT;
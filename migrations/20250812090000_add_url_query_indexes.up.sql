@@ -0,0 +1,29 @@
+-- Add up migration script here
+BEGIN;
+
+-- Substring search on original_url (`original_url LIKE '%term%'`) needs trigrams
+CREATE EXTENSION IF NOT EXISTS pg_trgm;
+CREATE INDEX IF NOT EXISTS idx_shortened_urls_original_url_trgm
+    ON shortened_urls USING GIN (original_url gin_trgm_ops);
+
+-- Listing one namespace, newest first
+CREATE INDEX IF NOT EXISTS idx_shortened_urls_namespace_created_at
+    ON shortened_urls(namespace, created_at)
+    WHERE namespace IS NOT NULL;
+
+-- Deactivated links are a small share of all links. Listing active ones is served by
+-- idx_shortened_urls_created_at, as most rows match
+CREATE INDEX IF NOT EXISTS idx_shortened_urls_inactive_created_at
+    ON shortened_urls(created_at)
+    WHERE NOT is_active;
+
+-- Custom aliases are a small share of all links
+CREATE INDEX IF NOT EXISTS idx_shortened_urls_custom_created_at
+    ON shortened_urls(created_at)
+    WHERE is_custom_code;
+
+-- Sorting by recent use; not partial, as never-accessed links sort first when descending
+CREATE INDEX IF NOT EXISTS idx_shortened_urls_last_accessed
+    ON shortened_urls(last_accessed);
+
+COMMIT;
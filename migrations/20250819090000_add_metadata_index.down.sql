@@ -0,0 +1,6 @@
+-- Add down migration script here
+BEGIN;
+
+DROP INDEX IF EXISTS idx_shortened_urls_metadata;
+
+COMMIT;
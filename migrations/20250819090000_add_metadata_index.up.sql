@@ -0,0 +1,9 @@
+-- Add up migration script here
+BEGIN;
+
+-- Metadata search: `metadata ? key` narrows text searches on a key, `metadata @> ...`
+-- matches tags. The default jsonb_ops class supports both operators
+CREATE INDEX IF NOT EXISTS idx_shortened_urls_metadata
+    ON shortened_urls USING GIN (metadata);
+
+COMMIT;
@@ -0,0 +1,6 @@
+-- Add down migration script here
+BEGIN;
+
+DROP INDEX IF EXISTS idx_url_visit_daily_day;
+
+COMMIT;
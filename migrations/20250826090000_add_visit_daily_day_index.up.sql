@@ -0,0 +1,7 @@
+-- Add up migration script here
+BEGIN;
+
+-- The leaderboard sums recent days across all links; the primary key leads with url_id
+CREATE INDEX IF NOT EXISTS idx_url_visit_daily_day ON url_visit_daily(day);
+
+COMMIT;
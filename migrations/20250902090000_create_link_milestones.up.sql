@@ -0,0 +1,19 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE link_milestones (
+    url_id UUID NOT NULL REFERENCES shortened_urls(id) ON DELETE CASCADE,
+    milestone BIGINT NOT NULL,
+    reached_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    notified_at TIMESTAMP WITH TIME ZONE,
+    PRIMARY KEY (url_id, milestone)
+);
+
+-- Create indices for performance optimization
+CREATE INDEX idx_link_milestones_unnotified ON link_milestones(reached_at) WHERE notified_at IS NULL;
+
+-- Add table and column descriptions
+COMMENT ON TABLE link_milestones IS 'Click counts reached by links, one row per milestone so each is reported once';
+COMMENT ON COLUMN link_milestones.notified_at IS 'When the link owner was told, NULL while pending';
+
+COMMIT;
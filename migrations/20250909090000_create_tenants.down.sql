@@ -0,0 +1,15 @@
+-- Add down migration script here
+BEGIN;
+
+-- Links of other tenants would collide with the default tenant's once the tenant is gone
+DELETE FROM shortened_urls WHERE tenant_id IS NOT NULL;
+
+DROP INDEX IF EXISTS idx_shortened_urls_tenant_created_at;
+DROP INDEX IF EXISTS idx_shortened_urls_tenant_namespace_code;
+ALTER TABLE shortened_urls DROP COLUMN IF EXISTS tenant_id;
+CREATE UNIQUE INDEX idx_shortened_urls_namespace_code
+    ON shortened_urls (COALESCE(namespace, ''), short_code);
+
+DROP TABLE IF EXISTS tenants;
+
+COMMIT;
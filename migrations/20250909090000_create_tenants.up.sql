@@ -0,0 +1,31 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE tenants (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    slug VARCHAR(50) NOT NULL UNIQUE CHECK (slug ~ '^[a-z0-9_-]+$'),
+    name VARCHAR(255) NOT NULL,
+    domain VARCHAR(255) UNIQUE,
+    base_url TEXT,
+    fallback_url TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE shortened_urls
+    ADD COLUMN tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE;
+
+-- Each tenant has its own codes
+DROP INDEX IF EXISTS idx_shortened_urls_namespace_code;
+CREATE UNIQUE INDEX idx_shortened_urls_tenant_namespace_code
+    ON shortened_urls (COALESCE(tenant_id, '00000000-0000-0000-0000-000000000000'), COALESCE(namespace, ''), short_code);
+CREATE INDEX idx_shortened_urls_tenant_created_at
+    ON shortened_urls (tenant_id, created_at) WHERE tenant_id IS NOT NULL;
+
+-- Add table and column descriptions
+COMMENT ON TABLE tenants IS 'Brands served by this instance, each seeing only its own links';
+COMMENT ON COLUMN tenants.domain IS 'Requests for this host belong to the tenant, NULL when only selected by header';
+COMMENT ON COLUMN tenants.base_url IS 'Public base URL of the tenant short links, NULL for PUBLIC_BASE_URL';
+COMMENT ON COLUMN tenants.fallback_url IS 'Where visitors of expired links go, NULL for FALLBACK_URL';
+COMMENT ON COLUMN shortened_urls.tenant_id IS 'The tenant owning the link, NULL for the default tenant';
+
+COMMIT;
@@ -0,0 +1,24 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE branding (
+    tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE,
+    logo_url TEXT,
+    primary_color VARCHAR(7),
+    background_color VARCHAR(7),
+    footer_text VARCHAR(500),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- One row per tenant, the default tenant included
+CREATE UNIQUE INDEX idx_branding_tenant
+    ON branding (COALESCE(tenant_id, '00000000-0000-0000-0000-000000000000'));
+
+-- Add table and column descriptions
+COMMENT ON TABLE branding IS 'Look of the pages shown to visitors, per tenant';
+COMMENT ON COLUMN branding.tenant_id IS 'The tenant branded, NULL for the default tenant';
+COMMENT ON COLUMN branding.primary_color IS 'Hex color of headings and links, like #1a73e8';
+COMMENT ON COLUMN branding.background_color IS 'Hex color of the page background';
+COMMENT ON COLUMN branding.footer_text IS 'Plain text shown at the bottom of every page';
+
+COMMIT;
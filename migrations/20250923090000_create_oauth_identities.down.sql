@@ -0,0 +1,7 @@
+-- Add down migration script here
+BEGIN;
+
+DROP TABLE IF EXISTS oauth_states;
+DROP TABLE IF EXISTS oauth_identities;
+
+COMMIT;
@@ -0,0 +1,31 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE oauth_identities (
+    provider VARCHAR(20) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email VARCHAR(255),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (provider, subject)
+);
+
+CREATE INDEX idx_oauth_identities_user_id ON oauth_identities(user_id);
+
+CREATE TABLE oauth_states (
+    state VARCHAR(64) PRIMARY KEY,
+    provider VARCHAR(20) NOT NULL,
+    code_verifier VARCHAR(128) NOT NULL,
+    link_user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Add table and column descriptions
+COMMENT ON TABLE oauth_identities IS 'Accounts at login providers, linked to the users they sign in as';
+COMMENT ON COLUMN oauth_identities.subject IS 'The account ID at the provider, stable across email changes';
+COMMENT ON COLUMN oauth_identities.email IS 'Email reported by the provider when the identity was linked';
+COMMENT ON TABLE oauth_states IS 'Logins started at a provider and not completed yet';
+COMMENT ON COLUMN oauth_states.code_verifier IS 'PKCE secret proving the callback completes this login';
+COMMENT ON COLUMN oauth_states.link_user_id IS 'The signed-in user to link the identity to, NULL to sign in';
+
+COMMIT;
@@ -0,0 +1,24 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE service_tokens (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL,
+    user_id UUID REFERENCES users(id) ON DELETE CASCADE,
+    token_hash CHAR(64) NOT NULL UNIQUE,
+    scopes TEXT[] NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMP WITH TIME ZONE,
+    expires_at TIMESTAMP WITH TIME ZONE,
+    revoked_at TIMESTAMP WITH TIME ZONE
+);
+
+CREATE INDEX idx_service_tokens_user_id ON service_tokens(user_id);
+
+-- Add table and column descriptions
+COMMENT ON TABLE service_tokens IS 'Machine credentials for integrations, limited to their scopes';
+COMMENT ON COLUMN service_tokens.user_id IS 'The user links created with the token belong to, NULL for none';
+COMMENT ON COLUMN service_tokens.token_hash IS 'SHA-256 of the secret, which is only shown on creation';
+COMMENT ON COLUMN service_tokens.scopes IS 'Granted scopes like urls:write, or a whole family like admin:*';
+
+COMMIT;
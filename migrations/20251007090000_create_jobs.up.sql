@@ -0,0 +1,22 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE jobs (
+    name VARCHAR(100) PRIMARY KEY,
+    schedule TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'idle' CHECK (status IN ('idle', 'running', 'succeeded', 'failed', 'interrupted')),
+    last_started_at TIMESTAMP WITH TIME ZONE,
+    last_finished_at TIMESTAMP WITH TIME ZONE,
+    last_message TEXT,
+    next_run_at TIMESTAMP WITH TIME ZONE,
+    run_count BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Add table and column descriptions
+COMMENT ON TABLE jobs IS 'Scheduled background jobs and the outcome of their last run';
+COMMENT ON COLUMN jobs.schedule IS 'Cron expression or @every interval the job runs on';
+COMMENT ON COLUMN jobs.status IS 'interrupted when the instance stopped during a run';
+COMMENT ON COLUMN jobs.last_message IS 'Summary of the last run, or its error';
+
+COMMIT;
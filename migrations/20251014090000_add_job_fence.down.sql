@@ -0,0 +1,7 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE jobs
+    DROP COLUMN IF EXISTS fence;
+
+COMMIT;
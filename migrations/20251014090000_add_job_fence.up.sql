@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE jobs
+    ADD COLUMN fence BIGINT NOT NULL DEFAULT 0;
+
+-- Add column descriptions
+COMMENT ON COLUMN jobs.fence IS 'Incremented whenever an instance takes the job lock; outcomes reported with an older value are ignored';
+
+COMMIT;
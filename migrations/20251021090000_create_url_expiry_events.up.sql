@@ -0,0 +1,14 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE url_expiry_events (
+    url_id UUID NOT NULL REFERENCES shortened_urls(id) ON DELETE CASCADE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    published_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (url_id, expires_at)
+);
+
+-- Add table and column descriptions
+COMMENT ON TABLE url_expiry_events IS 'Expiries already published as events, keyed by expiry so extending a link re-arms the event';
+
+COMMIT;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN status TEXT NOT NULL DEFAULT 'active'
+        CHECK (status IN ('active', 'flagged', 'blocked', 'pending_review'));
+
+-- Links with an open flag become flagged, as the detector does from now on
+UPDATE shortened_urls SET status = 'flagged'
+WHERE id IN (SELECT url_id FROM link_flags WHERE resolved_at IS NULL);
+
+-- Create indices for performance optimization
+CREATE INDEX idx_shortened_urls_status ON shortened_urls(status) WHERE status <> 'active';
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.status IS 'Moderation state deciding how redirects are served: active, flagged, blocked or pending_review';
+
+COMMIT;
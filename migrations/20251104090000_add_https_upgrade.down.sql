@@ -0,0 +1,9 @@
+-- Add down migration script here
+BEGIN;
+
+DROP INDEX IF EXISTS idx_shortened_urls_http_destinations;
+
+ALTER TABLE shortened_urls
+    DROP COLUMN IF EXISTS upgrade_https;
+
+COMMIT;
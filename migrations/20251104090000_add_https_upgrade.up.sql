@@ -0,0 +1,13 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN upgrade_https BOOLEAN;
+
+-- Create indices for performance optimization
+CREATE INDEX idx_shortened_urls_http_destinations ON shortened_urls(id) WHERE original_url LIKE 'http://%';
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.upgrade_https IS 'Rewrite an http:// destination to https:// when its host supports it, NULL to follow HTTPS_UPGRADE_ENABLED';
+
+COMMIT;
@@ -0,0 +1,12 @@
+-- Add down migration script here
+BEGIN;
+
+DROP INDEX IF EXISTS idx_shortened_urls_broken;
+DROP INDEX IF EXISTS idx_shortened_urls_last_checked_at;
+
+ALTER TABLE shortened_urls
+    DROP COLUMN IF EXISTS last_checked_at,
+    DROP COLUMN IF EXISTS check_status,
+    DROP COLUMN IF EXISTS is_broken;
+
+COMMIT;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN last_checked_at TIMESTAMP WITH TIME ZONE,
+    ADD COLUMN check_status INTEGER,
+    ADD COLUMN is_broken BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Create indices for performance optimization
+CREATE INDEX idx_shortened_urls_last_checked_at ON shortened_urls(last_checked_at NULLS FIRST) WHERE is_active;
+CREATE INDEX idx_shortened_urls_broken ON shortened_urls(created_at) WHERE is_broken;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.last_checked_at IS 'When the link rot checker last requested the destination, NULL if never';
+COMMENT ON COLUMN shortened_urls.check_status IS 'HTTP status the destination answered the last check with, NULL if it did not answer';
+COMMENT ON COLUMN shortened_urls.is_broken IS 'The destination was unreachable, missing or failing at the last check';
+
+COMMIT;
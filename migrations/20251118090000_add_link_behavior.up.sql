@@ -0,0 +1,11 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN behavior TEXT NOT NULL DEFAULT 'redirect'
+        CHECK (behavior IN ('redirect', 'proxy', 'preview'));
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.behavior IS 'How visitors reach the destination: redirect, proxy (the file is streamed through) or preview (a page showing the file)';
+
+COMMIT;
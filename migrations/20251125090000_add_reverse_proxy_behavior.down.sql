@@ -0,0 +1,13 @@
+-- Add down migration script here
+BEGIN;
+
+UPDATE shortened_urls SET behavior = 'redirect' WHERE behavior = 'reverse_proxy';
+
+ALTER TABLE shortened_urls
+    DROP CONSTRAINT shortened_urls_behavior_check,
+    ADD CONSTRAINT shortened_urls_behavior_check
+        CHECK (behavior IN ('redirect', 'proxy', 'preview'));
+
+COMMENT ON COLUMN shortened_urls.behavior IS 'How visitors reach the destination: redirect, proxy (the file is streamed through) or preview (a page showing the file)';
+
+COMMIT;
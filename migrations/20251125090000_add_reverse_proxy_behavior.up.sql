@@ -0,0 +1,12 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    DROP CONSTRAINT shortened_urls_behavior_check,
+    ADD CONSTRAINT shortened_urls_behavior_check
+        CHECK (behavior IN ('redirect', 'proxy', 'preview', 'reverse_proxy'));
+
+-- Update column descriptions
+COMMENT ON COLUMN shortened_urls.behavior IS 'How visitors reach the destination: redirect, proxy (the file is streamed through), preview (a page showing the file) or reverse_proxy (any response of an allowed host is streamed through)';
+
+COMMIT;
@@ -0,0 +1,9 @@
+-- Add down migration script here
+BEGIN;
+
+DROP TABLE IF EXISTS app_links;
+
+ALTER TABLE shortened_urls
+    DROP COLUMN IF EXISTS app_url;
+
+COMMIT;
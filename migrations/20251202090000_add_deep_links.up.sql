@@ -0,0 +1,27 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN app_url TEXT;
+
+CREATE TABLE app_links (
+    tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE,
+    ios_app_ids TEXT[] NOT NULL DEFAULT '{}',
+    android_package VARCHAR(255),
+    android_cert_fingerprints TEXT[] NOT NULL DEFAULT '{}',
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- One row per tenant, the default tenant included
+CREATE UNIQUE INDEX idx_app_links_tenant
+    ON app_links (COALESCE(tenant_id, '00000000-0000-0000-0000-000000000000'));
+
+-- Add table and column descriptions
+COMMENT ON COLUMN shortened_urls.app_url IS 'URI opening the destination in a mobile app, like myapp://item/42, tried before the web destination';
+COMMENT ON TABLE app_links IS 'Mobile apps allowed to open short links of a tenant domain as universal links / app links';
+COMMENT ON COLUMN app_links.tenant_id IS 'The tenant whose domain the apps handle, NULL for the default tenant';
+COMMENT ON COLUMN app_links.ios_app_ids IS 'iOS app identifiers, team ID and bundle ID like ABCDE12345.com.example.app';
+COMMENT ON COLUMN app_links.android_package IS 'Android application ID, like com.example.app';
+COMMENT ON COLUMN app_links.android_cert_fingerprints IS 'SHA-256 fingerprints of the Android signing certificates, colon-separated hex';
+
+COMMIT;
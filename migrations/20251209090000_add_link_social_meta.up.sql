@@ -0,0 +1,14 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN og_title TEXT,
+    ADD COLUMN og_description TEXT,
+    ADD COLUMN og_image TEXT;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.og_title IS 'Title shown when the link is shared on social platforms';
+COMMENT ON COLUMN shortened_urls.og_description IS 'Description shown when the link is shared on social platforms';
+COMMENT ON COLUMN shortened_urls.og_image IS 'URL of the image shown when the link is shared on social platforms';
+
+COMMIT;
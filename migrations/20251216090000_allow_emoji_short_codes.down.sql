@@ -0,0 +1,10 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls DROP CONSTRAINT IF EXISTS shortened_urls_short_code_check;
+ALTER TABLE shortened_urls ADD CONSTRAINT shortened_urls_short_code_check
+    CHECK (short_code ~ '^[a-zA-Z0-9_-]+$');
+
+COMMENT ON COLUMN shortened_urls.short_code IS 'Unique shortcode used in the shortened URL (max 100 chars, alphanumeric, hyphens and underscores)';
+
+COMMIT;
@@ -0,0 +1,12 @@
+-- Add up migration script here
+BEGIN;
+
+-- Custom aliases may be emoji, which the application validates; the column only keeps
+-- out what would break a URL path
+ALTER TABLE shortened_urls DROP CONSTRAINT IF EXISTS shortened_urls_short_code_check;
+ALTER TABLE shortened_urls ADD CONSTRAINT shortened_urls_short_code_check
+    CHECK (short_code ~ '^[^\x01-\x20\x7f/?#%\\]+$');
+
+COMMENT ON COLUMN shortened_urls.short_code IS 'Unique shortcode used in the shortened URL (max 100 chars, alphanumeric, hyphens, underscores or emoji, stored NFC-normalized)';
+
+COMMIT;
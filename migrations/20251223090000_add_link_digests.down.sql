@@ -0,0 +1,10 @@
+-- Add down migration script here
+BEGIN;
+
+DROP TABLE IF EXISTS digest_deliveries;
+
+ALTER TABLE notification_preferences
+    DROP COLUMN IF EXISTS digest_unsubscribed,
+    DROP COLUMN IF EXISTS digest_frequency;
+
+COMMIT;
@@ -0,0 +1,22 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE notification_preferences
+    ADD COLUMN digest_frequency TEXT NOT NULL DEFAULT 'weekly'
+        CHECK (digest_frequency IN ('weekly', 'monthly')),
+    ADD COLUMN digest_unsubscribed BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE digest_deliveries (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    frequency TEXT NOT NULL,
+    period_start DATE NOT NULL,
+    sent_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, frequency, period_start)
+);
+
+-- Add table and column descriptions
+COMMENT ON COLUMN notification_preferences.digest_frequency IS 'How often the user gets a digest of their links'' clicks: weekly or monthly';
+COMMENT ON COLUMN notification_preferences.digest_unsubscribed IS 'Whether the user opted out of digests, by email and on their channels';
+COMMENT ON TABLE digest_deliveries IS 'Digests already sent, one per user and period';
+
+COMMIT;
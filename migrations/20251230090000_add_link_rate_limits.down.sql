@@ -0,0 +1,6 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls DROP COLUMN IF EXISTS max_redirects_per_minute;
+
+COMMIT;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls
+    ADD COLUMN max_redirects_per_minute INTEGER CHECK (max_redirects_per_minute > 0);
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.max_redirects_per_minute IS 'Redirects allowed per minute before visitors are answered 429, for destinations that can''t take much traffic (NULL for no limit)';
+
+COMMIT;
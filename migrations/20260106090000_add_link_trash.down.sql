@@ -0,0 +1,10 @@
+-- Add down migration script here
+BEGIN;
+
+-- Links still in the trash were deleted as far as their owners know
+DELETE FROM shortened_urls WHERE deleted_at IS NOT NULL;
+
+DROP INDEX IF EXISTS idx_shortened_urls_deleted_at;
+ALTER TABLE shortened_urls DROP COLUMN IF EXISTS deleted_at;
+
+COMMIT;
@@ -0,0 +1,13 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls ADD COLUMN deleted_at TIMESTAMPTZ;
+
+-- The trash listing and the purge job only look at deleted links
+CREATE INDEX idx_shortened_urls_deleted_at ON shortened_urls (deleted_at)
+    WHERE deleted_at IS NOT NULL;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.deleted_at IS 'When the link was moved to the trash, from which it can be restored until the purge job deletes it (NULL for live links)';
+
+COMMIT;
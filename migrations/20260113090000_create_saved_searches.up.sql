@@ -0,0 +1,22 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE saved_searches (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID REFERENCES tenants(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    params JSONB NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Presets are picked by name, which each tenant uses once
+CREATE UNIQUE INDEX idx_saved_searches_tenant_name
+    ON saved_searches (COALESCE(tenant_id, '00000000-0000-0000-0000-000000000000'), name);
+
+-- Add table and column descriptions
+COMMENT ON TABLE saved_searches IS 'Named URL filters, applied to listings with ?preset=name';
+COMMENT ON COLUMN saved_searches.tenant_id IS 'The tenant owning the search, NULL for the default tenant';
+COMMENT ON COLUMN saved_searches.params IS 'Query parameters of the URL search endpoint, as a JSON object';
+
+COMMIT;
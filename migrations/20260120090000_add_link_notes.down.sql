@@ -0,0 +1,7 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE url_revisions DROP COLUMN IF EXISTS change_comment;
+ALTER TABLE shortened_urls DROP COLUMN IF EXISTS notes;
+
+COMMIT;
@@ -0,0 +1,11 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls ADD COLUMN notes TEXT;
+ALTER TABLE url_revisions ADD COLUMN change_comment TEXT;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.notes IS 'Free-text notes about the link for the team managing it, never shown to visitors';
+COMMENT ON COLUMN url_revisions.change_comment IS 'Why the destination was changed, as given with the update (NULL when not given)';
+
+COMMIT;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE link_favorites (
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    url_id UUID NOT NULL REFERENCES shortened_urls(id) ON DELETE CASCADE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, url_id)
+);
+
+-- Create indices for performance optimization
+CREATE INDEX idx_link_favorites_url_id ON link_favorites(url_id);
+
+-- Add table and column descriptions
+COMMENT ON TABLE link_favorites IS 'Links users pinned to find them first in their dashboard';
+COMMENT ON COLUMN link_favorites.created_at IS 'When the user pinned the link';
+
+COMMIT;
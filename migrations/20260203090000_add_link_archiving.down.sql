@@ -0,0 +1,6 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls DROP COLUMN IF EXISTS archived_at;
+
+COMMIT;
@@ -0,0 +1,9 @@
+-- Add up migration script here
+BEGIN;
+
+ALTER TABLE shortened_urls ADD COLUMN archived_at TIMESTAMP WITH TIME ZONE;
+
+-- Add column descriptions
+COMMENT ON COLUMN shortened_urls.archived_at IS 'When the link was archived, by hand or for going unused, hiding it from listings while it keeps redirecting (NULL when not archived)';
+
+COMMIT;
@@ -0,0 +1,7 @@
+-- Add down migration script here
+BEGIN;
+
+DROP INDEX IF EXISTS idx_shortened_urls_unarchived_last_used;
+DROP TABLE IF EXISTS online_migrations;
+
+COMMIT;
@@ -0,0 +1,12 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE online_migrations (
+    name TEXT PRIMARY KEY,
+    completed_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Add table and column descriptions
+COMMENT ON TABLE online_migrations IS 'Schema changes applied outside a transaction after the regular migrations, like concurrent index builds and batched backfills (see src/db/online.rs)';
+
+COMMIT;
@@ -0,0 +1,19 @@
+-- Add down migration script here
+BEGIN;
+
+DROP INDEX IF EXISTS idx_shortened_urls_short_code_bucket;
+DROP FUNCTION IF EXISTS short_code_bucket(TEXT);
+
+ALTER TABLE shortened_urls ALTER COLUMN id SET DEFAULT uuid_generate_v4();
+ALTER TABLE users ALTER COLUMN id SET DEFAULT uuid_generate_v4();
+ALTER TABLE api_keys ALTER COLUMN id SET DEFAULT uuid_generate_v4();
+ALTER TABLE account_exports ALTER COLUMN id SET DEFAULT uuid_generate_v4();
+ALTER TABLE notification_channels ALTER COLUMN id SET DEFAULT uuid_generate_v4();
+ALTER TABLE url_revisions ALTER COLUMN id SET DEFAULT gen_random_uuid();
+ALTER TABLE tenants ALTER COLUMN id SET DEFAULT gen_random_uuid();
+ALTER TABLE service_tokens ALTER COLUMN id SET DEFAULT gen_random_uuid();
+ALTER TABLE saved_searches ALTER COLUMN id SET DEFAULT gen_random_uuid();
+
+DROP FUNCTION IF EXISTS uuid_generate_v7();
+
+COMMIT;
@@ -0,0 +1,45 @@
+-- Add up migration script here
+BEGIN;
+
+-- UUIDv7 (RFC 9562): 48 bits of Unix time in milliseconds ahead of random bits, so new ids
+-- land at the end of primary key indexes instead of all over them. Existing rows keep their
+-- random ids, both kinds are valid UUIDs and stay unique
+CREATE OR REPLACE FUNCTION uuid_generate_v7() RETURNS UUID AS $$
+    SELECT encode(
+        set_bit(
+            set_bit(
+                overlay(
+                    uuid_send(gen_random_uuid())
+                    PLACING substring(int8send(floor(extract(epoch FROM clock_timestamp()) * 1000)::BIGINT) FROM 3)
+                    FROM 1 FOR 6
+                ),
+                52, 1
+            ),
+            53, 1
+        ),
+        'hex'
+    )::UUID;
+$$ LANGUAGE sql VOLATILE;
+
+ALTER TABLE shortened_urls ALTER COLUMN id SET DEFAULT uuid_generate_v7();
+ALTER TABLE users ALTER COLUMN id SET DEFAULT uuid_generate_v7();
+ALTER TABLE api_keys ALTER COLUMN id SET DEFAULT uuid_generate_v7();
+ALTER TABLE account_exports ALTER COLUMN id SET DEFAULT uuid_generate_v7();
+ALTER TABLE notification_channels ALTER COLUMN id SET DEFAULT uuid_generate_v7();
+ALTER TABLE url_revisions ALTER COLUMN id SET DEFAULT uuid_generate_v7();
+ALTER TABLE tenants ALTER COLUMN id SET DEFAULT uuid_generate_v7();
+ALTER TABLE service_tokens ALTER COLUMN id SET DEFAULT uuid_generate_v7();
+ALTER TABLE saved_searches ALTER COLUMN id SET DEFAULT uuid_generate_v7();
+
+-- Bucket of a short code for sharding, must match shard_bucket in src/repositories/shard.rs:
+-- the first two bytes of the SHA-256 of its lowercased two first characters, modulo 1024
+CREATE OR REPLACE FUNCTION short_code_bucket(code TEXT) RETURNS SMALLINT AS $$
+    SELECT ((get_byte(digest, 0) << 8 | get_byte(digest, 1)) % 1024)::SMALLINT
+    FROM (SELECT sha256(textsend(LOWER(LEFT(code, 2)))) AS digest) AS hashed;
+$$ LANGUAGE sql IMMUTABLE STRICT PARALLEL SAFE;
+
+-- Add function descriptions
+COMMENT ON FUNCTION uuid_generate_v7() IS 'Time-ordered UUID (version 7), default of primary keys';
+COMMENT ON FUNCTION short_code_bucket(TEXT) IS 'Shard bucket of a short code, by its first two characters (see src/repositories/shard.rs)';
+
+COMMIT;
@@ -0,0 +1,18 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE link_claims (
+    url_id UUID PRIMARY KEY REFERENCES shortened_urls(id) ON DELETE CASCADE,
+    token_hash TEXT NOT NULL UNIQUE,
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Create indices for performance optimization, expired tokens are removed by the link cleanup job
+CREATE INDEX idx_link_claims_expires_at ON link_claims(expires_at);
+
+-- Add table and column descriptions
+COMMENT ON TABLE link_claims IS 'One-time tokens handed to anonymous link creators, to take ownership of the link once signed in';
+COMMENT ON COLUMN link_claims.token_hash IS 'SHA-256 hex digest of the token, which is only shown in the create response';
+
+COMMIT;
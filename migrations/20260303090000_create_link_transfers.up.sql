@@ -0,0 +1,29 @@
+-- Add up migration script here
+BEGIN;
+
+CREATE TABLE link_transfers (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v7(),
+    url_id UUID NOT NULL REFERENCES shortened_urls(id) ON DELETE CASCADE,
+    from_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    to_user_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    from_tenant_id UUID,
+    to_tenant_id UUID,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'accepted', 'declined', 'cancelled')),
+    by_admin BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMP WITH TIME ZONE
+);
+
+-- Create indices for performance optimization
+CREATE UNIQUE INDEX idx_link_transfers_pending ON link_transfers(url_id) WHERE status = 'pending';
+CREATE INDEX idx_link_transfers_to_user_id ON link_transfers(to_user_id) WHERE status = 'pending';
+CREATE INDEX idx_link_transfers_url_id ON link_transfers(url_id, created_at);
+
+-- Add table and column descriptions
+COMMENT ON TABLE link_transfers IS 'Changes of owner or tenant of links, offered to the recipient or made by an admin, at most one pending per link';
+COMMENT ON COLUMN link_transfers.from_tenant_id IS 'Tenant of the link before the transfer, NULL for the default tenant; kept without a foreign key as history';
+COMMENT ON COLUMN link_transfers.to_tenant_id IS 'Tenant of the link after the transfer, NULL for the default tenant';
+COMMENT ON COLUMN link_transfers.by_admin IS 'Made with the admin token, without the recipient accepting';
+
+COMMIT;
@@ -0,0 +1,46 @@
+-- Add down migration script here
+BEGIN;
+
+-- Links get their old ids back while references still follow them
+DROP TRIGGER IF EXISTS shortened_urls_record_id_remap ON shortened_urls;
+UPDATE shortened_urls SET id = remaps.old_id
+FROM url_id_remaps remaps
+WHERE shortened_urls.id = remaps.new_id;
+DELETE FROM online_migrations WHERE name = '20260310090000_remap_v4_url_ids';
+
+ALTER TABLE url_visits DROP CONSTRAINT url_visits_url_id_fkey,
+    ADD CONSTRAINT url_visits_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON DELETE CASCADE NOT VALID;
+ALTER TABLE url_visit_daily DROP CONSTRAINT url_visit_daily_url_id_fkey,
+    ADD CONSTRAINT url_visit_daily_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON DELETE CASCADE NOT VALID;
+ALTER TABLE expiry_notifications DROP CONSTRAINT expiry_notifications_url_id_fkey,
+    ADD CONSTRAINT expiry_notifications_url_id_fkey FOREIGN KEY (url_id)
+    REFERENCES shortened_urls(id) ON DELETE CASCADE NOT VALID;
+ALTER TABLE link_flags DROP CONSTRAINT link_flags_url_id_fkey,
+    ADD CONSTRAINT link_flags_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON DELETE CASCADE NOT VALID;
+ALTER TABLE url_revisions DROP CONSTRAINT url_revisions_url_id_fkey,
+    ADD CONSTRAINT url_revisions_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON DELETE CASCADE NOT VALID;
+ALTER TABLE link_milestones DROP CONSTRAINT link_milestones_url_id_fkey,
+    ADD CONSTRAINT link_milestones_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON DELETE CASCADE NOT VALID;
+ALTER TABLE url_expiry_events DROP CONSTRAINT url_expiry_events_url_id_fkey,
+    ADD CONSTRAINT url_expiry_events_url_id_fkey FOREIGN KEY (url_id)
+    REFERENCES shortened_urls(id) ON DELETE CASCADE NOT VALID;
+ALTER TABLE link_favorites DROP CONSTRAINT link_favorites_url_id_fkey,
+    ADD CONSTRAINT link_favorites_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON DELETE CASCADE NOT VALID;
+ALTER TABLE link_claims DROP CONSTRAINT link_claims_url_id_fkey,
+    ADD CONSTRAINT link_claims_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON DELETE CASCADE NOT VALID;
+ALTER TABLE link_transfers DROP CONSTRAINT link_transfers_url_id_fkey,
+    ADD CONSTRAINT link_transfers_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON DELETE CASCADE NOT VALID;
+
+DROP FUNCTION IF EXISTS record_url_id_remap();
+DROP TABLE IF EXISTS url_id_remaps;
+DROP FUNCTION IF EXISTS uuid_generate_v7_at(TIMESTAMPTZ);
+
+COMMIT;
@@ -0,0 +1,80 @@
+-- Add up migration script here
+BEGIN;
+
+-- UUIDv7 for a given time, to give links created before UUIDv7 ids the id they would have
+-- had. Same layout as uuid_generate_v7()
+CREATE OR REPLACE FUNCTION uuid_generate_v7_at(at TIMESTAMPTZ) RETURNS UUID AS $$
+    SELECT encode(
+        set_bit(
+            set_bit(
+                overlay(
+                    uuid_send(gen_random_uuid())
+                    PLACING substring(int8send(floor(extract(epoch FROM at) * 1000)::BIGINT) FROM 3)
+                    FROM 1 FOR 6
+                ),
+                52, 1
+            ),
+            53, 1
+        ),
+        'hex'
+    )::UUID;
+$$ LANGUAGE sql VOLATILE STRICT;
+
+-- Old ids of remapped links, for clients and exports still referring to them
+CREATE TABLE url_id_remaps (
+    old_id UUID PRIMARY KEY,
+    new_id UUID NOT NULL,
+    remapped_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+CREATE INDEX idx_url_id_remaps_new_id ON url_id_remaps (new_id);
+
+CREATE OR REPLACE FUNCTION record_url_id_remap() RETURNS TRIGGER AS $$
+BEGIN
+    INSERT INTO url_id_remaps (old_id, new_id) VALUES (OLD.id, NEW.id);
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER shortened_urls_record_id_remap
+    AFTER UPDATE OF id ON shortened_urls
+    FOR EACH ROW WHEN (OLD.id IS DISTINCT FROM NEW.id)
+    EXECUTE FUNCTION record_url_id_remap();
+
+-- References follow a link whose id changes. The rows were checked by the constraints being
+-- replaced, so the new ones skip validating them again and don't scan the tables
+ALTER TABLE url_visits DROP CONSTRAINT url_visits_url_id_fkey,
+    ADD CONSTRAINT url_visits_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON UPDATE CASCADE ON DELETE CASCADE NOT VALID;
+ALTER TABLE url_visit_daily DROP CONSTRAINT url_visit_daily_url_id_fkey,
+    ADD CONSTRAINT url_visit_daily_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON UPDATE CASCADE ON DELETE CASCADE NOT VALID;
+ALTER TABLE expiry_notifications DROP CONSTRAINT expiry_notifications_url_id_fkey,
+    ADD CONSTRAINT expiry_notifications_url_id_fkey FOREIGN KEY (url_id)
+    REFERENCES shortened_urls(id) ON UPDATE CASCADE ON DELETE CASCADE NOT VALID;
+ALTER TABLE link_flags DROP CONSTRAINT link_flags_url_id_fkey,
+    ADD CONSTRAINT link_flags_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON UPDATE CASCADE ON DELETE CASCADE NOT VALID;
+ALTER TABLE url_revisions DROP CONSTRAINT url_revisions_url_id_fkey,
+    ADD CONSTRAINT url_revisions_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON UPDATE CASCADE ON DELETE CASCADE NOT VALID;
+ALTER TABLE link_milestones DROP CONSTRAINT link_milestones_url_id_fkey,
+    ADD CONSTRAINT link_milestones_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON UPDATE CASCADE ON DELETE CASCADE NOT VALID;
+ALTER TABLE url_expiry_events DROP CONSTRAINT url_expiry_events_url_id_fkey,
+    ADD CONSTRAINT url_expiry_events_url_id_fkey FOREIGN KEY (url_id)
+    REFERENCES shortened_urls(id) ON UPDATE CASCADE ON DELETE CASCADE NOT VALID;
+ALTER TABLE link_favorites DROP CONSTRAINT link_favorites_url_id_fkey,
+    ADD CONSTRAINT link_favorites_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON UPDATE CASCADE ON DELETE CASCADE NOT VALID;
+ALTER TABLE link_claims DROP CONSTRAINT link_claims_url_id_fkey,
+    ADD CONSTRAINT link_claims_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON UPDATE CASCADE ON DELETE CASCADE NOT VALID;
+ALTER TABLE link_transfers DROP CONSTRAINT link_transfers_url_id_fkey,
+    ADD CONSTRAINT link_transfers_url_id_fkey FOREIGN KEY (url_id) REFERENCES shortened_urls(id)
+    ON UPDATE CASCADE ON DELETE CASCADE NOT VALID;
+
+-- Add function descriptions
+COMMENT ON FUNCTION uuid_generate_v7_at(TIMESTAMPTZ) IS 'Time-ordered UUID (version 7) for a given time';
+COMMENT ON TABLE url_id_remaps IS 'UUIDv4 ids of links given a UUIDv7 id by the 20260310090000_remap_v4_url_ids online migration';
+
+COMMIT;
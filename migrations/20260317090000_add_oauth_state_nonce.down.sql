@@ -0,0 +1,6 @@
+-- Add down migration script here
+BEGIN;
+
+ALTER TABLE oauth_states DROP COLUMN IF EXISTS nonce_hash;
+
+COMMIT;
@@ -0,0 +1,10 @@
+-- Add up migration script here
+BEGIN;
+
+-- Logins in flight have no browser to tie them to, they're started again
+DELETE FROM oauth_states;
+ALTER TABLE oauth_states ADD COLUMN nonce_hash VARCHAR(64) NOT NULL;
+
+COMMENT ON COLUMN oauth_states.nonce_hash IS 'SHA-256 of the cookie set in the browser that started the login';
+
+COMMIT;
@@ -1,4 +1,7 @@
-use std::time::Instant;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use actix_cors::Cors;
 use actix_web::{
@@ -7,34 +10,78 @@ use actix_web::{
     web, App, HttpServer,
 };
 
-use env_logger::Env;
-use log::{debug, error, info};
+use log::{debug, error, info, warn};
 
 use crate::{
-    config::{Config, Environment},
+    cache::{AccessBacklog, LinkCache, LinkRateLimiter, RecentCreates},
+    config::{Config, DatabaseConfig, Environment},
     db::{Database, DatabaseError},
-    middleware::RequestLogger,
-    routes,
-    services,
+    errors::extract,
+    events::{self, EventBus},
+    handlers::{
+        BackupServiceType, ScannerGuardServiceType, ShortenedUrlServiceType, TenantServiceType,
+    },
+    i18n::Catalog,
+    jobs,
+    live_config::LiveConfig,
+    middleware::{
+        rate_limit::{X_RATELIMIT_LIMIT, X_RATELIMIT_REMAINING, X_RATELIMIT_RESET},
+        ApiVersioning, ContentNegotiation, CsrfProtection, Localization, Maintenance,
+        RateLimitHeaders, RequestLogger, RequestSigning, RequestTimeout, ScannerGuard,
+        ServiceTokenAuth,
+    },
+    repositories::{
+        AnalyticsRepository, BlockedIpRepository, CircuitBreakerRepository, ShortenedUrlRepository,
+        TenantRepository,
+    },
+    routes, seed,
+    services::{
+        self, BackupService, ScannerGuardService, ShortenedUrlService, ShortenedUrlServiceTrait,
+        TenantService,
+    },
+    storage::{ObjectStore, S3ObjectStore},
     types::{Result as AppResult, AppState},
-    AppError,
+    utils::{
+        circuit_breaker::CircuitBreaker, file_proxy::FileProxy, http_client::HttpClient,
+        https_upgrade::HttpsUpgrader, logger::ReloadableLogger, maintenance::MaintenanceMode,
+        rate_limiter::RateLimiter, redirect_loop::LoopGuard, reverse_proxy::ReverseProxy,
+        signing::RequestSigner,
+    },
+    warehouse, AppError,
 };
 
-// Setup logging with custom format and configuration
-fn setup_logging(config: &Config) -> Result<(), AppError> {
-    // Configure log level based on environment and config
-    let log_level = match config.app.environment {
-        Environment::Development => config.app.log_level.clone(),
-        Environment::Testing => "debug,actix_web=info".to_string(),
-        Environment::Production => "info,actix_web=warn".to_string(),
+// Setup logging with custom format and configuration, kept reloadable with the config file
+fn setup_logging(config: &Config) -> Result<&'static ReloadableLogger, AppError> {
+    ReloadableLogger::install(config)
+        .map_err(|e| AppError::Logger(format!("Failed to initialize logger: {}", e)))
+}
+
+/// Apply pending migrations and return, for deploys that migrate as a step of their own
+/// before starting instances with `DATABASE_USE_MIGRATIONS=false`
+pub async fn migrate() -> AppResult<()> {
+    let config = Config::load()?;
+    setup_logging(&config)?;
+
+    let failed = |e: DatabaseError| {
+        AppError::Server(std::io::Error::other(format!("Migration failed: {}", e)))
     };
+    let db = Database::connect(&DatabaseConfig {
+        use_migrations: false,
+        ..config.db.clone()
+    })
+    .await
+    .map_err(failed)?;
 
-    let env = Env::default()
-        .filter_or("RUST_LOG", log_level)
-        .write_style_or("RUST_LOG_STYLE", "always");
+    let pending = db.pending_migrations().await.map_err(failed)?;
+    match pending.last() {
+        None => info!("No migrations to apply"),
+        Some(latest) => info!("Applying {} migrations, up to {}", pending.len(), latest),
+    }
+    // Online migrations may still be pending, or unfinished after an interrupted run
+    db.migrate().await.map_err(failed)?;
 
-    env_logger::try_init_from_env(env)
-        .map_err(|e| AppError::Logger(format!("Failed to initialize logger: {}", e)))
+    db.shutdown().await;
+    Ok(())
 }
 
 pub async fn server() -> AppResult<()> {
@@ -42,7 +89,7 @@ pub async fn server() -> AppResult<()> {
     let config = Config::load()?;
 
     // Setup enhanced logging based on configuration
-    setup_logging(&config)?;
+    let logger = setup_logging(&config)?;
 
     // Capture start time for uptime calculation
     let start_time = Instant::now();
@@ -96,7 +143,7 @@ pub async fn server() -> AppResult<()> {
                 DatabaseError::DatabaseNotFound(ref db_name) => {
                     error!("Database not found: {}", db_name);
                     error!(
-                        "Hint: Create the database or enable create_database_if_missing in config"
+                        "Hint: Create the database or schema, or enable create_database_if_missing"
                     );
                 }
                 DatabaseError::MigrationError(ref msg) => {
@@ -108,10 +155,10 @@ pub async fn server() -> AppResult<()> {
                 }
             }
 
-            return Err(AppError::Server(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                format!("Database initialization failed: {}", e),
-            )));
+            return Err(AppError::Server(std::io::Error::other(format!(
+                "Database initialization failed: {}",
+                e
+            ))));
         }
     };
 
@@ -120,33 +167,215 @@ pub async fn server() -> AppResult<()> {
         info!("Connected to database: {} ({})", db_name, db_version);
     }
 
+    // Deploys migrating as a separate step may have skipped it
+    if !config.db.use_migrations {
+        match db.pending_migrations().await {
+            Ok(pending) => {
+                if let Some(latest) = pending.last() {
+                    warn!(
+                        "{} migrations not applied, up to {}; run with --migrate-only",
+                        pending.len(),
+                        latest
+                    );
+                }
+            }
+            Err(e) => warn!("Could not check for pending migrations: {}", e),
+        }
+    }
+
+    // Give local setups predictable data to work against
+    if config.seed.enabled {
+        seed::run(&db, &config).await?;
+    }
+
+    // One pool of outbound connections for providers, webhooks and storage
+    let http_client = Arc::new(HttpClient::new(&config.http_client)?);
+
+    // Connect object storage when configured
+    let object_store = match config.s3.clone() {
+        Some(s3_config) => {
+            let store: Arc<dyn ObjectStore> =
+                Arc::new(S3ObjectStore::new(s3_config, http_client.clone())?);
+            info!("Object storage configured at {}", store.location());
+            Some(store)
+        }
+        None => None,
+    };
+
+    // Backups are shared by all workers and the scheduler so runs never overlap
+    let backup_service: Arc<BackupServiceType> = Arc::new(BackupService::new(
+        Arc::new(ShortenedUrlRepository::new(db.clone())),
+        Arc::new(AnalyticsRepository::new(db.clone())),
+        object_store.clone(),
+        config.backup.include_visit_aggregates,
+    ));
+
+    // The link cache and the database circuit breaker are shared by all workers
+    let db_breaker = Arc::new(CircuitBreaker::new(
+        "Database",
+        config.db_circuit_breaker.failure_threshold,
+        Duration::from_secs(config.db_circuit_breaker.open_seconds),
+    ));
+    let link_cache = Arc::new(LinkCache::new(
+        &config.link_cache,
+        config.short_codes.case_insensitive,
+    ));
+    // Link events are fanned out to notifications and the configured publisher
+    let event_bus = Arc::new(EventBus::new(config.events.capacity));
+    events::spawn_subscribers(&event_bus, &db, &config, http_client.clone())?;
+    // Visits recorded by any worker are batched together for the analytics warehouse
+    let visit_exporter = warehouse::build_visit_exporter(&config, http_client.clone())?;
+
+    // Create requests sent twice by a client get the link created the first time
+    let recent_creates =
+        RecentCreates::new(Duration::from_secs(config.create_dedup.window_seconds));
+    let shortened_url_service: Arc<ShortenedUrlServiceType> = Arc::new(
+        ShortenedUrlService::new(
+            Arc::new(CircuitBreakerRepository::new(
+                ShortenedUrlRepository::new(db.clone()),
+                db_breaker.clone(),
+            )),
+            config.short_codes.clone(),
+            config.pagination.clone(),
+            config.milestones.clone(),
+            link_cache.clone(),
+            Arc::new(AccessBacklog::new(config.access_replay.max_pending_links)),
+            event_bus.clone(),
+        )
+        .with_recent_creates(recent_creates)
+        .with_link_claims(config.link_claims.ttl_days),
+    );
+
+    // Start with the busiest links cached, so a restart doesn't send them all to the database
+    let preload = config.link_cache.preload.min(config.link_cache.capacity);
+    if config.link_cache.enabled && preload > 0 {
+        let started = Instant::now();
+        match shortened_url_service.warm_cache(preload).await {
+            Ok(count) => info!(
+                "Preloaded {} of the most accessed links into the link cache in {:?}",
+                count,
+                started.elapsed()
+            ),
+            Err(e) => warn!("Failed to preload the link cache, starting cold: {}", e),
+        }
+    }
+
+    // Miss counts and the block cache are shared by all workers
+    let scanner_guard: Arc<ScannerGuardServiceType> = Arc::new(ScannerGuardService::new(
+        Arc::new(BlockedIpRepository::new(db.clone())),
+        config.scanner_guard.clone(),
+    ));
+
+    // Links created without an API key are counted per client across all workers
+    let anonymous_create_limiter = Arc::new(RateLimiter::new(
+        config.anonymous_create.rate_limit,
+        Duration::from_secs(config.anonymous_create.window_seconds),
+    ));
+
+    // Redirect limits of individual links are counted across all workers
+    let link_rate_limiter = Arc::new(LinkRateLimiter::new());
+
+    // Hosts checked for HTTPS support are remembered across workers and the upgrade job
+    let https_upgrader = Arc::new(HttpsUpgrader::new(
+        &config.https_upgrade,
+        http_client.clone(),
+    ));
+
+    // Proxied and previewed files get a client of their own, which only reaches public hosts
+    let file_proxy = Arc::new(FileProxy::new(&config.file_proxy, &config.http_client)?);
+
+    // Reverse proxied destinations get a client of their own, which doesn't follow redirects
+    let reverse_proxy = Arc::new(ReverseProxy::new(
+        &config.reverse_proxy,
+        &config.http_client,
+    )?);
+
+    // Destinations on this shortener's own hosts are told apart for every link set up
+    let loop_guard = Arc::new(LoopGuard::new(&config.redirect_loops));
+
+    // Messages of every bundled and configured locale
+    let catalog = Arc::new(Catalog::load(&config.i18n)?);
+
+    // Nonces of signed requests are shared by all workers
+    let request_signer = Arc::new(RequestSigner::new(config.request_signing.clone()));
+
+    // Tenants are resolved from memory on every request
+    let tenant_service: Arc<TenantServiceType> = Arc::new(TenantService::new(Arc::new(
+        TenantRepository::new(db.clone()),
+    )));
+    if let Err(e) = tenant_service.refresh().await {
+        warn!("Failed to load tenants, retrying in the background: {}", e);
+    }
+
+    // Settings safe to change while running are reloaded from the config file for all workers
+    let live_config = Arc::new(LiveConfig::new(
+        config.clone(),
+        Some(logger),
+        anonymous_create_limiter.clone(),
+        scanner_guard.clone(),
+    ));
+
+    // Maintenance is switched for all workers and the scheduled jobs at once
+    let maintenance = Arc::new(MaintenanceMode::new(&config.maintenance));
+    if config.maintenance.enabled {
+        warn!("Starting in maintenance mode, the API answers 503 until it's switched off");
+    }
+
+    // Start background maintenance jobs
+    let job_scheduler = jobs::spawn_background_jobs(
+        &db,
+        &config,
+        object_store,
+        jobs::SharedServices {
+            backup: backup_service.clone(),
+            scanner_guard: scanner_guard.clone(),
+            shortened_urls: shortened_url_service.clone(),
+            tenants: tenant_service.clone(),
+            http_client: http_client.clone(),
+            events: event_bus.clone(),
+            visit_exporter: visit_exporter.clone(),
+            https_upgrader: https_upgrader.clone(),
+            maintenance: maintenance.clone(),
+            live_config: live_config.clone(),
+        },
+    )
+    .await?;
+
     // Create a shared database reference for shutdown handling
     let db_for_shutdown = db.clone();
+    let exporter_for_shutdown = visit_exporter.clone();
 
     // Start the HTTP server
     let _server = HttpServer::new(move || {
         // Create a default CORS policy that is restrictive
+        let live = live_config.clone();
         let cors = Cors::default()
-            // Allow only your frontend origin in a production environment
-            .allowed_origin("http://localhost:3000") // Replace with your frontend URL
-            // For development environments, you might want to allow localhost with different ports
-            .allowed_origin("http://127.0.0.1:3000")
-            // Optionally allow development URLs conditionally
-            .allowed_origin_fn(|origin, _req_head| {
-                // In development, you might want to be more permissive
-                if cfg!(debug_assertions) {
-                    // Check if origin starts with http://localhost:
-                    origin.as_bytes().starts_with(b"http://localhost:")
-                } else {
-                    // In production, be strict
-                    false
-                }
+            // Allow the origins in CORS_ALLOWED_ORIGINS, which is reloaded without a restart
+            .allowed_origin_fn(move |origin, _req_head| {
+                let origin = origin.to_str().unwrap_or_default();
+                // In development, any localhost port is allowed as well
+                live.allows_origin(origin)
+                    || (cfg!(debug_assertions) && origin.starts_with("http://localhost:"))
             })
             // Define which headers are allowed
             .allowed_headers(vec![
                 http::header::AUTHORIZATION,
                 http::header::ACCEPT,
                 http::header::CONTENT_TYPE,
+                http::header::HeaderName::from_static("x-tenant"),
+                http::header::HeaderName::from_static("x-csrf-token"),
+                http::header::HeaderName::from_static("x-captcha-token"),
+                http::header::HeaderName::from_static("x-signature"),
+                http::header::HeaderName::from_static("x-signature-key"),
+                http::header::HeaderName::from_static("x-signature-timestamp"),
+                http::header::HeaderName::from_static("x-signature-nonce"),
+            ])
+            // Let browser clients read how much of their rate limit is left
+            .expose_headers(vec![
+                X_RATELIMIT_LIMIT,
+                X_RATELIMIT_REMAINING,
+                X_RATELIMIT_RESET,
+                http::header::RETRY_AFTER,
             ])
             // Define which methods are allowed
             .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
@@ -161,10 +390,56 @@ pub async fn server() -> AppResult<()> {
             .app_data(web::Data::new(AppState {
                 start_time,
                 db: db.clone(),
+                db_breaker: db_breaker.clone(),
+                link_cache: link_cache.clone(),
+                http_client: http_client.clone(),
+                shortened_urls: shortened_url_service.clone(),
+                job_scheduler: job_scheduler.clone(),
+                event_bus: event_bus.clone(),
+                visit_exporter: visit_exporter.clone(),
+                maintenance: maintenance.clone(),
                 version: app_config.app.version.clone(),
             }))
             // Make the full configuration available to handlers
             .app_data(web::Data::new(app_config.clone()))
+            .app_data(web::Data::from(backup_service.clone()))
+            .app_data(web::Data::from(scanner_guard.clone()))
+            .app_data(web::Data::from(shortened_url_service.clone()))
+            .app_data(web::Data::from(tenant_service.clone()))
+            .app_data(web::Data::from(job_scheduler.clone()))
+            .app_data(web::Data::from(anonymous_create_limiter.clone()))
+            .app_data(web::Data::from(link_rate_limiter.clone()))
+            .app_data(web::Data::from(https_upgrader.clone()))
+            .app_data(web::Data::from(file_proxy.clone()))
+            .app_data(web::Data::from(reverse_proxy.clone()))
+            .app_data(web::Data::from(loop_guard.clone()))
+            .app_data(web::Data::from(catalog.clone()))
+            .app_data(web::Data::from(maintenance.clone()))
+            .app_data(web::Data::from(live_config.clone()))
+            // Answer unparsable bodies, paths and queries with the usual error response
+            .app_data(extract::json_config())
+            .app_data(extract::path_config())
+            .app_data(extract::query_config())
+            // Tell rate limited clients how much of their limit is left
+            .wrap(RateLimitHeaders)
+            // Refuse cross-site forgeries of cookie authenticated requests
+            .wrap(CsrfProtection::new(app_config.csrf.clone()))
+            // Keep service tokens to the endpoints their scopes cover
+            .wrap(ServiceTokenAuth)
+            // Check signatures of partner requests before anything acts on them
+            .wrap(RequestSigning::new(request_signer.clone()))
+            // Give up on handlers stuck on a slow database
+            .wrap(RequestTimeout::new(app_config.request_timeouts.clone()))
+            // Slow down and block clients enumerating short codes
+            .wrap(ScannerGuard::new(scanner_guard.clone()))
+            // Refuse the API while operators work on the database, redirects keep serving
+            .wrap(Maintenance::new(maintenance.clone()))
+            // Translate error messages into the language the client asks for
+            .wrap(Localization)
+            // Encode API responses as MessagePack or CBOR when asked to
+            .wrap(ContentNegotiation)
+            // Route /api/v{N}/ paths and flag deprecated ones, before anything looks at paths
+            .wrap(ApiVersioning::new(app_config.api_versions.clone()))
             .wrap(Logger::new(log_format))
             // Add request tracking ID
             .wrap(DefaultHeaders::new().add(("X-Request-ID", uuid::Uuid::new_v4().to_string())))
@@ -173,11 +448,16 @@ pub async fn server() -> AppResult<()> {
 
         // Configure routes
         app.configure(|cfg| {
-                // Register services and routes 
-                services::register(db.clone(), cfg);
-                routes::configure_routes(cfg);
-            }
-        )
+            // Register services and routes
+            services::register(
+                db.clone(),
+                &app_config,
+                http_client.clone(),
+                visit_exporter.clone(),
+                cfg,
+            );
+            routes::configure_routes(cfg);
+        })
     })
     .workers(config.server.workers)
     .bind((config.server.host.to_string(), config.server.port))?
@@ -203,6 +483,12 @@ pub async fn server() -> AppResult<()> {
 
     // Once the server has stopped, clean up the database connections
     info!("Web server stopped, cleaning up resources...");
+    // Send the visits still pending rather than losing them with the process
+    if let Some(exporter) = exporter_for_shutdown {
+        if let Err(e) = exporter.flush().await {
+            warn!("Failed to export pending visits before exiting: {}", e);
+        }
+    }
     db_for_shutdown.shutdown().await;
     info!("All resources cleaned up, goodbye!");
 
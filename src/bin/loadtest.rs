@@ -0,0 +1,125 @@
+// src/bin/loadtest.rs - Minimal load generator for smoke-testing redirect latency
+//
+// Usage: loadtest <url> [requests] [concurrency]
+//
+// Redirects are not followed, so only the shortener itself is measured.
+
+use std::collections::BTreeMap;
+use std::process;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use reqwest::redirect::Policy;
+use tokio::sync::Mutex;
+
+const DEFAULT_REQUESTS: usize = 1000;
+const DEFAULT_CONCURRENCY: usize = 16;
+
+struct Samples {
+    latencies: Vec<Duration>,
+    statuses: BTreeMap<u16, usize>,
+    errors: usize,
+}
+
+fn parse_arg(args: &[String], index: usize, default: usize) -> usize {
+    match args.get(index) {
+        Some(value) => value.parse().unwrap_or_else(|_| {
+            eprintln!("Invalid number: {}", value);
+            process::exit(2);
+        }),
+        None => default,
+    }
+}
+
+fn percentile(sorted: &[Duration], pct: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+    let index = ((sorted.len() as f64 * pct / 100.0).ceil() as usize).clamp(1, sorted.len()) - 1;
+    sorted[index]
+}
+
+#[tokio::main]
+async fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let Some(url) = args.first().cloned() else {
+        eprintln!("Usage: loadtest <url> [requests] [concurrency]");
+        process::exit(2);
+    };
+    let requests = parse_arg(&args, 1, DEFAULT_REQUESTS);
+    let concurrency = parse_arg(&args, 2, DEFAULT_CONCURRENCY).clamp(1, requests.max(1));
+
+    let client = reqwest::Client::builder()
+        .redirect(Policy::none())
+        .build()
+        .expect("Failed to build HTTP client");
+
+    let samples = Arc::new(Mutex::new(Samples {
+        latencies: Vec::with_capacity(requests),
+        statuses: BTreeMap::new(),
+        errors: 0,
+    }));
+
+    let started = Instant::now();
+    let workers = (0..concurrency)
+        .map(|worker| {
+            let client = client.clone();
+            let url = url.clone();
+            let samples = samples.clone();
+            // Spread the requests evenly, giving the remainder to the first workers
+            let share = requests / concurrency + usize::from(worker < requests % concurrency);
+
+            tokio::spawn(async move {
+                for _ in 0..share {
+                    let sent = Instant::now();
+                    let result = client.get(&url).send().await;
+                    let elapsed = sent.elapsed();
+
+                    let mut samples = samples.lock().await;
+                    match result {
+                        Ok(response) => {
+                            samples.latencies.push(elapsed);
+                            *samples
+                                .statuses
+                                .entry(response.status().as_u16())
+                                .or_default() += 1;
+                        }
+                        Err(_) => samples.errors += 1,
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for worker in workers {
+        let _ = worker.await;
+    }
+    let total = started.elapsed();
+
+    let mut samples = samples.lock().await;
+    samples.latencies.sort();
+    let completed = samples.latencies.len();
+
+    println!("Target:       {}", url);
+    println!(
+        "Requests:     {} ({} failed)",
+        completed + samples.errors,
+        samples.errors
+    );
+    println!("Concurrency:  {}", concurrency);
+    println!("Duration:     {:.2?}", total);
+    println!(
+        "Throughput:   {:.1} req/s",
+        completed as f64 / total.as_secs_f64()
+    );
+    for (status, count) in &samples.statuses {
+        println!("Status {}:   {}", status, count);
+    }
+    println!("Latency p50:  {:.2?}", percentile(&samples.latencies, 50.0));
+    println!("Latency p90:  {:.2?}", percentile(&samples.latencies, 90.0));
+    println!("Latency p99:  {:.2?}", percentile(&samples.latencies, 99.0));
+    println!(
+        "Latency max:  {:.2?}",
+        samples.latencies.last().copied().unwrap_or_default()
+    );
+}
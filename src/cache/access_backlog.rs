@@ -0,0 +1,112 @@
+// src/cache/access_backlog.rs - Access counts waiting for the database to come back
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Utc};
+use log::warn;
+use uuid::Uuid;
+
+/// Redirects of one link that couldn't be counted yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingAccess {
+    pub url_id: Uuid,
+    pub count: i64,
+    pub last_accessed: DateTime<Utc>,
+}
+
+/// Access counts of redirects served while the database was unreachable
+///
+/// Counts are merged per link, so the backlog grows with the number of links visited
+/// during an outage rather than the number of visits. Past `max_links`, accesses of
+/// further links are dropped.
+pub struct AccessBacklog {
+    max_links: usize,
+    pending: Mutex<HashMap<Uuid, PendingAccess>>,
+}
+
+impl AccessBacklog {
+    pub fn new(max_links: usize) -> Self {
+        Self {
+            max_links,
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts one access to be written later
+    pub fn record(&self, url_id: Uuid, at: DateTime<Utc>) {
+        self.merge(PendingAccess {
+            url_id,
+            count: 1,
+            last_accessed: at,
+        });
+    }
+
+    /// Puts accesses back after a failed replay
+    pub fn restore(&self, accesses: impl IntoIterator<Item = PendingAccess>) {
+        for access in accesses {
+            self.merge(access);
+        }
+    }
+
+    fn merge(&self, access: PendingAccess) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(entry) = pending.get_mut(&access.url_id) {
+            entry.count += access.count;
+            entry.last_accessed = entry.last_accessed.max(access.last_accessed);
+        } else if pending.len() < self.max_links {
+            pending.insert(access.url_id, access);
+        } else {
+            warn!(
+                "Access backlog is full, dropping {} access(es) to {}",
+                access.count, access.url_id
+            );
+        }
+    }
+
+    /// Takes every pending access, leaving the backlog empty
+    pub fn drain(&self) -> Vec<PendingAccess> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        pending.drain().map(|(_, access)| access).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[test]
+    fn merges_accesses_per_link_and_caps_the_number_of_links() {
+        let backlog = AccessBacklog::new(1);
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+        let now = Utc::now();
+
+        backlog.record(first, now);
+        backlog.record(first, now - Duration::seconds(5));
+        backlog.record(second, now);
+        backlog.restore([PendingAccess {
+            url_id: first,
+            count: 3,
+            last_accessed: now - Duration::seconds(10),
+        }]);
+
+        assert_eq!(
+            backlog.drain(),
+            vec![PendingAccess {
+                url_id: first,
+                count: 5,
+                last_accessed: now,
+            }]
+        );
+        assert!(backlog.is_empty());
+    }
+}
@@ -0,0 +1,352 @@
+// src/cache/link_cache.rs - In-memory cache of links resolved for redirects
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{config::LinkCacheConfig, models::ShortenedUrl};
+
+struct CachedLink {
+    url: ShortenedUrl,
+    cached_at: Instant,
+}
+
+/// Cache effectiveness since startup, as reported by the health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkCacheStats {
+    pub enabled: bool,
+    pub entries: usize,
+    pub missing_entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Lookups of codes recently found not to exist, answered without the database
+    pub negative_hits: u64,
+    /// Expired entries served because the database was unreachable
+    pub stale_hits: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    negative_hits: AtomicU64,
+    stale_hits: AtomicU64,
+}
+
+/// Links recently resolved by code, so repeated redirects don't each query the database
+///
+/// Entries are fresh for the configured TTL. Expired entries are kept until evicted, so
+/// redirects can still be served from them while the database is unreachable. The cache
+/// is per instance: changes made elsewhere show up once the entry expires.
+///
+/// Codes found not to exist are remembered for a shorter TTL, so clients probing random
+/// codes don't each cost a query. Creating a code forgets that it was missing.
+///
+/// Codes are cached per tenant, `None` being the default tenant.
+pub struct LinkCache {
+    enabled: bool,
+    ttl: Duration,
+    negative_ttl: Duration,
+    capacity: usize,
+    case_insensitive: bool,
+    entries: RwLock<HashMap<String, CachedLink>>,
+    missing: RwLock<HashMap<String, Instant>>,
+    counters: Counters,
+}
+
+impl LinkCache {
+    pub fn new(config: &LinkCacheConfig, case_insensitive: bool) -> Self {
+        Self {
+            enabled: config.enabled && config.capacity > 0,
+            ttl: Duration::from_secs(config.ttl_seconds),
+            negative_ttl: Duration::from_secs(config.negative_ttl_seconds),
+            capacity: config.capacity,
+            case_insensitive,
+            entries: RwLock::new(HashMap::new()),
+            missing: RwLock::new(HashMap::new()),
+            counters: Counters::default(),
+        }
+    }
+
+    /// A cache that never holds anything, for one-off tools and tests
+    pub fn disabled() -> Self {
+        Self::new(
+            &LinkCacheConfig {
+                enabled: false,
+                ttl_seconds: 0,
+                capacity: 0,
+                preload: 0,
+                negative_ttl_seconds: 0,
+            },
+            false,
+        )
+    }
+
+    /// The key a code is cached under, the same for every spelling that resolves to it
+    pub fn key(&self, tenant: Option<&Uuid>, namespace: Option<&str>, code: &str) -> String {
+        let path = match namespace {
+            Some(namespace) => format!("{}/{}", namespace, code),
+            None => code.to_string(),
+        };
+        let path = if self.case_insensitive {
+            path.to_ascii_lowercase()
+        } else {
+            path
+        };
+        match tenant {
+            Some(tenant) => format!("{}:{}", tenant, path),
+            None => path,
+        }
+    }
+
+    fn lookup(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+        max_age: Option<Duration>,
+    ) -> Option<ShortenedUrl> {
+        if !self.enabled {
+            return None;
+        }
+
+        let entries = self.entries.read().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&self.key(tenant, namespace, code))
+            .filter(|entry| max_age.is_none_or(|max_age| entry.cached_at.elapsed() < max_age))
+            .map(|entry| entry.url.clone())
+    }
+
+    /// The cached link for a code, if cached within the TTL
+    pub fn get(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Option<ShortenedUrl> {
+        let url = self.lookup(tenant, namespace, code, Some(self.ttl));
+        if self.enabled {
+            let counter = match url {
+                Some(_) => &self.counters.hits,
+                None => &self.counters.misses,
+            };
+            counter.fetch_add(1, Ordering::Relaxed);
+        }
+        url
+    }
+
+    /// The cached link for a code however old, for when the database can't be asked
+    pub fn get_stale(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Option<ShortenedUrl> {
+        let url = self.lookup(tenant, namespace, code, None);
+        if url.is_some() {
+            self.counters.stale_hits.fetch_add(1, Ordering::Relaxed);
+        }
+        url
+    }
+
+    fn negative_enabled(&self) -> bool {
+        self.enabled && !self.negative_ttl.is_zero()
+    }
+
+    /// Whether the code was recently found not to exist
+    pub fn is_missing(&self, tenant: Option<&Uuid>, namespace: Option<&str>, code: &str) -> bool {
+        if !self.negative_enabled() {
+            return false;
+        }
+
+        let missing = self.missing.read().unwrap_or_else(|e| e.into_inner());
+        let is_missing = missing
+            .get(&self.key(tenant, namespace, code))
+            .is_some_and(|at| at.elapsed() < self.negative_ttl);
+        if is_missing {
+            self.counters.negative_hits.fetch_add(1, Ordering::Relaxed);
+        }
+        is_missing
+    }
+
+    /// Remembers that a code doesn't exist
+    pub fn insert_missing(&self, tenant: Option<&Uuid>, namespace: Option<&str>, code: &str) {
+        if !self.negative_enabled() {
+            return;
+        }
+
+        let mut missing = self.missing.write().unwrap_or_else(|e| e.into_inner());
+        if missing.len() >= self.capacity {
+            missing.retain(|_, at| at.elapsed() < self.negative_ttl);
+            // Still full of fresh entries, so most likely a scan: keep what we have
+            if missing.len() >= self.capacity {
+                return;
+            }
+        }
+        missing.insert(self.key(tenant, namespace, code), Instant::now());
+    }
+
+    /// Forgets that a code was missing, once it has been created
+    pub fn remove_missing(&self, tenant: Option<&Uuid>, namespace: Option<&str>, code: &str) {
+        if !self.negative_enabled() {
+            return;
+        }
+
+        let mut missing = self.missing.write().unwrap_or_else(|e| e.into_inner());
+        missing.remove(&self.key(tenant, namespace, code));
+    }
+
+    pub fn stats(&self) -> LinkCacheStats {
+        LinkCacheStats {
+            enabled: self.enabled,
+            entries: self.len(),
+            missing_entries: self.missing.read().unwrap_or_else(|e| e.into_inner()).len(),
+            hits: self.counters.hits.load(Ordering::Relaxed),
+            misses: self.counters.misses.load(Ordering::Relaxed),
+            negative_hits: self.counters.negative_hits.load(Ordering::Relaxed),
+            stale_hits: self.counters.stale_hits.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn insert(&self, url: &ShortenedUrl) {
+        if !self.enabled {
+            return;
+        }
+
+        let key = self.key(
+            url.tenant_id.as_ref(),
+            url.namespace.as_deref(),
+            &url.short_code,
+        );
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+
+        // Make room by dropping the entry cached longest ago
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.cached_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(
+            key,
+            CachedLink {
+                url: url.clone(),
+                cached_at: Instant::now(),
+            },
+        );
+    }
+
+    /// Drops a link after it changed, so the next redirect reads it again
+    pub fn invalidate(&self, id: &Uuid) {
+        if !self.enabled {
+            return;
+        }
+
+        let mut entries = self.entries.write().unwrap_or_else(|e| e.into_inner());
+        entries.retain(|_, entry| entry.url.id != *id);
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.read().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(ttl_seconds: u64, capacity: usize) -> LinkCache {
+        LinkCache::new(
+            &LinkCacheConfig {
+                enabled: true,
+                ttl_seconds,
+                capacity,
+                preload: 0,
+                negative_ttl_seconds: ttl_seconds,
+            },
+            true,
+        )
+    }
+
+    fn link(namespace: Option<&str>, code: &str) -> ShortenedUrl {
+        ShortenedUrl {
+            id: Uuid::new_v4(),
+            namespace: namespace.map(str::to_string),
+            short_code: code.to_string(),
+            original_url: format!("https://example.com/{}", code),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn serves_fresh_entries_and_keeps_expired_ones_for_outages() {
+        let fresh = cache(60, 10);
+        let url = link(Some("docs"), "setup");
+        fresh.insert(&url);
+        assert_eq!(
+            fresh.get(None, Some("Docs"), "SETUP").map(|u| u.id),
+            Some(url.id)
+        );
+        assert!(fresh.get(None, None, "setup").is_none());
+        assert!(fresh
+            .get(Some(&Uuid::new_v4()), Some("docs"), "setup")
+            .is_none());
+
+        let expired = cache(0, 10);
+        expired.insert(&url);
+        assert!(expired.get(None, Some("docs"), "setup").is_none());
+        assert_eq!(
+            expired.get_stale(None, Some("docs"), "setup").map(|u| u.id),
+            Some(url.id)
+        );
+    }
+
+    #[test]
+    fn evicts_the_oldest_entry_and_invalidates_by_id() {
+        let cache = cache(60, 2);
+        let first = link(None, "first");
+        let second = link(None, "second");
+        for url in [&first, &second, &link(None, "third")] {
+            cache.insert(url);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get_stale(None, None, "first").is_none());
+
+        cache.invalidate(&second.id);
+        assert!(cache.get(None, None, "second").is_none());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn remembers_missing_codes_until_created() {
+        let cache = cache(60, 1);
+        assert!(!cache.is_missing(None, None, "nope"));
+
+        cache.insert_missing(None, None, "Nope");
+        assert!(cache.is_missing(None, None, "nope"));
+
+        // Full of fresh entries, so new ones are not remembered
+        cache.insert_missing(None, None, "other");
+        assert!(!cache.is_missing(None, None, "other"));
+
+        cache.remove_missing(None, None, "nope");
+        assert!(!cache.is_missing(None, None, "nope"));
+
+        let stats = cache.stats();
+        assert_eq!(stats.negative_hits, 1);
+        assert_eq!(stats.missing_entries, 0);
+    }
+}
@@ -0,0 +1,86 @@
+// src/cache/link_rate_limiter.rs - Redirect rate limits of individual links
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+// Links tracked before buckets that refilled completely are swept out
+const SWEEP_THRESHOLD: usize = 10_000;
+
+// Limits are set per minute, so an idle bucket is full again after this long
+const REFILL_PERIOD: Duration = Duration::from_secs(60);
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+/// Token buckets holding back redirects to links with a per-minute limit
+///
+/// Each bucket holds a minute's worth of redirects and refills continuously, so short
+/// bursts go through while the average stays under the limit. Buckets live in memory, so
+/// every instance applies the limit on its own.
+pub struct LinkRateLimiter {
+    buckets: Mutex<HashMap<Uuid, Bucket>>,
+}
+
+impl Default for LinkRateLimiter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LinkRateLimiter {
+    pub fn new() -> Self {
+        Self {
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes a token for a redirect to a link allowing `per_minute` redirects
+    ///
+    /// ### Returns
+    /// * `Ok(())` - The redirect is within the limit
+    /// * `Err(Duration)` - How long until the next token is available
+    pub fn check(&self, url_id: Uuid, per_minute: u32) -> Result<(), Duration> {
+        let capacity = f64::from(per_minute.max(1));
+        let per_second = capacity / REFILL_PERIOD.as_secs_f64();
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        if buckets.len() >= SWEEP_THRESHOLD {
+            buckets.retain(|_, bucket| now.duration_since(bucket.refilled_at) < REFILL_PERIOD);
+        }
+
+        let bucket = buckets.entry(url_id).or_insert(Bucket {
+            tokens: capacity,
+            refilled_at: now,
+        });
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * per_second).min(capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens < 1.0 {
+            return Err(Duration::from_secs_f64((1.0 - bucket.tokens) / per_second));
+        }
+        bucket.tokens -= 1.0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_each_link_separately() {
+        let limiter = LinkRateLimiter::new();
+        let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+        assert!((0..3).all(|_| limiter.check(first, 3).is_ok()));
+        let wait = limiter.check(first, 3).unwrap_err();
+        assert!(wait > Duration::from_secs(19) && wait <= Duration::from_secs(20));
+        assert!(limiter.check(second, 3).is_ok());
+    }
+}
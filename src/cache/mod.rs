@@ -0,0 +1,11 @@
+mod access_backlog;
+mod link_cache;
+mod link_rate_limiter;
+mod recent_creates;
+mod single_flight;
+
+pub use access_backlog::{AccessBacklog, PendingAccess};
+pub use link_cache::{LinkCache, LinkCacheStats};
+pub use link_rate_limiter::LinkRateLimiter;
+pub use recent_creates::RecentCreates;
+pub use single_flight::SingleFlight;
@@ -0,0 +1,181 @@
+// src/cache/recent_creates.rs - Links just created, to answer a create request sent twice
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::SingleFlight;
+use crate::models::ShortenedUrlResponseDto;
+
+// Requests remembered at most, the oldest being forgotten first once a burst reaches it
+const MAX_ENTRIES: usize = 10_000;
+
+/// Answers to the creations of the last few seconds, by a hash of the request that made them
+///
+/// A form submitted twice, or a client retrying a request that timed out, gets the link the
+/// first request created, with its claim token, instead of a second one. Identical requests
+/// arriving together share one creation. Entries live in memory, so every instance only knows
+/// its own requests.
+pub struct RecentCreates {
+    window: Duration,
+    capacity: usize,
+    created: Mutex<Created>,
+    in_flight: SingleFlight<Option<ShortenedUrlResponseDto>>,
+}
+
+#[derive(Default)]
+struct Created {
+    answers: HashMap<String, (Instant, ShortenedUrlResponseDto)>,
+    /// Keys in the order they were inserted, an answer replaced since leaving a stale entry
+    order: VecDeque<(Instant, String)>,
+}
+
+impl Created {
+    fn pop_oldest(&mut self) {
+        if let Some((at, key)) = self.order.pop_front() {
+            if self
+                .answers
+                .get(&key)
+                .is_some_and(|(inserted, _)| *inserted == at)
+            {
+                self.answers.remove(&key);
+            }
+        }
+    }
+}
+
+impl RecentCreates {
+    pub fn new(window: Duration) -> Self {
+        Self::with_capacity(window, MAX_ENTRIES)
+    }
+
+    /// Remembers at most `capacity` requests, however many arrive within the window
+    pub fn with_capacity(window: Duration, capacity: usize) -> Self {
+        Self {
+            window,
+            capacity,
+            created: Mutex::new(Created::default()),
+            in_flight: SingleFlight::new(),
+        }
+    }
+
+    /// Remembers nothing, every request creates a link
+    pub fn disabled() -> Self {
+        Self::new(Duration::ZERO)
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.window.is_zero()
+    }
+
+    /// The answer to the creation for `key` within the window
+    pub fn get(&self, key: &str) -> Option<ShortenedUrlResponseDto> {
+        let created = self.created.lock().unwrap_or_else(|e| e.into_inner());
+        created
+            .answers
+            .get(key)
+            .filter(|(at, _)| at.elapsed() < self.window)
+            .map(|(_, answer)| answer.clone())
+    }
+
+    pub fn insert(&self, key: String, answer: ShortenedUrlResponseDto) {
+        let now = Instant::now();
+        let mut created = self.created.lock().unwrap_or_else(|e| e.into_inner());
+        while created
+            .order
+            .front()
+            .is_some_and(|(at, _)| now.duration_since(*at) >= self.window)
+        {
+            created.pop_oldest();
+        }
+        while created.order.len() >= self.capacity.max(1) {
+            created.pop_oldest();
+        }
+        created.order.push_back((now, key.clone()));
+        created.answers.insert(key, (now, answer));
+    }
+
+    /// Creations running, so identical requests can wait for the first one's answer. `None` is
+    /// shared when it failed
+    pub fn in_flight(&self) -> &SingleFlight<Option<ShortenedUrlResponseDto>> {
+        &self.in_flight
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::models::{LinkBehavior, LinkStatus};
+
+    fn answer(short_code: &str) -> ShortenedUrlResponseDto {
+        ShortenedUrlResponseDto {
+            id: None,
+            is_active: true,
+            status: LinkStatus::Active,
+            access_count: 0,
+            short_code: short_code.to_string(),
+            namespace: None,
+            original_url: "https://example.com".to_string(),
+            is_custom_code: false,
+            created_at: Utc::now(),
+            metadata: None,
+            expires_at: None,
+            allowed_cidrs: Vec::new(),
+            denied_cidrs: Vec::new(),
+            single_use: false,
+            consumed_at: None,
+            forward_path: false,
+            forward_query: false,
+            fallback_url: None,
+            max_idle_days: None,
+            idle_expires_at: None,
+            upgrade_https: None,
+            last_checked_at: None,
+            check_status: None,
+            is_broken: false,
+            behavior: LinkBehavior::Redirect,
+            app_url: None,
+            og_title: None,
+            og_description: None,
+            og_image: None,
+            max_redirects_per_minute: None,
+            notes: None,
+            archived_at: None,
+            version: 0,
+            claim_token: None,
+            claim_expires_at: None,
+        }
+    }
+
+    fn short_code(recent: &RecentCreates, key: &str) -> Option<String> {
+        recent.get(key).map(|answer| answer.short_code)
+    }
+
+    #[test]
+    fn test_forgets_after_window() {
+        let recent = RecentCreates::new(Duration::from_millis(20));
+        recent.insert("key".to_string(), answer("abc123"));
+        assert_eq!(short_code(&recent, "key").as_deref(), Some("abc123"));
+        assert!(recent.get("other").is_none());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(recent.get("key").is_none());
+        assert!(!RecentCreates::disabled().is_enabled());
+    }
+
+    #[test]
+    fn test_forgets_oldest_past_capacity() {
+        let recent = RecentCreates::with_capacity(Duration::from_secs(60), 2);
+        recent.insert("first".to_string(), answer("one"));
+        recent.insert("second".to_string(), answer("two"));
+        // Answering again moves a request to the back of the line
+        recent.insert("first".to_string(), answer("three"));
+        recent.insert("third".to_string(), answer("four"));
+
+        assert!(recent.get("second").is_none());
+        assert_eq!(short_code(&recent, "first").as_deref(), Some("three"));
+        assert_eq!(short_code(&recent, "third").as_deref(), Some("four"));
+        assert_eq!(recent.created.lock().unwrap().answers.len(), 2);
+    }
+}
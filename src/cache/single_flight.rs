@@ -0,0 +1,130 @@
+// src/cache/single_flight.rs - Coalesces concurrent identical lookups
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Mutex;
+
+use tokio::sync::broadcast;
+
+/// Runs one lookup per key at a time, sharing its result with callers asking meanwhile
+///
+/// The first caller for a key runs the lookup, later callers wait for its result. If the
+/// first caller goes away before finishing (e.g. the request was cancelled), the waiting
+/// callers each run the lookup themselves.
+pub struct SingleFlight<V> {
+    calls: Mutex<HashMap<String, broadcast::Sender<V>>>,
+}
+
+impl<V> Default for SingleFlight<V> {
+    fn default() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+// Forgets the call if its leader is dropped before finishing, so no one waits on it forever
+struct CallGuard<'a, V> {
+    flight: &'a SingleFlight<V>,
+    key: &'a str,
+}
+
+impl<V> CallGuard<'_, V> {
+    // Ends the call, handing back the channel to its waiters
+    fn finish(self) -> Option<broadcast::Sender<V>> {
+        let sender = self
+            .flight
+            .calls
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(self.key);
+        std::mem::forget(self);
+        sender
+    }
+}
+
+impl<V> Drop for CallGuard<'_, V> {
+    fn drop(&mut self) {
+        let mut calls = self.flight.calls.lock().unwrap_or_else(|e| e.into_inner());
+        calls.remove(self.key);
+    }
+}
+
+impl<V: Clone> SingleFlight<V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn run<F, Fut>(&self, key: &str, lookup: F) -> V
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = V>,
+    {
+        let waiting = {
+            let mut calls = self.calls.lock().unwrap_or_else(|e| e.into_inner());
+            match calls.get(key) {
+                Some(sender) => Some(sender.subscribe()),
+                None => {
+                    calls.insert(key.to_string(), broadcast::channel(1).0);
+                    None
+                }
+            }
+        };
+
+        if let Some(mut receiver) = waiting {
+            return match receiver.recv().await {
+                Ok(value) => value,
+                Err(_) => lookup().await,
+            };
+        }
+
+        let guard = CallGuard { flight: self, key };
+        let value = lookup().await;
+
+        if let Some(sender) = guard.finish() {
+            // Nobody waiting is fine
+            let _ = sender.send(value.clone());
+        }
+        value
+    }
+
+    /// Number of lookups currently running
+    pub fn in_flight(&self) -> usize {
+        self.calls.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn concurrent_callers_share_one_lookup() {
+        let flight = Arc::new(SingleFlight::<usize>::new());
+        let lookups = Arc::new(AtomicUsize::new(0));
+
+        let callers = (0..10)
+            .map(|_| {
+                let flight = flight.clone();
+                let lookups = lookups.clone();
+                tokio::spawn(async move {
+                    flight
+                        .run("code", || async {
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            lookups.fetch_add(1, Ordering::SeqCst) + 42
+                        })
+                        .await
+                })
+            })
+            .collect::<Vec<_>>();
+
+        for caller in callers {
+            assert_eq!(caller.await.unwrap(), 42);
+        }
+        assert_eq!(lookups.load(Ordering::SeqCst), 1);
+        assert_eq!(flight.in_flight(), 0);
+    }
+}
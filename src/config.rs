@@ -1,10 +1,21 @@
-use std::{env, net::IpAddr, str::FromStr};
+use std::{collections::HashMap, env, fmt, net::IpAddr, str::FromStr};
 
-use dotenvy::dotenv;
+use dotenvy::from_filename;
+use ipnet::IpNet;
 use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
+use url::Url;
 
-use crate::errors::ConfigError;
+use crate::{
+    errors::ConfigError,
+    models::{ChannelKind, CodeStrategy, FlagAction},
+    utils::{
+        id_generator::{BASE62_ALPHABET, UNAMBIGUOUS_ALPHABET},
+        network::parse_cidr,
+        unicode::nfc,
+    },
+    validations::MAX_ALIAS_LENGTH,
+};
 
 // Server-specific configuration
 #[derive(Debug, Deserialize, Clone)]
@@ -12,6 +23,8 @@ pub struct ServerConfig {
     pub host: IpAddr,
     pub port: u16,
     pub workers: usize,
+    /// Proxies whose `Forwarded` and `X-Forwarded-For` headers tell the client address
+    pub trusted_proxies: Vec<IpNet>,
 }
 
 // Application-specific configuration
@@ -21,6 +34,8 @@ pub struct AppConfig {
     pub version: String,
     pub environment: Environment,
     pub log_level: String,
+    /// Where visitors of expired or deactivated links go when the link sets no fallback
+    pub fallback_url: Option<String>,
 }
 
 // Environment enum for different deployment environments
@@ -62,6 +77,809 @@ pub struct DatabaseConfig {
     pub skip_db_exists_check: bool,
     pub connect_timeout_seconds: u64,
     pub create_database_if_missing: bool,
+    /// Postgres schema holding the tables, so several environments can share a database
+    /// (None for `public`)
+    pub schema: Option<String>,
+}
+
+// Where raw visit events go once they age out of the retention window
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ArchiveSinkKind {
+    None,
+    Csv,
+    S3,
+}
+
+impl FromStr for ArchiveSinkKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" | "" => Ok(ArchiveSinkKind::None),
+            "csv" => Ok(ArchiveSinkKind::Csv),
+            "s3" => Ok(ArchiveSinkKind::S3),
+            _ => Err(format!(
+                "Invalid archive sink: {}. Must be one of: none, csv, s3",
+                s
+            )),
+        }
+    }
+}
+
+// External system receiving link events from the event bus
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EventPublisherKind {
+    None,
+    Nats,
+    Kafka,
+}
+
+impl FromStr for EventPublisherKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" | "" => Ok(EventPublisherKind::None),
+            "nats" => Ok(EventPublisherKind::Nats),
+            "kafka" => Ok(EventPublisherKind::Kafka),
+            _ => Err(format!(
+                "Invalid event publisher: {}. Must be one of: none, nats, kafka",
+                s
+            )),
+        }
+    }
+}
+
+// Analytics warehouse receiving every visit as it happens
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum VisitExportKind {
+    None,
+    ClickHouse,
+    Http,
+}
+
+impl FromStr for VisitExportKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" | "" => Ok(VisitExportKind::None),
+            "clickhouse" => Ok(VisitExportKind::ClickHouse),
+            "http" => Ok(VisitExportKind::Http),
+            _ => Err(format!(
+                "Invalid visit export sink: {}. Must be one of: none, clickhouse, http",
+                s
+            )),
+        }
+    }
+}
+
+// Service checking the CAPTCHA solved before anonymous link creation
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptchaProvider {
+    HCaptcha,
+    ReCaptcha,
+}
+
+impl CaptchaProvider {
+    /// Endpoint verifying the response token a solved widget produced
+    pub fn default_verify_url(&self) -> &'static str {
+        match self {
+            CaptchaProvider::HCaptcha => "https://api.hcaptcha.com/siteverify",
+            CaptchaProvider::ReCaptcha => "https://www.google.com/recaptcha/api/siteverify",
+        }
+    }
+}
+
+impl FromStr for CaptchaProvider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hcaptcha" => Ok(CaptchaProvider::HCaptcha),
+            "recaptcha" => Ok(CaptchaProvider::ReCaptcha),
+            _ => Err(format!(
+                "Invalid CAPTCHA provider: {}. Must be one of: hcaptcha, recaptcha",
+                s
+            )),
+        }
+    }
+}
+
+// Bounds for generated code length; the column holds up to 100 characters
+const MIN_SHORT_CODE_LENGTH: usize = 4;
+const MAX_SHORT_CODE_LENGTH: usize = 32;
+
+// Characters generated short codes are drawn from
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeAlphabet {
+    Base62,
+    /// Base62 without look-alikes (0/O/o, 1/l/I/i), for codes read off print
+    Unambiguous,
+    /// Any set of at least two distinct letters, digits, hyphens or underscores
+    Custom(String),
+}
+
+impl CodeAlphabet {
+    pub fn chars(&self) -> &str {
+        match self {
+            CodeAlphabet::Base62 => BASE62_ALPHABET,
+            CodeAlphabet::Unambiguous => UNAMBIGUOUS_ALPHABET,
+            CodeAlphabet::Custom(chars) => chars,
+        }
+    }
+}
+
+impl FromStr for CodeAlphabet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "base62" => return Ok(CodeAlphabet::Base62),
+            "unambiguous" => return Ok(CodeAlphabet::Unambiguous),
+            _ => {}
+        }
+
+        // Anything else is taken as the literal set of characters
+        if let Some(c) = s
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || *c == '-' || *c == '_'))
+        {
+            return Err(format!(
+                "Invalid short code alphabet: '{}' is not allowed in short codes. \
+                 Use base62, unambiguous or a set of letters, digits, hyphens and underscores",
+                c
+            ));
+        }
+
+        let mut chars = s.chars().collect::<Vec<_>>();
+        chars.sort_unstable();
+        chars.dedup();
+        if chars.len() < 2 {
+            return Err(
+                "Invalid short code alphabet: at least 2 distinct characters are needed"
+                    .to_string(),
+            );
+        }
+
+        Ok(CodeAlphabet::Custom(chars.into_iter().collect()))
+    }
+}
+
+// Inclusive bounds on custom alias length
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+pub struct AliasLengthLimits {
+    pub min: usize,
+    pub max: usize,
+}
+
+// Short code generation and lookup
+#[derive(Debug, Deserialize, Clone)]
+pub struct ShortCodeConfig {
+    pub case_insensitive: bool,
+    pub alphabet: CodeAlphabet,
+    pub length: usize,
+    /// How codes are picked when a request doesn't say
+    pub strategy: CodeStrategy,
+    /// Custom aliases must also stick to the alphabet
+    pub strict_aliases: bool,
+    /// Custom aliases may be made of emoji
+    pub emoji_aliases: bool,
+    /// Alias limits for anonymous requests
+    pub alias_length: AliasLengthLimits,
+    /// Alias limits for requests made with an API key
+    pub user_alias_length: AliasLengthLimits,
+}
+
+impl ShortCodeConfig {
+    /// Characters to generate codes from; without uppercase when codes are case-insensitive
+    pub fn generator_chars(&self) -> String {
+        self.alphabet
+            .chars()
+            .chars()
+            .filter(|c| !(self.case_insensitive && c.is_ascii_uppercase()))
+            .collect()
+    }
+
+    /// Whether a (canonical) code only uses characters codes are generated from
+    pub fn fits_alphabet(&self, code: &str) -> bool {
+        let chars = self.generator_chars();
+        code.chars().all(|c| chars.contains(c))
+    }
+
+    /// The form a code is stored in: NFC-normalized, and lowercase when case-insensitive
+    pub fn canonicalize(&self, code: &str) -> String {
+        let code = nfc(code);
+        if self.case_insensitive {
+            code.to_lowercase()
+        } else {
+            code.into_owned()
+        }
+    }
+}
+
+impl Default for ShortCodeConfig {
+    fn default() -> Self {
+        Self {
+            case_insensitive: false,
+            alphabet: CodeAlphabet::Base62,
+            length: 6,
+            strategy: CodeStrategy::Random,
+            strict_aliases: false,
+            emoji_aliases: false,
+            alias_length: AliasLengthLimits {
+                min: 1,
+                max: MAX_ALIAS_LENGTH,
+            },
+            user_alias_length: AliasLengthLimits {
+                min: 1,
+                max: MAX_ALIAS_LENGTH,
+            },
+        }
+    }
+}
+
+// How the SMTP connection is secured
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpTls {
+    None,
+    StartTls,
+    Tls,
+}
+
+impl FromStr for SmtpTls {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(SmtpTls::None),
+            "starttls" => Ok(SmtpTls::StartTls),
+            "tls" => Ok(SmtpTls::Tls),
+            _ => Err(format!(
+                "Invalid SMTP TLS mode: {}. Must be one of: none, starttls, tls",
+                s
+            )),
+        }
+    }
+}
+
+// Visit analytics and retention configuration
+#[derive(Debug, Deserialize, Clone)]
+pub struct AnalyticsConfig {
+    pub retention_enabled: bool,
+    pub retention_days: u32,
+    pub retention_interval_seconds: u64,
+    pub retention_batch_size: i64,
+    pub archive_sink: ArchiveSinkKind,
+    pub archive_dir: String,
+    pub honor_dnt: bool,
+    pub opt_out_cookie: Option<String>,
+    /// Keep raw visit events in Postgres; when off only daily aggregates are stored there
+    pub store_raw_visits: bool,
+    pub export: VisitExportConfig,
+}
+
+// Batched export of visit events to ClickHouse or an ndjson HTTP endpoint
+#[derive(Deserialize, Clone)]
+pub struct VisitExportConfig {
+    pub sink: VisitExportKind,
+    /// ClickHouse HTTP interface, or the endpoint receiving ndjson batches
+    pub url: Option<String>,
+    pub clickhouse_table: String,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub batch_size: usize,
+    pub flush_interval_seconds: u64,
+    /// Visits held in memory while the sink is unreachable before new ones are dropped
+    pub max_pending: usize,
+}
+
+impl fmt::Debug for VisitExportConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VisitExportConfig")
+            .field("sink", &self.sink)
+            .field("url", &self.url)
+            .field("clickhouse_table", &self.clickhouse_table)
+            .field("user", &self.user)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("batch_size", &self.batch_size)
+            .field("flush_interval_seconds", &self.flush_interval_seconds)
+            .field("max_pending", &self.max_pending)
+            .finish()
+    }
+}
+
+// S3-compatible object storage connection
+#[derive(Deserialize, Clone)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    pub prefix: String,
+    pub path_style: bool,
+}
+
+// Keep credentials out of the debug-logged configuration
+impl fmt::Debug for S3Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("S3Config")
+            .field("endpoint", &self.endpoint)
+            .field("region", &self.region)
+            .field("bucket", &self.bucket)
+            .field("access_key", &self.access_key)
+            .field("secret_key", &"<redacted>")
+            .field("prefix", &self.prefix)
+            .field("path_style", &self.path_style)
+            .finish()
+    }
+}
+
+// Click anomaly detection thresholds
+#[derive(Debug, Deserialize, Clone)]
+pub struct AnomalyConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    pub window_seconds: i32,
+    pub min_clicks: i64,
+    pub max_distinct_ips: i64,
+    pub action: FlagAction,
+    pub throttle_seconds: i64,
+}
+
+// Sample data loaded at startup outside production
+#[derive(Debug, Deserialize, Clone)]
+pub struct SeedConfig {
+    pub enabled: bool,
+    pub file: String,
+}
+
+// In-memory cache of links resolved for redirects, shared by all workers
+#[derive(Debug, Deserialize, Clone)]
+pub struct LinkCacheConfig {
+    pub enabled: bool,
+    pub ttl_seconds: u64,
+    pub capacity: usize,
+    /// How many of the most accessed links to load at startup
+    pub preload: usize,
+    /// How long a code found not to exist is remembered, 0 to always ask the database
+    pub negative_ttl_seconds: u64,
+}
+
+// Tenants are kept in memory by every instance
+#[derive(Debug, Deserialize, Clone)]
+pub struct TenantConfig {
+    /// How often tenants added through other instances are picked up
+    pub sync_interval_seconds: u64,
+}
+
+// Access counts of redirects served from the cache while the database was down
+#[derive(Debug, Deserialize, Clone)]
+pub struct AccessReplayConfig {
+    pub interval_seconds: u64,
+    pub max_pending_links: usize,
+}
+
+// When to stop calling the database after repeated connection failures
+#[derive(Debug, Deserialize, Clone)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub open_seconds: u64,
+}
+
+// Click counts worth telling a link's owner about
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct MilestoneConfig {
+    /// Ascending, empty when milestones are off
+    pub thresholds: Vec<i64>,
+    pub notify_interval_seconds: u64,
+}
+
+impl MilestoneConfig {
+    /// The milestones passed when a link's count went from `previous` to `current`
+    pub fn crossed(&self, previous: i64, current: i64) -> Vec<i64> {
+        self.thresholds
+            .iter()
+            .copied()
+            .filter(|&milestone| previous < milestone && milestone <= current)
+            .collect()
+    }
+}
+
+// Stats downloaded as spreadsheets
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReportConfig {
+    /// Larger XLSX reports are refused, CSV ones stream without a limit
+    pub xlsx_max_rows: usize,
+}
+
+// Page sizes of list and search endpoints
+#[derive(Debug, Deserialize, Clone)]
+pub struct PaginationConfig {
+    /// Page size when the request doesn't set a limit
+    pub default_page_size: i64,
+    /// Larger limits are lowered to this
+    pub max_page_size: i64,
+}
+
+impl PaginationConfig {
+    /// The page size to use for a requested limit
+    pub fn page_size(&self, limit: Option<i64>) -> i64 {
+        limit
+            .unwrap_or(self.default_page_size)
+            .clamp(1, self.max_page_size)
+    }
+}
+
+impl Default for PaginationConfig {
+    fn default() -> Self {
+        Self {
+            default_page_size: 100,
+            max_page_size: 1000,
+        }
+    }
+}
+
+// Deactivation of links past their idle limit
+#[derive(Debug, Deserialize, Clone)]
+pub struct LinkCleanupConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+}
+
+// Archiving links nobody followed for a while, hiding them from listings
+#[derive(Debug, Deserialize, Clone)]
+pub struct LinkArchiveConfig {
+    pub enabled: bool,
+    /// Days without a click before a link is archived
+    pub after_days: u32,
+    pub interval_seconds: u64,
+}
+
+// How long deleted links stay in the trash before the purge job deletes them for good
+#[derive(Debug, Deserialize, Clone)]
+pub struct TrashConfig {
+    pub retention_days: u32,
+    pub purge_enabled: bool,
+    pub purge_interval_seconds: u64,
+    pub purge_batch_size: i64,
+}
+
+// Browser origins allowed to call the API
+#[derive(Debug, Deserialize, Clone)]
+pub struct CorsConfig {
+    pub allowed_origins: Vec<String>,
+}
+
+// Watching the config file for settings that can change without a restart
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConfigReloadConfig {
+    pub file: String,
+    /// How often the file is checked for changes, 0 to only reload through the admin API
+    pub interval_seconds: u64,
+}
+
+// Maintenance mode at startup, switched at runtime through the admin API
+#[derive(Debug, Deserialize, Clone)]
+pub struct MaintenanceConfig {
+    pub enabled: bool,
+    /// Told to API clients while maintenance is on
+    pub message: String,
+}
+
+// Rewriting http:// destinations to https:// for hosts that serve it
+#[derive(Debug, Deserialize, Clone)]
+pub struct HttpsUpgradeConfig {
+    /// Upgrade links that don't choose with `upgrade_https`
+    pub enabled: bool,
+    pub check_timeout_ms: u64,
+    /// How long the outcome of checking a host is reused
+    pub cache_ttl_seconds: u64,
+    /// Re-check stored http:// links in the background
+    pub job_enabled: bool,
+    pub interval_seconds: u64,
+    pub batch_size: i64,
+}
+
+// Serving file destinations of links set to proxy or preview them
+#[derive(Debug, Deserialize, Clone)]
+pub struct FileProxyConfig {
+    /// Files larger than this are redirected to instead of proxied
+    pub max_bytes: u64,
+    pub timeout_ms: u64,
+}
+
+// Languages of error messages and visitor pages
+#[derive(Debug, Deserialize, Clone)]
+pub struct I18nConfig {
+    /// Locale used when the visitor accepts none of the available ones
+    pub default_locale: String,
+    /// Directory of `<locale>.json` message files added to the bundled English ones
+    pub locales_dir: Option<String>,
+}
+
+// Serving destinations of reverse_proxy links through this service
+#[derive(Debug, Deserialize, Clone)]
+pub struct ReverseProxyConfig {
+    pub enabled: bool,
+    /// Hosts destinations may be on, each also allowing its subdomains
+    pub allowed_domains: Vec<String>,
+    /// Responses larger than this are refused, or cut off when their size isn't announced
+    pub max_bytes: u64,
+    pub timeout_ms: u64,
+}
+
+// Keeping links from pointing back at this shortener
+#[derive(Debug, Deserialize, Clone)]
+pub struct RedirectLoopConfig {
+    /// Hosts this instance serves links on: the one of PUBLIC_BASE_URL and any others
+    pub hosts: Vec<String>,
+    /// Short links followed through at most when a destination is one of ours
+    pub max_depth: usize,
+}
+
+// Periodic reachability checks of link destinations
+#[derive(Debug, Deserialize, Clone)]
+pub struct LinkRotConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    /// Links checked per run, least recently checked first
+    pub sample_size: i64,
+    pub timeout_ms: u64,
+    /// Destinations checked at the same time
+    pub concurrency: usize,
+    /// Tell owners on their channels when a link breaks
+    pub notify: bool,
+}
+
+// Tarpit and block thresholds for clients enumerating short codes
+#[derive(Debug, Deserialize, Clone)]
+pub struct ScannerGuardConfig {
+    pub enabled: bool,
+    pub window_seconds: u64,
+    pub tarpit_threshold: u32,
+    pub tarpit_base_delay_ms: u64,
+    pub tarpit_max_delay_ms: u64,
+    pub block_threshold: u32,
+    pub block_seconds: i64,
+    pub sync_interval_seconds: u64,
+    pub honeypot_paths: Vec<String>,
+}
+
+// Limit on links created without an API key, from the home page form or the API
+#[derive(Debug, Deserialize, Clone)]
+pub struct AnonymousCreateConfig {
+    /// Links one client address may create per window, 0 for no limit
+    pub rate_limit: u32,
+    pub window_seconds: u64,
+}
+
+// Identical create requests from one client answered with the link created first
+#[derive(Debug, Deserialize, Clone)]
+pub struct CreateDedupConfig {
+    /// How long a request is remembered, 0 to create a link for every request
+    pub window_seconds: u64,
+}
+
+// One-time tokens letting anonymous link creators take ownership once signed in
+#[derive(Debug, Deserialize, Clone)]
+pub struct LinkClaimConfig {
+    /// Days a claim token stays valid, 0 to hand out none
+    pub ttl_days: u32,
+}
+
+// CAPTCHA required from anonymous link creators
+#[derive(Deserialize, Clone)]
+pub struct CaptchaConfig {
+    pub enabled: bool,
+    pub provider: CaptchaProvider,
+    /// Public key the widget on the home page is rendered with
+    pub site_key: String,
+    pub secret: String,
+    pub verify_url: String,
+}
+
+impl fmt::Debug for CaptchaConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaptchaConfig")
+            .field("enabled", &self.enabled)
+            .field("provider", &self.provider)
+            .field("site_key", &self.site_key)
+            .field("secret", &"<redacted>")
+            .field("verify_url", &self.verify_url)
+            .finish()
+    }
+}
+
+// Channel receiving operator alerts
+#[derive(Deserialize, Clone)]
+pub struct AdminChannelConfig {
+    pub kind: ChannelKind,
+    pub url: String,
+}
+
+// Chat webhook URLs embed their credential
+impl fmt::Debug for AdminChannelConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdminChannelConfig")
+            .field("kind", &self.kind)
+            .field("url", &"<redacted>")
+            .finish()
+    }
+}
+
+// Outgoing mail server
+#[derive(Deserialize, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+}
+
+impl fmt::Debug for SmtpConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmtpConfig")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<redacted>"))
+            .field("from", &self.from)
+            .finish()
+    }
+}
+
+// Social login, each provider enabled once its client credentials are set
+#[derive(Debug, Deserialize, Clone)]
+pub struct OAuthConfig {
+    /// Public URL providers send users back to, under `/api/auth/{provider}/callback`
+    pub redirect_base_url: String,
+    /// How long a user has to complete a login at the provider
+    pub state_ttl_seconds: u64,
+    /// Whether the cookie tying a login to the browser is sent over TLS only
+    pub secure_cookie: bool,
+    pub github: Option<OAuthClientConfig>,
+    pub google: Option<OAuthClientConfig>,
+}
+
+// Credentials of this app at an OAuth provider
+#[derive(Deserialize, Clone)]
+pub struct OAuthClientConfig {
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+impl fmt::Debug for OAuthClientConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OAuthClientConfig")
+            .field("client_id", &self.client_id)
+            .field("client_secret", &"<redacted>")
+            .finish()
+    }
+}
+
+// Link owner notification configuration
+#[derive(Debug, Deserialize, Clone)]
+pub struct NotificationConfig {
+    pub expiry_enabled: bool,
+    pub expiry_interval_seconds: u64,
+    pub expiry_notice_days: i32,
+    pub public_base_url: String,
+    pub digest_enabled: bool,
+    pub digest_interval_seconds: u64,
+    /// How many of an owner's most clicked links a digest lists
+    pub digest_top_links: i64,
+}
+
+// Scheduled backup configuration
+#[derive(Debug, Deserialize, Clone)]
+pub struct BackupConfig {
+    pub enabled: bool,
+    pub interval_seconds: u64,
+    pub include_visit_aggregates: bool,
+}
+
+// Credentials guarding privileged endpoints
+#[derive(Deserialize, Clone)]
+pub struct AuthConfig {
+    pub admin_token: Option<String>,
+    /// Serve the admin dashboard at `/admin`, signed in with the admin token
+    pub admin_dashboard: bool,
+}
+
+impl fmt::Debug for AuthConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthConfig")
+            .field(
+                "admin_token",
+                &self.admin_token.as_ref().map(|_| "<redacted>"),
+            )
+            .field("admin_dashboard", &self.admin_dashboard)
+            .finish()
+    }
+}
+
+// Shared secrets of partners signing their API requests
+#[derive(Deserialize, Clone)]
+pub struct RequestSigningConfig {
+    pub keys: HashMap<String, String>,
+    pub tolerance_seconds: i64,
+    pub required: bool,
+}
+
+impl fmt::Debug for RequestSigningConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestSigningConfig")
+            .field("keys", &self.keys.keys().collect::<Vec<_>>())
+            .field("tolerance_seconds", &self.tolerance_seconds)
+            .field("required", &self.required)
+            .finish()
+    }
+}
+
+// Domain events (link created, clicked, expired) and where they are published
+#[derive(Debug, Deserialize, Clone)]
+pub struct EventConfig {
+    pub publisher: EventPublisherKind,
+    /// NATS server address, like `nats://localhost:4222`
+    pub nats_url: Option<String>,
+    /// Base URL of a Kafka REST proxy
+    pub kafka_rest_url: Option<String>,
+    /// NATS subject prefix, or Kafka topic
+    pub topic: String,
+    /// Events held for each subscriber before the slowest ones miss some
+    pub capacity: usize,
+    pub expiry_interval_seconds: u64,
+}
+
+// Schedules replacing the interval of background jobs, by job name
+#[derive(Debug, Deserialize, Clone)]
+pub struct JobConfig {
+    pub schedules: HashMap<String, String>,
+}
+
+// Shared client for outbound HTTP calls
+#[derive(Debug, Deserialize, Clone)]
+pub struct HttpClientConfig {
+    pub timeout_ms: u64,
+    pub connect_timeout_ms: u64,
+    pub pool_max_idle_per_host: usize,
+    pub max_retries: u32,
+    pub retry_base_delay_ms: u64,
+    pub retry_max_delay_ms: u64,
+}
+
+// How long handlers may take before requests are answered with 504, 0 disables a limit
+#[derive(Debug, Deserialize, Clone)]
+pub struct RequestTimeoutConfig {
+    pub redirect_ms: u64,
+    pub api_ms: u64,
+    pub admin_ms: u64,
+}
+
+// Deprecation of the unversioned API paths
+#[derive(Debug, Deserialize, Clone)]
+pub struct ApiVersionConfig {
+    /// HTTP date announced in the `Sunset` header of unversioned API responses
+    pub unversioned_sunset: Option<String>,
+}
+
+// Double-submit CSRF protection for cookie authenticated browser sessions
+#[derive(Debug, Deserialize, Clone)]
+pub struct CsrfConfig {
+    pub enabled: bool,
+    pub cookie_name: String,
+    pub secure_cookie: bool,
 }
 
 // Config struct that matches our environment variables
@@ -70,22 +888,85 @@ pub struct Config {
     pub server: ServerConfig,
     pub app: AppConfig,
     pub db: DatabaseConfig,
+    pub analytics: AnalyticsConfig,
+    pub s3: Option<S3Config>,
+    pub backup: BackupConfig,
+    pub auth: AuthConfig,
+    pub request_signing: RequestSigningConfig,
+    pub csrf: CsrfConfig,
+    pub api_versions: ApiVersionConfig,
+    pub request_timeouts: RequestTimeoutConfig,
+    pub http_client: HttpClientConfig,
+    pub jobs: JobConfig,
+    pub events: EventConfig,
+    pub oauth: OAuthConfig,
+    pub smtp: Option<SmtpConfig>,
+    pub notifications: NotificationConfig,
+    pub anomaly: AnomalyConfig,
+    pub link_cleanup: LinkCleanupConfig,
+    pub link_archive: LinkArchiveConfig,
+    pub trash: TrashConfig,
+    pub maintenance: MaintenanceConfig,
+    pub cors: CorsConfig,
+    pub config_reload: ConfigReloadConfig,
+    pub https_upgrade: HttpsUpgradeConfig,
+    pub link_rot: LinkRotConfig,
+    pub file_proxy: FileProxyConfig,
+    pub reverse_proxy: ReverseProxyConfig,
+    pub redirect_loops: RedirectLoopConfig,
+    pub i18n: I18nConfig,
+    pub link_cache: LinkCacheConfig,
+    pub db_circuit_breaker: CircuitBreakerConfig,
+    pub access_replay: AccessReplayConfig,
+    pub pagination: PaginationConfig,
+    pub reports: ReportConfig,
+    pub milestones: MilestoneConfig,
+    pub tenants: TenantConfig,
+    pub scanner_guard: ScannerGuardConfig,
+    pub anonymous_create: AnonymousCreateConfig,
+    pub create_dedup: CreateDedupConfig,
+    pub link_claims: LinkClaimConfig,
+    pub captcha: CaptchaConfig,
+    pub admin_channel: Option<AdminChannelConfig>,
+    pub short_codes: ShortCodeConfig,
+    pub seed: SeedConfig,
 }
 
 impl Config {
     // Load configuration from environment variables
     pub fn load() -> ConfigResult<Self> {
         // Load .env file if it exists
-        match dotenv() {
-            Ok(_) => debug!(".env file loaded successfully"),
-            Err(e) => warn!("Could not load .env file: {}", e),
-        }
+        // CONFIG_FILE names another file, looked up like .env in this directory and its parents
+        let config_file = env::var("CONFIG_FILE").unwrap_or_else(|_| ".env".to_string());
+        let loaded_file = match from_filename(&config_file) {
+            Ok(path) => {
+                debug!("{} file loaded successfully", path.display());
+                Some(path)
+            }
+            Err(e) => {
+                warn!("Could not load {} file: {}", config_file, e);
+                None
+            }
+        };
 
         // Create the server config
         let server = ServerConfig {
             host: get_env_or_default("SERVER_HOST", "127.0.0.1")?,
             port: get_env_or_default("SERVER_PORT", "8000")?,
             workers: get_env_or_default("SERVER_WORKERS", "4")?,
+            trusted_proxies: get_env_or_default::<String>("TRUSTED_PROXIES", "")?
+                .split(',')
+                .map(str::trim)
+                .filter(|proxy| !proxy.is_empty())
+                .map(|proxy| {
+                    parse_cidr(proxy).ok_or_else(|| {
+                        ConfigError::ParseError(format!(
+                            "TRUSTED_PROXIES has an invalid address or range '{}'",
+                            proxy
+                        ))
+                    })
+                })
+                .collect::<ConfigResult<Vec<_>>>()?,
         };
 
         // Get version from Cargo.toml or environment
@@ -99,7 +980,13 @@ impl Config {
             version: env::var("APP_VERSION").unwrap_or(version),
             environment: get_env_or_default("APP_ENVIRONMENT", "development")?,
             log_level: get_env_or_default("RUST_LOG", "info")?,
+            fallback_url: get_env_optional("FALLBACK_URL"),
         };
+        if let Some(url) = &app.fallback_url {
+            url::Url::parse(url).map_err(|e| {
+                ConfigError::ParseError(format!("FALLBACK_URL is not a valid URL: {}", e))
+            })?;
+        }
 
         // Database config
         let db = DatabaseConfig {
@@ -116,9 +1003,557 @@ impl Config {
                 "DATABASE_CREATE_DATABASE_IF_MISSING",
                 "true",
             )?,
+            schema: get_env_optional("DATABASE_SCHEMA"),
+        };
+        if let Some(schema) = &db.schema {
+            if !is_schema_name(schema) {
+                return Err(ConfigError::ParseError(format!(
+                    "DATABASE_SCHEMA '{}' must be up to 63 lowercase letters, digits and \
+                     underscores, not starting with a digit or 'pg_'",
+                    schema
+                )));
+            }
+        }
+
+        // Analytics config
+        let analytics = AnalyticsConfig {
+            retention_enabled: get_env_or_default("VISIT_RETENTION_ENABLED", "true")?,
+            retention_days: get_env_or_default("VISIT_RETENTION_DAYS", "90")?,
+            retention_interval_seconds: get_env_or_default(
+                "VISIT_RETENTION_INTERVAL_SECONDS",
+                "3600",
+            )?,
+            retention_batch_size: get_env_or_default("VISIT_RETENTION_BATCH_SIZE", "1000")?,
+            archive_sink: get_env_or_default("VISIT_ARCHIVE_SINK", "none")?,
+            archive_dir: get_env_or_default("VISIT_ARCHIVE_DIR", "./archive")?,
+            honor_dnt: get_env_or_default("ANALYTICS_HONOR_DNT", "true")?,
+            opt_out_cookie: get_env_optional("ANALYTICS_OPT_OUT_COOKIE"),
+            store_raw_visits: get_env_or_default("VISIT_STORE_RAW", "true")?,
+            export: VisitExportConfig {
+                sink: get_env_or_default("VISIT_EXPORT_SINK", "none")?,
+                url: get_env_optional("VISIT_EXPORT_URL"),
+                clickhouse_table: get_env_or_default(
+                    "VISIT_EXPORT_CLICKHOUSE_TABLE",
+                    "url_visits",
+                )?,
+                user: get_env_optional("VISIT_EXPORT_USER"),
+                password: get_env_optional("VISIT_EXPORT_PASSWORD"),
+                batch_size: get_env_or_default("VISIT_EXPORT_BATCH_SIZE", "1000")?,
+                flush_interval_seconds: get_env_or_default(
+                    "VISIT_EXPORT_FLUSH_INTERVAL_SECONDS",
+                    "5",
+                )?,
+                max_pending: get_env_or_default("VISIT_EXPORT_MAX_PENDING", "100000")?,
+            },
+        };
+
+        // Object storage is only configured when a bucket is given
+        let s3 = match get_env_optional("S3_BUCKET") {
+            Some(bucket) => Some(S3Config {
+                bucket,
+                endpoint: get_env_or_default("S3_ENDPOINT", "https://s3.amazonaws.com")?,
+                region: get_env_or_default("S3_REGION", "us-east-1")?,
+                access_key: get_env_or_default("S3_ACCESS_KEY", "")?,
+                secret_key: get_env_or_default("S3_SECRET_KEY", "")?,
+                prefix: get_env_or_default("S3_PREFIX", "")?,
+                path_style: get_env_or_default("S3_PATH_STYLE", "true")?,
+            }),
+            None => None,
+        };
+
+        // Backup config
+        let backup = BackupConfig {
+            enabled: get_env_or_default("BACKUP_ENABLED", "false")?,
+            interval_seconds: get_env_or_default("BACKUP_INTERVAL_SECONDS", "86400")?,
+            include_visit_aggregates: get_env_or_default(
+                "BACKUP_INCLUDE_VISIT_AGGREGATES",
+                "true",
+            )?,
+        };
+
+        // Auth config
+        let auth = AuthConfig {
+            admin_token: get_env_optional("ADMIN_API_TOKEN"),
+            admin_dashboard: get_env_or_default("ADMIN_DASHBOARD_ENABLED", "false")?,
+        };
+
+        // Request signing config, keys given as `key_id:secret` pairs
+        let request_signing = RequestSigningConfig {
+            keys: get_env_optional("REQUEST_SIGNING_KEYS")
+                .map(|keys| parse_signing_keys(&keys))
+                .transpose()?
+                .unwrap_or_default(),
+            tolerance_seconds: get_env_or_default("REQUEST_SIGNING_TOLERANCE_SECONDS", "300")?,
+            required: get_env_or_default("REQUEST_SIGNING_REQUIRED", "false")?,
+        };
+
+        let http_client = HttpClientConfig {
+            timeout_ms: get_env_or_default("HTTP_CLIENT_TIMEOUT_MS", "10000")?,
+            connect_timeout_ms: get_env_or_default("HTTP_CLIENT_CONNECT_TIMEOUT_MS", "3000")?,
+            pool_max_idle_per_host: get_env_or_default("HTTP_CLIENT_POOL_MAX_IDLE_PER_HOST", "16")?,
+            max_retries: get_env_or_default("HTTP_CLIENT_MAX_RETRIES", "2")?,
+            retry_base_delay_ms: get_env_or_default("HTTP_CLIENT_RETRY_BASE_DELAY_MS", "200")?,
+            retry_max_delay_ms: get_env_or_default("HTTP_CLIENT_RETRY_MAX_DELAY_MS", "5000")?,
+        };
+
+        let request_timeouts = RequestTimeoutConfig {
+            redirect_ms: get_env_or_default("REDIRECT_TIMEOUT_MS", "3000")?,
+            api_ms: get_env_or_default("API_TIMEOUT_MS", "30000")?,
+            admin_ms: get_env_or_default("ADMIN_TIMEOUT_MS", "600000")?,
+        };
+
+        let api_versions = ApiVersionConfig {
+            unversioned_sunset: get_env_optional("API_UNVERSIONED_SUNSET"),
+        };
+
+        // CSRF config, cookies are only marked secure in production where TLS is expected
+        let csrf = CsrfConfig {
+            enabled: get_env_or_default("CSRF_PROTECTION_ENABLED", "false")?,
+            cookie_name: get_env_or_default("CSRF_COOKIE_NAME", "csrf_token")?,
+            secure_cookie: app.environment == Environment::Production,
+        };
+
+        // Mail is only configured when a server is given
+        let smtp = match get_env_optional("SMTP_HOST") {
+            Some(host) => Some(SmtpConfig {
+                host,
+                port: get_env_or_default("SMTP_PORT", "587")?,
+                tls: get_env_or_default("SMTP_TLS", "starttls")?,
+                username: get_env_optional("SMTP_USERNAME"),
+                password: get_env_optional("SMTP_PASSWORD"),
+                from: get_env_or_default("SMTP_FROM", "URL Shortener <no-reply@localhost>")?,
+            }),
+            None => None,
+        };
+
+        // Notification config
+        let notifications = NotificationConfig {
+            expiry_enabled: get_env_or_default("EXPIRY_NOTIFICATIONS_ENABLED", "false")?,
+            expiry_interval_seconds: get_env_or_default(
+                "EXPIRY_NOTIFICATION_INTERVAL_SECONDS",
+                "3600",
+            )?,
+            expiry_notice_days: get_env_or_default("EXPIRY_NOTICE_DAYS", "3")?,
+            public_base_url: get_env_or_default("PUBLIC_BASE_URL", "http://127.0.0.1:8000")?,
+            digest_enabled: get_env_or_default("DIGEST_ENABLED", "false")?,
+            digest_interval_seconds: get_env_or_default("DIGEST_INTERVAL_SECONDS", "3600")?,
+            digest_top_links: get_env_or_default("DIGEST_TOP_LINKS", "5")?,
+        };
+        if notifications.digest_top_links < 1 {
+            return Err(ConfigError::ParseError(
+                "DIGEST_TOP_LINKS must be at least 1".to_string(),
+            ));
+        }
+
+        // Social login config
+        let oauth_client = |prefix: &str| match (
+            get_env_optional(&format!("{}_CLIENT_ID", prefix)),
+            get_env_optional(&format!("{}_CLIENT_SECRET", prefix)),
+        ) {
+            (Some(client_id), Some(client_secret)) => Some(OAuthClientConfig {
+                client_id,
+                client_secret,
+            }),
+            _ => None,
+        };
+        let oauth = OAuthConfig {
+            redirect_base_url: get_env_optional("OAUTH_REDIRECT_BASE_URL")
+                .unwrap_or_else(|| notifications.public_base_url.clone()),
+            state_ttl_seconds: get_env_or_default("OAUTH_STATE_TTL_SECONDS", "600")?,
+            secure_cookie: app.environment == Environment::Production,
+            github: oauth_client("OAUTH_GITHUB"),
+            google: oauth_client("OAUTH_GOOGLE"),
+        };
+
+        // Anomaly detection config
+        let anomaly = AnomalyConfig {
+            enabled: get_env_or_default("ANOMALY_DETECTION_ENABLED", "false")?,
+            interval_seconds: get_env_or_default("ANOMALY_CHECK_INTERVAL_SECONDS", "60")?,
+            window_seconds: get_env_or_default("ANOMALY_WINDOW_SECONDS", "60")?,
+            min_clicks: get_env_or_default("ANOMALY_MIN_CLICKS", "100")?,
+            max_distinct_ips: get_env_or_default("ANOMALY_MAX_DISTINCT_IPS", "3")?,
+            action: get_env_or_default("ANOMALY_ACTION", "notify")?,
+            throttle_seconds: get_env_or_default("ANOMALY_THROTTLE_SECONDS", "3600")?,
+        };
+
+        let seed = SeedConfig {
+            enabled: get_env_or_default("SEED_DATA", "false")?,
+            file: get_env_or_default("SEED_FILE", "seeds/dev.json")?,
+        };
+
+        let link_cleanup = LinkCleanupConfig {
+            enabled: get_env_or_default("LINK_CLEANUP_ENABLED", "true")?,
+            interval_seconds: get_env_or_default("LINK_CLEANUP_INTERVAL_SECONDS", "3600")?,
+        };
+
+        let link_archive = LinkArchiveConfig {
+            enabled: get_env_or_default("LINK_ARCHIVE_ENABLED", "false")?,
+            after_days: get_env_or_default("LINK_ARCHIVE_AFTER_DAYS", "180")?,
+            interval_seconds: get_env_or_default("LINK_ARCHIVE_INTERVAL_SECONDS", "3600")?,
+        };
+        if link_archive.after_days < 1 {
+            return Err(ConfigError::ParseError(
+                "LINK_ARCHIVE_AFTER_DAYS must be at least 1".to_string(),
+            ));
+        }
+
+        let trash = TrashConfig {
+            retention_days: get_env_or_default("TRASH_RETENTION_DAYS", "30")?,
+            purge_enabled: get_env_or_default("TRASH_PURGE_ENABLED", "true")?,
+            purge_interval_seconds: get_env_or_default("TRASH_PURGE_INTERVAL_SECONDS", "3600")?,
+            purge_batch_size: get_env_or_default("TRASH_PURGE_BATCH_SIZE", "500")?,
+        };
+        if trash.purge_batch_size < 1 {
+            return Err(ConfigError::ParseError(
+                "TRASH_PURGE_BATCH_SIZE must be at least 1".to_string(),
+            ));
+        }
+
+        let maintenance = MaintenanceConfig {
+            enabled: get_env_or_default("MAINTENANCE_MODE", "false")?,
+            message: get_env_or_default(
+                "MAINTENANCE_MESSAGE",
+                "The service is undergoing maintenance, please try again shortly",
+            )?,
+        };
+        if maintenance.message.trim().is_empty() {
+            return Err(ConfigError::ParseError(
+                "MAINTENANCE_MESSAGE must not be empty".to_string(),
+            ));
+        }
+
+        let cors = CorsConfig {
+            allowed_origins: get_env_or_default::<String>(
+                "CORS_ALLOWED_ORIGINS",
+                "http://localhost:3000,http://127.0.0.1:3000",
+            )?
+            .split(',')
+            .map(|origin| origin.trim().trim_end_matches('/').to_string())
+            .filter(|origin| !origin.is_empty())
+            .collect(),
+        };
+        if let Some(origin) = cors.allowed_origins.iter().find(|o| Url::parse(o).is_err()) {
+            return Err(ConfigError::ParseError(format!(
+                "CORS_ALLOWED_ORIGINS has an invalid origin '{}'",
+                origin
+            )));
+        }
+
+        let config_reload = ConfigReloadConfig {
+            file: loaded_file.map_or(config_file, |path| path.display().to_string()),
+            interval_seconds: get_env_or_default("CONFIG_RELOAD_INTERVAL_SECONDS", "30")?,
+        };
+
+        let https_upgrade = HttpsUpgradeConfig {
+            enabled: get_env_or_default("HTTPS_UPGRADE_ENABLED", "false")?,
+            check_timeout_ms: get_env_or_default("HTTPS_UPGRADE_CHECK_TIMEOUT_MS", "3000")?,
+            cache_ttl_seconds: get_env_or_default("HTTPS_UPGRADE_CACHE_TTL_SECONDS", "86400")?,
+            job_enabled: get_env_or_default("HTTPS_UPGRADE_JOB_ENABLED", "false")?,
+            interval_seconds: get_env_or_default("HTTPS_UPGRADE_INTERVAL_SECONDS", "86400")?,
+            batch_size: get_env_or_default("HTTPS_UPGRADE_BATCH_SIZE", "500")?,
+        };
+        if https_upgrade.batch_size < 1 {
+            return Err(ConfigError::ParseError(
+                "HTTPS_UPGRADE_BATCH_SIZE must be at least 1".to_string(),
+            ));
+        }
+
+        let link_rot = LinkRotConfig {
+            enabled: get_env_or_default("LINK_ROT_CHECK_ENABLED", "false")?,
+            interval_seconds: get_env_or_default("LINK_ROT_INTERVAL_SECONDS", "3600")?,
+            sample_size: get_env_or_default("LINK_ROT_SAMPLE_SIZE", "100")?,
+            timeout_ms: get_env_or_default("LINK_ROT_TIMEOUT_MS", "5000")?,
+            concurrency: get_env_or_default("LINK_ROT_CONCURRENCY", "8")?,
+            notify: get_env_or_default("LINK_ROT_NOTIFY", "false")?,
+        };
+        if link_rot.sample_size < 1 || link_rot.concurrency < 1 {
+            return Err(ConfigError::ParseError(
+                "LINK_ROT_SAMPLE_SIZE and LINK_ROT_CONCURRENCY must be at least 1".to_string(),
+            ));
+        }
+
+        let file_proxy = FileProxyConfig {
+            max_bytes: get_env_or_default("FILE_PROXY_MAX_BYTES", "10485760")?,
+            timeout_ms: get_env_or_default("FILE_PROXY_TIMEOUT_MS", "10000")?,
+        };
+
+        let reverse_proxy = ReverseProxyConfig {
+            enabled: get_env_or_default("REVERSE_PROXY_ENABLED", "false")?,
+            allowed_domains: get_env_or_default::<String>("REVERSE_PROXY_ALLOWED_DOMAINS", "")?
+                .split(',')
+                .map(|d| d.trim().trim_start_matches('.').to_ascii_lowercase())
+                .filter(|d| !d.is_empty())
+                .collect(),
+            max_bytes: get_env_or_default("REVERSE_PROXY_MAX_BYTES", "52428800")?,
+            timeout_ms: get_env_or_default("REVERSE_PROXY_TIMEOUT_MS", "15000")?,
+        };
+        if reverse_proxy.enabled && reverse_proxy.allowed_domains.is_empty() {
+            return Err(ConfigError::ParseError(
+                "REVERSE_PROXY_ENABLED requires REVERSE_PROXY_ALLOWED_DOMAINS".to_string(),
+            ));
+        }
+
+        let mut loop_hosts = get_env_or_default::<String>("SHORTENER_HOSTS", "")?
+            .split(',')
+            .map(|h| h.trim().to_ascii_lowercase())
+            .filter(|h| !h.is_empty())
+            .collect::<Vec<_>>();
+        let public_host = Url::parse(&notifications.public_base_url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_ascii_lowercase));
+        loop_hosts.extend(public_host);
+        let redirect_loops = RedirectLoopConfig {
+            hosts: loop_hosts,
+            max_depth: get_env_or_default("REDIRECT_MAX_DEPTH", "5")?,
+        };
+        if redirect_loops.max_depth < 1 {
+            return Err(ConfigError::ParseError(
+                "REDIRECT_MAX_DEPTH must be at least 1".to_string(),
+            ));
+        }
+
+        let i18n = I18nConfig {
+            default_locale: get_env_or_default("I18N_DEFAULT_LOCALE", "en")?,
+            locales_dir: get_env_optional("I18N_LOCALES_DIR"),
+        };
+
+        let link_cache = LinkCacheConfig {
+            enabled: get_env_or_default("LINK_CACHE_ENABLED", "true")?,
+            ttl_seconds: get_env_or_default("LINK_CACHE_TTL_SECONDS", "60")?,
+            capacity: get_env_or_default("LINK_CACHE_CAPACITY", "10000")?,
+            preload: get_env_or_default("LINK_CACHE_PRELOAD", "1000")?,
+            negative_ttl_seconds: get_env_or_default("LINK_CACHE_NEGATIVE_TTL_SECONDS", "10")?,
+        };
+
+        let db_circuit_breaker = CircuitBreakerConfig {
+            failure_threshold: get_env_or_default("DB_CIRCUIT_BREAKER_FAILURES", "5")?,
+            open_seconds: get_env_or_default("DB_CIRCUIT_BREAKER_OPEN_SECONDS", "30")?,
+        };
+
+        let access_replay = AccessReplayConfig {
+            interval_seconds: get_env_or_default("ACCESS_REPLAY_INTERVAL_SECONDS", "15")?,
+            max_pending_links: get_env_or_default("ACCESS_BACKLOG_MAX_LINKS", "100000")?,
         };
 
-        let config = Config { db, app, server };
+        let pagination = PaginationConfig {
+            default_page_size: get_env_or_default("DEFAULT_PAGE_SIZE", "100")?,
+            max_page_size: get_env_or_default("MAX_PAGE_SIZE", "1000")?,
+        };
+        if pagination.default_page_size < 1
+            || pagination.default_page_size > pagination.max_page_size
+        {
+            return Err(ConfigError::ParseError(
+                "DEFAULT_PAGE_SIZE and MAX_PAGE_SIZE must satisfy 1 <= default <= max".to_string(),
+            ));
+        }
+
+        let reports = ReportConfig {
+            xlsx_max_rows: get_env_or_default("REPORT_XLSX_MAX_ROWS", "100000")?,
+        };
+        // A worksheet holds 1,048,576 rows, the header included
+        if reports.xlsx_max_rows >= 1_048_576 {
+            return Err(ConfigError::ParseError(
+                "REPORT_XLSX_MAX_ROWS must be below 1048576".to_string(),
+            ));
+        }
+
+        let mut thresholds = get_env_or_default::<String>("CLICK_MILESTONES", "100,1000,10000")?
+            .split(',')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .map(|m| match m.parse::<i64>() {
+                Ok(milestone) if milestone > 0 => Ok(milestone),
+                _ => Err(ConfigError::ParseError(format!(
+                    "CLICK_MILESTONES must be positive whole numbers, got '{}'",
+                    m
+                ))),
+            })
+            .collect::<ConfigResult<Vec<_>>>()?;
+        thresholds.sort_unstable();
+        thresholds.dedup();
+        let milestones = MilestoneConfig {
+            thresholds,
+            notify_interval_seconds: get_env_or_default("MILESTONE_NOTIFY_INTERVAL_SECONDS", "30")?,
+        };
+
+        let tenants = TenantConfig {
+            sync_interval_seconds: get_env_or_default("TENANT_SYNC_INTERVAL_SECONDS", "60")?,
+        };
+
+        // Anonymous creation limits
+        let anonymous_create = AnonymousCreateConfig {
+            rate_limit: get_env_or_default("ANONYMOUS_CREATE_RATE_LIMIT", "10")?,
+            window_seconds: get_env_or_default("ANONYMOUS_CREATE_WINDOW_SECONDS", "60")?,
+        };
+
+        // Double-submit protection
+        let create_dedup = CreateDedupConfig {
+            window_seconds: get_env_or_default("CREATE_DEDUP_WINDOW_SECONDS", "10")?,
+        };
+
+        let link_claims = LinkClaimConfig {
+            ttl_days: get_env_or_default("LINK_CLAIM_TTL_DAYS", "30")?,
+        };
+
+        // CAPTCHA config, the keys are only required once it's enabled
+        let captcha_provider: CaptchaProvider = get_env_or_default("CAPTCHA_PROVIDER", "hcaptcha")?;
+        let captcha = CaptchaConfig {
+            enabled: get_env_or_default("CAPTCHA_ENABLED", "false")?,
+            site_key: get_env_optional("CAPTCHA_SITE_KEY").unwrap_or_default(),
+            secret: get_env_optional("CAPTCHA_SECRET").unwrap_or_default(),
+            verify_url: get_env_optional("CAPTCHA_VERIFY_URL")
+                .unwrap_or_else(|| captcha_provider.default_verify_url().to_string()),
+            provider: captcha_provider,
+        };
+        if captcha.enabled && (captcha.site_key.is_empty() || captcha.secret.is_empty()) {
+            return Err(ConfigError::ParseError(
+                "CAPTCHA_ENABLED requires CAPTCHA_SITE_KEY and CAPTCHA_SECRET".to_string(),
+            ));
+        }
+
+        // Scanner guard config
+        let scanner_guard = ScannerGuardConfig {
+            enabled: get_env_or_default("SCANNER_GUARD_ENABLED", "false")?,
+            window_seconds: get_env_or_default("SCANNER_WINDOW_SECONDS", "60")?,
+            tarpit_threshold: get_env_or_default("SCANNER_TARPIT_THRESHOLD", "10")?,
+            tarpit_base_delay_ms: get_env_or_default("SCANNER_TARPIT_BASE_DELAY_MS", "250")?,
+            tarpit_max_delay_ms: get_env_or_default("SCANNER_TARPIT_MAX_DELAY_MS", "10000")?,
+            block_threshold: get_env_or_default("SCANNER_BLOCK_THRESHOLD", "50")?,
+            block_seconds: get_env_or_default("SCANNER_BLOCK_SECONDS", "3600")?,
+            sync_interval_seconds: get_env_or_default("SCANNER_SYNC_INTERVAL_SECONDS", "30")?,
+            honeypot_paths: get_env_or_default::<String>(
+                "SCANNER_HONEYPOT_PATHS",
+                "/.env,/.git/config,/wp-login.php,/phpmyadmin",
+            )?
+            .split(',')
+            .map(|p| p.trim().to_string())
+            .filter(|p| !p.is_empty())
+            .collect(),
+        };
+
+        // Short code config
+        let alias_length = AliasLengthLimits {
+            min: get_env_or_default("CUSTOM_ALIAS_MIN_LENGTH", "1")?,
+            max: get_env_or_default("CUSTOM_ALIAS_MAX_LENGTH", &MAX_ALIAS_LENGTH.to_string())?,
+        };
+        // API key holders fall back to the general limits
+        let user_alias_length = AliasLengthLimits {
+            min: get_env_or_default(
+                "USER_CUSTOM_ALIAS_MIN_LENGTH",
+                &alias_length.min.to_string(),
+            )?,
+            max: get_env_or_default(
+                "USER_CUSTOM_ALIAS_MAX_LENGTH",
+                &alias_length.max.to_string(),
+            )?,
+        };
+        for (prefix, limits) in [("", &alias_length), ("USER_", &user_alias_length)] {
+            if limits.min < 1 || limits.min > limits.max || limits.max > MAX_ALIAS_LENGTH {
+                return Err(ConfigError::ParseError(format!(
+                    "{prefix}CUSTOM_ALIAS_MIN_LENGTH and {prefix}CUSTOM_ALIAS_MAX_LENGTH must satisfy \
+                     1 <= min <= max <= {}",
+                    MAX_ALIAS_LENGTH
+                )));
+            }
+        }
+
+        let short_codes = ShortCodeConfig {
+            case_insensitive: get_env_or_default("SHORT_CODE_CASE_INSENSITIVE", "false")?,
+            alphabet: get_env_or_default("SHORT_CODE_ALPHABET", "base62")?,
+            length: get_env_or_default("SHORT_CODE_LENGTH", "6")?,
+            strategy: get_env_or_default("SHORT_CODE_STRATEGY", "random")?,
+            strict_aliases: get_env_or_default("SHORT_CODE_STRICT_ALIASES", "false")?,
+            emoji_aliases: get_env_or_default("SHORT_CODE_EMOJI_ALIASES", "false")?,
+            alias_length,
+            user_alias_length,
+        };
+        if !(MIN_SHORT_CODE_LENGTH..=MAX_SHORT_CODE_LENGTH).contains(&short_codes.length) {
+            return Err(ConfigError::ParseError(format!(
+                "SHORT_CODE_LENGTH must be between {} and {}",
+                MIN_SHORT_CODE_LENGTH, MAX_SHORT_CODE_LENGTH
+            )));
+        }
+        if short_codes.generator_chars().chars().count() < 2 {
+            return Err(ConfigError::ParseError(
+                "SHORT_CODE_ALPHABET needs 2 or more lowercase characters for case-insensitive codes"
+                    .to_string(),
+            ));
+        }
+
+        // Operator alerts are only sent when a channel URL is given
+        let admin_channel = match get_env_optional("ADMIN_NOTIFY_URL") {
+            Some(url) => Some(AdminChannelConfig {
+                url,
+                kind: get_env_or_default("ADMIN_NOTIFY_KIND", "webhook")?,
+            }),
+            None => None,
+        };
+
+        // Events config
+        let events = EventConfig {
+            publisher: get_env_or_default("EVENT_PUBLISHER", "none")?,
+            nats_url: get_env_optional("EVENT_NATS_URL"),
+            kafka_rest_url: get_env_optional("EVENT_KAFKA_REST_URL"),
+            topic: get_env_or_default("EVENT_TOPIC", "url_shortener.events")?,
+            capacity: get_env_or_default("EVENT_BUS_CAPACITY", "1024")?,
+            expiry_interval_seconds: get_env_or_default("EVENT_EXPIRY_INTERVAL_SECONDS", "60")?,
+        };
+
+        // Job schedules config, given as `name=expression` pairs separated by semicolons
+        let jobs = JobConfig {
+            schedules: get_env_optional("JOB_SCHEDULES")
+                .map(|value| parse_job_schedules(&value))
+                .transpose()?
+                .unwrap_or_default(),
+        };
+
+        let config = Config {
+            db,
+            app,
+            server,
+            analytics,
+            s3,
+            backup,
+            auth,
+            request_signing,
+            csrf,
+            api_versions,
+            request_timeouts,
+            http_client,
+            jobs,
+            events,
+            oauth,
+            smtp,
+            notifications,
+            anomaly,
+            link_cleanup,
+            link_archive,
+            trash,
+            maintenance,
+            cors,
+            config_reload,
+            https_upgrade,
+            link_rot,
+            file_proxy,
+            reverse_proxy,
+            redirect_loops,
+            i18n,
+            link_cache,
+            db_circuit_breaker,
+            access_replay,
+            pagination,
+            reports,
+            milestones,
+            tenants,
+            scanner_guard,
+            anonymous_create,
+            create_dedup,
+            link_claims,
+            captcha,
+            admin_channel,
+            short_codes,
+            seed,
+        };
         info!("Configuration loaded successfully");
         debug!("Loaded config: {:?}", config);
 
@@ -145,6 +1580,55 @@ where
     }
 }
 
+/// Helper function to parse comma separated `key_id:secret` pairs
+fn parse_signing_keys(value: &str) -> ConfigResult<HashMap<String, String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once(':') {
+            Some((id, secret)) if !id.trim().is_empty() && !secret.trim().is_empty() => {
+                Ok((id.trim().to_string(), secret.trim().to_string()))
+            }
+            _ => Err(ConfigError::ParseError(
+                "REQUEST_SIGNING_KEYS entries must look like key_id:secret".to_string(),
+            )),
+        })
+        .collect()
+}
+
+/// Helper function to parse semicolon separated `name=expression` pairs
+fn parse_job_schedules(value: &str) -> ConfigResult<HashMap<String, String>> {
+    value
+        .split(';')
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((name, expression)) if !name.trim().is_empty() => {
+                Ok((name.trim().to_string(), expression.trim().to_string()))
+            }
+            _ => Err(ConfigError::ParseError(
+                "JOB_SCHEDULES entries must look like name=expression".to_string(),
+            )),
+        })
+        .collect()
+}
+
+/// Helper function to get an optional env variable, treating empty values as unset
+fn get_env_optional(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|v| !v.trim().is_empty())
+}
+
+// Schema names are put in statements and the search path as they are, so only plain ones
+fn is_schema_name(name: &str) -> bool {
+    name.len() <= 63
+        && !name.starts_with("pg_")
+        && name.starts_with(|c: char| c.is_ascii_lowercase() || c == '_')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
 
 // pub struct CorsConfig {
 //     pub allowed_origins: Vec<String>,
@@ -222,4 +1706,57 @@ where
 //                 .max_age(3600)
 //         }
 //     }
-// }
\ No newline at end of file
+// }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_code_alphabet() {
+        assert_eq!("Unambiguous".parse(), Ok(CodeAlphabet::Unambiguous));
+        assert_eq!(
+            "cabba-".parse(),
+            Ok(CodeAlphabet::Custom("-abc".to_string()))
+        );
+        assert!("abc/".parse::<CodeAlphabet>().is_err());
+        assert!("aaa".parse::<CodeAlphabet>().is_err());
+
+        let codes = ShortCodeConfig {
+            case_insensitive: true,
+            alphabet: CodeAlphabet::Custom("AB12cd".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(codes.generator_chars(), "12cd");
+        assert!(codes.fits_alphabet("c1d2"));
+        assert!(!codes.fits_alphabet("ab12"));
+    }
+    #[test]
+    fn test_is_schema_name() {
+        assert!(is_schema_name("staging"));
+        assert!(is_schema_name("_preview_42"));
+        assert!(!is_schema_name("Staging"));
+        assert!(!is_schema_name("1st"));
+        assert!(!is_schema_name("pg_catalog"));
+        assert!(!is_schema_name("staging; DROP TABLE users"));
+        assert!(!is_schema_name(&"a".repeat(64)));
+    }
+    #[test]
+    fn test_milestones_crossed() {
+        let milestones = MilestoneConfig {
+            thresholds: vec![100, 1000, 10000],
+            ..Default::default()
+        };
+        assert_eq!(milestones.crossed(99, 100), vec![100]);
+        assert!(milestones.crossed(100, 101).is_empty());
+        assert_eq!(milestones.crossed(90, 1500), vec![100, 1000]);
+    }
+
+    #[test]
+    fn test_page_size() {
+        let pages = PaginationConfig::default();
+        assert_eq!(pages.page_size(None), 100);
+        assert_eq!(pages.page_size(Some(20)), 20);
+        assert_eq!(pages.page_size(Some(5000)), 1000);
+    }
+}
@@ -0,0 +1,56 @@
+// src/dashboard.rs - Admin dashboard files, embedded in the binary
+use crate::utils::template::render;
+
+const INDEX_HTML: &str = include_str!("../static/admin/index.html");
+
+/// A script or stylesheet of the dashboard
+pub struct Asset {
+    pub content_type: &'static str,
+    pub body: &'static str,
+}
+
+const ASSETS: &[(&str, Asset)] = &[
+    (
+        "app.js",
+        Asset {
+            content_type: "text/javascript; charset=utf-8",
+            body: include_str!("../static/admin/app.js"),
+        },
+    ),
+    (
+        "style.css",
+        Asset {
+            content_type: "text/css; charset=utf-8",
+            body: include_str!("../static/admin/style.css"),
+        },
+    ),
+];
+
+/// Render the dashboard page, telling its script which cookie holds the CSRF token
+pub fn index_page(csrf_cookie: &str) -> String {
+    render(INDEX_HTML, &[("csrf_cookie", csrf_cookie)], true)
+}
+
+/// Look up a dashboard asset by file name
+pub fn asset(name: &str) -> Option<&'static Asset> {
+    ASSETS
+        .iter()
+        .find(|(file, _)| *file == name)
+        .map(|(_, asset)| asset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_references_embedded_assets() {
+        let page = index_page("csrf_token");
+        assert!(page.contains("<meta name=\"csrf-cookie\" content=\"csrf_token\">"));
+        for (file, _) in ASSETS {
+            assert!(page.contains(&format!("/admin/{}", file)));
+        }
+        assert!(asset("app.js").is_some());
+        assert!(asset("../Cargo.toml").is_none());
+    }
+}
@@ -1,16 +1,18 @@
 use std::time::Duration;
 
-use log::{debug, error, info, warn};
+use log::{debug, info, warn};
 use serde::{Deserialize, Serialize};
-use sqlx::migrate::MigrateDatabase;
+use sqlx::migrate::{MigrateDatabase, Migrator};
 use sqlx::{
-    postgres::{PgPool, PgPoolOptions},
+    postgres::{PgConnectOptions, PgPool, PgPoolOptions},
     Postgres,
 };
 use thiserror::Error;
 
 use crate::config::DatabaseConfig;
 
+pub mod online;
+
 #[derive(Debug, Error)]
 pub enum DatabaseError {
     #[error("Database connection error: {0}")]
@@ -28,10 +30,14 @@ pub enum DatabaseError {
 
 pub type DbResult<T> = Result<T, DatabaseError>;
 
+// Migrations embedded in the binary at build time
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
 /// Represents an established database connection pool
 #[derive(Clone)]
 pub struct Database {
     pool: PgPool,
+    schema: Option<String>,
 }
 
 /// Database health status
@@ -59,6 +65,16 @@ pub struct DatabaseHealth {
     pub db_info: Option<DbInfo>,
 }
 
+/// Connections of the pool, as reported by the admin system info
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PoolStats {
+    /// Connections open, idle or in use
+    pub size: u32,
+    pub idle: usize,
+    pub min_connections: u32,
+    pub max_connections: u32,
+}
+
 impl Database {
     /// Create a new database connection pool from configuration
     pub async fn connect(config: &DatabaseConfig) -> DbResult<Self> {
@@ -73,12 +89,19 @@ impl Database {
             Self::ensure_database_exists(config).await?;
         }
 
+        // Every connection looks up tables in the configured schema first. Extensions stay
+        // visible in public, where the migrations install them
+        let mut options: PgConnectOptions = config.url.parse()?;
+        if let Some(schema) = &config.schema {
+            options = options.options([("search_path", format!("{},public", schema))]);
+        }
+
         // Create the connection pool
         let pool = PgPoolOptions::new()
             .max_connections(config.max_connections)
             .min_connections(config.min_connections)
             .acquire_timeout(Duration::from_secs(config.connect_timeout_seconds))
-            .connect(&config.url)
+            .connect_with(options)
             .await
             .map_err(|e| {
                 warn!("Failed to connect to database: {}", e);
@@ -87,12 +110,25 @@ impl Database {
 
         info!("Successfully connected to database");
 
+        if let Some(schema) = &config.schema {
+            Self::ensure_schema_exists(&pool, schema, config.create_database_if_missing).await?;
+        }
+
         // Run migrations if enabled
         if config.use_migrations {
             Self::run_migrations(&pool).await?;
         }
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool,
+            schema: config.schema.clone(),
+        })
+    }
+
+    /// Wraps a pool that is already connected and migrated, like the ones `#[sqlx::test]` gives
+    #[cfg(test)]
+    pub fn from_pool(pool: PgPool) -> Self {
+        Self { pool, schema: None }
     }
 
     /// Get a reference to the connection pool
@@ -100,6 +136,22 @@ impl Database {
         &self.pool
     }
 
+    /// The schema holding the tables, `None` for `public`
+    pub fn schema(&self) -> Option<&str> {
+        self.schema.as_deref()
+    }
+
+    /// Connections currently open, and the pool's bounds
+    pub fn pool_stats(&self) -> PoolStats {
+        let options = self.pool.options();
+        PoolStats {
+            size: self.pool.size(),
+            idle: self.pool.num_idle(),
+            min_connections: options.get_min_connections(),
+            max_connections: options.get_max_connections(),
+        }
+    }
+
     /// Check if the database connection is healthy
     pub async fn health_check(&self) -> DbResult<DatabaseHealth> {
         // Measure query execution time
@@ -115,16 +167,13 @@ impl Database {
         match result {
             Ok(_) => {
                 // Optionally get additional database information
-                let db_info = match sqlx::query_as!(
+                let db_info = sqlx::query_as!(
                     DbInfo,
                     "SELECT current_database() as name, version() as version",
                 )
                 .fetch_one(self.get_pool())
                 .await
-                {
-                    Ok(info) => Some(info),
-                    Err(_) => None,
-                };
+                .ok();
 
                 Ok(DatabaseHealth {
                     status: DBHealthStatus::Healthy,
@@ -211,23 +260,102 @@ impl Database {
         Ok(())
     }
 
+    /// Ensure the configured schema exists, create it if allowed, and check connections use it
+    async fn ensure_schema_exists(pool: &PgPool, schema: &str, create: bool) -> DbResult<()> {
+        let exists = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM pg_namespace WHERE nspname = $1) as "exists!""#,
+            schema
+        )
+        .fetch_one(pool)
+        .await?;
+
+        if !exists {
+            if !create {
+                return Err(DatabaseError::DatabaseNotFound(format!(
+                    "Schema '{}' does not exist",
+                    schema
+                )));
+            }
+
+            // The name was checked to be a plain identifier when the configuration was loaded
+            info!("Schema '{}' does not exist, creating it", schema);
+            sqlx::query(&format!("CREATE SCHEMA IF NOT EXISTS {}", schema))
+                .execute(pool)
+                .await?;
+
+            // Migrations would otherwise install extensions in the first schema of the search
+            // path, out of sight of the other schemas sharing the database
+            for extension in ["\"uuid-ossp\"", "pg_trgm"] {
+                sqlx::query(&format!(
+                    "CREATE EXTENSION IF NOT EXISTS {} WITH SCHEMA public",
+                    extension
+                ))
+                .execute(pool)
+                .await?;
+            }
+        }
+
+        let current = sqlx::query_scalar!("SELECT current_schema()")
+            .fetch_one(pool)
+            .await?;
+        if current.as_deref() != Some(schema) {
+            return Err(DatabaseError::DatabaseNotFound(format!(
+                "Connections use schema '{}' instead of '{}'",
+                current.unwrap_or_default(),
+                schema
+            )));
+        }
+
+        debug!("Using schema '{}'", schema);
+        Ok(())
+    }
+
+    /// Apply the migrations the database is missing, whether or not `use_migrations` is set
+    pub async fn migrate(&self) -> DbResult<()> {
+        Self::run_migrations(&self.pool).await
+    }
+
+    /// Versions of the embedded migrations the database hasn't applied yet
+    pub async fn pending_migrations(&self) -> DbResult<Vec<i64>> {
+        // A database never migrated has no history table yet
+        let migrated: bool =
+            sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                .fetch_one(&self.pool)
+                .await?;
+        let applied: Vec<i64> = if migrated {
+            sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+                .fetch_all(&self.pool)
+                .await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(MIGRATOR
+            .iter()
+            .filter(|migration| migration.migration_type.is_up_migration())
+            .map(|migration| migration.version)
+            .filter(|version| !applied.contains(version))
+            .collect())
+    }
+
     /// Run database migrations
     async fn run_migrations(pool: &PgPool) -> DbResult<()> {
         info!("Running database migrations");
 
         // can we check if a migration file has been modified, if so, drop the database and recreate it only in development
-        
 
-        match sqlx::migrate!("./migrations").run(pool).await {
-            Ok(_) => {
-                info!("Database migrations completed successfully");
-                Ok(())
-            }
-            Err(e) => {
-                warn!("Database migration error: {}", e);
-                Err(DatabaseError::MigrationError(e.to_string()))
-            }
+        if let Err(e) = MIGRATOR.run(pool).await {
+            warn!("Database migration error: {}", e);
+            return Err(DatabaseError::MigrationError(e.to_string()));
         }
+
+        // Changes too slow for a transaction go after the migrations they depend on
+        online::run(pool).await.inspect_err(|e| {
+            warn!("Online migration error: {}", e);
+        })?;
+
+        info!("Database migrations completed successfully");
+        Ok(())
     }
 
     /// Gracefully close the database connection pool
@@ -264,3 +392,62 @@ fn extract_db_name_from_url(url: &str) -> Option<String> {
 
     Some(db_name.to_string())
 }
+
+/// Rows and services that tests running against a `#[sqlx::test]` database start from
+#[cfg(test)]
+pub mod testing {
+    use std::{sync::Arc, time::Duration};
+
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::Database;
+    use crate::{
+        cache::{AccessBacklog, LinkCache},
+        config::{MilestoneConfig, PaginationConfig, ShortCodeConfig},
+        events::EventBus,
+        handlers::ShortenedUrlServiceType,
+        repositories::{CircuitBreakerRepository, ShortenedUrlRepository},
+        services::ShortenedUrlService,
+        utils::circuit_breaker::CircuitBreaker,
+    };
+
+    /// A link service on the test database, as the app builds it but with caching and
+    /// events turned off
+    pub fn url_service(pool: &PgPool) -> Arc<ShortenedUrlServiceType> {
+        let breaker = CircuitBreaker::new("Database", 5, Duration::from_secs(30));
+        Arc::new(ShortenedUrlService::new(
+            Arc::new(CircuitBreakerRepository::new(
+                ShortenedUrlRepository::new(Database::from_pool(pool.clone())),
+                Arc::new(breaker),
+            )),
+            ShortCodeConfig::default(),
+            PaginationConfig::default(),
+            MilestoneConfig::default(),
+            Arc::new(LinkCache::disabled()),
+            Arc::new(AccessBacklog::new(0)),
+            Arc::new(EventBus::disabled()),
+        ))
+    }
+
+    /// Saves a link to example.com under `short_code` and returns its ID
+    pub async fn insert_link(pool: &PgPool, short_code: &str) -> Uuid {
+        sqlx::query_scalar(
+            "INSERT INTO shortened_urls (original_url, short_code) VALUES ($1, $2) RETURNING id",
+        )
+        .bind("https://example.com")
+        .bind(short_code)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    /// Saves a user with the given email and returns their ID
+    pub async fn insert_user(pool: &PgPool, email: &str) -> Uuid {
+        sqlx::query_scalar("INSERT INTO users (email) VALUES ($1) RETURNING id")
+            .bind(email)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+}
@@ -0,0 +1,280 @@
+// src/db/online.rs - Schema changes applied while instances keep serving traffic
+use std::time::{Duration, Instant};
+
+use log::{debug, info};
+use sqlx::{PgConnection, PgPool};
+
+use super::{DatabaseError, DbResult};
+
+// Key of the advisory lock keeping online migrations to one instance at a time
+const ONLINE_MIGRATION_LOCK: i64 = 0x6f6e6c696e65;
+
+// Rest between backfill batches, so replication and autovacuum keep up
+const BACKFILL_PAUSE: Duration = Duration::from_millis(50);
+
+// How often a running backfill reports its progress
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// A schema change that would lock a big table for too long inside a migration transaction
+///
+/// Regular migrations run in a transaction holding their locks until the end, blocking
+/// redirects while an index builds or every row is rewritten. These changes run after them,
+/// outside a transaction, and are recorded in `online_migrations` once done.
+pub struct OnlineMigration {
+    /// Recorded once applied, like the version of a regular migration
+    pub name: &'static str,
+    pub change: OnlineChange,
+}
+
+pub enum OnlineChange {
+    /// `CREATE INDEX CONCURRENTLY`, which lets writes go on while the index builds.
+    /// `definition` follows the index name, e.g. `ON urls (created_at) WHERE is_active`
+    Index {
+        index: &'static str,
+        definition: &'static str,
+    },
+    /// An `UPDATE` applied `batch_size` rows at a time, each batch committed on its own.
+    /// `filter` must stop matching the rows `set` was applied to, or the backfill never ends
+    Backfill {
+        table: &'static str,
+        set: &'static str,
+        filter: &'static str,
+        batch_size: i64,
+    },
+}
+
+/// Online migrations in the order they apply, appended to like the migrations directory
+pub static ONLINE_MIGRATIONS: &[OnlineMigration] = &[
+    OnlineMigration {
+        // The archive job looks for links unused since a cutoff among the unarchived ones
+        name: "20260210090000_index_links_to_archive",
+        change: OnlineChange::Index {
+            index: "idx_shortened_urls_unarchived_last_used",
+            definition: "ON shortened_urls (COALESCE(last_accessed, created_at)) \
+                         WHERE archived_at IS NULL AND deleted_at IS NULL",
+        },
+    },
+    OnlineMigration {
+        // Finds the links of a bucket, to move them when shards are split
+        name: "20260217090000_index_short_code_buckets",
+        change: OnlineChange::Index {
+            index: "idx_shortened_urls_short_code_bucket",
+            definition: "ON shortened_urls (short_code_bucket(short_code))",
+        },
+    },
+    OnlineMigration {
+        // Links created before UUIDv7 ids get the id they would have had, references
+        // following and old ids kept in url_id_remaps
+        name: "20260310090000_remap_v4_url_ids",
+        change: OnlineChange::Backfill {
+            table: "shortened_urls",
+            set: "id = uuid_generate_v7_at(created_at)",
+            filter: "substring(id::text, 15, 1) = '4'",
+            batch_size: 500,
+        },
+    },
+];
+
+/// Apply the online migrations not recorded yet, one instance at a time
+pub async fn run(pool: &PgPool) -> DbResult<()> {
+    // Session locks and concurrent index builds need a connection outside any transaction
+    let mut conn = pool.acquire().await?;
+    sqlx::query("SELECT pg_advisory_lock($1)")
+        .bind(ONLINE_MIGRATION_LOCK)
+        .execute(&mut *conn)
+        .await?;
+
+    let applied = apply_pending(&mut conn).await;
+
+    let unlocked = sqlx::query("SELECT pg_advisory_unlock($1)")
+        .bind(ONLINE_MIGRATION_LOCK)
+        .execute(&mut *conn)
+        .await;
+    if unlocked.is_err() {
+        conn.close_on_drop();
+    }
+    applied
+}
+
+async fn apply_pending(conn: &mut PgConnection) -> DbResult<()> {
+    let done: Vec<String> = sqlx::query_scalar("SELECT name FROM online_migrations")
+        .fetch_all(&mut *conn)
+        .await?;
+
+    for migration in ONLINE_MIGRATIONS {
+        if done.iter().any(|name| name == migration.name) {
+            continue;
+        }
+
+        info!("Applying online migration '{}'", migration.name);
+        let start = Instant::now();
+        match &migration.change {
+            OnlineChange::Index { index, definition } => {
+                create_index_concurrently(conn, index, definition).await?
+            }
+            OnlineChange::Backfill {
+                table,
+                set,
+                filter,
+                batch_size,
+            } => {
+                backfill(conn, migration.name, table, set, filter, *batch_size).await?;
+            }
+        }
+
+        sqlx::query("INSERT INTO online_migrations (name) VALUES ($1)")
+            .bind(migration.name)
+            .execute(&mut *conn)
+            .await?;
+        info!(
+            "Applied online migration '{}' in {:.1}s",
+            migration.name,
+            start.elapsed().as_secs_f64()
+        );
+    }
+
+    Ok(())
+}
+
+/// Build an index without blocking writes to its table
+///
+/// A build that failed or was interrupted leaves an invalid index behind, which `IF NOT
+/// EXISTS` would take for done; it's dropped and built again.
+pub async fn create_index_concurrently(
+    conn: &mut PgConnection,
+    index: &str,
+    definition: &str,
+) -> DbResult<()> {
+    let invalid: Option<bool> = sqlx::query_scalar(
+        "SELECT NOT indisvalid FROM pg_index WHERE indexrelid = to_regclass($1)",
+    )
+    .bind(index)
+    .fetch_optional(&mut *conn)
+    .await?;
+    if invalid == Some(true) {
+        info!(
+            "Dropping invalid index '{}' left by an earlier build",
+            index
+        );
+        sqlx::query(&format!("DROP INDEX CONCURRENTLY IF EXISTS {}", index))
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    sqlx::query(&format!(
+        "CREATE INDEX CONCURRENTLY IF NOT EXISTS {} {}",
+        index, definition
+    ))
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+/// Apply `SET set` to the rows of `table` matching `filter`, `batch_size` at a time, logging
+/// progress as it goes
+///
+/// ### Returns
+/// * `DbResult<u64>` - Number of rows updated
+pub async fn backfill(
+    conn: &mut PgConnection,
+    name: &str,
+    table: &str,
+    set: &str,
+    filter: &str,
+    batch_size: i64,
+) -> DbResult<u64> {
+    if batch_size < 1 {
+        return Err(DatabaseError::MigrationError(format!(
+            "Backfill '{}' needs a batch size of at least 1",
+            name
+        )));
+    }
+
+    let total: i64 =
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {} WHERE {}", table, filter))
+            .fetch_one(&mut *conn)
+            .await?;
+    info!("Backfill '{}': {} rows to update", name, total);
+
+    // Rows are picked by physical location, which the update then finds without a scan. A
+    // row changed meanwhile has moved and is picked again by a later batch
+    let statement = format!(
+        "UPDATE {table} SET {set} WHERE ctid = ANY(ARRAY(\
+         SELECT ctid FROM {table} WHERE {filter} LIMIT {batch_size}))"
+    );
+    let mut updated = 0;
+    let mut reported = Instant::now();
+    loop {
+        let batch = sqlx::query(&statement)
+            .execute(&mut *conn)
+            .await?
+            .rows_affected();
+        if batch == 0 {
+            break;
+        }
+
+        updated += batch;
+        if reported.elapsed() >= PROGRESS_INTERVAL {
+            info!("Backfill '{}': {} of {} rows updated", name, updated, total);
+            reported = Instant::now();
+        } else {
+            debug!("Backfill '{}': {} of {} rows updated", name, updated, total);
+        }
+        tokio::time::sleep(BACKFILL_PAUSE).await;
+    }
+
+    info!("Backfill '{}': done, {} rows updated", name, updated);
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[sqlx::test]
+    async fn test_v4_url_ids_are_remapped(pool: PgPool) {
+        let old_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO shortened_urls (id, original_url, short_code, created_at) \
+             VALUES (gen_random_uuid(), 'https://example.com', 'legacy', '2024-05-01T12:00:00Z') \
+             RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(old_id.get_version_num(), 4);
+        sqlx::query(
+            "INSERT INTO url_revisions (url_id, revision, original_url) \
+             VALUES ($1, 1, 'https://example.org')",
+        )
+        .bind(old_id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        run(&pool).await.unwrap();
+
+        let new_id: Uuid = sqlx::query_scalar("SELECT id FROM shortened_urls")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(new_id.get_version_num(), 7);
+        // The id sorts by the creation time of the link
+        let (seconds, _) = new_id.get_timestamp().unwrap().to_unix();
+        assert_eq!(seconds, 1_714_564_800);
+
+        let revision_url: Uuid = sqlx::query_scalar("SELECT url_id FROM url_revisions")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(revision_url, new_id);
+        let remapped: Uuid =
+            sqlx::query_scalar("SELECT new_id FROM url_id_remaps WHERE old_id = $1")
+                .bind(old_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(remapped, new_id);
+    }
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// Machine-readable reason sent as `code` with every error response, so clients can branch
+/// on it instead of parsing the message, which may be translated
+///
+/// Each [`AppError`](super::AppError) variant has a general code; errors clients are likely
+/// to handle on their own are tagged with a more specific one through
+/// [`AppError::with_code`](super::AppError::with_code).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    // General codes, one per kind of error
+    ValidationFailed,
+    InvalidFields,
+    Conflict,
+    NotFound,
+    Unauthorized,
+    Forbidden,
+    Gone,
+    ServiceUnavailable,
+    RateLimited,
+    GatewayTimeout,
+    BadGateway,
+    RedirectLoop,
+    InternalError,
+
+    // Specific codes
+    /// The link is past its expiry date, visit or click limit
+    UrlExpired,
+    /// The custom short code asked for belongs to another link
+    AliasTaken,
+    /// The single-use link has already been followed
+    UrlConsumed,
+    /// The link was disabled for breaching the acceptable use policy
+    LinkBlocked,
+    /// The link waits for a moderator before it can be followed
+    LinkPendingReview,
+    /// The link is suspended for a while because of unusual traffic
+    LinkSuspended,
+    /// The link is restricted to networks the client isn't on
+    NetworkRestricted,
+    /// The service is in maintenance mode
+    Maintenance,
+}
@@ -0,0 +1,84 @@
+use actix_web::{error::Error as ActixError, web};
+
+use super::AppError;
+
+// Request bodies, paths and queries that can't be parsed are reported like any other invalid
+// input, rather than as actix's plain text errors without a code
+fn invalid_request(err: impl std::fmt::Display) -> ActixError {
+    AppError::Validation(err.to_string()).into()
+}
+
+/// JSON body settings; routes that need another size limit build on this one
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default().error_handler(|err, _req| invalid_request(err))
+}
+
+pub fn path_config() -> web::PathConfig {
+    web::PathConfig::default().error_handler(|err, _req| invalid_request(err))
+}
+
+pub fn query_config() -> web::QueryConfig {
+    web::QueryConfig::default().error_handler(|err, _req| invalid_request(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, App, HttpResponse};
+    use serde::Deserialize;
+    use uuid::Uuid;
+
+    #[derive(Deserialize)]
+    struct Page {
+        limit: u32,
+    }
+
+    #[actix_web::test]
+    async fn test_unparsable_requests_are_validation_errors() {
+        let app = test::init_service(
+            App::new()
+                .app_data(json_config().limit(64))
+                .app_data(path_config())
+                .app_data(query_config())
+                .route(
+                    "/items/{id}",
+                    web::post().to(
+                        |_: web::Path<Uuid>, query: web::Query<Page>, body: web::Json<Page>| async move {
+                            HttpResponse::Ok().json(query.limit + body.limit)
+                        },
+                    ),
+                ),
+        )
+        .await;
+        let id = Uuid::new_v4();
+        let page = serde_json::json!({ "limit": 1 });
+        let requests = [
+            test::TestRequest::post()
+                .uri("/items/nope?limit=1")
+                .set_json(&page),
+            test::TestRequest::post()
+                .uri(&format!("/items/{id}?limit=x"))
+                .set_json(&page),
+            test::TestRequest::post()
+                .uri(&format!("/items/{id}?limit=1"))
+                .insert_header(("content-type", "application/json"))
+                .set_payload("{\"limit\":"),
+            test::TestRequest::post()
+                .uri(&format!("/items/{id}?limit=1"))
+                .set_json(serde_json::json!({ "limit": 1, "padding": "x".repeat(64) })),
+        ];
+
+        for request in requests {
+            let response = test::call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+            let body: serde_json::Value = test::read_body_json(response).await;
+            assert_eq!(body["code"], "VALIDATION_FAILED");
+        }
+
+        let request = test::TestRequest::post()
+            .uri(&format!("/items/{id}?limit=1"))
+            .set_json(&page);
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
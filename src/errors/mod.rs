@@ -1,31 +1,56 @@
-use std::io::Error as IoError;
+use std::{collections::BTreeMap, io::Error as IoError, time::Duration};
 
 use actix_web::{
-    http::StatusCode, 
+    http::{header::RETRY_AFTER, StatusCode},
     HttpResponse, ResponseError,
 };
 use serde_json::json;
 use thiserror::Error;
 
+pub mod code;
 pub mod config;
+pub mod extract;
 pub mod repository;
 
+pub use code::ErrorCode;
 pub use config::ConfigError;
 pub use repository::RepositoryError;
 
+/// Validation messages keyed by the offending field name
+pub type FieldErrors = BTreeMap<String, Vec<String>>;
+
 #[derive(Debug, Error)]
 pub enum AppError {
     // Service-level domain errors
     #[error("Validation error: {0}")]
     Validation(String),
+    #[error("Validation error: {}", format_field_errors(.0))]
+    InvalidFields(FieldErrors),
     #[error("Conflict error: {0}")]
     Conflict(String),
     #[error("Not found error: {0}")]
     NotFound(String),
     #[error("Internal error: {0}")]
     Internal(String),
-    /* #[error("Unauthorized")]
-    Unauthorized, */
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Gone: {0}")]
+    Gone(String),
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+    #[error("Too many requests: {0}")]
+    TooManyRequests(String),
+    // Too many requests, along with how long until the client may try again
+    #[error("Too many requests: {0}")]
+    RateLimited(String, Duration),
+    #[error("Gateway timeout: {0}")]
+    Timeout(String),
+    #[error("Bad gateway: {0}")]
+    BadGateway(String),
+    #[error("Loop detected: {0}")]
+    LoopDetected(String),
     // Infrastructure/system errors
     #[error("Server error: {0}")]
     Server(#[from] IoError),
@@ -33,6 +58,9 @@ pub enum AppError {
     Config(String),
     #[error("Logger error: {0}")]
     Logger(String),
+    // Any of the above, sent with a more specific code than its own
+    #[error("{1}")]
+    Coded(ErrorCode, Box<AppError>),
 }
 
 impl From<ConfigError> for AppError {
@@ -47,63 +75,189 @@ impl From<RepositoryError> for AppError {
             RepositoryError::NotFound(msg) => AppError::NotFound(msg),
             RepositoryError::Conflict(msg) => AppError::Conflict(msg),
             RepositoryError::InvalidData(msg) => AppError::Validation(msg),
+            err @ RepositoryError::Database(_) if err.is_outage() => {
+                AppError::Unavailable(err.to_string())
+            }
             RepositoryError::Database(mgs) => AppError::Internal(mgs.to_string()),
+            RepositoryError::Unavailable(msg) => AppError::Unavailable(msg),
         }
     }
 }
 
 impl From<validator::ValidationErrors> for AppError {
     fn from(errors: validator::ValidationErrors) -> Self {
-        // Flatten field errors into a single string
-        let message = errors
+        // Keep the reasons grouped per field so clients can map them onto inputs
+        let fields = errors
             .field_errors()
-            .iter()
+            .into_iter()
             .map(|(field, errs)| {
                 let reasons = errs
                     .iter()
-                    .map(|e| e.message.clone().unwrap_or_else(|| "invalid".into()))
-                    .collect::<Vec<_>>()
-                    .join(", ");
-                format!("{}: {}", field, reasons)
+                    .map(|e| {
+                        e.message
+                            .clone()
+                            .unwrap_or_else(|| e.code.clone())
+                            .to_string()
+                    })
+                    .collect::<Vec<_>>();
+                (field.to_string(), reasons)
             })
-            .collect::<Vec<_>>()
-            .join("; ");
-        AppError::Validation(message)
+            .collect::<FieldErrors>();
+        AppError::InvalidFields(fields)
     }
 }
 
+// Flatten field errors into a single string
+fn format_field_errors(fields: &FieldErrors) -> String {
+    fields
+        .iter()
+        .map(|(field, reasons)| format!("{}: {}", field, reasons.join(", ")))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+impl AppError {
+    /// The message shown to clients, without the error type
+    pub fn message(&self) -> String {
+        match self.to_string().split_once(':') {
+            Some((_, message)) if !message.trim().is_empty() => message.trim().to_string(),
+            _ => "An error occurred".to_string(),
+        }
+    }
+
+    /// How long the client should wait before trying again, sent as `Retry-After`
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            AppError::RateLimited(_, wait) => Some(*wait),
+            AppError::Coded(_, inner) => inner.retry_after(),
+            _ => None,
+        }
+    }
+
+    // The error behind a specific code, or the error itself
+    fn unwrap_coded(&self) -> &AppError {
+        match self {
+            AppError::Coded(_, inner) => inner.unwrap_coded(),
+            error => error,
+        }
+    }
+
+    /// Tags the error with a more specific code, keeping its status and message
+    pub fn with_code(self, code: ErrorCode) -> Self {
+        match self {
+            AppError::Coded(_, inner) => AppError::Coded(code, inner),
+            inner => AppError::Coded(code, Box::new(inner)),
+        }
+    }
+
+    /// The machine-readable code sent to clients
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::Coded(code, _) => *code,
+            AppError::Validation(_) => ErrorCode::ValidationFailed,
+            AppError::InvalidFields(_) => ErrorCode::InvalidFields,
+            AppError::Conflict(_) => ErrorCode::Conflict,
+            AppError::NotFound(_) => ErrorCode::NotFound,
+            AppError::Unauthorized(_) => ErrorCode::Unauthorized,
+            AppError::Forbidden(_) => ErrorCode::Forbidden,
+            AppError::Gone(_) => ErrorCode::Gone,
+            AppError::Unavailable(_) => ErrorCode::ServiceUnavailable,
+            AppError::TooManyRequests(_) | AppError::RateLimited(..) => ErrorCode::RateLimited,
+            AppError::Timeout(_) => ErrorCode::GatewayTimeout,
+            AppError::BadGateway(_) => ErrorCode::BadGateway,
+            AppError::LoopDetected(_) => ErrorCode::RedirectLoop,
+            AppError::Internal(_)
+            | AppError::Server(_)
+            | AppError::Config(_)
+            | AppError::Logger(_) => ErrorCode::InternalError,
+        }
+    }
+}
+
+// Whole seconds for `Retry-After`, rounded up so clients don't come back too early
+pub fn retry_after_secs(wait: Duration) -> u64 {
+    wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+}
+
 impl ResponseError for AppError {
     fn status_code(&self) -> StatusCode {
         match self {
             AppError::NotFound(_) => StatusCode::NOT_FOUND,
-            AppError::Validation(_) => StatusCode::BAD_REQUEST,
+            AppError::Validation(_) | AppError::InvalidFields(_) => StatusCode::BAD_REQUEST,
             AppError::Conflict(_) => StatusCode::CONFLICT,
-            // AppError::Unauthorized => StatusCode::UNAUTHORIZED,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Gone(_) => StatusCode::GONE,
+            AppError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            AppError::TooManyRequests(_) | AppError::RateLimited(..) => {
+                StatusCode::TOO_MANY_REQUESTS
+            }
+            AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+            AppError::BadGateway(_) => StatusCode::BAD_GATEWAY,
+            AppError::LoopDetected(_) => StatusCode::LOOP_DETECTED,
             AppError::Internal(_)
             | AppError::Server(_)
             | AppError::Config(_)
             | AppError::Logger(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            AppError::Coded(_, inner) => inner.status_code(),
         }
     }
 
     fn error_response(&self) -> HttpResponse {
         let error_string = self.to_string();
-        let (error_type, message) = error_string
-        .split_once(":")
-        .map(|(t, m)| (t.trim(), m.trim()))
-        .unwrap_or(("Error", "An error occurred"));
-
-        let error_message = if message.is_empty() {
-            "An error occurred"
-        } else {
-            message
-        };
-        
+        let error_type = error_string
+            .split_once(':')
+            .map(|(t, _)| t.trim())
+            .unwrap_or("Error");
+        let error_message = self.message();
+
         let code = self.status_code().as_u16();
-        HttpResponse::build(self.status_code()).json(json!({
+        let mut body = json!({
             "type": error_type.to_uppercase(),
+            "code": self.code(),
             "message": error_message,
             "status_code": code,
-        }))
+        });
+
+        // Expose the per-field breakdown alongside the flattened message
+        if let AppError::InvalidFields(fields) = self.unwrap_coded() {
+            body["errors"] = json!(fields);
+        }
+
+        let mut response = HttpResponse::build(self.status_code());
+        if let Some(wait) = self.retry_after() {
+            response.insert_header((RETRY_AFTER, retry_after_secs(wait)));
+        }
+        response.json(body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::body::MessageBody;
+
+    fn body(error: &AppError) -> serde_json::Value {
+        let bytes = error.error_response().into_body().try_into_bytes().unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_codes_keep_status_message_and_retry_after() {
+        let plain = AppError::Validation("Bad input".to_string());
+        assert_eq!(body(&plain)["code"], "VALIDATION_FAILED");
+
+        let wait = Duration::from_millis(1500);
+        let coded = AppError::RateLimited("Slow down".to_string(), wait)
+            .with_code(ErrorCode::LinkSuspended);
+        assert_eq!(coded.code(), ErrorCode::LinkSuspended);
+        assert_eq!(coded.status_code(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(coded.message(), "Slow down");
+
+        let response = coded.error_response();
+        assert_eq!(response.headers().get(RETRY_AFTER).unwrap(), "2");
+        let body = body(&coded);
+        assert_eq!(body["code"], "LINK_SUSPENDED");
+        assert_eq!(body["type"], "TOO MANY REQUESTS");
     }
 }
@@ -18,6 +18,30 @@ pub enum RepositoryError {
     /// Invalid input data
     #[error("Invalid data: {0}")]
     InvalidData(String),
+
+    /// The database is known to be down and wasn't called
+    #[error("Database unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl RepositoryError {
+    /// Whether the error means the database couldn't be reached, as opposed to
+    /// the database rejecting the query
+    pub fn is_outage(&self) -> bool {
+        match self {
+            Self::Unavailable(_) => true,
+            Self::Database(err) => matches!(
+                err,
+                SqlxError::Io(_)
+                    | SqlxError::Tls(_)
+                    | SqlxError::Protocol(_)
+                    | SqlxError::PoolTimedOut
+                    | SqlxError::PoolClosed
+                    | SqlxError::WorkerCrashed
+            ),
+            _ => false,
+        }
+    }
 }
 
 impl From<SqlxError> for RepositoryError {
@@ -0,0 +1,195 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{
+    self,
+    error::{RecvError, TryRecvError},
+};
+use tokio::task::JoinHandle;
+
+use super::DomainEvent;
+use crate::types::Result;
+
+// Events handed to a subscriber at once when it falls behind
+const MAX_BATCH_SIZE: usize = 100;
+
+/// Receives the events published on the bus, in batches
+#[async_trait]
+pub trait EventSubscriber: Send + Sync {
+    /// Short name used in logs
+    fn name(&self) -> &'static str;
+
+    /// Handles a batch of events in the order they were published
+    async fn handle(&self, events: &[DomainEvent]) -> Result<()>;
+}
+
+/// Event counts since startup, as reported by the health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventBusStats {
+    pub published: u64,
+    /// Deliveries to subscribers, counting an event once per subscriber
+    pub delivered: u64,
+    /// Deliveries a subscriber failed to handle
+    pub failed: u64,
+    /// Events a slow subscriber missed because the bus was full
+    pub dropped: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    published: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// In-process bus the services publish domain events to
+///
+/// Publishing never waits: every subscriber reads from its own bounded buffer in a
+/// background task, and one that falls further behind than the capacity misses events
+/// rather than slowing down redirects. Delivery is best effort and not persisted.
+pub struct EventBus {
+    sender: Option<broadcast::Sender<DomainEvent>>,
+    counters: Arc<Counters>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self {
+            sender: Some(sender),
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// A bus dropping every event, for tools that don't run subscribers
+    pub fn disabled() -> Self {
+        Self {
+            sender: None,
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// Hand an event to every subscriber
+    pub fn publish(&self, event: DomainEvent) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        self.counters.published.fetch_add(1, Ordering::Relaxed);
+        // Only fails while nobody subscribed, in which case nobody misses the event
+        let _ = sender.send(event);
+    }
+
+    /// Deliver the events published from now on to a subscriber, in a background task
+    pub fn subscribe(&self, subscriber: Arc<dyn EventSubscriber>) -> Option<JoinHandle<()>> {
+        let mut receiver = self.sender.as_ref()?.subscribe();
+        let counters = self.counters.clone();
+
+        Some(tokio::spawn(async move {
+            loop {
+                let mut batch = match receiver.recv().await {
+                    Ok(event) => vec![event],
+                    Err(RecvError::Lagged(missed)) => {
+                        lagged(&counters, subscriber.name(), missed);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return,
+                };
+                while batch.len() < MAX_BATCH_SIZE {
+                    match receiver.try_recv() {
+                        Ok(event) => batch.push(event),
+                        Err(TryRecvError::Lagged(missed)) => {
+                            lagged(&counters, subscriber.name(), missed)
+                        }
+                        Err(_) => break,
+                    }
+                }
+
+                let count = batch.len() as u64;
+                match subscriber.handle(&batch).await {
+                    Ok(()) => {
+                        counters.delivered.fetch_add(count, Ordering::Relaxed);
+                    }
+                    Err(e) => {
+                        counters.failed.fetch_add(count, Ordering::Relaxed);
+                        warn!(
+                            "Event subscriber '{}' failed to handle {} events: {}",
+                            subscriber.name(),
+                            count,
+                            e
+                        );
+                    }
+                }
+            }
+        }))
+    }
+
+    pub fn stats(&self) -> EventBusStats {
+        EventBusStats {
+            published: self.counters.published.load(Ordering::Relaxed),
+            delivered: self.counters.delivered.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+fn lagged(counters: &Counters, name: &str, missed: u64) {
+    counters.dropped.fetch_add(missed, Ordering::Relaxed);
+    warn!(
+        "Event subscriber '{}' fell behind and missed {} events",
+        name, missed
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::sync::mpsc;
+
+    use super::*;
+    use crate::models::ShortenedUrl;
+
+    struct Forward(mpsc::UnboundedSender<Vec<&'static str>>);
+
+    #[async_trait]
+    impl EventSubscriber for Forward {
+        fn name(&self) -> &'static str {
+            "forward"
+        }
+
+        async fn handle(&self, events: &[DomainEvent]) -> Result<()> {
+            let _ = self.0.send(events.iter().map(|e| e.name()).collect());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribers_receive_events_in_order() {
+        let bus = EventBus::new(16);
+        let (sender, mut received) = mpsc::unbounded_channel();
+        bus.subscribe(Arc::new(Forward(sender))).unwrap();
+
+        let url = ShortenedUrl::default();
+        bus.publish(DomainEvent::url_created(&url));
+        bus.publish(DomainEvent::url_clicked(&url));
+        bus.publish(DomainEvent::url_expired(&url));
+
+        let mut names = Vec::new();
+        while names.len() < 3 {
+            names.extend(received.recv().await.unwrap());
+        }
+        assert_eq!(names, ["url_created", "url_clicked", "url_expired"]);
+        assert_eq!(bus.stats().published, 3);
+
+        // A disabled bus has no subscribers to hand events to
+        let disabled = EventBus::disabled();
+        disabled.publish(DomainEvent::url_created(&url));
+        assert!(disabled
+            .subscribe(Arc::new(Forward(mpsc::unbounded_channel().0)))
+            .is_none());
+        assert_eq!(disabled.stats().published, 0);
+    }
+}
@@ -0,0 +1,87 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::models::ShortenedUrl;
+
+/// Something that happened to a link, published on the event bus
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DomainEvent {
+    UrlCreated {
+        url_id: Uuid,
+        tenant_id: Option<Uuid>,
+        /// The code as it appears in the short URL, with its namespace
+        short_code: String,
+        original_url: String,
+        created_by: Option<Uuid>,
+        occurred_at: DateTime<Utc>,
+    },
+    /// A visitor was redirected; nothing identifying the visitor is included
+    UrlClicked {
+        url_id: Uuid,
+        tenant_id: Option<Uuid>,
+        short_code: String,
+        occurred_at: DateTime<Utc>,
+    },
+    /// A link reached its expiry date
+    UrlExpired {
+        url_id: Uuid,
+        tenant_id: Option<Uuid>,
+        short_code: String,
+        original_url: String,
+        created_by: Option<Uuid>,
+        occurred_at: DateTime<Utc>,
+    },
+}
+
+impl DomainEvent {
+    pub fn url_created(url: &ShortenedUrl) -> Self {
+        DomainEvent::UrlCreated {
+            url_id: url.id,
+            tenant_id: url.tenant_id,
+            short_code: url.path(),
+            original_url: url.original_url.clone(),
+            created_by: url.created_by,
+            occurred_at: url.created_at,
+        }
+    }
+
+    pub fn url_clicked(url: &ShortenedUrl) -> Self {
+        DomainEvent::UrlClicked {
+            url_id: url.id,
+            tenant_id: url.tenant_id,
+            short_code: url.path(),
+            occurred_at: Utc::now(),
+        }
+    }
+
+    pub fn url_expired(url: &ShortenedUrl) -> Self {
+        DomainEvent::UrlExpired {
+            url_id: url.id,
+            tenant_id: url.tenant_id,
+            short_code: url.path(),
+            original_url: url.original_url.clone(),
+            created_by: url.created_by,
+            occurred_at: url.expires_at.unwrap_or_else(Utc::now),
+        }
+    }
+
+    /// Name of the event type, as used in NATS subjects
+    pub fn name(&self) -> &'static str {
+        match self {
+            DomainEvent::UrlCreated { .. } => "url_created",
+            DomainEvent::UrlClicked { .. } => "url_clicked",
+            DomainEvent::UrlExpired { .. } => "url_expired",
+        }
+    }
+
+    /// The link the event is about, used to keep each link's events in order
+    pub fn url_id(&self) -> Uuid {
+        match self {
+            DomainEvent::UrlCreated { url_id, .. }
+            | DomainEvent::UrlClicked { url_id, .. }
+            | DomainEvent::UrlExpired { url_id, .. } => *url_id,
+        }
+    }
+}
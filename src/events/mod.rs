@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use log::info;
+
+mod bus;
+mod event;
+mod publishers;
+mod subscribers;
+
+pub use bus::{EventBus, EventBusStats, EventSubscriber};
+pub use event::DomainEvent;
+pub use publishers::{build_publisher, KafkaRestPublisher, NatsPublisher};
+pub use subscribers::NotificationSubscriber;
+
+use crate::{
+    config::Config, db::Database, repositories::NotificationRepository, types::Result,
+    utils::http_client::HttpClient,
+};
+
+/// Start the in-process subscribers and the external publisher selected in configuration
+pub fn spawn_subscribers(
+    bus: &EventBus,
+    db: &Database,
+    config: &Config,
+    http_client: Arc<HttpClient>,
+) -> Result<()> {
+    bus.subscribe(Arc::new(NotificationSubscriber::new(
+        Arc::new(NotificationRepository::new(db.clone())),
+        config.notifications.public_base_url.clone(),
+        http_client.clone(),
+    )));
+
+    if let Some(publisher) = build_publisher(config, http_client)? {
+        info!(
+            "Publishing link events to {} ('{}')",
+            publisher.name(),
+            config.events.topic
+        );
+        bus.subscribe(publisher);
+    }
+
+    Ok(())
+}
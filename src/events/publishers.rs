@@ -0,0 +1,262 @@
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use log::debug;
+use reqwest::header::CONTENT_TYPE;
+use serde_json::json;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{
+    tcp::{OwnedReadHalf, OwnedWriteHalf},
+    TcpStream,
+};
+use tokio::sync::Mutex;
+
+use super::{DomainEvent, EventSubscriber};
+use crate::{
+    config::{Config, EventPublisherKind},
+    errors::AppError,
+    types::Result,
+    utils::http_client::HttpClient,
+};
+
+// Brokers answer quickly; a dead one shouldn't hold events much longer than this
+const PUBLISH_TIMEOUT: Duration = Duration::from_secs(5);
+
+const DEFAULT_NATS_PORT: u16 = 4222;
+
+/// Publishes events to NATS under `<prefix>.<event type>`, like `url_shortener.events.url_created`
+///
+/// Speaks the NATS text protocol over plain TCP. Each batch ends with a PING, and is only
+/// considered published once the server answers with PONG.
+pub struct NatsPublisher {
+    address: String,
+    subject_prefix: String,
+    connection: Mutex<Option<NatsConnection>>,
+}
+
+impl NatsPublisher {
+    pub fn new(url: &str, subject_prefix: &str) -> Self {
+        let address = url.trim_start_matches("nats://").trim_end_matches('/');
+        let address = if address.contains(':') {
+            address.to_string()
+        } else {
+            format!("{}:{}", address, DEFAULT_NATS_PORT)
+        };
+
+        Self {
+            address,
+            subject_prefix: subject_prefix.to_string(),
+            connection: Mutex::new(None),
+        }
+    }
+
+    async fn send(&self, frames: &[u8]) -> io::Result<()> {
+        let mut connection = self.connection.lock().await;
+        // A kept connection may have been closed by the server since, so a batch failing on
+        // it is sent again on a new one, possibly reaching subscribers twice
+        if let Some(open) = connection.as_mut() {
+            match open.publish(frames).await {
+                Ok(()) => return Ok(()),
+                Err(e) => debug!(
+                    "NATS connection to {} lost, reconnecting: {}",
+                    self.address, e
+                ),
+            }
+        }
+
+        *connection = None;
+        let mut open = NatsConnection::open(&self.address).await?;
+        open.publish(frames).await?;
+        *connection = Some(open);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for NatsPublisher {
+    fn name(&self) -> &'static str {
+        "nats"
+    }
+
+    async fn handle(&self, events: &[DomainEvent]) -> Result<()> {
+        let mut frames = Vec::new();
+        for event in events {
+            let subject = format!("{}.{}", self.subject_prefix, event.name());
+            let payload =
+                serde_json::to_vec(event).map_err(|e| AppError::Internal(e.to_string()))?;
+            frames.extend(pub_frame(&subject, &payload));
+        }
+
+        tokio::time::timeout(PUBLISH_TIMEOUT, self.send(&frames))
+            .await
+            .map_err(|_| AppError::Internal("Publishing to NATS timed out".to_string()))?
+            .map_err(|e| AppError::Internal(format!("Publishing to NATS failed: {}", e)))
+    }
+}
+
+struct NatsConnection {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+}
+
+impl NatsConnection {
+    async fn open(address: &str) -> io::Result<Self> {
+        let (reader, writer) = TcpStream::connect(address).await?.into_split();
+        let mut connection = Self {
+            reader: BufReader::new(reader),
+            writer,
+        };
+
+        // The server greets with INFO before accepting anything
+        let greeting = connection.read_line().await?;
+        if !greeting.starts_with("INFO") {
+            return Err(protocol_error(&greeting));
+        }
+        connection
+            .writer
+            .write_all(
+                b"CONNECT {\"verbose\":false,\"pedantic\":false,\"name\":\"url-shortener\"}\r\n",
+            )
+            .await?;
+
+        Ok(connection)
+    }
+
+    async fn publish(&mut self, frames: &[u8]) -> io::Result<()> {
+        self.writer.write_all(frames).await?;
+        self.writer.write_all(b"PING\r\n").await?;
+
+        // The server handles commands in order, so its PONG covers everything sent before
+        loop {
+            let line = self.read_line().await?;
+            match line.as_str() {
+                "PONG" => return Ok(()),
+                "PING" => self.writer.write_all(b"PONG\r\n").await?,
+                line if line.starts_with("-ERR") => return Err(protocol_error(line)),
+                _ => {}
+            }
+        }
+    }
+
+    async fn read_line(&mut self) -> io::Result<String> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(line.trim_end().to_string())
+    }
+}
+
+// Frame a message as a NATS PUB command
+fn pub_frame(subject: &str, payload: &[u8]) -> Vec<u8> {
+    let mut frame = format!("PUB {} {}\r\n", subject, payload.len()).into_bytes();
+    frame.extend_from_slice(payload);
+    frame.extend_from_slice(b"\r\n");
+    frame
+}
+
+fn protocol_error(line: &str) -> io::Error {
+    io::Error::other(format!("unexpected reply '{}'", line))
+}
+
+/// Publishes events to a Kafka topic through a Kafka REST proxy
+///
+/// Records are keyed by link ID, so the events of a link land on one partition in order.
+pub struct KafkaRestPublisher {
+    client: Arc<HttpClient>,
+    url: String,
+}
+
+impl KafkaRestPublisher {
+    pub fn new(rest_url: &str, topic: &str, client: Arc<HttpClient>) -> Self {
+        Self {
+            client,
+            url: format!("{}/topics/{}", rest_url.trim_end_matches('/'), topic),
+        }
+    }
+}
+
+#[async_trait]
+impl EventSubscriber for KafkaRestPublisher {
+    fn name(&self) -> &'static str {
+        "kafka"
+    }
+
+    async fn handle(&self, events: &[DomainEvent]) -> Result<()> {
+        let records = events
+            .iter()
+            .map(|event| json!({ "key": event.url_id(), "value": event }))
+            .collect::<Vec<_>>();
+
+        let request = self
+            .client
+            .post(&self.url)
+            .timeout(PUBLISH_TIMEOUT)
+            .header(CONTENT_TYPE, "application/vnd.kafka.json.v2+json")
+            .json(&json!({ "records": records }));
+        let response = self
+            .client
+            .send(request)
+            .await
+            .map_err(|e| AppError::Internal(format!("Publishing to Kafka failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(AppError::Internal(format!(
+                "Kafka REST proxy responded with {}",
+                response.status()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Build the external publisher selected in configuration, if any
+pub fn build_publisher(
+    config: &Config,
+    client: Arc<HttpClient>,
+) -> Result<Option<Arc<dyn EventSubscriber>>> {
+    let events = &config.events;
+    let publisher: Arc<dyn EventSubscriber> = match events.publisher {
+        EventPublisherKind::None => return Ok(None),
+        EventPublisherKind::Nats => match &events.nats_url {
+            Some(url) => Arc::new(NatsPublisher::new(url, &events.topic)),
+            None => {
+                return Err(AppError::Config(
+                    "EVENT_PUBLISHER=nats requires EVENT_NATS_URL to be configured".to_string(),
+                ))
+            }
+        },
+        EventPublisherKind::Kafka => match &events.kafka_rest_url {
+            Some(url) => Arc::new(KafkaRestPublisher::new(url, &events.topic, client)),
+            None => {
+                return Err(AppError::Config(
+                    "EVENT_PUBLISHER=kafka requires EVENT_KAFKA_REST_URL to be configured"
+                        .to_string(),
+                ))
+            }
+        },
+    };
+
+    Ok(Some(publisher))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nats_frames() {
+        assert_eq!(
+            pub_frame("links.url_created", b"{}"),
+            b"PUB links.url_created 2\r\n{}\r\n"
+        );
+
+        let publisher = NatsPublisher::new("nats://broker", "links");
+        assert_eq!(publisher.address, "broker:4222");
+        let publisher = NatsPublisher::new("localhost:4333", "links");
+        assert_eq!(publisher.address, "localhost:4333");
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::warn;
+
+use super::{DomainEvent, EventSubscriber};
+use crate::{
+    notifications::{short_url, ChannelDispatcher, NotificationEvent},
+    repositories::NotificationRepositoryTrait,
+    types::Result,
+    utils::http_client::HttpClient,
+};
+
+/// Tells link owners on their channels (Slack, Discord, webhooks) when a link expired
+pub struct NotificationSubscriber<R: NotificationRepositoryTrait> {
+    dispatcher: ChannelDispatcher<R>,
+    base_url: String,
+}
+
+impl<R: NotificationRepositoryTrait + Send + Sync> NotificationSubscriber<R> {
+    pub fn new(repository: Arc<R>, base_url: String, http_client: Arc<HttpClient>) -> Self {
+        Self {
+            dispatcher: ChannelDispatcher::new(repository, http_client),
+            base_url,
+        }
+    }
+}
+
+#[async_trait]
+impl<R: NotificationRepositoryTrait + Send + Sync> EventSubscriber for NotificationSubscriber<R> {
+    fn name(&self) -> &'static str {
+        "notifications"
+    }
+
+    async fn handle(&self, events: &[DomainEvent]) -> Result<()> {
+        for event in events {
+            let DomainEvent::UrlExpired {
+                short_code,
+                original_url,
+                created_by: Some(owner),
+                occurred_at,
+                ..
+            } = event
+            else {
+                continue;
+            };
+
+            let notification = NotificationEvent::LinkExpired {
+                short_code: short_code.clone(),
+                short_url: short_url(&self.base_url, short_code),
+                original_url: original_url.clone(),
+                expired_at: *occurred_at,
+            };
+            // One owner's failing channels shouldn't keep the others from hearing about theirs
+            if let Err(e) = self.dispatcher.dispatch(owner, &notification).await {
+                warn!(
+                    "Failed to notify owner of '{}' about its expiry: {}",
+                    short_code, e
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use actix_web::{dev::Payload, FromRequest, HttpMessage, HttpRequest};
+use futures_util::future::{ready, Ready};
+
+use crate::errors::AppError;
+
+/// Latest API version, served under `/api/v{N}/`
+pub const CURRENT_API_VERSION: u16 = 1;
+
+/// Versions still served, the oldest first
+pub const SUPPORTED_API_VERSIONS: &[u16] = &[1];
+
+/// Versions announced as deprecated with their sunset date (an HTTP date), if one is set
+pub const DEPRECATED_API_VERSIONS: &[(u16, Option<&str>)] = &[];
+
+/// API version a request was made against
+///
+/// Set by the `ApiVersioning` middleware from the `/api/v{N}/` prefix. Requests to
+/// unversioned `/api/` paths count as version 1, so handlers changing their responses in a
+/// later version can keep older clients working.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ApiVersion(pub u16);
+
+impl Default for ApiVersion {
+    fn default() -> Self {
+        ApiVersion(1)
+    }
+}
+
+impl FromRequest for ApiVersion {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(Ok(req
+            .extensions()
+            .get::<ApiVersion>()
+            .copied()
+            .unwrap_or_default()))
+    }
+}
@@ -0,0 +1,92 @@
+use std::ops::Deref;
+
+use actix_web::{
+    dev::Payload, http::header::AUTHORIZATION, web, FromRequest, HttpMessage, HttpRequest,
+};
+use futures_util::future::LocalBoxFuture;
+
+use crate::{
+    errors::AppError,
+    handlers::UserServiceType,
+    models::{ServiceTokenIdentity, User},
+    services::UserServiceTrait,
+};
+
+/// The user identified by the request's `Authorization: Bearer <api key>` header
+///
+/// Rejects the request with `401` when no key or an unknown/revoked key is presented.
+#[derive(Debug, Clone)]
+pub struct AuthenticatedUser(pub User);
+
+impl Deref for AuthenticatedUser {
+    type Target = User;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let resolved = resolve_user(req);
+        Box::pin(async move {
+            match resolved.await? {
+                Some(user) => Ok(AuthenticatedUser(user)),
+                None => Err(AppError::Unauthorized(
+                    "An API key is required for this endpoint".to_string(),
+                )),
+            }
+        })
+    }
+}
+
+/// Like [`AuthenticatedUser`], but anonymous requests are allowed
+///
+/// A presented key that doesn't resolve is still rejected rather than treated as anonymous.
+#[derive(Debug, Clone)]
+pub struct OptionalUser(pub Option<User>);
+
+impl FromRequest for OptionalUser {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let resolved = resolve_user(req);
+        Box::pin(async move { Ok(OptionalUser(resolved.await?)) })
+    }
+}
+
+// Look up the owner of the bearer key, if one was presented
+fn resolve_user(req: &HttpRequest) -> LocalBoxFuture<'static, Result<Option<User>, AppError>> {
+    // Service tokens were checked by the middleware and act for their owner, if any
+    if let Some(identity) = req.extensions().get::<ServiceTokenIdentity>() {
+        let owner = identity.owner.clone();
+        return Box::pin(async move { Ok(owner) });
+    }
+
+    let token = req
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim().to_string());
+    let service = req.app_data::<web::Data<UserServiceType>>().cloned();
+
+    Box::pin(async move {
+        let Some(token) = token else {
+            return Ok(None);
+        };
+        let service = service
+            .ok_or_else(|| AppError::Internal("User service is not registered".to_string()))?;
+
+        match service.authenticate(&token).await? {
+            Some(user) => Ok(Some(user)),
+            None => Err(AppError::Unauthorized(
+                "Invalid or revoked API key".to_string(),
+            )),
+        }
+    })
+}
@@ -0,0 +1,97 @@
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use futures_util::future::LocalBoxFuture;
+use uuid::Uuid;
+
+use crate::{
+    errors::AppError, handlers::ShortenedUrlServiceType, services::ShortenedUrlServiceTrait,
+};
+
+/// The link named by the `{id}` segment of the request path
+///
+/// Links created with random ids were moved to time-ordered ones; ids clients kept from
+/// before are taken to the link's current id, so they keep working.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkId(pub Uuid);
+
+impl LinkId {
+    pub fn into_inner(self) -> Uuid {
+        self.0
+    }
+}
+
+impl FromRequest for LinkId {
+    type Error = AppError;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let id = req.match_info().get("id").unwrap_or_default();
+        let id = Uuid::parse_str(id)
+            .map_err(|_| AppError::Validation(format!("'{}' is not a valid link ID", id)));
+        let service = req
+            .app_data::<web::Data<ShortenedUrlServiceType>>()
+            .cloned();
+
+        Box::pin(async move {
+            let id = id?;
+            match service {
+                Some(service) => service.resolve_id(id).await.map(LinkId),
+                None => Ok(LinkId(id)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, App, HttpResponse};
+    use sqlx::PgPool;
+
+    use crate::db::testing::{insert_link, url_service};
+
+    #[sqlx::test]
+    async fn test_ids_from_before_the_remap_still_work(pool: PgPool) {
+        let current = insert_link(&pool, "current").await;
+        let old_id: Uuid = sqlx::query_scalar(
+            "INSERT INTO shortened_urls (id, original_url, short_code) \
+             VALUES (gen_random_uuid(), 'https://example.com', 'legacy') RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let new_id: Uuid = sqlx::query_scalar(
+            "UPDATE shortened_urls SET id = uuid_generate_v7_at(created_at) \
+             WHERE id = $1 RETURNING id",
+        )
+        .bind(old_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+
+        let app = test::init_service(
+            App::new()
+                .app_data(web::Data::from(url_service(&pool)))
+                .route(
+                    "/api/urls/{id}",
+                    web::get().to(|id: LinkId| async move {
+                        HttpResponse::Ok().body(id.into_inner().to_string())
+                    }),
+                ),
+        )
+        .await;
+        let resolve = |id: String| test::TestRequest::get().uri(&format!("/api/urls/{}", id));
+
+        for (requested, expected) in [(old_id, new_id), (new_id, new_id), (current, current)] {
+            let response = test::call_service(&app, resolve(requested.to_string()).to_request());
+            let body = test::read_body(response.await).await;
+            assert_eq!(body, expected.to_string());
+        }
+        // Random ids that were never remapped are looked up as they are
+        let unknown = Uuid::new_v4();
+        let body = test::call_and_read_body(&app, resolve(unknown.to_string()).to_request());
+        assert_eq!(body.await, unknown.to_string());
+
+        let response = test::call_service(&app, resolve("nope".to_string()).to_request()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,13 @@
+pub mod api_version;
+pub mod authenticated_user;
+pub mod link_id;
+pub mod tenant;
+pub mod validated_query;
+
+pub use api_version::{
+    ApiVersion, CURRENT_API_VERSION, DEPRECATED_API_VERSIONS, SUPPORTED_API_VERSIONS,
+};
+pub use authenticated_user::{AuthenticatedUser, OptionalUser};
+pub use link_id::LinkId;
+pub use tenant::{CurrentTenant, TENANT_HEADER};
+pub use validated_query::ValidatedQuery;
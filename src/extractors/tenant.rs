@@ -0,0 +1,54 @@
+use std::ops::Deref;
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use futures_util::future::{ready, Ready};
+use uuid::Uuid;
+
+use crate::{errors::AppError, handlers::TenantServiceType, models::Tenant};
+
+/// Request header naming the tenant by slug, taking precedence over the request's host
+pub const TENANT_HEADER: &str = "X-Tenant";
+
+/// The tenant a request belongs to, from the tenant header or the host it was sent to
+///
+/// `None` stands for the default tenant. Naming an unknown tenant in the header is
+/// rejected with `404`.
+#[derive(Debug, Clone)]
+pub struct CurrentTenant(pub Option<Tenant>);
+
+impl CurrentTenant {
+    /// ID the tenant's links are stored under, `None` for the default tenant
+    pub fn id(&self) -> Option<Uuid> {
+        self.0.as_ref().map(|t| t.id)
+    }
+}
+
+impl Deref for CurrentTenant {
+    type Target = Option<Tenant>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl FromRequest for CurrentTenant {
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(resolve_tenant(req).map(CurrentTenant))
+    }
+}
+
+fn resolve_tenant(req: &HttpRequest) -> Result<Option<Tenant>, AppError> {
+    let service = req
+        .app_data::<web::Data<TenantServiceType>>()
+        .ok_or_else(|| AppError::Internal("Tenant service is not registered".to_string()))?;
+
+    let slug = req
+        .headers()
+        .get(TENANT_HEADER)
+        .and_then(|v| v.to_str().ok());
+    let info = req.connection_info();
+    service.resolve(slug, Some(info.host()))
+}
@@ -0,0 +1,57 @@
+use std::ops::{Deref, DerefMut};
+
+use actix_web::{dev::Payload, web, FromRequest, HttpRequest};
+use futures_util::future::{ready, Ready};
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::errors::AppError;
+
+/// Query string extractor that deserializes into `T` and runs its validator rules
+///
+/// Malformed values (e.g. a non-numeric `limit`) and rule violations (e.g. a negative
+/// `offset`) are both rejected with a `400` in the standard error shape, the latter
+/// including the per-field breakdown.
+#[derive(Debug)]
+pub struct ValidatedQuery<T>(pub T);
+
+impl<T> ValidatedQuery<T> {
+    /// Unwrap into the inner value
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ValidatedQuery<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for ValidatedQuery<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> FromRequest for ValidatedQuery<T>
+where
+    T: DeserializeOwned + Validate,
+{
+    type Error = AppError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let result = web::Query::<T>::from_query(req.query_string())
+            .map_err(|e| AppError::Validation(format!("Invalid query parameters: {}", e)))
+            .and_then(|query| {
+                let value = query.into_inner();
+                value.validate()?;
+                Ok(ValidatedQuery(value))
+            });
+
+        ready(result)
+    }
+}
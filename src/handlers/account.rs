@@ -0,0 +1,161 @@
+use actix_web::{
+    http::header::{ContentDisposition, DispositionParam, DispositionType, LOCATION},
+    web, HttpResponse, Responder,
+};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    extractors::AuthenticatedUser,
+    models::{CreateNotificationChannelDto, DeleteAccountParams, UpdateNotificationPreferencesDto},
+    repositories::{AccountRepository, NotificationRepository, UserRepository},
+    services::{
+        AccountService, AccountServiceTrait, NotificationService, NotificationServiceTrait,
+        UserService,
+    },
+    types::Result,
+};
+
+pub type AccountServiceType = AccountService<AccountRepository>;
+pub type UserServiceType = UserService<UserRepository>;
+pub type NotificationServiceType = NotificationService<NotificationRepository>;
+
+/// Start account export route handler
+pub async fn start_export_handler(
+    user: AuthenticatedUser,
+    service: web::Data<AccountServiceType>,
+) -> Result<impl Responder> {
+    let export = service.start_export(&user).await?;
+    let status_url = format!("/api/account/export/{}", export.id);
+    Ok(HttpResponse::Accepted()
+        .insert_header((LOCATION, status_url.clone()))
+        .json(json!({
+            "data": export,
+            "status_url": status_url,
+            "message": "Export started",
+        })))
+}
+
+/// Get account export status route handler
+pub async fn get_export_handler(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<AccountServiceType>,
+) -> Result<impl Responder> {
+    let export = service.get_export(&user, &id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": export,
+        "message": "Successfully retrieved export",
+    })))
+}
+
+/// Download account export archive route handler
+pub async fn download_export_handler(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<AccountServiceType>,
+) -> Result<impl Responder> {
+    let id = id.into_inner();
+    let archive = service.download_export(&user, &id).await?;
+    Ok(HttpResponse::Ok()
+        .content_type("application/gzip")
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: vec![DispositionParam::Filename(format!(
+                "account-export-{}.json.gz",
+                id
+            ))],
+        })
+        .body(archive))
+}
+
+/// Delete account route handler
+pub async fn delete_account_handler(
+    user: AuthenticatedUser,
+    params: web::Query<DeleteAccountParams>,
+    service: web::Data<AccountServiceType>,
+) -> Result<impl Responder> {
+    let report = service
+        .delete_account(&user, params.mode.unwrap_or_default())
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": report,
+        "message": "Successfully deleted account",
+    })))
+}
+
+/// Get notification preferences route handler
+pub async fn get_notification_preferences_handler(
+    user: AuthenticatedUser,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    let preferences = service.get_preferences(&user).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": preferences,
+        "message": "Successfully retrieved notification preferences",
+    })))
+}
+
+/// Update notification preferences route handler
+pub async fn update_notification_preferences_handler(
+    user: AuthenticatedUser,
+    dto: web::Json<UpdateNotificationPreferencesDto>,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    let preferences = service.update_preferences(&user, dto.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": preferences,
+        "message": "Successfully updated notification preferences",
+    })))
+}
+
+/// List notification channels route handler
+pub async fn list_notification_channels_handler(
+    user: AuthenticatedUser,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    let channels = service.list_channels(&user).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": channels,
+        "message": "Successfully retrieved notification channels",
+    })))
+}
+
+/// Create notification channel route handler
+pub async fn create_notification_channel_handler(
+    user: AuthenticatedUser,
+    dto: web::Json<CreateNotificationChannelDto>,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    let channel = service.create_channel(&user, dto.into_inner()).await?;
+    Ok(HttpResponse::Created().json(json!({
+        "data": channel,
+        "message": "Successfully created notification channel",
+    })))
+}
+
+/// Delete notification channel route handler
+pub async fn delete_notification_channel_handler(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    service.delete_channel(&user, &id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": null,
+        "message": "Successfully deleted notification channel",
+    })))
+}
+
+/// Send a test event to a notification channel route handler
+pub async fn test_notification_channel_handler(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    service.test_channel(&user, &id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": null,
+        "message": "Test notification delivered",
+    })))
+}
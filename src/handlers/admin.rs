@@ -0,0 +1,401 @@
+use actix_web::{
+    http::header::{CACHE_CONTROL, CONTENT_SECURITY_POLICY},
+    web, HttpResponse, Responder,
+};
+use log::warn;
+use serde_json::json;
+use validator::Validate;
+
+use super::{ShortenedUrlServiceType, UserServiceType, PAGE_LIMIT_HEADER};
+use crate::{
+    config::Config,
+    dashboard,
+    errors::AppError,
+    extractors::{CurrentTenant, LinkId, ValidatedQuery},
+    jobs::JobScheduler,
+    live_config::{LiveConfig, RELOADABLE_KEYS},
+    models::{
+        BlockIpDto, CreateTenantDto, CreateUserDto, DryRunParams, LinkFlagQueryParams,
+        ShortenedUrlResponseDto, Snapshot, UpdateAppLinksDto, UpdateBrandingDto,
+        UpdateLinkStatusDto, UpdateMaintenanceDto,
+    },
+    repositories::{
+        AnalyticsRepository, AppLinksRepository, BlockedIpRepository, BrandingRepository,
+        JobRepository, LinkFlagRepository, ShortenedUrlRepository, SnapshotRepository,
+        TenantRepository,
+    },
+    services::{
+        AppLinksService, AppLinksServiceTrait, BackupService, BrandingService,
+        BrandingServiceTrait, LinkFlagService, LinkFlagServiceTrait, ScannerGuardService,
+        ShortenedUrlServiceTrait, SnapshotService, TenantService, UserServiceTrait,
+    },
+    system_info::{config_summary, feature_flags, BuildInfo, SystemInfo},
+    types::{AppState, Result},
+    utils::maintenance::MaintenanceMode,
+};
+
+pub type BackupServiceType = BackupService<ShortenedUrlRepository, AnalyticsRepository>;
+pub type LinkFlagServiceType = LinkFlagService<LinkFlagRepository>;
+pub type ScannerGuardServiceType = ScannerGuardService<BlockedIpRepository>;
+pub type TenantServiceType = TenantService<TenantRepository>;
+pub type BrandingServiceType = BrandingService<BrandingRepository>;
+pub type AppLinksServiceType = AppLinksService<AppLinksRepository>;
+pub type JobSchedulerType = JobScheduler<JobRepository>;
+pub type SnapshotServiceType = SnapshotService<SnapshotRepository>;
+
+/// Trigger backup route handler
+pub async fn trigger_backup_handler(
+    service: web::Data<BackupServiceType>,
+) -> Result<impl Responder> {
+    let report = service.run_backup().await?;
+    Ok(HttpResponse::Created().json(json!({
+        "data": report,
+        "message": "Successfully created backup",
+    })))
+}
+
+/// Take snapshot route handler
+pub async fn take_snapshot_handler(
+    service: web::Data<SnapshotServiceType>,
+) -> Result<impl Responder> {
+    let snapshot = service.take().await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": snapshot,
+        "message": "Successfully took snapshot",
+    })))
+}
+
+/// Restore snapshot route handler
+pub async fn restore_snapshot_handler(
+    query: ValidatedQuery<DryRunParams>,
+    snapshot: web::Json<Snapshot>,
+    service: web::Data<SnapshotServiceType>,
+    tenants: web::Data<TenantServiceType>,
+    scanner_guard: web::Data<ScannerGuardServiceType>,
+) -> Result<impl Responder> {
+    let dry_run = query.into_inner().dry_run;
+    let report = service.restore(snapshot.into_inner(), dry_run).await?;
+    let message = match (dry_run, report.conflicts.is_empty()) {
+        (true, true) => "Dry run: snapshot would be restored",
+        (true, false) => "Dry run: snapshot clashes with data of this instance",
+        (false, _) => {
+            // Restored tenants and blocks would otherwise only apply after the next reload
+            tenants.refresh().await?;
+            scanner_guard.refresh().await?;
+            "Successfully restored snapshot"
+        }
+    };
+    Ok(HttpResponse::Ok().json(json!({
+        "data": report,
+        "message": message,
+    })))
+}
+
+/// Create user route handler
+pub async fn create_user_handler(
+    dto: web::Json<CreateUserDto>,
+    service: web::Data<UserServiceType>,
+) -> Result<impl Responder> {
+    let (user, key, secret) = service.create(dto.into_inner()).await?;
+    Ok(HttpResponse::Created().json(json!({
+        "data": {
+            "user": user,
+            "api_key": key,
+            "api_key_secret": secret,
+        },
+        "message": "Successfully created user, store the API key secret now as it won't be shown again",
+    })))
+}
+
+/// List flagged links route handler
+pub async fn list_flags_handler(
+    query: ValidatedQuery<LinkFlagQueryParams>,
+    service: web::Data<LinkFlagServiceType>,
+) -> Result<impl Responder> {
+    let page = service.list_flags(query.into_inner()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header((PAGE_LIMIT_HEADER, page.limit))
+        .json(json!({
+            "data": page.items,
+            "message": "Successfully retrieved flags",
+        })))
+}
+
+/// Resolve flag route handler
+pub async fn resolve_flag_handler(
+    id: web::Path<i64>,
+    service: web::Data<LinkFlagServiceType>,
+) -> Result<impl Responder> {
+    let flag = service.resolve_flag(id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": flag,
+        "message": "Successfully resolved flag",
+    })))
+}
+
+/// Update link status route handler
+pub async fn update_link_status_handler(
+    id: LinkId,
+    dto: web::Json<UpdateLinkStatusDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let url = service.set_status(&id.into_inner(), dto.status).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": ShortenedUrlResponseDto::from(url),
+        "message": "Successfully updated link status",
+    })))
+}
+
+/// Admin stats route handler, covering the trash of every tenant for now
+pub async fn admin_stats_handler(
+    service: web::Data<ShortenedUrlServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    let trash = service.trash_stats(config.trash.retention_days).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": { "trash": trash },
+        "message": "Successfully retrieved stats",
+    })))
+}
+
+/// System info route handler
+///
+/// Build, settings with secrets redacted, connection pool, cache and jobs of the instance
+/// answering, for debugging deployments.
+pub async fn system_info_handler(
+    state: web::Data<AppState>,
+    live_config: web::Data<LiveConfig>,
+) -> Result<impl Responder> {
+    let config = live_config.current();
+    let info = SystemInfo {
+        build: BuildInfo::current(&state.version),
+        config: config_summary(&config),
+        features: feature_flags(&config),
+        db_pool: state.db.pool_stats(),
+        link_cache: state.link_cache.stats(),
+        jobs: state.job_scheduler.list().await?,
+        uptime_seconds: state.start_time.elapsed().as_secs(),
+    };
+    Ok(HttpResponse::Ok().json(json!({
+        "data": info,
+        "message": "Successfully retrieved system info",
+    })))
+}
+
+/// Get effective config route handler
+pub async fn get_config_handler(live_config: web::Data<LiveConfig>) -> Result<impl Responder> {
+    Ok(HttpResponse::Ok().json(json!({
+        "data": {
+            "config": config_summary(&live_config.current()),
+            "reloadable": RELOADABLE_KEYS,
+            "last_reload": live_config.last_reload(),
+        },
+        "message": "Successfully retrieved config",
+    })))
+}
+
+/// Reload config file route handler
+pub async fn reload_config_handler(live_config: web::Data<LiveConfig>) -> Result<impl Responder> {
+    let report = live_config.reload()?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": report,
+        "message": "Successfully reloaded config",
+    })))
+}
+
+/// Get maintenance mode route handler
+pub async fn get_maintenance_handler(
+    maintenance: web::Data<MaintenanceMode>,
+) -> Result<impl Responder> {
+    Ok(HttpResponse::Ok().json(json!({
+        "data": maintenance.status(),
+        "message": "Successfully retrieved maintenance mode",
+    })))
+}
+
+/// Switch maintenance mode route handler
+pub async fn update_maintenance_handler(
+    dto: web::Json<UpdateMaintenanceDto>,
+    maintenance: web::Data<MaintenanceMode>,
+) -> Result<impl Responder> {
+    let dto = dto.into_inner();
+    dto.validate()?;
+    let status = maintenance.set(dto.enabled, dto.message);
+    if status.enabled {
+        warn!("Maintenance mode switched on: {}", status.message);
+    } else {
+        warn!("Maintenance mode switched off");
+    }
+    Ok(HttpResponse::Ok().json(json!({
+        "data": status,
+        "message": format!(
+            "Successfully switched maintenance mode {}",
+            if status.enabled { "on" } else { "off" }
+        ),
+    })))
+}
+
+/// List blocked IPs route handler
+pub async fn list_blocked_ips_handler(
+    service: web::Data<ScannerGuardServiceType>,
+) -> Result<impl Responder> {
+    let blocks = service.list_blocks().await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": blocks,
+        "message": "Successfully retrieved blocked IPs",
+    })))
+}
+
+/// Block IP route handler
+pub async fn block_ip_handler(
+    dto: web::Json<BlockIpDto>,
+    service: web::Data<ScannerGuardServiceType>,
+) -> Result<impl Responder> {
+    let block = service.block(dto.into_inner()).await?;
+    Ok(HttpResponse::Created().json(json!({
+        "data": block,
+        "message": "Successfully blocked IP",
+    })))
+}
+
+/// Unblock IP route handler
+pub async fn unblock_ip_handler(
+    ip: web::Path<String>,
+    service: web::Data<ScannerGuardServiceType>,
+) -> Result<impl Responder> {
+    service.unblock(&ip.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": null,
+        "message": "Successfully unblocked IP",
+    })))
+}
+
+/// List tenants route handler
+pub async fn list_tenants_handler(service: web::Data<TenantServiceType>) -> Result<impl Responder> {
+    let tenants = service.list().await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": tenants,
+        "message": "Successfully retrieved tenants",
+    })))
+}
+
+/// Create tenant route handler
+pub async fn create_tenant_handler(
+    dto: web::Json<CreateTenantDto>,
+    service: web::Data<TenantServiceType>,
+) -> Result<impl Responder> {
+    let tenant = service.create(dto.into_inner()).await?;
+    Ok(HttpResponse::Created().json(json!({
+        "data": tenant,
+        "message": "Successfully created tenant",
+    })))
+}
+
+/// Get branding route handler
+pub async fn get_branding_handler(
+    tenant: CurrentTenant,
+    service: web::Data<BrandingServiceType>,
+) -> Result<impl Responder> {
+    let branding = service.get(tenant.id()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": branding,
+        "message": "Successfully retrieved branding",
+    })))
+}
+
+/// Update branding route handler
+pub async fn update_branding_handler(
+    tenant: CurrentTenant,
+    dto: web::Json<UpdateBrandingDto>,
+    service: web::Data<BrandingServiceType>,
+) -> Result<impl Responder> {
+    let branding = service.update(tenant.id(), dto.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": branding,
+        "message": "Successfully updated branding",
+    })))
+}
+
+/// Get app links route handler
+pub async fn get_app_links_handler(
+    tenant: CurrentTenant,
+    service: web::Data<AppLinksServiceType>,
+) -> Result<impl Responder> {
+    let app_links = service.get(tenant.id()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": app_links,
+        "message": "Successfully retrieved app links",
+    })))
+}
+
+/// Update app links route handler
+pub async fn update_app_links_handler(
+    tenant: CurrentTenant,
+    dto: web::Json<UpdateAppLinksDto>,
+    service: web::Data<AppLinksServiceType>,
+) -> Result<impl Responder> {
+    let app_links = service.update(tenant.id(), dto.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": app_links,
+        "message": "Successfully updated app links",
+    })))
+}
+
+/// List jobs route handler
+pub async fn list_jobs_handler(scheduler: web::Data<JobSchedulerType>) -> Result<impl Responder> {
+    let jobs = scheduler.list().await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": jobs,
+        "message": "Successfully retrieved jobs",
+    })))
+}
+
+/// Run job route handler
+pub async fn run_job_handler(
+    name: web::Path<String>,
+    scheduler: web::Data<JobSchedulerType>,
+) -> Result<impl Responder> {
+    let name = name.into_inner();
+    scheduler.into_inner().trigger(&name).await?;
+    Ok(HttpResponse::Accepted().json(json!({
+        "message": format!("Started job '{}'", name),
+    })))
+}
+
+// Scripts and styles only come from the dashboard itself, and it can't be framed
+const DASHBOARD_CSP: &str = "default-src 'self'; frame-ancestors 'none'";
+
+// Without an admin token nobody could sign in, so the dashboard isn't served either
+fn ensure_dashboard_enabled(config: &Config) -> Result<()> {
+    if config.auth.admin_dashboard && config.auth.admin_token.is_some() {
+        Ok(())
+    } else {
+        Err(AppError::NotFound(
+            "Admin dashboard is disabled".to_string(),
+        ))
+    }
+}
+
+/// Admin dashboard page route handler
+pub async fn dashboard_handler(config: web::Data<Config>) -> Result<impl Responder> {
+    ensure_dashboard_enabled(&config)?;
+    Ok(HttpResponse::Ok()
+        .content_type("text/html; charset=utf-8")
+        .insert_header((CONTENT_SECURITY_POLICY, DASHBOARD_CSP))
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        .body(dashboard::index_page(&config.csrf.cookie_name)))
+}
+
+/// Admin dashboard script and stylesheet route handler
+pub async fn dashboard_asset_handler(
+    file: web::Path<String>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    ensure_dashboard_enabled(&config)?;
+    let asset = dashboard::asset(&file)
+        .ok_or_else(|| AppError::NotFound(format!("No dashboard file '{}'", file)))?;
+    Ok(HttpResponse::Ok()
+        .content_type(asset.content_type)
+        .insert_header((CACHE_CONTROL, "no-cache"))
+        .body(asset.body))
+}
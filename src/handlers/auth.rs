@@ -0,0 +1,152 @@
+use actix_web::{
+    cookie::{time::Duration, Cookie, SameSite},
+    http::header::LOCATION,
+    web, HttpRequest, HttpResponse, Responder,
+};
+use serde_json::json;
+
+use crate::{
+    config::Config,
+    extractors::OptionalUser,
+    models::{OAuthCallbackParams, OAuthProvider},
+    repositories::{OAuthRepository, UserRepository},
+    services::{OAuthService, OAuthServiceTrait},
+    types::Result,
+};
+
+pub type OAuthServiceType = OAuthService<OAuthRepository, UserRepository>;
+
+/// Cookie tying a login to the browser that started it
+pub const OAUTH_NONCE_COOKIE: &str = "oauth_nonce";
+
+// Only sent back to the callback, which providers reach with a top-level GET
+fn nonce_cookie(value: String) -> Cookie<'static> {
+    Cookie::build(OAUTH_NONCE_COOKIE, value)
+        .path("/api/auth")
+        .http_only(true)
+        .same_site(SameSite::Lax)
+        .finish()
+}
+
+/// Start social login route handler
+///
+/// Callers presenting an API key link the provider account to themselves instead.
+pub async fn oauth_login_handler(
+    user: OptionalUser,
+    provider: web::Path<OAuthProvider>,
+    service: web::Data<OAuthServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    let link_user = user.0.map(|u| u.id);
+    let (location, nonce) = service.start(provider.into_inner(), link_user).await?;
+    let mut cookie = nonce_cookie(nonce);
+    cookie.set_secure(config.oauth.secure_cookie);
+    cookie.set_max_age(Duration::seconds(config.oauth.state_ttl_seconds as i64));
+    Ok(HttpResponse::Found()
+        .insert_header((LOCATION, location))
+        .cookie(cookie)
+        .finish())
+}
+
+/// Social login callback route handler
+pub async fn oauth_callback_handler(
+    req: HttpRequest,
+    provider: web::Path<OAuthProvider>,
+    query: web::Query<OAuthCallbackParams>,
+    service: web::Data<OAuthServiceType>,
+) -> Result<impl Responder> {
+    let nonce = req.cookie(OAUTH_NONCE_COOKIE);
+    let (user, key, secret) = service
+        .complete(
+            provider.into_inner(),
+            query.into_inner(),
+            nonce.as_ref().map(Cookie::value),
+        )
+        .await?;
+
+    // The login is done, the nonce has nothing left to protect
+    let mut cookie = nonce_cookie(String::new());
+    cookie.make_removal();
+    Ok(HttpResponse::Ok().cookie(cookie).json(json!({
+        "data": {
+            "user": user,
+            "api_key": key,
+            "api_key_secret": secret,
+        },
+        "message": "Successfully signed in, store the API key secret now as it won't be shown again",
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use actix_web::{http::StatusCode, test, App};
+    use sqlx::PgPool;
+    use url::Url;
+
+    use super::*;
+    use crate::{
+        config::{HttpClientConfig, OAuthClientConfig, OAuthConfig},
+        db::Database,
+        utils::http_client::HttpClient,
+    };
+
+    fn oauth_service(pool: &PgPool) -> OAuthServiceType {
+        let config = OAuthConfig {
+            redirect_base_url: "https://sho.rt".to_string(),
+            state_ttl_seconds: 600,
+            secure_cookie: false,
+            github: Some(OAuthClientConfig {
+                client_id: "client-1".to_string(),
+                client_secret: "secret".to_string(),
+            }),
+            google: None,
+        };
+        let client = HttpClient::new(&HttpClientConfig {
+            timeout_ms: 1000,
+            connect_timeout_ms: 1000,
+            pool_max_idle_per_host: 1,
+            max_retries: 0,
+            retry_base_delay_ms: 10,
+            retry_max_delay_ms: 10,
+        })
+        .unwrap();
+        OAuthService::new(
+            Arc::new(OAuthRepository::new(Database::from_pool(pool.clone()))),
+            Arc::new(UserRepository::new(Database::from_pool(pool.clone()))),
+            config,
+            Arc::new(client),
+        )
+    }
+
+    #[sqlx::test]
+    async fn test_callback_needs_the_browser_that_started_the_login(pool: PgPool) {
+        let service = web::Data::new(oauth_service(&pool));
+        let (location, _nonce) = service.start(OAuthProvider::GitHub, None).await.unwrap();
+        let state = Url::parse(&location)
+            .unwrap()
+            .query_pairs()
+            .find(|(name, _)| name == "state")
+            .map(|(_, state)| state.into_owned())
+            .unwrap();
+        let app = test::init_service(App::new().app_data(service.clone()).route(
+            "/api/auth/{provider}/callback",
+            web::get().to(oauth_callback_handler),
+        ))
+        .await;
+        let callback = format!("/api/auth/github/callback?state={state}&code=code-1");
+
+        // Someone else's browser, sent the callback with a state it never started
+        let requests = [
+            test::TestRequest::get().uri(&callback),
+            test::TestRequest::get()
+                .uri(&callback)
+                .cookie(Cookie::new(OAUTH_NONCE_COOKIE, "guessed")),
+        ];
+        for request in requests {
+            let response = test::call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+    }
+}
@@ -1,3 +1,15 @@
+mod account;
+mod admin;
+mod auth;
+mod saved_search;
+mod service_token;
 mod shortened_url;
+mod stats;
 
+pub use account::*;
+pub use admin::*;
+pub use auth::*;
+pub use saved_search::*;
+pub use service_token::*;
 pub use shortened_url::*;
+pub use stats::*;
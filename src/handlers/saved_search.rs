@@ -0,0 +1,81 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    extractors::CurrentTenant,
+    models::SaveSearchDto,
+    repositories::SavedSearchRepository,
+    services::{SavedSearchService, SavedSearchServiceTrait},
+    types::Result,
+};
+
+pub type SavedSearchServiceType = SavedSearchService<SavedSearchRepository>;
+
+/// List saved searches route handler
+pub async fn list_saved_searches_handler(
+    tenant: CurrentTenant,
+    service: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    let searches = service.list(tenant.id()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": searches,
+        "message": "Successfully retrieved saved searches",
+    })))
+}
+
+/// Get saved search by ID route handler
+pub async fn get_saved_search_handler(
+    tenant: CurrentTenant,
+    id: web::Path<Uuid>,
+    service: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    let search = service.get(&id.into_inner(), tenant.id()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": search,
+        "message": "Successfully retrieved saved search",
+    })))
+}
+
+/// Create saved search route handler
+pub async fn create_saved_search_handler(
+    tenant: CurrentTenant,
+    dto: web::Json<SaveSearchDto>,
+    service: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    let search = service.create(tenant.id(), dto.into_inner()).await?;
+    Ok(HttpResponse::Created().json(json!({
+        "data": search,
+        "message": "Successfully saved search",
+    })))
+}
+
+/// Replace saved search route handler
+pub async fn update_saved_search_handler(
+    tenant: CurrentTenant,
+    id: web::Path<Uuid>,
+    dto: web::Json<SaveSearchDto>,
+    service: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    let search = service
+        .update(&id.into_inner(), tenant.id(), dto.into_inner())
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": search,
+        "message": "Successfully updated saved search",
+    })))
+}
+
+/// Delete saved search route handler
+pub async fn delete_saved_search_handler(
+    tenant: CurrentTenant,
+    id: web::Path<Uuid>,
+    service: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    let id = id.into_inner();
+    service.delete(&id, tenant.id()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "deleted_id": &id,
+        "message": format!("Successfully deleted saved search with ID '{}'", id),
+    })))
+}
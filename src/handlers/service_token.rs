@@ -0,0 +1,51 @@
+use actix_web::{web, HttpResponse, Responder};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    models::CreateServiceTokenDto,
+    repositories::ServiceTokenRepository,
+    services::{ServiceTokenService, ServiceTokenServiceTrait},
+    types::Result,
+};
+
+pub type ServiceTokenServiceType = ServiceTokenService<ServiceTokenRepository>;
+
+/// List service tokens route handler
+pub async fn list_service_tokens_handler(
+    service: web::Data<ServiceTokenServiceType>,
+) -> Result<impl Responder> {
+    let tokens = service.list().await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": tokens,
+        "message": "Successfully retrieved service tokens",
+    })))
+}
+
+/// Create service token route handler
+pub async fn create_service_token_handler(
+    dto: web::Json<CreateServiceTokenDto>,
+    service: web::Data<ServiceTokenServiceType>,
+) -> Result<impl Responder> {
+    let (token, secret) = service.create(dto.into_inner()).await?;
+    Ok(HttpResponse::Created().json(json!({
+        "data": {
+            "token": token,
+            "secret": secret,
+        },
+        "message": "Successfully created service token, store the secret now as it won't be shown again",
+    })))
+}
+
+/// Revoke service token route handler
+pub async fn revoke_service_token_handler(
+    id: web::Path<Uuid>,
+    service: web::Data<ServiceTokenServiceType>,
+) -> Result<impl Responder> {
+    let id = id.into_inner();
+    service.revoke(&id).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "revoked_id": &id,
+        "message": format!("Successfully revoked service token with ID '{}'", id),
+    })))
+}
@@ -1,25 +1,106 @@
-use actix_web::{http::header::LOCATION, web, HttpResponse, Responder};
+use actix_web::{
+    body::SizedStream,
+    http::header::{
+        ContentType, ETag, EntityTag, HeaderName, IfMatch, ACCEPT, CACHE_CONTROL, CONTENT_LANGUAGE,
+        DNT, IF_MATCH, LOCATION, REFERER, RETRY_AFTER, USER_AGENT, VARY, X_CONTENT_TYPE_OPTIONS,
+    },
+    http::StatusCode,
+    web, FromRequest, HttpMessage, HttpRequest, HttpResponse, Responder, ResponseError,
+};
 use chrono::Utc;
-use log::{debug, info};
+use futures::StreamExt;
+use log::{debug, info, warn};
 use serde_json::json;
 use uuid::Uuid;
 
+use super::{AppLinksServiceType, BrandingServiceType, SavedSearchServiceType, TenantServiceType};
 use crate::{
-    errors::AppError,
-    types::Result,
-    models::{CreateShortenedUrlDto, ShortenedUrlQueryParams, ShortenedUrlUpdateParams},
-    repositories::ShortenedUrlRepository,
-    services::{ShortenedUrlService, ShortenedUrlServiceTrait},
+    cache::LinkRateLimiter,
+    config::{AnalyticsConfig, Config},
+    errors::{retry_after_secs, AppError, ErrorCode},
+    extractors::{AuthenticatedUser, CurrentTenant, LinkId, OptionalUser, ValidatedQuery},
+    i18n::request_locale,
+    middleware::presents_admin_token,
+    models::{
+        Branding, BulkDeleteUrlsDto, BulkItemResult, BulkUpdateUrlsDto, ClaimLinkDto,
+        CreateShortenedUrlDto, DryRunParams, DuplicateShortenedUrlDto, ExpiringQueryParams,
+        LinkBehavior, LinkStatus, NewUrlVisit, ResolveCodesDto, ServiceTokenIdentity, ShortenedUrl,
+        ShortenedUrlQueryParams, ShortenedUrlResponseDto, ShortenedUrlUpdateParams, TenantScope,
+        TransferLinkDto, TrashedUrlDto,
+    },
+    pages::{app_link_page, error_page, home_page, preview_page, social_page, warning_page},
+    repositories::{AnalyticsRepository, CircuitBreakerRepository, ShortenedUrlRepository},
+    services::{
+        AnalyticsService, AnalyticsServiceTrait, AppLinksServiceTrait, BrandingServiceTrait,
+        CaptchaService, SavedSearchServiceTrait, ShortenedUrlService, ShortenedUrlServiceTrait,
+    },
+    types::{ResponsePayload, Result},
+    utils::{
+        file_proxy::{proxied_headers, FileProxy},
+        https_upgrade::HttpsUpgrader,
+        maintenance::MaintenanceMode,
+        network::client_ip,
+        rate_limiter::RateLimiter,
+        redirect_loop::LoopGuard,
+        reverse_proxy::ReverseProxy,
+        url_rewrite::{append_path, merge_query},
+        user_agent::is_link_preview_crawler,
+    },
 };
 
-pub type ShortenedUrlServiceType = ShortenedUrlService<ShortenedUrlRepository>;
+pub type ShortenedUrlServiceType =
+    ShortenedUrlService<CircuitBreakerRepository<ShortenedUrlRepository>>;
+pub type AnalyticsServiceType = AnalyticsService<AnalyticsRepository>;
+
+/// Response header with the page size a listing applied, lower than requested when clamped
+pub const PAGE_LIMIT_HEADER: &str = "X-Page-Limit";
+
+/// Request header carrying the response token of a solved CAPTCHA
+pub const CAPTCHA_HEADER: &str = "x-captcha-token";
 
 /// Create shortened URL route handler
 pub async fn create_handler(
+    req: HttpRequest,
+    user: OptionalUser,
+    tenant: CurrentTenant,
     dto: web::Json<CreateShortenedUrlDto>,
     service: web::Data<ShortenedUrlServiceType>,
+    limiter: web::Data<RateLimiter>,
+    captcha: web::Data<CaptchaService>,
 ) -> Result<impl Responder> {
-    let url = service.create(dto.into_inner()).await?;
+    check_anonymous(&req, &user, &limiter, &captcha).await?;
+
+    let mut dto = dto.into_inner();
+    dto.client_ip = client_ip(&req);
+
+    // Plain http destinations move to https when the link or the instance asks for it
+    // and the host serves it
+    if let Some(upgrader) = req.app_data::<web::Data<HttpsUpgrader>>() {
+        if dto.upgrade_https.unwrap_or(upgrader.by_default()) {
+            if let Some(upgraded) = upgrader.upgrade(&dto.original_url).await {
+                debug!("Upgraded '{}' to '{}'", dto.original_url, upgraded);
+                dto.original_url = upgraded;
+            }
+        }
+    }
+
+    if dto.behavior == Some(LinkBehavior::ReverseProxy) {
+        check_reverse_proxy(&req, &dto.original_url)?;
+    }
+    check_redirect_loop(&req, &dto.original_url)?;
+
+    let owner = user.0.map(|u| u.id);
+    // Read here rather than as an argument, which would be one too many
+    let query = ValidatedQuery::<DryRunParams>::extract(&req).await?;
+    if query.dry_run {
+        let url = service.preview_create(dto, owner, tenant.id()).await?;
+        return Ok(HttpResponse::Ok().json(json!({
+            "data": url,
+            "message": "Dry run: URL would be created",
+        })));
+    }
+
+    let url = service.create(dto, owner, tenant.id()).await?;
     Ok(HttpResponse::Created().json(json!({
         "data": url,
         "message": "Successfully created URL",
@@ -28,100 +109,1304 @@ pub async fn create_handler(
 
 /// Get all URLs route handler
 pub async fn get_all_handler(
-    query: web::Query<ShortenedUrlQueryParams>,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
     service: web::Data<ShortenedUrlServiceType>,
+    presets: web::Data<SavedSearchServiceType>,
 ) -> Result<impl Responder> {
-    let urls = service.get_all(query.limit, query.offset).await?;
-    Ok(HttpResponse::Ok().json(json!({
-        "data": urls,
-        "message": "Successfully retrieved URLs",
-    })))
+    // A preset, pinned or archived links turn the listing into a search with their filters
+    let page = if query.preset.is_some()
+        || query.favorites.is_some()
+        || query.include_archived.is_some()
+    {
+        let query = presets
+            .apply(tenant.id(), scoped(query, &tenant, &user)?)
+            .await?;
+        service.get_by_query(&query).await?
+    } else {
+        service
+            .get_all(query.limit, query.offset, tenant.id())
+            .await?
+    };
+    let page = page.map(ShortenedUrlResponseDto::from);
+    Ok(HttpResponse::Ok()
+        .insert_header((PAGE_LIMIT_HEADER, page.limit))
+        .json(json!({
+            "data": page.items,
+            "message": "Successfully retrieved URLs",
+        })))
 }
 
 /// Get URLs by query route handler
 pub async fn get_by_query_handler(
-    query: web::Query<ShortenedUrlQueryParams>,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
+    service: web::Data<ShortenedUrlServiceType>,
+    presets: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    let query = presets
+        .apply(tenant.id(), scoped(query, &tenant, &user)?)
+        .await?;
+    let page = service.get_by_query(&query).await?;
+    let page = page.map(ShortenedUrlResponseDto::from);
+    Ok(HttpResponse::Ok()
+        .insert_header((PAGE_LIMIT_HEADER, page.limit))
+        .json(json!({
+            "data": page.items,
+            "message": "Successfully retrieved URLs",
+        })))
+}
+
+/// Count URLs route handler
+pub async fn count_handler(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
     service: web::Data<ShortenedUrlServiceType>,
+    presets: web::Data<SavedSearchServiceType>,
 ) -> Result<impl Responder> {
-    let urls = service.get_by_query(&query.into_inner()).await?;
+    let query = presets
+        .apply(tenant.id(), scoped(query, &tenant, &user)?)
+        .await?;
+    let count = service.count(&query).await?;
     Ok(HttpResponse::Ok().json(json!({
-        "data": urls,
-        "message": "Successfully retrieved URLs",
+        "data": { "count": count },
+        "message": "Successfully counted URLs",
     })))
 }
 
-/// Get URL by ID route handler
-pub async fn get_by_id_handler(
-    id: web::Path<Uuid>,
+/// Export URLs route handler
+///
+/// Streams every matching URL as newline-delimited JSON, one object per line, without
+/// loading them all into memory. Errors before the first row get a regular error response;
+/// a later one cuts the response short, so clients should check the body was complete.
+pub async fn export_handler(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
+    service: web::Data<ShortenedUrlServiceType>,
+    presets: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    let query = presets
+        .apply(tenant.id(), scoped(query, &tenant, &user)?)
+        .await?;
+    let mut rows = service.export(query);
+    let first = rows.next().await.transpose()?;
+
+    let lines = futures::stream::iter(first.map(Ok)).chain(rows).map(|row| {
+        let url =
+            ShortenedUrlResponseDto::from(row.inspect_err(|e| warn!("URL export aborted: {}", e))?);
+        let mut line = serde_json::to_vec(&url).map_err(|e| AppError::Internal(e.to_string()))?;
+        line.push(b'\n');
+        Ok::<_, AppError>(web::Bytes::from(line))
+    });
+
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(lines))
+}
+
+/// Batch resolve short codes route handler
+pub async fn resolve_codes_handler(
+    tenant: CurrentTenant,
+    dto: web::Json<ResolveCodesDto>,
     service: web::Data<ShortenedUrlServiceType>,
 ) -> Result<impl Responder> {
-    let url = service.get_by_id(&id.into_inner()).await?;
+    let resolved = service.resolve_codes(dto.into_inner(), tenant.id()).await?;
     Ok(HttpResponse::Ok().json(json!({
-        "data": url,
-        "message": "Successfully retrieved URL",
+        "data": resolved,
+        "message": "Successfully resolved codes",
     })))
 }
 
+/// Get URL by ID route handler
+pub async fn get_by_id_handler(
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let details = service.get_details(&id.into_inner(), tenant.id()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(version_tag(details.url.version)))
+        .json(json!({
+            "data": details,
+            "message": "Successfully retrieved URL",
+        })))
+}
+
 /// Update URL route handler
 pub async fn update_handler(
-    id: web::Path<Uuid>,
+    req: HttpRequest,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    query: ValidatedQuery<DryRunParams>,
     params: web::Json<ShortenedUrlUpdateParams>,
     service: web::Data<ShortenedUrlServiceType>,
 ) -> Result<impl Responder> {
-    let url = service.update(&id.into_inner(), params.into_inner()).await?;
+    let id = id.into_inner();
+    let mut params = params.into_inner();
+
+    // The base version may come from the ETag of a previous read
+    if let Some(version) = if_match_version(&req)? {
+        if params.version.is_some_and(|v| v != version) {
+            return Err(AppError::Validation(
+                "If-Match and 'version' disagree".to_string(),
+            ));
+        }
+        params.version = Some(version);
+    }
+
+    if let Some(destination) = &params.original_url {
+        check_redirect_loop(&req, destination)?;
+    }
+
+    // Reverse proxied links may only be pointed at allowed hosts
+    if params.behavior == Some(LinkBehavior::ReverseProxy) || params.original_url.is_some() {
+        let current = service.get_by_id(&id, tenant.id()).await?;
+        if params.behavior.unwrap_or(current.behavior) == LinkBehavior::ReverseProxy {
+            let destination = params
+                .original_url
+                .as_deref()
+                .unwrap_or(&current.original_url);
+            check_reverse_proxy(&req, destination)?;
+        }
+    }
+
+    // Nothing changed, so there's no new version to tag
+    if query.dry_run {
+        let user_id = user.0.as_ref().map(|u| u.id);
+        let details = service
+            .preview_update(&id, params, user_id, tenant.id())
+            .await?;
+        return Ok(HttpResponse::Ok().json(json!({
+            "data": details,
+            "message": "Dry run: URL would be updated",
+        })));
+    }
+
+    let changed_by = user.0.map(|u| u.id);
+    service.update(&id, params, changed_by, tenant.id()).await?;
+
+    let details = service.get_details(&id, tenant.id()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(version_tag(details.url.version)))
+        .json(json!({
+            "data": details,
+            "message": "Successfully updated URL",
+        })))
+}
+
+/// URL destination history route handler
+pub async fn history_handler(
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let revisions = service.get_history(&id.into_inner(), tenant.id()).await?;
     Ok(HttpResponse::Ok().json(json!({
+        "data": revisions,
+        "message": "Successfully retrieved URL history",
+    })))
+}
+
+/// Revert URL destination route handler
+pub async fn revert_handler(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    path: web::Path<(Uuid, i32)>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let id = id.into_inner();
+    let (_, revision) = path.into_inner();
+    let changed_by = user.0.map(|u| u.id);
+    let url = service
+        .revert(&id, revision, changed_by, tenant.id())
+        .await?;
+    Ok(HttpResponse::Ok()
+        .insert_header(ETag(version_tag(url.version)))
+        .json(json!({
+            "data": ShortenedUrlResponseDto::from(url),
+            "message": format!("Successfully reverted URL to revision {}", revision),
+        })))
+}
+
+/// Duplicate URL route handler
+pub async fn duplicate_handler(
+    req: HttpRequest,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    dto: web::Json<DuplicateShortenedUrlDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+    limiter: web::Data<RateLimiter>,
+) -> Result<impl Responder> {
+    // A copy is a new link, made under the same limits as creating one. The CAPTCHA
+    // service is read here rather than as an argument, which would be one too many
+    let captcha = web::Data::<CaptchaService>::extract(&req)
+        .await
+        .map_err(|err| AppError::Internal(err.to_string()))?;
+    check_anonymous(&req, &user, &limiter, &captcha).await?;
+
+    let owner = user.0.map(|u| u.id);
+    let url = service
+        .duplicate(&id.into_inner(), dto.into_inner(), owner, tenant.id())
+        .await?;
+    Ok(HttpResponse::Created().json(json!({
         "data": url,
-        "message": "Successfully retrieved URL",
+        "message": "Successfully duplicated URL",
     })))
 }
 
 /// Delete URL route handler
 pub async fn delete_handler(
-    id: web::Path<Uuid>,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
     service: web::Data<ShortenedUrlServiceType>,
 ) -> Result<impl Responder> {
     let id = id.into_inner();
-    let _ = service.delete(&id).await?;
+    let user_id = user.0.map(|u| u.id);
+    let _ = service.delete(&id, user_id, tenant.id()).await?;
     Ok(HttpResponse::Ok().json(json!({
         "deleted_id": &id,
         "message": format!("Successfully deleted URL with ID '{}'", id),
     })))
 }
 
+/// Trash listing route handler
+pub async fn trash_handler(
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
+    service: web::Data<ShortenedUrlServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    let page = service
+        .get_trash(query.limit, query.offset, tenant.id())
+        .await?;
+    let page = page.map(|trashed| TrashedUrlDto::new(trashed, config.trash.retention_days));
+    Ok(HttpResponse::Ok()
+        .insert_header((PAGE_LIMIT_HEADER, page.limit))
+        .json(json!({
+            "data": page.items,
+            "message": "Successfully retrieved deleted URLs",
+        })))
+}
+
+/// List URLs expiring soon route handler
+pub async fn expiring_handler(
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ExpiringQueryParams>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let page = service.get_expiring(&query, tenant.id()).await?;
+    let page = page.map(ShortenedUrlResponseDto::from);
+    Ok(HttpResponse::Ok()
+        .insert_header((PAGE_LIMIT_HEADER, page.limit))
+        .json(json!({
+            "data": page.items,
+            "message": "Successfully retrieved expiring URLs",
+        })))
+}
+
+/// Restore URL from the trash route handler
+pub async fn restore_handler(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let user_id = user.0.map(|u| u.id);
+    let url = service
+        .restore(&id.into_inner(), user_id, tenant.id())
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": ShortenedUrlResponseDto::from(url),
+        "message": "Successfully restored URL",
+    })))
+}
+
+/// Archive URL route handler
+pub async fn archive_handler(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let user_id = user.0.map(|u| u.id);
+    let url = service
+        .set_archived(&id.into_inner(), true, user_id, tenant.id())
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": ShortenedUrlResponseDto::from(url),
+        "message": "Successfully archived URL",
+    })))
+}
+
+/// Unarchive URL route handler
+pub async fn unarchive_handler(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let user_id = user.0.map(|u| u.id);
+    let url = service
+        .set_archived(&id.into_inner(), false, user_id, tenant.id())
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": ShortenedUrlResponseDto::from(url),
+        "message": "Successfully unarchived URL",
+    })))
+}
+
+/// Pin URL as a favorite route handler
+pub async fn favorite_handler(
+    user: AuthenticatedUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let url = service
+        .add_favorite(&id.into_inner(), &user.id, tenant.id())
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": ShortenedUrlResponseDto::from(url),
+        "message": "Successfully added URL to favorites",
+    })))
+}
+
+/// Unpin URL from the favorites route handler
+pub async fn unfavorite_handler(
+    user: AuthenticatedUser,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let id = id.into_inner();
+    let _ = service.remove_favorite(&id, &user.id).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "unfavorited_id": &id,
+        "message": format!("Successfully removed URL with ID '{}' from favorites", id),
+    })))
+}
+
+/// Claim URL created without signing in route handler
+pub async fn claim_handler(
+    user: AuthenticatedUser,
+    tenant: CurrentTenant,
+    dto: web::Json<ClaimLinkDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let url = service
+        .claim(dto.into_inner(), &user.id, tenant.id())
+        .await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": ShortenedUrlResponseDto::from(url),
+        "message": "Successfully claimed URL",
+    })))
+}
+
+/// Transfer URL to another user or tenant route handler
+pub async fn transfer_handler(
+    req: HttpRequest,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    dto: web::Json<TransferLinkDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+    tenants: web::Data<TenantServiceType>,
+) -> Result<impl Responder> {
+    let id = id.into_inner();
+    let dto = dto.into_inner();
+
+    // Admins move links at once, to another tenant too
+    if presents_admin_token(&req) {
+        let to_tenant = match dto.to_tenant.as_deref() {
+            Some(slug) => tenants.resolve(Some(slug), None)?.map(|t| t.id),
+            None => None,
+        };
+        let url = service
+            .force_transfer(&id, dto.to_user_id, to_tenant, tenant.id())
+            .await?;
+        return Ok(HttpResponse::Ok().json(json!({
+            "data": ShortenedUrlResponseDto::from(url),
+            "message": "Successfully transferred URL",
+        })));
+    }
+
+    let Some(user) = user.0 else {
+        return Err(AppError::Unauthorized(
+            "Transferring a URL requires an API key".to_string(),
+        ));
+    };
+    let transfer = service
+        .offer_transfer(&id, dto, &user.id, tenant.id())
+        .await?;
+    Ok(HttpResponse::Accepted().json(json!({
+        "data": transfer,
+        "message": "Transfer offered, waiting for the recipient to accept",
+    })))
+}
+
+/// List transfers offered to the user route handler
+pub async fn pending_transfers_handler(
+    user: AuthenticatedUser,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let transfers = service.pending_transfers(&user.id).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": transfers,
+        "message": "Successfully retrieved pending transfers",
+    })))
+}
+
+/// Get URL transfer history route handler
+pub async fn transfers_handler(
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let transfers = service.get_transfers(&id.into_inner(), tenant.id()).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": transfers,
+        "message": "Successfully retrieved URL transfers",
+    })))
+}
+
+/// Accept transfer route handler
+pub async fn accept_transfer_handler(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let url = service.accept_transfer(&id.into_inner(), &user.id).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": ShortenedUrlResponseDto::from(url),
+        "message": "Successfully accepted transfer",
+    })))
+}
+
+/// Decline or withdraw transfer route handler
+pub async fn close_transfer_handler(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let transfer = service.close_transfer(&id.into_inner(), &user.id).await?;
+    Ok(HttpResponse::Ok().json(json!({
+        "data": transfer,
+        "message": "Successfully closed transfer",
+    })))
+}
+
+/// Bulk update route handler
+///
+/// Answers with the outcome for each URL; one that failed doesn't hold back the others.
+pub async fn bulk_update_handler(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    dto: web::Json<BulkUpdateUrlsDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let changed_by = user.0.map(|u| u.id);
+    let results = service
+        .bulk_update(dto.into_inner(), changed_by, tenant.id())
+        .await?;
+    let message = format!(
+        "Successfully updated {} of {} URLs",
+        succeeded(&results),
+        results.len()
+    );
+    Ok(HttpResponse::Ok().json(json!({
+        "data": results,
+        "message": message,
+    })))
+}
+
+/// Bulk delete route handler
+///
+/// Answers with the outcome for each URL; one that failed doesn't hold back the others.
+pub async fn bulk_delete_handler(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    dto: web::Json<BulkDeleteUrlsDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    let user_id = user.0.map(|u| u.id);
+    let results = service
+        .bulk_delete(dto.into_inner(), user_id, tenant.id())
+        .await?;
+    let message = format!(
+        "Successfully deleted {} of {} URLs",
+        succeeded(&results),
+        results.len()
+    );
+    Ok(HttpResponse::Ok().json(json!({
+        "data": results,
+        "message": message,
+    })))
+}
+
 /// Redirect route handler
 pub async fn redirect_handler(
+    req: HttpRequest,
+    tenant: CurrentTenant,
     path: web::Path<String>,
     service: web::Data<ShortenedUrlServiceType>,
-) -> Result<impl Responder> {
-    let short_code = path.into_inner();
-    debug!("Redirect requested for code: {}", short_code);
+    analytics: web::Data<AnalyticsServiceType>,
+    branding: web::Data<BrandingServiceType>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let code = path.into_inner();
+    debug!("Redirect requested for code: {}", code);
 
     // Find the URL by short code, it should fail if not found
-    let url = service.get_by_code(&short_code).await?;
+    let result = match service.get_by_code(tenant.id(), None, &code).await {
+        Ok(url) => {
+            redirect(
+                &req,
+                &tenant,
+                url,
+                String::new(),
+                service,
+                analytics,
+                config,
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+    visitor_page(&req, &tenant, &branding, result).await
+}
+
+/// Redirect route handler for longer paths, either a namespaced code like `docs/setup`
+/// or a code followed by a path to forward, like `docs/guide/install`
+pub async fn nested_redirect_handler(
+    req: HttpRequest,
+    tenant: CurrentTenant,
+    path: web::Path<(String, String)>,
+    service: web::Data<ShortenedUrlServiceType>,
+    analytics: web::Data<AnalyticsServiceType>,
+    branding: web::Data<BrandingServiceType>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let (first, rest) = path.into_inner();
+    debug!("Redirect requested for path: {}/{}", first, rest);
+
+    let result = match service.get_by_path(tenant.id(), &first, &rest).await {
+        Ok((url, tail)) => redirect(&req, &tenant, url, tail, service, analytics, config).await,
+        Err(e) => Err(e),
+    };
+    visitor_page(&req, &tenant, &branding, result).await
+}
+
+/// Home page route handler: a form to shorten a link for browsers, a welcome message for
+/// API clients
+pub async fn home_handler(
+    req: HttpRequest,
+    tenant: CurrentTenant,
+    branding: web::Data<BrandingServiceType>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    if !accepts_html(&req) {
+        return Ok(HttpResponse::Ok().json(ResponsePayload {
+            status: 200,
+            message: String::from("Welcome and have a great time!"),
+        }));
+    }
+
+    let branding = branding.get(tenant.id()).await.unwrap_or_else(|e| {
+        warn!("Failed to load branding, using the default: {}", e);
+        Branding::default()
+    });
+    Ok(HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .body(home_page(
+            &branding,
+            &config.csrf.cookie_name,
+            config.captcha.enabled.then_some(&config.captcha),
+        )))
+}
+
+/// Apple App Site Association route handler, naming the iOS apps that may open the
+/// tenant's links as universal links
+pub async fn apple_app_site_association_handler(
+    tenant: CurrentTenant,
+    service: web::Data<AppLinksServiceType>,
+) -> Result<HttpResponse> {
+    let app_links = service.get(tenant.id()).await?;
+    let file = app_links.apple_app_site_association().ok_or_else(|| {
+        AppError::NotFound("No iOS app is associated with this domain".to_string())
+    })?;
+    Ok(HttpResponse::Ok().json(file))
+}
+
+/// Digital Asset Links route handler, naming the Android app that may open the tenant's
+/// links as app links
+pub async fn asset_links_handler(
+    tenant: CurrentTenant,
+    service: web::Data<AppLinksServiceType>,
+) -> Result<HttpResponse> {
+    let app_links = service.get(tenant.id()).await?;
+    let file = app_links.asset_links().ok_or_else(|| {
+        AppError::NotFound("No Android app is associated with this domain".to_string())
+    })?;
+    Ok(HttpResponse::Ok().json(file))
+}
+
+// Browsers following a link that goes nowhere get a page in the tenant's branding,
+// API clients keep getting the JSON error
+async fn visitor_page(
+    req: &HttpRequest,
+    tenant: &CurrentTenant,
+    branding: &BrandingServiceType,
+    result: Result<HttpResponse>,
+) -> Result<HttpResponse> {
+    let error = match result {
+        Err(e) if accepts_html(req) => e,
+        result => return result,
+    };
+
+    // The page is still worth showing unbranded when the branding can't be read
+    let branding = branding.get(tenant.id()).await.unwrap_or_else(|e| {
+        warn!("Failed to load branding, using the default: {}", e);
+        Branding::default()
+    });
+    let status = error.status_code();
+    let locale = request_locale(req);
+    let message = locale.translate(&error.message());
+    let mut response = HttpResponse::build(status);
+    if let Some(wait) = error.retry_after() {
+        response.insert_header((RETRY_AFTER, retry_after_secs(wait)));
+    }
+    Ok(response
+        .content_type(ContentType::html())
+        .insert_header((CONTENT_LANGUAGE, locale.tag()))
+        .insert_header((VARY, "Accept-Language"))
+        .body(error_page(&branding, &locale, status, &message)))
+}
+
+async fn redirect(
+    req: &HttpRequest,
+    tenant: &CurrentTenant,
+    url: ShortenedUrl,
+    tail: String,
+    service: web::Data<ShortenedUrlServiceType>,
+    analytics: web::Data<AnalyticsServiceType>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    // The code as it appears in the short URL, for messages
+    let short_code = url.path();
+
+    // Moderation comes first, a blocked link doesn't even lead to its fallback
+    match url.status {
+        LinkStatus::Blocked => {
+            info!("URL with code '{}' is blocked", short_code);
+            return Err(AppError::Gone(format!(
+                "URL with code '{}' has been disabled for breaching the acceptable use policy",
+                short_code
+            ))
+            .with_code(ErrorCode::LinkBlocked));
+        }
+        LinkStatus::PendingReview => {
+            info!("URL with code '{}' is pending review", short_code);
+            return Err(AppError::Forbidden(format!(
+                "URL with code '{}' is awaiting review and can't be followed yet",
+                short_code
+            ))
+            .with_code(ErrorCode::LinkPendingReview));
+        }
+        LinkStatus::Active | LinkStatus::Flagged => {}
+    }
 
     // Check if URL is still valid
-    if url.is_valid() {
+    if !url.is_valid() {
+        // Send visitors to a friendly page instead of an error when one is configured,
+        // for the link, its tenant or the whole instance
+        let fallback = url
+            .fallback_url
+            .as_ref()
+            .or(tenant.as_ref().and_then(|t| t.fallback_url.as_ref()))
+            .or(config.app.fallback_url.as_ref());
+        if let Some(fallback) = fallback {
+            info!(
+                "URL with code '{}' has expired, sending to '{}'",
+                short_code, fallback
+            );
+            return Ok(HttpResponse::TemporaryRedirect()
+                .insert_header((LOCATION, fallback.clone()))
+                .finish());
+        }
+
         info!("URL with code '{}' has expired", short_code);
-        return Err(AppError::Validation(format!(
-            "URL with code '{}' has expired",
+        return Err(
+            AppError::Validation(format!("URL with code '{}' has expired", short_code))
+                .with_code(ErrorCode::UrlExpired),
+        );
+    }
+
+    // Links flagged for abnormal traffic stay suspended until the throttle lapses or is lifted,
+    // which is when visitors are told to come back
+    if let Some(until) = url.throttled_until.filter(|_| url.is_throttled()) {
+        info!("URL with code '{}' is throttled", short_code);
+        let wait = (until - Utc::now()).to_std().unwrap_or_default();
+        return Err(AppError::RateLimited(
+            format!(
+                "URL with code '{}' is temporarily suspended due to unusual traffic",
+                short_code
+            ),
+            wait,
+        )
+        .with_code(ErrorCode::LinkSuspended));
+    }
+
+    // Links restricted to some networks refuse everyone else
+    let client_ip = client_ip(req);
+    if !url.allows_ip(client_ip) {
+        info!(
+            "Refusing '{}' to {:?} under its access rules",
+            short_code, client_ip
+        );
+        return Err(AppError::Forbidden(format!(
+            "URL with code '{}' is not available from your network",
             short_code
-        )));
+        ))
+        .with_code(ErrorCode::NetworkRestricted));
+    }
+
+    // Links pointing to fragile destinations only let through so many redirects a minute
+    if let (Some(per_minute), Some(limiter)) = (
+        url.max_redirects_per_minute,
+        req.app_data::<web::Data<LinkRateLimiter>>(),
+    ) {
+        limiter
+            .check(url.id, per_minute.max(1) as u32)
+            .map_err(|wait| {
+                info!("URL with code '{}' is over its rate limit", short_code);
+                AppError::RateLimited(
+                    format!(
+                        "URL with code '{}' is receiving too many visits, try again in {}s",
+                        short_code,
+                        retry_after_secs(wait).max(1)
+                    ),
+                    wait,
+                )
+            })?;
     }
 
-    // Increment access count (don't wait for the result to avoid delaying the redirect)
-    let params = ShortenedUrlUpdateParams {
-        access_count: url.access_count + 1,
-        last_accessed: Some(Utc::now()),
-        metadata: Some(format!("Last accessed at: {}", Utc::now()).into()),
-        ..Default::default()
+    // Chains of short links on this shortener are refused once they loop
+    if let Some(guard) = req.app_data::<web::Data<LoopGuard>>() {
+        check_redirect_chain(guard, &service, tenant.id(), &url).await?;
+    }
+
+    // Links acting as a prefix carry the rest of the request over to the destination,
+    // others may still pass on query parameters such as campaign tags
+    let location = if url.forward_path {
+        append_path(&url.original_url, &tail, req.query_string())
+            .ok_or_else(|| AppError::Validation(format!("Path '{}' can't be forwarded", tail)))?
+    } else if url.forward_query {
+        merge_query(&url.original_url, req.query_string())
+            .unwrap_or_else(|| url.original_url.clone())
+    } else {
+        url.original_url.clone()
     };
-    let _ = service.update(&url.id, params).await;
+
+    // Crawlers previewing a shared link get its own title and image. They aren't visitors,
+    // so they don't count as a visit, and the page leaves out the destination as anyone can
+    // claim to be a crawler
+    if url.has_social_preview()
+        && header_value(req, USER_AGENT).is_some_and(|agent| is_link_preview_crawler(&agent))
+    {
+        info!("Serving social preview of '{}'", short_code);
+        return Ok(HttpResponse::Ok()
+            .content_type(ContentType::html())
+            .body(social_page(&url, req.full_url().as_str())));
+    }
+
+    // In maintenance nothing is written: accesses are counted once it's over, visits aren't
+    // recorded and single-use links wait, as they can't be used up
+    let read_only = req
+        .app_data::<web::Data<MaintenanceMode>>()
+        .is_some_and(|mode| mode.is_enabled());
+    if read_only && url.single_use {
+        info!(
+            "URL with code '{}' is single-use, refused during maintenance",
+            short_code
+        );
+        return Err(AppError::Unavailable(format!(
+            "URL with code '{}' can't be used during maintenance, please try again shortly",
+            short_code
+        ))
+        .with_code(ErrorCode::Maintenance));
+    }
+
+    // Single-use links are consumed before redirecting, later hits get 410
+    service.consume(&url).await?;
+
+    // Count the access, which also keeps links with an idle limit alive
+    if read_only {
+        service.defer_access(&url);
+    } else if let Err(e) = service.record_access(&url).await {
+        warn!("Failed to count access to '{}': {}", short_code, e);
+    }
+
+    // Record the visit in the background so the redirect isn't delayed. Visitors who
+    // opted out are only counted, nothing identifying them is stored
+    let code = short_code.clone();
+    if read_only {
+        debug!("Not recording visit to '{}' during maintenance", code);
+    } else if is_tracking_opted_out(req, &config.analytics) {
+        let url_id = url.id;
+        tokio::spawn(async move {
+            if let Err(e) = analytics.record_untracked_visit(&url_id).await {
+                warn!("Failed to count visit for '{}': {}", code, e);
+            }
+        });
+    } else {
+        let visit = NewUrlVisit {
+            url_id: url.id,
+            ip_address: client_ip.map(|ip| ip.to_string()),
+            user_agent: header_value(req, USER_AGENT),
+            referer: header_value(req, REFERER),
+        };
+        tokio::spawn(async move {
+            if let Err(e) = analytics.record_visit(visit).await {
+                warn!("Failed to record visit for '{}': {}", code, e);
+            }
+        });
+    }
+
+    // Flagged links still lead to their destination, once the visitor has been warned
+    if url.status == LinkStatus::Flagged {
+        info!("Warning before sending '{}' to '{}'", short_code, location);
+        return Ok(warning_response(req, tenant, &short_code, &location).await);
+    }
+
+    // Mobile visitors are offered the app first, with the destination as the fallback.
+    // Reverse proxied destinations stay hidden, so those links always go through here
+    if let Some(app_url) = &url.app_url {
+        if url.behavior != LinkBehavior::ReverseProxy && accepts_html(req) && is_mobile(req) {
+            info!("Opening '{}' in app at '{}'", short_code, app_url);
+            return Ok(app_link_response(req, tenant, app_url, &location).await);
+        }
+    }
+
+    // Files may be served from the short URL itself; web pages, and files that can't be,
+    // are redirected to as usual
+    let served = match url.behavior {
+        LinkBehavior::ReverseProxy => {
+            // The destination is meant to stay hidden, so it's never redirected to
+            info!("Reverse proxying '{}' to '{}'", short_code, location);
+            return reverse_proxy_response(req, &short_code, &location).await;
+        }
+        LinkBehavior::Proxy => proxy_response(req, &location).await,
+        LinkBehavior::Preview if accepts_html(req) => {
+            preview_response(req, tenant, &location).await
+        }
+        LinkBehavior::Preview | LinkBehavior::Redirect => None,
+    };
+    if let Some(response) = served {
+        info!("Serving '{}' from '{}'", short_code, location);
+        return Ok(response);
+    }
 
     // Log the successful redirect
-    info!("Redirecting '{}' to '{}'", short_code, url.original_url);
+    info!("Redirecting '{}' to '{}'", short_code, location);
 
     // Return redirect response
     Ok(HttpResponse::TemporaryRedirect()
-        .insert_header((LOCATION, url.original_url.clone()))
+        .insert_header((LOCATION, location))
         .finish())
 }
+
+// The interstitial shown in place of a redirect, in the tenant's branding
+async fn warning_response(
+    req: &HttpRequest,
+    tenant: &CurrentTenant,
+    short_code: &str,
+    location: &str,
+) -> HttpResponse {
+    let branding = request_branding(req, tenant).await;
+    let locale = request_locale(req);
+    HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .insert_header((CACHE_CONTROL, "no-store"))
+        .insert_header((CONTENT_LANGUAGE, locale.tag()))
+        .body(warning_page(&branding, &locale, short_code, location))
+}
+
+// The page opening the app, in the tenant's branding
+async fn app_link_response(
+    req: &HttpRequest,
+    tenant: &CurrentTenant,
+    app_url: &str,
+    location: &str,
+) -> HttpResponse {
+    let branding = request_branding(req, tenant).await;
+    let locale = request_locale(req);
+    HttpResponse::Ok()
+        .content_type(ContentType::html())
+        .insert_header((CACHE_CONTROL, "no-store"))
+        .insert_header((CONTENT_LANGUAGE, locale.tag()))
+        .body(app_link_page(&branding, &locale, app_url, location))
+}
+
+// The destination file streamed through, unless it can't be proxied
+async fn proxy_response(req: &HttpRequest, location: &str) -> Option<HttpResponse> {
+    let proxy = req.app_data::<web::Data<FileProxy>>()?;
+    let (upstream, size) = proxy.fetch(location).await?;
+
+    let mut response = HttpResponse::Ok();
+    for header in proxied_headers(location, &upstream) {
+        response.insert_header(header);
+    }
+    // Every visit has to come through here to be counted
+    response.insert_header((CACHE_CONTROL, "no-store"));
+    Some(response.body(SizedStream::new(size, proxy.body(upstream))))
+}
+
+// Whatever the destination answers, streamed back as is apart from filtered headers
+async fn reverse_proxy_response(
+    req: &HttpRequest,
+    short_code: &str,
+    location: &str,
+) -> Result<HttpResponse> {
+    let proxy = req
+        .app_data::<web::Data<ReverseProxy>>()
+        .ok_or_else(|| AppError::Internal("Reverse proxy is not configured".to_string()))?;
+    let headers = req
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())));
+    let upstream = proxy.fetch(short_code, location, headers).await?;
+
+    let status = StatusCode::from_u16(upstream.status).unwrap_or(StatusCode::BAD_GATEWAY);
+    let mut response = HttpResponse::build(status);
+    for header in upstream.headers {
+        response.insert_header(header);
+    }
+    response.insert_header((X_CONTENT_TYPE_OPTIONS, "nosniff"));
+
+    let body = proxy.body(upstream.response);
+    Ok(match upstream.size {
+        _ if status == StatusCode::NO_CONTENT || status == StatusCode::NOT_MODIFIED => {
+            response.finish()
+        }
+        Some(size) => response.body(SizedStream::new(size, body)),
+        None => response.streaming(body),
+    })
+}
+
+// A page previewing the destination file, unless it's a web page
+async fn preview_response(
+    req: &HttpRequest,
+    tenant: &CurrentTenant,
+    location: &str,
+) -> Option<HttpResponse> {
+    let proxy = req.app_data::<web::Data<FileProxy>>()?;
+    let file = proxy.probe(location).await;
+    if file.is_page() {
+        return None;
+    }
+
+    let branding = request_branding(req, tenant).await;
+    let locale = request_locale(req);
+    Some(
+        HttpResponse::Ok()
+            .content_type(ContentType::html())
+            .insert_header((CACHE_CONTROL, "no-store"))
+            .insert_header((CONTENT_LANGUAGE, locale.tag()))
+            .body(preview_page(&branding, &locale, location, &file)),
+    )
+}
+
+// The tenant's branding for pages served in place of a redirect
+async fn request_branding(req: &HttpRequest, tenant: &CurrentTenant) -> Branding {
+    match req.app_data::<web::Data<BrandingServiceType>>() {
+        Some(service) => service.get(tenant.id()).await.unwrap_or_else(|e| {
+            warn!("Failed to load branding, using the default: {}", e);
+            Branding::default()
+        }),
+        None => Branding::default(),
+    }
+}
+
+// Anyone can create links without credentials, so those are limited per client address
+// and may have to prove they're human
+async fn check_anonymous(
+    req: &HttpRequest,
+    user: &OptionalUser,
+    limiter: &RateLimiter,
+    captcha: &CaptchaService,
+) -> Result<()> {
+    let anonymous = user.0.is_none()
+        && !req.extensions().contains::<ServiceTokenIdentity>()
+        && !presents_admin_token(req);
+    if anonymous {
+        let ip = client_ip(req);
+        if let Some(ip) = ip {
+            let quota = limiter.check(ip);
+            // Answered along with the response, over the limit or not
+            if let Some(current) = quota.unwrap_or_else(Some) {
+                req.extensions_mut().insert(current);
+            }
+            quota.map_err(|quota| {
+                AppError::RateLimited(
+                    format!(
+                        "Too many links created without an API key, try again in {}s",
+                        retry_after_secs(quota.reset).max(1)
+                    ),
+                    quota.reset,
+                )
+            })?;
+        }
+        let token = header_value(req, HeaderName::from_static(CAPTCHA_HEADER));
+        captcha
+            .verify(token.as_deref(), ip.map(|ip| ip.to_string()).as_deref())
+            .await?;
+    }
+    Ok(())
+}
+
+// Refuse reverse_proxy links to destinations the proxy won't serve
+fn check_reverse_proxy(req: &HttpRequest, destination: &str) -> Result<()> {
+    match req.app_data::<web::Data<ReverseProxy>>() {
+        Some(proxy) => proxy.check(destination),
+        None => Err(AppError::Validation(
+            "The reverse_proxy behavior is not available".to_string(),
+        )),
+    }
+}
+
+// Destinations on this shortener's own hosts would redirect in a loop
+fn check_redirect_loop(req: &HttpRequest, destination: &str) -> Result<()> {
+    match req.app_data::<web::Data<LoopGuard>>() {
+        Some(guard) => guard.check(destination),
+        None => Ok(()),
+    }
+}
+
+// Follow a link pointing back at this shortener through the short links it leads to, as
+// links set up before the check or before a host was added may still do so. A chain coming
+// back to a link already seen, or longer than the guard allows, is a loop
+async fn check_redirect_chain(
+    guard: &LoopGuard,
+    service: &ShortenedUrlServiceType,
+    tenant: Option<Uuid>,
+    url: &ShortenedUrl,
+) -> Result<()> {
+    let mut seen = vec![url.id];
+    let mut destination = url.original_url.clone();
+    for _ in 0..guard.max_depth() {
+        let Some(path) = guard.own_path(&destination) else {
+            return Ok(());
+        };
+        let (first, rest) = path.split_once('/').unwrap_or((&path, ""));
+        let next = match service.get_by_path(tenant, first, rest).await {
+            Ok((next, _)) => next,
+            // The chain ends on a missing link, which answers 404 rather than looping
+            Err(AppError::NotFound(_)) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if seen.contains(&next.id) {
+            break;
+        }
+        seen.push(next.id);
+        destination = next.original_url;
+    }
+
+    info!("URL with code '{}' leads back to itself", url.path());
+    Err(AppError::LoopDetected(format!(
+        "URL with code '{}' redirects in a loop",
+        url.path()
+    )))
+}
+
+// Listings only ever see the links of the request's tenant, and the favorites of its user
+fn scoped(
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
+    tenant: &CurrentTenant,
+    user: &OptionalUser,
+) -> Result<ShortenedUrlQueryParams> {
+    let user = user.0.as_ref().map(|u| u.id);
+    if query.favorites.is_some() && user.is_none() {
+        return Err(AppError::Unauthorized(
+            "Filtering by favorites requires an API key".to_string(),
+        ));
+    }
+    Ok(ShortenedUrlQueryParams {
+        tenant: TenantScope::Only(tenant.id()),
+        user,
+        ..query.into_inner()
+    })
+}
+
+// Number of URLs a bulk change went through for
+fn succeeded(results: &[BulkItemResult]) -> usize {
+    results.iter().filter(|result| result.success).count()
+}
+
+// URLs are tagged with their version, so a read can be followed by a conditional update
+fn version_tag(version: i32) -> EntityTag {
+    EntityTag::new_strong(version.to_string())
+}
+
+// The version named by an If-Match header, if one was sent
+fn if_match_version(req: &HttpRequest) -> Result<Option<i32>> {
+    if !req.headers().contains_key(IF_MATCH) {
+        return Ok(None);
+    }
+    // A header that doesn't parse must not turn the update into an unconditional one
+    let tags = match req.get_header::<IfMatch>() {
+        Some(IfMatch::Items(tags)) => tags,
+        Some(IfMatch::Any) => return Ok(None),
+        None => {
+            return Err(AppError::Validation(
+                "If-Match must be a quoted version, like \"3\"".to_string(),
+            ))
+        }
+    };
+
+    match tags.as_slice() {
+        [tag] => tag.tag().parse().map(Some).map_err(|_| {
+            AppError::Validation(format!("If-Match '{}' is not a URL version", tag.tag()))
+        }),
+        _ => Err(AppError::Validation(
+            "If-Match must name exactly one version".to_string(),
+        )),
+    }
+}
+
+// Read a header as an owned string, skipping values that aren't valid UTF-8
+fn header_value(req: &HttpRequest, name: actix_web::http::header::HeaderName) -> Option<String> {
+    req.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+}
+
+// Whether the client prefers a web page, as browsers navigating to a link do
+fn accepts_html(req: &HttpRequest) -> bool {
+    header_value(req, ACCEPT).is_some_and(|accept| accept.contains("text/html"))
+}
+
+// Whether the visitor is on a phone or tablet, where apps can take over links
+fn is_mobile(req: &HttpRequest) -> bool {
+    header_value(req, USER_AGENT).is_some_and(|agent| {
+        ["iPhone", "iPad", "iPod", "Android"]
+            .iter()
+            .any(|device| agent.contains(device))
+    })
+}
+
+// Whether the visitor asked not to be tracked, via DNT or the configured opt-out cookie
+fn is_tracking_opted_out(req: &HttpRequest, config: &AnalyticsConfig) -> bool {
+    if config.honor_dnt && header_value(req, DNT).as_deref().map(str::trim) == Some("1") {
+        return true;
+    }
+
+    config
+        .opt_out_cookie
+        .as_deref()
+        .and_then(|name| req.cookie(name))
+        .is_some_and(|c| !matches!(c.value(), "" | "0" | "false"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ArchiveSinkKind, VisitExportConfig, VisitExportKind};
+    use actix_web::{cookie::Cookie, test::TestRequest};
+
+    fn config(honor_dnt: bool, cookie: Option<&str>) -> AnalyticsConfig {
+        AnalyticsConfig {
+            retention_enabled: false,
+            retention_days: 90,
+            retention_interval_seconds: 3600,
+            retention_batch_size: 1000,
+            archive_sink: ArchiveSinkKind::None,
+            archive_dir: String::new(),
+            honor_dnt,
+            opt_out_cookie: cookie.map(String::from),
+            store_raw_visits: true,
+            export: VisitExportConfig {
+                sink: VisitExportKind::None,
+                url: None,
+                clickhouse_table: "url_visits".to_string(),
+                user: None,
+                password: None,
+                batch_size: 1000,
+                flush_interval_seconds: 5,
+                max_pending: 100_000,
+            },
+        }
+    }
+
+    #[test]
+    fn test_is_tracking_opted_out() {
+        let dnt = TestRequest::default()
+            .insert_header((DNT, "1"))
+            .to_http_request();
+        assert!(is_tracking_opted_out(&dnt, &config(true, None)));
+        assert!(!is_tracking_opted_out(&dnt, &config(false, None)));
+
+        let cookie = TestRequest::default()
+            .cookie(Cookie::new("no_track", "1"))
+            .to_http_request();
+        assert!(is_tracking_opted_out(
+            &cookie,
+            &config(false, Some("no_track"))
+        ));
+        assert!(!is_tracking_opted_out(
+            &cookie,
+            &config(true, Some("other"))
+        ));
+
+        let cleared = TestRequest::default()
+            .cookie(Cookie::new("no_track", "0"))
+            .to_http_request();
+        assert!(!is_tracking_opted_out(
+            &cleared,
+            &config(true, Some("no_track"))
+        ));
+    }
+
+    #[test]
+    fn test_if_match_version() {
+        let version = |value: Option<&str>| {
+            let mut req = TestRequest::default();
+            if let Some(value) = value {
+                req = req.insert_header((IF_MATCH, value));
+            }
+            if_match_version(&req.to_http_request())
+        };
+
+        assert_eq!(version(None).unwrap(), None);
+        assert_eq!(version(Some("*")).unwrap(), None);
+        assert_eq!(version(Some("\"3\"")).unwrap(), Some(3));
+        assert_eq!(version(Some("W/\"3\"")).unwrap(), Some(3));
+
+        // Malformed or ambiguous tags are refused rather than ignored
+        for malformed in ["3", "\"three\"", "\"3\", \"4\""] {
+            let err = version(Some(malformed)).unwrap_err();
+            assert_eq!(err.status_code(), StatusCode::BAD_REQUEST, "{}", malformed);
+        }
+    }
+
+    #[actix_web::test]
+    async fn test_claiming_needs_a_signed_in_user() {
+        let app = actix_web::test::init_service(
+            actix_web::App::new().route("/api/urls/claim", web::post().to(claim_handler)),
+        )
+        .await;
+        let req = TestRequest::post()
+            .uri("/api/urls/claim")
+            .set_json(json!({ "token": "secret" }))
+            .to_request();
+        let res = actix_web::test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    }
+}
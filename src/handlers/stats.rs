@@ -0,0 +1,136 @@
+use actix_web::{
+    http::header::{ContentDisposition, DispositionParam, DispositionType},
+    web, HttpResponse, Responder,
+};
+use chrono::Utc;
+use futures::{stream, stream::BoxStream, StreamExt, TryStreamExt};
+use log::warn;
+use serde_json::json;
+
+use super::PAGE_LIMIT_HEADER;
+use crate::{
+    config::{Config, ReportConfig},
+    errors::AppError,
+    extractors::{CurrentTenant, ValidatedQuery},
+    models::{GroupByQueryParams, ReportFormat, TopLinksQueryParams},
+    repositories::StatsRepository,
+    services::{StatsService, StatsServiceTrait},
+    types::Result,
+    utils::report::{csv_header, csv_line, xlsx_report, ReportRow},
+};
+
+pub type StatsServiceType = StatsService<StatsRepository>;
+
+const XLSX_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+
+/// Grouped link stats route handler
+///
+/// Answers with a CSV or XLSX report instead when `format` asks for one.
+pub async fn group_by_handler(
+    tenant: CurrentTenant,
+    query: ValidatedQuery<GroupByQueryParams>,
+    service: web::Data<StatsServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    let params = query.into_inner();
+    let format = params.format.unwrap_or_default();
+    if format != ReportFormat::Json {
+        let rows = service.export_group_by(&params, tenant.id());
+        return report_response(rows, format, "stats", &config.reports).await;
+    }
+
+    let page = service.group_by(&params, tenant.id()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header((PAGE_LIMIT_HEADER, page.limit))
+        .json(json!({
+            "data": page.items,
+            "message": "Successfully retrieved stats",
+        })))
+}
+
+/// Top links leaderboard route handler
+///
+/// Answers with a CSV or XLSX report instead when `format` asks for one.
+pub async fn top_links_handler(
+    tenant: CurrentTenant,
+    query: ValidatedQuery<TopLinksQueryParams>,
+    service: web::Data<StatsServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    let params = query.into_inner();
+    let format = params.format.unwrap_or_default();
+    if format != ReportFormat::Json {
+        let rows = service.export_top_links(&params, tenant.id());
+        return report_response(rows, format, "top-links", &config.reports).await;
+    }
+
+    let page = service.top_links(&params, tenant.id()).await?;
+    Ok(HttpResponse::Ok()
+        .insert_header((PAGE_LIMIT_HEADER, page.limit))
+        .json(json!({
+            "data": page.items,
+            "message": "Successfully retrieved top links",
+        })))
+}
+
+// A report file to download: CSV streamed row by row, XLSX built once every row is read.
+// Errors before the first CSV row get a regular error response; a later one cuts the
+// download short
+async fn report_response<T>(
+    mut rows: BoxStream<'static, Result<T>>,
+    format: ReportFormat,
+    name: &str,
+    config: &ReportConfig,
+) -> Result<HttpResponse>
+where
+    T: ReportRow + Send + 'static,
+{
+    let extension = if format == ReportFormat::Csv {
+        "csv"
+    } else {
+        "xlsx"
+    };
+    let disposition = ContentDisposition {
+        disposition: DispositionType::Attachment,
+        parameters: vec![DispositionParam::Filename(format!(
+            "{}-{}.{}",
+            name,
+            Utc::now().format("%Y-%m-%d"),
+            extension
+        ))],
+    };
+
+    if format == ReportFormat::Csv {
+        let first = rows.next().await.transpose()?;
+        let lines = stream::iter(first.map(Ok)).chain(rows).map(|row| {
+            let row = row.inspect_err(|e| warn!("Stats report aborted: {}", e))?;
+            Ok::<_, AppError>(web::Bytes::from(csv_line(&row)))
+        });
+        let header = stream::once(async { Ok(web::Bytes::from(csv_header::<T>())) });
+        return Ok(HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header(disposition)
+            .streaming(header.chain(lines)));
+    }
+
+    // Read one row past the limit to tell a full report from one that doesn't fit
+    let max_rows = config.xlsx_max_rows;
+    let rows = rows.take(max_rows + 1).try_collect::<Vec<_>>().await?;
+    if rows.len() > max_rows {
+        return Err(AppError::Validation(format!(
+            "The report has more than {} rows, the most an XLSX report may hold; \
+             set a lower limit or use format=csv",
+            max_rows
+        )));
+    }
+
+    let sheet = name.to_string();
+    let workbook = web::block(move || xlsx_report(&sheet, &rows))
+        .await
+        .map_err(|e| AppError::Internal(e.to_string()))?
+        .map_err(|e| AppError::Internal(format!("Failed to build the XLSX report: {}", e)))?;
+    Ok(HttpResponse::Ok()
+        .content_type(XLSX_CONTENT_TYPE)
+        .insert_header(disposition)
+        .body(workbook))
+}
@@ -0,0 +1,344 @@
+// src/i18n.rs - Error messages and visitor pages in the language visitors ask for
+use std::{collections::HashMap, fs, path::Path, sync::OnceLock};
+
+use actix_web::{http::header::ACCEPT_LANGUAGE, web, HttpRequest};
+use log::info;
+
+use crate::{config::I18nConfig, errors::AppError, types::Result, utils::template::html_escape};
+
+/// The locale every message exists in
+pub const BUNDLED_LOCALE: &str = "en";
+
+const BUNDLED_MESSAGES: &str = include_str!("../locales/en.json");
+
+// Messages whose English text error messages are recognized from
+const ERROR_PREFIX: &str = "error.";
+
+/// Messages of every available locale, by message ID
+///
+/// English is bundled and complete. More locales are read from `<locale>.json` files in
+/// `I18N_LOCALES_DIR`, using the same IDs as `locales/en.json`; messages they leave out
+/// stay in English.
+pub struct Catalog {
+    locales: HashMap<String, HashMap<String, String>>,
+    default_locale: String,
+    // English error messages, to recognize them in the messages errors carry
+    patterns: Vec<(String, Pattern)>,
+}
+
+impl Catalog {
+    /// The bundled English messages, plus the locales of the configured directory
+    ///
+    /// ### Errors
+    /// * `AppError::Config` - If a locale file can't be read or the default locale has no
+    ///   messages
+    pub fn load(config: &I18nConfig) -> Result<Self> {
+        let mut catalog = Self::bundled();
+        if let Some(dir) = &config.locales_dir {
+            catalog.load_dir(Path::new(dir))?;
+        }
+
+        let default_locale = config.default_locale.to_ascii_lowercase();
+        if !catalog.locales.contains_key(&default_locale) {
+            return Err(AppError::Config(format!(
+                "I18N_DEFAULT_LOCALE '{}' has no messages",
+                config.default_locale
+            )));
+        }
+        catalog.default_locale = default_locale;
+        Ok(catalog)
+    }
+
+    /// The bundled English messages alone
+    pub fn bundled() -> Self {
+        let messages = serde_json::from_str::<HashMap<String, String>>(BUNDLED_MESSAGES)
+            .expect("bundled messages are valid JSON");
+        let patterns = messages
+            .iter()
+            .filter(|(id, _)| id.starts_with(ERROR_PREFIX))
+            .map(|(id, text)| (id.clone(), Pattern::new(text)))
+            .collect();
+
+        Self {
+            locales: HashMap::from([(BUNDLED_LOCALE.to_string(), messages)]),
+            default_locale: BUNDLED_LOCALE.to_string(),
+            patterns,
+        }
+    }
+
+    fn load_dir(&mut self, dir: &Path) -> Result<()> {
+        let unreadable = |e: std::io::Error| {
+            AppError::Config(format!("Could not read I18N_LOCALES_DIR {:?}: {}", dir, e))
+        };
+        for entry in fs::read_dir(dir).map_err(unreadable)? {
+            let path = entry.map_err(unreadable)?.path();
+            let (Some(locale), Some("json")) = (
+                path.file_stem().and_then(|s| s.to_str()),
+                path.extension().and_then(|s| s.to_str()),
+            ) else {
+                continue;
+            };
+
+            let messages = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| {
+                    serde_json::from_str::<HashMap<String, String>>(&text)
+                        .map_err(|e| e.to_string())
+                })
+                .map_err(|e| AppError::Config(format!("Invalid locale file {:?}: {}", path, e)))?;
+            info!("Loaded {} messages for locale '{}'", messages.len(), locale);
+            self.locales
+                .entry(locale.to_ascii_lowercase())
+                .or_default()
+                .extend(messages);
+        }
+        Ok(())
+    }
+
+    /// The locale to answer in, the first of an `Accept-Language` header's that has
+    /// messages, or the default one
+    pub fn locale(&self, accept_language: Option<&str>) -> Locale<'_> {
+        let tag = accept_language
+            .and_then(|header| self.negotiate(header))
+            .unwrap_or(&self.default_locale);
+        Locale { catalog: self, tag }
+    }
+
+    fn negotiate(&self, header: &str) -> Option<&str> {
+        let mut ranges = header
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim().to_ascii_lowercase();
+                let quality = parts
+                    .find_map(|param| param.trim().strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.trim().parse::<f32>().ok())?;
+                (!tag.is_empty() && quality > 0.0).then_some((tag, quality))
+            })
+            .collect::<Vec<_>>();
+        // Stable, so equally preferred locales keep the client's order
+        ranges.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        ranges.iter().find_map(|(tag, _)| {
+            if tag == "*" {
+                return Some(self.default_locale.as_str());
+            }
+            // `fr-CH` falls back to `fr`
+            let primary = tag.split('-').next().unwrap_or(tag);
+            [tag.as_str(), primary].into_iter().find_map(|candidate| {
+                self.locales
+                    .get_key_value(candidate)
+                    .map(|(locale, _)| locale.as_str())
+            })
+        })
+    }
+
+    fn message(&self, locale: &str, id: &str) -> Option<&str> {
+        self.locales
+            .get(locale)
+            .and_then(|messages| messages.get(id))
+            .or_else(|| self.locales[BUNDLED_LOCALE].get(id))
+            .map(String::as_str)
+    }
+}
+
+/// The messages of one locale
+#[derive(Clone, Copy)]
+pub struct Locale<'a> {
+    catalog: &'a Catalog,
+    tag: &'a str,
+}
+
+impl<'a> Locale<'a> {
+    /// The locale's tag, like `en` or `pt-br`
+    pub fn tag(&self) -> &'a str {
+        self.tag
+    }
+
+    /// A message with its `{name}` placeholders filled in
+    pub fn text(&self, id: &str, args: &[(&str, &str)]) -> String {
+        match self.catalog.message(self.tag, id) {
+            Some(text) => fill(text, args),
+            None => id.to_string(),
+        }
+    }
+
+    /// A message as HTML: its text escaped, with placeholders replaced by markup
+    pub fn markup(&self, id: &str, args: &[(&str, &str)]) -> String {
+        fill(&html_escape(&self.text(id, &[])), args)
+    }
+
+    /// An error message in this locale, when its English text is one of the catalog's
+    ///
+    /// Messages the catalog doesn't know, or that this locale doesn't translate, are
+    /// returned as they are.
+    pub fn translate(&self, message: &str) -> String {
+        if self.tag == BUNDLED_LOCALE {
+            return message.to_string();
+        }
+
+        let translated = self.catalog.patterns.iter().find_map(|(id, pattern)| {
+            let args = pattern.captures(message)?;
+            let text = self.catalog.locales.get(self.tag)?.get(id)?;
+            let args = args
+                .iter()
+                .map(|(name, value)| (*name, value.as_str()))
+                .collect::<Vec<_>>();
+            Some(fill(text, &args))
+        });
+        translated.unwrap_or_else(|| message.to_string())
+    }
+}
+
+/// The locale to answer a request in
+pub fn request_locale(req: &HttpRequest) -> Locale<'_> {
+    let accept_language = req
+        .headers()
+        .get(ACCEPT_LANGUAGE)
+        .and_then(|v| v.to_str().ok());
+    match req.app_data::<web::Data<Catalog>>() {
+        Some(catalog) => catalog.get_ref().locale(accept_language),
+        None => {
+            static BUNDLED: OnceLock<Catalog> = OnceLock::new();
+            BUNDLED.get_or_init(Catalog::bundled).locale(None)
+        }
+    }
+}
+
+fn fill(text: &str, args: &[(&str, &str)]) -> String {
+    args.iter().fold(text.to_string(), |out, (name, value)| {
+        out.replace(&format!("{{{}}}", name), value)
+    })
+}
+
+enum Piece {
+    Text(String),
+    Placeholder(String),
+}
+
+// A message with placeholders, matched against messages to find what filled them in
+struct Pattern {
+    pieces: Vec<Piece>,
+}
+
+impl Pattern {
+    fn new(text: &str) -> Self {
+        let mut pieces = Vec::new();
+        let mut rest = text;
+        while let Some((before, after)) = rest.split_once('{') {
+            let Some((name, after)) = after.split_once('}') else {
+                break;
+            };
+            if !before.is_empty() {
+                pieces.push(Piece::Text(before.to_string()));
+            }
+            pieces.push(Piece::Placeholder(name.to_string()));
+            rest = after;
+        }
+        if !rest.is_empty() {
+            pieces.push(Piece::Text(rest.to_string()));
+        }
+        Self { pieces }
+    }
+
+    // The value of each placeholder, if the message has this pattern
+    fn captures(&self, message: &str) -> Option<Vec<(&str, String)>> {
+        let mut captures = Vec::new();
+        let mut rest = message;
+        let mut placeholder = None;
+
+        for (i, piece) in self.pieces.iter().enumerate() {
+            match piece {
+                Piece::Placeholder(name) => placeholder = Some(name.as_str()),
+                Piece::Text(text) => {
+                    let Some(name) = placeholder.take() else {
+                        rest = rest.strip_prefix(text.as_str())?;
+                        continue;
+                    };
+                    // The last text has to end the message, earlier ones end the value
+                    let at = if i == self.pieces.len() - 1 {
+                        rest.strip_suffix(text.as_str())?.len()
+                    } else {
+                        rest.find(text.as_str())?
+                    };
+                    if at == 0 {
+                        return None;
+                    }
+                    captures.push((name, rest[..at].to_string()));
+                    rest = &rest[at + text.len()..];
+                }
+            }
+        }
+
+        if let Some(name) = placeholder {
+            if rest.is_empty() {
+                return None;
+            }
+            captures.push((name, rest.to_string()));
+            rest = "";
+        }
+        rest.is_empty().then_some(captures)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> Catalog {
+        let mut catalog = Catalog::bundled();
+        catalog.locales.insert(
+            "fr".to_string(),
+            HashMap::from([
+                (
+                    "error.link_not_found".to_string(),
+                    "L'URL avec le code '{code}' est introuvable".to_string(),
+                ),
+                (
+                    "page.not_found.title".to_string(),
+                    "Lien introuvable".to_string(),
+                ),
+            ]),
+        );
+        catalog
+    }
+
+    #[test]
+    fn test_negotiates_available_locales() {
+        let catalog = catalog();
+        assert_eq!(catalog.locale(None).tag(), "en");
+        assert_eq!(
+            catalog.locale(Some("fr-CH, fr;q=0.9, en;q=0.8")).tag(),
+            "fr"
+        );
+        assert_eq!(catalog.locale(Some("de, en;q=0.5, fr;q=0.7")).tag(), "fr");
+        assert_eq!(catalog.locale(Some("fr;q=0, en")).tag(), "en");
+        assert_eq!(catalog.locale(Some("de, *;q=0.1")).tag(), "en");
+        assert_eq!(catalog.locale(Some("de")).tag(), "en");
+    }
+
+    #[test]
+    fn test_translates_known_messages() {
+        let catalog = catalog();
+        let fr = catalog.locale(Some("fr"));
+        assert_eq!(
+            fr.translate("URL with code 'docs/it's' not found"),
+            "L'URL avec le code 'docs/it's' est introuvable"
+        );
+        // Known but untranslated, and unknown messages stay in English
+        assert_eq!(
+            fr.translate("URL with code 'x' has expired"),
+            "URL with code 'x' has expired"
+        );
+        assert_eq!(fr.translate("Something else"), "Something else");
+
+        assert_eq!(fr.text("page.not_found.title", &[]), "Lien introuvable");
+        assert_eq!(fr.text("page.gone.title", &[]), "Link no longer available");
+        assert_eq!(
+            catalog
+                .locale(None)
+                .markup("page.warning.flagged", &[("code", "<b>x</b>")]),
+            "The link <b>x</b> has been flagged and is under review. It leads to:"
+        );
+    }
+}
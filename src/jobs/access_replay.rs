@@ -0,0 +1,33 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{info, warn};
+use tokio::task::JoinHandle;
+
+use crate::{
+    handlers::ShortenedUrlServiceType, services::ShortenedUrlServiceTrait,
+    utils::maintenance::MaintenanceMode,
+};
+
+/// Write access counts deferred during a database outage or maintenance once the database
+/// is reachable again and maintenance is over
+pub fn spawn_access_replay(
+    service: Arc<ShortenedUrlServiceType>,
+    maintenance: Arc<MaintenanceMode>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if maintenance.is_enabled() {
+                continue;
+            }
+            match service.replay_accesses().await {
+                Ok(0) => {}
+                Ok(count) => info!("Replayed deferred access counts of {} links", count),
+                Err(e) => warn!("Deferred access counts not replayed yet: {}", e),
+            }
+        }
+    })
+}
@@ -0,0 +1,143 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use log::warn;
+
+use super::Job;
+use crate::{
+    config::{AdminChannelConfig, AnomalyConfig},
+    models::{ClickSpike, FlagAction, LinkFlag},
+    notifications::{short_url, ChannelDispatcher, NotificationEvent},
+    repositories::{LinkFlagRepositoryTrait, NotificationRepositoryTrait},
+    types::Result,
+    utils::http_client::HttpClient,
+};
+
+/// Flags links whose recent clicks come in bursts from a handful of IPs
+///
+/// Only visits stored in `url_visits` are analysed, so opted-out (DNT) clicks are not counted.
+pub struct AnomalyDetectionJob<R: LinkFlagRepositoryTrait, N: NotificationRepositoryTrait> {
+    repository: Arc<R>,
+    dispatcher: ChannelDispatcher<N>,
+    config: AnomalyConfig,
+    admin_channel: Option<AdminChannelConfig>,
+    base_url: String,
+}
+
+impl<R, N> AnomalyDetectionJob<R, N>
+where
+    R: LinkFlagRepositoryTrait + Send + Sync + 'static,
+    N: NotificationRepositoryTrait + Send + Sync + 'static,
+{
+    pub fn new(
+        repository: Arc<R>,
+        notification_repository: Arc<N>,
+        config: AnomalyConfig,
+        admin_channel: Option<AdminChannelConfig>,
+        base_url: String,
+        http_client: Arc<HttpClient>,
+    ) -> Self {
+        Self {
+            repository,
+            dispatcher: ChannelDispatcher::new(notification_repository, http_client),
+            config,
+            admin_channel,
+            base_url,
+        }
+    }
+
+    /// Flag every link spiking in the current window
+    ///
+    /// Returns the number of new flags.
+    pub async fn run_once(&self) -> Result<u64> {
+        let since = Utc::now() - Duration::seconds(self.config.window_seconds as i64);
+        let spikes = self
+            .repository
+            .find_click_spikes(since, self.config.min_clicks, self.config.max_distinct_ips)
+            .await?;
+
+        let mut flagged = 0;
+        for spike in spikes {
+            if let Some(flag) = self.flag(&spike).await? {
+                warn!(
+                    "Flagged link '{}' ({}): {}",
+                    spike.short_code,
+                    serde_json::to_string(&flag.action).unwrap_or_default(),
+                    flag.reason
+                );
+                self.notify(&spike, &flag).await;
+                flagged += 1;
+            }
+        }
+
+        Ok(flagged)
+    }
+
+    async fn flag(&self, spike: &ClickSpike) -> Result<Option<LinkFlag>> {
+        let reason = format!(
+            "{} clicks from {} IP address(es) in {}s",
+            spike.clicks, spike.distinct_ips, self.config.window_seconds
+        );
+        let throttled_until = match self.config.action {
+            FlagAction::Throttle => {
+                Some(Utc::now() + Duration::seconds(self.config.throttle_seconds))
+            }
+            FlagAction::Notify => None,
+        };
+
+        let flag = self
+            .repository
+            .create_flag(
+                spike,
+                &reason,
+                self.config.action,
+                self.config.window_seconds,
+                throttled_until,
+            )
+            .await?;
+        Ok(flag)
+    }
+
+    // Alert the operators and the link owner's channels; delivery failures are only logged
+    async fn notify(&self, spike: &ClickSpike, flag: &LinkFlag) {
+        let event = NotificationEvent::LinkFlagged {
+            short_code: spike.short_code.clone(),
+            short_url: short_url(&self.base_url, &spike.short_code),
+            reason: flag.reason.clone(),
+            action: flag.action,
+        };
+
+        if let Some(channel) = &self.admin_channel {
+            if let Err(e) = self
+                .dispatcher
+                .send_to(channel.kind, &channel.url, &event)
+                .await
+            {
+                warn!("Failed to alert admins about '{}': {}", spike.short_code, e);
+            }
+        }
+
+        if let Some(owner) = spike.created_by {
+            if let Err(e) = self.dispatcher.dispatch(&owner, &event).await {
+                warn!("Failed to notify owner of '{}': {}", spike.short_code, e);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<R, N> Job for AnomalyDetectionJob<R, N>
+where
+    R: LinkFlagRepositoryTrait + Send + Sync + 'static,
+    N: NotificationRepositoryTrait + Send + Sync + 'static,
+{
+    fn name(&self) -> &'static str {
+        "anomaly_detection"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let flagged = self.run_once().await?;
+        Ok(format!("Flagged {} links", flagged))
+    }
+}
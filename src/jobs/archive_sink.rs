@@ -0,0 +1,219 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::debug;
+use tokio::fs;
+
+use crate::{
+    config::{ArchiveSinkKind, Config},
+    errors::AppError,
+    models::UrlVisit,
+    storage::ObjectStore,
+    types::Result,
+    utils::report::csv_field,
+};
+
+/// Destination for raw visit events that are about to be removed from the database
+///
+/// Sinks are called before the rows are deleted; if `archive` fails the batch is kept
+/// and retried on the next run, so a sink may see the same events more than once.
+#[async_trait]
+pub trait ArchiveSink: Send + Sync {
+    /// Short name used in logs
+    fn name(&self) -> &'static str;
+
+    /// Persists a batch of visit events
+    async fn archive(&self, visits: &[UrlVisit]) -> Result<()>;
+}
+
+/// Drops raw events, keeping only the daily aggregates
+pub struct DiscardSink;
+
+#[async_trait]
+impl ArchiveSink for DiscardSink {
+    fn name(&self) -> &'static str {
+        "none"
+    }
+
+    async fn archive(&self, _visits: &[UrlVisit]) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes each batch of raw events to its own CSV file in a directory
+pub struct CsvFileSink {
+    dir: PathBuf,
+}
+
+impl CsvFileSink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+#[async_trait]
+impl ArchiveSink for CsvFileSink {
+    fn name(&self) -> &'static str {
+        "csv"
+    }
+
+    async fn archive(&self, visits: &[UrlVisit]) -> Result<()> {
+        let (Some(first), Some(last)) = (visits.first(), visits.last()) else {
+            return Ok(());
+        };
+
+        fs::create_dir_all(&self.dir).await?;
+
+        // Name files by ID range so a retried batch overwrites its earlier attempt
+        let path = self
+            .dir
+            .join(format!("url_visits_{}_{}.csv", first.id, last.id));
+
+        fs::write(&path, visits_to_csv(visits)).await?;
+        debug!(
+            "Archived {} visit events to {}",
+            visits.len(),
+            path.display()
+        );
+
+        Ok(())
+    }
+}
+
+/// Uploads each batch of raw events as a CSV object to the configured object store
+pub struct ObjectStoreSink {
+    store: Arc<dyn ObjectStore>,
+}
+
+impl ObjectStoreSink {
+    pub fn new(store: Arc<dyn ObjectStore>) -> Self {
+        Self { store }
+    }
+}
+
+#[async_trait]
+impl ArchiveSink for ObjectStoreSink {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    async fn archive(&self, visits: &[UrlVisit]) -> Result<()> {
+        let (Some(first), Some(last)) = (visits.first(), visits.last()) else {
+            return Ok(());
+        };
+
+        let key = format!("url_visits/url_visits_{}_{}.csv", first.id, last.id);
+        self.store
+            .put_object(&key, visits_to_csv(visits).into_bytes(), "text/csv", None)
+            .await?;
+        debug!("Archived {} visit events to {}", visits.len(), key);
+
+        Ok(())
+    }
+}
+
+/// Build the sink selected in configuration
+pub fn build_sink(
+    config: &Config,
+    store: Option<Arc<dyn ObjectStore>>,
+) -> Result<Arc<dyn ArchiveSink>> {
+    let sink: Arc<dyn ArchiveSink> = match config.analytics.archive_sink {
+        ArchiveSinkKind::None => Arc::new(DiscardSink),
+        ArchiveSinkKind::Csv => Arc::new(CsvFileSink::new(&config.analytics.archive_dir)),
+        ArchiveSinkKind::S3 => match store {
+            Some(store) => Arc::new(ObjectStoreSink::new(store)),
+            None => {
+                return Err(AppError::Config(
+                    "VISIT_ARCHIVE_SINK=s3 requires S3_BUCKET to be configured".to_string(),
+                ))
+            }
+        },
+    };
+
+    Ok(sink)
+}
+
+// Render visit events as CSV with a header row
+fn visits_to_csv(visits: &[UrlVisit]) -> String {
+    let mut out = String::from("id,url_id,visited_at,ip_address,user_agent,referer\n");
+    for visit in visits {
+        let row = [
+            visit.id.to_string(),
+            visit.url_id.to_string(),
+            visit.visited_at.to_rfc3339(),
+            visit.ip_address.clone().unwrap_or_default(),
+            visit.user_agent.clone().unwrap_or_default(),
+            visit.referer.clone().unwrap_or_default(),
+        ];
+        let fields = row.iter().map(|f| csv_field(f)).collect::<Vec<_>>();
+        out.push_str(&fields.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct MemoryStore {
+        objects: Mutex<Vec<(String, String)>>,
+    }
+
+    #[async_trait]
+    impl ObjectStore for MemoryStore {
+        fn location(&self) -> String {
+            "memory".to_string()
+        }
+
+        async fn put_object(
+            &self,
+            key: &str,
+            body: Vec<u8>,
+            _content_type: &str,
+            _content_encoding: Option<&str>,
+        ) -> Result<()> {
+            let body = String::from_utf8(body).unwrap();
+            self.objects.lock().unwrap().push((key.to_string(), body));
+            Ok(())
+        }
+    }
+
+    fn visit(id: i64) -> UrlVisit {
+        UrlVisit {
+            id,
+            url_id: Uuid::nil(),
+            visited_at: Utc::now(),
+            ip_address: Some("203.0.113.9".to_string()),
+            user_agent: Some("agent, with comma".to_string()),
+            referer: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_object_store_sink_uploads_batches_by_id_range() {
+        let store = Arc::new(MemoryStore::default());
+        let sink = ObjectStoreSink::new(store.clone());
+
+        sink.archive(&[]).await.unwrap();
+        sink.archive(&[visit(7), visit(9)]).await.unwrap();
+
+        let objects = store.objects.lock().unwrap();
+        assert_eq!(objects.len(), 1);
+        let (key, body) = &objects[0];
+        assert_eq!(key, "url_visits/url_visits_7_9.csv");
+        assert_eq!(body.lines().count(), 3);
+        assert!(body
+            .lines()
+            .nth(1)
+            .unwrap()
+            .ends_with(",\"agent, with comma\","));
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::Job;
+use crate::{handlers::BackupServiceType, types::Result};
+
+/// Dumps the database to object storage
+///
+/// The service is shared with the admin API, which keeps runs from overlapping.
+pub struct BackupJob {
+    service: Arc<BackupServiceType>,
+}
+
+impl BackupJob {
+    pub fn new(service: Arc<BackupServiceType>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl Job for BackupJob {
+    fn name(&self) -> &'static str {
+        "backup"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let report = self.service.run_backup().await?;
+        Ok(format!(
+            "Uploaded {} objects to {}",
+            report.objects.len(),
+            report.location
+        ))
+    }
+}
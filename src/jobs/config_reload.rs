@@ -0,0 +1,20 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::error;
+use tokio::task::JoinHandle;
+
+use crate::live_config::LiveConfig;
+
+/// Reload settings safe to change while running whenever the config file is modified
+pub fn spawn_config_reload(live_config: Arc<LiveConfig>, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = live_config.reload_if_changed() {
+                error!("Failed to reload the config file: {}", e);
+            }
+        }
+    })
+}
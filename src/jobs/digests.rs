@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Datelike, Days, NaiveDate, NaiveTime, Utc};
+use log::warn;
+
+use super::Job;
+use crate::{
+    models::{DigestFrequency, DigestRecipient, LinkDigest},
+    notifications::{digest_email, ChannelDispatcher, NotificationEvent, SmtpMailer},
+    repositories::{NotificationRepositoryTrait, StatsRepositoryTrait},
+    types::Result,
+    utils::http_client::HttpClient,
+};
+
+// Upper bound on owners handled per frequency and run so a backlog is spread over several ticks
+const BATCH_SIZE: i64 = 100;
+
+/// Sends link owners a weekly or monthly digest of their most clicked links and click trend,
+/// by email and on their channels
+///
+/// Digests cover the last full period, Monday to Sunday or a calendar month in UTC, and are
+/// sent once per owner and period. Owners whose links got no clicks in the period nor the
+/// one before get nothing.
+pub struct DigestJob<R: NotificationRepositoryTrait, S: StatsRepositoryTrait> {
+    repository: Arc<R>,
+    stats: Arc<S>,
+    mailer: Option<Arc<SmtpMailer>>,
+    dispatcher: ChannelDispatcher<R>,
+    top_links: i64,
+    base_url: String,
+}
+
+impl<R, S> DigestJob<R, S>
+where
+    R: NotificationRepositoryTrait + Send + Sync + 'static,
+    S: StatsRepositoryTrait + Send + Sync + 'static,
+{
+    pub fn new(
+        repository: Arc<R>,
+        stats: Arc<S>,
+        mailer: Option<Arc<SmtpMailer>>,
+        top_links: i64,
+        base_url: String,
+        http_client: Arc<HttpClient>,
+    ) -> Self {
+        Self {
+            dispatcher: ChannelDispatcher::new(repository.clone(), http_client),
+            repository,
+            stats,
+            mailer,
+            top_links,
+            base_url,
+        }
+    }
+
+    /// Send one batch of pending digests of each frequency
+    ///
+    /// Returns the number of digests sent. Owners whose email fails are retried on the
+    /// next run; channel failures are only logged.
+    pub async fn run_once(&self) -> Result<u64> {
+        let today = Utc::now().date_naive();
+
+        let mut sent = 0;
+        for frequency in [DigestFrequency::Weekly, DigestFrequency::Monthly] {
+            let (start, end) = digest_period(frequency, today);
+            let recipients = self
+                .repository
+                .find_digest_recipients(frequency, start, BATCH_SIZE)
+                .await?;
+
+            for recipient in recipients {
+                let digest = self.compile(&recipient, frequency, start, end).await?;
+                if digest.clicks > 0 || digest.previous_clicks > 0 {
+                    if let Some(mailer) = &self.mailer {
+                        if let Err(e) = mailer.send(digest_email(&recipient, &digest)).await {
+                            warn!("Failed to send digest to user {}: {}", recipient.user_id, e);
+                            continue;
+                        }
+                    }
+
+                    let event = NotificationEvent::LinkDigest(digest);
+                    self.dispatcher.dispatch(&recipient.user_id, &event).await?;
+                    sent += 1;
+                }
+
+                self.repository
+                    .mark_digest_sent(&recipient.user_id, frequency, start)
+                    .await?;
+            }
+        }
+
+        Ok(sent)
+    }
+
+    // Gather an owner's clicks over the period and the one before it
+    async fn compile(
+        &self,
+        recipient: &DigestRecipient,
+        frequency: DigestFrequency,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> Result<LinkDigest> {
+        let (previous_start, _) = digest_period(frequency, start);
+        let midnight = |day: NaiveDate| day.and_time(NaiveTime::MIN).and_utc();
+        let (from, until) = (midnight(start), midnight(end + Days::new(1)));
+
+        let clicks = self
+            .stats
+            .count_owner_clicks(&recipient.user_id, from, until)
+            .await?;
+        let previous_clicks = self
+            .stats
+            .count_owner_clicks(&recipient.user_id, midnight(previous_start), from)
+            .await?;
+        let top_links = self
+            .stats
+            .find_owner_top_links(
+                &recipient.user_id,
+                from,
+                until,
+                &self.base_url,
+                self.top_links,
+            )
+            .await?;
+
+        Ok(LinkDigest {
+            frequency,
+            period_start: start,
+            period_end: end,
+            clicks,
+            previous_clicks,
+            top_links,
+        })
+    }
+}
+
+#[async_trait]
+impl<R, S> Job for DigestJob<R, S>
+where
+    R: NotificationRepositoryTrait + Send + Sync + 'static,
+    S: StatsRepositoryTrait + Send + Sync + 'static,
+{
+    fn name(&self) -> &'static str {
+        "digests"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let sent = self.run_once().await?;
+        Ok(format!("Sent {} digests", sent))
+    }
+}
+
+/// First and last day of the last full period before `today`: the previous Monday to
+/// Sunday week, or the previous calendar month
+pub fn digest_period(frequency: DigestFrequency, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+    match frequency {
+        DigestFrequency::Weekly => {
+            let monday = today - Days::new(today.weekday().num_days_from_monday().into());
+            (monday - Days::new(7), monday - Days::new(1))
+        }
+        DigestFrequency::Monthly => {
+            let end = today.with_day(1).unwrap_or(today) - Days::new(1);
+            (end.with_day(1).unwrap_or(end), end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_digest_period() {
+        // Wednesday and Monday of the same week
+        let weekly = (day(2026, 1, 5), day(2026, 1, 11));
+        assert_eq!(
+            digest_period(DigestFrequency::Weekly, day(2026, 1, 14)),
+            weekly
+        );
+        assert_eq!(
+            digest_period(DigestFrequency::Weekly, day(2026, 1, 12)),
+            weekly
+        );
+        assert_eq!(
+            digest_period(DigestFrequency::Weekly, day(2026, 1, 1)),
+            (day(2025, 12, 22), day(2025, 12, 28))
+        );
+
+        assert_eq!(
+            digest_period(DigestFrequency::Monthly, day(2026, 3, 1)),
+            (day(2026, 2, 1), day(2026, 2, 28))
+        );
+        assert_eq!(
+            digest_period(DigestFrequency::Monthly, day(2026, 1, 20)),
+            (day(2025, 12, 1), day(2025, 12, 31))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+
+use super::Job;
+use crate::{
+    events::{DomainEvent, EventBus},
+    repositories::ShortenedUrlRepositoryTrait,
+    types::Result,
+};
+
+// Expiries older than this when first seen, e.g. after downtime, are not announced anymore
+const LOOKBACK_HOURS: i64 = 24;
+
+/// Publishes an event for each link that reached its expiry date
+pub struct ExpiryEventJob<R: ShortenedUrlRepositoryTrait> {
+    repository: Arc<R>,
+    events: Arc<EventBus>,
+}
+
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync + 'static> ExpiryEventJob<R> {
+    pub fn new(repository: Arc<R>, events: Arc<EventBus>) -> Self {
+        Self { repository, events }
+    }
+
+    /// Publish the expiries not published yet, returning how many there were
+    pub async fn run_once(&self) -> Result<usize> {
+        let since = Utc::now() - Duration::hours(LOOKBACK_HOURS);
+        let expired = self.repository.claim_expired(since).await?;
+        for url in &expired {
+            self.events.publish(DomainEvent::url_expired(url));
+        }
+        Ok(expired.len())
+    }
+}
+
+#[async_trait]
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync + 'static> Job for ExpiryEventJob<R> {
+    fn name(&self) -> &'static str {
+        "expiry_events"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let count = self.run_once().await?;
+        Ok(format!("Published {} expiry events", count))
+    }
+}
@@ -0,0 +1,99 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use log::warn;
+
+use super::Job;
+use crate::{
+    notifications::{
+        link_expiring_email, short_url, ChannelDispatcher, NotificationEvent, SmtpMailer,
+    },
+    repositories::NotificationRepositoryTrait,
+    types::Result,
+    utils::http_client::HttpClient,
+};
+
+// Upper bound on links handled per run so a backlog is spread over several ticks
+const BATCH_SIZE: i64 = 200;
+
+/// Tells link owners, by email and on their team channels, when their links are about to expire
+pub struct ExpiryNotificationJob<R: NotificationRepositoryTrait> {
+    repository: Arc<R>,
+    mailer: Option<Arc<SmtpMailer>>,
+    dispatcher: ChannelDispatcher<R>,
+    default_notice_days: i32,
+    base_url: String,
+}
+
+impl<R: NotificationRepositoryTrait + Send + Sync + 'static> ExpiryNotificationJob<R> {
+    pub fn new(
+        repository: Arc<R>,
+        mailer: Option<Arc<SmtpMailer>>,
+        default_notice_days: i32,
+        base_url: String,
+        http_client: Arc<HttpClient>,
+    ) -> Self {
+        Self {
+            dispatcher: ChannelDispatcher::new(repository.clone(), http_client),
+            repository,
+            mailer,
+            default_notice_days,
+            base_url,
+        }
+    }
+
+    /// Send one batch of pending expiry notices
+    ///
+    /// Returns the number of links notified. Links whose email fails are retried on the
+    /// next run; channel failures are only logged.
+    pub async fn run_once(&self) -> Result<u64> {
+        let links = self
+            .repository
+            .find_unnotified_expiring_links(self.default_notice_days, BATCH_SIZE)
+            .await?;
+
+        let now = Utc::now();
+        let mut notified = 0;
+        for link in links {
+            let base_url = link.base_url.as_deref().unwrap_or(&self.base_url);
+            if let (Some(mailer), true) = (&self.mailer, link.email_enabled) {
+                let email = link_expiring_email(&link, base_url, now);
+                if let Err(e) = mailer.send(email).await {
+                    warn!(
+                        "Failed to send expiry notice for '{}': {}",
+                        link.short_code, e
+                    );
+                    continue;
+                }
+            }
+
+            let event = NotificationEvent::LinkExpiring {
+                short_url: short_url(base_url, &link.short_code),
+                short_code: link.short_code.clone(),
+                original_url: link.original_url.clone(),
+                expires_at: link.expires_at,
+            };
+            self.dispatcher.dispatch(&link.owner_id, &event).await?;
+
+            self.repository
+                .mark_expiry_notified(&link.url_id, link.expires_at)
+                .await?;
+            notified += 1;
+        }
+
+        Ok(notified)
+    }
+}
+
+#[async_trait]
+impl<R: NotificationRepositoryTrait + Send + Sync + 'static> Job for ExpiryNotificationJob<R> {
+    fn name(&self) -> &'static str {
+        "expiry_notifications"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let notified = self.run_once().await?;
+        Ok(format!("Notified {} links", notified))
+    }
+}
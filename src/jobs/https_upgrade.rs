@@ -0,0 +1,93 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::{debug, info};
+
+use super::Job;
+use crate::{
+    errors::RepositoryError,
+    models::ShortenedUrlUpdateParams,
+    repositories::{commit, ShortenedUrlRepositoryTrait},
+    types::Result,
+    utils::https_upgrade::HttpsUpgrader,
+};
+
+/// Re-checks stored http:// destinations and upgrades those whose host now serves HTTPS
+///
+/// Upgrades are regular edits: the old destination is kept as a revision, and a link
+/// changed since it was read is left for the next run.
+pub struct HttpsUpgradeJob<R: ShortenedUrlRepositoryTrait> {
+    repository: Arc<R>,
+    upgrader: Arc<HttpsUpgrader>,
+    batch_size: i64,
+}
+
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync + 'static> HttpsUpgradeJob<R> {
+    pub fn new(repository: Arc<R>, upgrader: Arc<HttpsUpgrader>, batch_size: i64) -> Self {
+        Self {
+            repository,
+            upgrader,
+            batch_size,
+        }
+    }
+
+    /// Check every eligible link, returning how many were checked and how many upgraded
+    pub async fn run_once(&self) -> Result<(usize, usize)> {
+        let (mut checked, mut upgraded) = (0, 0);
+        let mut after = None;
+        loop {
+            let batch = self
+                .repository
+                .find_http_destinations(self.upgrader.by_default(), after, self.batch_size)
+                .await?;
+            let Some(last) = batch.last() else {
+                return Ok((checked, upgraded));
+            };
+            after = Some(last.id);
+
+            for url in batch {
+                checked += 1;
+                let Some(https) = self.upgrader.upgrade(&url.original_url).await else {
+                    continue;
+                };
+
+                let params = ShortenedUrlUpdateParams {
+                    original_url: Some(https.clone()),
+                    version: Some(url.version),
+                    ..Default::default()
+                };
+                let mut tx = self.repository.begin().await?;
+                match self
+                    .repository
+                    .update_in(&mut tx, &url.id, &params, None)
+                    .await
+                {
+                    Ok(_) => {
+                        commit(tx).await?;
+                        upgraded += 1;
+                        info!("Upgraded '{}' to '{}'", url.path(), https);
+                    }
+                    Err(RepositoryError::Conflict(_)) => {
+                        debug!("'{}' changed while being upgraded, skipping it", url.path());
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync + 'static> Job for HttpsUpgradeJob<R> {
+    fn name(&self) -> &'static str {
+        "https_upgrade"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let (checked, upgraded) = self.run_once().await?;
+        Ok(format!(
+            "Upgraded {} of {} http:// links to https://",
+            upgraded, checked
+        ))
+    }
+}
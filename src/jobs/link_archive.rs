@@ -0,0 +1,42 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+
+use super::Job;
+use crate::{repositories::ShortenedUrlRepositoryTrait, types::Result};
+
+/// Archives links nobody followed for a number of days, leaving them out of listings while
+/// they keep redirecting
+pub struct LinkArchiveJob<R: ShortenedUrlRepositoryTrait> {
+    repository: Arc<R>,
+    after_days: u32,
+}
+
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync + 'static> LinkArchiveJob<R> {
+    pub fn new(repository: Arc<R>, after_days: u32) -> Self {
+        Self {
+            repository,
+            after_days,
+        }
+    }
+
+    /// Archive every link unused for `after_days`, returning how many were archived
+    pub async fn run_once(&self) -> Result<u64> {
+        let cutoff = Utc::now() - Duration::days(self.after_days as i64);
+        let archived = self.repository.archive_unused(cutoff).await?;
+        Ok(archived)
+    }
+}
+
+#[async_trait]
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync + 'static> Job for LinkArchiveJob<R> {
+    fn name(&self) -> &'static str {
+        "link_archive"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let count = self.run_once().await?;
+        Ok(format!("Archived {} unused links", count))
+    }
+}
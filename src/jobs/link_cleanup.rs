@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::Job;
+use crate::{repositories::ShortenedUrlRepositoryTrait, types::Result};
+
+/// Deactivates links that outlived their idle limit without being accessed, and removes the
+/// claim tokens of anonymous links past their expiry
+pub struct LinkCleanupJob<R: ShortenedUrlRepositoryTrait> {
+    repository: Arc<R>,
+}
+
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync + 'static> LinkCleanupJob<R> {
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository }
+    }
+
+    /// Deactivate every idle link, returning how many were deactivated
+    pub async fn run_once(&self) -> Result<u64> {
+        let deactivated = self.repository.deactivate_idle().await?;
+        Ok(deactivated)
+    }
+}
+
+#[async_trait]
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync + 'static> Job for LinkCleanupJob<R> {
+    fn name(&self) -> &'static str {
+        "link_cleanup"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let count = self.run_once().await?;
+        let claims = self.repository.purge_expired_claims().await?;
+        Ok(format!(
+            "Deactivated {} idle links, removed {} expired claim tokens",
+            count, claims
+        ))
+    }
+}
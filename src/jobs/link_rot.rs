@@ -0,0 +1,187 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use log::{info, warn};
+use reqwest::StatusCode;
+
+use super::Job;
+use crate::{
+    config::LinkRotConfig,
+    models::ShortenedUrl,
+    notifications::{short_url, ChannelDispatcher, NotificationEvent},
+    repositories::{NotificationRepositoryTrait, ShortenedUrlRepositoryTrait},
+    types::Result,
+    utils::http_client::HttpClient,
+};
+
+/// Checks whether link destinations still answer, recording the outcome on each link
+///
+/// Every run samples the links checked longest ago, so all of them get their turn over
+/// successive runs. Owners hear about a link once, when it turns broken.
+pub struct LinkRotJob<R: ShortenedUrlRepositoryTrait, N: NotificationRepositoryTrait> {
+    repository: Arc<R>,
+    dispatcher: ChannelDispatcher<N>,
+    client: Arc<HttpClient>,
+    config: LinkRotConfig,
+    base_url: String,
+}
+
+impl<R, N> LinkRotJob<R, N>
+where
+    R: ShortenedUrlRepositoryTrait + Send + Sync + 'static,
+    N: NotificationRepositoryTrait + Send + Sync + 'static,
+{
+    pub fn new(
+        repository: Arc<R>,
+        notification_repository: Arc<N>,
+        config: LinkRotConfig,
+        base_url: String,
+        client: Arc<HttpClient>,
+    ) -> Self {
+        Self {
+            repository,
+            dispatcher: ChannelDispatcher::new(notification_repository, client.clone()),
+            client,
+            config,
+            base_url,
+        }
+    }
+
+    /// Check one sample of links, returning how many were checked and how many are broken
+    pub async fn run_once(&self) -> Result<(usize, usize)> {
+        let links = self
+            .repository
+            .find_links_to_check(self.config.sample_size)
+            .await?;
+        let checked = links.len();
+
+        let outcomes = stream::iter(links)
+            .map(|url| async move {
+                let status = self.check(&url.original_url).await;
+                (url, status)
+            })
+            .buffer_unordered(self.config.concurrency)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut broken = 0;
+        for (url, status) in outcomes {
+            let is_broken = is_broken(status);
+            let status = status.map(|status| status.as_u16() as i32);
+            self.repository
+                .record_check(&url.id, status, is_broken)
+                .await?;
+            if !is_broken {
+                continue;
+            }
+
+            broken += 1;
+            if !url.is_broken {
+                info!(
+                    "'{}' is broken: {} answered {:?}",
+                    url.path(),
+                    url.original_url,
+                    status
+                );
+                if self.config.notify {
+                    self.notify(&url, status).await;
+                }
+            }
+        }
+
+        Ok((checked, broken))
+    }
+
+    // Status of the destination's answer, none if it couldn't be reached
+    async fn check(&self, destination: &str) -> Option<StatusCode> {
+        let timeout = Duration::from_millis(self.config.timeout_ms);
+        let status = self.send(self.client.head(destination), timeout).await?;
+        // Some servers don't implement HEAD, ask for the page itself then
+        if status == StatusCode::METHOD_NOT_ALLOWED || status == StatusCode::NOT_IMPLEMENTED {
+            return self.send(self.client.get(destination), timeout).await;
+        }
+        Some(status)
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        timeout: Duration,
+    ) -> Option<StatusCode> {
+        self.client
+            .send(request.timeout(timeout))
+            .await
+            .ok()
+            .map(|response| response.status())
+    }
+
+    async fn notify(&self, url: &ShortenedUrl, status: Option<i32>) {
+        let Some(owner_id) = &url.created_by else {
+            return;
+        };
+        let event = NotificationEvent::LinkBroken {
+            short_code: url.path(),
+            short_url: short_url(&self.base_url, &url.path()),
+            original_url: url.original_url.clone(),
+            status,
+        };
+        if let Err(e) = self.dispatcher.dispatch(owner_id, &event).await {
+            warn!(
+                "Failed to notify owner of broken link '{}': {}",
+                url.path(),
+                e
+            );
+        }
+    }
+}
+
+/// Whether a destination answering with `status` counts as broken
+///
+/// Only answers saying the page is gone or the server is failing count; redirects and
+/// other client errors, such as a login wall, mean something still lives there.
+fn is_broken(status: Option<StatusCode>) -> bool {
+    match status {
+        None => true,
+        Some(status) => {
+            status == StatusCode::NOT_FOUND
+                || status == StatusCode::GONE
+                || status.is_server_error()
+        }
+    }
+}
+
+#[async_trait]
+impl<R, N> Job for LinkRotJob<R, N>
+where
+    R: ShortenedUrlRepositoryTrait + Send + Sync + 'static,
+    N: NotificationRepositoryTrait + Send + Sync + 'static,
+{
+    fn name(&self) -> &'static str {
+        "link_rot"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let (checked, broken) = self.run_once().await?;
+        Ok(format!("Checked {} links, {} broken", checked, broken))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_broken_statuses() {
+        assert!(is_broken(None));
+        assert!(is_broken(Some(StatusCode::NOT_FOUND)));
+        assert!(is_broken(Some(StatusCode::GONE)));
+        assert!(is_broken(Some(StatusCode::BAD_GATEWAY)));
+
+        assert!(!is_broken(Some(StatusCode::OK)));
+        assert!(!is_broken(Some(StatusCode::MOVED_PERMANENTLY)));
+        assert!(!is_broken(Some(StatusCode::FORBIDDEN)));
+        assert!(!is_broken(Some(StatusCode::TOO_MANY_REQUESTS)));
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::Job;
+use crate::{
+    notifications::{short_url, ChannelDispatcher, NotificationEvent},
+    repositories::NotificationRepositoryTrait,
+    types::Result,
+    utils::http_client::HttpClient,
+};
+
+// Upper bound on milestones handled per run so a backlog is spread over several ticks
+const BATCH_SIZE: i64 = 200;
+
+/// Tells link owners on their channels when their links reach a click milestone
+///
+/// Redirects record each milestone once, this job delivers them. Milestones of anonymous
+/// links are marked as notified without sending anything.
+pub struct MilestoneNotificationJob<R: NotificationRepositoryTrait> {
+    repository: Arc<R>,
+    dispatcher: ChannelDispatcher<R>,
+    base_url: String,
+}
+
+impl<R: NotificationRepositoryTrait + Send + Sync + 'static> MilestoneNotificationJob<R> {
+    pub fn new(repository: Arc<R>, base_url: String, http_client: Arc<HttpClient>) -> Self {
+        Self {
+            dispatcher: ChannelDispatcher::new(repository.clone(), http_client),
+            repository,
+            base_url,
+        }
+    }
+
+    /// Send one batch of pending milestone notifications
+    ///
+    /// Returns the number of milestones handled. Channel failures are only logged.
+    pub async fn run_once(&self) -> Result<u64> {
+        let milestones = self
+            .repository
+            .find_unnotified_milestones(BATCH_SIZE)
+            .await?;
+
+        let mut notified = 0;
+        for milestone in milestones {
+            if let Some(owner_id) = &milestone.owner_id {
+                let event = NotificationEvent::LinkMilestone {
+                    short_url: short_url(
+                        milestone.base_url.as_deref().unwrap_or(&self.base_url),
+                        &milestone.short_code,
+                    ),
+                    short_code: milestone.short_code.clone(),
+                    original_url: milestone.original_url.clone(),
+                    clicks: milestone.milestone,
+                    reached_at: milestone.reached_at,
+                };
+                self.dispatcher.dispatch(owner_id, &event).await?;
+            }
+
+            self.repository
+                .mark_milestone_notified(&milestone.url_id, milestone.milestone)
+                .await?;
+            notified += 1;
+        }
+
+        Ok(notified)
+    }
+}
+
+#[async_trait]
+impl<R: NotificationRepositoryTrait + Send + Sync + 'static> Job for MilestoneNotificationJob<R> {
+    fn name(&self) -> &'static str {
+        "milestone_notifications"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let notified = self.run_once().await?;
+        Ok(format!("Handled {} milestones", notified))
+    }
+}
@@ -0,0 +1,396 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{info, warn};
+
+mod access_replay;
+mod anomaly_detection;
+mod archive_sink;
+mod backup;
+mod config_reload;
+mod digests;
+mod expiry_events;
+mod expiry_notifications;
+mod https_upgrade;
+mod link_archive;
+mod link_cleanup;
+mod link_rot;
+mod milestone_notifications;
+mod scanner_guard;
+mod schedule;
+mod scheduler;
+mod tenant_sync;
+mod trash_purge;
+mod visit_export;
+mod visit_retention;
+
+pub use access_replay::spawn_access_replay;
+pub use anomaly_detection::AnomalyDetectionJob;
+pub use archive_sink::{build_sink, ArchiveSink};
+pub use backup::BackupJob;
+pub use config_reload::spawn_config_reload;
+pub use digests::DigestJob;
+pub use expiry_events::ExpiryEventJob;
+pub use expiry_notifications::ExpiryNotificationJob;
+pub use https_upgrade::HttpsUpgradeJob;
+pub use link_archive::LinkArchiveJob;
+pub use link_cleanup::LinkCleanupJob;
+pub use link_rot::LinkRotJob;
+pub use milestone_notifications::MilestoneNotificationJob;
+pub use scanner_guard::spawn_scanner_guard_sync;
+pub use schedule::Schedule;
+pub use scheduler::{Job, JobLockStats, JobScheduler};
+pub use tenant_sync::spawn_tenant_sync;
+pub use trash_purge::TrashPurgeJob;
+pub use visit_export::spawn_visit_export;
+pub use visit_retention::VisitRetentionJob;
+
+use crate::{
+    config::Config,
+    db::Database,
+    errors::AppError,
+    events::EventBus,
+    handlers::{
+        BackupServiceType, JobSchedulerType, ScannerGuardServiceType, ShortenedUrlServiceType,
+        TenantServiceType,
+    },
+    live_config::LiveConfig,
+    notifications::SmtpMailer,
+    repositories::{
+        AnalyticsRepository, JobRepository, LinkFlagRepository, NotificationRepository,
+        ShortenedUrlRepository, StatsRepository,
+    },
+    storage::ObjectStore,
+    types::Result,
+    utils::{http_client::HttpClient, https_upgrade::HttpsUpgrader, maintenance::MaintenanceMode},
+    warehouse::VisitExporter,
+};
+
+/// Services created once in `app` that jobs share with the HTTP workers
+pub struct SharedServices {
+    pub backup: Arc<BackupServiceType>,
+    pub scanner_guard: Arc<ScannerGuardServiceType>,
+    pub shortened_urls: Arc<ShortenedUrlServiceType>,
+    pub tenants: Arc<TenantServiceType>,
+    pub http_client: Arc<HttpClient>,
+    pub events: Arc<EventBus>,
+    pub visit_exporter: Option<Arc<VisitExporter>>,
+    pub https_upgrader: Arc<HttpsUpgrader>,
+    pub maintenance: Arc<MaintenanceMode>,
+    pub live_config: Arc<LiveConfig>,
+}
+
+/// Start the background jobs enabled in configuration
+///
+/// Scheduled jobs run every `*_INTERVAL_SECONDS` unless `JOB_SCHEDULES` gives them another
+/// schedule. Returns the scheduler, through which the admin API lists and triggers them.
+pub async fn spawn_background_jobs(
+    db: &Database,
+    config: &Config,
+    store: Option<Arc<dyn ObjectStore>>,
+    shared: SharedServices,
+) -> Result<Arc<JobSchedulerType>> {
+    let mut scheduler = JobScheduler::new(Arc::new(JobRepository::new(db.clone())));
+    scheduler.pause_during(shared.maintenance.clone());
+    let schedule = |name: &str, interval_seconds: u64| -> Result<Schedule> {
+        match config.jobs.schedules.get(name) {
+            Some(expression) => expression.parse().map_err(|e| {
+                AppError::Config(format!("Invalid schedule for job '{}': {}", name, e))
+            }),
+            None => Ok(Schedule::Every(Duration::from_secs(interval_seconds))),
+        }
+    };
+
+    if config.analytics.retention_enabled {
+        let sink = build_sink(config, store)?;
+        info!(
+            "Starting visit retention job (keep {} days, '{}' sink)",
+            config.analytics.retention_days,
+            sink.name()
+        );
+
+        scheduler.add(
+            VisitRetentionJob::new(
+                Arc::new(AnalyticsRepository::new(db.clone())),
+                sink,
+                config.analytics.retention_days,
+                config.analytics.retention_batch_size,
+            ),
+            schedule(
+                "visit_retention",
+                config.analytics.retention_interval_seconds,
+            )?,
+            true,
+        );
+    }
+
+    if config.backup.enabled {
+        if config.s3.is_none() {
+            return Err(AppError::Config(
+                "BACKUP_ENABLED requires S3_BUCKET to be configured".to_string(),
+            ));
+        }
+
+        info!("Starting backup job");
+        // A restart shouldn't immediately trigger a full dump
+        scheduler.add(
+            BackupJob::new(shared.backup),
+            schedule("backup", config.backup.interval_seconds)?,
+            false,
+        );
+    }
+
+    if config.notifications.expiry_enabled {
+        // Without SMTP, expiry notices only go to team channels
+        let mailer = match &config.smtp {
+            Some(smtp) => Some(Arc::new(SmtpMailer::new(smtp)?)),
+            None => None,
+        };
+
+        info!(
+            "Starting expiry notification job (email {})",
+            if mailer.is_some() {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+        scheduler.add(
+            ExpiryNotificationJob::new(
+                Arc::new(NotificationRepository::new(db.clone())),
+                mailer,
+                config.notifications.expiry_notice_days,
+                config.notifications.public_base_url.clone(),
+                shared.http_client.clone(),
+            ),
+            schedule(
+                "expiry_notifications",
+                config.notifications.expiry_interval_seconds,
+            )?,
+            true,
+        );
+    }
+
+    if config.notifications.digest_enabled {
+        // Without SMTP, digests only go to team channels
+        let mailer = match &config.smtp {
+            Some(smtp) => Some(Arc::new(SmtpMailer::new(smtp)?)),
+            None => None,
+        };
+
+        info!(
+            "Starting digest job (email {})",
+            if mailer.is_some() {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+        scheduler.add(
+            DigestJob::new(
+                Arc::new(NotificationRepository::new(db.clone())),
+                Arc::new(StatsRepository::new(db.clone())),
+                mailer,
+                config.notifications.digest_top_links,
+                config.notifications.public_base_url.clone(),
+                shared.http_client.clone(),
+            ),
+            schedule("digests", config.notifications.digest_interval_seconds)?,
+            true,
+        );
+    }
+
+    if !config.milestones.thresholds.is_empty() {
+        info!(
+            "Starting milestone notification job (milestones {:?})",
+            config.milestones.thresholds
+        );
+        scheduler.add(
+            MilestoneNotificationJob::new(
+                Arc::new(NotificationRepository::new(db.clone())),
+                config.notifications.public_base_url.clone(),
+                shared.http_client.clone(),
+            ),
+            schedule(
+                "milestone_notifications",
+                config.milestones.notify_interval_seconds,
+            )?,
+            true,
+        );
+    }
+
+    if config.anomaly.enabled {
+        info!(
+            "Starting anomaly detection job (>= {} clicks from <= {} IPs per {}s, action '{}')",
+            config.anomaly.min_clicks,
+            config.anomaly.max_distinct_ips,
+            config.anomaly.window_seconds,
+            serde_json::to_string(&config.anomaly.action).unwrap_or_default()
+        );
+        scheduler.add(
+            AnomalyDetectionJob::new(
+                Arc::new(LinkFlagRepository::new(db.clone())),
+                Arc::new(NotificationRepository::new(db.clone())),
+                config.anomaly.clone(),
+                config.admin_channel.clone(),
+                config.notifications.public_base_url.clone(),
+                shared.http_client.clone(),
+            ),
+            schedule("anomaly_detection", config.anomaly.interval_seconds)?,
+            true,
+        );
+    }
+
+    if config.link_cleanup.enabled {
+        info!("Starting idle link cleanup job");
+        scheduler.add(
+            LinkCleanupJob::new(Arc::new(ShortenedUrlRepository::new(db.clone()))),
+            schedule("link_cleanup", config.link_cleanup.interval_seconds)?,
+            true,
+        );
+    }
+
+    if config.link_archive.enabled {
+        info!(
+            "Starting link archive job (unused for {} days)",
+            config.link_archive.after_days
+        );
+        scheduler.add(
+            LinkArchiveJob::new(
+                Arc::new(ShortenedUrlRepository::new(db.clone())),
+                config.link_archive.after_days,
+            ),
+            schedule("link_archive", config.link_archive.interval_seconds)?,
+            true,
+        );
+    }
+
+    if config.trash.purge_enabled {
+        info!(
+            "Starting trash purge job (keep {} days)",
+            config.trash.retention_days
+        );
+        scheduler.add(
+            TrashPurgeJob::new(
+                Arc::new(ShortenedUrlRepository::new(db.clone())),
+                config.trash.retention_days,
+                config.trash.purge_batch_size,
+            ),
+            schedule("trash_purge", config.trash.purge_interval_seconds)?,
+            true,
+        );
+    }
+
+    if config.https_upgrade.job_enabled {
+        info!(
+            "Starting HTTPS upgrade job ({} links by default)",
+            if config.https_upgrade.enabled {
+                "all"
+            } else {
+                "opted-in"
+            }
+        );
+        scheduler.add(
+            HttpsUpgradeJob::new(
+                Arc::new(ShortenedUrlRepository::new(db.clone())),
+                shared.https_upgrader,
+                config.https_upgrade.batch_size,
+            ),
+            schedule("https_upgrade", config.https_upgrade.interval_seconds)?,
+            false,
+        );
+    }
+
+    if config.link_rot.enabled {
+        info!(
+            "Starting link rot check ({} links per run, notifications {})",
+            config.link_rot.sample_size,
+            if config.link_rot.notify {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        );
+        scheduler.add(
+            LinkRotJob::new(
+                Arc::new(ShortenedUrlRepository::new(db.clone())),
+                Arc::new(NotificationRepository::new(db.clone())),
+                config.link_rot.clone(),
+                config.notifications.public_base_url.clone(),
+                shared.http_client,
+            ),
+            schedule("link_rot", config.link_rot.interval_seconds)?,
+            true,
+        );
+    }
+
+    // Always on, subscribers such as owner notifications rely on it
+    scheduler.add(
+        ExpiryEventJob::new(
+            Arc::new(ShortenedUrlRepository::new(db.clone())),
+            shared.events,
+        ),
+        schedule("expiry_events", config.events.expiry_interval_seconds)?,
+        true,
+    );
+
+    for name in config.jobs.schedules.keys() {
+        if !scheduler.has_job(name) {
+            warn!(
+                "JOB_SCHEDULES names job '{}', which is unknown or not enabled",
+                name
+            );
+        }
+    }
+    let scheduler = Arc::new(scheduler);
+    scheduler.start().await?;
+
+    // Always on, it only touches the database after an outage or maintenance left counts behind
+    spawn_access_replay(
+        shared.shortened_urls,
+        shared.maintenance,
+        Duration::from_secs(config.access_replay.interval_seconds),
+    );
+
+    // Always on, tenants are few and resolving requests relies on them
+    spawn_tenant_sync(
+        shared.tenants,
+        Duration::from_secs(config.tenants.sync_interval_seconds),
+    );
+
+    if config.config_reload.interval_seconds > 0 {
+        info!(
+            "Watching {} for settings to reload (every {}s)",
+            config.config_reload.file, config.config_reload.interval_seconds
+        );
+        spawn_config_reload(
+            shared.live_config,
+            Duration::from_secs(config.config_reload.interval_seconds),
+        );
+    }
+
+    if let Some(exporter) = shared.visit_exporter {
+        info!(
+            "Starting visit export to {} (every {}s)",
+            exporter.sink_name(),
+            config.analytics.export.flush_interval_seconds
+        );
+        spawn_visit_export(
+            exporter,
+            Duration::from_secs(config.analytics.export.flush_interval_seconds),
+        );
+    }
+
+    if config.scanner_guard.enabled {
+        info!(
+            "Starting scanner guard sync (every {}s)",
+            config.scanner_guard.sync_interval_seconds
+        );
+        spawn_scanner_guard_sync(
+            shared.scanner_guard,
+            Duration::from_secs(config.scanner_guard.sync_interval_seconds),
+        );
+    }
+
+    Ok(scheduler)
+}
@@ -0,0 +1,25 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::error;
+use tokio::task::JoinHandle;
+
+use crate::handlers::ScannerGuardServiceType;
+
+/// Keep the scanner guard's block list in sync with the database
+///
+/// Picks up blocks made by other instances or the admin API and forgets expired ones.
+pub fn spawn_scanner_guard_sync(
+    service: Arc<ScannerGuardServiceType>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = service.refresh().await {
+                error!("Failed to refresh blocked IPs: {}", e);
+            }
+        }
+    })
+}
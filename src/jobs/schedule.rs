@@ -0,0 +1,250 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Duration as ChronoDuration, TimeZone, Timelike, Utc};
+
+// Enough minute, hour and day steps to cover several years of a sparse schedule
+const MAX_SEARCH_STEPS: usize = 100_000;
+
+/// When a job runs: a fixed interval (`@every 15m`) or a cron expression in UTC
+///
+/// Cron expressions have the usual five fields (minute, hour, day of month, month, day of
+/// week) with `*`, lists, ranges and steps, plus the `@hourly`, `@daily`, `@weekly` and
+/// `@monthly` shorthands. Like cron, a day matches either restricted day field.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schedule {
+    Every(Duration),
+    Cron(CronSchedule),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronSchedule {
+    source: String,
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl Schedule {
+    /// The first run time strictly after `after`, `None` if the schedule never fires
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        match self {
+            Schedule::Every(interval) => Some(after + ChronoDuration::from_std(*interval).ok()?),
+            Schedule::Cron(cron) => cron.next_after(after),
+        }
+    }
+}
+
+impl CronSchedule {
+    fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + ChronoDuration::minutes(1);
+        let mut t = start;
+
+        for _ in 0..MAX_SEARCH_STEPS {
+            if self.months & (1 << t.month()) == 0 {
+                let (year, month) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+            } else if !self.day_matches(&t) {
+                t = (t + ChronoDuration::days(1)).with_hour(0)?.with_minute(0)?;
+            } else if self.hours & (1 << t.hour()) == 0 {
+                t = (t + ChronoDuration::hours(1)).with_minute(0)?;
+            } else if self.minutes & (1 << t.minute()) == 0 {
+                t += ChronoDuration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, t: &DateTime<Utc>) -> bool {
+        let day = self.days & (1 << t.day()) != 0;
+        let weekday = self.weekdays & (1 << t.weekday().num_days_from_sunday()) != 0;
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let expression = match s {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            _ => s,
+        };
+
+        if let Some(interval) = expression.strip_prefix("@every") {
+            return parse_interval(interval.trim()).map(Schedule::Every);
+        }
+
+        let fields = expression.split_whitespace().collect::<Vec<_>>();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!("'{}' is not a cron expression with 5 fields", s));
+        };
+
+        // Sunday is both 0 and 7
+        let weekdays = parse_field(weekday, 0, 7)?;
+        let weekdays = ((weekdays | (weekdays >> 7)) & 0x7f) as u8;
+
+        Ok(Schedule::Cron(CronSchedule {
+            source: s.to_string(),
+            minutes: parse_field(minute, 0, 59)?,
+            hours: parse_field(hour, 0, 23)? as u32,
+            days: parse_field(day, 1, 31)? as u32,
+            months: parse_field(month, 1, 12)? as u16,
+            weekdays,
+            days_restricted: day != "*",
+            weekdays_restricted: weekday != "*",
+        }))
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Schedule::Every(interval) => write!(f, "@every {}s", interval.as_secs()),
+            Schedule::Cron(cron) => write!(f, "{}", cron.source),
+        }
+    }
+}
+
+// Parse `90s`, `15m`, `6h` or `1d`
+fn parse_interval(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("'{}' is not an interval like 90s, 15m, 6h or 1d", value);
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = value.split_at(split);
+    let amount = amount.parse::<u64>().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "s" => amount,
+        "m" => amount * 60,
+        "h" => amount * 3600,
+        "d" => amount * 86400,
+        _ => return Err(invalid()),
+    };
+    if seconds == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+// Parse one cron field into a bitset of the values it matches
+fn parse_field(field: &str, min: u32, max: u32) -> Result<u64, String> {
+    let invalid = || {
+        format!(
+            "Invalid cron field '{}', values go from {} to {}",
+            field, min, max
+        )
+    };
+    let mut bits = 0u64;
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|s| *s > 0)
+                    .ok_or_else(invalid)?,
+            ),
+            None => (part, 1),
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (
+                    start.parse().map_err(|_| invalid())?,
+                    end.parse().map_err(|_| invalid())?,
+                ),
+                // `5/10` means from 5 on, every 10
+                None => {
+                    let start = range.parse().map_err(|_| invalid())?;
+                    (start, if part.contains('/') { max } else { start })
+                }
+            },
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expression: &str, after: &str) -> Option<DateTime<Utc>> {
+        expression
+            .parse::<Schedule>()
+            .unwrap()
+            .next_after(at(after))
+    }
+
+    #[test]
+    fn test_schedule_next_run() {
+        let every = "@every 15m".parse::<Schedule>().unwrap();
+        assert_eq!(every, Schedule::Every(Duration::from_secs(900)));
+        assert_eq!(every.to_string(), "@every 900s");
+
+        assert_eq!(
+            next("*/15 * * * *", "2025-01-01T10:07:30Z"),
+            Some(at("2025-01-01T10:15:00Z"))
+        );
+        assert_eq!(
+            next("0 3 * * *", "2025-01-01T03:00:00Z"),
+            Some(at("2025-01-02T03:00:00Z"))
+        );
+        assert_eq!(
+            next("@monthly", "2025-01-31T12:00:00Z"),
+            Some(at("2025-02-01T00:00:00Z"))
+        );
+        // 2025-01-04 is a Saturday, 7 means Sunday
+        assert_eq!(
+            next("30 9 * * 1-5", "2025-01-04T00:00:00Z"),
+            Some(at("2025-01-06T09:30:00Z"))
+        );
+        assert_eq!(
+            next("0 0 * * 7", "2025-01-01T00:00:00Z"),
+            Some(at("2025-01-05T00:00:00Z"))
+        );
+        // Either restricted day field matches
+        assert_eq!(
+            next("0 0 15 * 0", "2025-01-06T00:00:00Z"),
+            Some(at("2025-01-12T00:00:00Z"))
+        );
+        assert_eq!(next("0 0 30 2 *", "2025-01-01T00:00:00Z"), None);
+
+        assert!("* * *".parse::<Schedule>().is_err());
+        assert!("60 * * * *".parse::<Schedule>().is_err());
+        assert!("*/0 * * * *".parse::<Schedule>().is_err());
+        assert!("@every 0s".parse::<Schedule>().is_err());
+        assert!("@every 5w".parse::<Schedule>().is_err());
+    }
+}
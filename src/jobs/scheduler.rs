@@ -0,0 +1,378 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use super::Schedule;
+use crate::{
+    errors::AppError,
+    models::{JobRecord, JobStatus},
+    repositories::{JobLease, JobRepositoryTrait},
+    types::Result,
+    utils::maintenance::MaintenanceMode,
+};
+
+// How long to wait before trying again when the job lock can't be checked
+const LOCK_RETRY_SECONDS: i64 = 60;
+// How often a due job checks whether maintenance is over
+const MAINTENANCE_RETRY_SECONDS: i64 = 30;
+
+/// A unit of background work run by the scheduler
+#[async_trait]
+pub trait Job: Send + Sync {
+    /// Unique name, used in `JOB_SCHEDULES` and the admin API
+    fn name(&self) -> &'static str;
+
+    /// Run once, returning a summary of what was done
+    async fn run(&self) -> Result<String>;
+}
+
+/// Job lock counts since startup, as reported by the health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobLockStats {
+    pub acquired: u64,
+    /// Due or triggered runs skipped because another instance held the lock
+    pub contended: u64,
+    /// Runs whose lock was taken over by another instance, so their outcome was ignored
+    pub fenced: u64,
+    pub release_failures: u64,
+}
+
+#[derive(Default)]
+struct LockCounters {
+    acquired: AtomicU64,
+    contended: AtomicU64,
+    fenced: AtomicU64,
+    release_failures: AtomicU64,
+}
+
+struct ScheduledJob {
+    job: Arc<dyn Job>,
+    schedule: Schedule,
+    /// Interval jobs usually run right at startup, unless that would be wasteful
+    run_at_startup: bool,
+    running: AtomicBool,
+    next_run: Mutex<Option<DateTime<Utc>>>,
+}
+
+/// Runs jobs on their schedule and records every run in the `jobs` table
+///
+/// The next run is persisted, so restarts neither skip nor repeat runs. Every run holds the
+/// job lock, so across all instances sharing the database a job never runs twice at the same
+/// time, and a due run happens on a single instance; triggering a running job is refused.
+/// Due runs wait while the instance is in maintenance, triggered ones don't.
+pub struct JobScheduler<R: JobRepositoryTrait> {
+    repository: Arc<R>,
+    jobs: Vec<Arc<ScheduledJob>>,
+    counters: LockCounters,
+    maintenance: Option<Arc<MaintenanceMode>>,
+}
+
+impl<R: JobRepositoryTrait + Send + Sync + 'static> JobScheduler<R> {
+    pub fn new(repository: Arc<R>) -> Self {
+        Self {
+            repository,
+            jobs: Vec::new(),
+            counters: LockCounters::default(),
+            maintenance: None,
+        }
+    }
+
+    /// Hold due runs back while the instance is in maintenance
+    pub fn pause_during(&mut self, maintenance: Arc<MaintenanceMode>) {
+        self.maintenance = Some(maintenance);
+    }
+
+    /// Add a job, to be started with [`JobScheduler::start`]
+    pub fn add(&mut self, job: impl Job + 'static, schedule: Schedule, run_at_startup: bool) {
+        self.jobs.push(Arc::new(ScheduledJob {
+            job: Arc::new(job),
+            schedule,
+            run_at_startup,
+            running: AtomicBool::new(false),
+            next_run: Mutex::new(None),
+        }));
+    }
+
+    /// Whether a job of that name was added
+    pub fn has_job(&self, name: &str) -> bool {
+        self.jobs.iter().any(|job| job.job.name() == name)
+    }
+
+    /// Record the jobs and run each on its schedule in the background
+    pub async fn start(self: &Arc<Self>) -> Result<Vec<JoinHandle<()>>> {
+        let mut handles = Vec::with_capacity(self.jobs.len());
+
+        for job in &self.jobs {
+            let now = Utc::now();
+            let due = match job.schedule {
+                Schedule::Every(_) if job.run_at_startup => Some(now),
+                _ => job.schedule.next_after(now),
+            };
+            let record = self
+                .repository
+                .register(job.job.name(), &job.schedule.to_string(), due)
+                .await?;
+            let next_run = record.next_run_at.or(due);
+            *job.next_run.lock().unwrap() = next_run;
+
+            match next_run {
+                Some(at) => info!(
+                    "Scheduled job '{}' ({}), next run at {}",
+                    record.name, record.schedule, at
+                ),
+                None => warn!(
+                    "Job '{}' ({}) has no upcoming run",
+                    record.name, record.schedule
+                ),
+            }
+
+            let scheduler = self.clone();
+            let job = job.clone();
+            handles.push(tokio::spawn(async move {
+                loop {
+                    let Some(next_run) = *job.next_run.lock().unwrap() else {
+                        warn!("Job '{}' has no upcoming run, stopping it", job.job.name());
+                        return;
+                    };
+                    let now = Utc::now();
+                    if next_run > now {
+                        // Sleep at most a minute so runs triggered meanwhile move this one
+                        let wait = (next_run - now).to_std().unwrap_or_default();
+                        tokio::time::sleep(wait.min(std::time::Duration::from_secs(60))).await;
+                        continue;
+                    }
+                    scheduler.run_due(&job).await;
+                }
+            }));
+        }
+
+        Ok(handles)
+    }
+
+    /// Run a job now, in the background
+    ///
+    /// ### Errors
+    /// - `AppError::NotFound` if no job has that name
+    /// - `AppError::Conflict` if the job is running, here or on another instance
+    pub async fn trigger(self: &Arc<Self>, name: &str) -> Result<()> {
+        let job = self
+            .jobs
+            .iter()
+            .find(|job| job.job.name() == name)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Job '{}' does not exist", name)))?;
+        if job.running.load(Ordering::SeqCst) {
+            return Err(AppError::Conflict(format!(
+                "Job '{}' is already running",
+                name
+            )));
+        }
+
+        let Some(lease) = self.repository.acquire(name).await? else {
+            self.counters.contended.fetch_add(1, Ordering::Relaxed);
+            return Err(AppError::Conflict(format!(
+                "Job '{}' is running on another instance",
+                name
+            )));
+        };
+        self.counters.acquired.fetch_add(1, Ordering::Relaxed);
+
+        let scheduler = self.clone();
+        tokio::spawn(async move { scheduler.run(&job, lease).await });
+        Ok(())
+    }
+
+    pub fn lock_stats(&self) -> JobLockStats {
+        JobLockStats {
+            acquired: self.counters.acquired.load(Ordering::Relaxed),
+            contended: self.counters.contended.load(Ordering::Relaxed),
+            fenced: self.counters.fenced.load(Ordering::Relaxed),
+            release_failures: self.counters.release_failures.load(Ordering::Relaxed),
+        }
+    }
+
+    /// The scheduled jobs with the outcome of their last run
+    pub async fn list(&self) -> Result<Vec<JobRecord>> {
+        let records = self.repository.find_all().await?;
+        Ok(records
+            .into_iter()
+            .filter(|record| self.has_job(&record.name))
+            .collect())
+    }
+
+    // Run a job whose time has come, unless another instance has it or already ran it
+    async fn run_due(&self, job: &ScheduledJob) {
+        let name = job.job.name();
+        let now = Utc::now();
+        if self
+            .maintenance
+            .as_ref()
+            .is_some_and(|mode| mode.is_enabled())
+        {
+            debug!("Job '{}' is due, waiting for maintenance to end", name);
+            let retry_at = now + Duration::seconds(MAINTENANCE_RETRY_SECONDS);
+            *job.next_run.lock().unwrap() = Some(retry_at);
+            return;
+        }
+        // A triggered run is in progress and will set the next run when done
+        if job.running.load(Ordering::SeqCst) {
+            *job.next_run.lock().unwrap() = job.schedule.next_after(now);
+            return;
+        }
+
+        match self.repository.acquire(name).await {
+            Ok(Some(lease)) => {
+                self.counters.acquired.fetch_add(1, Ordering::Relaxed);
+                match lease.next_run_at.filter(|at| *at > now) {
+                    Some(at) => {
+                        debug!("Job '{}' already ran elsewhere, next run at {}", name, at);
+                        *job.next_run.lock().unwrap() = Some(at);
+                        self.release(lease).await;
+                    }
+                    None => self.run(job, lease).await,
+                }
+            }
+            Ok(None) => {
+                self.counters.contended.fetch_add(1, Ordering::Relaxed);
+                debug!("Job '{}' is running on another instance", name);
+                // That instance records the next run, which is checked again once it is due
+                *job.next_run.lock().unwrap() = job.schedule.next_after(now);
+            }
+            Err(e) => {
+                warn!("Failed to take the lock of job '{}': {}", name, e);
+                *job.next_run.lock().unwrap() = Some(now + Duration::seconds(LOCK_RETRY_SECONDS));
+            }
+        }
+    }
+
+    async fn run(&self, job: &ScheduledJob, lease: JobLease) {
+        if job.running.swap(true, Ordering::SeqCst) {
+            self.release(lease).await;
+            return;
+        }
+        let name = job.job.name();
+        match self.repository.mark_started(&lease).await {
+            Ok(true) => {}
+            Ok(false) => {
+                self.counters.fenced.fetch_add(1, Ordering::Relaxed);
+                warn!("Job '{}' lost its lock before starting", name);
+                self.release(lease).await;
+                job.running.store(false, Ordering::SeqCst);
+                return;
+            }
+            Err(e) => warn!("Failed to record the start of job '{}': {}", name, e),
+        }
+
+        let result = job.job.run().await;
+        let next_run = job.schedule.next_after(Utc::now());
+        *job.next_run.lock().unwrap() = next_run;
+
+        let (status, message) = match result {
+            Ok(summary) => {
+                info!("Job '{}': {}", name, summary);
+                (JobStatus::Succeeded, summary)
+            }
+            Err(e) => {
+                error!("Job '{}' failed: {}", name, e);
+                (JobStatus::Failed, e.to_string())
+            }
+        };
+        match self
+            .repository
+            .mark_finished(&lease, status, &message, next_run)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                self.counters.fenced.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "Ignored the outcome of job '{}', another instance took its lock meanwhile",
+                    name
+                );
+            }
+            Err(e) => warn!("Failed to record the outcome of job '{}': {}", name, e),
+        }
+
+        self.release(lease).await;
+        job.running.store(false, Ordering::SeqCst);
+    }
+
+    async fn release(&self, lease: JobLease) {
+        let name = lease.name.clone();
+        if let Err(e) = self.repository.release(lease).await {
+            self.counters
+                .release_failures
+                .fetch_add(1, Ordering::Relaxed);
+            warn!("Failed to release the lock of job '{}': {}", name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::db::Database;
+    use crate::repositories::JobRepository;
+
+    // Another instance takes the lock over while the job runs
+    struct TakenOver {
+        pool: PgPool,
+    }
+
+    #[async_trait]
+    impl Job for TakenOver {
+        fn name(&self) -> &'static str {
+            "taken_over"
+        }
+
+        async fn run(&self) -> Result<String> {
+            sqlx::query("UPDATE jobs SET fence = fence + 1 WHERE name = 'taken_over'")
+                .execute(&self.pool)
+                .await
+                .unwrap();
+            Ok("done".to_string())
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_trigger_respects_the_job_lock(pool: PgPool) {
+        let repository = Arc::new(JobRepository::new(Database::from_pool(pool.clone())));
+        let mut scheduler = JobScheduler::new(repository.clone());
+        scheduler.add(TakenOver { pool }, "@every 1h".parse().unwrap(), false);
+        let scheduler = Arc::new(scheduler);
+        repository
+            .register("taken_over", "@every 1h", None)
+            .await
+            .unwrap();
+
+        // Refused while another instance holds the lock
+        let held = repository.acquire("taken_over").await.unwrap().unwrap();
+        let refused = scheduler.trigger("taken_over").await;
+        assert!(
+            matches!(refused, Err(AppError::Conflict(_))),
+            "{:?}",
+            refused
+        );
+        assert_eq!(scheduler.lock_stats().contended, 1);
+        repository.release(held).await.unwrap();
+
+        // The outcome of a run whose lock was taken over is ignored
+        scheduler.trigger("taken_over").await.unwrap();
+        for _ in 0..100 {
+            if scheduler.lock_stats().fenced > 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+        }
+        assert_eq!(scheduler.lock_stats().fenced, 1);
+        let record = scheduler.list().await.unwrap().remove(0);
+        assert_eq!(record.last_message, None);
+    }
+}
@@ -0,0 +1,22 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::error;
+use tokio::task::JoinHandle;
+
+use crate::handlers::TenantServiceType;
+
+/// Keep the in-memory tenants in sync with the database
+///
+/// Picks up tenants added through other instances.
+pub fn spawn_tenant_sync(service: Arc<TenantServiceType>, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = service.refresh().await {
+                error!("Failed to refresh tenants: {}", e);
+            }
+        }
+    })
+}
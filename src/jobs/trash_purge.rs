@@ -0,0 +1,104 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use log::debug;
+
+use super::Job;
+use crate::{repositories::ShortenedUrlRepositoryTrait, types::Result};
+
+/// Deletes links for good, visits included, once they've been in the trash longer than the
+/// retention period
+pub struct TrashPurgeJob<R: ShortenedUrlRepositoryTrait> {
+    repository: Arc<R>,
+    retention_days: u32,
+    batch_size: i64,
+}
+
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync + 'static> TrashPurgeJob<R> {
+    pub fn new(repository: Arc<R>, retention_days: u32, batch_size: i64) -> Self {
+        Self {
+            repository,
+            retention_days,
+            batch_size,
+        }
+    }
+
+    /// Purge every link trashed before the retention window, returning how many were purged
+    pub async fn run_once(&self) -> Result<u64> {
+        let cutoff = Utc::now() - Duration::days(self.retention_days as i64);
+        let mut purged = 0;
+
+        // Batches keep each delete, and the visits cascading from it, short
+        loop {
+            let deleted = self.repository.purge_trash(cutoff, self.batch_size).await?;
+            purged += deleted;
+            debug!("Purged {} links trashed before {}", deleted, cutoff);
+
+            if (deleted as i64) < self.batch_size {
+                break;
+            }
+        }
+
+        Ok(purged)
+    }
+}
+
+#[async_trait]
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync + 'static> Job for TrashPurgeJob<R> {
+    fn name(&self) -> &'static str {
+        "trash_purge"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let purged = self.run_once().await?;
+        Ok(format!("Purged {} links from the trash", purged))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::db::{testing::insert_link, Database};
+    use crate::repositories::ShortenedUrlRepository;
+
+    async fn trash(pool: &PgPool, short_code: &str, days_ago: i64) {
+        let id = insert_link(pool, short_code).await;
+        sqlx::query("UPDATE shortened_urls SET deleted_at = $1 WHERE id = $2")
+            .bind(Utc::now() - Duration::days(days_ago))
+            .bind(id)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test]
+    async fn test_only_links_past_retention_are_purged(pool: PgPool) {
+        let repository = Arc::new(ShortenedUrlRepository::new(Database::from_pool(
+            pool.clone(),
+        )));
+        trash(&pool, "old1", 45).await;
+        trash(&pool, "old2", 31).await;
+        trash(&pool, "recent", 29).await;
+        insert_link(&pool, "live").await;
+
+        let cutoff = Utc::now() - Duration::days(30);
+        let stats = repository.count_trash(cutoff).await.unwrap();
+        assert_eq!((stats.trashed, stats.due_for_purge), (3, 2));
+
+        // Batches smaller than the backlog still purge all of it
+        let job = TrashPurgeJob::new(repository.clone(), 30, 1);
+        assert_eq!(job.run_once().await.unwrap(), 2);
+
+        let mut left: Vec<String> = sqlx::query_scalar("SELECT short_code FROM shortened_urls")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        left.sort();
+        assert_eq!(left, ["live", "recent"]);
+        let stats = repository.count_trash(cutoff).await.unwrap();
+        assert_eq!((stats.trashed, stats.due_for_purge), (1, 0));
+    }
+}
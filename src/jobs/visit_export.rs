@@ -0,0 +1,29 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, warn};
+use tokio::task::JoinHandle;
+
+use crate::warehouse::VisitExporter;
+
+/// Send pending visits to the analytics warehouse every interval, or as soon as a batch fills up
+pub fn spawn_visit_export(exporter: Arc<VisitExporter>, interval: Duration) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = exporter.batch_ready() => {}
+            }
+            match exporter.flush().await {
+                Ok(0) => {}
+                Ok(count) => debug!("Exported {} visits to {}", count, exporter.sink_name()),
+                Err(e) => warn!(
+                    "Failed to export visits to {}, retrying with the next flush: {}",
+                    exporter.sink_name(),
+                    e
+                ),
+            }
+        }
+    })
+}
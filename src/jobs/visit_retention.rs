@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use log::debug;
+
+use super::{archive_sink::ArchiveSink, Job};
+use crate::{repositories::AnalyticsRepositoryTrait, types::Result};
+
+/// Moves raw visit events past the retention window into daily aggregates
+pub struct VisitRetentionJob<R: AnalyticsRepositoryTrait> {
+    repository: Arc<R>,
+    sink: Arc<dyn ArchiveSink>,
+    retention_days: u32,
+    batch_size: i64,
+}
+
+impl<R: AnalyticsRepositoryTrait + Send + Sync + 'static> VisitRetentionJob<R> {
+    pub fn new(
+        repository: Arc<R>,
+        sink: Arc<dyn ArchiveSink>,
+        retention_days: u32,
+        batch_size: i64,
+    ) -> Self {
+        Self {
+            repository,
+            sink,
+            retention_days,
+            batch_size,
+        }
+    }
+
+    /// Archive and roll up every event older than the retention window
+    ///
+    /// Returns the number of raw events removed.
+    pub async fn run_once(&self) -> Result<u64> {
+        let cutoff = Utc::now() - Duration::days(self.retention_days as i64);
+        let mut removed = 0;
+
+        loop {
+            let batch = self
+                .repository
+                .find_visits_before(cutoff, self.batch_size)
+                .await?;
+
+            if batch.is_empty() {
+                break;
+            }
+
+            // Hand the rows to the sink first so nothing is deleted unarchived
+            self.sink.archive(&batch).await?;
+
+            let ids = batch.iter().map(|v| v.id).collect::<Vec<_>>();
+            removed += self.repository.rollup_and_delete_visits(&ids).await?;
+            debug!("Rolled up {} visit events older than {}", ids.len(), cutoff);
+
+            if (batch.len() as i64) < self.batch_size {
+                break;
+            }
+        }
+
+        Ok(removed)
+    }
+}
+
+#[async_trait]
+impl<R: AnalyticsRepositoryTrait + Send + Sync + 'static> Job for VisitRetentionJob<R> {
+    fn name(&self) -> &'static str {
+        "visit_retention"
+    }
+
+    async fn run(&self) -> Result<String> {
+        let removed = self.run_once().await?;
+        Ok(format!(
+            "Archived {} events via '{}' sink",
+            removed,
+            self.sink.name()
+        ))
+    }
+}
@@ -0,0 +1,31 @@
+// src/lib.rs - Library root, shared by the server binary, benchmarks and tools
+
+pub mod app;
+pub mod cache;
+pub mod config;
+pub mod dashboard;
+pub mod db;
+pub mod errors;
+pub mod events;
+pub mod extractors;
+pub mod handlers;
+pub mod i18n;
+pub mod jobs;
+pub mod live_config;
+pub mod middleware;
+pub mod models;
+pub mod notifications;
+pub mod pages;
+pub mod repositories;
+pub mod routes;
+pub mod seed;
+pub mod services;
+pub mod storage;
+pub mod system_info;
+pub mod telemetry;
+pub mod types;
+pub mod utils;
+pub mod validations;
+pub mod warehouse;
+
+pub use errors::AppError;
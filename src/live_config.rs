@@ -0,0 +1,296 @@
+// src/live_config.rs - Settings reloaded from the config file without a restart
+use std::{
+    collections::{BTreeSet, HashMap},
+    env, fs,
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use log::{debug, info, warn};
+use serde::Serialize;
+
+use crate::{
+    config::{Config, ScannerGuardConfig},
+    errors::AppError,
+    handlers::ScannerGuardServiceType,
+    types::Result,
+    utils::{logger::ReloadableLogger, rate_limiter::RateLimiter},
+};
+
+/// Variables applied when the config file changes; any other needs a restart
+pub const RELOADABLE_KEYS: &[&str] = &[
+    "LOG_LEVEL",
+    "RUST_LOG",
+    "CORS_ALLOWED_ORIGINS",
+    "ANONYMOUS_CREATE_RATE_LIMIT",
+    "ANONYMOUS_CREATE_WINDOW_SECONDS",
+    "SCANNER_WINDOW_SECONDS",
+    "SCANNER_TARPIT_THRESHOLD",
+    "SCANNER_TARPIT_BASE_DELAY_MS",
+    "SCANNER_TARPIT_MAX_DELAY_MS",
+    "SCANNER_BLOCK_THRESHOLD",
+    "SCANNER_BLOCK_SECONDS",
+    "SCANNER_HONEYPOT_PATHS",
+];
+
+/// What the last reload of the config file changed, by variable name
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadReport {
+    pub reloaded_at: DateTime<Utc>,
+    pub applied: Vec<String>,
+    /// Changed in the file but set in the process environment, which takes precedence
+    pub overridden: Vec<String>,
+    /// Changed in the file, taking effect on the next restart
+    pub restart_required: Vec<String>,
+}
+
+/// The configuration in effect, with the settings that are safe to change while running
+/// reloaded from the config file
+///
+/// Like at startup, variables set in the process environment win over the file. Structural
+/// settings, like the database URL, keep their startup value and are reported as needing a
+/// restart when they change.
+pub struct LiveConfig {
+    path: PathBuf,
+    current: RwLock<Config>,
+    /// Values in the file as of the last reload, which also keeps reloads one at a time
+    file_values: Mutex<HashMap<String, String>>,
+    modified: Mutex<Option<SystemTime>>,
+    last_reload: RwLock<Option<ReloadReport>>,
+    logger: Option<&'static ReloadableLogger>,
+    anonymous_create_limiter: Arc<RateLimiter>,
+    scanner_guard: Arc<ScannerGuardServiceType>,
+}
+
+impl LiveConfig {
+    pub fn new(
+        config: Config,
+        logger: Option<&'static ReloadableLogger>,
+        anonymous_create_limiter: Arc<RateLimiter>,
+        scanner_guard: Arc<ScannerGuardServiceType>,
+    ) -> Self {
+        let path = PathBuf::from(&config.config_reload.file);
+        let file_values = read_file(&path).unwrap_or_default();
+        Self {
+            modified: Mutex::new(modified_at(&path)),
+            path,
+            current: RwLock::new(config),
+            file_values: Mutex::new(file_values),
+            last_reload: RwLock::new(None),
+            logger,
+            anonymous_create_limiter,
+            scanner_guard,
+        }
+    }
+
+    /// The configuration in effect
+    pub fn current(&self) -> Config {
+        self.current
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    pub fn last_reload(&self) -> Option<ReloadReport> {
+        self.last_reload
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Whether browsers on this origin may call the API
+    pub fn allows_origin(&self, origin: &str) -> bool {
+        let current = self.current.read().unwrap_or_else(|e| e.into_inner());
+        current.cors.allowed_origins.iter().any(|o| o == origin)
+    }
+
+    /// Reload when the config file was modified since it was last read
+    pub fn reload_if_changed(&self) -> Result<Option<ReloadReport>> {
+        let modified = modified_at(&self.path);
+        {
+            let mut last = self.modified.lock().unwrap_or_else(|e| e.into_inner());
+            if modified.is_none() || modified == *last {
+                return Ok(None);
+            }
+            *last = modified;
+        }
+        self.reload().map(Some)
+    }
+
+    /// Read the config file and apply the reloadable settings that changed
+    ///
+    /// ### Errors
+    /// - `AppError::Config` if the file can't be read or the new settings are invalid, in
+    ///   which case nothing is applied
+    pub fn reload(&self) -> Result<ReloadReport> {
+        let values = read_file(&self.path)?;
+        let mut file_values = self.file_values.lock().unwrap_or_else(|e| e.into_inner());
+
+        let changed = file_values
+            .keys()
+            .chain(values.keys())
+            .filter(|key| file_values.get(*key) != values.get(*key))
+            .cloned()
+            .collect::<BTreeSet<_>>();
+        let (reloadable, restart_required): (Vec<_>, Vec<_>) = changed
+            .into_iter()
+            .partition(|key| RELOADABLE_KEYS.contains(&key.as_str()));
+
+        // Only what the file set before may be replaced, the environment stays as it was
+        let mut applied = Vec::new();
+        let mut overridden = Vec::new();
+        let mut previous = Vec::new();
+        for key in reloadable {
+            let before = env::var(&key).ok();
+            if before != file_values.get(&key).cloned() {
+                overridden.push(key);
+                continue;
+            }
+            match values.get(&key) {
+                Some(value) => env::set_var(&key, value),
+                None => env::remove_var(&key),
+            }
+            previous.push((key.clone(), before));
+            applied.push(key);
+        }
+
+        let loaded = match Config::load() {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                for (key, before) in previous {
+                    match before {
+                        Some(value) => env::set_var(&key, value),
+                        None => env::remove_var(&key),
+                    }
+                }
+                return Err(AppError::Config(format!(
+                    "Not reloading {}: {}",
+                    self.path.display(),
+                    e
+                )));
+            }
+        };
+
+        for key in applied.iter().chain(&overridden) {
+            match values.get(key) {
+                Some(value) => file_values.insert(key.clone(), value.clone()),
+                None => file_values.remove(key),
+            };
+        }
+        self.apply(loaded);
+
+        let report = ReloadReport {
+            reloaded_at: Utc::now(),
+            applied,
+            overridden,
+            restart_required,
+        };
+        if !report.applied.is_empty() {
+            info!("Reloaded {}", report.applied.join(", "));
+        }
+        if !report.overridden.is_empty() {
+            debug!(
+                "Kept {} from the environment over the config file",
+                report.overridden.join(", ")
+            );
+        }
+        if !report.restart_required.is_empty() {
+            warn!(
+                "{} changed in {}, restart to apply",
+                report.restart_required.join(", "),
+                self.path.display()
+            );
+        }
+        *self.last_reload.write().unwrap_or_else(|e| e.into_inner()) = Some(report.clone());
+        Ok(report)
+    }
+
+    // Take the reloadable settings of a freshly loaded configuration, and only those
+    fn apply(&self, loaded: Config) {
+        let mut current = self.current.write().unwrap_or_else(|e| e.into_inner());
+        current.app.log_level = loaded.app.log_level;
+        current.cors = loaded.cors;
+        current.anonymous_create = loaded.anonymous_create;
+        current.scanner_guard = ScannerGuardConfig {
+            enabled: current.scanner_guard.enabled,
+            sync_interval_seconds: current.scanner_guard.sync_interval_seconds,
+            ..loaded.scanner_guard
+        };
+
+        if let Some(logger) = self.logger {
+            logger.reload(&current);
+        }
+        self.anonymous_create_limiter.set_limit(
+            current.anonymous_create.rate_limit,
+            Duration::from_secs(current.anonymous_create.window_seconds),
+        );
+        self.scanner_guard
+            .update_config(current.scanner_guard.clone());
+    }
+}
+
+fn read_file(path: &PathBuf) -> Result<HashMap<String, String>> {
+    let unreadable =
+        |e: dotenvy::Error| AppError::Config(format!("Can't read {}: {}", path.display(), e));
+    dotenvy::from_path_iter(path)
+        .map_err(unreadable)?
+        .map(|item| item.map_err(unreadable))
+        .collect()
+}
+
+fn modified_at(path: &PathBuf) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::{db::Database, repositories::BlockedIpRepository, services::ScannerGuardService};
+
+    #[tokio::test]
+    async fn test_reload_applies_only_reloadable_settings() {
+        let path = env::temp_dir().join(format!("live-config-{}.env", std::process::id()));
+        fs::write(&path, "ANONYMOUS_CREATE_RATE_LIMIT=5\nAPP_NAME=shortener\n").unwrap();
+
+        // As at startup, the file fills in the environment before the config is loaded
+        dotenvy::from_path(&path).unwrap();
+        let mut config = Config::load().unwrap();
+        config.config_reload.file = path.display().to_string();
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let scanner_guard = ScannerGuardService::new(
+            Arc::new(BlockedIpRepository::new(Database::from_pool(pool))),
+            config.scanner_guard.clone(),
+        );
+        let live = LiveConfig::new(
+            config,
+            None,
+            Arc::new(RateLimiter::new(5, Duration::from_secs(60))),
+            Arc::new(scanner_guard),
+        );
+
+        fs::write(&path, "ANONYMOUS_CREATE_RATE_LIMIT=7\nAPP_NAME=renamed\n").unwrap();
+        let report = live.reload().unwrap();
+        assert_eq!(report.applied, ["ANONYMOUS_CREATE_RATE_LIMIT"]);
+        assert_eq!(report.restart_required, ["APP_NAME"]);
+        assert_eq!(live.current().anonymous_create.rate_limit, 7);
+        assert_eq!(live.current().app.name, "shortener");
+
+        // An invalid value leaves everything as it was
+        fs::write(
+            &path,
+            "ANONYMOUS_CREATE_RATE_LIMIT=many\nAPP_NAME=renamed\n",
+        )
+        .unwrap();
+        assert!(live.reload().is_err());
+        assert_eq!(live.current().anonymous_create.rate_limit, 7);
+        assert_eq!(env::var("ANONYMOUS_CREATE_RATE_LIMIT").unwrap(), "7");
+
+        fs::remove_file(&path).unwrap();
+        env::remove_var("ANONYMOUS_CREATE_RATE_LIMIT");
+        env::remove_var("APP_NAME");
+    }
+}
@@ -1,28 +1,23 @@
-use std::process;
+use std::{env, process};
 
 use log::error;
 
-mod app;
-mod config;
-mod db;
-mod errors;
-mod handlers;
-mod middleware;
-mod models;
-mod repositories;
-mod routes;
-mod services;
-mod telemetry;
-mod types;
-mod utils;
-mod validations;
-
-use errors::AppError;
+use url_shortener::{app, AppError};
 
 #[actix_web::main]
 async fn main() {
-    // Run the server with error handling for critical failures
-    if let Err(err) = app::server().await {
+    // `migrate` or `--migrate-only` applies pending migrations and exits instead of serving
+    let migrate_only = env::args()
+        .skip(1)
+        .any(|arg| arg == "migrate" || arg == "--migrate-only");
+    let result = if migrate_only {
+        app::migrate().await
+    } else {
+        app::server().await
+    };
+
+    // Exit with a code telling critical failures apart
+    if let Err(err) = result {
         match err {
             AppError::Server(e) => {
                 error!("Critical server error: {}", e);
@@ -0,0 +1,200 @@
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::http::Uri;
+use actix_web::{Error, HttpMessage};
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+use crate::{
+    config::ApiVersionConfig,
+    errors::AppError,
+    extractors::{
+        ApiVersion, CURRENT_API_VERSION, DEPRECATED_API_VERSIONS, SUPPORTED_API_VERSIONS,
+    },
+};
+
+const API_PATH_PREFIX: &str = "/api/";
+
+/// The URI a versioned API request was sent to, before it was routed as unversioned
+///
+/// Found in the request extensions, for anything that must see the request as the client
+/// sent it, like signature checks.
+#[derive(Debug, Clone)]
+pub struct RequestedUri(pub Uri);
+
+/// Serves the API under `/api/v{N}/` and announces deprecated versions
+///
+/// Versioned paths are routed to the same handlers as their unversioned form, with the
+/// version available to handlers through the [`ApiVersion`] extractor. Unsupported versions
+/// get a 404. Unversioned paths and deprecated versions keep working, but their responses
+/// carry `Deprecation`, `Sunset` (once a date is set) and a `Link` to the current version.
+pub struct ApiVersioning {
+    config: ApiVersionConfig,
+}
+
+impl ApiVersioning {
+    pub fn new(config: ApiVersionConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ApiVersioning
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ApiVersioningMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ApiVersioningMiddleware {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        })
+    }
+}
+
+pub struct ApiVersioningMiddleware<S> {
+    service: Rc<S>,
+    config: ApiVersionConfig,
+}
+
+impl<S, B> Service<ServiceRequest> for ApiVersioningMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        if !req.path().starts_with(API_PATH_PREFIX) {
+            return Box::pin(service.call(req));
+        }
+
+        let (version, path, deprecation) = match split_version(req.path()) {
+            Some((version, _)) if !SUPPORTED_API_VERSIONS.contains(&version) => {
+                return Box::pin(async move {
+                    Err(
+                        AppError::NotFound(format!("API version v{} is not supported", version))
+                            .into(),
+                    )
+                });
+            }
+            Some((version, path)) => {
+                let deprecation = DEPRECATED_API_VERSIONS
+                    .iter()
+                    .find(|(deprecated, _)| *deprecated == version)
+                    .map(|(_, sunset)| sunset.map(String::from));
+                (
+                    version,
+                    path.clone(),
+                    deprecation.map(|sunset| (sunset, path)),
+                )
+            }
+            // Unversioned paths are the first version, kept for existing clients
+            None => {
+                let path = req.path().to_string();
+                let sunset = self.config.unversioned_sunset.clone();
+                (1, path.clone(), Some((sunset, path)))
+            }
+        };
+
+        if path != req.path() {
+            let requested = RequestedUri(req.uri().clone());
+            req.extensions_mut().insert(requested);
+            if let Err(e) = rewrite_path(&mut req, &path) {
+                return Box::pin(async move { Err(e.into()) });
+            }
+        }
+        req.extensions_mut().insert(ApiVersion(version));
+
+        Box::pin(async move {
+            let mut res = service.call(req).await?;
+
+            if let Some((sunset, path)) = deprecation {
+                let headers = res.headers_mut();
+                headers.insert(
+                    HeaderName::from_static("deprecation"),
+                    HeaderValue::from_static("true"),
+                );
+                if let Some(sunset) = sunset.and_then(|s| HeaderValue::from_str(&s).ok()) {
+                    headers.insert(HeaderName::from_static("sunset"), sunset);
+                }
+                let successor = format!(
+                    "</api/v{}/{}>; rel=\"successor-version\"",
+                    CURRENT_API_VERSION,
+                    path.trim_start_matches(API_PATH_PREFIX)
+                );
+                if let Ok(link) = HeaderValue::from_str(&successor) {
+                    headers.append(HeaderName::from_static("link"), link);
+                }
+            }
+            Ok(res)
+        })
+    }
+}
+
+// Split `/api/v2/urls` into the version and the unversioned path `/api/urls`
+fn split_version(path: &str) -> Option<(u16, String)> {
+    let rest = path.strip_prefix(API_PATH_PREFIX)?.strip_prefix('v')?;
+    let (version, tail) = rest.split_once('/').unwrap_or((rest, ""));
+    let version = version.parse().ok()?;
+    Some((
+        version,
+        format!("{}{}", API_PATH_PREFIX, tail)
+            .trim_end_matches('/')
+            .to_string(),
+    ))
+}
+
+// Route the request as if it was sent to the unversioned path, keeping its query
+fn rewrite_path(req: &mut ServiceRequest, path: &str) -> Result<(), AppError> {
+    let path_and_query = match req.query_string() {
+        "" => path.to_string(),
+        query => format!("{}?{}", path, query),
+    };
+    let mut parts = req.head().uri.clone().into_parts();
+    parts.path_and_query = Some(
+        path_and_query
+            .parse()
+            .map_err(|_| AppError::Validation("Invalid request path".to_string()))?,
+    );
+    let uri = Uri::from_parts(parts)
+        .map_err(|_| AppError::Validation("Invalid request path".to_string()))?;
+
+    req.match_info_mut().get_mut().update(&uri);
+    req.head_mut().uri = uri;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_version() {
+        assert_eq!(
+            split_version("/api/v1/urls/search"),
+            Some((1, "/api/urls/search".into()))
+        );
+        assert_eq!(split_version("/api/v2/"), Some((2, "/api".into())));
+        assert_eq!(split_version("/api/v1"), Some((1, "/api".into())));
+        assert_eq!(split_version("/api/urls"), None);
+        assert_eq!(split_version("/api/vx/urls"), None);
+        assert_eq!(split_version("/v1/urls"), None);
+    }
+}
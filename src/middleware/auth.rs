@@ -0,0 +1,292 @@
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::Method;
+use actix_web::{web, Error, HttpMessage, HttpRequest};
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+use sha2::{Digest, Sha256};
+use std::rc::Rc;
+
+use log::warn;
+
+use crate::{
+    config::Config,
+    errors::AppError,
+    handlers::ServiceTokenServiceType,
+    models::ServiceTokenIdentity,
+    services::ServiceTokenServiceTrait,
+    utils::{id_generator::SERVICE_TOKEN_PREFIX, network::client_ip},
+};
+
+/// Guards the admin API with the static `ADMIN_API_TOKEN`
+///
+/// The token is accepted as `Authorization: Bearer <token>` or `X-Admin-Token: <token>`.
+/// When no token is configured the admin API is disabled entirely. Service tokens with an
+/// admin scope are let through as well, [`ServiceTokenAuth`] having checked them already.
+pub struct AdminAuth;
+
+impl<S, B> Transform<S, ServiceRequest> for AdminAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AdminAuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(AdminAuthMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct AdminAuthMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for AdminAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        if req.extensions().contains::<ServiceTokenIdentity>() {
+            return Box::pin(self.service.call(req));
+        }
+
+        let expected = req
+            .app_data::<web::Data<Config>>()
+            .and_then(|config| config.auth.admin_token.clone());
+
+        let Some(expected) = expected else {
+            return Box::pin(async {
+                Err(AppError::Forbidden("Admin API is disabled".to_string()).into())
+            });
+        };
+
+        let provided = bearer_token(&req).or_else(|| {
+            req.headers()
+                .get("X-Admin-Token")
+                .and_then(|v| v.to_str().ok())
+                .map(String::from)
+        });
+
+        match provided {
+            Some(token) if tokens_match(&token, &expected) => Box::pin(self.service.call(req)),
+            _ => {
+                warn!(
+                    "Rejected admin request {} {} from {}",
+                    req.method(),
+                    req.path(),
+                    client_ip(req.request()).map_or("unknown".to_string(), |ip| ip.to_string())
+                );
+                Box::pin(async {
+                    Err(AppError::Unauthorized("Missing or invalid admin token".to_string()).into())
+                })
+            }
+        }
+    }
+}
+
+/// Limits service tokens to the endpoints their scopes cover
+///
+/// Requests presenting a service token as `Authorization: Bearer ust_...` get a 401 when
+/// the token is unknown, expired or revoked, and a 403 when it lacks the endpoint's scope.
+/// Accepted tokens are attached to the request, where [`AdminAuth`] and the user
+/// extractors find them. Requests without a service token pass through untouched.
+pub struct ServiceTokenAuth;
+
+impl<S, B> Transform<S, ServiceRequest> for ServiceTokenAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ServiceTokenAuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ServiceTokenAuthMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct ServiceTokenAuthMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ServiceTokenAuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let secret = bearer_token(&req).filter(|token| token.starts_with(SERVICE_TOKEN_PREFIX));
+        let scope = match (secret.is_some(), token_access(req.method(), req.path())) {
+            (true, TokenAccess::Scope(scope)) => scope,
+            (true, TokenAccess::Denied) => {
+                return Box::pin(async {
+                    Err(AppError::Forbidden(
+                        "Service tokens can't be used for this endpoint".to_string(),
+                    )
+                    .into())
+                })
+            }
+            // Public endpoints such as redirects don't care who is asking
+            _ => return Box::pin(self.service.call(req)),
+        };
+        let secret = secret.unwrap_or_default();
+        let tokens = req
+            .app_data::<web::Data<ServiceTokenServiceType>>()
+            .cloned();
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let tokens = tokens.ok_or_else(|| {
+                AppError::Internal("Service token service is not registered".to_string())
+            })?;
+            let identity = tokens.authenticate(&secret).await?.ok_or_else(|| {
+                AppError::Unauthorized("Invalid, expired or revoked service token".to_string())
+            })?;
+
+            if !identity.token.allows(scope) {
+                warn!(
+                    "Refused service token '{}' on {} {}, it lacks '{}'",
+                    identity.token.name,
+                    req.method(),
+                    req.path(),
+                    scope
+                );
+                return Err(
+                    AppError::Forbidden(format!("This token lacks the '{}' scope", scope)).into(),
+                );
+            }
+
+            req.extensions_mut().insert(identity);
+            service.call(req).await
+        })
+    }
+}
+
+enum TokenAccess {
+    /// No credentials needed, like redirects
+    Open,
+    /// Service tokens need this scope
+    Scope(&'static str),
+    /// Only for users, like account endpoints
+    Denied,
+}
+
+// What a service token needs to reach an endpoint
+fn token_access(method: &Method, path: &str) -> TokenAccess {
+    let under = |prefix: &str| {
+        path == prefix
+            || path
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with('/'))
+    };
+    let read = method == Method::GET || method == Method::HEAD;
+
+    if !under("/api") {
+        TokenAccess::Open
+    } else if under("/api/admin") || under("/api/tokens") {
+        TokenAccess::Scope(if read { "admin:read" } else { "admin:write" })
+    } else if under("/api/stats") {
+        TokenAccess::Scope("stats:read")
+    } else if under("/api/urls") {
+        // Resolving codes only reads, even though it's a POST
+        if read || path == "/api/urls/resolve" {
+            TokenAccess::Scope("urls:read")
+        } else {
+            TokenAccess::Scope("urls:write")
+        }
+    } else {
+        TokenAccess::Denied
+    }
+}
+
+/// Whether a request carries the admin token in `X-Admin-Token`, as the dashboard sends it
+/// to endpoints outside the admin API
+pub(crate) fn presents_admin_token(req: &HttpRequest) -> bool {
+    let provided = req
+        .headers()
+        .get("X-Admin-Token")
+        .and_then(|v| v.to_str().ok());
+    let expected = req
+        .app_data::<web::Data<Config>>()
+        .and_then(|config| config.auth.admin_token.as_deref());
+    match (provided, expected) {
+        (Some(provided), Some(expected)) => tokens_match(provided, expected),
+        _ => false,
+    }
+}
+
+// Extract the token from an `Authorization: Bearer` header
+pub(crate) fn bearer_token(req: &ServiceRequest) -> Option<String> {
+    req.headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(|v| v.trim().to_string())
+}
+
+// Compare digests so the comparison time doesn't depend on the secret's contents
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    Sha256::digest(provided.as_bytes()) == Sha256::digest(expected.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scope(method: Method, path: &str) -> Option<&'static str> {
+        match token_access(&method, path) {
+            TokenAccess::Scope(scope) => Some(scope),
+            TokenAccess::Open => Some("open"),
+            TokenAccess::Denied => None,
+        }
+    }
+
+    #[test]
+    fn test_token_access_by_endpoint() {
+        assert_eq!(scope(Method::POST, "/api/urls"), Some("urls:write"));
+        assert_eq!(scope(Method::DELETE, "/api/urls/1"), Some("urls:write"));
+        assert_eq!(scope(Method::GET, "/api/urls/search"), Some("urls:read"));
+        assert_eq!(scope(Method::POST, "/api/urls/resolve"), Some("urls:read"));
+        assert_eq!(scope(Method::GET, "/api/stats/top"), Some("stats:read"));
+        assert_eq!(scope(Method::GET, "/api/tokens"), Some("admin:read"));
+        assert_eq!(
+            scope(Method::POST, "/api/admin/backups"),
+            Some("admin:write")
+        );
+        assert_eq!(scope(Method::GET, "/api/account/export"), None);
+        assert_eq!(scope(Method::GET, "/api/urlsx"), None);
+        assert_eq!(scope(Method::GET, "/docs/setup"), Some("open"));
+    }
+}
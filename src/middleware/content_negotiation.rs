@@ -0,0 +1,205 @@
+use actix_web::body::{self, EitherBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{self, Accept, Header, HeaderValue};
+use actix_web::Error;
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+use log::error;
+
+const API_PATH_PREFIX: &str = "/api/";
+
+/// Binary formats API responses can be re-encoded into
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ResponseFormat {
+    MessagePack,
+    Cbor,
+}
+
+impl ResponseFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ResponseFormat::MessagePack => "application/msgpack",
+            ResponseFormat::Cbor => "application/cbor",
+        }
+    }
+
+    /// Encode a JSON document in this format
+    pub fn encode(&self, value: &serde_json::Value) -> Result<Vec<u8>, String> {
+        match self {
+            ResponseFormat::MessagePack => {
+                rmp_serde::to_vec_named(value).map_err(|e| e.to_string())
+            }
+            ResponseFormat::Cbor => {
+                let mut buf = Vec::new();
+                ciborium::into_writer(value, &mut buf).map_err(|e| e.to_string())?;
+                Ok(buf)
+            }
+        }
+    }
+
+    // The preferred format of an Accept header, None when JSON is preferred or acceptable first
+    fn negotiate(accept: &Accept) -> Option<Self> {
+        accept.ranked().iter().find_map(|mime| {
+            match (mime.type_().as_str(), mime.subtype().as_str()) {
+                ("application", "msgpack" | "x-msgpack" | "vnd.msgpack") => {
+                    Some(Some(ResponseFormat::MessagePack))
+                }
+                ("application", "cbor") => Some(Some(ResponseFormat::Cbor)),
+                ("application", "json") | ("application", "*") | ("*", "*") => Some(None),
+                _ => None,
+            }
+        })?
+    }
+}
+
+/// Serializes API responses as MessagePack or CBOR when the Accept header prefers them
+///
+/// Handlers keep answering with JSON; responses under `/api/` with a JSON body, handler
+/// errors included, are re-encoded on the way out. Everything else, like redirects and streamed
+/// exports, is left as is.
+pub struct ContentNegotiation;
+
+impl<S, B> Transform<S, ServiceRequest> for ContentNegotiation
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: body::MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = ContentNegotiationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ContentNegotiationMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct ContentNegotiationMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for ContentNegotiationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: body::MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        let format = if req.path().starts_with(API_PATH_PREFIX) {
+            Accept::parse(&req)
+                .ok()
+                .and_then(|accept| ResponseFormat::negotiate(&accept))
+        } else {
+            None
+        };
+        let Some(format) = format else {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) });
+        };
+
+        Box::pin(async move {
+            let res = service.call(req).await?.map_into_boxed_body();
+
+            let is_json = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.starts_with("application/json"));
+            if !is_json {
+                return Ok(res.map_into_right_body());
+            }
+
+            let (http_req, res) = res.into_parts();
+            let (mut head, body) = res.into_parts();
+            let bytes = body::to_bytes(body)
+                .await
+                .map_err(|e| actix_web::error::ErrorInternalServerError(e.to_string()))?;
+
+            let encoded = serde_json::from_slice::<serde_json::Value>(&bytes)
+                .map_err(|e| e.to_string())
+                .and_then(|value| format.encode(&value));
+            let res = match encoded {
+                Ok(encoded) => {
+                    let headers = head.headers_mut();
+                    headers.insert(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(format.content_type()),
+                    );
+                    headers.append(header::VARY, HeaderValue::from_static("accept"));
+                    head.set_body(encoded).map_into_boxed_body()
+                }
+                Err(e) => {
+                    // Fall back to the JSON already produced rather than failing the request
+                    error!(
+                        "Failed to encode response as {}: {}",
+                        format.content_type(),
+                        e
+                    );
+                    head.set_body(bytes).map_into_boxed_body()
+                }
+            };
+
+            Ok(ServiceResponse::new(http_req, res).map_into_right_body())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{test, web, App, HttpResponse};
+    use serde_json::json;
+
+    #[actix_web::test]
+    async fn test_negotiated_formats() {
+        let app = test::init_service(App::new().wrap(ContentNegotiation).route(
+            "/api/ping",
+            web::get().to(|| async { HttpResponse::Ok().json(json!({"message": "pong"})) }),
+        ))
+        .await;
+        let get = |accept: &'static str| {
+            test::TestRequest::get()
+                .uri("/api/ping")
+                .insert_header((header::ACCEPT, accept))
+                .to_request()
+        };
+
+        let res = test::call_service(&app, get("application/msgpack")).await;
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/msgpack"
+        );
+        let value: serde_json::Value = rmp_serde::from_slice(&test::read_body(res).await).unwrap();
+        assert_eq!(value, json!({"message": "pong"}));
+
+        let res = test::call_service(&app, get("application/cbor, application/json;q=0.5")).await;
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/cbor"
+        );
+        let value: serde_json::Value =
+            ciborium::from_reader(&test::read_body(res).await[..]).unwrap();
+        assert_eq!(value, json!({"message": "pong"}));
+
+        let res = test::call_service(&app, get("application/json, application/cbor;q=0.5")).await;
+        assert_eq!(
+            res.headers().get(header::CONTENT_TYPE).unwrap(),
+            "application/json"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::{header, Method};
+use actix_web::Error;
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+use sha2::{Digest, Sha256};
+use std::rc::Rc;
+
+use log::warn;
+
+use crate::{config::CsrfConfig, errors::AppError, utils::id_generator::generate_csrf_token};
+
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// Double-submit CSRF protection for cookie authenticated browser sessions
+///
+/// HTML responses hand browsers without one a random token cookie, readable by scripts.
+/// State-changing requests that send cookies must echo that token in `X-CSRF-Token`, which
+/// other sites can't read and so can't forge. Requests authenticating with an
+/// `Authorization` header are left alone, browsers never attach it on their own.
+pub struct CsrfProtection {
+    config: CsrfConfig,
+}
+
+impl CsrfProtection {
+    pub fn new(config: CsrfConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for CsrfProtection
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = CsrfProtectionMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(CsrfProtectionMiddleware {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        })
+    }
+}
+
+pub struct CsrfProtectionMiddleware<S> {
+    service: Rc<S>,
+    config: CsrfConfig,
+}
+
+impl<S, B> Service<ServiceRequest> for CsrfProtectionMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let config = self.config.clone();
+
+        if !config.enabled {
+            return Box::pin(service.call(req));
+        }
+
+        let cookie = req
+            .cookie(&config.cookie_name)
+            .map(|c| c.value().to_string());
+        let safe = matches!(*req.method(), Method::GET | Method::HEAD | Method::OPTIONS);
+
+        if !safe && relies_on_cookies(&req) {
+            let echoed = req
+                .headers()
+                .get(CSRF_HEADER)
+                .and_then(|v| v.to_str().ok())
+                .map(str::trim);
+            let valid = match (cookie.as_deref(), echoed) {
+                (Some(cookie), Some(echoed)) => !cookie.is_empty() && tokens_match(cookie, echoed),
+                _ => false,
+            };
+            if !valid {
+                warn!(
+                    "Refused {} {} without a valid CSRF token",
+                    req.method(),
+                    req.path()
+                );
+                return Box::pin(async {
+                    Err(AppError::Forbidden("Missing or invalid CSRF token".to_string()).into())
+                });
+            }
+        }
+
+        Box::pin(async move {
+            let mut res = service.call(req).await?;
+
+            // Pages are where forms and scripts live, so that's where tokens are issued
+            let is_html = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .is_some_and(|v| v.starts_with("text/html"));
+            if cookie.is_none() && is_html {
+                let cookie = Cookie::build(config.cookie_name, generate_csrf_token())
+                    .path("/")
+                    .same_site(SameSite::Strict)
+                    .secure(config.secure_cookie)
+                    .finish();
+                res.response_mut().add_cookie(&cookie)?;
+            }
+            Ok(res)
+        })
+    }
+}
+
+// Only requests carrying cookies and no explicit credentials can be forged cross-site
+fn relies_on_cookies(req: &ServiceRequest) -> bool {
+    !req.headers().contains_key(header::AUTHORIZATION) && req.headers().contains_key(header::COOKIE)
+}
+
+// Compare digests so the comparison time doesn't depend on the token's contents
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    Sha256::digest(provided.as_bytes()) == Sha256::digest(expected.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+
+    #[actix_web::test]
+    async fn test_csrf_double_submit() {
+        let config = CsrfConfig {
+            enabled: true,
+            cookie_name: "csrf_token".to_string(),
+            secure_cookie: false,
+        };
+        let app = test::init_service(
+            App::new()
+                .wrap(CsrfProtection::new(config))
+                .route(
+                    "/page",
+                    web::get().to(|| async {
+                        HttpResponse::Ok()
+                            .content_type("text/html")
+                            .body("<form></form>")
+                    }),
+                )
+                .route("/action", web::post().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let res =
+            test::call_service(&app, test::TestRequest::get().uri("/page").to_request()).await;
+        let token = res
+            .response()
+            .cookies()
+            .find(|c| c.name() == "csrf_token")
+            .map(|c| c.value().to_string())
+            .expect("pages issue a token");
+
+        let post = || {
+            test::TestRequest::post()
+                .uri("/action")
+                .cookie(Cookie::new("csrf_token", token.clone()))
+        };
+        let status = |req: test::TestRequest| {
+            let app = &app;
+            async move {
+                match app.call(req.to_request()).await {
+                    Ok(res) => res.status(),
+                    Err(e) => e.as_response_error().status_code(),
+                }
+            }
+        };
+
+        assert_eq!(status(post()).await, StatusCode::FORBIDDEN);
+        assert_eq!(
+            status(post().insert_header((CSRF_HEADER, "wrong"))).await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(post().insert_header((CSRF_HEADER, token.as_str()))).await,
+            StatusCode::OK
+        );
+        let bearer = post().insert_header((header::AUTHORIZATION, "Bearer usk_x"));
+        assert_eq!(status(bearer).await, StatusCode::OK);
+        let no_cookies = test::TestRequest::post().uri("/action");
+        assert_eq!(status(no_cookies).await, StatusCode::OK);
+    }
+}
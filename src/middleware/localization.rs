@@ -0,0 +1,157 @@
+use actix_web::body::{self, EitherBody};
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::error::InternalError;
+use actix_web::http::header::{self, HeaderValue};
+use actix_web::{web, Error, HttpResponse};
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+use serde_json::Value;
+use std::rc::Rc;
+
+use crate::{
+    errors::AppError,
+    i18n::{Catalog, Locale},
+};
+
+/// Translates the messages of error responses into the language of `Accept-Language`
+///
+/// Handlers and the middleware inside this one keep failing with English messages; those the
+/// catalog knows are swapped for the negotiated locale's on the way out, and the response says
+/// which language it's in. Successful responses are left as they are.
+pub struct Localization;
+
+impl<S, B> Transform<S, ServiceRequest> for Localization
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: body::MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = LocalizationMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(LocalizationMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct LocalizationMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for LocalizationMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: body::MessageBody + 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        let Some(catalog) = req.app_data::<web::Data<Catalog>>().cloned() else {
+            return Box::pin(async move { Ok(service.call(req).await?.map_into_left_body()) });
+        };
+        let accept_language = req
+            .headers()
+            .get(header::ACCEPT_LANGUAGE)
+            .and_then(|v| v.to_str().ok())
+            .map(String::from);
+
+        Box::pin(async move {
+            let locale = catalog.locale(accept_language.as_deref());
+            let res = match service.call(req).await {
+                Ok(res) => res,
+                // Errors of inner middleware are answered with their translation
+                Err(e) if e.as_error::<AppError>().is_some() => {
+                    let res = localize(e.error_response(), &locale).await?;
+                    return Err(InternalError::from_response(e.to_string(), res).into());
+                }
+                Err(e) => return Err(e),
+            };
+
+            let is_app_error = res
+                .response()
+                .error()
+                .is_some_and(|e| e.as_error::<AppError>().is_some());
+            if !is_app_error {
+                return Ok(res.map_into_left_body());
+            }
+
+            let (http_req, res) = res.into_parts();
+            let res = localize(res.map_into_boxed_body(), &locale).await?;
+            Ok(ServiceResponse::new(http_req, res).map_into_right_body())
+        })
+    }
+}
+
+// An error response with its JSON body translated, saying which language it's in
+async fn localize(res: HttpResponse, locale: &Locale<'_>) -> Result<HttpResponse, Error> {
+    let is_json = res
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if !is_json {
+        return Ok(res);
+    }
+
+    let (mut head, body) = res.into_parts();
+    let bytes = body::to_bytes(body)
+        .await
+        .map_err(|e| actix_web::error::ErrorInternalServerError(e.to_string()))?;
+    let Ok(mut value) = serde_json::from_slice::<Value>(&bytes) else {
+        return Ok(head.set_body(bytes).map_into_boxed_body());
+    };
+
+    translate_error(&mut value, |message| locale.translate(message));
+    if let Ok(tag) = HeaderValue::from_str(locale.tag()) {
+        head.headers_mut().insert(header::CONTENT_LANGUAGE, tag);
+    }
+    head.headers_mut()
+        .append(header::VARY, HeaderValue::from_static("accept-language"));
+    Ok(head.set_body(value.to_string()).map_into_boxed_body())
+}
+
+// Translate the message of an error body, and the reasons of each field when there are some
+fn translate_error(value: &mut Value, translate: impl Fn(&str) -> String) {
+    let Some(fields) = value.get_mut("errors").and_then(Value::as_object_mut) else {
+        if let Some(message) = value.get_mut("message") {
+            if let Some(translated) = message.as_str().map(&translate) {
+                *message = Value::String(translated);
+            }
+        }
+        return;
+    };
+
+    // The message of field errors lists them all, so it's rebuilt from the translations
+    let mut parts = Vec::new();
+    for (field, reasons) in fields.iter_mut() {
+        let Some(reasons) = reasons.as_array_mut() else {
+            continue;
+        };
+        for reason in reasons.iter_mut() {
+            if let Some(translated) = reason.as_str().map(&translate) {
+                *reason = Value::String(translated);
+            }
+        }
+        let reasons = reasons
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(", ");
+        parts.push(format!("{}: {}", field, reasons));
+    }
+    value["message"] = Value::String(parts.join("; "));
+}
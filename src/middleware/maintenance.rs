@@ -0,0 +1,121 @@
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::{
+    errors::{AppError, ErrorCode},
+    utils::maintenance::MaintenanceMode,
+};
+
+const API_PATH_PREFIX: &str = "/api/";
+const ADMIN_PATH_PREFIX: &str = "/api/admin/";
+
+/// Answers API requests with 503 while the instance is in maintenance
+///
+/// Admin endpoints stay reachable to switch maintenance off again. Redirects, the health
+/// check and the admin dashboard go through; redirects stop writing to the database themselves.
+pub struct Maintenance {
+    mode: Arc<MaintenanceMode>,
+}
+
+impl Maintenance {
+    pub fn new(mode: Arc<MaintenanceMode>) -> Self {
+        Self { mode }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for Maintenance
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = MaintenanceMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(MaintenanceMiddleware {
+            service: Rc::new(service),
+            mode: self.mode.clone(),
+        })
+    }
+}
+
+pub struct MaintenanceMiddleware<S> {
+    service: Rc<S>,
+    mode: Arc<MaintenanceMode>,
+}
+
+impl<S, B> Service<ServiceRequest> for MaintenanceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let path = req.path();
+        if path.starts_with(API_PATH_PREFIX)
+            && !path.starts_with(ADMIN_PATH_PREFIX)
+            && self.mode.is_enabled()
+        {
+            let message = self.mode.status().message;
+            let error = AppError::Unavailable(message).with_code(ErrorCode::Maintenance);
+            return Box::pin(async move { Err(error.into()) });
+        }
+
+        Box::pin(self.service.call(req))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::MaintenanceConfig;
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+
+    #[actix_web::test]
+    async fn test_only_api_is_refused() {
+        let mode = Arc::new(MaintenanceMode::new(&MaintenanceConfig {
+            enabled: true,
+            message: "Back soon".to_string(),
+        }));
+        let app = test::init_service(
+            App::new()
+                .wrap(Maintenance::new(mode.clone()))
+                .route("/api/urls", web::get().to(HttpResponse::Ok))
+                .route("/api/admin/maintenance", web::get().to(HttpResponse::Ok))
+                .route("/{code}", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        for (path, expected) in [
+            ("/api/urls", StatusCode::SERVICE_UNAVAILABLE),
+            ("/api/admin/maintenance", StatusCode::OK),
+            ("/abc123", StatusCode::OK),
+        ] {
+            let res = app
+                .call(test::TestRequest::get().uri(path).to_request())
+                .await;
+            let status = res.map_or_else(|e| e.as_response_error().status_code(), |r| r.status());
+            assert_eq!(status, expected, "{}", path);
+        }
+
+        mode.set(false, None);
+        let res = test::call_service(&app, test::TestRequest::get().uri("/api/urls").to_request());
+        assert_eq!(res.await.status(), StatusCode::OK);
+    }
+}
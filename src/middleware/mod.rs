@@ -1,3 +1,24 @@
+pub mod api_version;
+pub mod auth;
+pub mod content_negotiation;
+pub mod csrf;
+pub mod localization;
+pub mod maintenance;
+pub mod rate_limit;
 pub mod request_logger;
+pub mod request_signing;
+pub mod scanner_guard;
+pub mod timeout;
 
-pub use request_logger::RequestLogger;
\ No newline at end of file
+pub use api_version::ApiVersioning;
+pub(crate) use auth::presents_admin_token;
+pub use auth::{AdminAuth, ServiceTokenAuth};
+pub use content_negotiation::ContentNegotiation;
+pub use csrf::CsrfProtection;
+pub use localization::Localization;
+pub use maintenance::Maintenance;
+pub use rate_limit::RateLimitHeaders;
+pub use request_logger::RequestLogger;
+pub use request_signing::RequestSigning;
+pub use scanner_guard::ScannerGuard;
+pub use timeout::RequestTimeout;
@@ -0,0 +1,123 @@
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage};
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+
+use crate::{errors::retry_after_secs, utils::rate_limiter::Quota};
+
+pub const X_RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+pub const X_RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+pub const X_RATELIMIT_RESET: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+
+/// Tells rate limited clients where they stand with `X-RateLimit-Limit`, `-Remaining` and
+/// `-Reset`, in seconds
+///
+/// Handlers checking a limiter store the resulting [`Quota`] in the request extensions; the
+/// headers are added to whatever the request is answered with, a 429 included, so clients can
+/// slow down before running into the limit. Requests no limiter looked at get none.
+pub struct RateLimitHeaders;
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimitHeaders
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RateLimitHeadersMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimitHeadersMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct RateLimitHeadersMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitHeadersMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+
+        Box::pin(async move {
+            let mut res = service.call(req).await?;
+            let quota = res.request().extensions().get::<Quota>().copied();
+            if let Some(quota) = quota {
+                insert_quota(res.headers_mut(), &quota);
+            }
+            Ok(res)
+        })
+    }
+}
+
+fn insert_quota(headers: &mut HeaderMap, quota: &Quota) {
+    for (name, value) in [
+        (X_RATELIMIT_LIMIT, u64::from(quota.limit)),
+        (X_RATELIMIT_REMAINING, u64::from(quota.remaining)),
+        (X_RATELIMIT_RESET, retry_after_secs(quota.reset)),
+    ] {
+        headers.insert(name, HeaderValue::from(value));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App, HttpRequest, HttpResponse};
+    use std::time::Duration;
+
+    use crate::errors::AppError;
+
+    async fn limited(req: HttpRequest) -> Result<HttpResponse, AppError> {
+        let quota = Quota {
+            limit: 10,
+            remaining: 0,
+            reset: Duration::from_millis(2500),
+        };
+        req.extensions_mut().insert(quota);
+        Err(AppError::RateLimited("Slow down".to_string(), quota.reset))
+    }
+
+    #[actix_web::test]
+    async fn test_headers_follow_the_quota() {
+        let app = test::init_service(
+            App::new()
+                .wrap(RateLimitHeaders)
+                .route("/limited", web::post().to(limited))
+                .route("/open", web::get().to(HttpResponse::Ok)),
+        )
+        .await;
+
+        let req = test::TestRequest::post().uri("/limited").to_request();
+        let res = test::call_service(&app, req).await;
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        let header = |name: HeaderName| res.headers().get(name).and_then(|v| v.to_str().ok());
+        assert_eq!(header(X_RATELIMIT_LIMIT), Some("10"));
+        assert_eq!(header(X_RATELIMIT_REMAINING), Some("0"));
+        assert_eq!(header(X_RATELIMIT_RESET), Some("3"));
+
+        let req = test::TestRequest::get().uri("/open").to_request();
+        let res = test::call_service(&app, req).await;
+        assert!(!res.headers().contains_key(X_RATELIMIT_LIMIT));
+    }
+}
@@ -0,0 +1,118 @@
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::StatusCode;
+use actix_web::Error;
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use log::error;
+
+use crate::{errors::AppError, handlers::ScannerGuardServiceType, utils::network::client_ip};
+
+const ADMIN_PATH_PREFIX: &str = "/api/admin/";
+
+/// Slows down and blocks clients that enumerate short codes
+///
+/// Blocked addresses get a 403 without reaching the app. Otherwise every 404 counts as a miss;
+/// past the tarpit threshold responses are delayed, doubling with each miss, and past the
+/// block threshold the address is blocked. Requesting a honeypot path blocks immediately.
+pub struct ScannerGuard {
+    service: Arc<ScannerGuardServiceType>,
+}
+
+impl ScannerGuard {
+    pub fn new(service: Arc<ScannerGuardServiceType>) -> Self {
+        Self { service }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for ScannerGuard
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = ScannerGuardMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(ScannerGuardMiddleware {
+            service: Rc::new(service),
+            guard: self.service.clone(),
+        })
+    }
+}
+
+pub struct ScannerGuardMiddleware<S> {
+    service: Rc<S>,
+    guard: Arc<ScannerGuardServiceType>,
+}
+
+impl<S, B> Service<ServiceRequest> for ScannerGuardMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let guard = self.guard.clone();
+
+        // The admin API is token protected and must stay reachable to lift blocks
+        if !guard.is_enabled() || req.path().starts_with(ADMIN_PATH_PREFIX) {
+            return Box::pin(service.call(req));
+        }
+
+        let ip = client_ip(req.request());
+        let ip = match ip {
+            Some(ip) => ip,
+            None => return Box::pin(service.call(req)),
+        };
+
+        if guard.is_blocked(&ip) {
+            return Box::pin(async {
+                Err(AppError::Forbidden("Access from this address is blocked".to_string()).into())
+            });
+        }
+
+        let honeypot = guard
+            .is_honeypot(req.path())
+            .then(|| req.path().to_string());
+        let delay = guard.tarpit_delay(&ip);
+
+        Box::pin(async move {
+            if let Some(path) = honeypot {
+                if let Err(e) = guard.record_honeypot_hit(ip, &path).await {
+                    error!("Failed to block {}: {}", ip, e);
+                }
+                return Err(
+                    AppError::Forbidden("Access from this address is blocked".to_string()).into(),
+                );
+            }
+
+            if !delay.is_zero() {
+                tokio::time::sleep(delay).await;
+            }
+
+            let res = service.call(req).await?;
+            if res.status() == StatusCode::NOT_FOUND {
+                if let Err(e) = guard.record_miss(ip).await {
+                    error!("Failed to record miss for {}: {}", ip, e);
+                }
+            }
+            Ok(res)
+        })
+    }
+}
@@ -0,0 +1,132 @@
+use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::Error;
+use futures_util::future::{ok, LocalBoxFuture, Ready};
+use std::rc::Rc;
+use std::time::Duration;
+
+use log::warn;
+
+use crate::{config::RequestTimeoutConfig, errors::AppError};
+
+/// Answers requests whose handler takes too long with 504
+///
+/// Admin endpoints, other API endpoints and everything else (redirects above all) have their
+/// own limit. Past it the handler future is dropped, cancelling whatever it was waiting on.
+/// Streamed bodies are not limited once the response has started.
+pub struct RequestTimeout {
+    config: RequestTimeoutConfig,
+}
+
+impl RequestTimeout {
+    pub fn new(config: RequestTimeoutConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequestTimeout
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestTimeoutMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequestTimeoutMiddleware {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        })
+    }
+}
+
+pub struct RequestTimeoutMiddleware<S> {
+    service: Rc<S>,
+    config: RequestTimeoutConfig,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestTimeoutMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let path = req.path();
+        let limit_ms = if path.starts_with("/api/admin/") {
+            self.config.admin_ms
+        } else if path.starts_with("/api/") {
+            self.config.api_ms
+        } else {
+            self.config.redirect_ms
+        };
+
+        if limit_ms == 0 {
+            return Box::pin(self.service.call(req));
+        }
+
+        let method = req.method().clone();
+        let path = path.to_string();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            match tokio::time::timeout(Duration::from_millis(limit_ms), fut).await {
+                Ok(res) => res,
+                Err(_) => {
+                    warn!("{} {} timed out after {}ms", method, path, limit_ms);
+                    Err(
+                        AppError::Timeout(format!("The request took longer than {}ms", limit_ms))
+                            .into(),
+                    )
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::{http::StatusCode, test, web, App, HttpResponse};
+
+    #[actix_web::test]
+    async fn test_slow_handlers_time_out() {
+        let config = RequestTimeoutConfig {
+            redirect_ms: 50,
+            api_ms: 500,
+            admin_ms: 0,
+        };
+        let slow = || async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            HttpResponse::Ok().finish()
+        };
+        let app = test::init_service(
+            App::new()
+                .wrap(RequestTimeout::new(config))
+                .route("/abc", web::get().to(slow))
+                .route("/api/urls", web::get().to(slow)),
+        )
+        .await;
+
+        let res = app
+            .call(test::TestRequest::get().uri("/abc").to_request())
+            .await;
+        let status = res.map_or_else(|e| e.as_response_error().status_code(), |r| r.status());
+        assert_eq!(status, StatusCode::GATEWAY_TIMEOUT);
+
+        let res = test::call_service(&app, test::TestRequest::get().uri("/api/urls").to_request());
+        assert_eq!(res.await.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::{DailyVisitAggregate, ShortenedUrlResponseDto, User};
+
+/// Lifecycle of an asynchronous account export
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ExportStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// An account export request (the archive bytes are fetched separately)
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct AccountExport {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub status: ExportStatus,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// Everything stored about an account, as delivered in the export archive
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountArchive {
+    pub exported_at: DateTime<Utc>,
+    pub user: User,
+    pub links: Vec<ShortenedUrlResponseDto>,
+    pub visit_aggregates: Vec<DailyVisitAggregate>,
+}
+
+/// How DELETE /api/account treats the caller's links
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeletionMode {
+    /// Delete the account together with its links and their visit history
+    #[default]
+    Erase,
+    /// Delete the account but keep its links resolving, detached from any owner
+    Anonymize,
+}
+
+// Query parameters for account deletion
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct DeleteAccountParams {
+    pub mode: Option<DeletionMode>,
+}
+
+/// Outcome of an account deletion
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AccountDeletionReport {
+    pub user_id: Uuid,
+    pub mode: DeletionMode,
+    pub links_affected: u64,
+}
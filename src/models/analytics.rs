@@ -0,0 +1,65 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A single recorded redirect of a shortened URL
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct UrlVisit {
+    /// Sequential ID of the visit event
+    pub id: i64,
+
+    /// The shortened URL that was visited
+    pub url_id: Uuid,
+
+    /// When the redirect happened
+    pub visited_at: DateTime<Utc>,
+
+    /// Resolved client IP of the visitor
+    pub ip_address: Option<String>,
+
+    /// User-Agent header sent by the visitor
+    pub user_agent: Option<String>,
+
+    /// Referer header sent by the visitor
+    pub referer: Option<String>,
+}
+
+/// A visit as sent to an analytics warehouse, one JSON object per line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedVisit {
+    pub url_id: Uuid,
+    pub visited_at: DateTime<Utc>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub referer: Option<String>,
+}
+
+impl From<&UrlVisit> for ExportedVisit {
+    fn from(visit: &UrlVisit) -> Self {
+        Self {
+            url_id: visit.url_id,
+            visited_at: visit.visited_at,
+            ip_address: visit.ip_address.clone(),
+            user_agent: visit.user_agent.clone(),
+            referer: visit.referer.clone(),
+        }
+    }
+}
+
+// Data captured from a redirect request before it is persisted
+#[derive(Debug, Clone, Default)]
+pub struct NewUrlVisit {
+    pub url_id: Uuid,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub referer: Option<String>,
+}
+
+/// Visit count for a URL on a single day, produced by the retention rollup
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DailyVisitAggregate {
+    pub url_id: Uuid,
+    pub day: NaiveDate,
+    pub visits: i64,
+}
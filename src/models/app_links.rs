@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::validations::{
+    validate_android_package, validate_cert_fingerprints, validate_ios_app_ids,
+};
+
+/// Mobile apps allowed to open a tenant's short links directly, as iOS universal links
+/// and Android app links
+///
+/// The apps prove the association by fetching the files generated from this from the
+/// tenant's domain. Tenants that never set theirs get the default, with no apps.
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize)]
+pub struct AppLinks {
+    /// iOS app identifiers, team ID and bundle ID like `ABCDE12345.com.example.app`
+    pub ios_app_ids: Vec<String>,
+
+    /// Android application ID, like `com.example.app`
+    pub android_package: Option<String>,
+
+    /// SHA-256 fingerprints of the Android app's signing certificates
+    pub android_cert_fingerprints: Vec<String>,
+
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl AppLinks {
+    /// The `apple-app-site-association` file, if an iOS app is set up
+    pub fn apple_app_site_association(&self) -> Option<JsonValue> {
+        if self.ios_app_ids.is_empty() {
+            return None;
+        }
+        let details = self
+            .ios_app_ids
+            .iter()
+            .map(|id| json!({ "appID": id, "paths": ["*"] }))
+            .collect::<Vec<_>>();
+        Some(json!({
+            "applinks": {
+                "apps": [],
+                "details": details,
+            },
+        }))
+    }
+
+    /// The `assetlinks.json` file, if an Android app is set up
+    pub fn asset_links(&self) -> Option<JsonValue> {
+        let package = self.android_package.as_ref()?;
+        if self.android_cert_fingerprints.is_empty() {
+            return None;
+        }
+        Some(json!([{
+            "relation": ["delegate_permission/common.handle_all_urls"],
+            "target": {
+                "namespace": "android_app",
+                "package_name": package,
+                "sha256_cert_fingerprints": self.android_cert_fingerprints,
+            },
+        }]))
+    }
+}
+
+// DTO for replacing a tenant's app links, fields left out remove that platform's app
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct UpdateAppLinksDto {
+    #[serde(default)]
+    #[validate(custom(function = "validate_ios_app_ids"))]
+    pub ios_app_ids: Vec<String>,
+
+    #[validate(custom(function = "validate_android_package"))]
+    pub android_package: Option<String>,
+
+    #[serde(default)]
+    #[validate(custom(function = "validate_cert_fingerprints"))]
+    pub android_cert_fingerprints: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_association_files() {
+        let mut links = AppLinks::default();
+        assert!(links.apple_app_site_association().is_none());
+        assert!(links.asset_links().is_none());
+
+        links.ios_app_ids = vec!["ABCDE12345.com.example.app".to_string()];
+        links.android_package = Some("com.example.app".to_string());
+        links.android_cert_fingerprints = vec!["14:6D:E9".to_string()];
+
+        let aasa = links.apple_app_site_association().unwrap();
+        assert_eq!(
+            aasa["applinks"]["details"][0]["appID"],
+            "ABCDE12345.com.example.app"
+        );
+        let assets = links.asset_links().unwrap();
+        assert_eq!(assets[0]["target"]["package_name"], "com.example.app");
+        assert_eq!(
+            assets[0]["target"]["sha256_cert_fingerprints"][0],
+            "14:6D:E9"
+        );
+    }
+}
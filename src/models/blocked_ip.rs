@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::validations::validate_ip_address;
+
+/// Who put a block in place
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum BlockSource {
+    /// The scanner guard, after too many misses
+    Auto,
+    /// An admin, through the API
+    Manual,
+}
+
+/// A client address refused by the scanner guard
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct BlockedIp {
+    pub ip: String,
+    pub reason: String,
+    pub source: BlockSource,
+
+    /// End of the block, `None` for a permanent block
+    pub blocked_until: Option<DateTime<Utc>>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+// DTO for blocking an address by hand
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct BlockIpDto {
+    #[validate(custom(function = "validate_ip_address"))]
+    pub ip: String,
+
+    #[validate(length(
+        min = 1,
+        max = 500,
+        message = "Reason must be between 1 and 500 characters"
+    ))]
+    pub reason: Option<String>,
+
+    /// How long the block lasts, permanent when omitted
+    #[validate(range(min = 1, message = "Duration must be at least 1 second"))]
+    pub duration_seconds: Option<i64>,
+}
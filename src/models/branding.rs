@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use validator::Validate;
+
+use crate::validations::{validate_color, validate_url};
+
+/// Look of the pages shown to a tenant's visitors, like the one for a missing link
+///
+/// Unset fields use the built-in look. Tenants that never changed theirs get the
+/// default, with no `updated_at`.
+#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize)]
+pub struct Branding {
+    pub logo_url: Option<String>,
+
+    /// Hex color of headings and links
+    pub primary_color: Option<String>,
+
+    /// Hex color of the page background
+    pub background_color: Option<String>,
+
+    /// Plain text shown at the bottom of every page
+    pub footer_text: Option<String>,
+
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+// DTO for replacing a tenant's branding, fields left out go back to the default
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct UpdateBrandingDto {
+    #[validate(custom(function = "validate_url"))]
+    pub logo_url: Option<String>,
+
+    #[validate(custom(function = "validate_color"))]
+    pub primary_color: Option<String>,
+
+    #[validate(custom(function = "validate_color"))]
+    pub background_color: Option<String>,
+
+    #[validate(length(max = 500, message = "Footer text must be at most 500 characters"))]
+    pub footer_text: Option<String>,
+}
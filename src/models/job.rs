@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Where a scheduled job stands
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    /// Never ran yet
+    Idle,
+    Running,
+    Succeeded,
+    Failed,
+    /// The instance stopped while the job was running
+    Interrupted,
+}
+
+/// A scheduled background job with the outcome of its last run
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct JobRecord {
+    pub name: String,
+    pub schedule: String,
+    pub status: JobStatus,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_finished_at: Option<DateTime<Utc>>,
+    /// Summary of the last run, or its error
+    pub last_message: Option<String>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub run_count: i64,
+    /// Fencing token of the last instance that took the job lock
+    pub fence: i64,
+}
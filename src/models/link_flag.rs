@@ -0,0 +1,77 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+/// What the anomaly detector does with a link it flags
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum FlagAction {
+    /// Tell the admins; visitors are warned before being redirected while the flag is open
+    Notify,
+    /// Tell the admins and suspend redirects for a while
+    Throttle,
+}
+
+impl FromStr for FlagAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "notify" => Ok(FlagAction::Notify),
+            "throttle" => Ok(FlagAction::Throttle),
+            _ => Err(format!(
+                "Invalid flag action: {}. Must be one of: notify, throttle",
+                s
+            )),
+        }
+    }
+}
+
+/// A link flagged for abnormal traffic
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct LinkFlag {
+    pub id: i64,
+    pub url_id: Uuid,
+    pub reason: String,
+    pub action: FlagAction,
+
+    /// Clicks seen in the detection window
+    pub clicks: i64,
+
+    /// Distinct client IPs behind those clicks
+    pub distinct_ips: i64,
+
+    pub window_seconds: i32,
+    pub flagged_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+/// Click burst on a link within the detection window
+#[derive(Debug, Clone, FromRow)]
+pub struct ClickSpike {
+    pub url_id: Uuid,
+    /// Including the namespace, if any
+    pub short_code: String,
+    pub created_by: Option<Uuid>,
+    pub clicks: i64,
+    pub distinct_ips: i64,
+}
+
+// Query parameters for listing flags
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct LinkFlagQueryParams {
+    /// Only open (false) or only resolved (true) flags, all when omitted
+    pub resolved: Option<bool>,
+
+    /// Lowered to the configured maximum page size when larger
+    #[validate(range(min = 1, message = "Limit must be at least 1"))]
+    pub limit: Option<i64>,
+
+    #[validate(range(min = 0, message = "Offset must be positive"))]
+    pub offset: Option<i64>,
+}
@@ -0,0 +1,60 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::validations::validate_tenant_slug;
+
+/// Where a transfer of a link stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum TransferStatus {
+    /// Offered by the owner, waiting for the recipient
+    Pending,
+    /// Accepted by the recipient, or made by an admin
+    Accepted,
+    /// Turned down by the recipient
+    Declined,
+    /// Withdrawn by the owner, or replaced by a transfer an admin made
+    Cancelled,
+}
+
+/// A change of owner or tenant of a link, kept once resolved as its transfer history
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct LinkTransfer {
+    pub id: Uuid,
+    pub url_id: Uuid,
+
+    /// Owner of the link when the transfer was made, `None` for anonymous links
+    pub from_user_id: Option<Uuid>,
+
+    /// Owner of the link once transferred
+    pub to_user_id: Option<Uuid>,
+
+    /// Tenant of the link when the transfer was made, `None` for the default tenant
+    pub from_tenant_id: Option<Uuid>,
+
+    /// Tenant of the link once transferred, the same one unless an admin moved it
+    pub to_tenant_id: Option<Uuid>,
+
+    pub status: TransferStatus,
+
+    /// Made with the admin token, without the recipient accepting
+    pub by_admin: bool,
+
+    pub created_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+// DTO for handing a link over to another user, or with the admin token, another tenant
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct TransferLinkDto {
+    /// The new owner, who has to accept unless the admin token is used
+    pub to_user_id: Option<Uuid>,
+
+    /// Slug of the tenant to move the link to, only with the admin token
+    #[validate(custom(function = "validate_tenant_slug"))]
+    pub to_tenant: Option<String>,
+}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+// DTO for switching maintenance mode on or off
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct UpdateMaintenanceDto {
+    pub enabled: bool,
+
+    /// Told to API clients instead of `MAINTENANCE_MESSAGE`
+    #[validate(length(
+        min = 1,
+        max = 500,
+        message = "Message must be between 1 and 500 characters"
+    ))]
+    pub message: Option<String>,
+}
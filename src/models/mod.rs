@@ -1,6 +1,58 @@
+pub mod account;
+pub mod analytics;
+pub mod app_links;
+pub mod blocked_ip;
+pub mod branding;
+pub mod job;
+pub mod link_flag;
+pub mod link_transfer;
+pub mod maintenance;
+pub mod notification;
+pub mod oauth;
+pub mod page;
+pub mod saved_search;
+pub mod service_token;
 pub mod shortened_url;
+pub mod snapshot;
+pub mod stats;
+pub mod tenant;
+pub mod url_revision;
+pub mod user;
 
+pub use account::{
+    AccountArchive, AccountDeletionReport, AccountExport, DeleteAccountParams, DeletionMode,
+    ExportStatus,
+};
+pub use analytics::{DailyVisitAggregate, ExportedVisit, NewUrlVisit, UrlVisit};
+pub use app_links::{AppLinks, UpdateAppLinksDto};
+pub use blocked_ip::{BlockIpDto, BlockSource, BlockedIp};
+pub use branding::{Branding, UpdateBrandingDto};
+pub use job::{JobRecord, JobStatus};
+pub use link_flag::{ClickSpike, FlagAction, LinkFlag, LinkFlagQueryParams};
+pub use link_transfer::{LinkTransfer, TransferLinkDto, TransferStatus};
+pub use maintenance::UpdateMaintenanceDto;
+pub use notification::{
+    ChannelKind, CreateNotificationChannelDto, DigestFrequency, DigestLink, DigestRecipient,
+    ExpiringLink, LinkDigest, NotificationChannel, NotificationEventKind, NotificationPreferences,
+    ReachedMilestone, UpdateNotificationPreferencesDto,
+};
+pub use oauth::{OAuthCallbackParams, OAuthProfile, OAuthProvider, PendingLogin};
+pub use page::Page;
+pub use saved_search::{SaveSearchDto, SavedSearch};
+pub use service_token::{CreateServiceTokenDto, ServiceToken, ServiceTokenIdentity};
 pub use shortened_url::{
-    CreateShortenedUrlDto, ShortenedUrl, ShortenedUrlQueryParams, ShortenedUrlResponseDto,
-    ShortenedUrlUpdateParams,
+    AliasConflict, BulkDeleteUrlsDto, BulkItemResult, BulkUpdateUrlsDto, ClaimLinkDto,
+    CodeStrategy, CreateShortenedUrlDto, DryRunParams, DuplicateShortenedUrlDto,
+    ExpiringQueryParams, LinkBehavior, LinkHealth, LinkState, LinkStatus, NewShortenedUrl,
+    Reachability, ResolveCodesDto, ResolvedCode, ShortenedUrl, ShortenedUrlDetailsDto,
+    ShortenedUrlQueryParams, ShortenedUrlResponseDto, ShortenedUrlUpdateParams, TrashStats,
+    TrashedUrl, TrashedUrlDto, UpdateLinkStatusDto, MAX_BULK_URLS,
+};
+pub use snapshot::{RestoreReport, Snapshot, TableRestore, SNAPSHOT_FORMAT_VERSION};
+pub use stats::{
+    GroupByQueryParams, GroupStats, ReportFormat, StatsDimension, StatsWindow, TopLink,
+    TopLinksQueryParams,
 };
+pub use tenant::{CreateTenantDto, Tenant, TenantScope};
+pub use url_revision::UrlRevision;
+pub use user::{ApiKey, CreateUserDto, User};
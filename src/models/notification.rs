@@ -0,0 +1,212 @@
+use std::str::FromStr;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::validations::validate_channel_target;
+
+/// A user's notification settings
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct NotificationPreferences {
+    pub user_id: Uuid,
+
+    /// Whether to email the user before their links expire
+    pub expiry_emails: bool,
+
+    /// How many days before expiry the email is sent
+    pub expiry_notice_days: i32,
+
+    /// How often the user gets a digest of their links' clicks
+    pub digest_frequency: DigestFrequency,
+
+    /// Whether the user opted out of digests, by email and on their channels
+    pub digest_unsubscribed: bool,
+
+    pub updated_at: DateTime<Utc>,
+}
+
+// DTO for updating notification preferences, omitted fields are left unchanged
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct UpdateNotificationPreferencesDto {
+    pub expiry_emails: Option<bool>,
+
+    #[validate(range(
+        min = 1,
+        max = 30,
+        message = "Expiry notice must be between 1 and 30 days"
+    ))]
+    pub expiry_notice_days: Option<i32>,
+
+    pub digest_frequency: Option<DigestFrequency>,
+
+    pub digest_unsubscribed: Option<bool>,
+}
+
+/// How often a user gets a digest of their links' clicks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum DigestFrequency {
+    #[default]
+    Weekly,
+    Monthly,
+}
+
+impl DigestFrequency {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DigestFrequency::Weekly => "weekly",
+            DigestFrequency::Monthly => "monthly",
+        }
+    }
+}
+
+/// A link owner due a digest for a period
+#[derive(Debug, Clone, FromRow)]
+pub struct DigestRecipient {
+    pub user_id: Uuid,
+    pub email: String,
+    pub name: Option<String>,
+}
+
+/// One of the links clicked most during a digest period
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DigestLink {
+    /// Including the namespace, if any
+    pub short_code: String,
+    pub short_url: String,
+    pub original_url: String,
+    pub clicks: i64,
+}
+
+/// An owner's clicks over a digest period, compared with the period before
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkDigest {
+    pub frequency: DigestFrequency,
+    /// First day of the period
+    pub period_start: NaiveDate,
+    /// Last day of the period
+    pub period_end: NaiveDate,
+    pub clicks: i64,
+    pub previous_clicks: i64,
+    /// Most clicked first
+    pub top_links: Vec<DigestLink>,
+}
+
+/// A link nearing expiry whose owner hasn't been notified yet
+#[derive(Debug, Clone, FromRow)]
+pub struct ExpiringLink {
+    pub url_id: Uuid,
+    pub owner_id: Uuid,
+    /// Including the namespace, if any
+    pub short_code: String,
+    pub original_url: String,
+    pub expires_at: DateTime<Utc>,
+    pub owner_email: String,
+    pub owner_name: Option<String>,
+    /// Base URL of the link's tenant, if it has its own
+    pub base_url: Option<String>,
+
+    /// Whether the owner wants the expiry email, team channels are notified regardless
+    pub email_enabled: bool,
+}
+
+/// A click milestone reached by a link whose owner hasn't been told yet
+#[derive(Debug, Clone, FromRow)]
+pub struct ReachedMilestone {
+    pub url_id: Uuid,
+    /// None for anonymous links, which have nobody to tell
+    pub owner_id: Option<Uuid>,
+    /// Including the namespace, if any
+    pub short_code: String,
+    pub original_url: String,
+    pub milestone: i64,
+    pub reached_at: DateTime<Utc>,
+    /// Base URL of the link's tenant, if it has its own
+    pub base_url: Option<String>,
+}
+
+/// Where a notification channel delivers events
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    Slack,
+    Discord,
+    Webhook,
+}
+
+impl FromStr for ChannelKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "slack" => Ok(ChannelKind::Slack),
+            "discord" => Ok(ChannelKind::Discord),
+            "webhook" => Ok(ChannelKind::Webhook),
+            _ => Err(format!(
+                "Invalid channel kind: {}. Must be one of: slack, discord, webhook",
+                s
+            )),
+        }
+    }
+}
+
+/// Kinds of events a channel can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEventKind {
+    LinkExpiring,
+    LinkExpired,
+    LinkFlagged,
+    LinkMilestone,
+    LinkBroken,
+    LinkDigest,
+    Test,
+}
+
+impl NotificationEventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationEventKind::LinkExpiring => "link_expiring",
+            NotificationEventKind::LinkExpired => "link_expired",
+            NotificationEventKind::LinkFlagged => "link_flagged",
+            NotificationEventKind::LinkMilestone => "link_milestone",
+            NotificationEventKind::LinkBroken => "link_broken",
+            NotificationEventKind::LinkDigest => "link_digest",
+            NotificationEventKind::Test => "test",
+        }
+    }
+}
+
+/// A team channel receiving a user's events
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct NotificationChannel {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub kind: ChannelKind,
+
+    /// Incoming webhook URL of the channel
+    pub target_url: String,
+
+    /// Subscribed event kinds, empty for all events
+    pub events: Vec<String>,
+
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+// DTO for adding a notification channel
+#[derive(Debug, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_channel_target"))]
+pub struct CreateNotificationChannelDto {
+    pub kind: ChannelKind,
+
+    pub target_url: String,
+
+    /// Event kinds to deliver, all events when omitted or empty
+    pub events: Option<Vec<NotificationEventKind>>,
+}
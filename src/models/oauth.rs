@@ -0,0 +1,62 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A provider users can sign in with instead of holding an API key up front
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum OAuthProvider {
+    GitHub,
+    Google,
+}
+
+impl OAuthProvider {
+    /// Name used in paths and stored with identities
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OAuthProvider::GitHub => "github",
+            OAuthProvider::Google => "google",
+        }
+    }
+}
+
+impl fmt::Display for OAuthProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OAuthProvider::GitHub => write!(f, "GitHub"),
+            OAuthProvider::Google => write!(f, "Google"),
+        }
+    }
+}
+
+// Query parameters a provider sends users back with
+#[derive(Debug, Deserialize)]
+pub struct OAuthCallbackParams {
+    pub state: String,
+    pub code: Option<String>,
+    /// Set instead of `code` when the user declined or the provider refused
+    pub error: Option<String>,
+}
+
+/// A login started at a provider, waiting for the user to come back
+#[derive(Debug, Clone, FromRow)]
+pub struct PendingLogin {
+    pub code_verifier: String,
+    /// SHA-256 of the nonce in the cookie of the browser that started the login
+    pub nonce_hash: String,
+    /// The signed-in user the identity gets linked to, `None` to sign in
+    pub link_user_id: Option<Uuid>,
+}
+
+/// Who the user is at the provider
+#[derive(Debug, Clone)]
+pub struct OAuthProfile {
+    /// The account ID at the provider
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub name: Option<String>,
+}
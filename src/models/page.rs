@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+/// One page of a listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+
+    /// The page size applied, which may be lower than the one requested
+    pub limit: i64,
+}
+
+impl<T> Page<T> {
+    /// The same page with every item converted, e.g. from entities to response DTOs
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            limit: self.limit,
+        }
+    }
+}
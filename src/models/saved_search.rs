@@ -0,0 +1,37 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::ShortenedUrlQueryParams;
+use crate::validations::validate_saved_search;
+
+/// A named set of URL filters, like "expiring soon", applied to listings with `?preset=name`
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub id: Uuid,
+    pub name: String,
+
+    /// Query parameters of the search endpoint, only the ones set
+    pub params: JsonValue,
+
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// DTO for saving a search, or replacing a saved one
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_saved_search", skip_on_field_errors = false))]
+pub struct SaveSearchDto {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+
+    #[validate(nested)]
+    pub params: ShortenedUrlQueryParams,
+}
@@ -0,0 +1,74 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::User;
+use crate::validations::validate_scopes;
+
+/// Scopes a service token can be granted, each family also as a whole with `family:*`
+pub const SCOPES: &[&str] = &[
+    "urls:read",
+    "urls:write",
+    "stats:read",
+    "admin:read",
+    "admin:write",
+];
+
+/// A machine credential for integrations such as CI pipelines creating links
+///
+/// Unlike API keys, tokens don't stand for a user: they can only reach the endpoints
+/// their scopes cover. Links they create belong to `user_id`, if set.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ServiceToken {
+    pub id: Uuid,
+    pub name: String,
+    pub user_id: Option<Uuid>,
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ServiceToken {
+    /// Whether the token was granted `scope`, itself or through its family wildcard
+    pub fn allows(&self, scope: &str) -> bool {
+        let family = scope.split_once(':').map(|(family, _)| family);
+        self.scopes.iter().any(|granted| {
+            granted == scope
+                || granted
+                    .strip_suffix(":*")
+                    .is_some_and(|granted| Some(granted) == family)
+        })
+    }
+}
+
+/// A service token presented with a request, checked against the endpoint's scope
+#[derive(Debug, Clone)]
+pub struct ServiceTokenIdentity {
+    pub token: ServiceToken,
+    /// The user the token acts for, if any
+    pub owner: Option<User>,
+}
+
+// DTO for creating a service token
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateServiceTokenDto {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+
+    #[validate(custom(function = "validate_scopes"))]
+    pub scopes: Vec<String>,
+
+    /// The user links created with the token belong to
+    pub user_id: Option<Uuid>,
+
+    #[validate(range(min = 1, max = 3650, message = "Tokens can last 1 to 3650 days"))]
+    pub expires_in_days: Option<i64>,
+}
@@ -1,16 +1,24 @@
 use std::fmt::{Display, Formatter, Result};
+use std::net::IpAddr;
+use std::str::FromStr;
 
 use chrono::{DateTime, Utc};
+use ipnet::IpNet;
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use sqlx::FromRow;
 use uuid::Uuid;
 use validator::Validate;
 
-use crate::validations::{validate_custom_alias, validate_date, validate_url};
+use crate::models::TenantScope;
+use crate::utils::query_parser::SearchQuery;
+use crate::validations::{
+    validate_app_url, validate_bulk_delete, validate_bulk_update, validate_cidr_list,
+    validate_custom_alias, validate_date, validate_namespace, validate_query_params, validate_url,
+};
 
 // DTO for creating a new shortened URL
-#[derive(Debug, Serialize, Deserialize, Validate)]
+#[derive(Debug, Default, Clone, Serialize, Deserialize, Validate)]
 pub struct CreateShortenedUrlDto {
     #[validate(custom(function = "validate_url"))]
     pub original_url: String,
@@ -18,6 +26,16 @@ pub struct CreateShortenedUrlDto {
     #[validate(custom(function = "validate_custom_alias"))]
     pub custom_alias: Option<String>,
 
+    /// How to pick the code when there's no custom alias, `SHORT_CODE_STRATEGY` by default
+    pub code_strategy: Option<CodeStrategy>,
+
+    /// What to do when the custom alias is taken, failing by default
+    pub on_conflict: Option<AliasConflict>,
+
+    /// Serve the link at /{namespace}/{code} instead of /{code}
+    #[validate(custom(function = "validate_namespace"))]
+    pub namespace: Option<String>,
+
     #[validate(custom(function = "validate_date"))]
     pub expires_at: Option<DateTime<Utc>>,
 
@@ -26,6 +44,71 @@ pub struct CreateShortenedUrlDto {
 
     // validate custom metadata
     pub metadata: Option<JsonValue>,
+
+    /// Only clients in these ranges may follow the link
+    #[validate(custom(function = "validate_cidr_list"))]
+    pub allowed_cidrs: Option<Vec<String>>,
+
+    /// Clients in these ranges are refused
+    #[validate(custom(function = "validate_cidr_list"))]
+    pub denied_cidrs: Option<Vec<String>>,
+
+    /// The link stops working after its first redirect
+    pub single_use: Option<bool>,
+
+    /// Append any path and query after the code to the original URL
+    pub forward_path: Option<bool>,
+
+    /// Merge the query parameters of each visit into the original URL
+    pub forward_query: Option<bool>,
+
+    /// Where to send visitors once the link is expired or deactivated
+    #[validate(custom(function = "validate_url"))]
+    pub fallback_url: Option<String>,
+
+    /// Deactivate the link after this many days without access
+    #[validate(range(min = 1, max = 3650, message = "Idle days must be between 1 and 3650"))]
+    pub max_idle_days: Option<i32>,
+
+    /// Rewrite an http:// destination to https:// when its host supports it
+    pub upgrade_https: Option<bool>,
+
+    /// How visitors reach the destination, redirected by default
+    pub behavior: Option<LinkBehavior>,
+
+    /// URI opening the destination in a mobile app, like `myapp://item/42`, tried by
+    /// mobile visitors before the original URL
+    #[validate(custom(function = "validate_app_url"))]
+    pub app_url: Option<String>,
+
+    /// Title shown when the link is shared on social platforms
+    #[validate(length(max = 200, message = "og_title must be at most 200 characters"))]
+    pub og_title: Option<String>,
+
+    /// Description shown when the link is shared on social platforms
+    #[validate(length(max = 500, message = "og_description must be at most 500 characters"))]
+    pub og_description: Option<String>,
+
+    /// Image shown when the link is shared on social platforms
+    #[validate(custom(function = "validate_url"))]
+    pub og_image: Option<String>,
+
+    /// Redirects allowed per minute, for destinations that can't take much traffic
+    #[validate(range(
+        min = 1,
+        max = 1000000,
+        message = "Max redirects per minute must be between 1 and 1000000"
+    ))]
+    pub max_redirects_per_minute: Option<i32>,
+
+    /// Notes about the link for the team managing it, never shown to visitors
+    #[validate(length(max = 5000, message = "notes must be at most 5000 characters"))]
+    pub notes: Option<String>,
+
+    /// Address of the client, to recognise a request it sent twice; set from the request,
+    /// never from the body
+    #[serde(skip)]
+    pub client_ip: Option<IpAddr>,
 }
 
 // update DTO
@@ -34,6 +117,7 @@ pub struct ShortenedUrlUpdateParams {
     #[validate(custom(function = "validate_url"))]
     pub original_url: Option<String>,
 
+    #[serde(default)]
     #[validate(range(min = 0))]
     pub access_count: i64,
 
@@ -46,6 +130,56 @@ pub struct ShortenedUrlUpdateParams {
     pub is_active: Option<bool>,
 
     pub metadata: Option<JsonValue>,
+
+    #[validate(custom(function = "validate_cidr_list"))]
+    pub allowed_cidrs: Option<Vec<String>>,
+
+    #[validate(custom(function = "validate_cidr_list"))]
+    pub denied_cidrs: Option<Vec<String>>,
+
+    pub forward_path: Option<bool>,
+
+    pub forward_query: Option<bool>,
+
+    #[validate(custom(function = "validate_url"))]
+    pub fallback_url: Option<String>,
+
+    #[validate(range(min = 1, max = 3650, message = "Idle days must be between 1 and 3650"))]
+    pub max_idle_days: Option<i32>,
+
+    /// Applied to the stored destination by the HTTPS upgrade job
+    pub upgrade_https: Option<bool>,
+
+    pub behavior: Option<LinkBehavior>,
+
+    #[validate(custom(function = "validate_app_url"))]
+    pub app_url: Option<String>,
+
+    #[validate(length(max = 200, message = "og_title must be at most 200 characters"))]
+    pub og_title: Option<String>,
+
+    #[validate(length(max = 500, message = "og_description must be at most 500 characters"))]
+    pub og_description: Option<String>,
+
+    #[validate(custom(function = "validate_url"))]
+    pub og_image: Option<String>,
+
+    #[validate(range(
+        min = 1,
+        max = 1000000,
+        message = "Max redirects per minute must be between 1 and 1000000"
+    ))]
+    pub max_redirects_per_minute: Option<i32>,
+
+    #[validate(length(max = 5000, message = "notes must be at most 5000 characters"))]
+    pub notes: Option<String>,
+
+    /// Why the destination changes, kept with the revision recording the previous one
+    #[validate(length(max = 500, message = "change_comment must be at most 500 characters"))]
+    pub change_comment: Option<String>,
+
+    /// The version the change is based on, also accepted as an `If-Match` header
+    pub version: Option<i32>,
 }
 
 #[derive(Debug, Clone, Default, Copy, Deserialize, Serialize, PartialEq)]
@@ -94,28 +228,629 @@ impl SortField {
     }
 }
 
+impl FromStr for SortField {
+    type Err = String;
+
+    // Field names are the snake_case names used by the API, which match the columns
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "id" => Ok(SortField::Id),
+            "short_code" => Ok(SortField::ShortCode),
+            "original_url" => Ok(SortField::OriginalUrl),
+            "created_at" => Ok(SortField::CreatedAt),
+            "expires_at" => Ok(SortField::ExpiresAt),
+            "last_accessed" => Ok(SortField::LastAccessed),
+            "access_count" => Ok(SortField::AccessCount),
+            _ => Err(format!(
+                "Unknown sort field '{}', expected one of: id, short_code, original_url, \
+                 created_at, expires_at, last_accessed, access_count",
+                s
+            )),
+        }
+    }
+}
+
+impl FromStr for OrderDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "asc" => Ok(OrderDirection::Asc),
+            "desc" => Ok(OrderDirection::Desc),
+            _ => Err(format!(
+                "Unknown sort direction '{}', expected asc or desc",
+                s
+            )),
+        }
+    }
+}
+
+/// One field to sort by; without a direction of its own, `order_direction` applies
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SortKey {
+    pub field: SortField,
+    pub direction: Option<OrderDirection>,
+}
+
+/// Fields to sort by, most significant first
+///
+/// Given in `order_by` as a comma-separated list of `field` or `field:direction`, e.g.
+/// `access_count:desc,created_at:asc`. Unknown or repeated fields are rejected.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SortOrder(Vec<SortKey>);
+
+impl SortOrder {
+    pub fn keys(&self) -> &[SortKey] {
+        &self.0
+    }
+}
+
+impl Default for SortOrder {
+    fn default() -> Self {
+        SortField::default().into()
+    }
+}
+
+impl From<SortField> for SortOrder {
+    fn from(field: SortField) -> Self {
+        SortOrder(vec![SortKey {
+            field,
+            direction: None,
+        }])
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let mut keys: Vec<SortKey> = Vec::new();
+        for part in s.split(',').map(str::trim) {
+            let (field, direction) = match part.split_once(':') {
+                Some((field, direction)) => (field.trim(), Some(direction.trim().parse()?)),
+                None => (part, None),
+            };
+            let field = field.parse()?;
+            if keys.iter().any(|key| key.field == field) {
+                return Err(format!(
+                    "Sort field '{}' is listed more than once",
+                    field.as_column()
+                ));
+            }
+            keys.push(SortKey { field, direction });
+        }
+        Ok(SortOrder(keys))
+    }
+}
+
+impl TryFrom<String> for SortOrder {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<SortOrder> for String {
+    fn from(order: SortOrder) -> Self {
+        order
+            .0
+            .iter()
+            .map(|key| match key.direction {
+                Some(direction) => format!(
+                    "{}:{}",
+                    key.field.as_column(),
+                    direction.to_string().to_lowercase()
+                ),
+                None => key.field.as_column().to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// A condition on one top-level metadata key, given as `key:value`
+///
+/// Only the first `:` separates, so values may contain colons.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct MetadataFilter {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for MetadataFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once(':')
+            .ok_or_else(|| format!("Metadata filter '{}' must look like key:value", s))?;
+        if key.is_empty() || key.len() > 100 {
+            return Err("Metadata key must be between 1 and 100 characters".to_string());
+        }
+        if value.is_empty() || value.len() > 500 {
+            return Err("Metadata value must be between 1 and 500 characters".to_string());
+        }
+        Ok(MetadataFilter {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+impl TryFrom<String> for MetadataFilter {
+    type Error = String;
+
+    fn try_from(s: String) -> std::result::Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<MetadataFilter> for String {
+    fn from(filter: MetadataFilter) -> Self {
+        format!("{}:{}", filter.key, filter.value)
+    }
+}
+
 // Query parameters struct for the flexible find method
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Debug, Default, Clone, Deserialize, Serialize, Validate)]
+#[validate(schema(function = "validate_query_params", skip_on_field_errors = false))]
 pub struct ShortenedUrlQueryParams {
-    pub id: Option<i64>,
+    pub id: Option<Uuid>,
+
+    /// Lowered to the configured maximum page size when larger
+    #[validate(range(min = 1, message = "Limit must be at least 1"))]
     pub limit: Option<i64>,
+
+    #[validate(range(
+        min = 0,
+        max = 1000000,
+        message = "Offset must be between 0 and 1000000"
+    ))]
     pub offset: Option<i64>,
+
     pub is_expired: Option<bool>,
     pub is_active: Option<bool>,
     pub is_custom_code: Option<bool>,
+    pub status: Option<LinkStatus>,
+
+    /// Only links whose destination failed (true) or passed (false) the last check
+    pub broken: Option<bool>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Short code must be between 1 and 100 characters"
+    ))]
     pub short_code: Option<String>,
-    pub order_by: Option<SortField>,
+
+    #[validate(length(
+        min = 1,
+        max = 50,
+        message = "Namespace must be between 1 and 50 characters"
+    ))]
+    pub namespace: Option<String>,
+
+    /// e.g. `access_count:desc,created_at`, see [`SortOrder`]
+    pub order_by: Option<SortOrder>,
+
+    #[validate(length(
+        min = 1,
+        max = 2048,
+        message = "URL filter must be between 1 and 2048 characters"
+    ))]
     pub original_url: Option<String>,
+
+    #[validate(range(min = 0, message = "Minimum access count cannot be negative"))]
     pub min_access_count: Option<i64>,
+
     pub created_after: Option<DateTime<Utc>>,
     pub created_before: Option<DateTime<Utc>>,
     pub order_direction: Option<OrderDirection>,
+
+    /// Metadata text under a key containing a value, ignoring case, e.g. `title:launch`
+    pub metadata_contains: Option<MetadataFilter>,
+
+    /// Metadata array under a key including a value, e.g. `tags:promo`
+    pub metadata_includes: Option<MetadataFilter>,
+
+    /// Filters written in the search mini-language, e.g. `domain:example.com clicks:>100`,
+    /// see [`SearchQuery`]
+    pub q: Option<SearchQuery>,
+
+    /// Name of a saved search whose filters apply, under the ones given alongside
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Preset must be between 1 and 100 characters"
+    ))]
+    pub preset: Option<String>,
+
+    /// Only links the signed-in user pinned (true) or didn't pin (false)
+    pub favorites: Option<bool>,
+
+    /// Archived links are left out unless true
+    pub include_archived: Option<bool>,
+
+    /// Set from the request's tenant, never from the query string
+    #[serde(skip)]
+    pub tenant: TenantScope,
+
+    /// Set from the request's user, whose favorites `favorites` looks at
+    #[serde(skip)]
+    pub user: Option<Uuid>,
+}
+
+// Query parameters of create and update, to check a change without making it
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct DryRunParams {
+    /// Run every check and return the would-be URL, saving nothing
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+// DTO for copying a link to a new one
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct DuplicateShortenedUrlDto {
+    /// Code of the copy, generated when not given
+    #[validate(custom(function = "validate_custom_alias"))]
+    pub custom_alias: Option<String>,
+
+    /// Expiry of the copy; without one, the original's is kept while it's in the future
+    #[validate(custom(function = "validate_date"))]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    #[validate(range(min = 0, max = 365, message = "Expiry days must be between 0 and 365"))]
+    pub expires_in_days: Option<u32>,
+}
+
+// DTO for claiming a link created without signing in
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct ClaimLinkDto {
+    /// Token the creation of the link was answered with
+    #[validate(length(min = 1, max = 100, message = "Claim token is invalid"))]
+    pub token: String,
+}
+
+// Query parameters for listing the links about to expire
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+pub struct ExpiringQueryParams {
+    /// Days ahead to look, 7 when omitted
+    #[validate(range(min = 1, max = 365, message = "Window must be between 1 and 365 days"))]
+    pub within_days: Option<u32>,
+
+    /// Lowered to the configured maximum page size when larger
+    #[validate(range(min = 1, message = "Limit must be at least 1"))]
+    pub limit: Option<i64>,
+
+    #[validate(range(
+        min = 0,
+        max = 1000000,
+        message = "Offset must be between 0 and 1000000"
+    ))]
+    pub offset: Option<i64>,
+}
+
+// DTO for resolving many short codes in one request
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct ResolveCodesDto {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Between 1 and 100 codes can be resolved at once"
+    ))]
+    pub codes: Vec<String>,
+}
+
+/// URLs a bulk change applies to at most, so one request can't hold a lock on every link
+pub const MAX_BULK_URLS: u64 = 1000;
+
+// DTO for changing many URLs at once, picked by ID or by the filters of the search endpoint
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_bulk_update", skip_on_field_errors = false))]
+pub struct BulkUpdateUrlsDto {
+    #[validate(length(
+        min = 1,
+        max = MAX_BULK_URLS,
+        message = "Between 1 and 1000 URLs can be changed at once"
+    ))]
+    pub ids: Option<Vec<Uuid>>,
+
+    #[validate(nested)]
+    pub filter: Option<ShortenedUrlQueryParams>,
+
+    /// `false` deactivates the URLs, `true` reactivates them
+    pub is_active: Option<bool>,
+
+    /// Ignored when `is_active` is given, which sets the expiry itself
+    #[validate(custom(function = "validate_date"))]
+    pub expires_at: Option<DateTime<Utc>>,
+
+    /// Added to the `tags` metadata array of every URL that doesn't have it yet
+    #[validate(length(min = 1, max = 50, message = "Tag must be between 1 and 50 characters"))]
+    pub add_tag: Option<String>,
+}
+
+// DTO for deleting many URLs at once, picked by ID or by the filters of the search endpoint
+#[derive(Debug, Default, Serialize, Deserialize, Validate)]
+#[validate(schema(function = "validate_bulk_delete", skip_on_field_errors = false))]
+pub struct BulkDeleteUrlsDto {
+    #[validate(length(
+        min = 1,
+        max = MAX_BULK_URLS,
+        message = "Between 1 and 1000 URLs can be deleted at once"
+    ))]
+    pub ids: Option<Vec<Uuid>>,
+
+    #[validate(nested)]
+    pub filter: Option<ShortenedUrlQueryParams>,
+}
+
+/// Outcome of a bulk change for one URL
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BulkItemResult {
+    pub id: Uuid,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BulkItemResult {
+    pub fn done(id: Uuid) -> Self {
+        Self {
+            id,
+            success: true,
+            error: None,
+        }
+    }
+
+    pub fn failed(id: Uuid, error: impl Into<String>) -> Self {
+        Self {
+            id,
+            success: false,
+            error: Some(error.into()),
+        }
+    }
+}
+
+/// Where a short code points, as returned by the batch resolve endpoint
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResolvedCode {
+    pub original_url: String,
+    pub active: bool,
+    pub expired: bool,
+}
+
+impl From<&ShortenedUrl> for ResolvedCode {
+    fn from(url: &ShortenedUrl) -> Self {
+        ResolvedCode {
+            original_url: url.original_url.clone(),
+            active: url.is_active,
+            expired: url.is_expired(),
+        }
+    }
+}
+
+/// Moderation state of a link, deciding how its redirects are served
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LinkStatus {
+    /// Redirects as usual
+    #[default]
+    Active,
+    /// Visitors are warned before being sent on, set while the link has an open flag
+    Flagged,
+    /// Refused with 410 for breaching the acceptable use policy
+    Blocked,
+    /// Not served until an admin has looked at it
+    PendingReview,
+}
+
+impl FromStr for LinkStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "active" => Ok(LinkStatus::Active),
+            "flagged" => Ok(LinkStatus::Flagged),
+            "blocked" => Ok(LinkStatus::Blocked),
+            "pending_review" => Ok(LinkStatus::PendingReview),
+            _ => Err(format!(
+                "Invalid link status: {}. Must be one of: active, flagged, blocked, pending_review",
+                s
+            )),
+        }
+    }
+}
+
+/// How a link takes visitors to its destination
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "text", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum LinkBehavior {
+    /// Answers with a redirect to the destination
+    #[default]
+    Redirect,
+    /// Streams the destination file from the short URL, under its own file name
+    Proxy,
+    /// Shows a page previewing the destination file, with a link to download it
+    Preview,
+    /// Streams whatever the destination answers, which must be on an allowed host, so
+    /// visitors never see its address
+    ReverseProxy,
+}
+
+impl FromStr for LinkBehavior {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "redirect" => Ok(LinkBehavior::Redirect),
+            "proxy" => Ok(LinkBehavior::Proxy),
+            "preview" => Ok(LinkBehavior::Preview),
+            "reverse_proxy" => Ok(LinkBehavior::ReverseProxy),
+            _ => Err(format!(
+                "Invalid link behavior: {}. Must be one of: redirect, proxy, preview, reverse_proxy",
+                s
+            )),
+        }
+    }
+}
+
+/// How codes are picked for links created without a custom alias
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CodeStrategy {
+    /// Drawn at random, so shortening a URL twice gives two links
+    #[default]
+    Random,
+    /// Derived from a hash of the normalized URL and the tenant, so shortening a URL again
+    /// gives back the same link
+    Hash,
+}
+
+impl FromStr for CodeStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "random" => Ok(CodeStrategy::Random),
+            "hash" => Ok(CodeStrategy::Hash),
+            _ => Err(format!(
+                "Invalid short code strategy: {}. Must be one of: random, hash",
+                s
+            )),
+        }
+    }
+}
+
+/// What creating a link does when its custom alias is taken
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AliasConflict {
+    /// Refuse the link
+    #[default]
+    Error,
+    /// Number the alias, `launch-2`, `launch-3` and so on, until one is free
+    Suffix,
+}
+
+// DTO for an admin moving a link to another status
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateLinkStatusDto {
+    pub status: LinkStatus,
+}
+
+/// A shortened URL about to be saved
+///
+/// Holds only what the caller decides. The ID, creation time, counters and moderation
+/// state are set by the database on insert and come back in the saved [`ShortenedUrl`].
+#[derive(Debug, Clone)]
+pub struct NewShortenedUrl {
+    pub original_url: String,
+    pub short_code: String,
+    pub namespace: Option<String>,
+    pub tenant_id: Option<Uuid>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+    pub is_custom_code: bool,
+    pub metadata: Option<JsonValue>,
+    pub allowed_cidrs: Vec<IpNet>,
+    pub denied_cidrs: Vec<IpNet>,
+    pub single_use: bool,
+    pub forward_path: bool,
+    pub forward_query: bool,
+    pub fallback_url: Option<String>,
+    pub max_idle_days: Option<i32>,
+    pub upgrade_https: Option<bool>,
+    pub behavior: LinkBehavior,
+    pub app_url: Option<String>,
+    pub og_title: Option<String>,
+    pub og_description: Option<String>,
+    pub og_image: Option<String>,
+    pub max_redirects_per_minute: Option<i32>,
+    pub notes: Option<String>,
+}
+
+impl NewShortenedUrl {
+    /// Starts a link sending `short_code` to `original_url`, with every option at its default
+    pub fn new(original_url: impl Into<String>, short_code: impl Into<String>) -> Self {
+        Self {
+            original_url: original_url.into(),
+            short_code: short_code.into(),
+            namespace: None,
+            tenant_id: None,
+            expires_at: None,
+            created_by: None,
+            is_custom_code: false,
+            metadata: None,
+            allowed_cidrs: Vec::new(),
+            denied_cidrs: Vec::new(),
+            single_use: false,
+            forward_path: false,
+            forward_query: false,
+            fallback_url: None,
+            max_idle_days: None,
+            upgrade_https: None,
+            behavior: LinkBehavior::default(),
+            app_url: None,
+            og_title: None,
+            og_description: None,
+            og_image: None,
+            max_redirects_per_minute: None,
+            notes: None,
+        }
+    }
+
+    /// Serve the code under a namespace (None for the root)
+    pub fn with_namespace(mut self, namespace: Option<String>) -> Self {
+        self.namespace = namespace;
+        self
+    }
+
+    /// Save the link for a tenant (None for the default tenant)
+    pub fn with_tenant(mut self, tenant_id: Option<Uuid>) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    /// Record the user creating the link (None for anonymous links)
+    pub fn with_creator(mut self, created_by: Option<Uuid>) -> Self {
+        self.created_by = created_by;
+        self
+    }
+
+    /// Mark the short code as chosen by the caller rather than generated
+    pub fn with_custom_code(mut self, is_custom_code: bool) -> Self {
+        self.is_custom_code = is_custom_code;
+        self
+    }
+
+    /// Expire the link at the given time (None to keep it)
+    pub fn with_expiry(mut self, expires_at: Option<DateTime<Utc>>) -> Self {
+        self.expires_at = expires_at;
+        self
+    }
+
+    /// Attach free-form metadata
+    pub fn with_metadata(mut self, metadata: Option<JsonValue>) -> Self {
+        self.metadata = metadata;
+        self
+    }
 }
 
 /// Represents a shortened URL in the system
-#[derive(Debug, Clone, Default, FromRow, Serialize, Deserialize)]
+///
+/// Rows only come from the database; new links are saved from a [`NewShortenedUrl`]. Not
+/// serializable, so internal columns can't end up in a response: handlers answer with a
+/// [`ShortenedUrlResponseDto`].
+#[derive(Debug, Clone, FromRow)]
+#[cfg_attr(test, derive(Default))]
 pub struct ShortenedUrl {
-    /// The unique ID of the shortened URL
+    /// The unique ID of the shortened URL, generated by the database on insert
     pub id: Uuid,
 
     /// The original, long URL that was shortened
@@ -124,7 +859,13 @@ pub struct ShortenedUrl {
     /// The generated short code that identifies this URL
     pub short_code: String,
 
-    /// When this shortened URL was created
+    /// Optional first path segment the code lives under
+    pub namespace: Option<String>,
+
+    /// The tenant the link belongs to (None for the default tenant)
+    pub tenant_id: Option<Uuid>,
+
+    /// When this shortened URL was created, set by the database on insert
     pub created_at: DateTime<Utc>,
 
     /// When this shortened URL was last accessed
@@ -136,8 +877,8 @@ pub struct ShortenedUrl {
     /// When this shortened URL expires (None means it never expires)
     pub expires_at: Option<DateTime<Utc>>,
 
-    // The identifier of the user or entity that created this shortened URL
-    // pub created_by: Option<String>,
+    /// The user that created this shortened URL (None for anonymous links)
+    pub created_by: Option<Uuid>,
 
     /// Indicates whether the short code was custom or auto-generated
     pub is_custom_code: bool,
@@ -147,50 +888,434 @@ pub struct ShortenedUrl {
 
     /// Additional metadata associated with the shortened URL
     pub metadata: Option<JsonValue>,
+
+    /// Redirects are refused until this time after a click anomaly
+    pub throttled_until: Option<DateTime<Utc>>,
+
+    /// Moderation state, changed by the anomaly detector and by admins
+    pub status: LinkStatus,
+
+    /// When not empty, only clients in these ranges may follow the link
+    pub allowed_cidrs: Vec<IpNet>,
+
+    /// Clients in these ranges are refused, even if also allowed
+    pub denied_cidrs: Vec<IpNet>,
+
+    /// Indicates whether the link can be followed only once
+    pub single_use: bool,
+
+    /// When a single-use link was followed (None while unused)
+    pub consumed_at: Option<DateTime<Utc>>,
+
+    /// Indicates whether `/{code}/more/path?x=1` forwards `more/path?x=1` to the original URL
+    pub forward_path: bool,
+
+    /// Indicates whether query parameters of the visit are merged into the original URL
+    pub forward_query: bool,
+
+    /// Where visitors are sent once the link is expired or deactivated (None for the default)
+    pub fallback_url: Option<String>,
+
+    /// The link is deactivated after this many days without access (None to keep it)
+    pub max_idle_days: Option<i32>,
+
+    /// Rewrite an http:// destination to https:// when its host supports it (None for the default)
+    pub upgrade_https: Option<bool>,
+
+    /// When the link rot checker last requested the destination
+    pub last_checked_at: Option<DateTime<Utc>>,
+
+    /// HTTP status of the last check (None if the destination didn't answer or wasn't checked)
+    pub check_status: Option<i32>,
+
+    /// Indicates whether the destination was unreachable, missing or failing at the last check
+    pub is_broken: bool,
+
+    /// How visitors reach the destination
+    pub behavior: LinkBehavior,
+
+    /// URI opening the destination in a mobile app, the original URL being the fallback
+    pub app_url: Option<String>,
+
+    /// Open Graph title, description and image shown to social platforms (None to leave
+    /// the destination's own)
+    pub og_title: Option<String>,
+    pub og_description: Option<String>,
+    pub og_image: Option<String>,
+
+    /// Redirects allowed per minute before visitors are answered 429 (None for no limit)
+    pub max_redirects_per_minute: Option<i32>,
+
+    /// Notes about the link for the team managing it, never shown to visitors
+    pub notes: Option<String>,
+
+    /// When the link was archived, hiding it from listings while it keeps redirecting
+    pub archived_at: Option<DateTime<Utc>>,
+
+    /// Incremented on every update, to detect concurrent edits
+    pub version: i32,
 }
 
 impl ShortenedUrl {
-    /// Checks if the shortened URL has expired
+    /// Checks if the shortened URL has expired, by date or from inactivity
     pub fn is_expired(&self) -> bool {
-        match self.expires_at {
-            Some(expiry) => Utc::now() > expiry,
-            None => false,
+        let now = Utc::now();
+        self.expires_at.is_some_and(|expiry| now > expiry)
+            || self.idle_expires_at().is_some_and(|expiry| now > expiry)
+    }
+
+    /// When the link expires if nobody follows it before then
+    pub fn idle_expires_at(&self) -> Option<DateTime<Utc>> {
+        let days = self.max_idle_days?;
+        let since = self.last_accessed.unwrap_or(self.created_at);
+        Some(since + chrono::Duration::days(days as i64))
+    }
+
+    /// Checks if redirects are currently suspended by the anomaly detector
+    pub fn is_throttled(&self) -> bool {
+        self.throttled_until.is_some_and(|until| Utc::now() < until)
+    }
+
+    /// The path the link is served at, `namespace/code` or just `code`
+    pub fn path(&self) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("{}/{}", namespace, self.short_code),
+            None => self.short_code.clone(),
         }
     }
 
-    /// Convenience method to check if the URL is still valid (not expired)
+    /// Checks if a single-use link has already been followed
+    pub fn is_consumed(&self) -> bool {
+        self.single_use && self.consumed_at.is_some()
+    }
+
+    /// Checks if a client may follow the link under its access rules
+    ///
+    /// Deny rules win over allow rules. When the link has rules but the client
+    /// address is unknown, access is refused.
+    pub fn allows_ip(&self, ip: Option<IpAddr>) -> bool {
+        if self.allowed_cidrs.is_empty() && self.denied_cidrs.is_empty() {
+            return true;
+        }
+
+        let Some(ip) = ip else {
+            return false;
+        };
+
+        if self.denied_cidrs.iter().any(|net| net.contains(&ip)) {
+            return false;
+        }
+
+        self.allowed_cidrs.is_empty() || self.allowed_cidrs.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Checks if the link overrides how it looks when shared on social platforms
+    pub fn has_social_meta(&self) -> bool {
+        self.og_title.is_some() || self.og_description.is_some() || self.og_image.is_some()
+    }
+
+    /// Checks if link preview crawlers get a page with the link's own tags
+    ///
+    /// Single-use links are meant for one visitor, and reverse proxied ones serve the
+    /// destination's page as it is, so neither is previewed.
+    pub fn has_social_preview(&self) -> bool {
+        self.has_social_meta()
+            && self.status == LinkStatus::Active
+            && !self.single_use
+            && self.behavior != LinkBehavior::ReverseProxy
+    }
+
+    /// Convenience method to check if the URL is still valid (active and not expired)
     pub fn is_valid(&self) -> bool {
-        !self.is_expired() || self.is_active
+        !self.is_expired() && self.is_active
     }
 }
 
 // DTO for response with shortened URL details
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ShortenedUrlResponseDto {
     pub id: Option<Uuid>,
     pub is_active: bool,
+    pub status: LinkStatus,
     pub access_count: i64,
     pub short_code: String,
+    pub namespace: Option<String>,
     pub original_url: String,
     pub is_custom_code: bool,
     pub created_at: DateTime<Utc>,
     pub metadata: Option<JsonValue>,
     pub expires_at: Option<DateTime<Utc>>,
+    pub allowed_cidrs: Vec<IpNet>,
+    pub denied_cidrs: Vec<IpNet>,
+    pub single_use: bool,
+    pub consumed_at: Option<DateTime<Utc>>,
+    pub forward_path: bool,
+    pub forward_query: bool,
+    pub fallback_url: Option<String>,
+    pub max_idle_days: Option<i32>,
+    pub idle_expires_at: Option<DateTime<Utc>>,
+    pub upgrade_https: Option<bool>,
+    pub last_checked_at: Option<DateTime<Utc>>,
+    pub check_status: Option<i32>,
+    pub is_broken: bool,
+    pub behavior: LinkBehavior,
+    pub app_url: Option<String>,
+    pub og_title: Option<String>,
+    pub og_description: Option<String>,
+    pub og_image: Option<String>,
+    pub max_redirects_per_minute: Option<i32>,
+    pub notes: Option<String>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub version: i32,
+
+    /// Secret to claim a link created without signing in, only in the answer to its creation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claim_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub claim_expires_at: Option<DateTime<Utc>>,
 }
 
 // Conversion functions between DTO and model
 impl From<ShortenedUrl> for ShortenedUrlResponseDto {
     fn from(url: ShortenedUrl) -> Self {
         ShortenedUrlResponseDto {
+            idle_expires_at: url.idle_expires_at(),
             id: Some(url.id),
             metadata: url.metadata,
             is_active: url.is_active,
+            status: url.status,
             expires_at: url.expires_at,
             short_code: url.short_code,
+            namespace: url.namespace,
             created_at: url.created_at,
             original_url: url.original_url,
             access_count: url.access_count,
             is_custom_code: url.is_custom_code,
+            allowed_cidrs: url.allowed_cidrs,
+            denied_cidrs: url.denied_cidrs,
+            single_use: url.single_use,
+            consumed_at: url.consumed_at,
+            forward_path: url.forward_path,
+            forward_query: url.forward_query,
+            fallback_url: url.fallback_url,
+            max_idle_days: url.max_idle_days,
+            upgrade_https: url.upgrade_https,
+            last_checked_at: url.last_checked_at,
+            check_status: url.check_status,
+            is_broken: url.is_broken,
+            behavior: url.behavior,
+            app_url: url.app_url,
+            og_title: url.og_title,
+            og_description: url.og_description,
+            og_image: url.og_image,
+            max_redirects_per_minute: url.max_redirects_per_minute,
+            notes: url.notes,
+            archived_at: url.archived_at,
+            version: url.version,
+            claim_token: None,
+            claim_expires_at: None,
         }
     }
 }
+
+/// Whether a link currently leads anywhere, in the order redirects check it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkState {
+    /// Redirects as usual
+    Active,
+    /// Redirects once visitors have been warned
+    Flagged,
+    /// Refused for breaching the acceptable use policy
+    Blocked,
+    /// Refused until an admin has looked at it
+    PendingReview,
+    /// Past its expiry date or idle limit
+    Expired,
+    /// Turned off by its owner or an admin
+    Deactivated,
+    /// Suspended for a while after a click anomaly
+    Throttled,
+    /// A single-use link that was already followed
+    Consumed,
+}
+
+/// What the last destination check found
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Reachability {
+    /// The destination wasn't checked yet
+    Unchecked,
+    Reachable,
+    /// Unreachable, missing or failing at the last check
+    Broken,
+}
+
+/// State of a link derived from its fields, so clients don't have to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkHealth {
+    pub state: LinkState,
+    pub is_expired: bool,
+    /// Whole days left before the link expires by date or from inactivity, 0 once expired
+    /// (None if it never expires)
+    pub days_until_expiry: Option<i64>,
+    pub reachability: Reachability,
+    pub last_checked_at: Option<DateTime<Utc>>,
+}
+
+// DTO for the response to a single URL, with its health
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShortenedUrlDetailsDto {
+    #[serde(flatten)]
+    pub url: ShortenedUrlResponseDto,
+    pub health: LinkHealth,
+}
+
+/// A deleted link waiting in the trash to be restored or purged
+#[derive(Debug, Clone, FromRow)]
+pub struct TrashedUrl {
+    #[sqlx(flatten)]
+    pub url: ShortenedUrl,
+    pub deleted_at: DateTime<Utc>,
+}
+
+// DTO for the response to a link in the trash
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashedUrlDto {
+    #[serde(flatten)]
+    pub url: ShortenedUrlResponseDto,
+    pub deleted_at: DateTime<Utc>,
+    /// When the purge job deletes the link for good
+    pub purge_at: DateTime<Utc>,
+}
+
+impl TrashedUrlDto {
+    pub fn new(trashed: TrashedUrl, retention_days: u32) -> Self {
+        Self {
+            purge_at: trashed.deleted_at + chrono::Duration::days(retention_days.into()),
+            deleted_at: trashed.deleted_at,
+            url: trashed.url.into(),
+        }
+    }
+}
+
+/// Links in the trash, as reported by the admin stats
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashStats {
+    pub trashed: i64,
+    /// Trashed longer than the retention period, deleted on the next purge
+    pub due_for_purge: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_is_valid_needs_active_and_unexpired() {
+        let live = ShortenedUrl {
+            is_active: true,
+            expires_at: Some(Utc::now() + Duration::days(1)),
+            ..Default::default()
+        };
+        assert!(live.is_valid());
+
+        let expired = ShortenedUrl {
+            expires_at: Some(Utc::now() - Duration::days(1)),
+            ..live.clone()
+        };
+        assert!(!expired.is_valid());
+
+        let deactivated = ShortenedUrl {
+            is_active: false,
+            ..live
+        };
+        assert!(!deactivated.is_valid());
+    }
+
+    fn link(allowed: &[&str], denied: &[&str]) -> ShortenedUrl {
+        ShortenedUrl {
+            allowed_cidrs: allowed.iter().map(|c| c.parse().unwrap()).collect(),
+            denied_cidrs: denied.iter().map(|c| c.parse().unwrap()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_new_link_builder() {
+        let expiry = Utc::now() + chrono::Duration::days(7);
+        let url = NewShortenedUrl::new("https://example.com", "docs")
+            .with_namespace(Some("team".to_string()))
+            .with_custom_code(true)
+            .with_expiry(Some(expiry));
+
+        assert_eq!(url.short_code, "docs");
+        assert_eq!(url.namespace.as_deref(), Some("team"));
+        assert!(url.is_custom_code);
+        assert_eq!(url.expires_at, Some(expiry));
+        assert_eq!(url.created_by, None);
+        assert!(url.allowed_cidrs.is_empty() && !url.single_use);
+    }
+
+    #[test]
+    fn test_idle_expiry() {
+        let now = Utc::now();
+        let mut url = ShortenedUrl {
+            created_at: now - chrono::Duration::days(40),
+            ..Default::default()
+        };
+        assert_eq!(url.idle_expires_at(), None);
+        assert!(!url.is_expired());
+
+        url.max_idle_days = Some(30);
+        assert_eq!(
+            url.idle_expires_at(),
+            Some(url.created_at + chrono::Duration::days(30))
+        );
+        assert!(url.is_expired());
+
+        url.last_accessed = Some(now - chrono::Duration::days(2));
+        assert!(!url.is_expired());
+    }
+
+    #[test]
+    fn test_allows_ip() {
+        let ip = |s: &str| Some(s.parse::<IpAddr>().unwrap());
+
+        let open = link(&[], &[]);
+        assert!(open.allows_ip(ip("203.0.113.9")));
+        assert!(open.allows_ip(None));
+
+        let internal = link(&["10.0.0.0/8"], &["10.66.0.0/16"]);
+        assert!(internal.allows_ip(ip("10.1.2.3")));
+        assert!(!internal.allows_ip(ip("10.66.0.1")));
+        assert!(!internal.allows_ip(ip("203.0.113.9")));
+        assert!(!internal.allows_ip(None));
+
+        let blocklist = link(&[], &["2001:db8::/32"]);
+        assert!(blocklist.allows_ip(ip("2001:db9::1")));
+        assert!(!blocklist.allows_ip(ip("2001:db8::1")));
+    }
+
+    #[test]
+    fn test_single_use_links_get_no_social_preview() {
+        let shared = ShortenedUrl {
+            og_title: Some("Launch day".to_string()),
+            ..Default::default()
+        };
+        assert!(shared.has_social_preview());
+
+        let single_use = ShortenedUrl {
+            single_use: true,
+            ..shared.clone()
+        };
+        assert!(!single_use.has_social_preview());
+        let proxied = ShortenedUrl {
+            behavior: LinkBehavior::ReverseProxy,
+            ..shared
+        };
+        assert!(!proxied.has_social_preview());
+        assert!(!ShortenedUrl::default().has_social_preview());
+    }
+}
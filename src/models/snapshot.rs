@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+/// Version of the snapshot layout, raised when its fields change
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+/// Links and settings of an instance, read in one transaction
+///
+/// Rows are kept as stored, every column included, so a snapshot can only be restored on an
+/// instance at the same `schema_version`. Users, API keys and visits aren't part of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub format_version: u32,
+
+    /// Latest migration applied to the database the snapshot was taken from
+    pub schema_version: i64,
+
+    pub taken_at: DateTime<Utc>,
+
+    pub tenants: Vec<JsonValue>,
+    pub branding: Vec<JsonValue>,
+    pub app_links: Vec<JsonValue>,
+    pub saved_searches: Vec<JsonValue>,
+
+    /// Manual blocks only, automatic ones expire on their own
+    pub blocked_ips: Vec<JsonValue>,
+
+    /// Links, deleted ones in the trash included
+    pub links: Vec<JsonValue>,
+}
+
+/// What restoring a snapshot did, or would do on a dry run
+#[derive(Debug, Serialize)]
+pub struct RestoreReport {
+    pub dry_run: bool,
+    pub tables: Vec<TableRestore>,
+
+    /// Links whose owner isn't a user of this instance, restored without one
+    pub unknown_owners: u64,
+
+    /// Tenants and links clashing with different ones already here, which prevent the restore
+    pub conflicts: Vec<String>,
+}
+
+/// Rows of one table in a snapshot, and how many were restored
+#[derive(Debug, Serialize)]
+pub struct TableRestore {
+    pub table: &'static str,
+    pub rows: u64,
+    pub inserted: u64,
+    /// Already here, by id or unique name, and kept as they are
+    pub skipped: u64,
+}
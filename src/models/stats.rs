@@ -0,0 +1,129 @@
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    utils::report::{Cell, ReportRow},
+    validations::validate_timezone,
+};
+
+/// What links are grouped by in aggregate stats
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsDimension {
+    /// Day the link was created, in the requested time zone (UTC by default), newest first
+    Day,
+    /// Host of the original URL
+    Domain,
+    /// Each entry of the `tags` metadata array; a link with several tags counts in each
+    Tag,
+    IsCustomCode,
+}
+
+/// How stats are returned: as JSON, or as a report file to download
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    /// Streamed, every row unless a limit is given
+    Csv,
+    /// Every row unless a limit is given, up to the configured maximum
+    Xlsx,
+}
+
+// Query parameters for grouped stats
+#[derive(Debug, Deserialize, Serialize, Validate)]
+pub struct GroupByQueryParams {
+    pub dimension: StatsDimension,
+
+    /// IANA time zone whose midnight starts each day, UTC unless given
+    #[validate(custom(function = "validate_timezone"))]
+    pub tz: Option<String>,
+
+    /// Lowered to the configured maximum page size when larger, for JSON
+    #[validate(range(min = 1, message = "Limit must be at least 1"))]
+    pub limit: Option<i64>,
+
+    pub format: Option<ReportFormat>,
+}
+
+/// Link count and clicks of one group
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct GroupStats {
+    /// The group's value, `None` for links without one (e.g. untagged links)
+    pub group: Option<String>,
+    pub links: i64,
+    pub clicks: i64,
+}
+
+impl ReportRow for GroupStats {
+    const COLUMNS: &'static [&'static str] = &["group", "links", "clicks"];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            self.group.clone().map_or(Cell::Empty, Cell::Text),
+            Cell::Number(self.links),
+            Cell::Number(self.clicks),
+        ]
+    }
+}
+
+/// How far back the leaderboard counts clicks
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq)]
+pub enum StatsWindow {
+    #[default]
+    #[serde(rename = "24h")]
+    Day,
+    #[serde(rename = "7d")]
+    Week,
+    #[serde(rename = "30d")]
+    Month,
+}
+
+impl StatsWindow {
+    pub fn duration(&self) -> Duration {
+        match self {
+            StatsWindow::Day => Duration::hours(24),
+            StatsWindow::Week => Duration::days(7),
+            StatsWindow::Month => Duration::days(30),
+        }
+    }
+}
+
+// Query parameters for the leaderboard
+#[derive(Debug, Default, Deserialize, Serialize, Validate)]
+pub struct TopLinksQueryParams {
+    pub window: Option<StatsWindow>,
+
+    /// Lowered to the configured maximum page size when larger, for JSON
+    #[validate(range(min = 1, message = "Limit must be at least 1"))]
+    pub limit: Option<i64>,
+
+    pub format: Option<ReportFormat>,
+}
+
+/// A link and its clicks within the leaderboard window
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct TopLink {
+    pub url_id: Uuid,
+    /// `namespace/code` for namespaced links
+    pub short_code: String,
+    pub original_url: String,
+    pub clicks: i64,
+}
+
+impl ReportRow for TopLink {
+    const COLUMNS: &'static [&'static str] = &["url_id", "short_code", "original_url", "clicks"];
+
+    fn cells(&self) -> Vec<Cell> {
+        vec![
+            Cell::Text(self.url_id.to_string()),
+            Cell::Text(self.short_code.clone()),
+            Cell::Text(self.original_url.clone()),
+            Cell::Number(self.clicks),
+        ]
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::validations::{validate_domain, validate_tenant_slug, validate_url};
+
+/// A brand served by this instance, seeing only its own links
+///
+/// Requests pick their tenant with the tenant header or by the domain they were sent
+/// to. Requests matching no tenant belong to the default tenant, which owns the links
+/// whose `tenant_id` is `None`.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Tenant {
+    pub id: Uuid,
+
+    /// Name used in the tenant header
+    pub slug: String,
+
+    pub name: String,
+
+    /// Requests for this host belong to the tenant
+    pub domain: Option<String>,
+
+    /// Public base URL of the tenant's short links, instead of `PUBLIC_BASE_URL`
+    pub base_url: Option<String>,
+
+    /// Where visitors of expired links go, instead of `FALLBACK_URL`
+    pub fallback_url: Option<String>,
+
+    pub created_at: DateTime<Utc>,
+}
+
+// DTO for adding a tenant
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateTenantDto {
+    #[validate(custom(function = "validate_tenant_slug"))]
+    pub slug: String,
+
+    #[validate(length(
+        min = 1,
+        max = 255,
+        message = "Name must be between 1 and 255 characters"
+    ))]
+    pub name: String,
+
+    #[validate(custom(function = "validate_domain"))]
+    pub domain: Option<String>,
+
+    #[validate(custom(function = "validate_url"))]
+    pub base_url: Option<String>,
+
+    #[validate(custom(function = "validate_url"))]
+    pub fallback_url: Option<String>,
+}
+
+/// Which tenants' links a query may see
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum TenantScope {
+    /// Links of every tenant, for instance-wide work like backups
+    #[default]
+    All,
+    /// Links of one tenant, `None` being the default tenant
+    Only(Option<Uuid>),
+}
@@ -0,0 +1,25 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// A previous destination of a shortened URL, recorded when it was changed
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct UrlRevision {
+    pub id: Uuid,
+    pub url_id: Uuid,
+
+    /// Sequence number of the change, starting at 1 for each URL
+    pub revision: i32,
+
+    /// The destination before the change
+    pub original_url: String,
+
+    /// The user who made the change (None if anonymous)
+    pub changed_by: Option<Uuid>,
+
+    pub changed_at: DateTime<Utc>,
+
+    /// Why the destination was changed, when the change said
+    pub change_comment: Option<String>,
+}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use sqlx::FromRow;
+use uuid::Uuid;
+use validator::Validate;
+
+// DTO for creating a new user
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct CreateUserDto {
+    #[validate(email(message = "Email must be a valid address"))]
+    pub email: String,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Display name must be between 1 and 100 characters"
+    ))]
+    pub display_name: Option<String>,
+
+    pub settings: Option<JsonValue>,
+}
+
+/// Represents an account that owns shortened URLs
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct User {
+    /// The unique ID of the user
+    pub id: Uuid,
+
+    /// Contact email, unique per user
+    pub email: String,
+
+    /// Optional name shown in dashboards
+    pub display_name: Option<String>,
+
+    /// User preferences
+    pub settings: JsonValue,
+
+    /// When this user was created
+    pub created_at: DateTime<Utc>,
+}
+
+/// Metadata of an API key; the secret itself is only ever returned once, on creation
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    pub created_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::{json, Value as JsonValue};
+
+use super::NotificationEvent;
+use crate::{errors::AppError, types::Result, utils::http_client::HttpClient};
+
+// Chat webhooks answer quickly; don't let a dead endpoint hold up a job
+const NOTIFY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delivers events to one kind of team channel
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    /// Post an event to the channel's webhook URL
+    async fn notify(&self, target_url: &str, event: &NotificationEvent) -> Result<()>;
+}
+
+/// Slack incoming webhooks
+pub struct SlackNotifier {
+    client: Arc<HttpClient>,
+}
+
+impl SlackNotifier {
+    pub fn new(client: Arc<HttpClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Notifier for SlackNotifier {
+    async fn notify(&self, target_url: &str, event: &NotificationEvent) -> Result<()> {
+        post_json(
+            &self.client,
+            target_url,
+            &json!({ "text": event.summary() }),
+        )
+        .await
+    }
+}
+
+/// Discord channel webhooks
+pub struct DiscordNotifier {
+    client: Arc<HttpClient>,
+}
+
+impl DiscordNotifier {
+    pub fn new(client: Arc<HttpClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Notifier for DiscordNotifier {
+    async fn notify(&self, target_url: &str, event: &NotificationEvent) -> Result<()> {
+        post_json(
+            &self.client,
+            target_url,
+            &json!({ "content": event.summary() }),
+        )
+        .await
+    }
+}
+
+/// Generic JSON webhooks receiving the structured event
+pub struct WebhookNotifier {
+    client: Arc<HttpClient>,
+}
+
+impl WebhookNotifier {
+    pub fn new(client: Arc<HttpClient>) -> Self {
+        Self { client }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, target_url: &str, event: &NotificationEvent) -> Result<()> {
+        let body = json!({
+            "sent_at": Utc::now(),
+            "summary": event.summary(),
+            "data": event,
+        });
+        post_json(&self.client, target_url, &body).await
+    }
+}
+
+async fn post_json(client: &HttpClient, url: &str, body: &JsonValue) -> Result<()> {
+    let response = client
+        .send(client.post(url).timeout(NOTIFY_TIMEOUT).json(body))
+        .await
+        .map_err(|e| AppError::Internal(format!("Notification request failed: {}", e)))?;
+
+    if !response.status().is_success() {
+        return Err(AppError::Internal(format!(
+            "Notification endpoint responded with {}",
+            response.status()
+        )));
+    }
+
+    Ok(())
+}
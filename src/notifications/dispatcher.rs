@@ -0,0 +1,81 @@
+use std::sync::Arc;
+
+use log::warn;
+use uuid::Uuid;
+
+use super::{DiscordNotifier, NotificationEvent, Notifier, SlackNotifier, WebhookNotifier};
+use crate::{
+    models::{ChannelKind, NotificationChannel},
+    repositories::NotificationRepositoryTrait,
+    types::Result,
+    utils::http_client::HttpClient,
+};
+
+/// Fans events out to the channels their owner subscribed
+pub struct ChannelDispatcher<R: NotificationRepositoryTrait> {
+    repository: Arc<R>,
+    slack: SlackNotifier,
+    discord: DiscordNotifier,
+    webhook: WebhookNotifier,
+}
+
+impl<R: NotificationRepositoryTrait + Send + Sync> ChannelDispatcher<R> {
+    pub fn new(repository: Arc<R>, client: Arc<HttpClient>) -> Self {
+        Self {
+            repository,
+            slack: SlackNotifier::new(client.clone()),
+            discord: DiscordNotifier::new(client.clone()),
+            webhook: WebhookNotifier::new(client),
+        }
+    }
+
+    fn notifier(&self, kind: ChannelKind) -> &dyn Notifier {
+        match kind {
+            ChannelKind::Slack => &self.slack,
+            ChannelKind::Discord => &self.discord,
+            ChannelKind::Webhook => &self.webhook,
+        }
+    }
+
+    /// Deliver an event to a single channel
+    pub async fn send(
+        &self,
+        channel: &NotificationChannel,
+        event: &NotificationEvent,
+    ) -> Result<()> {
+        self.send_to(channel.kind, &channel.target_url, event).await
+    }
+
+    /// Deliver an event to a channel that isn't stored, such as the configured admin channel
+    pub async fn send_to(
+        &self,
+        kind: ChannelKind,
+        target_url: &str,
+        event: &NotificationEvent,
+    ) -> Result<()> {
+        self.notifier(kind).notify(target_url, event).await
+    }
+
+    /// Deliver an event to every channel of a user subscribed to it
+    ///
+    /// Failing channels are logged and skipped; returns how many channels received the event.
+    pub async fn dispatch(&self, user_id: &Uuid, event: &NotificationEvent) -> Result<usize> {
+        let channels = self
+            .repository
+            .find_channels_for_event(user_id, event.kind())
+            .await?;
+
+        let mut delivered = 0;
+        for channel in &channels {
+            match self.send(channel, event).await {
+                Ok(()) => delivered += 1,
+                Err(e) => warn!(
+                    "Failed to notify {:?} channel {}: {}",
+                    channel.kind, channel.id, e
+                ),
+            }
+        }
+
+        Ok(delivered)
+    }
+}
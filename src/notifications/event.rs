@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use super::digest_trend;
+use crate::models::{FlagAction, LinkDigest, NotificationEventKind};
+
+/// Something a link owner may want to hear about, delivered to their channels
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum NotificationEvent {
+    LinkExpiring {
+        short_code: String,
+        short_url: String,
+        original_url: String,
+        expires_at: DateTime<Utc>,
+    },
+    /// A link reached its expiry date
+    LinkExpired {
+        short_code: String,
+        short_url: String,
+        original_url: String,
+        expired_at: DateTime<Utc>,
+    },
+    /// Abnormal traffic was detected on a link
+    LinkFlagged {
+        short_code: String,
+        short_url: String,
+        reason: String,
+        action: FlagAction,
+    },
+    /// A link's click count reached a configured milestone
+    LinkMilestone {
+        short_code: String,
+        short_url: String,
+        original_url: String,
+        clicks: i64,
+        reached_at: DateTime<Utc>,
+    },
+    /// A link's destination stopped answering, or answers with an error
+    LinkBroken {
+        short_code: String,
+        short_url: String,
+        original_url: String,
+        /// HTTP status of the answer, none if the destination didn't answer
+        status: Option<i32>,
+    },
+    /// A periodic summary of the clicks on a user's links
+    LinkDigest(LinkDigest),
+    /// Sent on request to check a channel is wired up correctly
+    Test,
+}
+
+impl NotificationEvent {
+    pub fn kind(&self) -> NotificationEventKind {
+        match self {
+            NotificationEvent::LinkExpiring { .. } => NotificationEventKind::LinkExpiring,
+            NotificationEvent::LinkExpired { .. } => NotificationEventKind::LinkExpired,
+            NotificationEvent::LinkFlagged { .. } => NotificationEventKind::LinkFlagged,
+            NotificationEvent::LinkMilestone { .. } => NotificationEventKind::LinkMilestone,
+            NotificationEvent::LinkBroken { .. } => NotificationEventKind::LinkBroken,
+            NotificationEvent::LinkDigest(_) => NotificationEventKind::LinkDigest,
+            NotificationEvent::Test => NotificationEventKind::Test,
+        }
+    }
+
+    /// One line description used by chat channels
+    pub fn summary(&self) -> String {
+        match self {
+            NotificationEvent::LinkExpiring {
+                short_url,
+                original_url,
+                expires_at,
+                ..
+            } => format!(
+                "Short link {} (to {}) expires at {}",
+                short_url,
+                original_url,
+                expires_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+            NotificationEvent::LinkExpired {
+                short_url,
+                original_url,
+                expired_at,
+                ..
+            } => format!(
+                "Short link {} (to {}) expired at {}",
+                short_url,
+                original_url,
+                expired_at.format("%Y-%m-%d %H:%M UTC")
+            ),
+            NotificationEvent::LinkFlagged {
+                short_url,
+                reason,
+                action,
+                ..
+            } => match action {
+                FlagAction::Notify => format!("Unusual traffic on {}: {}", short_url, reason),
+                FlagAction::Throttle => format!(
+                    "Unusual traffic on {}: {}. Redirects are suspended for now",
+                    short_url, reason
+                ),
+            },
+            NotificationEvent::LinkMilestone {
+                short_url, clicks, ..
+            } => format!("Short link {} reached {} clicks", short_url, clicks),
+            NotificationEvent::LinkBroken {
+                short_url,
+                original_url,
+                status,
+                ..
+            } => match status {
+                Some(status) => format!(
+                    "Short link {} is broken: {} answered with {}",
+                    short_url, original_url, status
+                ),
+                None => format!(
+                    "Short link {} is broken: {} can't be reached",
+                    short_url, original_url
+                ),
+            },
+            NotificationEvent::LinkDigest(digest) => {
+                let mut summary = format!(
+                    "Your links got {} clicks from {} to {}, {}",
+                    digest.clicks,
+                    digest.period_start,
+                    digest.period_end,
+                    digest_trend(digest)
+                );
+                if let Some(top) = digest.top_links.first() {
+                    summary.push_str(&format!(
+                        ". Most clicked: {} ({} clicks)",
+                        top.short_url, top.clicks
+                    ));
+                }
+                summary
+            }
+            NotificationEvent::Test => "Test notification from the URL shortener".to_string(),
+        }
+    }
+}
@@ -0,0 +1,11 @@
+mod channels;
+mod dispatcher;
+mod email;
+mod event;
+mod templates;
+
+pub use channels::{DiscordNotifier, Notifier, SlackNotifier, WebhookNotifier};
+pub use dispatcher::ChannelDispatcher;
+pub use email::{EmailMessage, SmtpMailer};
+pub use event::NotificationEvent;
+pub use templates::{digest_email, digest_trend, link_expiring_email, short_url};
@@ -0,0 +1,236 @@
+use chrono::{DateTime, Utc};
+
+use super::EmailMessage;
+use crate::{
+    models::{DigestFrequency, DigestRecipient, ExpiringLink, LinkDigest},
+    utils::template::render,
+};
+
+const LINK_EXPIRING_SUBJECT: &str = "Your short link /{{short_code}} expires {{expires_in}}";
+
+const LINK_EXPIRING_TEXT: &str = "Hi {{name}},
+
+Your short link {{short_url}} expires {{expires_in}} ({{expires_at}}).
+It currently redirects to {{original_url}}.
+
+After that, visitors will no longer be redirected. Extend the expiry if you still need it.
+
+You can turn these emails off in your notification preferences.
+";
+
+const LINK_EXPIRING_HTML: &str = "<p>Hi {{name}},</p>
+<p>Your short link <a href=\"{{short_url}}\">{{short_url}}</a> expires {{expires_in}} ({{expires_at}}).<br>
+It currently redirects to {{original_url}}.</p>
+<p>After that, visitors will no longer be redirected. Extend the expiry if you still need it.</p>
+<p><small>You can turn these emails off in your notification preferences.</small></p>
+";
+
+/// Render the warning sent to an owner whose link is about to expire
+pub fn link_expiring_email(
+    link: &ExpiringLink,
+    base_url: &str,
+    now: DateTime<Utc>,
+) -> EmailMessage {
+    let short_url = short_url(base_url, &link.short_code);
+    let expires_at = link.expires_at.format("%Y-%m-%d %H:%M UTC").to_string();
+    let expires_in = match (link.expires_at - now).num_days() {
+        0 => "within 24 hours".to_string(),
+        1 => "in 1 day".to_string(),
+        days => format!("in {} days", days),
+    };
+    let name = link
+        .owner_name
+        .clone()
+        .unwrap_or_else(|| link.owner_email.clone());
+
+    let vars = [
+        ("name", name.as_str()),
+        ("short_code", link.short_code.as_str()),
+        ("short_url", short_url.as_str()),
+        ("original_url", link.original_url.as_str()),
+        ("expires_at", expires_at.as_str()),
+        ("expires_in", expires_in.as_str()),
+    ];
+
+    EmailMessage {
+        to: link.owner_email.clone(),
+        subject: render(LINK_EXPIRING_SUBJECT, &vars, false),
+        text: render(LINK_EXPIRING_TEXT, &vars, false),
+        html: render(LINK_EXPIRING_HTML, &vars, true),
+    }
+}
+
+const DIGEST_SUBJECT: &str = "Your {{frequency}} link digest: {{clicks}} clicks";
+
+const DIGEST_TEXT: &str = "Hi {{name}},
+
+Your short links got {{clicks}} clicks from {{period_start}} to {{period_end}}, {{trend}}.
+
+Most clicked links:
+{{top_links}}
+You can unsubscribe from these digests in your notification preferences.
+";
+
+const DIGEST_HTML: &str = "<p>Hi {{name}},</p>
+<p>Your short links got <strong>{{clicks}} clicks</strong> from {{period_start}} to {{period_end}}, {{trend}}.</p>
+<p>Most clicked links:</p>
+<ol>
+{{top_links}}</ol>
+<p><small>You can unsubscribe from these digests in your notification preferences.</small></p>
+";
+
+const DIGEST_LINK_TEXT: &str = "- {{short_url}}: {{clicks}} clicks, to {{original_url}}\n";
+
+const DIGEST_LINK_HTML: &str =
+    "<li><a href=\"{{short_url}}\">{{short_url}}</a>: {{clicks}} clicks<br><small>{{original_url}}</small></li>\n";
+
+/// Render the digest of an owner's clicks over a period
+pub fn digest_email(recipient: &DigestRecipient, digest: &LinkDigest) -> EmailMessage {
+    let name = recipient
+        .name
+        .clone()
+        .unwrap_or_else(|| recipient.email.clone());
+    let clicks = digest.clicks.to_string();
+    let period_start = digest.period_start.to_string();
+    let period_end = digest.period_end.to_string();
+    let trend = digest_trend(digest);
+
+    let vars = [
+        ("name", name.as_str()),
+        ("frequency", digest.frequency.as_str()),
+        ("clicks", clicks.as_str()),
+        ("period_start", period_start.as_str()),
+        ("period_end", period_end.as_str()),
+        ("trend", trend.as_str()),
+    ];
+
+    // Rows are rendered, and escaped, one at a time, then put in place of the list
+    let rows = |template: &str, escape: bool| {
+        digest
+            .top_links
+            .iter()
+            .map(|link| {
+                let clicks = link.clicks.to_string();
+                let vars = [
+                    ("short_url", link.short_url.as_str()),
+                    ("original_url", link.original_url.as_str()),
+                    ("clicks", clicks.as_str()),
+                ];
+                render(template, &vars, escape)
+            })
+            .collect::<String>()
+    };
+
+    EmailMessage {
+        to: recipient.email.clone(),
+        subject: render(DIGEST_SUBJECT, &vars, false),
+        text: render(DIGEST_TEXT, &vars, false)
+            .replace("{{top_links}}", &rows(DIGEST_LINK_TEXT, false)),
+        html: render(DIGEST_HTML, &vars, true)
+            .replace("{{top_links}}", &rows(DIGEST_LINK_HTML, true)),
+    }
+}
+
+/// How a digest's clicks compare with the period before, e.g. "up 25% on the previous week"
+pub fn digest_trend(digest: &LinkDigest) -> String {
+    let period = match digest.frequency {
+        DigestFrequency::Weekly => "week",
+        DigestFrequency::Monthly => "month",
+    };
+    let (clicks, previous) = (digest.clicks, digest.previous_clicks);
+
+    if previous == 0 {
+        return format!("none the previous {}", period);
+    }
+    if clicks == previous {
+        return format!("the same as the previous {}", period);
+    }
+    let change = ((clicks - previous) as f64 / previous as f64 * 100.0)
+        .round()
+        .abs();
+    let direction = if clicks > previous { "up" } else { "down" };
+    format!("{} {}% on the previous {}", direction, change, period)
+}
+
+/// Public URL of a short code
+pub fn short_url(base_url: &str, short_code: &str) -> String {
+    format!("{}/{}", base_url.trim_end_matches('/'), short_code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+    use uuid::Uuid;
+
+    use crate::models::DigestLink;
+
+    #[test]
+    fn test_link_expiring_email() {
+        let now = Utc::now();
+        let link = ExpiringLink {
+            url_id: Uuid::new_v4(),
+            owner_id: Uuid::new_v4(),
+            short_code: "abc123".to_string(),
+            original_url: "https://example.com/?a=1&b=<2>".to_string(),
+            expires_at: now + Duration::hours(30),
+            owner_email: "owner@example.com".to_string(),
+            owner_name: None,
+            base_url: None,
+            email_enabled: true,
+        };
+
+        let email = link_expiring_email(&link, "https://sho.rt/", now);
+        assert_eq!(email.to, "owner@example.com");
+        assert_eq!(email.subject, "Your short link /abc123 expires in 1 day");
+        assert!(email
+            .text
+            .contains("https://sho.rt/abc123 expires in 1 day"));
+        assert!(email.text.contains("https://example.com/?a=1&b=<2>"));
+        assert!(email
+            .html
+            .contains("https://example.com/?a=1&amp;b=&lt;2&gt;"));
+        assert!(!email.html.contains("{{"));
+    }
+
+    #[test]
+    fn test_digest_email() {
+        let recipient = DigestRecipient {
+            user_id: Uuid::new_v4(),
+            email: "owner@example.com".to_string(),
+            name: Some("Ada".to_string()),
+        };
+        let mut digest = LinkDigest {
+            frequency: DigestFrequency::Weekly,
+            period_start: NaiveDate::from_ymd_opt(2026, 1, 5).unwrap(),
+            period_end: NaiveDate::from_ymd_opt(2026, 1, 11).unwrap(),
+            clicks: 150,
+            previous_clicks: 120,
+            top_links: vec![DigestLink {
+                short_code: "abc123".to_string(),
+                short_url: "https://sho.rt/abc123".to_string(),
+                original_url: "https://example.com/?a=1&b=<2>".to_string(),
+                clicks: 100,
+            }],
+        };
+
+        let email = digest_email(&recipient, &digest);
+        assert_eq!(email.subject, "Your weekly link digest: 150 clicks");
+        assert!(email
+            .text
+            .contains("150 clicks from 2026-01-05 to 2026-01-11, up 25% on the previous week"));
+        assert!(email
+            .text
+            .contains("- https://sho.rt/abc123: 100 clicks, to https://example.com/?a=1&b=<2>"));
+        assert!(email
+            .html
+            .contains("https://example.com/?a=1&amp;b=&lt;2&gt;"));
+        assert!(!email.html.contains("{{"));
+
+        digest.previous_clicks = 300;
+        assert_eq!(digest_trend(&digest), "down 50% on the previous week");
+        digest.frequency = DigestFrequency::Monthly;
+        digest.previous_clicks = 0;
+        assert_eq!(digest_trend(&digest), "none the previous month");
+    }
+}
@@ -0,0 +1,695 @@
+// src/pages.rs - HTML pages shown to visitors following short links, and the home page
+use actix_web::http::StatusCode;
+
+use crate::{
+    config::{CaptchaConfig, CaptchaProvider},
+    i18n::Locale,
+    models::{Branding, ShortenedUrl},
+    utils::{
+        file_proxy::FileInfo,
+        template::{html_escape, render},
+    },
+};
+
+const DEFAULT_PRIMARY_COLOR: &str = "#1a73e8";
+const DEFAULT_BACKGROUND_COLOR: &str = "#ffffff";
+
+const ERROR_PAGE_HTML: &str = "<!DOCTYPE html>
+<html lang=\"{{lang}}\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{{title}}</title>
+<style>
+body { margin: 0; font-family: system-ui, sans-serif; background: {{background_color}}; color: #222; }
+main { max-width: 32rem; margin: 15vh auto 0; padding: 0 1.5rem; text-align: center; }
+img { max-height: 4rem; margin-bottom: 1.5rem; }
+h1 { color: {{primary_color}}; font-size: 1.75rem; }
+footer { margin-top: 3rem; color: #777; font-size: 0.875rem; }
+</style>
+</head>
+<body>
+<main>
+{{logo}}
+<h1>{{title}}</h1>
+<p>{{message}}</p>
+<footer>{{footer_text}}</footer>
+</main>
+</body>
+</html>
+";
+
+/// Render the page telling a visitor why a short link didn't take them anywhere
+pub fn error_page(
+    branding: &Branding,
+    locale: &Locale,
+    status: StatusCode,
+    message: &str,
+) -> String {
+    let title = locale.text(
+        match status {
+            StatusCode::NOT_FOUND => "page.not_found.title",
+            StatusCode::GONE => "page.gone.title",
+            StatusCode::TOO_MANY_REQUESTS => "page.suspended.title",
+            _ => "page.unavailable.title",
+        },
+        &[],
+    );
+    // The logo is markup of its own, everything else is escaped
+    let page = render(ERROR_PAGE_HTML, &[("logo", logo(branding).as_str())], false);
+    render(
+        &page,
+        &[
+            ("lang", locale.tag()),
+            ("title", title.as_str()),
+            ("message", message),
+            (
+                "primary_color",
+                branding
+                    .primary_color
+                    .as_deref()
+                    .unwrap_or(DEFAULT_PRIMARY_COLOR),
+            ),
+            (
+                "background_color",
+                branding
+                    .background_color
+                    .as_deref()
+                    .unwrap_or(DEFAULT_BACKGROUND_COLOR),
+            ),
+            (
+                "footer_text",
+                branding.footer_text.as_deref().unwrap_or_default(),
+            ),
+        ],
+        true,
+    )
+}
+
+const WARNING_PAGE_HTML: &str = "<!DOCTYPE html>
+<html lang=\"{{lang}}\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<meta name=\"robots\" content=\"noindex\">
+<title>{{title}}</title>
+<style>
+body { margin: 0; font-family: system-ui, sans-serif; background: {{background_color}}; color: #222; }
+main { max-width: 32rem; margin: 15vh auto 0; padding: 0 1.5rem; text-align: center; }
+img { max-height: 4rem; margin-bottom: 1.5rem; }
+h1 { color: #c5221f; font-size: 1.75rem; }
+code { display: block; margin: 1rem 0; padding: 0.6rem; background: #f1f3f4; border-radius: 4px; word-break: break-all; }
+a.continue { display: inline-block; padding: 0.6rem 1rem; border-radius: 4px; background: {{primary_color}}; color: #fff; text-decoration: none; }
+footer { margin-top: 3rem; color: #777; font-size: 0.875rem; }
+</style>
+</head>
+<body>
+<main>
+{{logo}}
+<h1>{{title}}</h1>
+<p>{{flagged}}</p>
+<code>{{destination}}</code>
+<p>{{advice}}</p>
+<a class=\"continue\" href=\"{{destination}}\" rel=\"noopener noreferrer nofollow\">{{continue}}</a>
+<footer>{{footer_text}}</footer>
+</main>
+</body>
+</html>
+";
+
+/// Render the page warning a visitor before following a flagged link
+pub fn warning_page(
+    branding: &Branding,
+    locale: &Locale,
+    short_code: &str,
+    destination: &str,
+) -> String {
+    let short_code = format!("<strong>{}</strong>", html_escape(short_code));
+    let flagged = locale.markup("page.warning.flagged", &[("code", short_code.as_str())]);
+    let page = render(
+        WARNING_PAGE_HTML,
+        &[
+            ("logo", logo(branding).as_str()),
+            ("flagged", flagged.as_str()),
+        ],
+        false,
+    );
+    render(
+        &page,
+        &[
+            ("lang", locale.tag()),
+            ("title", &locale.text("page.warning.title", &[])),
+            ("advice", &locale.text("page.warning.advice", &[])),
+            ("continue", &locale.text("page.warning.continue", &[])),
+            ("destination", destination),
+            (
+                "primary_color",
+                branding
+                    .primary_color
+                    .as_deref()
+                    .unwrap_or(DEFAULT_PRIMARY_COLOR),
+            ),
+            (
+                "background_color",
+                branding
+                    .background_color
+                    .as_deref()
+                    .unwrap_or(DEFAULT_BACKGROUND_COLOR),
+            ),
+            (
+                "footer_text",
+                branding.footer_text.as_deref().unwrap_or_default(),
+            ),
+        ],
+        true,
+    )
+}
+
+const PREVIEW_PAGE_HTML: &str = "<!DOCTYPE html>
+<html lang=\"{{lang}}\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{{file_name}}</title>
+<style>
+body { margin: 0; font-family: system-ui, sans-serif; background: {{background_color}}; color: #222; }
+main { max-width: 48rem; margin: 8vh auto 0; padding: 0 1.5rem; text-align: center; }
+main > img { max-height: 4rem; margin-bottom: 1.5rem; }
+h1 { color: {{primary_color}}; font-size: 1.5rem; word-break: break-all; }
+.preview { margin: 1.5rem 0; }
+.preview img, .preview video { max-width: 100%; max-height: 70vh; }
+.preview iframe { width: 100%; height: 70vh; border: 1px solid #ddd; }
+.details { color: #555; }
+a.download { display: inline-block; padding: 0.6rem 1rem; border-radius: 4px; background: {{primary_color}}; color: #fff; text-decoration: none; }
+footer { margin-top: 3rem; color: #777; font-size: 0.875rem; }
+</style>
+</head>
+<body>
+<main>
+{{logo}}
+<h1>{{file_name}}</h1>
+<div class=\"preview\">{{preview}}</div>
+<p class=\"details\">{{details}}</p>
+<a class=\"download\" href=\"{{destination}}\" rel=\"noopener noreferrer\" download>{{download}}</a>
+<footer>{{footer_text}}</footer>
+</main>
+</body>
+</html>
+";
+
+/// Render the page previewing a link's destination file, with a link to download it
+///
+/// Images, PDFs, video and audio are shown inline; other files only get their details.
+pub fn preview_page(
+    branding: &Branding,
+    locale: &Locale,
+    destination: &str,
+    file: &FileInfo,
+) -> String {
+    let source = html_escape(destination);
+    let title = html_escape(&file.file_name);
+    let content_type = file.content_type.as_deref().unwrap_or_default();
+    let preview = match content_type.split_once('/') {
+        Some(("image", _)) => format!("<img src=\"{}\" alt=\"{}\">", source, title),
+        Some(("video", _)) => format!("<video src=\"{}\" controls></video>", source),
+        Some(("audio", _)) => format!("<audio src=\"{}\" controls></audio>", source),
+        _ if content_type == "application/pdf" => {
+            format!("<iframe src=\"{}\" title=\"{}\"></iframe>", source, title)
+        }
+        _ => String::new(),
+    };
+    let details = match (file.content_type.as_deref(), file.size) {
+        (Some(content_type), Some(size)) => format!("{}, {}", content_type, human_size(size)),
+        (Some(content_type), None) => content_type.to_string(),
+        (None, Some(size)) => human_size(size),
+        (None, None) => String::new(),
+    };
+
+    let page = render(
+        PREVIEW_PAGE_HTML,
+        &[
+            ("logo", logo(branding).as_str()),
+            ("preview", preview.as_str()),
+        ],
+        false,
+    );
+    render(
+        &page,
+        &[
+            ("lang", locale.tag()),
+            ("download", &locale.text("page.preview.download", &[])),
+            ("file_name", file.file_name.as_str()),
+            ("details", details.as_str()),
+            ("destination", destination),
+            (
+                "primary_color",
+                branding
+                    .primary_color
+                    .as_deref()
+                    .unwrap_or(DEFAULT_PRIMARY_COLOR),
+            ),
+            (
+                "background_color",
+                branding
+                    .background_color
+                    .as_deref()
+                    .unwrap_or(DEFAULT_BACKGROUND_COLOR),
+            ),
+            (
+                "footer_text",
+                branding.footer_text.as_deref().unwrap_or_default(),
+            ),
+        ],
+        true,
+    )
+}
+
+const APP_LINK_PAGE_HTML: &str = "<!DOCTYPE html>
+<html lang=\"{{lang}}\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<meta name=\"robots\" content=\"noindex\">
+<title>{{title}}</title>
+<style>
+body { margin: 0; font-family: system-ui, sans-serif; background: {{background_color}}; color: #222; }
+main { max-width: 32rem; margin: 15vh auto 0; padding: 0 1.5rem; text-align: center; }
+img { max-height: 4rem; margin-bottom: 1.5rem; }
+h1 { color: {{primary_color}}; font-size: 1.5rem; }
+a { display: inline-block; margin: 0.5rem; padding: 0.6rem 1rem; border-radius: 4px; text-decoration: none; }
+a#app { background: {{primary_color}}; color: #fff; }
+a#web { color: {{primary_color}}; }
+footer { margin-top: 3rem; color: #777; font-size: 0.875rem; }
+</style>
+</head>
+<body>
+<main>
+{{logo}}
+<h1>{{title}}</h1>
+<a id=\"app\" href=\"{{app_url}}\">{{open}}</a>
+<a id=\"web\" href=\"{{destination}}\" rel=\"noopener noreferrer\">{{web}}</a>
+<footer>{{footer_text}}</footer>
+</main>
+<script>
+(function () {
+  var app = document.getElementById(\"app\").href;
+  var web = document.getElementById(\"web\").href;
+  // Leaving for the app hides the page; if it's still showing, the app isn't installed
+  setTimeout(function () {
+    if (!document.hidden) { window.location.replace(web); }
+  }, 1500);
+  window.location.href = app;
+})();
+</script>
+</body>
+</html>
+";
+
+/// Render the page trying to open a link in its mobile app, then falling back to the
+/// destination on the web when the app doesn't take over
+pub fn app_link_page(
+    branding: &Branding,
+    locale: &Locale,
+    app_url: &str,
+    destination: &str,
+) -> String {
+    let page = render(
+        APP_LINK_PAGE_HTML,
+        &[("logo", logo(branding).as_str())],
+        false,
+    );
+    render(
+        &page,
+        &[
+            ("lang", locale.tag()),
+            ("title", &locale.text("page.app_link.title", &[])),
+            ("open", &locale.text("page.app_link.open", &[])),
+            ("web", &locale.text("page.app_link.web", &[])),
+            ("app_url", app_url),
+            ("destination", destination),
+            (
+                "primary_color",
+                branding
+                    .primary_color
+                    .as_deref()
+                    .unwrap_or(DEFAULT_PRIMARY_COLOR),
+            ),
+            (
+                "background_color",
+                branding
+                    .background_color
+                    .as_deref()
+                    .unwrap_or(DEFAULT_BACKGROUND_COLOR),
+            ),
+            (
+                "footer_text",
+                branding.footer_text.as_deref().unwrap_or_default(),
+            ),
+        ],
+        true,
+    )
+}
+
+const SOCIAL_PAGE_HTML: &str = "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<title>{{title}}</title>
+<meta property=\"og:type\" content=\"website\">
+<meta property=\"og:url\" content=\"{{page_url}}\">
+<meta property=\"og:title\" content=\"{{title}}\">
+{{tags}}</head>
+<body>
+<h1>{{title}}</h1>
+</body>
+</html>
+";
+
+/// Render the page shown to link preview crawlers, carrying the link's Open Graph tags
+///
+/// Anyone can claim to be a crawler, so the page never names or leads on to the
+/// destination: it's only reached by following the link as a visitor.
+///
+/// ### Arguments
+/// * `url` - The link, whose `og_*` fields override the destination's own tags
+/// * `page_url` - The short URL the page is served at
+pub fn social_page(url: &ShortenedUrl, page_url: &str) -> String {
+    let meta = |property: &str, content: &str| {
+        format!(
+            "<meta property=\"{}\" content=\"{}\">\n",
+            property,
+            html_escape(content)
+        )
+    };
+    let mut tags = String::new();
+    if let Some(description) = &url.og_description {
+        tags.push_str(&meta("og:description", description));
+    }
+    let card = match &url.og_image {
+        Some(image) => {
+            tags.push_str(&meta("og:image", image));
+            "summary_large_image"
+        }
+        None => "summary",
+    };
+    tags.push_str(&format!(
+        "<meta name=\"twitter:card\" content=\"{}\">\n",
+        card
+    ));
+
+    let title = url.og_title.clone().unwrap_or_else(|| url.path());
+    let page = render(SOCIAL_PAGE_HTML, &[("tags", tags.as_str())], false);
+    render(
+        &page,
+        &[("title", title.as_str()), ("page_url", page_url)],
+        true,
+    )
+}
+
+// A byte count the way file managers show it, like `1.5 MB`
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} bytes", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+const HOME_PAGE_HTML: &str = "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<meta name=\"csrf-cookie\" content=\"{{csrf_cookie}}\">
+<title>Shorten a link</title>
+<style>
+body { margin: 0; font-family: system-ui, sans-serif; background: {{background_color}}; color: #222; }
+main { max-width: 36rem; margin: 15vh auto 0; padding: 0 1.5rem; text-align: center; }
+img { max-height: 4rem; margin-bottom: 1.5rem; }
+h1 { color: {{primary_color}}; font-size: 1.75rem; }
+form { display: flex; gap: 0.5rem; }
+input { flex: 1; font: inherit; padding: 0.6rem; border: 1px solid #bbb; border-radius: 4px; }
+button { font: inherit; padding: 0.6rem 1rem; border: 0; border-radius: 4px; background: {{primary_color}}; color: #fff; cursor: pointer; }
+.captcha { display: flex; justify-content: center; margin-top: 1rem; }
+#result { margin-top: 1.5rem; min-height: 1.5rem; }
+#result a { color: {{primary_color}}; font-weight: 600; }
+.error { color: #c5221f; }
+footer { margin-top: 3rem; color: #777; font-size: 0.875rem; }
+</style>
+</head>
+<body>
+<main>
+{{logo}}
+<h1>Shorten a link</h1>
+<form id=\"shorten\">
+<input id=\"url\" type=\"url\" placeholder=\"https://example.com/a/long/link\" aria-label=\"Link to shorten\" required>
+<button type=\"submit\">Shorten</button>
+</form>
+<div class=\"captcha\">{{captcha}}</div>
+<p id=\"result\" aria-live=\"polite\"></p>
+<footer>{{footer_text}}</footer>
+</main>
+<script>
+const form = document.getElementById(\"shorten\");
+const result = document.getElementById(\"result\");
+
+// Requests sending cookies echo the CSRF token the server handed out with this page
+function csrfToken() {
+  const name = document.querySelector('meta[name=\"csrf-cookie\"]').content;
+  const cookie = document.cookie.split(\"; \").find((c) => c.startsWith(name + \"=\"));
+  return cookie ? decodeURIComponent(cookie.slice(name.length + 1)) : \"\";
+}
+
+// Filled in by the CAPTCHA widget, when there is one
+function captchaToken() {
+  const field = document.querySelector('[name$=\"-captcha-response\"]');
+  return field ? field.value : \"\";
+}
+
+form.addEventListener(\"submit\", async (event) => {
+  event.preventDefault();
+  result.className = \"\";
+  result.textContent = \"Shortening...\";
+  try {
+    const response = await fetch(\"/api/urls\", {
+      method: \"POST\",
+      headers: {
+        \"Content-Type\": \"application/json\",
+        Accept: \"application/json\",
+        \"X-CSRF-Token\": csrfToken(),
+        \"X-Captcha-Token\": captchaToken(),
+      },
+      body: JSON.stringify({ original_url: document.getElementById(\"url\").value.trim() }),
+    });
+    const payload = await response.json();
+    if (!response.ok) {
+      throw new Error(payload.message || \"The link could not be shortened\");
+    }
+    const link = payload.data;
+    const code = link.namespace ? link.namespace + \"/\" + link.short_code : link.short_code;
+    const anchor = document.createElement(\"a\");
+    anchor.href = anchor.textContent = location.origin + \"/\" + code;
+    result.replaceChildren(anchor);
+    form.reset();
+  } catch (e) {
+    result.className = \"error\";
+    result.textContent = e.message;
+  }
+  // Every CAPTCHA response is only accepted once
+  if (window.hcaptcha) hcaptcha.reset();
+  if (window.grecaptcha) grecaptcha.reset();
+});
+</script>
+</body>
+</html>
+";
+
+/// Render the home page, where browsers can shorten a link without an API key
+pub fn home_page(
+    branding: &Branding,
+    csrf_cookie: &str,
+    captcha: Option<&CaptchaConfig>,
+) -> String {
+    let widget = captcha.map(captcha_widget).unwrap_or_default();
+    let page = render(
+        HOME_PAGE_HTML,
+        &[
+            ("logo", logo(branding).as_str()),
+            ("captcha", widget.as_str()),
+        ],
+        false,
+    );
+    render(
+        &page,
+        &[
+            ("csrf_cookie", csrf_cookie),
+            (
+                "primary_color",
+                branding
+                    .primary_color
+                    .as_deref()
+                    .unwrap_or(DEFAULT_PRIMARY_COLOR),
+            ),
+            (
+                "background_color",
+                branding
+                    .background_color
+                    .as_deref()
+                    .unwrap_or(DEFAULT_BACKGROUND_COLOR),
+            ),
+            (
+                "footer_text",
+                branding.footer_text.as_deref().unwrap_or_default(),
+            ),
+        ],
+        true,
+    )
+}
+
+// The provider's widget, which puts its response in a `*-captcha-response` field once solved
+fn captcha_widget(config: &CaptchaConfig) -> String {
+    let (script, class) = match config.provider {
+        CaptchaProvider::HCaptcha => ("https://js.hcaptcha.com/1/api.js", "h-captcha"),
+        CaptchaProvider::ReCaptcha => ("https://www.google.com/recaptcha/api.js", "g-recaptcha"),
+    };
+    format!(
+        "<script src=\"{}\" async defer></script><div class=\"{}\" data-sitekey=\"{}\"></div>",
+        script,
+        class,
+        html_escape(&config.site_key)
+    )
+}
+
+// The tenant's logo, or nothing when it has none
+fn logo(branding: &Branding) -> String {
+    branding
+        .logo_url
+        .as_deref()
+        .map(|url| format!("<img src=\"{}\" alt=\"\">", html_escape(url)))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::Catalog;
+
+    #[test]
+    fn test_error_page_applies_branding() {
+        let branding = Branding {
+            logo_url: Some("https://cdn.acme.test/logo.png?a=1&b=2".to_string()),
+            primary_color: Some("#ff6600".to_string()),
+            footer_text: Some("Acme <Links>".to_string()),
+            ..Default::default()
+        };
+
+        let catalog = Catalog::bundled();
+        let locale = catalog.locale(None);
+        let page = error_page(
+            &branding,
+            &locale,
+            StatusCode::NOT_FOUND,
+            "URL with code 'x' not found",
+        );
+        assert!(page.contains("<html lang=\"en\">"));
+        assert!(page.contains("<title>Link not found</title>"));
+        assert!(page.contains("<img src=\"https://cdn.acme.test/logo.png?a=1&amp;b=2\""));
+        assert!(page.contains("h1 { color: #ff6600;"));
+        assert!(page.contains("background: #ffffff;"));
+        assert!(page.contains("<footer>Acme &lt;Links&gt;</footer>"));
+
+        let plain = error_page(&Branding::default(), &locale, StatusCode::GONE, "Expired");
+        assert!(plain.contains("<h1>Link no longer available</h1>"));
+        assert!(!plain.contains("<img"));
+    }
+
+    #[test]
+    fn test_warning_page_links_to_destination() {
+        let page = warning_page(
+            &Branding::default(),
+            &Catalog::bundled().locale(None),
+            "promo",
+            "https://example.com/?a=1&b=\"<x>\"",
+        );
+        assert!(page.contains("<p>The link <strong>promo</strong> has been flagged"));
+        assert!(page.contains(
+            "href=\"https://example.com/?a=1&amp;b=&quot;&lt;x&gt;&quot;\" rel=\"noopener"
+        ));
+    }
+
+    #[test]
+    fn test_preview_page_embeds_by_type() {
+        let pdf = FileInfo {
+            file_name: "report.pdf".to_string(),
+            content_type: Some("application/pdf".to_string()),
+            size: Some(1536 * 1024),
+        };
+        let destination = "https://cdn.example.com/report.pdf?a=1&b=2";
+        let catalog = Catalog::bundled();
+        let locale = catalog.locale(None);
+        let page = preview_page(&Branding::default(), &locale, destination, &pdf);
+        assert!(page.contains("<iframe src=\"https://cdn.example.com/report.pdf?a=1&amp;b=2\""));
+        assert!(page.contains("application/pdf, 1.5 MB"));
+
+        let archive = FileInfo {
+            file_name: "backup.zip".to_string(),
+            ..Default::default()
+        };
+        let page = preview_page(
+            &Branding::default(),
+            &locale,
+            "https://example.com/backup.zip",
+            &archive,
+        );
+        assert!(page.contains("<div class=\"preview\"></div>"));
+        assert!(page.contains("href=\"https://example.com/backup.zip\""));
+    }
+
+    #[test]
+    fn test_app_link_page_falls_back_to_web() {
+        let page = app_link_page(
+            &Branding::default(),
+            &Catalog::bundled().locale(None),
+            "myapp://item/42?ref=\"x\"",
+            "https://example.com/item/42?a=1&b=2",
+        );
+        assert!(page.contains("id=\"app\" href=\"myapp://item/42?ref=&quot;x&quot;\""));
+        assert!(page.contains("id=\"web\" href=\"https://example.com/item/42?a=1&amp;b=2\""));
+        assert!(page.contains("window.location.replace(web)"));
+    }
+
+    #[test]
+    fn test_social_page_carries_link_tags() {
+        let url = ShortenedUrl {
+            short_code: "launch".to_string(),
+            og_title: Some("Launch \"day\"".to_string()),
+            og_image: Some("https://cdn.example.com/card.png?a=1&b=2".to_string()),
+            ..Default::default()
+        };
+        let page = social_page(&url, "https://sho.rt/launch");
+        assert!(page.contains("<meta property=\"og:title\" content=\"Launch &quot;day&quot;\">"));
+        assert!(page.contains(
+            "<meta property=\"og:image\" content=\"https://cdn.example.com/card.png?a=1&amp;b=2\">"
+        ));
+        assert!(page.contains("content=\"summary_large_image\""));
+        // Nothing leads on to the destination
+        assert!(!page.contains("refresh"));
+        assert!(!page.contains("<script"));
+        assert!(!page.contains("og:description"));
+
+        let plain = ShortenedUrl {
+            short_code: "launch".to_string(),
+            og_description: Some("See you there".to_string()),
+            ..Default::default()
+        };
+        let page = social_page(&plain, "https://sho.rt/launch");
+        assert!(page.contains("<title>launch</title>"));
+        assert!(page.contains("content=\"summary\""));
+    }
+}
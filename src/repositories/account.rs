@@ -0,0 +1,292 @@
+// src/repositories/account.rs - Account export and erasure data access
+use async_trait::async_trait;
+use sqlx::{PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{
+    AccountExport, DailyVisitAggregate, ExportStatus, LinkBehavior, LinkStatus, ShortenedUrl,
+};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait AccountRepositoryTrait {
+    /// Creates a pending export request for a user
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn create_export(&self, user_id: &Uuid) -> Result<AccountExport>;
+
+    /// Finds an export request belonging to a user
+    ///
+    /// ### Returns
+    /// * `Result<Option<AccountExport>>` - The export if found for this user
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_export(&self, user_id: &Uuid, id: &Uuid) -> Result<Option<AccountExport>>;
+
+    /// Fetches the archive of a completed export belonging to a user
+    ///
+    /// ### Returns
+    /// * `Result<Option<Vec<u8>>>` - The archive bytes, or `None` if missing or not completed
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_export_archive(&self, user_id: &Uuid, id: &Uuid) -> Result<Option<Vec<u8>>>;
+
+    /// Moves an export to a new status, storing the archive or error when finished
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn update_export(
+        &self,
+        id: &Uuid,
+        status: ExportStatus,
+        archive: Option<Vec<u8>>,
+        error: Option<String>,
+    ) -> Result<()>;
+
+    /// Finds every shortened URL owned by a user
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_links_by_owner(&self, user_id: &Uuid) -> Result<Vec<ShortenedUrl>>;
+
+    /// Computes daily visit counts for a user's links, merging rolled-up and raw events
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_visit_aggregates_by_owner(
+        &self,
+        user_id: &Uuid,
+    ) -> Result<Vec<DailyVisitAggregate>>;
+
+    /// Deletes a user together with their links (visits cascade), in one transaction
+    ///
+    /// ### Returns
+    /// * `Result<u64>` - Number of links deleted
+    ///
+    /// ### Errors
+    /// * `RepositoryError::NotFound` - If the user doesn't exist
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn erase_account(&self, user_id: &Uuid) -> Result<u64>;
+
+    /// Deletes a user but keeps their links, stripped of owner and metadata, in one transaction
+    ///
+    /// ### Returns
+    /// * `Result<u64>` - Number of links anonymized
+    ///
+    /// ### Errors
+    /// * `RepositoryError::NotFound` - If the user doesn't exist
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn anonymize_account(&self, user_id: &Uuid) -> Result<u64>;
+}
+
+// Implementation using actual database
+pub struct AccountRepository {
+    pool: PgPool,
+}
+
+impl AccountRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+
+    // Helper method for transactions
+    async fn begin_transaction(&self) -> Result<Transaction<'_, Postgres>> {
+        self.pool.begin().await.map_err(|e| {
+            log::error!("Failed to start database transaction: {}", e);
+            RepositoryError::Database(e)
+        })
+    }
+
+    // Delete the user row inside an open transaction, failing if it doesn't exist
+    async fn delete_user(tx: &mut Transaction<'_, Postgres>, user_id: &Uuid) -> Result<()> {
+        let result = sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
+            .execute(&mut **tx)
+            .await
+            .map_err(RepositoryError::Database)?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound(format!(
+                "User with ID {} not found",
+                user_id
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl AccountRepositoryTrait for AccountRepository {
+    async fn create_export(&self, user_id: &Uuid) -> Result<AccountExport> {
+        sqlx::query_as!(
+            AccountExport,
+            r#"
+                INSERT INTO account_exports (user_id)
+                VALUES ($1)
+                RETURNING id, user_id, status as "status: ExportStatus", error, created_at, completed_at
+            "#,
+            user_id
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn find_export(&self, user_id: &Uuid, id: &Uuid) -> Result<Option<AccountExport>> {
+        sqlx::query_as!(
+            AccountExport,
+            r#"
+                SELECT id, user_id, status as "status: ExportStatus", error, created_at, completed_at
+                FROM account_exports
+                WHERE id = $1 AND user_id = $2
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_export_archive(&self, user_id: &Uuid, id: &Uuid) -> Result<Option<Vec<u8>>> {
+        let archive = sqlx::query_scalar!(
+            r#"
+                SELECT archive
+                FROM account_exports
+                WHERE id = $1 AND user_id = $2 AND status = 'completed'
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(archive.flatten())
+    }
+
+    async fn update_export(
+        &self,
+        id: &Uuid,
+        status: ExportStatus,
+        archive: Option<Vec<u8>>,
+        error: Option<String>,
+    ) -> Result<()> {
+        let finished = matches!(status, ExportStatus::Completed | ExportStatus::Failed);
+        sqlx::query!(
+            r#"
+                UPDATE account_exports
+                SET status = $2,
+                    archive = $3,
+                    error = $4,
+                    completed_at = CASE WHEN $5 THEN NOW() ELSE NULL END
+                WHERE id = $1
+            "#,
+            id,
+            status as ExportStatus,
+            archive,
+            error,
+            finished
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(())
+    }
+
+    async fn find_links_by_owner(&self, user_id: &Uuid) -> Result<Vec<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE created_by = $1
+                ORDER BY created_at
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_visit_aggregates_by_owner(
+        &self,
+        user_id: &Uuid,
+    ) -> Result<Vec<DailyVisitAggregate>> {
+        sqlx::query_as!(
+            DailyVisitAggregate,
+            r#"
+                SELECT visits.url_id AS "url_id!", visits.day AS "day!", SUM(visits.visits)::BIGINT AS "visits!"
+                FROM (
+                    SELECT d.url_id, d.day, d.visits
+                    FROM url_visit_daily d
+                    JOIN shortened_urls s ON s.id = d.url_id
+                    WHERE s.created_by = $1
+                    UNION ALL
+                    SELECT v.url_id, (v.visited_at AT TIME ZONE 'UTC')::date, COUNT(*)
+                    FROM url_visits v
+                    JOIN shortened_urls s ON s.id = v.url_id
+                    WHERE s.created_by = $1
+                    GROUP BY 1, 2
+                ) visits
+                GROUP BY 1, 2
+                ORDER BY 2, 1
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn erase_account(&self, user_id: &Uuid) -> Result<u64> {
+        let mut tx = self.begin_transaction().await?;
+
+        let deleted = sqlx::query!("DELETE FROM shortened_urls WHERE created_by = $1", user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(RepositoryError::Database)?
+            .rows_affected();
+
+        Self::delete_user(&mut tx, user_id).await?;
+
+        tx.commit().await.map_err(|e| {
+            log::error!("Failed to commit transaction: {}", e);
+            RepositoryError::Database(e)
+        })?;
+
+        Ok(deleted)
+    }
+
+    async fn anonymize_account(&self, user_id: &Uuid) -> Result<u64> {
+        let mut tx = self.begin_transaction().await?;
+
+        let anonymized = sqlx::query!(
+            "UPDATE shortened_urls SET created_by = NULL, metadata = NULL WHERE created_by = $1",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(RepositoryError::Database)?
+        .rows_affected();
+
+        Self::delete_user(&mut tx, user_id).await?;
+
+        tx.commit().await.map_err(|e| {
+            log::error!("Failed to commit transaction: {}", e);
+            RepositoryError::Database(e)
+        })?;
+
+        Ok(anonymized)
+    }
+}
@@ -0,0 +1,200 @@
+// src/repositories/analytics.rs - Visit event data access
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{DailyVisitAggregate, NewUrlVisit, UrlVisit};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait AnalyticsRepositoryTrait {
+    /// Records a single visit event for a shortened URL
+    ///
+    /// ### Arguments
+    /// * `visit` - The visit data captured from the redirect request
+    ///
+    /// ### Returns
+    /// * `Result<UrlVisit>` - The stored visit event
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::InvalidData` - If the referenced URL does not exist
+    async fn record_visit(&self, visit: &NewUrlVisit) -> Result<UrlVisit>;
+
+    /// Counts a visit towards today's aggregate without storing a visit event
+    ///
+    /// ### Arguments
+    /// * `url_id` - The ID of the visited URL
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::InvalidData` - If the referenced URL does not exist
+    async fn increment_daily_visits(&self, url_id: &Uuid) -> Result<()>;
+
+    /// Finds the oldest raw visit events recorded before a cutoff
+    ///
+    /// ### Arguments
+    /// * `cutoff` - Only events strictly older than this are returned
+    /// * `limit` - The maximum number of events to return
+    ///
+    /// ### Returns
+    /// * `Result<Vec<UrlVisit>>` - The events, ordered by ID
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_visits_before(&self, cutoff: DateTime<Utc>, limit: i64) -> Result<Vec<UrlVisit>>;
+
+    /// Rolls the given raw events up into daily aggregates and deletes them in one statement
+    ///
+    /// ### Arguments
+    /// * `ids` - IDs of the raw visit events to roll up
+    ///
+    /// ### Returns
+    /// * `Result<u64>` - Number of raw events removed
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn rollup_and_delete_visits(&self, ids: &[i64]) -> Result<u64>;
+
+    /// Finds a page of daily visit aggregates
+    ///
+    /// ### Arguments
+    /// * `limit` - The maximum number of records to return
+    /// * `offset` - The number of records to skip
+    ///
+    /// ### Returns
+    /// * `Result<Vec<DailyVisitAggregate>>` - Aggregates ordered by day, then URL
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_daily_aggregates(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<DailyVisitAggregate>>;
+}
+
+// Implementation using actual database
+pub struct AnalyticsRepository {
+    pool: PgPool,
+}
+
+impl AnalyticsRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl AnalyticsRepositoryTrait for AnalyticsRepository {
+    async fn record_visit(&self, visit: &NewUrlVisit) -> Result<UrlVisit> {
+        sqlx::query_as!(
+            UrlVisit,
+            r#"
+                INSERT INTO url_visits (url_id, ip_address, user_agent, referer)
+                VALUES ($1, $2, $3, $4)
+                RETURNING id, url_id, visited_at, ip_address, user_agent, referer
+            "#,
+            visit.url_id,
+            visit.ip_address,
+            visit.user_agent,
+            visit.referer
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn increment_daily_visits(&self, url_id: &Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO url_visit_daily (url_id, day, visits)
+                VALUES ($1, (NOW() AT TIME ZONE 'UTC')::date, 1)
+                ON CONFLICT (url_id, day)
+                DO UPDATE SET visits = url_visit_daily.visits + 1
+            "#,
+            url_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(())
+    }
+
+    async fn find_visits_before(&self, cutoff: DateTime<Utc>, limit: i64) -> Result<Vec<UrlVisit>> {
+        sqlx::query_as!(
+            UrlVisit,
+            r#"
+                SELECT id, url_id, visited_at, ip_address, user_agent, referer
+                FROM url_visits
+                WHERE visited_at < $1
+                ORDER BY id
+                LIMIT $2
+            "#,
+            cutoff,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn rollup_and_delete_visits(&self, ids: &[i64]) -> Result<u64> {
+        // Delete the raw rows and fold them into the daily counts in one statement,
+        // so a visit is never both aggregated and still present (or neither)
+        let removed = sqlx::query_scalar!(
+            r#"
+                WITH moved AS (
+                    DELETE FROM url_visits
+                    WHERE id = ANY($1)
+                    RETURNING url_id, visited_at
+                ), aggregated AS (
+                    INSERT INTO url_visit_daily (url_id, day, visits)
+                    SELECT url_id, (visited_at AT TIME ZONE 'UTC')::date, COUNT(*)
+                    FROM moved
+                    GROUP BY 1, 2
+                    ON CONFLICT (url_id, day)
+                    DO UPDATE SET visits = url_visit_daily.visits + EXCLUDED.visits
+                )
+                SELECT COUNT(*) AS "removed!" FROM moved
+            "#,
+            ids
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to roll up visit events: {}", e);
+            RepositoryError::from(e)
+        })?;
+
+        Ok(removed as u64)
+    }
+
+    async fn find_daily_aggregates(
+        &self,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<DailyVisitAggregate>> {
+        sqlx::query_as!(
+            DailyVisitAggregate,
+            r#"
+                SELECT url_id, day, visits
+                FROM url_visit_daily
+                ORDER BY day, url_id
+                LIMIT $1 OFFSET $2
+            "#,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+}
@@ -0,0 +1,95 @@
+// src/repositories/app_links.rs - Per-tenant mobile apps opening short links
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{AppLinks, UpdateAppLinksDto};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait AppLinksRepositoryTrait {
+    /// Finds the app links of a tenant
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant, `None` for the default tenant
+    ///
+    /// ### Returns
+    /// * `Result<Option<AppLinks>>` - The app links, if the tenant set them up
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find(&self, tenant: Option<&Uuid>) -> Result<Option<AppLinks>>;
+
+    /// Replaces the app links of a tenant, creating them on first use
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant, `None` for the default tenant
+    /// * `dto` - The new app links
+    ///
+    /// ### Returns
+    /// * `Result<AppLinks>` - The stored app links
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn upsert(&self, tenant: Option<&Uuid>, dto: &UpdateAppLinksDto) -> Result<AppLinks>;
+}
+
+// Implementation using actual database
+pub struct AppLinksRepository {
+    pool: PgPool,
+}
+
+impl AppLinksRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl AppLinksRepositoryTrait for AppLinksRepository {
+    async fn find(&self, tenant: Option<&Uuid>) -> Result<Option<AppLinks>> {
+        sqlx::query_as!(
+            AppLinks,
+            r#"
+                SELECT ios_app_ids, android_package, android_cert_fingerprints,
+                       updated_at AS "updated_at?"
+                FROM app_links
+                WHERE tenant_id IS NOT DISTINCT FROM $1
+            "#,
+            tenant
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn upsert(&self, tenant: Option<&Uuid>, dto: &UpdateAppLinksDto) -> Result<AppLinks> {
+        sqlx::query_as!(
+            AppLinks,
+            r#"
+                INSERT INTO app_links (tenant_id, ios_app_ids, android_package, android_cert_fingerprints)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT ((COALESCE(tenant_id, '00000000-0000-0000-0000-000000000000')))
+                DO UPDATE SET
+                    ios_app_ids = EXCLUDED.ios_app_ids,
+                    android_package = EXCLUDED.android_package,
+                    android_cert_fingerprints = EXCLUDED.android_cert_fingerprints,
+                    updated_at = NOW()
+                RETURNING ios_app_ids, android_package, android_cert_fingerprints,
+                          updated_at AS "updated_at?"
+            "#,
+            tenant,
+            &dto.ios_app_ids,
+            dto.android_package,
+            &dto.android_cert_fingerprints
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+}
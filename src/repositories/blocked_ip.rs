@@ -0,0 +1,152 @@
+// src/repositories/blocked_ip.rs - Blocked client addresses
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{BlockSource, BlockedIp};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait BlockedIpRepositoryTrait {
+    /// Finds all blocks still in force
+    ///
+    /// ### Returns
+    /// * `Result<Vec<BlockedIp>>` - The active blocks, newest first
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_active(&self) -> Result<Vec<BlockedIp>>;
+
+    /// Blocks an address, replacing an existing block
+    ///
+    /// Automatic blocks never replace a manual one, so an admin's decision sticks.
+    ///
+    /// ### Arguments
+    /// * `ip` - The canonical address to block
+    /// * `reason` - Why the address is blocked
+    /// * `source` - Who is blocking it
+    /// * `blocked_until` - End of the block, `None` for permanent
+    ///
+    /// ### Returns
+    /// * `Result<Option<BlockedIp>>` - The stored block, or `None` if a manual block was kept
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn upsert(
+        &self,
+        ip: &str,
+        reason: &str,
+        source: BlockSource,
+        blocked_until: Option<DateTime<Utc>>,
+    ) -> Result<Option<BlockedIp>>;
+
+    /// Lifts the block on an address
+    ///
+    /// ### Arguments
+    /// * `ip` - The canonical address to unblock
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::NotFound` - If the address isn't blocked
+    async fn delete(&self, ip: &str) -> Result<()>;
+
+    /// Removes blocks that have run out
+    ///
+    /// ### Returns
+    /// * `Result<u64>` - Number of blocks removed
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn delete_expired(&self) -> Result<u64>;
+}
+
+// Implementation using actual database
+pub struct BlockedIpRepository {
+    pool: PgPool,
+}
+
+impl BlockedIpRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl BlockedIpRepositoryTrait for BlockedIpRepository {
+    async fn find_active(&self) -> Result<Vec<BlockedIp>> {
+        sqlx::query_as!(
+            BlockedIp,
+            r#"
+                SELECT ip, reason, source as "source: BlockSource", blocked_until, created_at
+                FROM blocked_ips
+                WHERE blocked_until IS NULL OR blocked_until > NOW()
+                ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn upsert(
+        &self,
+        ip: &str,
+        reason: &str,
+        source: BlockSource,
+        blocked_until: Option<DateTime<Utc>>,
+    ) -> Result<Option<BlockedIp>> {
+        sqlx::query_as!(
+            BlockedIp,
+            r#"
+                INSERT INTO blocked_ips (ip, reason, source, blocked_until)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (ip) DO UPDATE SET
+                    reason = EXCLUDED.reason,
+                    source = EXCLUDED.source,
+                    blocked_until = EXCLUDED.blocked_until,
+                    created_at = NOW()
+                WHERE EXCLUDED.source = 'manual' OR blocked_ips.source = 'auto'
+                RETURNING ip, reason, source as "source: BlockSource", blocked_until, created_at
+            "#,
+            ip,
+            reason,
+            source as BlockSource,
+            blocked_until
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn delete(&self, ip: &str) -> Result<()> {
+        let result = sqlx::query!("DELETE FROM blocked_ips WHERE ip = $1", ip)
+            .execute(&self.pool)
+            .await
+            .map_err(RepositoryError::Database)?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound(format!(
+                "Address '{}' is not blocked",
+                ip
+            )));
+        }
+
+        Ok(())
+    }
+
+    async fn delete_expired(&self) -> Result<u64> {
+        let result = sqlx::query!(
+            "DELETE FROM blocked_ips WHERE blocked_until IS NOT NULL AND blocked_until <= NOW()"
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+}
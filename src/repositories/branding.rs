@@ -0,0 +1,97 @@
+// src/repositories/branding.rs - Per-tenant look of visitor pages
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{Branding, UpdateBrandingDto};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait BrandingRepositoryTrait {
+    /// Finds the branding of a tenant
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant, `None` for the default tenant
+    ///
+    /// ### Returns
+    /// * `Result<Option<Branding>>` - The branding, if the tenant set one
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find(&self, tenant: Option<&Uuid>) -> Result<Option<Branding>>;
+
+    /// Replaces the branding of a tenant, creating it on first use
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant, `None` for the default tenant
+    /// * `dto` - The new branding
+    ///
+    /// ### Returns
+    /// * `Result<Branding>` - The stored branding
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn upsert(&self, tenant: Option<&Uuid>, dto: &UpdateBrandingDto) -> Result<Branding>;
+}
+
+// Implementation using actual database
+pub struct BrandingRepository {
+    pool: PgPool,
+}
+
+impl BrandingRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl BrandingRepositoryTrait for BrandingRepository {
+    async fn find(&self, tenant: Option<&Uuid>) -> Result<Option<Branding>> {
+        sqlx::query_as!(
+            Branding,
+            r#"
+                SELECT logo_url, primary_color, background_color, footer_text,
+                       updated_at AS "updated_at?"
+                FROM branding
+                WHERE tenant_id IS NOT DISTINCT FROM $1
+            "#,
+            tenant
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn upsert(&self, tenant: Option<&Uuid>, dto: &UpdateBrandingDto) -> Result<Branding> {
+        sqlx::query_as!(
+            Branding,
+            r#"
+                INSERT INTO branding (tenant_id, logo_url, primary_color, background_color, footer_text)
+                VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT ((COALESCE(tenant_id, '00000000-0000-0000-0000-000000000000')))
+                DO UPDATE SET
+                    logo_url = EXCLUDED.logo_url,
+                    primary_color = EXCLUDED.primary_color,
+                    background_color = EXCLUDED.background_color,
+                    footer_text = EXCLUDED.footer_text,
+                    updated_at = NOW()
+                RETURNING logo_url, primary_color, background_color, footer_text,
+                          updated_at AS "updated_at?"
+            "#,
+            tenant,
+            dto.logo_url,
+            dto.primary_color,
+            dto.background_color,
+            dto.footer_text
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+}
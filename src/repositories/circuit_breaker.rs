@@ -0,0 +1,381 @@
+// src/repositories/circuit_breaker.rs - Fails fast while the database is down
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{stream::BoxStream, StreamExt};
+use serde_json::Value as JsonValue;
+use uuid::Uuid;
+
+use crate::errors::RepositoryError;
+use crate::models::{
+    LinkStatus, LinkTransfer, NewShortenedUrl, ShortenedUrl, ShortenedUrlQueryParams,
+    ShortenedUrlUpdateParams, TransferStatus, TrashStats, TrashedUrl, UrlRevision,
+};
+use crate::utils::circuit_breaker::CircuitBreaker;
+
+use super::{DbTransaction, ShortenedUrlRepositoryTrait};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+/// Wraps a repository in a circuit breaker
+///
+/// Calls that fail to reach the database count towards opening the breaker. While it is
+/// open, calls return `RepositoryError::Unavailable` right away instead of waiting for a
+/// connection. Errors reported by the database itself (constraint violations, missing
+/// rows) show it is up and don't count.
+pub struct CircuitBreakerRepository<R> {
+    inner: R,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl<R> CircuitBreakerRepository<R> {
+    pub fn new(inner: R, breaker: Arc<CircuitBreaker>) -> Self {
+        Self { inner, breaker }
+    }
+
+    async fn guard<T>(&self, call: impl Future<Output = Result<T>>) -> Result<T> {
+        if !self.breaker.try_acquire() {
+            return Err(unavailable());
+        }
+
+        let result = call.await;
+        match &result {
+            Err(e) if e.is_outage() => self.breaker.record_failure(),
+            _ => self.breaker.record_success(),
+        }
+        result
+    }
+}
+
+fn unavailable() -> RepositoryError {
+    RepositoryError::Unavailable("The database is not reachable, try again shortly".to_string())
+}
+
+#[async_trait]
+impl<R: ShortenedUrlRepositoryTrait + Send + Sync> ShortenedUrlRepositoryTrait
+    for CircuitBreakerRepository<R>
+{
+    async fn save(&self, url: &NewShortenedUrl) -> Result<ShortenedUrl> {
+        self.guard(self.inner.save(url)).await
+    }
+
+    async fn begin(&self) -> Result<DbTransaction> {
+        self.guard(self.inner.begin()).await
+    }
+
+    async fn save_in(&self, tx: &mut DbTransaction, url: &NewShortenedUrl) -> Result<ShortenedUrl> {
+        self.guard(self.inner.save_in(tx, url)).await
+    }
+
+    async fn find(&self, params: &ShortenedUrlQueryParams) -> Result<Vec<ShortenedUrl>> {
+        self.guard(self.inner.find(params)).await
+    }
+
+    fn stream(&self, params: ShortenedUrlQueryParams) -> BoxStream<'static, Result<ShortenedUrl>> {
+        if !self.breaker.try_acquire() {
+            return futures::stream::once(async { Err(unavailable()) }).boxed();
+        }
+
+        // The first item tells whether the database was reached; a stream that ends without
+        // any leaves the breaker as it was
+        let breaker = self.breaker.clone();
+        let mut reported = false;
+        self.inner
+            .stream(params)
+            .inspect(move |row| {
+                if !std::mem::replace(&mut reported, true) {
+                    match row {
+                        Err(e) if e.is_outage() => breaker.record_failure(),
+                        _ => breaker.record_success(),
+                    }
+                }
+            })
+            .boxed()
+    }
+
+    async fn count(&self, params: &ShortenedUrlQueryParams) -> Result<i64> {
+        self.guard(self.inner.count(params)).await
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.find_by_id(id)).await
+    }
+
+    async fn find_remapped_id(&self, old_id: &Uuid) -> Result<Option<Uuid>> {
+        self.guard(self.inner.find_remapped_id(old_id)).await
+    }
+
+    async fn lock_by_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        id: &Uuid,
+    ) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.lock_by_id_in(tx, id)).await
+    }
+
+    async fn find_by_code(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.find_by_code(tenant, namespace, code))
+            .await
+    }
+
+    async fn find_all(
+        &self,
+        tenant: Option<&Uuid>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<ShortenedUrl>> {
+        self.guard(self.inner.find_all(tenant, limit, offset)).await
+    }
+
+    async fn find_by_codes(
+        &self,
+        tenant: Option<&Uuid>,
+        paths: &[String],
+        ignore_case: bool,
+    ) -> Result<Vec<ShortenedUrl>> {
+        self.guard(self.inner.find_by_codes(tenant, paths, ignore_case))
+            .await
+    }
+
+    async fn find_by_code_ignore_case(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.find_by_code_ignore_case(tenant, namespace, code))
+            .await
+    }
+
+    async fn update(
+        &self,
+        id: &Uuid,
+        params: &ShortenedUrlUpdateParams,
+        changed_by: Option<&Uuid>,
+    ) -> Result<u64> {
+        self.guard(self.inner.update(id, params, changed_by)).await
+    }
+
+    async fn update_in(
+        &self,
+        tx: &mut DbTransaction,
+        id: &Uuid,
+        params: &ShortenedUrlUpdateParams,
+        changed_by: Option<&Uuid>,
+    ) -> Result<u64> {
+        self.guard(self.inner.update_in(tx, id, params, changed_by))
+            .await
+    }
+
+    async fn find_revisions(&self, url_id: &Uuid) -> Result<Vec<UrlRevision>> {
+        self.guard(self.inner.find_revisions(url_id)).await
+    }
+
+    async fn find_revision(&self, url_id: &Uuid, revision: i32) -> Result<Option<UrlRevision>> {
+        self.guard(self.inner.find_revision(url_id, revision)).await
+    }
+
+    async fn delete(&self, id: &Uuid, require_exists: bool) -> Result<bool> {
+        self.guard(self.inner.delete(id, require_exists)).await
+    }
+
+    async fn delete_in(&self, tx: &mut DbTransaction, id: &Uuid) -> Result<bool> {
+        self.guard(self.inner.delete_in(tx, id)).await
+    }
+
+    async fn find_expiring(
+        &self,
+        tenant: Option<&Uuid>,
+        within_days: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ShortenedUrl>> {
+        self.guard(self.inner.find_expiring(tenant, within_days, limit, offset))
+            .await
+    }
+
+    async fn find_trash(
+        &self,
+        tenant: Option<&Uuid>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<TrashedUrl>> {
+        self.guard(self.inner.find_trash(tenant, limit, offset))
+            .await
+    }
+
+    async fn find_in_trash(
+        &self,
+        id: &Uuid,
+        tenant: Option<&Uuid>,
+    ) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.find_in_trash(id, tenant)).await
+    }
+
+    async fn restore(&self, id: &Uuid, tenant: Option<&Uuid>) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.restore(id, tenant)).await
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>, limit: i64) -> Result<u64> {
+        self.guard(self.inner.purge_trash(before, limit)).await
+    }
+
+    async fn count_trash(&self, before: DateTime<Utc>) -> Result<TrashStats> {
+        self.guard(self.inner.count_trash(before)).await
+    }
+
+    async fn consume(&self, id: &Uuid) -> Result<bool> {
+        self.guard(self.inner.consume(id)).await
+    }
+
+    async fn record_access(&self, id: &Uuid) -> Result<i64> {
+        self.guard(self.inner.record_access(id)).await
+    }
+
+    async fn add_accesses(
+        &self,
+        id: &Uuid,
+        count: i64,
+        last_accessed: DateTime<Utc>,
+    ) -> Result<i64> {
+        self.guard(self.inner.add_accesses(id, count, last_accessed))
+            .await
+    }
+
+    async fn claim_milestones(&self, id: &Uuid, milestones: &[i64]) -> Result<Vec<i64>> {
+        self.guard(self.inner.claim_milestones(id, milestones))
+            .await
+    }
+
+    async fn find_most_accessed(&self, limit: i64) -> Result<Vec<ShortenedUrl>> {
+        self.guard(self.inner.find_most_accessed(limit)).await
+    }
+
+    async fn dump_rows(&self, limit: i64, offset: i64) -> Result<Vec<JsonValue>> {
+        self.guard(self.inner.dump_rows(limit, offset)).await
+    }
+
+    async fn deactivate_idle(&self) -> Result<u64> {
+        self.guard(self.inner.deactivate_idle()).await
+    }
+
+    async fn archive_unused(&self, before: DateTime<Utc>) -> Result<u64> {
+        self.guard(self.inner.archive_unused(before)).await
+    }
+
+    async fn claim_expired(&self, since: DateTime<Utc>) -> Result<Vec<ShortenedUrl>> {
+        self.guard(self.inner.claim_expired(since)).await
+    }
+
+    async fn update_status(&self, id: &Uuid, status: LinkStatus) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.update_status(id, status)).await
+    }
+
+    async fn set_archived(&self, id: &Uuid, archived: bool) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.set_archived(id, archived)).await
+    }
+
+    async fn find_http_destinations(
+        &self,
+        by_default: bool,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<ShortenedUrl>> {
+        self.guard(self.inner.find_http_destinations(by_default, after, limit))
+            .await
+    }
+
+    async fn find_links_to_check(&self, limit: i64) -> Result<Vec<ShortenedUrl>> {
+        self.guard(self.inner.find_links_to_check(limit)).await
+    }
+
+    async fn record_check(&self, id: &Uuid, status: Option<i32>, broken: bool) -> Result<()> {
+        self.guard(self.inner.record_check(id, status, broken))
+            .await
+    }
+
+    async fn add_favorite(&self, user_id: &Uuid, url_id: &Uuid) -> Result<()> {
+        self.guard(self.inner.add_favorite(user_id, url_id)).await
+    }
+
+    async fn remove_favorite(&self, user_id: &Uuid, url_id: &Uuid) -> Result<bool> {
+        self.guard(self.inner.remove_favorite(user_id, url_id))
+            .await
+    }
+
+    async fn create_claim(
+        &self,
+        url_id: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        self.guard(self.inner.create_claim(url_id, token_hash, expires_at))
+            .await
+    }
+
+    async fn claim(
+        &self,
+        token_hash: &str,
+        user_id: &Uuid,
+        tenant: Option<&Uuid>,
+    ) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.claim(token_hash, user_id, tenant))
+            .await
+    }
+
+    async fn purge_expired_claims(&self) -> Result<u64> {
+        self.guard(self.inner.purge_expired_claims()).await
+    }
+
+    async fn offer_transfer(
+        &self,
+        url_id: &Uuid,
+        from_user_id: &Uuid,
+        to_user_id: &Uuid,
+    ) -> Result<Option<LinkTransfer>> {
+        self.guard(self.inner.offer_transfer(url_id, from_user_id, to_user_id))
+            .await
+    }
+
+    async fn find_transfer(&self, id: &Uuid) -> Result<Option<LinkTransfer>> {
+        self.guard(self.inner.find_transfer(id)).await
+    }
+
+    async fn find_pending_transfers(&self, to_user_id: &Uuid) -> Result<Vec<LinkTransfer>> {
+        self.guard(self.inner.find_pending_transfers(to_user_id))
+            .await
+    }
+
+    async fn find_transfers(&self, url_id: &Uuid) -> Result<Vec<LinkTransfer>> {
+        self.guard(self.inner.find_transfers(url_id)).await
+    }
+
+    async fn accept_transfer(&self, id: &Uuid, user_id: &Uuid) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.accept_transfer(id, user_id)).await
+    }
+
+    async fn close_transfer(
+        &self,
+        id: &Uuid,
+        status: TransferStatus,
+    ) -> Result<Option<LinkTransfer>> {
+        self.guard(self.inner.close_transfer(id, status)).await
+    }
+
+    async fn force_transfer(
+        &self,
+        url_id: &Uuid,
+        to_user_id: Option<&Uuid>,
+        to_tenant: Option<&Uuid>,
+    ) -> Result<Option<ShortenedUrl>> {
+        self.guard(self.inner.force_transfer(url_id, to_user_id, to_tenant))
+            .await
+    }
+}
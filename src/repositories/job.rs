@@ -0,0 +1,389 @@
+// src/repositories/job.rs - Scheduled job bookkeeping
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::{pool::PoolConnection, PgPool, Postgres};
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{JobRecord, JobStatus};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+// First key of the job advisory locks, the second being the hashed job name
+const JOB_LOCK_NAMESPACE: i32 = 0x6a6f6273;
+
+/// Exclusive right to run a job, held by one instance at a time
+///
+/// Backed by a Postgres session advisory lock on a connection of its own, released with
+/// [`JobRepositoryTrait::release`]. If that connection is lost, the lock goes with it and
+/// another instance may take the job, which is why outcomes carry the fencing token.
+pub struct JobLease {
+    pub name: String,
+    /// Token issued with the lock, higher than any issued before
+    pub fence: i64,
+    /// When the job is due according to the last run, which may have happened elsewhere
+    pub next_run_at: Option<DateTime<Utc>>,
+    connection: Option<PoolConnection<Postgres>>,
+}
+
+impl Drop for JobLease {
+    fn drop(&mut self) {
+        // Dropped without being released, e.g. after a panic: closing the connection frees
+        // the lock instead of handing it to the next pool user
+        if let Some(connection) = self.connection.as_mut() {
+            connection.close_on_drop();
+        }
+    }
+}
+
+#[async_trait]
+pub trait JobRepositoryTrait {
+    /// Registers a job at startup, keeping its history
+    ///
+    /// The stored next run is kept unless the schedule changed, so restarts don't run jobs
+    /// early. A run left `running` while no instance holds the job lock is marked
+    /// `interrupted`.
+    ///
+    /// ### Arguments
+    /// * `name` - The job name
+    /// * `schedule` - The schedule expression
+    /// * `next_run_at` - When the job runs next according to the schedule, if ever
+    ///
+    /// ### Returns
+    /// * `Result<JobRecord>` - The stored job
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn register(
+        &self,
+        name: &str,
+        schedule: &str,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<JobRecord>;
+
+    /// Takes the lock of a job without waiting, issuing a new fencing token
+    ///
+    /// A run left `running` is marked `interrupted`, as its instance no longer holds the lock.
+    ///
+    /// ### Arguments
+    /// * `name` - The job name
+    ///
+    /// ### Returns
+    /// * `Result<Option<JobLease>>` - The lease, or None if another instance holds the lock
+    ///
+    /// ### Errors
+    /// * `RepositoryError::NotFound` - If the job isn't registered
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn acquire(&self, name: &str) -> Result<Option<JobLease>>;
+
+    /// Releases the lock of a job
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If the lock couldn't be released; the connection
+    ///   holding it is closed, which releases it as well
+    async fn release(&self, lease: JobLease) -> Result<()>;
+
+    /// Records the start of a run
+    ///
+    /// ### Returns
+    /// * `Result<bool>` - false if a newer lease was issued since, so the run must not start
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn mark_started(&self, lease: &JobLease) -> Result<bool>;
+
+    /// Records the outcome of a run and when the job runs next
+    ///
+    /// ### Arguments
+    /// * `lease` - The lease the job ran under
+    /// * `status` - `Succeeded` or `Failed`
+    /// * `message` - Summary of the run, or its error
+    /// * `next_run_at` - When the job runs next, if ever
+    ///
+    /// ### Returns
+    /// * `Result<bool>` - false if the outcome was ignored, a newer lease having been issued
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn mark_finished(
+        &self,
+        lease: &JobLease,
+        status: JobStatus,
+        message: &str,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<bool>;
+
+    /// Finds all registered jobs
+    ///
+    /// ### Returns
+    /// * `Result<Vec<JobRecord>>` - The jobs ordered by name
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_all(&self) -> Result<Vec<JobRecord>>;
+}
+
+// Implementation using actual database
+pub struct JobRepository {
+    pool: PgPool,
+    schema: Option<String>,
+}
+
+impl JobRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+            schema: db.schema().map(String::from),
+        }
+    }
+
+    // Advisory locks span the database, so environments in other schemas lock their own key
+    fn lock_key(&self, name: &str) -> String {
+        match &self.schema {
+            Some(schema) => format!("{}.{}", schema, name),
+            None => name.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl JobRepositoryTrait for JobRepository {
+    async fn register(
+        &self,
+        name: &str,
+        schedule: &str,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<JobRecord> {
+        let mut tx = self.pool.begin().await.map_err(RepositoryError::Database)?;
+
+        // Only an instance holding the lock may be running the job
+        let unlocked = sqlx::query_scalar!(
+            r#"SELECT pg_try_advisory_xact_lock($1, hashtext($2)) as "unlocked!""#,
+            JOB_LOCK_NAMESPACE,
+            self.lock_key(name)
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        let record = sqlx::query_as!(
+            JobRecord,
+            r#"
+                INSERT INTO jobs (name, schedule, next_run_at)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (name) DO UPDATE SET
+                    schedule = EXCLUDED.schedule,
+                    next_run_at = CASE
+                        WHEN jobs.schedule = EXCLUDED.schedule AND jobs.next_run_at IS NOT NULL
+                        THEN jobs.next_run_at
+                        ELSE EXCLUDED.next_run_at
+                    END,
+                    status = CASE
+                        WHEN jobs.status = 'running' AND $4 THEN 'interrupted'
+                        ELSE jobs.status
+                    END,
+                    updated_at = NOW()
+                RETURNING name, schedule, status as "status: JobStatus", last_started_at,
+                    last_finished_at, last_message, next_run_at, run_count, fence
+            "#,
+            name,
+            schedule,
+            next_run_at,
+            unlocked
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        tx.commit().await.map_err(RepositoryError::Database)?;
+        Ok(record)
+    }
+
+    async fn acquire(&self, name: &str) -> Result<Option<JobLease>> {
+        let mut connection = self
+            .pool
+            .acquire()
+            .await
+            .map_err(RepositoryError::Database)?;
+
+        let locked = sqlx::query_scalar!(
+            r#"SELECT pg_try_advisory_lock($1, hashtext($2)) as "locked!""#,
+            JOB_LOCK_NAMESPACE,
+            self.lock_key(name)
+        )
+        .fetch_one(&mut *connection)
+        .await
+        .map_err(RepositoryError::Database)?;
+        if !locked {
+            return Ok(None);
+        }
+
+        // From here on dropping the lease closes the connection, releasing the lock
+        let mut lease = JobLease {
+            name: name.to_string(),
+            fence: 0,
+            next_run_at: None,
+            connection: Some(connection),
+        };
+        let record = sqlx::query!(
+            r#"
+                UPDATE jobs
+                SET fence = fence + 1,
+                    status = CASE WHEN status = 'running' THEN 'interrupted' ELSE status END,
+                    updated_at = NOW()
+                WHERE name = $1
+                RETURNING fence, next_run_at
+            "#,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?
+        .ok_or_else(|| RepositoryError::NotFound(format!("Job '{}' is not registered", name)))?;
+
+        lease.fence = record.fence;
+        lease.next_run_at = record.next_run_at;
+        Ok(Some(lease))
+    }
+
+    async fn release(&self, mut lease: JobLease) -> Result<()> {
+        let Some(mut connection) = lease.connection.take() else {
+            return Ok(());
+        };
+
+        let unlocked = sqlx::query_scalar!(
+            "SELECT pg_advisory_unlock($1, hashtext($2))",
+            JOB_LOCK_NAMESPACE,
+            self.lock_key(&lease.name)
+        )
+        .fetch_one(&mut *connection)
+        .await;
+        if let Err(e) = unlocked {
+            let _ = connection.close().await;
+            return Err(RepositoryError::Database(e));
+        }
+
+        Ok(())
+    }
+
+    async fn mark_started(&self, lease: &JobLease) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE jobs
+                SET status = 'running', last_started_at = NOW(), run_count = run_count + 1,
+                    updated_at = NOW()
+                WHERE name = $1 AND fence = $2
+            "#,
+            lease.name,
+            lease.fence
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn mark_finished(
+        &self,
+        lease: &JobLease,
+        status: JobStatus,
+        message: &str,
+        next_run_at: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE jobs
+                SET status = $3, last_finished_at = NOW(), last_message = $4, next_run_at = $5,
+                    updated_at = NOW()
+                WHERE name = $1 AND fence = $2
+            "#,
+            lease.name,
+            lease.fence,
+            status as JobStatus,
+            message,
+            next_run_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_all(&self) -> Result<Vec<JobRecord>> {
+        sqlx::query_as!(
+            JobRecord,
+            r#"
+                SELECT name, schedule, status as "status: JobStatus", last_started_at,
+                    last_finished_at, last_message, next_run_at, run_count, fence
+                FROM jobs
+                ORDER BY name
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repository(pool: &PgPool) -> JobRepository {
+        JobRepository::new(Database::from_pool(pool.clone()))
+    }
+
+    #[sqlx::test]
+    async fn lease_is_exclusive_until_released(pool: PgPool) {
+        let other_instance = repository(&pool);
+        let repository = repository(&pool);
+        repository
+            .register("cleanup", "@every 1h", None)
+            .await
+            .unwrap();
+
+        let lease = repository.acquire("cleanup").await.unwrap().unwrap();
+        assert!(other_instance.acquire("cleanup").await.unwrap().is_none());
+
+        repository.release(lease).await.unwrap();
+        let lease = other_instance.acquire("cleanup").await.unwrap().unwrap();
+        assert_eq!(lease.fence, 2);
+        other_instance.release(lease).await.unwrap();
+    }
+
+    #[sqlx::test]
+    async fn stale_fence_is_rejected_after_reacquisition(pool: PgPool) {
+        let repository = repository(&pool);
+        repository
+            .register("cleanup", "@every 1h", None)
+            .await
+            .unwrap();
+
+        // A run still going after its instance lost the lock
+        let lost = repository.acquire("cleanup").await.unwrap().unwrap();
+        let stale = JobLease {
+            name: lost.name.clone(),
+            fence: lost.fence,
+            next_run_at: None,
+            connection: None,
+        };
+        repository.release(lost).await.unwrap();
+
+        let current = repository.acquire("cleanup").await.unwrap().unwrap();
+        assert!(current.fence > stale.fence);
+        assert!(!repository.mark_started(&stale).await.unwrap());
+        let finished = repository
+            .mark_finished(&stale, JobStatus::Succeeded, "late", None)
+            .await
+            .unwrap();
+        assert!(!finished);
+
+        assert!(repository.mark_started(&current).await.unwrap());
+        let record = repository.find_all().await.unwrap().remove(0);
+        assert_eq!(record.status, JobStatus::Running);
+        assert_eq!(record.last_message, None);
+        repository.release(current).await.unwrap();
+    }
+}
@@ -0,0 +1,303 @@
+// src/repositories/link_flag.rs - Click anomaly detection and link flags
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{ClickSpike, FlagAction, LinkFlag, LinkFlagQueryParams};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait LinkFlagRepositoryTrait {
+    /// Finds unflagged links with many clicks from few IPs since a point in time
+    ///
+    /// Links whose flag was resolved after `since` are skipped.
+    ///
+    /// ### Arguments
+    /// * `since` - Start of the detection window
+    /// * `min_clicks` - Clicks in the window needed to count as a spike
+    /// * `max_distinct_ips` - Spikes from more IPs than this are treated as organic
+    ///
+    /// ### Returns
+    /// * `Result<Vec<ClickSpike>>` - The suspicious links
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_click_spikes(
+        &self,
+        since: DateTime<Utc>,
+        min_clicks: i64,
+        max_distinct_ips: i64,
+    ) -> Result<Vec<ClickSpike>>;
+
+    /// Flags a link, optionally suspending its redirects, unless it already has an open flag
+    ///
+    /// An active link becomes `LinkStatus::Flagged` along with it.
+    ///
+    /// ### Arguments
+    /// * `spike` - The traffic that triggered the flag
+    /// * `reason` - Human readable explanation
+    /// * `action` - What the detector did about it
+    /// * `window_seconds` - Length of the detection window
+    /// * `throttled_until` - Suspend redirects until then, for `FlagAction::Throttle`
+    ///
+    /// ### Returns
+    /// * `Result<Option<LinkFlag>>` - The new flag, or `None` if the link was already flagged
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn create_flag(
+        &self,
+        spike: &ClickSpike,
+        reason: &str,
+        action: FlagAction,
+        window_seconds: i32,
+        throttled_until: Option<DateTime<Utc>>,
+    ) -> Result<Option<LinkFlag>>;
+
+    /// Finds flags, newest first
+    ///
+    /// ### Arguments
+    /// * `params` - Resolution filter and pagination
+    ///
+    /// ### Returns
+    /// * `Result<Vec<LinkFlag>>` - The matching flags
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_flags(&self, params: &LinkFlagQueryParams) -> Result<Vec<LinkFlag>>;
+
+    /// Resolves an open flag and lifts any throttle it put on the link
+    ///
+    /// A link still `LinkStatus::Flagged` becomes active again; one an admin moved to
+    /// another status keeps it.
+    ///
+    /// ### Arguments
+    /// * `id` - The flag to resolve
+    ///
+    /// ### Returns
+    /// * `Result<LinkFlag>` - The resolved flag
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::NotFound` - If there is no open flag with this ID
+    async fn resolve_flag(&self, id: i64) -> Result<LinkFlag>;
+}
+
+// Implementation using actual database
+pub struct LinkFlagRepository {
+    pool: PgPool,
+}
+
+impl LinkFlagRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl LinkFlagRepositoryTrait for LinkFlagRepository {
+    async fn find_click_spikes(
+        &self,
+        since: DateTime<Utc>,
+        min_clicks: i64,
+        max_distinct_ips: i64,
+    ) -> Result<Vec<ClickSpike>> {
+        sqlx::query_as!(
+            ClickSpike,
+            r#"
+                SELECT
+                    v.url_id,
+                    CONCAT_WS('/', s.namespace, s.short_code) AS "short_code!",
+                    s.created_by,
+                    COUNT(*) AS "clicks!",
+                    COUNT(DISTINCT v.ip_address) AS "distinct_ips!"
+                FROM url_visits v
+                JOIN shortened_urls s ON s.id = v.url_id
+                WHERE v.visited_at >= $1
+                  AND NOT EXISTS (
+                      -- Skip open flags, and flags resolved during the window so the
+                      -- clicks an admin already reviewed don't flag the link again
+                      SELECT 1 FROM link_flags f
+                      WHERE f.url_id = v.url_id AND (f.resolved_at IS NULL OR f.resolved_at >= $1)
+                  )
+                GROUP BY v.url_id, s.namespace, s.short_code, s.created_by
+                HAVING COUNT(*) >= $2 AND COUNT(DISTINCT v.ip_address) <= $3
+            "#,
+            since,
+            min_clicks,
+            max_distinct_ips
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn create_flag(
+        &self,
+        spike: &ClickSpike,
+        reason: &str,
+        action: FlagAction,
+        window_seconds: i32,
+        throttled_until: Option<DateTime<Utc>>,
+    ) -> Result<Option<LinkFlag>> {
+        // Flag and throttle in one statement so a link is never throttled without a flag
+        sqlx::query_as!(
+            LinkFlag,
+            r#"
+                WITH flag AS (
+                    INSERT INTO link_flags (url_id, reason, action, clicks, distinct_ips, window_seconds)
+                    VALUES ($1, $2, $3, $4, $5, $6)
+                    ON CONFLICT (url_id) WHERE resolved_at IS NULL DO NOTHING
+                    RETURNING *
+                ), flagged AS (
+                    -- Blocked links and links awaiting review already don't redirect
+                    UPDATE shortened_urls
+                    SET throttled_until = COALESCE($7, throttled_until),
+                        status = CASE WHEN status = 'active' THEN 'flagged' ELSE status END
+                    WHERE id = $1
+                      AND EXISTS (SELECT 1 FROM flag)
+                )
+                SELECT id, url_id, reason, action as "action: FlagAction", clicks, distinct_ips,
+                       window_seconds, flagged_at, resolved_at
+                FROM flag
+            "#,
+            spike.url_id,
+            reason,
+            action as FlagAction,
+            spike.clicks,
+            spike.distinct_ips,
+            window_seconds,
+            throttled_until
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn find_flags(&self, params: &LinkFlagQueryParams) -> Result<Vec<LinkFlag>> {
+        sqlx::query_as!(
+            LinkFlag,
+            r#"
+                SELECT id, url_id, reason, action as "action: FlagAction", clicks, distinct_ips,
+                       window_seconds, flagged_at, resolved_at
+                FROM link_flags
+                WHERE $1::BOOLEAN IS NULL OR (resolved_at IS NOT NULL) = $1
+                ORDER BY flagged_at DESC, id DESC
+                LIMIT $2 OFFSET $3
+            "#,
+            params.resolved,
+            params.limit.unwrap_or(50),
+            params.offset.unwrap_or(0)
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn resolve_flag(&self, id: i64) -> Result<LinkFlag> {
+        sqlx::query_as!(
+            LinkFlag,
+            r#"
+                WITH flag AS (
+                    UPDATE link_flags
+                    SET resolved_at = NOW()
+                    WHERE id = $1 AND resolved_at IS NULL
+                    RETURNING *
+                ), released AS (
+                    UPDATE shortened_urls
+                    SET throttled_until = NULL,
+                        status = CASE WHEN status = 'flagged' THEN 'active' ELSE status END
+                    WHERE id IN (SELECT url_id FROM flag)
+                )
+                SELECT id, url_id, reason, action as "action: FlagAction", clicks, distinct_ips,
+                       window_seconds, flagged_at, resolved_at
+                FROM flag
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?
+        .ok_or_else(|| RepositoryError::NotFound(format!("No open flag with ID '{}'", id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::db::testing::insert_link;
+    use crate::models::LinkStatus;
+
+    async fn visit(pool: &PgPool, url_id: Uuid, ip: &str, times: usize) {
+        for _ in 0..times {
+            sqlx::query("INSERT INTO url_visits (url_id, ip_address) VALUES ($1, $2)")
+                .bind(url_id)
+                .bind(ip)
+                .execute(pool)
+                .await
+                .unwrap();
+        }
+    }
+
+    async fn link_state(pool: &PgPool, id: Uuid) -> (LinkStatus, Option<DateTime<Utc>>) {
+        sqlx::query_as("SELECT status, throttled_until FROM shortened_urls WHERE id = $1")
+            .bind(id)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_spike_is_flagged_once_and_throttle_lifted_on_resolve(pool: PgPool) {
+        let repository = LinkFlagRepository::new(Database::from_pool(pool.clone()));
+        let bot = insert_link(&pool, "bot").await;
+        let organic = insert_link(&pool, "organic").await;
+        visit(&pool, bot, "203.0.113.9", 30).await;
+        for i in 0..30 {
+            visit(&pool, organic, &format!("198.51.100.{}", i), 1).await;
+        }
+
+        let since = Utc::now() - Duration::minutes(5);
+        let spikes = repository.find_click_spikes(since, 20, 2).await.unwrap();
+        assert_eq!(spikes.len(), 1);
+        let spike = &spikes[0];
+        assert_eq!(
+            (spike.url_id, spike.clicks, spike.distinct_ips),
+            (bot, 30, 1)
+        );
+
+        let until = Utc::now() + Duration::minutes(10);
+        let flag = repository
+            .create_flag(spike, "burst", FlagAction::Throttle, 300, Some(until))
+            .await
+            .unwrap()
+            .expect("first flag is created");
+        let (status, throttled_until) = link_state(&pool, bot).await;
+        assert_eq!(status, LinkStatus::Flagged);
+        assert!(throttled_until.is_some());
+
+        // An open flag isn't duplicated, and keeps the link out of later detections
+        let again = repository.create_flag(spike, "burst", FlagAction::Throttle, 300, Some(until));
+        assert!(again.await.unwrap().is_none());
+        assert!(repository
+            .find_click_spikes(since, 20, 2)
+            .await
+            .unwrap()
+            .is_empty());
+
+        repository.resolve_flag(flag.id).await.unwrap();
+        assert_eq!(link_state(&pool, bot).await, (LinkStatus::Active, None));
+        assert!(matches!(
+            repository.resolve_flag(flag.id).await,
+            Err(RepositoryError::NotFound(_))
+        ));
+    }
+}
@@ -1,3 +1,55 @@
+pub mod account;
+pub mod analytics;
+pub mod app_links;
+pub mod blocked_ip;
+pub mod branding;
+pub mod circuit_breaker;
+pub mod job;
+pub mod link_flag;
+pub mod notification;
+pub mod oauth;
+pub mod saved_search;
+pub mod service_token;
+pub mod shard;
 pub mod shortened_url;
+pub mod snapshot;
+pub mod stats;
+pub mod tenant;
+pub mod user;
 
+use sqlx::{Postgres, Transaction};
+
+use crate::errors::RepositoryError;
+
+/// A transaction shared by several repository calls, so multi-step operations are atomic
+///
+/// Start one with a repository's `begin`, pass it to the `*_in` methods and finish
+/// with [`commit`]. Dropping it without committing rolls everything back.
+pub type DbTransaction = Transaction<'static, Postgres>;
+
+/// Commits a transaction started by a repository
+pub async fn commit(tx: DbTransaction) -> Result<(), RepositoryError> {
+    tx.commit().await.map_err(|e| {
+        log::error!("Failed to commit transaction: {}", e);
+        RepositoryError::Database(e)
+    })
+}
+
+pub use account::{AccountRepository, AccountRepositoryTrait};
+pub use analytics::{AnalyticsRepository, AnalyticsRepositoryTrait};
+pub use app_links::{AppLinksRepository, AppLinksRepositoryTrait};
+pub use blocked_ip::{BlockedIpRepository, BlockedIpRepositoryTrait};
+pub use branding::{BrandingRepository, BrandingRepositoryTrait};
+pub use circuit_breaker::CircuitBreakerRepository;
+pub use job::{JobLease, JobRepository, JobRepositoryTrait};
+pub use link_flag::{LinkFlagRepository, LinkFlagRepositoryTrait};
+pub use notification::{NotificationRepository, NotificationRepositoryTrait};
+pub use oauth::{OAuthRepository, OAuthRepositoryTrait};
+pub use saved_search::{SavedSearchRepository, SavedSearchRepositoryTrait};
+pub use service_token::{ServiceTokenRepository, ServiceTokenRepositoryTrait};
+pub use shard::{shard_bucket, ShardMap};
 pub use shortened_url::{ShortenedUrlRepository, ShortenedUrlRepositoryTrait};
+pub use snapshot::{SnapshotRepository, SnapshotRepositoryTrait};
+pub use stats::{StatsRepository, StatsRepositoryTrait};
+pub use tenant::{TenantRepository, TenantRepositoryTrait};
+pub use user::{UserRepository, UserRepositoryTrait};
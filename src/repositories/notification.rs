@@ -0,0 +1,517 @@
+// src/repositories/notification.rs - Notification preferences and delivery log
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{
+    ChannelKind, CreateNotificationChannelDto, DigestFrequency, DigestRecipient, ExpiringLink,
+    NotificationChannel, NotificationEventKind, NotificationPreferences, ReachedMilestone,
+    UpdateNotificationPreferencesDto,
+};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait NotificationRepositoryTrait {
+    /// Finds the stored notification preferences of a user
+    ///
+    /// ### Arguments
+    /// * `user_id` - The user whose preferences to find
+    ///
+    /// ### Returns
+    /// * `Result<Option<NotificationPreferences>>` - The preferences, or `None` if never saved
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_preferences(&self, user_id: &Uuid) -> Result<Option<NotificationPreferences>>;
+
+    /// Creates or updates the notification preferences of a user
+    ///
+    /// ### Arguments
+    /// * `user_id` - The user whose preferences to save
+    /// * `dto` - The fields to change
+    /// * `default_notice_days` - Notice period to store when the user has no preferences yet
+    ///
+    /// ### Returns
+    /// * `Result<NotificationPreferences>` - The saved preferences
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn upsert_preferences(
+        &self,
+        user_id: &Uuid,
+        dto: &UpdateNotificationPreferencesDto,
+        default_notice_days: i32,
+    ) -> Result<NotificationPreferences>;
+
+    /// Finds owned links expiring within their owner's notice period that haven't been notified
+    ///
+    /// Owners who turned expiry emails off are still returned so their team channels hear about it.
+    ///
+    /// ### Arguments
+    /// * `default_notice_days` - Notice period for owners without saved preferences
+    /// * `limit` - The maximum number of links to return
+    ///
+    /// ### Returns
+    /// * `Result<Vec<ExpiringLink>>` - The links, soonest expiry first
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_unnotified_expiring_links(
+        &self,
+        default_notice_days: i32,
+        limit: i64,
+    ) -> Result<Vec<ExpiringLink>>;
+
+    /// Records that the owner was told about a link's upcoming expiry
+    ///
+    /// ### Arguments
+    /// * `url_id` - The link that was notified
+    /// * `expires_at` - The expiry the notice was about
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn mark_expiry_notified(&self, url_id: &Uuid, expires_at: DateTime<Utc>) -> Result<()>;
+
+    /// Finds click milestones reached by links that haven't been notified yet
+    ///
+    /// ### Arguments
+    /// * `limit` - The maximum number of milestones to return
+    ///
+    /// ### Returns
+    /// * `Result<Vec<ReachedMilestone>>` - The milestones, earliest reached first
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_unnotified_milestones(&self, limit: i64) -> Result<Vec<ReachedMilestone>>;
+
+    /// Records that a link's milestone was notified
+    ///
+    /// ### Arguments
+    /// * `url_id` - The link that reached the milestone
+    /// * `milestone` - The click count reached
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn mark_milestone_notified(&self, url_id: &Uuid, milestone: i64) -> Result<()>;
+
+    /// Finds link owners due a digest for a period who haven't received it yet
+    ///
+    /// Owners without saved preferences get weekly digests; unsubscribed owners are skipped.
+    ///
+    /// ### Arguments
+    /// * `frequency` - The digest frequency of the period
+    /// * `period_start` - First day of the period
+    /// * `limit` - The maximum number of owners to return
+    ///
+    /// ### Returns
+    /// * `Result<Vec<DigestRecipient>>` - The owners to send the digest to
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_digest_recipients(
+        &self,
+        frequency: DigestFrequency,
+        period_start: NaiveDate,
+        limit: i64,
+    ) -> Result<Vec<DigestRecipient>>;
+
+    /// Records that a user's digest for a period was handled
+    ///
+    /// ### Arguments
+    /// * `user_id` - The owner the digest was for
+    /// * `frequency` - The digest frequency of the period
+    /// * `period_start` - First day of the period
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn mark_digest_sent(
+        &self,
+        user_id: &Uuid,
+        frequency: DigestFrequency,
+        period_start: NaiveDate,
+    ) -> Result<()>;
+
+    /// Adds a notification channel for a user
+    ///
+    /// ### Arguments
+    /// * `user_id` - The owner of the channel
+    /// * `dto` - The channel details
+    ///
+    /// ### Returns
+    /// * `Result<NotificationChannel>` - The stored channel
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn create_channel(
+        &self,
+        user_id: &Uuid,
+        dto: &CreateNotificationChannelDto,
+    ) -> Result<NotificationChannel>;
+
+    /// Finds all notification channels of a user
+    ///
+    /// ### Arguments
+    /// * `user_id` - The owner of the channels
+    ///
+    /// ### Returns
+    /// * `Result<Vec<NotificationChannel>>` - The channels, oldest first
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_channels_by_user(&self, user_id: &Uuid) -> Result<Vec<NotificationChannel>>;
+
+    /// Finds a user's enabled channels subscribed to an event kind
+    ///
+    /// ### Arguments
+    /// * `user_id` - The owner of the channels
+    /// * `kind` - The event being delivered
+    ///
+    /// ### Returns
+    /// * `Result<Vec<NotificationChannel>>` - The channels to deliver to
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_channels_for_event(
+        &self,
+        user_id: &Uuid,
+        kind: NotificationEventKind,
+    ) -> Result<Vec<NotificationChannel>>;
+
+    /// Deletes a user's notification channel
+    ///
+    /// ### Arguments
+    /// * `id` - The channel to delete
+    /// * `user_id` - The owner, so users can't delete each other's channels
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::NotFound` - If the user has no such channel
+    async fn delete_channel(&self, id: &Uuid, user_id: &Uuid) -> Result<()>;
+}
+
+// Implementation using actual database
+pub struct NotificationRepository {
+    pool: PgPool,
+}
+
+impl NotificationRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl NotificationRepositoryTrait for NotificationRepository {
+    async fn find_preferences(&self, user_id: &Uuid) -> Result<Option<NotificationPreferences>> {
+        sqlx::query_as!(
+            NotificationPreferences,
+            r#"
+                SELECT
+                    user_id,
+                    expiry_emails,
+                    expiry_notice_days,
+                    digest_frequency AS "digest_frequency: DigestFrequency",
+                    digest_unsubscribed,
+                    updated_at
+                FROM notification_preferences
+                WHERE user_id = $1
+            "#,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn upsert_preferences(
+        &self,
+        user_id: &Uuid,
+        dto: &UpdateNotificationPreferencesDto,
+        default_notice_days: i32,
+    ) -> Result<NotificationPreferences> {
+        sqlx::query_as!(
+            NotificationPreferences,
+            r#"
+                INSERT INTO notification_preferences (
+                    user_id, expiry_emails, expiry_notice_days, digest_frequency, digest_unsubscribed
+                )
+                VALUES (
+                    $1,
+                    COALESCE($2::BOOLEAN, TRUE),
+                    COALESCE($3::INTEGER, $4::INTEGER),
+                    COALESCE($5::TEXT, 'weekly'),
+                    COALESCE($6::BOOLEAN, FALSE)
+                )
+                ON CONFLICT (user_id) DO UPDATE SET
+                    expiry_emails = COALESCE($2::BOOLEAN, notification_preferences.expiry_emails),
+                    expiry_notice_days = COALESCE($3::INTEGER, notification_preferences.expiry_notice_days),
+                    digest_frequency = COALESCE($5::TEXT, notification_preferences.digest_frequency),
+                    digest_unsubscribed = COALESCE($6::BOOLEAN, notification_preferences.digest_unsubscribed),
+                    updated_at = NOW()
+                RETURNING
+                    user_id,
+                    expiry_emails,
+                    expiry_notice_days,
+                    digest_frequency AS "digest_frequency: DigestFrequency",
+                    digest_unsubscribed,
+                    updated_at
+            "#,
+            user_id,
+            dto.expiry_emails,
+            dto.expiry_notice_days,
+            default_notice_days,
+            dto.digest_frequency.map(|frequency| frequency.as_str()),
+            dto.digest_unsubscribed
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn find_unnotified_expiring_links(
+        &self,
+        default_notice_days: i32,
+        limit: i64,
+    ) -> Result<Vec<ExpiringLink>> {
+        sqlx::query_as!(
+            ExpiringLink,
+            r#"
+                SELECT
+                    s.id AS url_id,
+                    u.id AS owner_id,
+                    CONCAT_WS('/', s.namespace, s.short_code) AS "short_code!",
+                    s.original_url,
+                    s.expires_at AS "expires_at!",
+                    u.email AS owner_email,
+                    u.display_name AS owner_name,
+                    COALESCE(p.expiry_emails, TRUE) AS "email_enabled!",
+                    t.base_url AS "base_url?"
+                FROM shortened_urls s
+                JOIN users u ON u.id = s.created_by
+                LEFT JOIN notification_preferences p ON p.user_id = u.id
+                LEFT JOIN tenants t ON t.id = s.tenant_id
+                WHERE s.is_active
+                  AND s.expires_at > NOW()
+                  AND s.expires_at <= NOW() + make_interval(days => COALESCE(p.expiry_notice_days, $1))
+                  AND NOT EXISTS (
+                      SELECT 1 FROM expiry_notifications n
+                      WHERE n.url_id = s.id AND n.expires_at = s.expires_at
+                  )
+                ORDER BY s.expires_at
+                LIMIT $2
+            "#,
+            default_notice_days,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn mark_expiry_notified(&self, url_id: &Uuid, expires_at: DateTime<Utc>) -> Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO expiry_notifications (url_id, expires_at)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+            "#,
+            url_id,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(())
+    }
+
+    async fn find_unnotified_milestones(&self, limit: i64) -> Result<Vec<ReachedMilestone>> {
+        sqlx::query_as!(
+            ReachedMilestone,
+            r#"
+                SELECT
+                    m.url_id,
+                    s.created_by AS owner_id,
+                    CONCAT_WS('/', s.namespace, s.short_code) AS "short_code!",
+                    s.original_url,
+                    m.milestone,
+                    m.reached_at,
+                    t.base_url AS "base_url?"
+                FROM link_milestones m
+                JOIN shortened_urls s ON s.id = m.url_id
+                LEFT JOIN tenants t ON t.id = s.tenant_id
+                WHERE m.notified_at IS NULL
+                ORDER BY m.reached_at
+                LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn mark_milestone_notified(&self, url_id: &Uuid, milestone: i64) -> Result<()> {
+        sqlx::query!(
+            r#"
+                UPDATE link_milestones
+                SET notified_at = NOW()
+                WHERE url_id = $1 AND milestone = $2
+            "#,
+            url_id,
+            milestone
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(())
+    }
+
+    async fn find_digest_recipients(
+        &self,
+        frequency: DigestFrequency,
+        period_start: NaiveDate,
+        limit: i64,
+    ) -> Result<Vec<DigestRecipient>> {
+        sqlx::query_as!(
+            DigestRecipient,
+            r#"
+                SELECT u.id AS user_id, u.email, u.display_name AS name
+                FROM users u
+                LEFT JOIN notification_preferences p ON p.user_id = u.id
+                WHERE COALESCE(p.digest_frequency, 'weekly') = $1
+                  AND NOT COALESCE(p.digest_unsubscribed, FALSE)
+                  AND EXISTS (SELECT 1 FROM shortened_urls s WHERE s.created_by = u.id)
+                  AND NOT EXISTS (
+                      SELECT 1 FROM digest_deliveries d
+                      WHERE d.user_id = u.id AND d.frequency = $1 AND d.period_start = $2
+                  )
+                ORDER BY u.id
+                LIMIT $3
+            "#,
+            frequency.as_str(),
+            period_start,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn mark_digest_sent(
+        &self,
+        user_id: &Uuid,
+        frequency: DigestFrequency,
+        period_start: NaiveDate,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO digest_deliveries (user_id, frequency, period_start)
+                VALUES ($1, $2, $3)
+                ON CONFLICT DO NOTHING
+            "#,
+            user_id,
+            frequency.as_str(),
+            period_start
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(())
+    }
+
+    async fn create_channel(
+        &self,
+        user_id: &Uuid,
+        dto: &CreateNotificationChannelDto,
+    ) -> Result<NotificationChannel> {
+        let events = dto
+            .events
+            .iter()
+            .flatten()
+            .map(|kind| kind.as_str().to_string())
+            .collect::<Vec<_>>();
+
+        sqlx::query_as!(
+            NotificationChannel,
+            r#"
+                INSERT INTO notification_channels (user_id, kind, target_url, events)
+                VALUES ($1, $2, $3, $4)
+                RETURNING id, user_id, kind as "kind: ChannelKind", target_url, events, enabled, created_at
+            "#,
+            user_id,
+            dto.kind as ChannelKind,
+            dto.target_url,
+            &events
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn find_channels_by_user(&self, user_id: &Uuid) -> Result<Vec<NotificationChannel>> {
+        sqlx::query_as!(
+            NotificationChannel,
+            r#"
+                SELECT id, user_id, kind as "kind: ChannelKind", target_url, events, enabled, created_at
+                FROM notification_channels
+                WHERE user_id = $1
+                ORDER BY created_at
+            "#,
+            user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_channels_for_event(
+        &self,
+        user_id: &Uuid,
+        kind: NotificationEventKind,
+    ) -> Result<Vec<NotificationChannel>> {
+        sqlx::query_as!(
+            NotificationChannel,
+            r#"
+                SELECT id, user_id, kind as "kind: ChannelKind", target_url, events, enabled, created_at
+                FROM notification_channels
+                WHERE user_id = $1
+                  AND enabled
+                  AND (cardinality(events) = 0 OR $2 = ANY(events))
+            "#,
+            user_id,
+            kind.as_str()
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn delete_channel(&self, id: &Uuid, user_id: &Uuid) -> Result<()> {
+        let result = sqlx::query!(
+            "DELETE FROM notification_channels WHERE id = $1 AND user_id = $2",
+            id,
+            user_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        if result.rows_affected() == 0 {
+            return Err(RepositoryError::NotFound(format!(
+                "Notification channel with ID '{}' not found",
+                id
+            )));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+// src/repositories/oauth.rs - Social login identities and pending logins
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{OAuthProvider, PendingLogin, User};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait OAuthRepositoryTrait {
+    /// Records a login sent to a provider, dropping logins that were never completed
+    ///
+    /// ### Arguments
+    /// * `state` - The random state the provider sends back
+    /// * `provider` - The provider the user was sent to
+    /// * `login` - The PKCE verifier, the browser's nonce and the user to link, if any
+    /// * `ttl_seconds` - How long logins stay valid
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn save_login(
+        &self,
+        state: &str,
+        provider: OAuthProvider,
+        login: &PendingLogin,
+        ttl_seconds: u64,
+    ) -> Result<()>;
+
+    /// Takes a pending login, so each can only be completed once
+    ///
+    /// ### Arguments
+    /// * `state` - The state the provider sent back
+    /// * `nonce_hash` - SHA-256 of the nonce the browser sent back
+    /// * `provider` - The provider the callback came from
+    /// * `ttl_seconds` - How long logins stay valid
+    ///
+    /// ### Returns
+    /// * `Result<Option<PendingLogin>>` - The login, if started recently at that provider in
+    ///   the same browser
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn take_login(
+        &self,
+        state: &str,
+        nonce_hash: &str,
+        provider: OAuthProvider,
+        ttl_seconds: u64,
+    ) -> Result<Option<PendingLogin>>;
+
+    /// Finds the user an identity is linked to
+    ///
+    /// ### Arguments
+    /// * `provider` - The provider of the identity
+    /// * `subject` - The account ID at the provider
+    ///
+    /// ### Returns
+    /// * `Result<Option<User>>` - The user, if the identity is linked
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_user(&self, provider: OAuthProvider, subject: &str) -> Result<Option<User>>;
+
+    /// Links an identity to a user
+    ///
+    /// ### Arguments
+    /// * `provider` - The provider of the identity
+    /// * `subject` - The account ID at the provider
+    /// * `user_id` - The user signing in with it
+    /// * `email` - The email reported by the provider
+    ///
+    /// ### Returns
+    /// * `Result<bool>` - False if the identity was already linked, possibly to another user
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn link_identity(
+        &self,
+        provider: OAuthProvider,
+        subject: &str,
+        user_id: &Uuid,
+        email: Option<&str>,
+    ) -> Result<bool>;
+}
+
+// Implementation using actual database
+pub struct OAuthRepository {
+    pool: PgPool,
+}
+
+impl OAuthRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl OAuthRepositoryTrait for OAuthRepository {
+    async fn save_login(
+        &self,
+        state: &str,
+        provider: OAuthProvider,
+        login: &PendingLogin,
+        ttl_seconds: u64,
+    ) -> Result<()> {
+        sqlx::query!(
+            r#"
+                DELETE FROM oauth_states
+                WHERE created_at < NOW() - make_interval(secs => $1)
+            "#,
+            ttl_seconds as f64
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO oauth_states (state, provider, code_verifier, nonce_hash, link_user_id)
+                VALUES ($1, $2, $3, $4, $5)
+            "#,
+            state,
+            provider.as_str(),
+            login.code_verifier,
+            login.nonce_hash,
+            login.link_user_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(())
+    }
+
+    async fn take_login(
+        &self,
+        state: &str,
+        nonce_hash: &str,
+        provider: OAuthProvider,
+        ttl_seconds: u64,
+    ) -> Result<Option<PendingLogin>> {
+        sqlx::query_as!(
+            PendingLogin,
+            r#"
+                DELETE FROM oauth_states
+                WHERE state = $1
+                  AND nonce_hash = $2
+                  AND provider = $3
+                  AND created_at >= NOW() - make_interval(secs => $4)
+                RETURNING code_verifier, nonce_hash, link_user_id
+            "#,
+            state,
+            nonce_hash,
+            provider.as_str(),
+            ttl_seconds as f64
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_user(&self, provider: OAuthProvider, subject: &str) -> Result<Option<User>> {
+        sqlx::query_as!(
+            User,
+            r#"
+                SELECT u.id, u.email, u.display_name, u.settings, u.created_at
+                FROM oauth_identities i
+                JOIN users u ON u.id = i.user_id
+                WHERE i.provider = $1 AND i.subject = $2
+            "#,
+            provider.as_str(),
+            subject
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn link_identity(
+        &self,
+        provider: OAuthProvider,
+        subject: &str,
+        user_id: &Uuid,
+        email: Option<&str>,
+    ) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+                INSERT INTO oauth_identities (provider, subject, user_id, email)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (provider, subject) DO NOTHING
+            "#,
+            provider.as_str(),
+            subject,
+            user_id,
+            email
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::from)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,234 @@
+// src/repositories/saved_search.rs - Named URL filters, per tenant
+use async_trait::async_trait;
+use serde_json::Value as JsonValue;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::SavedSearch;
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait SavedSearchRepositoryTrait {
+    /// Finds every saved search of a tenant
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant, `None` for the default tenant
+    ///
+    /// ### Returns
+    /// * `Result<Vec<SavedSearch>>` - The searches, by name
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_all(&self, tenant: Option<&Uuid>) -> Result<Vec<SavedSearch>>;
+
+    /// Finds a saved search by ID
+    ///
+    /// ### Arguments
+    /// * `id` - The search ID
+    /// * `tenant` - The tenant, `None` for the default tenant
+    ///
+    /// ### Returns
+    /// * `Result<Option<SavedSearch>>` - The search, if the tenant has it
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_by_id(&self, id: &Uuid, tenant: Option<&Uuid>) -> Result<Option<SavedSearch>>;
+
+    /// Finds a saved search by name
+    ///
+    /// ### Arguments
+    /// * `name` - The search name, as given in `?preset=`
+    /// * `tenant` - The tenant, `None` for the default tenant
+    ///
+    /// ### Returns
+    /// * `Result<Option<SavedSearch>>` - The search, if the tenant has it
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_by_name(&self, name: &str, tenant: Option<&Uuid>) -> Result<Option<SavedSearch>>;
+
+    /// Stores a new saved search
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant, `None` for the default tenant
+    /// * `name` - The search name
+    /// * `params` - The query parameters set, as a JSON object
+    ///
+    /// ### Returns
+    /// * `Result<SavedSearch>` - The stored search
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::Conflict` - If the tenant already has a search with this name
+    async fn create(
+        &self,
+        tenant: Option<&Uuid>,
+        name: &str,
+        params: &JsonValue,
+    ) -> Result<SavedSearch>;
+
+    /// Replaces the name and parameters of a saved search
+    ///
+    /// ### Arguments
+    /// * `id` - The search ID
+    /// * `tenant` - The tenant, `None` for the default tenant
+    /// * `name` - The new name
+    /// * `params` - The new query parameters, as a JSON object
+    ///
+    /// ### Returns
+    /// * `Result<Option<SavedSearch>>` - The updated search, `None` if the tenant has no such one
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::Conflict` - If another search of the tenant has this name
+    async fn update(
+        &self,
+        id: &Uuid,
+        tenant: Option<&Uuid>,
+        name: &str,
+        params: &JsonValue,
+    ) -> Result<Option<SavedSearch>>;
+
+    /// Deletes a saved search
+    ///
+    /// ### Arguments
+    /// * `id` - The search ID
+    /// * `tenant` - The tenant, `None` for the default tenant
+    ///
+    /// ### Returns
+    /// * `Result<bool>` - False if the tenant has no such search
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn delete(&self, id: &Uuid, tenant: Option<&Uuid>) -> Result<bool>;
+}
+
+// Implementation using actual database
+pub struct SavedSearchRepository {
+    pool: PgPool,
+}
+
+impl SavedSearchRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl SavedSearchRepositoryTrait for SavedSearchRepository {
+    async fn find_all(&self, tenant: Option<&Uuid>) -> Result<Vec<SavedSearch>> {
+        sqlx::query_as!(
+            SavedSearch,
+            r#"
+                SELECT id, name, params, created_at, updated_at
+                FROM saved_searches
+                WHERE tenant_id IS NOT DISTINCT FROM $1
+                ORDER BY name
+            "#,
+            tenant
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_by_id(&self, id: &Uuid, tenant: Option<&Uuid>) -> Result<Option<SavedSearch>> {
+        sqlx::query_as!(
+            SavedSearch,
+            r#"
+                SELECT id, name, params, created_at, updated_at
+                FROM saved_searches
+                WHERE id = $1 AND tenant_id IS NOT DISTINCT FROM $2
+            "#,
+            id,
+            tenant
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_by_name(&self, name: &str, tenant: Option<&Uuid>) -> Result<Option<SavedSearch>> {
+        sqlx::query_as!(
+            SavedSearch,
+            r#"
+                SELECT id, name, params, created_at, updated_at
+                FROM saved_searches
+                WHERE name = $1 AND tenant_id IS NOT DISTINCT FROM $2
+            "#,
+            name,
+            tenant
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn create(
+        &self,
+        tenant: Option<&Uuid>,
+        name: &str,
+        params: &JsonValue,
+    ) -> Result<SavedSearch> {
+        sqlx::query_as!(
+            SavedSearch,
+            r#"
+                INSERT INTO saved_searches (tenant_id, name, params)
+                VALUES ($1, $2, $3)
+                RETURNING id, name, params, created_at, updated_at
+            "#,
+            tenant,
+            name,
+            params
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn update(
+        &self,
+        id: &Uuid,
+        tenant: Option<&Uuid>,
+        name: &str,
+        params: &JsonValue,
+    ) -> Result<Option<SavedSearch>> {
+        sqlx::query_as!(
+            SavedSearch,
+            r#"
+                UPDATE saved_searches
+                SET name = $3, params = $4, updated_at = NOW()
+                WHERE id = $1 AND tenant_id IS NOT DISTINCT FROM $2
+                RETURNING id, name, params, created_at, updated_at
+            "#,
+            id,
+            tenant,
+            name,
+            params
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn delete(&self, id: &Uuid, tenant: Option<&Uuid>) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM saved_searches
+                WHERE id = $1 AND tenant_id IS NOT DISTINCT FROM $2
+            "#,
+            id,
+            tenant
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
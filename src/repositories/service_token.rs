@@ -0,0 +1,182 @@
+// src/repositories/service_token.rs - Machine credentials with scopes
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{CreateServiceTokenDto, ServiceToken, ServiceTokenIdentity, User};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait ServiceTokenRepositoryTrait {
+    /// Stores a new token
+    ///
+    /// ### Arguments
+    /// * `dto` - The token's name, scopes, owner and lifetime
+    /// * `token_hash` - SHA-256 of the secret
+    ///
+    /// ### Returns
+    /// * `Result<ServiceToken>` - The stored token
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::InvalidData` - If the owner does not exist
+    async fn create(&self, dto: &CreateServiceTokenDto, token_hash: &str) -> Result<ServiceToken>;
+
+    /// Finds every token, revoked ones included
+    ///
+    /// ### Returns
+    /// * `Result<Vec<ServiceToken>>` - The tokens, newest first
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_all(&self) -> Result<Vec<ServiceToken>>;
+
+    /// Resolves a secret to its token and owner, recording the use
+    ///
+    /// ### Arguments
+    /// * `token_hash` - SHA-256 of the presented secret
+    ///
+    /// ### Returns
+    /// * `Result<Option<ServiceTokenIdentity>>` - The token, unless unknown, expired or revoked
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<ServiceTokenIdentity>>;
+
+    /// Revokes a token
+    ///
+    /// ### Arguments
+    /// * `id` - The token ID
+    ///
+    /// ### Returns
+    /// * `Result<bool>` - False if there is no such active token
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn revoke(&self, id: &Uuid) -> Result<bool>;
+}
+
+// Implementation using actual database
+pub struct ServiceTokenRepository {
+    pool: PgPool,
+}
+
+impl ServiceTokenRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl ServiceTokenRepositoryTrait for ServiceTokenRepository {
+    async fn create(&self, dto: &CreateServiceTokenDto, token_hash: &str) -> Result<ServiceToken> {
+        sqlx::query_as!(
+            ServiceToken,
+            r#"
+                INSERT INTO service_tokens (name, user_id, token_hash, scopes, expires_at)
+                VALUES ($1, $2, $3, $4, NOW() + make_interval(days => $5::INT))
+                RETURNING id, name, user_id, scopes, created_at, last_used_at, expires_at, revoked_at
+            "#,
+            dto.name,
+            dto.user_id,
+            token_hash,
+            &dto.scopes,
+            dto.expires_in_days.map(|days| days as i32)
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn find_all(&self) -> Result<Vec<ServiceToken>> {
+        sqlx::query_as!(
+            ServiceToken,
+            r#"
+                SELECT id, name, user_id, scopes, created_at, last_used_at, expires_at, revoked_at
+                FROM service_tokens
+                ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_by_hash(&self, token_hash: &str) -> Result<Option<ServiceTokenIdentity>> {
+        let row = sqlx::query!(
+            r#"
+                WITH used AS (
+                    UPDATE service_tokens
+                    SET last_used_at = NOW()
+                    WHERE token_hash = $1
+                      AND revoked_at IS NULL
+                      AND (expires_at IS NULL OR expires_at > NOW())
+                    RETURNING id, name, user_id, scopes, created_at, last_used_at, expires_at, revoked_at
+                )
+                SELECT
+                    used.id, used.name, used.user_id, used.scopes, used.created_at,
+                    used.last_used_at, used.expires_at, used.revoked_at,
+                    u.email AS "owner_email?", u.display_name AS owner_display_name,
+                    u.settings AS "owner_settings?", u.created_at AS "owner_created_at?"
+                FROM used
+                LEFT JOIN users u ON u.id = used.user_id
+            "#,
+            token_hash
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(row.map(|row| {
+            let owner = match (
+                row.user_id,
+                row.owner_email,
+                row.owner_settings,
+                row.owner_created_at,
+            ) {
+                (Some(id), Some(email), Some(settings), Some(created_at)) => Some(User {
+                    id,
+                    email,
+                    display_name: row.owner_display_name,
+                    settings,
+                    created_at,
+                }),
+                _ => None,
+            };
+            ServiceTokenIdentity {
+                token: ServiceToken {
+                    id: row.id,
+                    name: row.name,
+                    user_id: row.user_id,
+                    scopes: row.scopes,
+                    created_at: row.created_at,
+                    last_used_at: row.last_used_at,
+                    expires_at: row.expires_at,
+                    revoked_at: row.revoked_at,
+                },
+                owner,
+            }
+        }))
+    }
+
+    async fn revoke(&self, id: &Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE service_tokens
+                SET revoked_at = NOW()
+                WHERE id = $1 AND revoked_at IS NULL
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
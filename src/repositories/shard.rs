@@ -0,0 +1,125 @@
+// src/repositories/shard.rs - Which shard a link belongs to, by the prefix of its short code
+use std::ops::RangeInclusive;
+
+use sha2::{Digest, Sha256};
+
+/// Buckets short codes are spread over
+///
+/// Shards own whole buckets, so adding a shard moves some buckets to it instead of rehashing
+/// every code. Never change it: `short_code_bucket` in the database computes the same value.
+pub const SHARD_BUCKETS: u16 = 1024;
+
+/// Leading characters of a short code deciding its bucket
+///
+/// Codes only differing by case or by namespace share a bucket, so the lookups and uniqueness
+/// checks of a code stay on one shard.
+pub const SHARD_PREFIX_CHARS: usize = 2;
+
+/// Bucket of a short code, the first two bytes of the SHA-256 of its lowercased prefix
+///
+/// Matches `short_code_bucket(short_code)` in SQL, which `idx_shortened_urls_short_code_bucket`
+/// indexes to move the links of a bucket from one shard to another.
+pub fn shard_bucket(short_code: &str) -> u16 {
+    let prefix = short_code
+        .chars()
+        .take(SHARD_PREFIX_CHARS)
+        .collect::<String>()
+        .to_lowercase();
+    let digest = Sha256::digest(prefix.as_bytes());
+    u16::from_be_bytes([digest[0], digest[1]]) % SHARD_BUCKETS
+}
+
+/// Assignment of buckets to shards, each shard taking a contiguous range of them
+///
+/// Everything is on one shard for now. [`ShortenedUrlRepository`](super::ShortenedUrlRepository)
+/// picks the pool of a link with [`ShardMap::shard_for_code`] when saving it or looking it up by
+/// code; listings and lookups by id would have to fan out to every shard once there are several.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShardMap {
+    // First bucket of every shard but the first, ascending
+    starts: Vec<u16>,
+}
+
+impl ShardMap {
+    /// All buckets on a single shard
+    pub fn single() -> Self {
+        Self { starts: Vec::new() }
+    }
+
+    /// Buckets split in `shards` ranges of about the same size
+    ///
+    /// ### Panics
+    /// - If `shards` is 0 or more than [`SHARD_BUCKETS`]
+    pub fn even(shards: usize) -> Self {
+        assert!(
+            (1..=SHARD_BUCKETS as usize).contains(&shards),
+            "Shard count must be between 1 and {}",
+            SHARD_BUCKETS
+        );
+        let starts = (1..shards)
+            .map(|shard| (shard * SHARD_BUCKETS as usize / shards) as u16)
+            .collect();
+        Self { starts }
+    }
+
+    pub fn shard_count(&self) -> usize {
+        self.starts.len() + 1
+    }
+
+    pub fn shard_of_bucket(&self, bucket: u16) -> usize {
+        self.starts.partition_point(|start| *start <= bucket)
+    }
+
+    pub fn shard_for_code(&self, short_code: &str) -> usize {
+        self.shard_of_bucket(shard_bucket(short_code))
+    }
+
+    /// Buckets owned by `shard`
+    pub fn buckets(&self, shard: usize) -> RangeInclusive<u16> {
+        let start = match shard {
+            0 => 0,
+            _ => self.starts[shard - 1],
+        };
+        let end = self
+            .starts
+            .get(shard)
+            .map_or(SHARD_BUCKETS - 1, |next| next - 1);
+        start..=end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bucket_matches_database() {
+        // Values of short_code_bucket() in a UTF8 database
+        for (code, bucket) in [
+            ("ab", 910),
+            ("AB", 910),
+            ("abc123", 910),
+            ("x", 369),
+            ("docs", 11),
+            ("🎉🎉x", 959),
+        ] {
+            assert_eq!(shard_bucket(code), bucket, "{}", code);
+        }
+    }
+
+    #[test]
+    fn test_even_map_covers_every_bucket() {
+        assert_eq!(ShardMap::single().buckets(0), 0..=SHARD_BUCKETS - 1);
+        assert_eq!(ShardMap::single().shard_for_code("abc123"), 0);
+
+        let map = ShardMap::even(3);
+        assert_eq!(map.shard_count(), 3);
+        assert_eq!(map.buckets(0), 0..=340);
+        assert_eq!(map.buckets(1), 341..=681);
+        assert_eq!(map.buckets(2), 682..=SHARD_BUCKETS - 1);
+        for bucket in 0..SHARD_BUCKETS {
+            assert!(map.buckets(map.shard_of_bucket(bucket)).contains(&bucket));
+        }
+        assert_eq!(map.shard_for_code("abc123"), 2);
+    }
+}
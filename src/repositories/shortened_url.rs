@@ -1,30 +1,71 @@
 // src/repositories/shortened_url.rs - Data access
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use futures::{channel::mpsc, stream::BoxStream, SinkExt, StreamExt};
 use log::debug;
-use sqlx::{PgPool, Postgres, QueryBuilder, Transaction};
+use serde_json::Value as JsonValue;
+use sqlx::{PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
 use crate::db::Database;
 use crate::errors::RepositoryError;
-use crate::models::{ShortenedUrl, ShortenedUrlQueryParams, ShortenedUrlUpdateParams};
+use crate::models::{
+    LinkBehavior, LinkStatus, LinkTransfer, NewShortenedUrl, ShortenedUrl, ShortenedUrlQueryParams,
+    ShortenedUrlUpdateParams, TenantScope, TransferStatus, TrashStats, TrashedUrl, UrlRevision,
+};
+use crate::utils::network::parse_cidrs;
+use crate::utils::query_parser::{DateRange, LinkFlag, SearchFilter};
+
+use super::{commit, DbTransaction, ShardMap};
 
 type Result<T> = std::result::Result<T, RepositoryError>;
 
+// The lowercase host of a destination, skipping the scheme and any credentials
+const DESTINATION_HOST: &str =
+    "lower(substring(original_url FROM '^[^:]+://(?:[^/?#@]*@)?([^/?#:]+)'))";
+
+// Text matched literally by LIKE, with its wildcards escaped
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+// Rows read ahead of a slow stream consumer
+const STREAM_BUFFER: usize = 64;
+
+// Transactions start on the pool of the shard they write to
+async fn begin_on(pool: &PgPool) -> Result<DbTransaction> {
+    pool.begin().await.map_err(|e| {
+        log::error!("Failed to start database transaction: {}", e);
+        RepositoryError::Database(e)
+    })
+}
+
 #[async_trait]
 pub trait ShortenedUrlRepositoryTrait {
-    /// Saves a shortened URL to the database and assigns it a UUID
+    /// Saves a new shortened URL to the database
     ///
     /// ### Arguments
-    /// * `url` - The shortened URL to save, will be updated with the generated ID
+    /// * `url` - The link to save
     ///
     /// ### Returns
-    /// * `Result<Uuid>` - The UUID of the newly created record on success
+    /// * `Result<ShortenedUrl>` - The saved record, with the ID and creation time the
+    ///   database generated
     ///
     /// ### Errors
     /// * `RepositoryError::Database` - If a database error occurs
     /// * `RepositoryError::Conflict` - If there's a constraint violation (e.g., duplicate short code)
-    async fn save(&self, url: &ShortenedUrl) -> Result<ShortenedUrl>;
+    async fn save(&self, url: &NewShortenedUrl) -> Result<ShortenedUrl>;
+
+    /// Starts a transaction for several calls that must succeed or fail together
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn begin(&self) -> Result<DbTransaction>;
+
+    /// Saves a new shortened URL as part of a caller's transaction, see [`Self::save`]
+    async fn save_in(&self, tx: &mut DbTransaction, url: &NewShortenedUrl) -> Result<ShortenedUrl>;
 
     /// Finds some shortened URL by params
     ///
@@ -39,6 +80,22 @@ pub trait ShortenedUrlRepositoryTrait {
     /// * `RepositoryError::InvalidData` - If the database record cannot be mapped to a model
     async fn find(&self, params: &ShortenedUrlQueryParams) -> Result<Vec<ShortenedUrl>>;
 
+    /// Streams the shortened URLs matching params, row by row
+    ///
+    /// Unlike [`Self::find`], rows are handed out as the database sends them, so exports
+    /// of any size don't load everything into memory. The query stops once the stream is
+    /// dropped.
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs, as the last item
+    fn stream(&self, params: ShortenedUrlQueryParams) -> BoxStream<'static, Result<ShortenedUrl>>;
+
+    /// Counts the shortened URLs matching params, ignoring ordering and pagination
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn count(&self, params: &ShortenedUrlQueryParams) -> Result<i64>;
+
     /// Finds a shortened URL by its unique identifier (UUID)
     ///
     /// ### Arguments
@@ -52,9 +109,33 @@ pub trait ShortenedUrlRepositoryTrait {
     /// * `RepositoryError::InvalidData` - If the database record cannot be mapped to a model
     async fn find_by_id(&self, id: &Uuid) -> Result<Option<ShortenedUrl>>;
 
-    /// Finds a shortened URL by its unique short code
+    /// Finds the id a link has now, given the random (v4) id it had before being moved to a
+    /// time-ordered one
+    ///
+    /// ### Returns
+    /// * `Result<Option<Uuid>>` - The link's current id, or `None` if `old_id` was never
+    ///   remapped
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_remapped_id(&self, old_id: &Uuid) -> Result<Option<Uuid>>;
+
+    /// Finds a shortened URL by ID as part of a caller's transaction, locking the row
+    /// until the transaction ends so nobody else changes it in between
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn lock_by_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        id: &Uuid,
+    ) -> Result<Option<ShortenedUrl>>;
+
+    /// Finds a shortened URL by its short code, unique within a tenant's namespace
     ///
     /// ### Arguments
+    /// * `tenant` - The tenant owning the code, `None` for the default tenant
+    /// * `namespace` - The namespace the code lives under, `None` for top-level codes
     /// * `code` - The short code of the shortened URL to find
     ///
     /// ### Returns
@@ -63,11 +144,17 @@ pub trait ShortenedUrlRepositoryTrait {
     /// ### Errors
     /// * `RepositoryError::Database` - If a database error occurs
     /// * `RepositoryError::InvalidData` - If the database record cannot be mapped to a model
-    async fn find_by_code(&self, code: &str) -> Result<Option<ShortenedUrl>>;
+    async fn find_by_code(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Result<Option<ShortenedUrl>>;
 
-    /// Finds all shortened URLs with optional pagination
+    /// Finds all shortened URLs of a tenant with optional pagination
     ///
     /// ### Arguments
+    /// * `tenant` - The tenant owning the URLs, `None` for the default tenant
     /// * `limit` - The maximum number of records to return (optional)
     /// * `offset` - The number of records to skip before starting to return results (optional)
     ///
@@ -76,23 +163,104 @@ pub trait ShortenedUrlRepositoryTrait {
     ///
     /// ### Errors
     /// * `RepositoryError::Database` - If a database error occurs
-    async fn find_all(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<ShortenedUrl>>;
+    async fn find_all(
+        &self,
+        tenant: Option<&Uuid>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<ShortenedUrl>>;
+
+    /// Finds the shortened URLs matching any of the given codes, in a single query
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant owning the codes, `None` for the default tenant
+    /// * `paths` - Codes as they appear in short URLs (`code` or `namespace/code`),
+    ///   already lowercase when `ignore_case` is set
+    /// * `ignore_case` - Match codes regardless of case
+    ///
+    /// ### Returns
+    /// * `Result<Vec<ShortenedUrl>>` - The URLs found, codes without a match are left out
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_by_codes(
+        &self,
+        tenant: Option<&Uuid>,
+        paths: &[String],
+        ignore_case: bool,
+    ) -> Result<Vec<ShortenedUrl>>;
+
+    /// Finds a shortened URL by its code regardless of case
+    ///
+    /// An exact match wins over other spellings, which can only exist from
+    /// before case-insensitive codes were enabled.
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant owning the code, `None` for the default tenant
+    /// * `namespace` - The namespace the code lives under, `None` for top-level codes
+    /// * `code` - The short code to look up
+    ///
+    /// ### Returns
+    /// * `Result<Option<ShortenedUrl>>` - The URL if found
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_by_code_ignore_case(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Result<Option<ShortenedUrl>>;
 
     /// Updates a shortened URL in the database
     ///
+    /// When the destination changes, the previous one is recorded as a revision in the
+    /// same transaction. When `params.version` is given, the update only applies if the
+    /// URL is still at that version, and the version is incremented.
+    ///
     /// # Arguments
-    /// * `url` - The shortened URL to update with new values
+    /// * `id` - The UUID of the shortened URL to update
+    /// * `params` - The fields to change
+    /// * `changed_by` - The user making the change, if known
     ///
     /// # Returns
-    /// * `Result<()>` - Success or error
+    /// * `Result<u64>` - Number of rows updated, 0 if the URL doesn't exist
     ///
     /// # Errors
-    /// * `RepositoryError::NotFound` - If the URL doesn't exist
-    /// * `RepositoryError::InvalidData` - If the URL has no ID
+    /// * `RepositoryError::Conflict` - If the URL was updated since `params.version`
     /// * `RepositoryError::Database` - If a database error occurs
-    async fn update(&self, id: &Uuid, params: &ShortenedUrlUpdateParams) -> Result<u64>;
+    async fn update(
+        &self,
+        id: &Uuid,
+        params: &ShortenedUrlUpdateParams,
+        changed_by: Option<&Uuid>,
+    ) -> Result<u64>;
 
-    /// Deletes a shortened URL by its unique identifier (UUID)
+    /// Updates a shortened URL as part of a caller's transaction, see [`Self::update`]
+    async fn update_in(
+        &self,
+        tx: &mut DbTransaction,
+        id: &Uuid,
+        params: &ShortenedUrlUpdateParams,
+        changed_by: Option<&Uuid>,
+    ) -> Result<u64>;
+
+    /// Lists the previous destinations of a shortened URL, newest first
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_revisions(&self, url_id: &Uuid) -> Result<Vec<UrlRevision>>;
+
+    /// Finds one previous destination of a shortened URL by its sequence number
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_revision(&self, url_id: &Uuid, revision: i32) -> Result<Option<UrlRevision>>;
+
+    /// Moves a shortened URL to the trash by its unique identifier (UUID)
+    ///
+    /// Trashed URLs are left out of every lookup until restored, and deleted for good by
+    /// [`Self::purge_trash`]. Their code stays taken meanwhile.
     ///
     /// ### Arguments
     /// * `id` - The UUID of the shortened URL to delete
@@ -105,255 +273,2159 @@ pub trait ShortenedUrlRepositoryTrait {
     /// * `RepositoryError::NotFound` - If the URL doesn't exist and `require_exists` is `true`
     /// * `RepositoryError::Database` - If a database error occurs
     async fn delete(&self, id: &Uuid, require_exists: bool) -> Result<bool>;
-}
-
-// Implementation using actual database
-pub struct ShortenedUrlRepository {
-    pool: PgPool,
-}
 
-impl ShortenedUrlRepository {
-    pub fn new(db: Database) -> Self {
-        Self { pool: db.get_pool().clone() }
-    }
+    /// Moves a shortened URL to the trash as part of a caller's transaction, see [`Self::delete`]
+    ///
+    /// ### Returns
+    /// * `Result<bool>` - `true` if the URL existed and was moved to the trash
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn delete_in(&self, tx: &mut DbTransaction, id: &Uuid) -> Result<bool>;
 
-    // Helper method for transactions
-    async fn begin_transaction(&self) -> Result<Transaction<'_, Postgres>> {
-        self.pool.begin().await.map_err(|e| {
-            log::error!("Failed to start database transaction: {}", e);
-            RepositoryError::Database(e)
-        })
-    }
-}
+    /// Lists the live URLs of a tenant expiring within a number of days, soonest first
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant, `None` for the default tenant
+    /// * `within_days` - Days from now the expiry falls in
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_expiring(
+        &self,
+        tenant: Option<&Uuid>,
+        within_days: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ShortenedUrl>>;
 
-#[async_trait]
-impl ShortenedUrlRepositoryTrait for ShortenedUrlRepository {
-    async fn save(&self, url: &ShortenedUrl) -> Result<ShortenedUrl> {
-        // Start a transaction so we can rollback if needed
-        let mut tx = self.begin_transaction().await?;
+    /// Lists the trashed URLs of a tenant, most recently deleted first
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_trash(
+        &self,
+        tenant: Option<&Uuid>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<TrashedUrl>>;
 
-        // Insert the shortened URL
-        let record = sqlx::query_as!(
-            ShortenedUrl,
-            r#"
-                INSERT INTO shortened_urls 
-                (original_url, short_code, last_accessed, access_count, expires_at, is_custom_code, metadata)
-                VALUES ($1, $2, $3, $4, $5, $6, $7)
-                RETURNING *
-            "#,
-            url.original_url,
-            url.short_code,
-            url.last_accessed,
-            url.access_count as i64,
-            url.expires_at,
-            url.is_custom_code,
-            url.metadata
-        )
-        .fetch_one(&mut *tx)
-        .await
-        .map_err(|e| {
-            log::error!("Failed to insert shortened URL: {}", e);
-            RepositoryError::from(e)
-        })?;
+    /// Finds a trashed URL of a tenant by ID
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_in_trash(
+        &self,
+        id: &Uuid,
+        tenant: Option<&Uuid>,
+    ) -> Result<Option<ShortenedUrl>>;
 
-        // Commit the transaction
-        tx.commit().await.map_err(|e| {
-            log::error!("Failed to commit transaction: {}", e);
-            RepositoryError::Database(e)
-        })?;
+    /// Takes a URL of a tenant out of the trash
+    ///
+    /// ### Returns
+    /// * `Result<Option<ShortenedUrl>>` - The restored URL, `None` if it isn't in the trash
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn restore(&self, id: &Uuid, tenant: Option<&Uuid>) -> Result<Option<ShortenedUrl>>;
 
-        Ok(record)
-    }
+    /// Deletes for good up to `limit` URLs trashed before `before`, visits included
+    ///
+    /// ### Returns
+    /// * `Result<u64>` - Number of URLs deleted
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn purge_trash(&self, before: DateTime<Utc>, limit: i64) -> Result<u64>;
 
-    async fn find(&self, params: &ShortenedUrlQueryParams) -> Result<Vec<ShortenedUrl>> {
-        // Use QueryBuilder instead of manual string manipulation
-        let mut query_builder = QueryBuilder::new(
-            "SELECT * 
-            FROM shortened_urls 
-            WHERE 1=1"
-        );
+    /// Counts the URLs in the trash of every tenant, and those of them trashed before `before`
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn count_trash(&self, before: DateTime<Utc>) -> Result<TrashStats>;
 
-        // Add conditions based on provided parameters
-        if let Some(code) = &params.short_code {
-            query_builder.push(" AND short_code = ");
-            query_builder.push_bind(code);
-        }
+    /// Marks a single-use URL as consumed, if nobody has consumed it yet
+    ///
+    /// The check and the update happen in one statement, so when several requests
+    /// race for the same link exactly one of them wins.
+    ///
+    /// ### Arguments
+    /// * `id` - The UUID of the single-use URL
+    ///
+    /// ### Returns
+    /// * `Result<bool>` - `true` if this call consumed the link, `false` if it was already consumed
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn consume(&self, id: &Uuid) -> Result<bool>;
 
-        if let Some(url) = &params.original_url {
-            query_builder.push(" AND original_url LIKE ");
-            query_builder.push_bind(format!("%{}%", url));
-        }
+    /// Counts a redirect, bumping the access count and last access time
+    ///
+    /// ### Returns
+    /// * `Result<i64>` - The access count after this redirect, 0 if the URL doesn't exist.
+    ///   Concurrent redirects each see a different count.
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn record_access(&self, id: &Uuid) -> Result<i64>;
 
-        if let Some(id) = params.id {
-            query_builder.push(" AND id = ");
-            query_builder.push_bind(id);
-        }
+    /// Counts redirects that happened earlier, e.g. while the database was unreachable
+    ///
+    /// ### Arguments
+    /// * `id` - The UUID of the shortened URL
+    /// * `count` - How many redirects to add
+    /// * `last_accessed` - When the latest of them happened; an access recorded since is kept
+    ///
+    /// ### Returns
+    /// * `Result<i64>` - The access count afterwards, 0 if the URL doesn't exist
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn add_accesses(
+        &self,
+        id: &Uuid,
+        count: i64,
+        last_accessed: DateTime<Utc>,
+    ) -> Result<i64>;
 
-        if let Some(after) = params.created_after {
-            query_builder.push(" AND created_at >= ");
-            query_builder.push_bind(after);
-        }
+    /// Records click milestones reached by a link, skipping ones recorded before
+    ///
+    /// ### Arguments
+    /// * `id` - The UUID of the shortened URL
+    /// * `milestones` - The milestones its count just passed
+    ///
+    /// ### Returns
+    /// * `Result<Vec<i64>>` - The milestones recorded by this call, each returned to one caller only
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn claim_milestones(&self, id: &Uuid, milestones: &[i64]) -> Result<Vec<i64>>;
 
-        if let Some(before) = params.created_before {
-            query_builder.push(" AND created_at <= ");
-            query_builder.push_bind(before);
-        }
+    /// Lists the most accessed links that can still be followed, busiest first
+    ///
+    /// ### Arguments
+    /// * `limit` - Maximum number of links to return
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_most_accessed(&self, limit: i64) -> Result<Vec<ShortenedUrl>>;
 
-        let now = Utc::now();
-        if let Some(true) = params.is_expired {
-            // URLs that have an expiration date in the past
-            query_builder.push(" AND (expires_at IS NOT NULL AND expires_at < ");
-            query_builder.push_bind(now);
-            query_builder.push(")");
-        } else if let Some(false) = params.is_expired {
-            // URLs that either have no expiration or expiration in the future
-            query_builder.push(" AND (expires_at IS NULL OR expires_at >= ");
-            query_builder.push_bind(now);
-            query_builder.push(")");
-        }
+    /// Reads a page of raw rows, every column included, ordered by ID
+    ///
+    /// Meant for backups only: rows are not mapped to [`ShortenedUrl`], so columns never
+    /// exposed through the API are kept.
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn dump_rows(&self, limit: i64, offset: i64) -> Result<Vec<JsonValue>>;
 
-        if let Some(is_active) = params.is_active {
-            query_builder.push(" AND is_active = ");
-            query_builder.push_bind(is_active);
-        }
+    /// Deactivates links that weren't accessed within their idle limit
+    ///
+    /// ### Returns
+    /// * `Result<u64>` - Number of links deactivated
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn deactivate_idle(&self) -> Result<u64>;
 
-        if let Some(is_custom_code) = params.is_custom_code {
-            query_builder.push(" AND is_custom_code = ");
-            query_builder.push_bind(is_custom_code);
-        }
+    /// Archives links nobody followed since a point in time
+    ///
+    /// ### Arguments
+    /// * `before` - Links last accessed, or created when never accessed, before this are archived
+    ///
+    /// ### Returns
+    /// * `Result<u64>` - Number of links archived
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn archive_unused(&self, before: DateTime<Utc>) -> Result<u64>;
 
-        if let Some(min_count) = params.min_access_count {
-            query_builder.push(" AND access_count >= ");
-            query_builder.push_bind(min_count);
-        }
+    /// Claims the links that expired since a point in time and weren't claimed yet
+    ///
+    /// Each expiry is claimed once across all instances; extending a link re-arms it.
+    ///
+    /// ### Arguments
+    /// * `since` - Expiries before this are ignored
+    ///
+    /// ### Returns
+    /// * `Result<Vec<ShortenedUrl>>` - The links whose expiry was claimed
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn claim_expired(&self, since: DateTime<Utc>) -> Result<Vec<ShortenedUrl>>;
 
-        // Add order by with dynamic column and direction
-        let order_by = params.order_by.unwrap_or_default();
-        let direction = params.order_direction.unwrap_or_default();
+    /// Moves a link to another moderation status
+    ///
+    /// ### Arguments
+    /// * `id` - The link to change
+    /// * `status` - Its new status
+    ///
+    /// ### Returns
+    /// * `Result<Option<ShortenedUrl>>` - The changed link, or `None` if it doesn't exist
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn update_status(&self, id: &Uuid, status: LinkStatus) -> Result<Option<ShortenedUrl>>;
 
-        // Safely add the ORDER BY clause with the column name (not user input)
-        query_builder.push(" ORDER BY ");
-        query_builder.push(order_by.as_column());
-        query_builder.push(" ");
-        query_builder.push(direction.to_string());
+    /// Archives a link, or takes it out of the archive
+    ///
+    /// Archiving an archived link keeps when it was archived first.
+    ///
+    /// ### Arguments
+    /// * `id` - The link to change
+    /// * `archived` - Whether it's archived from now on
+    ///
+    /// ### Returns
+    /// * `Result<Option<ShortenedUrl>>` - The changed link, or `None` if it doesn't exist
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn set_archived(&self, id: &Uuid, archived: bool) -> Result<Option<ShortenedUrl>>;
 
-        // Add limit and offset
-        if let Some(limit) = params.limit {
-            query_builder.push(" LIMIT ");
-            query_builder.push_bind(limit);
-        }
+    /// Finds links with an http:// destination that should be upgraded to https://, by ID
+    ///
+    /// ### Arguments
+    /// * `by_default` - Whether links that didn't choose with `upgrade_https` are included
+    /// * `after` - Only links with a greater ID, to continue from the previous batch
+    /// * `limit` - Maximum number of links to return
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_http_destinations(
+        &self,
+        by_default: bool,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<ShortenedUrl>>;
 
-        if let Some(offset) = params.offset {
-            query_builder.push(" OFFSET ");
-            query_builder.push_bind(offset);
+    /// Finds active links whose destination was checked longest ago, never checked first
+    ///
+    /// ### Arguments
+    /// * `limit` - Maximum number of links to return
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_links_to_check(&self, limit: i64) -> Result<Vec<ShortenedUrl>>;
+
+    /// Records the outcome of checking a link's destination
+    ///
+    /// ### Arguments
+    /// * `id` - The checked link
+    /// * `status` - HTTP status of the answer, `None` if there was none
+    /// * `broken` - Whether the destination counts as broken
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn record_check(&self, id: &Uuid, status: Option<i32>, broken: bool) -> Result<()>;
+
+    /// Pins a link for a user, doing nothing if it already is
+    ///
+    /// ### Arguments
+    /// * `user_id` - The user pinning the link
+    /// * `url_id` - The pinned link
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn add_favorite(&self, user_id: &Uuid, url_id: &Uuid) -> Result<()>;
+
+    /// Unpins a link for a user
+    ///
+    /// ### Returns
+    /// * `Result<bool>` - Whether the link was pinned
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn remove_favorite(&self, user_id: &Uuid, url_id: &Uuid) -> Result<bool>;
+
+    /// Stores the claim token of a link created without signing in
+    ///
+    /// ### Arguments
+    /// * `url_id` - The link
+    /// * `token_hash` - SHA-256 hex digest of the token
+    /// * `expires_at` - When the token stops working
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn create_claim(
+        &self,
+        url_id: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()>;
+
+    /// Gives a link nobody owns to a user presenting its claim token, using the token up
+    ///
+    /// ### Arguments
+    /// * `token_hash` - SHA-256 hex digest of the presented token
+    /// * `user_id` - The new owner
+    /// * `tenant` - The tenant the link must belong to, `None` for the default tenant
+    ///
+    /// ### Returns
+    /// * `Result<Option<ShortenedUrl>>` - The claimed link, `None` if the token is unknown,
+    ///   expired or for a link of another tenant, trashed or owned meanwhile
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn claim(
+        &self,
+        token_hash: &str,
+        user_id: &Uuid,
+        tenant: Option<&Uuid>,
+    ) -> Result<Option<ShortenedUrl>>;
+
+    /// Deletes claim tokens that expired
+    ///
+    /// ### Returns
+    /// * `Result<u64>` - Number of tokens deleted
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn purge_expired_claims(&self) -> Result<u64>;
+
+    /// Offers a link to another user, who becomes its owner once accepting
+    ///
+    /// ### Arguments
+    /// * `url_id` - The link, which must not be in the trash
+    /// * `from_user_id` - The current owner of the link
+    /// * `to_user_id` - The user the link is offered to
+    ///
+    /// ### Returns
+    /// * `Result<Option<LinkTransfer>>` - The pending transfer, `None` if the link isn't owned
+    ///   by `from_user_id` or already has a pending transfer
+    ///
+    /// ### Errors
+    /// * `RepositoryError::InvalidData` - If there is no user with ID `to_user_id`
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn offer_transfer(
+        &self,
+        url_id: &Uuid,
+        from_user_id: &Uuid,
+        to_user_id: &Uuid,
+    ) -> Result<Option<LinkTransfer>>;
+
+    /// Finds a transfer by ID
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_transfer(&self, id: &Uuid) -> Result<Option<LinkTransfer>>;
+
+    /// Finds the transfers offered to a user and still pending, oldest first
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_pending_transfers(&self, to_user_id: &Uuid) -> Result<Vec<LinkTransfer>>;
+
+    /// Finds every transfer of a link, whatever came of it, newest first
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_transfers(&self, url_id: &Uuid) -> Result<Vec<LinkTransfer>>;
+
+    /// Accepts a pending transfer, making its recipient the owner of the link
+    ///
+    /// ### Arguments
+    /// * `id` - The transfer
+    /// * `user_id` - The recipient of the transfer
+    ///
+    /// ### Returns
+    /// * `Result<Option<ShortenedUrl>>` - The transferred link, `None` if the transfer isn't
+    ///   pending for `user_id`, or its link was trashed or changed owner since it was offered
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn accept_transfer(&self, id: &Uuid, user_id: &Uuid) -> Result<Option<ShortenedUrl>>;
+
+    /// Closes a pending transfer without changing the link
+    ///
+    /// ### Arguments
+    /// * `id` - The transfer
+    /// * `status` - `TransferStatus::Declined` or `TransferStatus::Cancelled`
+    ///
+    /// ### Returns
+    /// * `Result<Option<LinkTransfer>>` - The closed transfer, `None` if it wasn't pending
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn close_transfer(
+        &self,
+        id: &Uuid,
+        status: TransferStatus,
+    ) -> Result<Option<LinkTransfer>>;
+
+    /// Moves a link to another owner or tenant at once, recorded as a transfer made by an
+    /// admin; pending transfers of the link are cancelled
+    ///
+    /// ### Arguments
+    /// * `url_id` - The link, which must not be in the trash
+    /// * `to_user_id` - The new owner, `None` to keep the current one
+    /// * `to_tenant` - The tenant to move the link to, `None` to keep the current one
+    ///
+    /// ### Returns
+    /// * `Result<Option<ShortenedUrl>>` - The transferred link, `None` if it wasn't found
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Conflict` - If the code of the link is taken in `to_tenant`
+    /// * `RepositoryError::InvalidData` - If there is no user with ID `to_user_id`
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn force_transfer(
+        &self,
+        url_id: &Uuid,
+        to_user_id: Option<&Uuid>,
+        to_tenant: Option<&Uuid>,
+    ) -> Result<Option<ShortenedUrl>>;
+}
+
+// Implementation using actual database
+//
+// Inserts and lookups by code go to the shard of the code. Everything else, lookups by id and
+// listings included, uses the first shard, the only one so far.
+pub struct ShortenedUrlRepository {
+    pool: PgPool,
+    shards: ShardMap,
+    // Pool of every shard, by its number in `shards`
+    shard_pools: Vec<PgPool>,
+}
+
+impl ShortenedUrlRepository {
+    pub fn new(db: Database) -> Self {
+        let pool = db.get_pool().clone();
+        Self {
+            shards: ShardMap::single(),
+            shard_pools: vec![pool.clone()],
+            pool,
+        }
+    }
+
+    // The pool of the shard a short code belongs to
+    fn shard_pool(&self, short_code: &str) -> &PgPool {
+        &self.shard_pools[self.shards.shard_for_code(short_code)]
+    }
+
+    /// Builds the filtered, ordered and paginated query behind `find`
+    ///
+    /// Expected plans, checked against 200k rows:
+    /// * `short_code` - index scan on `idx_shortened_urls_short_code`
+    /// * `namespace` - backward scan on `idx_shortened_urls_namespace_created_at` when ordered by `created_at`
+    /// * `original_url` - bitmap scan on the trigram index `idx_shortened_urls_original_url_trgm`
+    /// * `created_after` / `created_before` - range scan on `idx_shortened_urls_created_at`
+    /// * `is_expired = true` - range scan on the partial `idx_shortened_urls_expires_at`
+    /// * `is_active = false` - partial `idx_shortened_urls_inactive_created_at`; `true` matches most
+    ///   rows and walks `idx_shortened_urls_created_at` (or the sort column's index) with a filter
+    /// * `is_custom_code = true` - partial `idx_shortened_urls_custom_created_at`
+    /// * `min_access_count` with `is_active = true` - partial `idx_shortened_urls_access_count_active`
+    /// * `metadata_contains` / `metadata_includes` - bitmap scan on the GIN `idx_shortened_urls_metadata`
+    /// * a tenant other than the default one - `idx_shortened_urls_tenant_created_at`
+    /// * ordering by `last_accessed` - `idx_shortened_urls_last_accessed`, no sort step
+    ///   (sorting by several fields only uses an index for the first one)
+    ///
+    /// The predicates in `push_filters` are written to match those indexes; keep them in that form.
+    pub fn find_query(params: &ShortenedUrlQueryParams) -> QueryBuilder<'_, Postgres> {
+        // Use QueryBuilder instead of manual string manipulation
+        let mut query_builder = QueryBuilder::new(
+            "SELECT * 
+            FROM shortened_urls 
+            WHERE deleted_at IS NULL",
+        );
+        Self::push_filters(&mut query_builder, params);
+
+        // Add order by with dynamic columns and directions
+        let order_by = params.order_by.clone().unwrap_or_default();
+        let default_direction = params.order_direction.unwrap_or_default();
+
+        // Safely add the ORDER BY clause with the column names (not user input)
+        query_builder.push(" ORDER BY ");
+        let mut keys = query_builder.separated(", ");
+        for key in order_by.keys() {
+            keys.push(format_args!(
+                "{} {}",
+                key.field.as_column(),
+                key.direction.unwrap_or(default_direction)
+            ));
+        }
+
+        // Add limit and offset
+        if let Some(limit) = params.limit {
+            query_builder.push(" LIMIT ");
+            query_builder.push_bind(limit);
+        }
+
+        if let Some(offset) = params.offset {
+            query_builder.push(" OFFSET ");
+            query_builder.push_bind(offset);
+        }
+
+        query_builder
+    }
+
+    /// Builds the query behind `count`, with the same filters as `find_query`
+    pub fn count_query(params: &ShortenedUrlQueryParams) -> QueryBuilder<'_, Postgres> {
+        let mut query_builder =
+            QueryBuilder::new("SELECT COUNT(*) FROM shortened_urls WHERE deleted_at IS NULL");
+        Self::push_filters(&mut query_builder, params);
+        query_builder
+    }
+
+    // Adds the WHERE conditions for the given filters
+    fn push_filters<'a>(
+        query_builder: &mut QueryBuilder<'a, Postgres>,
+        params: &'a ShortenedUrlQueryParams,
+    ) {
+        match params.tenant {
+            TenantScope::All => {}
+            TenantScope::Only(Some(tenant_id)) => {
+                query_builder.push(" AND tenant_id = ");
+                query_builder.push_bind(tenant_id);
+            }
+            TenantScope::Only(None) => {
+                query_builder.push(" AND tenant_id IS NULL");
+            }
+        }
+
+        if params.include_archived != Some(true) {
+            query_builder.push(" AND archived_at IS NULL");
+        }
+
+        // Add conditions based on provided parameters
+        if let Some(code) = &params.short_code {
+            query_builder.push(" AND short_code = ");
+            query_builder.push_bind(code);
+        }
+
+        if let Some(namespace) = &params.namespace {
+            query_builder.push(" AND namespace = ");
+            query_builder.push_bind(namespace);
+        }
+
+        if let Some(url) = &params.original_url {
+            query_builder.push(" AND original_url LIKE ");
+            query_builder.push_bind(format!("%{}%", url));
+        }
+
+        if let Some(id) = params.id {
+            query_builder.push(" AND id = ");
+            query_builder.push_bind(id);
+        }
+
+        if let Some(after) = params.created_after {
+            query_builder.push(" AND created_at >= ");
+            query_builder.push_bind(after);
+        }
+
+        if let Some(before) = params.created_before {
+            query_builder.push(" AND created_at <= ");
+            query_builder.push_bind(before);
+        }
+
+        let now = Utc::now();
+        if let Some(true) = params.is_expired {
+            // URLs that have an expiration date in the past
+            query_builder.push(" AND (expires_at IS NOT NULL AND expires_at < ");
+            query_builder.push_bind(now);
+            query_builder.push(")");
+        } else if let Some(false) = params.is_expired {
+            // URLs that either have no expiration or expiration in the future
+            query_builder.push(" AND (expires_at IS NULL OR expires_at >= ");
+            query_builder.push_bind(now);
+            query_builder.push(")");
+        }
+
+        if let Some(is_active) = params.is_active {
+            query_builder.push(" AND is_active = ");
+            query_builder.push_bind(is_active);
+        }
+
+        if let Some(is_custom_code) = params.is_custom_code {
+            query_builder.push(" AND is_custom_code = ");
+            query_builder.push_bind(is_custom_code);
+        }
+
+        if let Some(status) = params.status {
+            query_builder.push(" AND status = ");
+            query_builder.push_bind(status);
+        }
+
+        if let Some(broken) = params.broken {
+            query_builder.push(" AND is_broken = ");
+            query_builder.push_bind(broken);
+        }
+
+        // Without a user there are no favorites to look at
+        if let (Some(favorites), Some(user)) = (params.favorites, params.user) {
+            query_builder.push(if favorites {
+                " AND id IN "
+            } else {
+                " AND id NOT IN "
+            });
+            query_builder.push("(SELECT url_id FROM link_favorites WHERE user_id = ");
+            query_builder.push_bind(user);
+            query_builder.push(")");
+        }
+
+        if let Some(min_count) = params.min_access_count {
+            query_builder.push(" AND access_count >= ");
+            query_builder.push_bind(min_count);
+        }
+
+        // Checking for the key first lets the metadata index narrow down the rows
+        if let Some(filter) = &params.metadata_contains {
+            query_builder.push(" AND metadata ? ");
+            query_builder.push_bind(&filter.key);
+            query_builder.push(" AND metadata ->> ");
+            query_builder.push_bind(&filter.key);
+            query_builder.push(" ILIKE ");
+            query_builder.push_bind(format!("%{}%", escape_like(&filter.value)));
+        }
+
+        if let Some(filter) = &params.metadata_includes {
+            query_builder.push(" AND metadata @> ");
+            query_builder
+                .push_bind(serde_json::json!({ filter.key.clone(): [filter.value.clone()] }));
+        }
+
+        if let Some(search) = &params.q {
+            for term in search.terms() {
+                query_builder.push(" AND (");
+                Self::push_search_filter(query_builder, &term.filter);
+                // NULL columns make a negated condition hold rather than drop the row
+                query_builder.push(if term.negated { ") IS NOT TRUE" } else { ")" });
+            }
+        }
+    }
+
+    // Adds the condition of one term of the `q` search, with every value bound
+    fn push_search_filter(query_builder: &mut QueryBuilder<'_, Postgres>, filter: &SearchFilter) {
+        match filter {
+            SearchFilter::Text(text) => {
+                query_builder.push("original_url ILIKE ");
+                query_builder.push_bind(format!("%{}%", escape_like(text)));
+            }
+            SearchFilter::Domain(domain) => {
+                query_builder.push(DESTINATION_HOST).push(" = ");
+                query_builder.push_bind(domain.clone());
+                query_builder
+                    .push(" OR ")
+                    .push(DESTINATION_HOST)
+                    .push(" LIKE ");
+                query_builder.push_bind(format!("%.{}", escape_like(domain)));
+            }
+            SearchFilter::Clicks(range) => {
+                query_builder.push("TRUE");
+                if let Some(min) = range.min {
+                    query_builder.push(" AND access_count >= ").push_bind(min);
+                }
+                if let Some(max) = range.max {
+                    query_builder.push(" AND access_count <= ").push_bind(max);
+                }
+            }
+            SearchFilter::Created(range) => {
+                Self::push_date_range(query_builder, "created_at", range);
+            }
+            SearchFilter::Expires(range) => {
+                Self::push_date_range(query_builder, "expires_at", range);
+            }
+            SearchFilter::Tag(tag) => {
+                query_builder.push("metadata @> ");
+                query_builder.push_bind(serde_json::json!({ "tags": [tag] }));
+            }
+            SearchFilter::Code(code) => {
+                query_builder.push("short_code = ").push_bind(code.clone());
+            }
+            SearchFilter::Namespace(namespace) => {
+                query_builder
+                    .push("namespace = ")
+                    .push_bind(namespace.clone());
+            }
+            SearchFilter::Status(status) => {
+                query_builder.push("status = ").push_bind(*status);
+            }
+            SearchFilter::Is(LinkFlag::Active) => {
+                query_builder.push("is_active");
+            }
+            SearchFilter::Is(LinkFlag::Expired) => {
+                query_builder.push("expires_at < ").push_bind(Utc::now());
+            }
+            SearchFilter::Is(LinkFlag::Custom) => {
+                query_builder.push("is_custom_code");
+            }
+            SearchFilter::Is(LinkFlag::Broken) => {
+                query_builder.push("is_broken");
+            }
+        }
+    }
+
+    fn push_date_range(
+        query_builder: &mut QueryBuilder<'_, Postgres>,
+        column: &str,
+        range: &DateRange,
+    ) {
+        query_builder.push(column).push(" IS NOT NULL");
+        if let Some(from) = range.from {
+            query_builder
+                .push(" AND ")
+                .push(column)
+                .push(" >= ")
+                .push_bind(from);
+        }
+        if let Some(until) = range.until {
+            query_builder
+                .push(" AND ")
+                .push(column)
+                .push(" < ")
+                .push_bind(until);
+        }
+    }
+}
+
+#[async_trait]
+impl ShortenedUrlRepositoryTrait for ShortenedUrlRepository {
+    async fn save(&self, url: &NewShortenedUrl) -> Result<ShortenedUrl> {
+        // Start a transaction so we can rollback if needed
+        let mut tx = begin_on(self.shard_pool(&url.short_code)).await?;
+        let record = self.save_in(&mut tx, url).await?;
+        commit(tx).await?;
+
+        Ok(record)
+    }
+
+    async fn begin(&self) -> Result<DbTransaction> {
+        begin_on(&self.pool).await
+    }
+
+    async fn save_in(&self, tx: &mut DbTransaction, url: &NewShortenedUrl) -> Result<ShortenedUrl> {
+        // Insert the shortened URL
+        let record = sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                INSERT INTO shortened_urls 
+                (original_url, short_code, last_accessed, expires_at, is_custom_code, metadata, created_by, allowed_cidrs, denied_cidrs, single_use, namespace, forward_path, forward_query, fallback_url, max_idle_days, tenant_id, upgrade_https, behavior, app_url, og_title, og_description, og_image, max_redirects_per_minute, notes)
+                VALUES ($1, $2, NULL, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23)
+                RETURNING id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+            "#,
+            url.original_url,
+            url.short_code,
+            url.expires_at,
+            url.is_custom_code,
+            url.metadata,
+            url.created_by,
+            &url.allowed_cidrs,
+            &url.denied_cidrs,
+            url.single_use,
+            url.namespace,
+            url.forward_path,
+            url.forward_query,
+            url.fallback_url,
+            url.max_idle_days,
+            url.tenant_id,
+            url.upgrade_https,
+            url.behavior as LinkBehavior,
+            url.app_url,
+            url.og_title,
+            url.og_description,
+            url.og_image,
+            url.max_redirects_per_minute,
+            url.notes
+        )
+        .fetch_one(&mut **tx)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to insert shortened URL: {}", e);
+            RepositoryError::from(e)
+        })?;
+
+        Ok(record)
+    }
+
+    async fn find(&self, params: &ShortenedUrlQueryParams) -> Result<Vec<ShortenedUrl>> {
+        let mut query_builder = Self::find_query(params);
+
+        // Build the final query
+        let query = query_builder.build_query_as::<ShortenedUrl>();
+
+        // Execute and return the results
+        let results = query.fetch_all(&self.pool).await?;
+
+        Ok(results)
+    }
+
+    fn stream(&self, params: ShortenedUrlQueryParams) -> BoxStream<'static, Result<ShortenedUrl>> {
+        let pool = self.pool.clone();
+        let (mut sender, receiver) = mpsc::channel(STREAM_BUFFER);
+
+        // The row stream borrows the query, so it is driven by its own task
+        tokio::spawn(async move {
+            let mut query_builder = Self::find_query(&params);
+            let mut rows = query_builder.build_query_as::<ShortenedUrl>().fetch(&pool);
+
+            while let Some(row) = rows.next().await {
+                let failed = row.is_err();
+                // Sending fails once the consumer went away
+                if sender
+                    .send(row.map_err(RepositoryError::from))
+                    .await
+                    .is_err()
+                    || failed
+                {
+                    break;
+                }
+            }
+        });
+
+        receiver.boxed()
+    }
+
+    async fn count(&self, params: &ShortenedUrlQueryParams) -> Result<i64> {
+        let mut query_builder = Self::count_query(params);
+        let count = query_builder
+            .build_query_scalar::<i64>()
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    async fn find_by_id(&self, id: &Uuid) -> Result<Option<ShortenedUrl>> {
+        sqlx::query_as!(
+                ShortenedUrl,
+                r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE id = $1 AND deleted_at IS NULL
+                "#,
+                id
+            )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(RepositoryError::Database)
+    }
+
+    async fn find_remapped_id(&self, old_id: &Uuid) -> Result<Option<Uuid>> {
+        sqlx::query_scalar!(
+            "SELECT new_id FROM url_id_remaps WHERE old_id = $1",
+            old_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn lock_by_id_in(
+        &self,
+        tx: &mut DbTransaction,
+        id: &Uuid,
+    ) -> Result<Option<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE id = $1 AND deleted_at IS NULL
+                FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_all(
+        &self,
+        tenant: Option<&Uuid>,
+        limit: Option<i64>,
+        offset: Option<i64>,
+    ) -> Result<Vec<ShortenedUrl>> {
+        // Create a query params object without filters besides the tenant
+        let params = ShortenedUrlQueryParams {
+            limit,
+            offset,
+            tenant: TenantScope::Only(tenant.copied()),
+            ..Default::default()
+        };
+
+        // Use the existing find method
+        self.find(&params).await
+    }
+
+    async fn find_by_codes(
+        &self,
+        tenant: Option<&Uuid>,
+        paths: &[String],
+        ignore_case: bool,
+    ) -> Result<Vec<ShortenedUrl>> {
+        // Narrow down by the bare code first so the code index is used
+        let codes = paths
+            .iter()
+            .map(|path| path.rsplit('/').next().unwrap_or(path).to_string())
+            .collect::<Vec<_>>();
+
+        if ignore_case {
+            return sqlx::query_as!(
+                ShortenedUrl,
+                r#"
+                    SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                    FROM shortened_urls
+                    WHERE LOWER(short_code) = ANY($2)
+                      AND LOWER(COALESCE(namespace || '/', '') || short_code) = ANY($1)
+                      AND tenant_id IS NOT DISTINCT FROM $3
+                      AND deleted_at IS NULL
+                "#,
+                paths,
+                &codes,
+                tenant
+            )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(RepositoryError::Database);
+        }
+
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE short_code = ANY($2)
+                  AND COALESCE(namespace || '/', '') || short_code = ANY($1)
+                  AND tenant_id IS NOT DISTINCT FROM $3
+                  AND deleted_at IS NULL
+            "#,
+            paths,
+            &codes,
+            tenant
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_by_code_ignore_case(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Result<Option<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE LOWER(short_code) = LOWER($1)
+                  AND LOWER(COALESCE(namespace, '')) = LOWER(COALESCE($2, ''))
+                  AND tenant_id IS NOT DISTINCT FROM $3
+                  AND deleted_at IS NULL
+                ORDER BY short_code = $1 DESC, namespace IS NOT DISTINCT FROM $2 DESC
+                LIMIT 1
+            "#,
+            code,
+            namespace,
+            tenant
+        )
+        .fetch_optional(self.shard_pool(code))
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_by_code(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Result<Option<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE short_code = $1
+                  AND namespace IS NOT DISTINCT FROM $2
+                  AND tenant_id IS NOT DISTINCT FROM $3
+                  AND deleted_at IS NULL
+            "#,
+            code,
+            namespace,
+            tenant
+        )
+        .fetch_optional(self.shard_pool(code))
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn update(
+        &self,
+        id: &Uuid,
+        params: &ShortenedUrlUpdateParams,
+        changed_by: Option<&Uuid>,
+    ) -> Result<u64> {
+        let mut tx = self.begin().await?;
+        let affected = self.update_in(&mut tx, id, params, changed_by).await?;
+        commit(tx).await?;
+
+        Ok(affected)
+    }
+
+    async fn update_in(
+        &self,
+        tx: &mut DbTransaction,
+        id: &Uuid,
+        params: &ShortenedUrlUpdateParams,
+        changed_by: Option<&Uuid>,
+    ) -> Result<u64> {
+        debug!("Updating URL with id: {} and params: {:?}", id, params);
+
+        // Lock the row so concurrent changes get consecutive revisions
+        let current = sqlx::query_scalar!(
+            r#"
+                SELECT original_url FROM shortened_urls
+                WHERE id = $1 AND deleted_at IS NULL
+                FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(&mut **tx)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        let Some(current) = current else {
+            return Ok(0);
+        };
+
+        if params
+            .original_url
+            .as_ref()
+            .is_some_and(|url| *url != current)
+        {
+            sqlx::query!(
+                r#"
+                    INSERT INTO url_revisions (url_id, revision, original_url, changed_by, change_comment)
+                    SELECT $1, COALESCE(MAX(revision), 0) + 1, $2, $3, $4
+                    FROM url_revisions
+                    WHERE url_id = $1
+                "#,
+                id,
+                current,
+                changed_by,
+                params.change_comment
+            )
+            .execute(&mut **tx)
+            .await
+            .map_err(RepositoryError::Database)?;
+        }
+
+        let mut builder = QueryBuilder::new("UPDATE shortened_urls SET ");
+        let mut separated = builder.separated(", ");
+
+        separated.push("version = version + 1");
+
+        if let Some(url) = &params.original_url {
+            separated.push("original_url = ").push_bind_unseparated(url);
+        }
+
+        if let Some(is_active) = &params.is_active {
+            if *is_active {
+                separated.push("expires_at = NULL");
+            } else {
+                separated
+                    .push("expires_at = ")
+                    .push_bind_unseparated(Utc::now());
+            }
+        } else if let Some(expires_at) = params.expires_at {
+            separated
+                .push("expires_at = ")
+                .push_bind_unseparated(expires_at);
+        }
+
+        if let Some(metadata) = &params.metadata {
+            separated
+                .push("metadata = ")
+                .push_bind_unseparated(metadata);
+        }
+
+        if let Some(cidrs) = &params.allowed_cidrs {
+            separated
+                .push("allowed_cidrs = ")
+                .push_bind_unseparated(parse_cidrs(cidrs));
+        }
+
+        if let Some(cidrs) = &params.denied_cidrs {
+            separated
+                .push("denied_cidrs = ")
+                .push_bind_unseparated(parse_cidrs(cidrs));
+        }
+
+        if let Some(forward_path) = params.forward_path {
+            separated
+                .push("forward_path = ")
+                .push_bind_unseparated(forward_path);
+        }
+
+        if let Some(forward_query) = params.forward_query {
+            separated
+                .push("forward_query = ")
+                .push_bind_unseparated(forward_query);
+        }
+
+        if let Some(fallback_url) = &params.fallback_url {
+            separated
+                .push("fallback_url = ")
+                .push_bind_unseparated(fallback_url);
+        }
+
+        if let Some(max_idle_days) = params.max_idle_days {
+            separated
+                .push("max_idle_days = ")
+                .push_bind_unseparated(max_idle_days);
+        }
+
+        if let Some(upgrade_https) = params.upgrade_https {
+            separated
+                .push("upgrade_https = ")
+                .push_bind_unseparated(upgrade_https);
+        }
+
+        if let Some(behavior) = params.behavior {
+            separated
+                .push("behavior = ")
+                .push_bind_unseparated(behavior);
+        }
+
+        if let Some(app_url) = &params.app_url {
+            separated.push("app_url = ").push_bind_unseparated(app_url);
+        }
+
+        if let Some(og_title) = &params.og_title {
+            separated
+                .push("og_title = ")
+                .push_bind_unseparated(og_title);
+        }
+
+        if let Some(og_description) = &params.og_description {
+            separated
+                .push("og_description = ")
+                .push_bind_unseparated(og_description);
+        }
+
+        if let Some(og_image) = &params.og_image {
+            separated
+                .push("og_image = ")
+                .push_bind_unseparated(og_image);
+        }
+
+        if let Some(max_redirects_per_minute) = params.max_redirects_per_minute {
+            separated
+                .push("max_redirects_per_minute = ")
+                .push_bind_unseparated(max_redirects_per_minute);
+        }
+
+        if let Some(notes) = &params.notes {
+            separated.push("notes = ").push_bind_unseparated(notes);
+        }
+
+        // Add the WHERE clause, refusing edits based on an outdated version
+        builder.push(" WHERE id = ").push_bind(id);
+        if let Some(version) = params.version {
+            builder.push(" AND version = ").push_bind(version);
+        }
+
+        // Optional: RETURNING if you want the updated row back
+        // builder.push(" RETURNING *");
+
+        let query = builder.build();
+
+        // Execute it
+        let result = query.execute(&mut **tx).await?;
+        let affected = result.rows_affected();
+
+        // The row is locked and exists, so nothing updated means the version moved on
+        if affected == 0 {
+            return Err(RepositoryError::Conflict(format!(
+                "URL with ID {} was changed since version {}",
+                id,
+                params.version.unwrap_or_default()
+            )));
+        }
+
+        debug!("Updated URL with ID {}: {:?}", id, result);
+        Ok(affected)
+    }
+
+    async fn find_revisions(&self, url_id: &Uuid) -> Result<Vec<UrlRevision>> {
+        sqlx::query_as!(
+            UrlRevision,
+            r#"
+                SELECT id, url_id, revision, original_url, changed_by, changed_at, change_comment
+                FROM url_revisions
+                WHERE url_id = $1
+                ORDER BY revision DESC
+            "#,
+            url_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_revision(&self, url_id: &Uuid, revision: i32) -> Result<Option<UrlRevision>> {
+        sqlx::query_as!(
+            UrlRevision,
+            r#"
+                SELECT id, url_id, revision, original_url, changed_by, changed_at, change_comment
+                FROM url_revisions
+                WHERE url_id = $1 AND revision = $2
+            "#,
+            url_id,
+            revision
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn delete(&self, id: &Uuid, require_exists: bool) -> Result<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE shortened_urls
+            SET deleted_at = NOW()
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+            id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        let is_rows_deleted = result.rows_affected() > 0;
+
+        // Check if we should require the record to exist
+        if require_exists && !is_rows_deleted {
+            return Err(RepositoryError::NotFound(format!(
+                "URL with ID {} not found",
+                id
+            )));
         }
 
-        // Build the final query
-        let query = query_builder.build_query_as::<ShortenedUrl>();
+        // Return whether a row was actually deleted
+        Ok(is_rows_deleted)
+    }
+
+    async fn delete_in(&self, tx: &mut DbTransaction, id: &Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            "UPDATE shortened_urls SET deleted_at = NOW() WHERE id = $1 AND deleted_at IS NULL",
+            id
+        )
+        .execute(&mut **tx)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn find_expiring(
+        &self,
+        tenant: Option<&Uuid>,
+        within_days: i32,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<ShortenedUrl>> {
+        // The window is walked in expiry order on idx_shortened_urls_expires_at
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE expires_at > NOW()
+                  AND expires_at <= NOW() + make_interval(days => $2)
+                  AND tenant_id IS NOT DISTINCT FROM $1
+                  AND is_active
+                  AND consumed_at IS NULL
+                  AND deleted_at IS NULL
+                ORDER BY expires_at, id
+                LIMIT $3 OFFSET $4
+            "#,
+            tenant,
+            within_days,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_trash(
+        &self,
+        tenant: Option<&Uuid>,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<TrashedUrl>> {
+        sqlx::query_as::<_, TrashedUrl>(
+            r#"
+                SELECT *
+                FROM shortened_urls
+                WHERE deleted_at IS NOT NULL
+                  AND tenant_id IS NOT DISTINCT FROM $1
+                ORDER BY deleted_at DESC
+                LIMIT $2 OFFSET $3
+            "#,
+        )
+        .bind(tenant)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_in_trash(
+        &self,
+        id: &Uuid,
+        tenant: Option<&Uuid>,
+    ) -> Result<Option<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE id = $1
+                  AND deleted_at IS NOT NULL
+                  AND tenant_id IS NOT DISTINCT FROM $2
+            "#,
+            id,
+            tenant
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn restore(&self, id: &Uuid, tenant: Option<&Uuid>) -> Result<Option<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                UPDATE shortened_urls
+                SET deleted_at = NULL
+                WHERE id = $1
+                  AND deleted_at IS NOT NULL
+                  AND tenant_id IS NOT DISTINCT FROM $2
+                RETURNING id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+            "#,
+            id,
+            tenant
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn purge_trash(&self, before: DateTime<Utc>, limit: i64) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+                DELETE FROM shortened_urls
+                WHERE id IN (
+                    SELECT id FROM shortened_urls
+                    WHERE deleted_at < $1
+                    ORDER BY deleted_at
+                    LIMIT $2
+                )
+            "#,
+            before,
+            limit
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn count_trash(&self, before: DateTime<Utc>) -> Result<TrashStats> {
+        sqlx::query_as!(
+            TrashStats,
+            r#"
+                SELECT COUNT(*) as "trashed!",
+                       COUNT(*) FILTER (WHERE deleted_at < $1) as "due_for_purge!"
+                FROM shortened_urls
+                WHERE deleted_at IS NOT NULL
+            "#,
+            before
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn consume(&self, id: &Uuid) -> Result<bool> {
+        let consumed = sqlx::query_scalar!(
+            r#"
+                UPDATE shortened_urls
+                SET consumed_at = NOW()
+                WHERE id = $1 AND single_use AND consumed_at IS NULL
+                RETURNING id
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(consumed.is_some())
+    }
+
+    async fn record_access(&self, id: &Uuid) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+                UPDATE shortened_urls
+                SET access_count = access_count + 1, last_accessed = NOW()
+                WHERE id = $1
+                RETURNING access_count
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    async fn add_accesses(
+        &self,
+        id: &Uuid,
+        count: i64,
+        last_accessed: DateTime<Utc>,
+    ) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+                UPDATE shortened_urls
+                SET access_count = access_count + $2,
+                    last_accessed = GREATEST(last_accessed, $3)
+                WHERE id = $1
+                RETURNING access_count
+            "#,
+            id,
+            count,
+            last_accessed
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(count.unwrap_or(0))
+    }
+
+    async fn claim_milestones(&self, id: &Uuid, milestones: &[i64]) -> Result<Vec<i64>> {
+        // The primary key decides between concurrent claims of the same milestone
+        sqlx::query_scalar!(
+            r#"
+                INSERT INTO link_milestones (url_id, milestone)
+                SELECT $1, milestone FROM UNNEST($2::BIGINT[]) AS m(milestone)
+                ON CONFLICT DO NOTHING
+                RETURNING milestone
+            "#,
+            id,
+            milestones
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn find_most_accessed(&self, limit: i64) -> Result<Vec<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE is_active
+                  AND (expires_at IS NULL OR expires_at > NOW())
+                  AND consumed_at IS NULL
+                  AND deleted_at IS NULL
+                ORDER BY access_count DESC
+                LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
 
-        // Execute and return the results
-        let results = query.fetch_all(&self.pool).await?;
+    async fn dump_rows(&self, limit: i64, offset: i64) -> Result<Vec<JsonValue>> {
+        sqlx::query_scalar!(
+            r#"
+                SELECT to_jsonb(u) as "row!"
+                FROM shortened_urls u
+                ORDER BY id
+                LIMIT $1 OFFSET $2
+            "#,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
 
-        Ok(results)
+    async fn deactivate_idle(&self) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE shortened_urls
+                SET is_active = FALSE
+                WHERE is_active
+                  AND deleted_at IS NULL
+                  AND max_idle_days IS NOT NULL
+                  AND COALESCE(last_accessed, created_at) + make_interval(days => max_idle_days) < NOW()
+            "#
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected())
     }
 
-    async fn find_by_id(&self, id: &Uuid) -> Result<Option<ShortenedUrl>> {
+    async fn archive_unused(&self, before: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query!(
+            r#"
+                UPDATE shortened_urls
+                SET archived_at = NOW()
+                WHERE archived_at IS NULL
+                  AND deleted_at IS NULL
+                  AND COALESCE(last_accessed, created_at) < $1
+            "#,
+            before
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn claim_expired(&self, since: DateTime<Utc>) -> Result<Vec<ShortenedUrl>> {
         sqlx::query_as!(
-                ShortenedUrl,
-                r#"
-                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata
+            ShortenedUrl,
+            r#"
+                WITH claimed AS (
+                    INSERT INTO url_expiry_events (url_id, expires_at)
+                    SELECT id, expires_at
+                    FROM shortened_urls
+                    WHERE expires_at > $1 AND expires_at <= NOW() AND deleted_at IS NULL
+                    ON CONFLICT DO NOTHING
+                    RETURNING url_id
+                )
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE id IN (SELECT url_id FROM claimed)
+                ORDER BY expires_at
+            "#,
+            since
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn update_status(&self, id: &Uuid, status: LinkStatus) -> Result<Option<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                UPDATE shortened_urls
+                SET status = $2
+                WHERE id = $1 AND deleted_at IS NULL
+                RETURNING id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+            "#,
+            id,
+            status as LinkStatus
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn set_archived(&self, id: &Uuid, archived: bool) -> Result<Option<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                UPDATE shortened_urls
+                SET archived_at = CASE WHEN $2 THEN COALESCE(archived_at, NOW()) END
+                WHERE id = $1 AND deleted_at IS NULL
+                RETURNING id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+            "#,
+            id,
+            archived
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_http_destinations(
+        &self,
+        by_default: bool,
+        after: Option<Uuid>,
+        limit: i64,
+    ) -> Result<Vec<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+                FROM shortened_urls
+                WHERE original_url LIKE 'http://%'
+                  AND COALESCE(upgrade_https, $1)
+                  AND ($2::UUID IS NULL OR id > $2)
+                  AND deleted_at IS NULL
+                ORDER BY id
+                LIMIT $3
+            "#,
+            by_default,
+            after,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_links_to_check(&self, limit: i64) -> Result<Vec<ShortenedUrl>> {
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                SELECT id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
                 FROM shortened_urls
+                WHERE is_active AND status <> 'blocked' AND deleted_at IS NULL
+                ORDER BY last_checked_at NULLS FIRST
+                LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn record_check(&self, id: &Uuid, status: Option<i32>, broken: bool) -> Result<()> {
+        sqlx::query!(
+            r#"
+                UPDATE shortened_urls
+                SET last_checked_at = NOW(), check_status = $2, is_broken = $3
                 WHERE id = $1
-                "#,
-                id
-            )
-            .fetch_optional(&self.pool)
+            "#,
+            id,
+            status,
+            broken
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(())
+    }
+
+    async fn add_favorite(&self, user_id: &Uuid, url_id: &Uuid) -> Result<()> {
+        sqlx::query!(
+            r#"
+                INSERT INTO link_favorites (user_id, url_id)
+                VALUES ($1, $2)
+                ON CONFLICT DO NOTHING
+            "#,
+            user_id,
+            url_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(())
+    }
+
+    async fn remove_favorite(&self, user_id: &Uuid, url_id: &Uuid) -> Result<bool> {
+        let result = sqlx::query!(
+            "DELETE FROM link_favorites WHERE user_id = $1 AND url_id = $2",
+            user_id,
+            url_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn create_claim(
+        &self,
+        url_id: &Uuid,
+        token_hash: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query!(
+            "INSERT INTO link_claims (url_id, token_hash, expires_at) VALUES ($1, $2, $3)",
+            url_id,
+            token_hash,
+            expires_at
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)?;
+
+        Ok(())
+    }
+
+    async fn claim(
+        &self,
+        token_hash: &str,
+        user_id: &Uuid,
+        tenant: Option<&Uuid>,
+    ) -> Result<Option<ShortenedUrl>> {
+        // The token is only used up when it gets the link
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                WITH claimed AS (
+                    DELETE FROM link_claims c
+                    USING shortened_urls u
+                    WHERE c.token_hash = $1
+                      AND c.expires_at > NOW()
+                      AND u.id = c.url_id
+                      AND u.created_by IS NULL
+                      AND u.tenant_id IS NOT DISTINCT FROM $3
+                      AND u.deleted_at IS NULL
+                    RETURNING c.url_id
+                )
+                UPDATE shortened_urls
+                SET created_by = $2
+                WHERE id = (SELECT url_id FROM claimed)
+                RETURNING id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+            "#,
+            token_hash,
+            user_id,
+            tenant
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn purge_expired_claims(&self) -> Result<u64> {
+        let result = sqlx::query!("DELETE FROM link_claims WHERE expires_at <= NOW()")
+            .execute(&self.pool)
             .await
-            .map_err(RepositoryError::Database)
+            .map_err(RepositoryError::Database)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn offer_transfer(
+        &self,
+        url_id: &Uuid,
+        from_user_id: &Uuid,
+        to_user_id: &Uuid,
+    ) -> Result<Option<LinkTransfer>> {
+        sqlx::query_as!(
+            LinkTransfer,
+            r#"
+                INSERT INTO link_transfers (url_id, from_user_id, to_user_id, from_tenant_id, to_tenant_id)
+                SELECT id, created_by, $3, tenant_id, tenant_id
+                FROM shortened_urls
+                WHERE id = $1 AND created_by = $2 AND deleted_at IS NULL
+                ON CONFLICT (url_id) WHERE status = 'pending' DO NOTHING
+                RETURNING id, url_id, from_user_id, to_user_id, from_tenant_id, to_tenant_id,
+                       status as "status: TransferStatus", by_admin, created_at, resolved_at
+            "#,
+            url_id,
+            from_user_id,
+            to_user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+
+    async fn find_transfer(&self, id: &Uuid) -> Result<Option<LinkTransfer>> {
+        sqlx::query_as!(
+            LinkTransfer,
+            r#"
+                SELECT id, url_id, from_user_id, to_user_id, from_tenant_id, to_tenant_id,
+                       status as "status: TransferStatus", by_admin, created_at, resolved_at
+                FROM link_transfers
+                WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_pending_transfers(&self, to_user_id: &Uuid) -> Result<Vec<LinkTransfer>> {
+        sqlx::query_as!(
+            LinkTransfer,
+            r#"
+                SELECT id, url_id, from_user_id, to_user_id, from_tenant_id, to_tenant_id,
+                       status as "status: TransferStatus", by_admin, created_at, resolved_at
+                FROM link_transfers
+                WHERE to_user_id = $1 AND status = 'pending'
+                ORDER BY created_at
+            "#,
+            to_user_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_transfers(&self, url_id: &Uuid) -> Result<Vec<LinkTransfer>> {
+        sqlx::query_as!(
+            LinkTransfer,
+            r#"
+                SELECT id, url_id, from_user_id, to_user_id, from_tenant_id, to_tenant_id,
+                       status as "status: TransferStatus", by_admin, created_at, resolved_at
+                FROM link_transfers
+                WHERE url_id = $1
+                ORDER BY created_at DESC, id DESC
+            "#,
+            url_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn accept_transfer(&self, id: &Uuid, user_id: &Uuid) -> Result<Option<ShortenedUrl>> {
+        // The transfer is only accepted when the link is still as it was offered
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                WITH accepted AS (
+                    UPDATE link_transfers t
+                    SET status = 'accepted', resolved_at = NOW()
+                    FROM shortened_urls u
+                    WHERE t.id = $1
+                      AND t.to_user_id = $2
+                      AND t.status = 'pending'
+                      AND u.id = t.url_id
+                      AND u.created_by IS NOT DISTINCT FROM t.from_user_id
+                      AND u.deleted_at IS NULL
+                    RETURNING t.url_id
+                )
+                UPDATE shortened_urls
+                SET created_by = $2
+                WHERE id = (SELECT url_id FROM accepted)
+                RETURNING id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+            "#,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn close_transfer(
+        &self,
+        id: &Uuid,
+        status: TransferStatus,
+    ) -> Result<Option<LinkTransfer>> {
+        sqlx::query_as!(
+            LinkTransfer,
+            r#"
+                UPDATE link_transfers
+                SET status = $2, resolved_at = NOW()
+                WHERE id = $1 AND status = 'pending'
+                RETURNING id, url_id, from_user_id, to_user_id, from_tenant_id, to_tenant_id,
+                       status as "status: TransferStatus", by_admin, created_at, resolved_at
+            "#,
+            id,
+            status as TransferStatus
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn force_transfer(
+        &self,
+        url_id: &Uuid,
+        to_user_id: Option<&Uuid>,
+        to_tenant: Option<&Uuid>,
+    ) -> Result<Option<ShortenedUrl>> {
+        // Recorded and applied in one statement, so the history never misses a transfer
+        sqlx::query_as!(
+            ShortenedUrl,
+            r#"
+                WITH link AS (
+                    SELECT id AS link_id, created_by AS owner, tenant_id AS tenant
+                    FROM shortened_urls
+                    WHERE id = $1 AND deleted_at IS NULL
+                    FOR UPDATE
+                ), cancelled AS (
+                    UPDATE link_transfers
+                    SET status = 'cancelled', resolved_at = NOW()
+                    WHERE url_id IN (SELECT link_id FROM link) AND status = 'pending'
+                ), recorded AS (
+                    INSERT INTO link_transfers (url_id, from_user_id, to_user_id, from_tenant_id, to_tenant_id, status, by_admin, resolved_at)
+                    SELECT link_id, owner, COALESCE($2, owner), tenant, COALESCE($3, tenant), 'accepted', TRUE, NOW()
+                    FROM link
+                )
+                UPDATE shortened_urls
+                SET created_by = COALESCE($2, owner), tenant_id = COALESCE($3, tenant)
+                FROM link
+                WHERE id = link_id
+                RETURNING id, original_url, short_code, created_at, expires_at, last_accessed, access_count, is_custom_code, is_active, metadata, created_by, throttled_until, status as "status: LinkStatus", allowed_cidrs, denied_cidrs, single_use, consumed_at, namespace, forward_path, forward_query, fallback_url, max_idle_days, upgrade_https, last_checked_at, check_status, is_broken, behavior as "behavior: LinkBehavior", app_url, og_title, og_description, og_image, max_redirects_per_minute, version, tenant_id, notes, archived_at
+            "#,
+            url_id,
+            to_user_id,
+            to_tenant
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing::insert_link;
+    use crate::models::shortened_url::{OrderDirection, SortField, SortOrder};
+
+    fn repository(pool: &PgPool) -> ShortenedUrlRepository {
+        ShortenedUrlRepository::new(Database::from_pool(pool.clone()))
+    }
+
+    fn destination(url: &str) -> ShortenedUrlUpdateParams {
+        ShortenedUrlUpdateParams {
+            original_url: Some(url.to_string()),
+            ..Default::default()
+        }
     }
 
-    async fn find_all(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<ShortenedUrl>> {
-        // Create an empty query params object (no filters)
+    #[test]
+    fn find_query_keeps_predicates_index_friendly() {
         let params = ShortenedUrlQueryParams {
-            limit,
-            offset,
+            namespace: Some("docs".to_string()),
+            original_url: Some("example".to_string()),
+            is_expired: Some(true),
+            is_active: Some(false),
+            is_custom_code: Some(true),
+            min_access_count: Some(10),
+            metadata_contains: Some("title:launch".parse().unwrap()),
+            metadata_includes: Some("tags:promo".parse().unwrap()),
+            order_by: Some(SortField::LastAccessed.into()),
+            order_direction: Some(OrderDirection::Desc),
             ..Default::default()
         };
+        let query = ShortenedUrlRepository::find_query(&params);
+        let sql = query.sql();
 
-        // Use the existing find method
-        self.find(&params).await
+        // Plain column comparisons, no functions wrapped around indexed columns
+        for predicate in [
+            "namespace = $1",
+            "original_url LIKE $2",
+            "expires_at IS NOT NULL AND expires_at < $3",
+            "is_active = $4",
+            "is_custom_code = $5",
+            "access_count >= $6",
+            "metadata ? $7 AND metadata ->> $8 ILIKE $9",
+            "metadata @> $10",
+        ] {
+            assert!(
+                sql.contains(predicate),
+                "missing `{}` in {}",
+                predicate,
+                sql
+            );
+        }
+        assert!(sql.ends_with("ORDER BY last_accessed DESC"), "{}", sql);
+
+        // Counting applies the same filters, without ordering
+        let count = ShortenedUrlRepository::count_query(&params);
+        assert!(count.sql().ends_with("metadata @> $10"), "{}", count.sql());
     }
 
-    async fn find_by_code(&self, code: &str) -> Result<Option<ShortenedUrl>> {
+    #[test]
+    fn find_query_compiles_search_terms() {
         let params = ShortenedUrlQueryParams {
-            short_code: Some(code.to_string()),
+            q: Some(
+                "domain:example.com clicks:10..100 -tag:launch 50%_off"
+                    .parse()
+                    .unwrap(),
+            ),
             ..Default::default()
         };
+        let query = ShortenedUrlRepository::find_query(&params);
+        let sql = query.sql();
 
-        self.find(&params)
-            .await
-            .map(|results| results.into_iter().next())
+        for predicate in [
+            &format!(
+                "AND ({} = $1 OR {} LIKE $2)",
+                DESTINATION_HOST, DESTINATION_HOST
+            ),
+            "AND (TRUE AND access_count >= $3 AND access_count <= $4)",
+            "AND (metadata @> $5) IS NOT TRUE",
+            "AND (original_url ILIKE $6)",
+        ] {
+            assert!(
+                sql.contains(predicate),
+                "missing `{}` in {}",
+                predicate,
+                sql
+            );
+        }
+        assert_eq!(escape_like("50%_off"), "50\\%\\_off");
     }
 
-    async fn update(&self, id: &Uuid, params: &ShortenedUrlUpdateParams) -> Result<u64> {
-        debug!("Updating URL with id: {} and params: {:?}", id, params);
+    #[test]
+    fn find_query_orders_by_several_fields() {
+        let params = ShortenedUrlQueryParams {
+            order_by: Some("access_count:desc, created_at".parse().unwrap()),
+            order_direction: Some(OrderDirection::Asc),
+            ..Default::default()
+        };
+        let query = ShortenedUrlRepository::find_query(&params);
+        assert!(
+            query
+                .sql()
+                .ends_with("ORDER BY access_count DESC, created_at ASC"),
+            "{}",
+            query.sql()
+        );
 
-        let mut builder = QueryBuilder::new("UPDATE shortened_urls SET ");
-        let mut separated = builder.separated(", ");
+        assert!("access_count:down".parse::<SortOrder>().is_err());
+        assert!("clicks".parse::<SortOrder>().is_err());
+        assert!("id,id:desc".parse::<SortOrder>().is_err());
+    }
 
-        if let Some(url) = &params.original_url {
-            separated.push("original_url = ").push_bind(url);
-        }
+    #[sqlx::test]
+    async fn update_keeps_previous_destinations_as_revisions(pool: PgPool) {
+        let repository = repository(&pool);
+        let id = insert_link(&pool, "revised").await;
 
-        if let Some(is_active) = &params.is_active {
-            if *is_active {
-                separated.push("expires_at = NULL");
-            } else {
-                separated.push("expires_at = ").push_bind(Utc::now());
-            }
-        }
+        repository
+            .update(&id, &destination("https://example.org"), None)
+            .await
+            .unwrap();
+        repository
+            .update(&id, &destination("https://example.net"), None)
+            .await
+            .unwrap();
+        // Changes leaving the destination alone aren't revisions
+        let deactivate = ShortenedUrlUpdateParams {
+            is_active: Some(false),
+            ..Default::default()
+        };
+        repository.update(&id, &deactivate, None).await.unwrap();
 
-        separated.push("updated_at = ").push_bind(Utc::now());
+        let revisions = repository.find_revisions(&id).await.unwrap();
+        let history: Vec<_> = revisions
+            .iter()
+            .map(|r| (r.revision, r.original_url.as_str()))
+            .collect();
+        assert_eq!(
+            history,
+            [(2, "https://example.org"), (1, "https://example.com")]
+        );
 
-        // Add the WHERE clause
-        builder.push(" WHERE id = ").push_bind(id);
+        let first = repository.find_revision(&id, 1).await.unwrap().unwrap();
+        assert_eq!(first.original_url, "https://example.com");
+        assert!(repository.find_revision(&id, 3).await.unwrap().is_none());
+    }
 
-        // Optional: RETURNING if you want the updated row back
-        // builder.push(" RETURNING *");
+    #[sqlx::test]
+    async fn update_checks_the_version_it_is_based_on(pool: PgPool) {
+        let repository = repository(&pool);
+        let id = insert_link(&pool, "versioned").await;
+        let initial = repository.find_by_id(&id).await.unwrap().unwrap().version;
 
-        let query = builder.build();
+        let based_on = |version| ShortenedUrlUpdateParams {
+            version: Some(version),
+            ..destination("https://example.org")
+        };
+        assert_eq!(
+            repository
+                .update(&id, &based_on(initial), None)
+                .await
+                .unwrap(),
+            1
+        );
+        let updated = repository.find_by_id(&id).await.unwrap().unwrap();
+        assert_eq!(updated.version, initial + 1);
 
-        // Execute it
-        let result = query.execute(&self.pool).await?;
-        let affected = result.rows_affected();
+        // A second edit based on the same read lost the race
+        let stale = repository.update(&id, &based_on(initial), None).await;
+        assert!(
+            matches!(stale, Err(RepositoryError::Conflict(_))),
+            "{:?}",
+            stale
+        );
+        let err = crate::errors::AppError::from(stale.unwrap_err());
+        assert_eq!(actix_web::ResponseError::status_code(&err), 409);
 
-        debug!("Updated URL with ID {}: {:?}", id, result);
-        Ok(affected)
+        // Updates naming no version apply on top of whatever is there
+        repository
+            .update(&id, &destination("https://example.net"), None)
+            .await
+            .unwrap();
+        let latest = repository.find_by_id(&id).await.unwrap().unwrap();
+        assert_eq!(latest.version, initial + 2);
+        assert_eq!(latest.original_url, "https://example.net");
     }
 
-    async fn delete(&self, id: &Uuid, require_exists: bool) -> Result<bool> {
-        let result = sqlx::query!(
-            r#"
-            DELETE FROM shortened_urls
-            WHERE id = $1
-            "#,
-            id
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(RepositoryError::Database)?;
+    #[sqlx::test]
+    async fn dropped_transaction_rolls_back(pool: PgPool) {
+        let repository = repository(&pool);
+        let id = insert_link(&pool, "kept").await;
 
-        let is_rows_deleted = result.rows_affected() > 0;
+        let mut tx = repository.begin().await.unwrap();
+        let saved = repository
+            .save_in(
+                &mut tx,
+                &NewShortenedUrl::new("https://example.org".to_string(), "dropped"),
+            )
+            .await
+            .unwrap();
+        repository
+            .update_in(&mut tx, &id, &destination("https://example.org"), None)
+            .await
+            .unwrap();
+        drop(tx);
 
-        // Check if we should require the record to exist
-        if require_exists && !is_rows_deleted {
-            return Err(RepositoryError::NotFound(format!(
-                "URL with ID {} not found",
-                id
-            )));
+        assert!(repository.find_by_id(&saved.id).await.unwrap().is_none());
+        let kept = repository.find_by_id(&id).await.unwrap().unwrap();
+        assert_eq!(kept.original_url, "https://example.com");
+        assert!(repository.find_revisions(&id).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test]
+    async fn metadata_contains_matches_wildcards_literally(pool: PgPool) {
+        let repository = repository(&pool);
+        for (code, title) in [
+            ("sale", "50% off"),
+            ("launch", "500 off"),
+            ("spring", "Spring"),
+        ] {
+            let id = insert_link(&pool, code).await;
+            sqlx::query("UPDATE shortened_urls SET metadata = $1 WHERE id = $2")
+                .bind(serde_json::json!({ "title": title }))
+                .bind(id)
+                .execute(&pool)
+                .await
+                .unwrap();
         }
 
-        // Return whether a row was actually deleted
-        Ok(is_rows_deleted)
+        let matching = |filter: &str| {
+            let params = ShortenedUrlQueryParams {
+                metadata_contains: Some(filter.parse().unwrap()),
+                ..Default::default()
+            };
+            let repository = &repository;
+            async move {
+                let urls = repository.find(&params).await.unwrap();
+                let mut codes: Vec<_> = urls.into_iter().map(|url| url.short_code).collect();
+                codes.sort();
+                codes
+            }
+        };
+        assert_eq!(matching("title:50%").await, ["sale"]);
+        assert_eq!(matching("title:_0").await, Vec::<String>::new());
+        assert_eq!(matching("title:OFF").await, ["launch", "sale"]);
     }
 }
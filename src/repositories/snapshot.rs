@@ -0,0 +1,304 @@
+// src/repositories/snapshot.rs - Links and settings read and restored as a whole
+use async_trait::async_trait;
+use chrono::Utc;
+use serde_json::Value as JsonValue;
+use sqlx::{types::Json, PgConnection, PgPool};
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{RestoreReport, Snapshot, TableRestore, SNAPSHOT_FORMAT_VERSION};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+// Conflicts listed at most, restoring onto an unrelated instance may clash on every code
+const MAX_CONFLICTS: i64 = 100;
+
+#[async_trait]
+pub trait SnapshotRepositoryTrait {
+    /// Finds the latest migration applied to the database
+    ///
+    /// ### Returns
+    /// * `Result<i64>` - Version of the migration, 0 when none was applied
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn schema_version(&self) -> Result<i64>;
+
+    /// Reads links and settings as of a single point in time
+    ///
+    /// ### Returns
+    /// * `Result<Snapshot>` - Every row of the snapshot tables
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn take(&self) -> Result<Snapshot>;
+
+    /// Inserts the rows of a snapshot missing here, in one transaction
+    ///
+    /// Rows already here, by id or unique name, are kept. Nothing is written when tenants or
+    /// links of the snapshot clash with different ones, which the report lists.
+    ///
+    /// ### Arguments
+    /// * `snapshot` - The snapshot, taken at the schema version of this database
+    /// * `dry_run` - Roll everything back once done, to see what restoring would do
+    ///
+    /// ### Returns
+    /// * `Result<RestoreReport>` - Rows inserted and skipped by table, and the conflicts
+    ///
+    /// ### Errors
+    /// * `RepositoryError::InvalidData` - If rows don't fit their table
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn restore(&self, snapshot: &Snapshot, dry_run: bool) -> Result<RestoreReport>;
+}
+
+// Implementation using actual database
+pub struct SnapshotRepository {
+    pool: PgPool,
+}
+
+impl SnapshotRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl SnapshotRepositoryTrait for SnapshotRepository {
+    async fn schema_version(&self) -> Result<i64> {
+        let mut conn = self.pool.acquire().await?;
+        schema_version(&mut conn).await
+    }
+
+    async fn take(&self) -> Result<Snapshot> {
+        let mut tx = self.pool.begin().await?;
+        // Every table is read as of the first query
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ, READ ONLY")
+            .execute(&mut *tx)
+            .await?;
+
+        let snapshot = Snapshot {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            schema_version: schema_version(&mut tx).await?,
+            taken_at: Utc::now(),
+            tenants: rows(&mut tx, "tenants", "TRUE", "id").await?,
+            branding: rows(&mut tx, "branding", "TRUE", "tenant_id").await?,
+            app_links: rows(&mut tx, "app_links", "TRUE", "tenant_id").await?,
+            saved_searches: rows(&mut tx, "saved_searches", "TRUE", "id").await?,
+            blocked_ips: rows(&mut tx, "blocked_ips", "source = 'manual'", "ip").await?,
+            links: rows(&mut tx, "shortened_urls", "TRUE", "id").await?,
+        };
+        tx.rollback().await?;
+        Ok(snapshot)
+    }
+
+    async fn restore(&self, snapshot: &Snapshot, dry_run: bool) -> Result<RestoreReport> {
+        // Referenced tables first
+        let tables = [
+            ("tenants", &snapshot.tenants),
+            ("branding", &snapshot.branding),
+            ("app_links", &snapshot.app_links),
+            ("saved_searches", &snapshot.saved_searches),
+            ("blocked_ips", &snapshot.blocked_ips),
+            ("shortened_urls", &snapshot.links),
+        ];
+
+        let mut tx = self.pool.begin().await?;
+        for (table, rows) in tables {
+            stage(&mut tx, table, rows).await?;
+        }
+
+        let mut report = RestoreReport {
+            dry_run,
+            tables: tables
+                .iter()
+                .map(|(table, rows)| TableRestore {
+                    table,
+                    rows: rows.len() as u64,
+                    inserted: 0,
+                    skipped: 0,
+                })
+                .collect(),
+            unknown_owners: 0,
+            conflicts: find_conflicts(&mut tx).await?,
+        };
+        if !report.conflicts.is_empty() {
+            tx.rollback().await?;
+            return Ok(report);
+        }
+
+        // Owners are users, which a snapshot doesn't carry
+        report.unknown_owners = sqlx::query(
+            r#"
+                UPDATE restore_shortened_urls r
+                SET created_by = NULL
+                WHERE created_by IS NOT NULL
+                  AND NOT EXISTS (SELECT 1 FROM users WHERE id = r.created_by)
+                  AND NOT EXISTS (SELECT 1 FROM shortened_urls WHERE id = r.id)
+            "#,
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        for restored in &mut report.tables {
+            restored.inserted = sqlx::query(&format!(
+                "INSERT INTO {table} SELECT * FROM restore_{table} ON CONFLICT DO NOTHING",
+                table = restored.table
+            ))
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+            restored.skipped = restored.rows - restored.inserted;
+        }
+
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+        Ok(report)
+    }
+}
+
+async fn schema_version(conn: &mut PgConnection) -> Result<i64> {
+    // Not checked at compile time, sqlx creates the table when migrating
+    sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM _sqlx_migrations WHERE success")
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(RepositoryError::Database)
+}
+
+// Rows of a table as JSON objects, keyed by column
+async fn rows(
+    conn: &mut PgConnection,
+    table: &str,
+    filter: &str,
+    order: &str,
+) -> Result<Vec<JsonValue>> {
+    sqlx::query_scalar(&format!(
+        "SELECT to_jsonb(t) FROM {} t WHERE {} ORDER BY {}",
+        table, filter, order
+    ))
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(RepositoryError::Database)
+}
+
+// Load rows into a temporary copy of their table, dropped with the transaction
+async fn stage(conn: &mut PgConnection, table: &str, rows: &[JsonValue]) -> Result<()> {
+    sqlx::query(&format!(
+        "CREATE TEMP TABLE restore_{table} ON COMMIT DROP AS \
+         SELECT * FROM jsonb_populate_recordset(NULL::{table}, $1)"
+    ))
+    .bind(Json(rows))
+    .execute(&mut *conn)
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(e) => {
+            RepositoryError::InvalidData(format!("Rows of {} don't fit the table: {}", table, e))
+        }
+        e => RepositoryError::Database(e),
+    })?;
+    Ok(())
+}
+
+// Tenants and links of the snapshot taking a slug, domain or code another one has here
+async fn find_conflicts(conn: &mut PgConnection) -> Result<Vec<String>> {
+    sqlx::query_scalar(
+        r#"
+            SELECT format('Tenant ''%s'' clashes with tenant ''%s''', r.slug, t.slug)
+            FROM restore_tenants r
+            JOIN tenants t ON t.id <> r.id AND (t.slug = r.slug OR t.domain = r.domain)
+            UNION ALL
+            (
+                SELECT format(
+                    'Code ''%s'' is taken by another link',
+                    COALESCE(r.namespace || '/', '') || r.short_code
+                )
+                FROM restore_shortened_urls r
+                JOIN shortened_urls u
+                  ON u.id <> r.id
+                 AND u.short_code = r.short_code
+                 AND COALESCE(u.namespace, '') = COALESCE(r.namespace, '')
+                 AND COALESCE(u.tenant_id, '00000000-0000-0000-0000-000000000000')
+                   = COALESCE(r.tenant_id, '00000000-0000-0000-0000-000000000000')
+                ORDER BY r.short_code
+            )
+            LIMIT $1
+        "#,
+    )
+    .bind(MAX_CONFLICTS)
+    .fetch_all(&mut *conn)
+    .await
+    .map_err(RepositoryError::Database)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::testing::insert_link;
+
+    async fn codes(pool: &PgPool) -> Vec<String> {
+        sqlx::query_scalar("SELECT short_code FROM shortened_urls ORDER BY short_code")
+            .fetch_all(pool)
+            .await
+            .unwrap()
+    }
+
+    fn links_restored(report: &RestoreReport) -> (u64, u64) {
+        let links = report
+            .tables
+            .iter()
+            .find(|t| t.table == "shortened_urls")
+            .unwrap();
+        (links.inserted, links.skipped)
+    }
+
+    #[sqlx::test]
+    async fn restore_inserts_missing_rows_unless_dry_run(pool: PgPool) {
+        let repository = SnapshotRepository::new(Database::from_pool(pool.clone()));
+        insert_link(&pool, "kept").await;
+        insert_link(&pool, "lost").await;
+        let snapshot = repository.take().await.unwrap();
+        sqlx::query("DELETE FROM shortened_urls WHERE short_code = 'lost'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // A dry run reports what restoring would do, and leaves everything as it was
+        let report = repository.restore(&snapshot, true).await.unwrap();
+        assert!(report.dry_run && report.conflicts.is_empty());
+        assert_eq!(links_restored(&report), (1, 1));
+        assert_eq!(codes(&pool).await, ["kept"]);
+
+        let report = repository.restore(&snapshot, false).await.unwrap();
+        assert_eq!(links_restored(&report), (1, 1));
+        assert_eq!(codes(&pool).await, ["kept", "lost"]);
+    }
+
+    #[sqlx::test]
+    async fn restore_writes_nothing_on_conflict(pool: PgPool) {
+        let repository = SnapshotRepository::new(Database::from_pool(pool.clone()));
+        insert_link(&pool, "launch").await;
+        insert_link(&pool, "spring").await;
+        let snapshot = repository.take().await.unwrap();
+        sqlx::query("DELETE FROM shortened_urls")
+            .execute(&pool)
+            .await
+            .unwrap();
+        // Another link took one of the codes meanwhile
+        insert_link(&pool, "launch").await;
+
+        let report = repository.restore(&snapshot, false).await.unwrap();
+        assert_eq!(report.conflicts, ["Code 'launch' is taken by another link"]);
+        assert_eq!(codes(&pool).await, ["launch"]);
+
+        // Rows that don't fit their table fail the whole restore
+        let mut broken = repository.take().await.unwrap();
+        broken.links.push(serde_json::json!({ "id": "not-a-uuid" }));
+        let err = repository.restore(&broken, false).await.unwrap_err();
+        assert!(matches!(err, RepositoryError::InvalidData(_)), "{:?}", err);
+    }
+}
@@ -0,0 +1,391 @@
+// src/repositories/stats.rs - Aggregate link statistics for reporting
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{channel::mpsc, stream::BoxStream, SinkExt, StreamExt, TryStreamExt};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{DigestLink, GroupStats, StatsDimension, TopLink};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+// Rows read ahead of a slow stream consumer
+const STREAM_BUFFER: usize = 64;
+
+#[async_trait]
+pub trait StatsRepositoryTrait {
+    /// Counts links of a tenant and sums their clicks per group
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant owning the links, `None` for the default tenant
+    /// * `dimension` - What to group links by
+    /// * `tz` - IANA name of the time zone days are bucketed in
+    /// * `limit` - Maximum number of groups to return
+    ///
+    /// ### Returns
+    /// * `Result<Vec<GroupStats>>` - Days newest first, other groups largest first
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn group_by(
+        &self,
+        tenant: Option<&Uuid>,
+        dimension: StatsDimension,
+        tz: &str,
+        limit: i64,
+    ) -> Result<Vec<GroupStats>>;
+
+    /// Streams the groups of `group_by`, for reports
+    ///
+    /// ### Arguments
+    /// * `limit` - Maximum number of groups to return, all of them when `None`
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs, as the last item
+    fn stream_group_by(
+        &self,
+        tenant: Option<Uuid>,
+        dimension: StatsDimension,
+        tz: String,
+        limit: Option<i64>,
+    ) -> BoxStream<'static, Result<GroupStats>>;
+
+    /// Finds the active links of a tenant clicked most since a point in time, most clicked first
+    ///
+    /// Clicks only kept as daily aggregates (rolled up, or not recorded individually)
+    /// count for the whole day, so the oldest day of the window may be over-counted.
+    ///
+    /// ### Arguments
+    /// * `tenant` - The tenant owning the links, `None` for the default tenant
+    /// * `since` - Start of the window
+    /// * `limit` - Maximum number of links to return
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_top_links(
+        &self,
+        tenant: Option<&Uuid>,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<TopLink>>;
+
+    /// Streams the links of `find_top_links`, for reports
+    ///
+    /// ### Arguments
+    /// * `limit` - Maximum number of links to return, all of them when `None`
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs, as the last item
+    fn stream_top_links(
+        &self,
+        tenant: Option<Uuid>,
+        since: DateTime<Utc>,
+        limit: Option<i64>,
+    ) -> BoxStream<'static, Result<TopLink>>;
+
+    /// Finds the links of an owner clicked most within a period, most clicked first
+    ///
+    /// The period should start and end at midnight UTC, so daily aggregates fall either
+    /// inside or outside of it.
+    ///
+    /// ### Arguments
+    /// * `owner` - The user who created the links
+    /// * `from` - Start of the period
+    /// * `until` - End of the period, exclusive
+    /// * `base_url` - Base URL of links whose tenant has none of its own
+    /// * `limit` - Maximum number of links to return
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_owner_top_links(
+        &self,
+        owner: &Uuid,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+        base_url: &str,
+        limit: i64,
+    ) -> Result<Vec<DigestLink>>;
+
+    /// Counts the clicks on all links of an owner within a period
+    ///
+    /// ### Arguments
+    /// * `owner` - The user who created the links
+    /// * `from` - Start of the period, at midnight UTC
+    /// * `until` - End of the period, exclusive, at midnight UTC
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn count_owner_clicks(
+        &self,
+        owner: &Uuid,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<i64>;
+}
+
+pub struct StatsRepository {
+    pool: PgPool,
+}
+
+impl StatsRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+
+    /// Builds the grouping query for a dimension
+    ///
+    /// Only the fixed SQL fragments below end up in the query, never request input; the time
+    /// zone is bound as `$3`, and a null limit `$1` returns every group.
+    pub fn group_by_query(dimension: StatsDimension) -> String {
+        let (group, from, order) = match dimension {
+            StatsDimension::Day => (
+                "to_char(created_at AT TIME ZONE $3, 'YYYY-MM-DD')",
+                "shortened_urls",
+                "\"group\" DESC",
+            ),
+            StatsDimension::Domain => (
+                // Skips the scheme and any credentials, stops before a port or path
+                "lower(substring(original_url FROM '^[^:]+://(?:[^/?#@]*@)?([^/?#:]+)'))",
+                "shortened_urls",
+                "links DESC, \"group\"",
+            ),
+            StatsDimension::Tag => (
+                "tag",
+                "shortened_urls LEFT JOIN LATERAL jsonb_array_elements_text(
+                    CASE WHEN jsonb_typeof(metadata -> 'tags') = 'array'
+                         THEN metadata -> 'tags' ELSE '[]'::jsonb END
+                 ) AS tag ON TRUE",
+                "links DESC, \"group\"",
+            ),
+            StatsDimension::IsCustomCode => (
+                "is_custom_code::TEXT",
+                "shortened_urls",
+                "links DESC, \"group\"",
+            ),
+        };
+
+        format!(
+            "SELECT {} AS \"group\", COUNT(*) AS links, COALESCE(SUM(access_count), 0)::BIGINT AS clicks
+            FROM {}
+            WHERE tenant_id IS NOT DISTINCT FROM $2
+            GROUP BY 1
+            ORDER BY {} NULLS LAST
+            LIMIT $1",
+            group, from, order
+        )
+    }
+}
+
+#[async_trait]
+impl StatsRepositoryTrait for StatsRepository {
+    async fn group_by(
+        &self,
+        tenant: Option<&Uuid>,
+        dimension: StatsDimension,
+        tz: &str,
+        limit: i64,
+    ) -> Result<Vec<GroupStats>> {
+        self.stream_group_by(tenant.copied(), dimension, tz.to_string(), Some(limit))
+            .try_collect()
+            .await
+    }
+
+    fn stream_group_by(
+        &self,
+        tenant: Option<Uuid>,
+        dimension: StatsDimension,
+        tz: String,
+        limit: Option<i64>,
+    ) -> BoxStream<'static, Result<GroupStats>> {
+        let pool = self.pool.clone();
+        let (mut sender, receiver) = mpsc::channel(STREAM_BUFFER);
+
+        // The row stream borrows the query, so it is driven by its own task
+        tokio::spawn(async move {
+            let query = Self::group_by_query(dimension);
+            let mut rows = sqlx::query_as::<_, GroupStats>(&query)
+                .bind(limit)
+                .bind(tenant)
+                .bind(tz)
+                .fetch(&pool);
+
+            while let Some(row) = rows.next().await {
+                let failed = row.is_err();
+                // Sending fails once the consumer went away
+                if sender
+                    .send(row.map_err(RepositoryError::Database))
+                    .await
+                    .is_err()
+                    || failed
+                {
+                    break;
+                }
+            }
+        });
+
+        receiver.boxed()
+    }
+
+    async fn find_top_links(
+        &self,
+        tenant: Option<&Uuid>,
+        since: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<TopLink>> {
+        self.stream_top_links(tenant.copied(), since, Some(limit))
+            .try_collect()
+            .await
+    }
+
+    fn stream_top_links(
+        &self,
+        tenant: Option<Uuid>,
+        since: DateTime<Utc>,
+        limit: Option<i64>,
+    ) -> BoxStream<'static, Result<TopLink>> {
+        let pool = self.pool.clone();
+        let (mut sender, receiver) = mpsc::channel(STREAM_BUFFER);
+
+        // The row stream borrows the query, so it is driven by its own task
+        tokio::spawn(async move {
+            let mut rows = sqlx::query_as!(
+                TopLink,
+                r#"
+                    WITH clicks AS (
+                        SELECT url_id, COUNT(*) AS clicks
+                        FROM url_visits
+                        WHERE visited_at >= $1
+                        GROUP BY url_id
+                        UNION ALL
+                        SELECT url_id, SUM(visits)::BIGINT
+                        FROM url_visit_daily
+                        WHERE day >= ($1 AT TIME ZONE 'UTC')::date
+                        GROUP BY url_id
+                    )
+                    SELECT
+                        s.id AS url_id,
+                        CONCAT_WS('/', s.namespace, s.short_code) AS "short_code!",
+                        s.original_url,
+                        SUM(c.clicks)::BIGINT AS "clicks!"
+                    FROM clicks c
+                    JOIN shortened_urls s ON s.id = c.url_id
+                    WHERE s.is_active AND s.tenant_id IS NOT DISTINCT FROM $3
+                    GROUP BY s.id
+                    ORDER BY 4 DESC, s.id
+                    LIMIT $2
+                "#,
+                since,
+                limit,
+                tenant
+            )
+            .fetch(&pool);
+
+            while let Some(row) = rows.next().await {
+                let failed = row.is_err();
+                // Sending fails once the consumer went away
+                if sender
+                    .send(row.map_err(RepositoryError::Database))
+                    .await
+                    .is_err()
+                    || failed
+                {
+                    break;
+                }
+            }
+        });
+
+        receiver.boxed()
+    }
+
+    async fn find_owner_top_links(
+        &self,
+        owner: &Uuid,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+        base_url: &str,
+        limit: i64,
+    ) -> Result<Vec<DigestLink>> {
+        sqlx::query_as!(
+            DigestLink,
+            r#"
+                WITH owned AS (
+                    SELECT id FROM shortened_urls WHERE created_by = $1
+                ),
+                clicks AS (
+                    SELECT url_id, COUNT(*) AS clicks
+                    FROM url_visits
+                    WHERE url_id IN (SELECT id FROM owned)
+                      AND visited_at >= $2 AND visited_at < $3
+                    GROUP BY url_id
+                    UNION ALL
+                    SELECT url_id, SUM(visits)::BIGINT
+                    FROM url_visit_daily
+                    WHERE url_id IN (SELECT id FROM owned)
+                      AND day >= ($2 AT TIME ZONE 'UTC')::date
+                      AND day < ($3 AT TIME ZONE 'UTC')::date
+                    GROUP BY url_id
+                )
+                SELECT
+                    CONCAT_WS('/', s.namespace, s.short_code) AS "short_code!",
+                    CONCAT(
+                        rtrim(COALESCE(t.base_url, $4), '/'),
+                        '/',
+                        CONCAT_WS('/', s.namespace, s.short_code)
+                    ) AS "short_url!",
+                    s.original_url,
+                    SUM(c.clicks)::BIGINT AS "clicks!"
+                FROM clicks c
+                JOIN shortened_urls s ON s.id = c.url_id
+                LEFT JOIN tenants t ON t.id = s.tenant_id
+                GROUP BY s.id, t.base_url
+                ORDER BY 4 DESC, s.id
+                LIMIT $5
+            "#,
+            owner,
+            from,
+            until,
+            base_url,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn count_owner_clicks(
+        &self,
+        owner: &Uuid,
+        from: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<i64> {
+        sqlx::query_scalar!(
+            r#"
+                SELECT (
+                    (
+                        SELECT COUNT(*)
+                        FROM url_visits v
+                        JOIN shortened_urls s ON s.id = v.url_id
+                        WHERE s.created_by = $1 AND v.visited_at >= $2 AND v.visited_at < $3
+                    ) + (
+                        SELECT COALESCE(SUM(d.visits), 0)
+                        FROM url_visit_daily d
+                        JOIN shortened_urls s ON s.id = d.url_id
+                        WHERE s.created_by = $1
+                          AND d.day >= ($2 AT TIME ZONE 'UTC')::date
+                          AND d.day < ($3 AT TIME ZONE 'UTC')::date
+                    )
+                )::BIGINT AS "clicks!"
+            "#,
+            owner,
+            from,
+            until
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+}
@@ -0,0 +1,83 @@
+// src/repositories/tenant.rs - Tenants sharing the instance
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{CreateTenantDto, Tenant};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait TenantRepositoryTrait {
+    /// Finds every tenant
+    ///
+    /// ### Returns
+    /// * `Result<Vec<Tenant>>` - The tenants, by slug
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_all(&self) -> Result<Vec<Tenant>>;
+
+    /// Adds a tenant
+    ///
+    /// ### Arguments
+    /// * `dto` - The tenant to add
+    ///
+    /// ### Returns
+    /// * `Result<Tenant>` - The stored tenant
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::Conflict` - If the slug or domain is taken
+    async fn create(&self, dto: &CreateTenantDto) -> Result<Tenant>;
+}
+
+// Implementation using actual database
+pub struct TenantRepository {
+    pool: PgPool,
+}
+
+impl TenantRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl TenantRepositoryTrait for TenantRepository {
+    async fn find_all(&self) -> Result<Vec<Tenant>> {
+        sqlx::query_as!(
+            Tenant,
+            r#"
+                SELECT id, slug, name, domain, base_url, fallback_url, created_at
+                FROM tenants
+                ORDER BY slug
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn create(&self, dto: &CreateTenantDto) -> Result<Tenant> {
+        sqlx::query_as!(
+            Tenant,
+            r#"
+                INSERT INTO tenants (slug, name, domain, base_url, fallback_url)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id, slug, name, domain, base_url, fallback_url, created_at
+            "#,
+            dto.slug,
+            dto.name,
+            dto.domain,
+            dto.base_url,
+            dto.fallback_url
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+}
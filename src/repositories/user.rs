@@ -0,0 +1,151 @@
+// src/repositories/user.rs - Users and API keys data access
+use async_trait::async_trait;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::db::Database;
+use crate::errors::RepositoryError;
+use crate::models::{ApiKey, CreateUserDto, User};
+
+type Result<T> = std::result::Result<T, RepositoryError>;
+
+#[async_trait]
+pub trait UserRepositoryTrait {
+    /// Creates a new user
+    ///
+    /// ### Arguments
+    /// * `dto` - The user details
+    ///
+    /// ### Returns
+    /// * `Result<User>` - The newly created user
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::Conflict` - If the email is already registered
+    async fn create(&self, dto: &CreateUserDto) -> Result<User>;
+
+    /// Finds a user by email address
+    ///
+    /// ### Returns
+    /// * `Result<Option<User>>` - The user if registered
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>>;
+
+    /// Finds the owner of a non-revoked API key and records the key as used
+    ///
+    /// ### Arguments
+    /// * `key_hash` - SHA-256 hex digest of the presented key
+    ///
+    /// ### Returns
+    /// * `Result<Option<User>>` - The key's owner, or `None` if the key is unknown or revoked
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    async fn find_by_api_key_hash(&self, key_hash: &str) -> Result<Option<User>>;
+
+    /// Stores a new API key for a user
+    ///
+    /// ### Arguments
+    /// * `user_id` - The owner of the key
+    /// * `name` - Label to tell keys apart
+    /// * `key_hash` - SHA-256 hex digest of the key
+    ///
+    /// ### Returns
+    /// * `Result<ApiKey>` - The stored key metadata
+    ///
+    /// ### Errors
+    /// * `RepositoryError::Database` - If a database error occurs
+    /// * `RepositoryError::InvalidData` - If the user does not exist
+    async fn create_api_key(&self, user_id: &Uuid, name: &str, key_hash: &str) -> Result<ApiKey>;
+}
+
+// Implementation using actual database
+pub struct UserRepository {
+    pool: PgPool,
+}
+
+impl UserRepository {
+    pub fn new(db: Database) -> Self {
+        Self {
+            pool: db.get_pool().clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl UserRepositoryTrait for UserRepository {
+    async fn create(&self, dto: &CreateUserDto) -> Result<User> {
+        sqlx::query_as!(
+            User,
+            r#"
+                INSERT INTO users (email, display_name, settings)
+                VALUES ($1, $2, COALESCE($3, '{}'::jsonb))
+                RETURNING id, email, display_name, settings, created_at
+            "#,
+            dto.email,
+            dto.display_name,
+            dto.settings
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| {
+            log::error!("Failed to insert user: {}", e);
+            RepositoryError::from(e)
+        })
+    }
+
+    async fn find_by_email(&self, email: &str) -> Result<Option<User>> {
+        sqlx::query_as!(
+            User,
+            r#"
+                SELECT id, email, display_name, settings, created_at
+                FROM users
+                WHERE email = $1
+            "#,
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn find_by_api_key_hash(&self, key_hash: &str) -> Result<Option<User>> {
+        sqlx::query_as!(
+            User,
+            r#"
+                WITH used AS (
+                    UPDATE api_keys
+                    SET last_used_at = NOW()
+                    WHERE key_hash = $1 AND revoked_at IS NULL
+                    RETURNING user_id
+                )
+                SELECT u.id, u.email, u.display_name, u.settings, u.created_at
+                FROM users u
+                JOIN used ON used.user_id = u.id
+            "#,
+            key_hash
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(RepositoryError::Database)
+    }
+
+    async fn create_api_key(&self, user_id: &Uuid, name: &str, key_hash: &str) -> Result<ApiKey> {
+        sqlx::query_as!(
+            ApiKey,
+            r#"
+                INSERT INTO api_keys (user_id, name, key_hash)
+                VALUES ($1, $2, $3)
+                RETURNING id, user_id, name, created_at, last_used_at, revoked_at
+            "#,
+            user_id,
+            name,
+            key_hash
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(RepositoryError::from)
+    }
+}
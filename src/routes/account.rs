@@ -0,0 +1,137 @@
+use actix_web::{web, Responder};
+use uuid::Uuid;
+
+use crate::{
+    extractors::AuthenticatedUser,
+    handlers::{
+        create_notification_channel_handler, delete_account_handler,
+        delete_notification_channel_handler, download_export_handler, get_export_handler,
+        get_notification_preferences_handler, list_notification_channels_handler,
+        start_export_handler, test_notification_channel_handler,
+        update_notification_preferences_handler, AccountServiceType, NotificationServiceType,
+    },
+    models::{CreateNotificationChannelDto, DeleteAccountParams, UpdateNotificationPreferencesDto},
+    types::Result,
+};
+
+// Start account export route handler
+async fn start_export(
+    user: AuthenticatedUser,
+    service: web::Data<AccountServiceType>,
+) -> Result<impl Responder> {
+    start_export_handler(user, service).await
+}
+
+// Get account export status route handler
+async fn get_export(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<AccountServiceType>,
+) -> Result<impl Responder> {
+    get_export_handler(user, id, service).await
+}
+
+// Download account export route handler
+async fn download_export(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<AccountServiceType>,
+) -> Result<impl Responder> {
+    download_export_handler(user, id, service).await
+}
+
+// Delete account route handler
+async fn delete_account(
+    user: AuthenticatedUser,
+    params: web::Query<DeleteAccountParams>,
+    service: web::Data<AccountServiceType>,
+) -> Result<impl Responder> {
+    delete_account_handler(user, params, service).await
+}
+
+// Get notification preferences route handler
+async fn get_notification_preferences(
+    user: AuthenticatedUser,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    get_notification_preferences_handler(user, service).await
+}
+
+// Update notification preferences route handler
+async fn update_notification_preferences(
+    user: AuthenticatedUser,
+    dto: web::Json<UpdateNotificationPreferencesDto>,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    update_notification_preferences_handler(user, dto, service).await
+}
+
+// List notification channels route handler
+async fn list_notification_channels(
+    user: AuthenticatedUser,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    list_notification_channels_handler(user, service).await
+}
+
+// Create notification channel route handler
+async fn create_notification_channel(
+    user: AuthenticatedUser,
+    dto: web::Json<CreateNotificationChannelDto>,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    create_notification_channel_handler(user, dto, service).await
+}
+
+// Delete notification channel route handler
+async fn delete_notification_channel(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    delete_notification_channel_handler(user, id, service).await
+}
+
+// Test notification channel route handler
+async fn test_notification_channel(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<NotificationServiceType>,
+) -> Result<impl Responder> {
+    test_notification_channel_handler(user, id, service).await
+}
+
+// Configure all routes function
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/account")
+            .route("", web::delete().to(delete_account))
+            .route("/export", web::get().to(start_export))
+            .route("/export/{id}", web::get().to(get_export))
+            .route("/export/{id}/download", web::get().to(download_export))
+            .route(
+                "/notifications",
+                web::get().to(get_notification_preferences),
+            )
+            .route(
+                "/notifications",
+                web::put().to(update_notification_preferences),
+            )
+            .route(
+                "/notifications/channels",
+                web::get().to(list_notification_channels),
+            )
+            .route(
+                "/notifications/channels",
+                web::post().to(create_notification_channel),
+            )
+            .route(
+                "/notifications/channels/{id}",
+                web::delete().to(delete_notification_channel),
+            )
+            .route(
+                "/notifications/channels/{id}/test",
+                web::post().to(test_notification_channel),
+            ),
+    );
+}
@@ -0,0 +1,261 @@
+use actix_web::{web, Responder};
+
+use crate::{
+    config::Config,
+    errors::extract,
+    extractors::{CurrentTenant, LinkId, ValidatedQuery},
+    handlers::{
+        admin_stats_handler, block_ip_handler, create_tenant_handler, create_user_handler,
+        dashboard_asset_handler, dashboard_handler, get_app_links_handler, get_branding_handler,
+        get_config_handler, get_maintenance_handler, list_blocked_ips_handler, list_flags_handler,
+        list_jobs_handler, list_tenants_handler, reload_config_handler, resolve_flag_handler,
+        restore_snapshot_handler, run_job_handler, system_info_handler, take_snapshot_handler,
+        trigger_backup_handler, unblock_ip_handler, update_app_links_handler,
+        update_branding_handler, update_link_status_handler, update_maintenance_handler,
+        AppLinksServiceType, BackupServiceType, BrandingServiceType, JobSchedulerType,
+        LinkFlagServiceType, ScannerGuardServiceType, ShortenedUrlServiceType, SnapshotServiceType,
+        TenantServiceType, UserServiceType,
+    },
+    live_config::LiveConfig,
+    middleware::AdminAuth,
+    models::{
+        BlockIpDto, CreateTenantDto, CreateUserDto, DryRunParams, LinkFlagQueryParams, Snapshot,
+        UpdateAppLinksDto, UpdateBrandingDto, UpdateLinkStatusDto, UpdateMaintenanceDto,
+    },
+    types::{AppState, Result},
+    utils::maintenance::MaintenanceMode,
+};
+
+// Snapshots carry every link, far more than the default JSON limit
+const MAX_SNAPSHOT_BYTES: usize = 256 * 1024 * 1024;
+
+// Trigger backup route handler
+async fn trigger_backup(service: web::Data<BackupServiceType>) -> Result<impl Responder> {
+    trigger_backup_handler(service).await
+}
+
+// Take snapshot route handler
+async fn take_snapshot(service: web::Data<SnapshotServiceType>) -> Result<impl Responder> {
+    take_snapshot_handler(service).await
+}
+
+// Restore snapshot route handler
+async fn restore_snapshot(
+    query: ValidatedQuery<DryRunParams>,
+    snapshot: web::Json<Snapshot>,
+    service: web::Data<SnapshotServiceType>,
+    tenants: web::Data<TenantServiceType>,
+    scanner_guard: web::Data<ScannerGuardServiceType>,
+) -> Result<impl Responder> {
+    restore_snapshot_handler(query, snapshot, service, tenants, scanner_guard).await
+}
+
+// Create user route handler
+async fn create_user(
+    dto: web::Json<CreateUserDto>,
+    service: web::Data<UserServiceType>,
+) -> Result<impl Responder> {
+    create_user_handler(dto, service).await
+}
+
+// List flagged links route handler
+async fn list_flags(
+    query: ValidatedQuery<LinkFlagQueryParams>,
+    service: web::Data<LinkFlagServiceType>,
+) -> Result<impl Responder> {
+    list_flags_handler(query, service).await
+}
+
+// Resolve flag route handler
+async fn resolve_flag(
+    id: web::Path<i64>,
+    service: web::Data<LinkFlagServiceType>,
+) -> Result<impl Responder> {
+    resolve_flag_handler(id, service).await
+}
+
+// Update link status route handler
+async fn update_link_status(
+    id: LinkId,
+    dto: web::Json<UpdateLinkStatusDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    update_link_status_handler(id, dto, service).await
+}
+
+// Get maintenance mode route handler
+async fn get_maintenance(maintenance: web::Data<MaintenanceMode>) -> Result<impl Responder> {
+    get_maintenance_handler(maintenance).await
+}
+
+// Switch maintenance mode route handler
+async fn update_maintenance(
+    dto: web::Json<UpdateMaintenanceDto>,
+    maintenance: web::Data<MaintenanceMode>,
+) -> Result<impl Responder> {
+    update_maintenance_handler(dto, maintenance).await
+}
+
+// List blocked IPs route handler
+async fn list_blocked_ips(service: web::Data<ScannerGuardServiceType>) -> Result<impl Responder> {
+    list_blocked_ips_handler(service).await
+}
+
+// Block IP route handler
+async fn block_ip(
+    dto: web::Json<BlockIpDto>,
+    service: web::Data<ScannerGuardServiceType>,
+) -> Result<impl Responder> {
+    block_ip_handler(dto, service).await
+}
+
+// Unblock IP route handler
+async fn unblock_ip(
+    ip: web::Path<String>,
+    service: web::Data<ScannerGuardServiceType>,
+) -> Result<impl Responder> {
+    unblock_ip_handler(ip, service).await
+}
+
+// List tenants route handler
+async fn list_tenants(service: web::Data<TenantServiceType>) -> Result<impl Responder> {
+    list_tenants_handler(service).await
+}
+
+// Create tenant route handler
+async fn create_tenant(
+    dto: web::Json<CreateTenantDto>,
+    service: web::Data<TenantServiceType>,
+) -> Result<impl Responder> {
+    create_tenant_handler(dto, service).await
+}
+
+// Get branding route handler
+async fn get_branding(
+    tenant: CurrentTenant,
+    service: web::Data<BrandingServiceType>,
+) -> Result<impl Responder> {
+    get_branding_handler(tenant, service).await
+}
+
+// Admin stats route handler
+async fn admin_stats(
+    service: web::Data<ShortenedUrlServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    admin_stats_handler(service, config).await
+}
+
+// System info route handler
+async fn system_info(
+    state: web::Data<AppState>,
+    live_config: web::Data<LiveConfig>,
+) -> Result<impl Responder> {
+    system_info_handler(state, live_config).await
+}
+
+// Get effective config route handler
+async fn get_config(live_config: web::Data<LiveConfig>) -> Result<impl Responder> {
+    get_config_handler(live_config).await
+}
+
+// Reload config file route handler
+async fn reload_config(live_config: web::Data<LiveConfig>) -> Result<impl Responder> {
+    reload_config_handler(live_config).await
+}
+
+// Update branding route handler
+async fn update_branding(
+    tenant: CurrentTenant,
+    dto: web::Json<UpdateBrandingDto>,
+    service: web::Data<BrandingServiceType>,
+) -> Result<impl Responder> {
+    update_branding_handler(tenant, dto, service).await
+}
+
+// Get app links route handler
+async fn get_app_links(
+    tenant: CurrentTenant,
+    service: web::Data<AppLinksServiceType>,
+) -> Result<impl Responder> {
+    get_app_links_handler(tenant, service).await
+}
+
+// Update app links route handler
+async fn update_app_links(
+    tenant: CurrentTenant,
+    dto: web::Json<UpdateAppLinksDto>,
+    service: web::Data<AppLinksServiceType>,
+) -> Result<impl Responder> {
+    update_app_links_handler(tenant, dto, service).await
+}
+
+// List jobs route handler
+async fn list_jobs(scheduler: web::Data<JobSchedulerType>) -> Result<impl Responder> {
+    list_jobs_handler(scheduler).await
+}
+
+// Run job route handler
+async fn run_job(
+    name: web::Path<String>,
+    scheduler: web::Data<JobSchedulerType>,
+) -> Result<impl Responder> {
+    run_job_handler(name, scheduler).await
+}
+
+// Admin dashboard page route handler
+async fn dashboard(config: web::Data<Config>) -> Result<impl Responder> {
+    dashboard_handler(config).await
+}
+
+// Admin dashboard asset route handler
+async fn dashboard_asset(
+    file: web::Path<String>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    dashboard_asset_handler(file, config).await
+}
+
+/// Dashboard routes, registered before the short code routes that would otherwise take them
+pub fn configure_dashboard_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route("/admin", web::get().to(dashboard))
+        .route("/admin/", web::get().to(dashboard))
+        .route("/admin/{file}", web::get().to(dashboard_asset));
+}
+
+// Configure all routes function
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/admin")
+            .wrap(AdminAuth)
+            .route("/backups", web::post().to(trigger_backup))
+            .route("/snapshot", web::post().to(take_snapshot))
+            .service(
+                web::resource("/restore")
+                    .app_data(extract::json_config().limit(MAX_SNAPSHOT_BYTES))
+                    .route(web::post().to(restore_snapshot)),
+            )
+            .route("/users", web::post().to(create_user))
+            .route("/flags", web::get().to(list_flags))
+            .route("/flags/{id}/resolve", web::post().to(resolve_flag))
+            .route("/urls/{id}/status", web::put().to(update_link_status))
+            .route("/stats", web::get().to(admin_stats))
+            .route("/system", web::get().to(system_info))
+            .route("/config", web::get().to(get_config))
+            .route("/config/reload", web::post().to(reload_config))
+            .route("/maintenance", web::get().to(get_maintenance))
+            .route("/maintenance", web::put().to(update_maintenance))
+            .route("/blocked-ips", web::get().to(list_blocked_ips))
+            .route("/blocked-ips", web::post().to(block_ip))
+            .route("/blocked-ips/{ip}", web::delete().to(unblock_ip))
+            .route("/tenants", web::get().to(list_tenants))
+            .route("/tenants", web::post().to(create_tenant))
+            .route("/branding", web::get().to(get_branding))
+            .route("/branding", web::put().to(update_branding))
+            .route("/app-links", web::get().to(get_app_links))
+            .route("/app-links", web::put().to(update_app_links))
+            .route("/jobs", web::get().to(list_jobs))
+            .route("/jobs/{name}/run", web::post().to(run_job)),
+        // add more routes here
+    );
+}
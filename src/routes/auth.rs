@@ -0,0 +1,39 @@
+use actix_web::{web, HttpRequest, Responder};
+
+use crate::{
+    config::Config,
+    extractors::OptionalUser,
+    handlers::{oauth_callback_handler, oauth_login_handler, OAuthServiceType},
+    models::{OAuthCallbackParams, OAuthProvider},
+    types::Result,
+};
+
+// Start social login route handler
+async fn oauth_login(
+    user: OptionalUser,
+    provider: web::Path<OAuthProvider>,
+    service: web::Data<OAuthServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    oauth_login_handler(user, provider, service, config).await
+}
+
+// Social login callback route handler
+async fn oauth_callback(
+    req: HttpRequest,
+    provider: web::Path<OAuthProvider>,
+    query: web::Query<OAuthCallbackParams>,
+    service: web::Data<OAuthServiceType>,
+) -> Result<impl Responder> {
+    oauth_callback_handler(req, provider, query, service).await
+}
+
+// Configure all routes function
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/auth")
+            .route("/{provider}/login", web::get().to(oauth_login))
+            .route("/{provider}/callback", web::get().to(oauth_callback)),
+        // add more routes here
+    );
+}
@@ -1,22 +1,32 @@
+mod account;
+mod admin;
+mod auth;
+mod service_token;
 mod shortened_url;
+mod stats;
 
-use actix_web::{web, HttpResponse, Responder};
+use actix_web::{web, HttpRequest, HttpResponse, Responder};
 
 use crate::{
+    config::Config,
     db::{DBHealthStatus, DatabaseHealth},
-    handlers::{redirect_handler, ShortenedUrlServiceType},
-    types::{AppState, HealthStatus, ResponsePayload, Result},
+    extractors::CurrentTenant,
+    handlers::{
+        apple_app_site_association_handler, asset_links_handler, home_handler,
+        nested_redirect_handler, redirect_handler, AnalyticsServiceType, AppLinksServiceType,
+        BrandingServiceType, ShortenedUrlServiceType,
+    },
+    types::{AppState, HealthStatus, Result},
 };
 
 // Handler function for the root route "/"
-async fn index_url() -> impl Responder {
-    let welcome_message = ResponsePayload {
-        status: 200,
-        message: String::from("Welcome and have a great time!"),
-    };
-
-    // Return the struct as JSON
-    HttpResponse::Ok().json(welcome_message)
+async fn index_url(
+    req: HttpRequest,
+    tenant: CurrentTenant,
+    branding: web::Data<BrandingServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    home_handler(req, tenant, branding, config).await
 }
 
 // Handler function for the health check endpoint
@@ -35,9 +45,30 @@ async fn health_check_url(data: web::Data<AppState>) -> impl Responder {
         },
     };
 
+    // While the breaker is open, the API refuses database work and redirects rely on the cache.
+    // In maintenance the API is refused on purpose, redirects keep serving
+    let db_circuit_breaker = data.db_breaker.status();
+    let status = if data.maintenance.is_enabled() {
+        "MAINTENANCE"
+    } else if data.db_breaker.is_open() {
+        "DEGRADED"
+    } else {
+        "OK"
+    };
+
     let status = HealthStatus {
-        status: String::from("OK"),
+        status: String::from(status),
         db_health: Some(db_health),
+        db_circuit_breaker,
+        link_cache: data.link_cache.stats(),
+        http_client: data.http_client.stats(),
+        short_codes: data.shortened_urls.code_stats(),
+        job_locks: data.job_scheduler.lock_stats(),
+        events: data.event_bus.stats(),
+        visit_export: data
+            .visit_exporter
+            .as_ref()
+            .map(|exporter| exporter.stats()),
         version: data.version.clone(),
         uptime_seconds: uptime,
     };
@@ -46,12 +77,46 @@ async fn health_check_url(data: web::Data<AppState>) -> impl Responder {
     HttpResponse::Ok().json(status)
 }
 
+// Apple App Site Association route handler
+async fn apple_app_site_association(
+    tenant: CurrentTenant,
+    service: web::Data<AppLinksServiceType>,
+) -> Result<impl Responder> {
+    apple_app_site_association_handler(tenant, service).await
+}
+
+// Digital Asset Links route handler
+async fn asset_links(
+    tenant: CurrentTenant,
+    service: web::Data<AppLinksServiceType>,
+) -> Result<impl Responder> {
+    asset_links_handler(tenant, service).await
+}
+
 // Redirect to original URL route handler
 async fn redirect_url(
+    req: HttpRequest,
+    tenant: CurrentTenant,
     path: web::Path<String>,
     service: web::Data<ShortenedUrlServiceType>,
+    analytics: web::Data<AnalyticsServiceType>,
+    branding: web::Data<BrandingServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    redirect_handler(req, tenant, path, service, analytics, branding, config).await
+}
+
+// Redirect to original URL of a namespaced code or forwarded path route handler
+async fn redirect_nested_url(
+    req: HttpRequest,
+    tenant: CurrentTenant,
+    path: web::Path<(String, String)>,
+    service: web::Data<ShortenedUrlServiceType>,
+    analytics: web::Data<AnalyticsServiceType>,
+    branding: web::Data<BrandingServiceType>,
+    config: web::Data<Config>,
 ) -> Result<impl Responder> {
-    redirect_handler(path, service).await
+    nested_redirect_handler(req, tenant, path, service, analytics, branding, config).await
 }
 
 // Configure all routes function
@@ -59,6 +124,19 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     // Register routes from individual modules
     cfg.route("/", web::get().to(index_url))
         .route("/health", web::get().to(health_check_url))
+        .configure(admin::configure_dashboard_routes)
+        .route(
+            "/.well-known/apple-app-site-association",
+            web::get().to(apple_app_site_association),
+        )
+        .route("/.well-known/assetlinks.json", web::get().to(asset_links))
         .route("/{code}", web::get().to(redirect_url))
-        .configure(shortened_url::configure_routes);
+        .configure(shortened_url::configure_routes)
+        .configure(account::configure_routes)
+        .configure(admin::configure_routes)
+        .configure(auth::configure_routes)
+        .configure(service_token::configure_routes)
+        .configure(stats::configure_routes)
+        // Registered last so the API paths above take precedence
+        .route("/{code}/{tail:.*}", web::get().to(redirect_nested_url));
 }
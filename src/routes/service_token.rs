@@ -0,0 +1,47 @@
+use actix_web::{web, Responder};
+use uuid::Uuid;
+
+use crate::{
+    handlers::{
+        create_service_token_handler, list_service_tokens_handler, revoke_service_token_handler,
+        ServiceTokenServiceType,
+    },
+    middleware::AdminAuth,
+    models::CreateServiceTokenDto,
+    types::Result,
+};
+
+// List service tokens route handler
+async fn list_service_tokens(
+    service: web::Data<ServiceTokenServiceType>,
+) -> Result<impl Responder> {
+    list_service_tokens_handler(service).await
+}
+
+// Create service token route handler
+async fn create_service_token(
+    dto: web::Json<CreateServiceTokenDto>,
+    service: web::Data<ServiceTokenServiceType>,
+) -> Result<impl Responder> {
+    create_service_token_handler(dto, service).await
+}
+
+// Revoke service token route handler
+async fn revoke_service_token(
+    id: web::Path<Uuid>,
+    service: web::Data<ServiceTokenServiceType>,
+) -> Result<impl Responder> {
+    revoke_service_token_handler(id, service).await
+}
+
+// Configure all routes function
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/tokens")
+            .wrap(AdminAuth)
+            .route("", web::get().to(list_service_tokens))
+            .route("", web::post().to(create_service_token))
+            .route("/{id}", web::delete().to(revoke_service_token)),
+        // add more routes here
+    );
+}
@@ -1,63 +1,351 @@
-use actix_web::{web, Responder};
+use actix_web::{web, HttpRequest, Responder};
 use uuid::Uuid;
 
 use crate::{
+    config::Config,
+    extractors::{AuthenticatedUser, CurrentTenant, LinkId, OptionalUser, ValidatedQuery},
     handlers::{
-        create_handler, delete_handler, get_all_handler, get_by_id_handler, get_by_query_handler,
-        update_handler, ShortenedUrlServiceType,
+        accept_transfer_handler, archive_handler, bulk_delete_handler, bulk_update_handler,
+        claim_handler, close_transfer_handler, count_handler, create_handler,
+        create_saved_search_handler, delete_handler, delete_saved_search_handler,
+        duplicate_handler, expiring_handler, export_handler, favorite_handler, get_all_handler,
+        get_by_id_handler, get_by_query_handler, get_saved_search_handler, history_handler,
+        list_saved_searches_handler, pending_transfers_handler, resolve_codes_handler,
+        restore_handler, revert_handler, transfer_handler, transfers_handler, trash_handler,
+        unarchive_handler, unfavorite_handler, update_handler, update_saved_search_handler,
+        SavedSearchServiceType, ShortenedUrlServiceType, TenantServiceType,
     },
-    models::{CreateShortenedUrlDto, ShortenedUrlQueryParams, ShortenedUrlUpdateParams},
+    models::{
+        BulkDeleteUrlsDto, BulkUpdateUrlsDto, ClaimLinkDto, CreateShortenedUrlDto, DryRunParams,
+        DuplicateShortenedUrlDto, ExpiringQueryParams, ResolveCodesDto, SaveSearchDto,
+        ShortenedUrlQueryParams, ShortenedUrlUpdateParams, TransferLinkDto,
+    },
+    services::CaptchaService,
     types::Result,
+    utils::rate_limiter::RateLimiter,
 };
 
 // Create shortened URL route handler
 async fn create_url(
+    req: HttpRequest,
+    user: OptionalUser,
+    tenant: CurrentTenant,
     dto: web::Json<CreateShortenedUrlDto>,
     service: web::Data<ShortenedUrlServiceType>,
+    limiter: web::Data<RateLimiter>,
+    captcha: web::Data<CaptchaService>,
 ) -> Result<impl Responder> {
-    create_handler(dto, service).await
+    create_handler(req, user, tenant, dto, service, limiter, captcha).await
 }
 
 // Get all URLs route handler
 async fn get_all_url(
-    query: web::Query<ShortenedUrlQueryParams>,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
     service: web::Data<ShortenedUrlServiceType>,
+    presets: web::Data<SavedSearchServiceType>,
 ) -> Result<impl Responder> {
-    get_all_handler(query, service).await
+    get_all_handler(user, tenant, query, service, presets).await
 }
 
 // Get URLs by query route handler
 async fn get_all_url_by_query(
-    query: web::Query<ShortenedUrlQueryParams>,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
     service: web::Data<ShortenedUrlServiceType>,
+    presets: web::Data<SavedSearchServiceType>,
 ) -> Result<impl Responder> {
     log::info!("query 0: {:?}", query);
-    get_by_query_handler(query, service).await
+    get_by_query_handler(user, tenant, query, service, presets).await
+}
+
+// Count URLs route handler
+async fn count_urls(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
+    service: web::Data<ShortenedUrlServiceType>,
+    presets: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    count_handler(user, tenant, query, service, presets).await
+}
+
+// Export URLs as newline-delimited JSON route handler
+async fn export_urls(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
+    service: web::Data<ShortenedUrlServiceType>,
+    presets: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    export_handler(user, tenant, query, service, presets).await
+}
+
+// Batch resolve short codes route handler
+async fn resolve_codes(
+    tenant: CurrentTenant,
+    dto: web::Json<ResolveCodesDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    resolve_codes_handler(tenant, dto, service).await
+}
+
+// List URLs expiring soon route handler
+async fn get_expiring(
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ExpiringQueryParams>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    expiring_handler(tenant, query, service).await
+}
+
+// List deleted URLs route handler
+async fn get_trash(
+    tenant: CurrentTenant,
+    query: ValidatedQuery<ShortenedUrlQueryParams>,
+    service: web::Data<ShortenedUrlServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    trash_handler(tenant, query, service, config).await
+}
+
+// List saved searches route handler
+async fn list_saved_searches(
+    tenant: CurrentTenant,
+    service: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    list_saved_searches_handler(tenant, service).await
+}
+
+// Create saved search route handler
+async fn create_saved_search(
+    tenant: CurrentTenant,
+    dto: web::Json<SaveSearchDto>,
+    service: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    create_saved_search_handler(tenant, dto, service).await
+}
+
+// Get saved search by ID route handler
+async fn get_saved_search(
+    tenant: CurrentTenant,
+    id: web::Path<Uuid>,
+    service: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    get_saved_search_handler(tenant, id, service).await
+}
+
+// Replace saved search route handler
+async fn update_saved_search(
+    tenant: CurrentTenant,
+    id: web::Path<Uuid>,
+    dto: web::Json<SaveSearchDto>,
+    service: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    update_saved_search_handler(tenant, id, dto, service).await
+}
+
+// Delete saved search route handler
+async fn delete_saved_search(
+    tenant: CurrentTenant,
+    id: web::Path<Uuid>,
+    service: web::Data<SavedSearchServiceType>,
+) -> Result<impl Responder> {
+    delete_saved_search_handler(tenant, id, service).await
 }
 
 // Get URL by ID route handler
 async fn get_url_by_id(
-    id: web::Path<Uuid>,
+    tenant: CurrentTenant,
+    id: LinkId,
     service: web::Data<ShortenedUrlServiceType>,
 ) -> Result<impl Responder> {
-    get_by_id_handler(id, service).await
+    get_by_id_handler(tenant, id, service).await
 }
 
 // Update URL by ID route handler
 async fn update_url(
-    id: web::Path<Uuid>,
+    req: HttpRequest,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    query: ValidatedQuery<DryRunParams>,
     param: web::Json<ShortenedUrlUpdateParams>,
     service: web::Data<ShortenedUrlServiceType>,
 ) -> Result<impl Responder> {
-    update_handler(id, param, service).await
+    update_handler(req, user, tenant, id, query, param, service).await
+}
+
+// Get URL destination history route handler
+async fn get_url_history(
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    history_handler(tenant, id, service).await
+}
+
+// Revert URL destination to a revision route handler
+async fn revert_url(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    path: web::Path<(Uuid, i32)>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    revert_handler(user, tenant, id, path, service).await
+}
+
+// Duplicate URL route handler
+async fn duplicate_url(
+    req: HttpRequest,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    dto: web::Json<DuplicateShortenedUrlDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+    limiter: web::Data<RateLimiter>,
+) -> Result<impl Responder> {
+    duplicate_handler(req, user, tenant, id, dto, service, limiter).await
 }
 
 // Delete URL by ID route handler
 async fn delete_url(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    delete_handler(user, tenant, id, service).await
+}
+
+// Restore URL from the trash route handler
+async fn restore_url(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    restore_handler(user, tenant, id, service).await
+}
+
+// Archive URL route handler
+async fn archive_url(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    archive_handler(user, tenant, id, service).await
+}
+
+// Unarchive URL route handler
+async fn unarchive_url(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    unarchive_handler(user, tenant, id, service).await
+}
+
+// Pin URL as a favorite route handler
+async fn favorite_url(
+    user: AuthenticatedUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    favorite_handler(user, tenant, id, service).await
+}
+
+// Unpin URL from the favorites route handler
+async fn unfavorite_url(
+    user: AuthenticatedUser,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    unfavorite_handler(user, id, service).await
+}
+
+// Claim URL created without signing in route handler
+async fn claim_url(
+    user: AuthenticatedUser,
+    tenant: CurrentTenant,
+    dto: web::Json<ClaimLinkDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    claim_handler(user, tenant, dto, service).await
+}
+
+// Transfer URL to another user or tenant route handler
+async fn transfer_url(
+    req: HttpRequest,
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    id: LinkId,
+    dto: web::Json<TransferLinkDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+    tenants: web::Data<TenantServiceType>,
+) -> Result<impl Responder> {
+    transfer_handler(req, user, tenant, id, dto, service, tenants).await
+}
+
+// List transfers offered to the user route handler
+async fn list_pending_transfers(
+    user: AuthenticatedUser,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    pending_transfers_handler(user, service).await
+}
+
+// Get URL transfer history route handler
+async fn get_url_transfers(
+    tenant: CurrentTenant,
+    id: LinkId,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    transfers_handler(tenant, id, service).await
+}
+
+// Accept transfer route handler
+async fn accept_transfer(
+    user: AuthenticatedUser,
     id: web::Path<Uuid>,
     service: web::Data<ShortenedUrlServiceType>,
 ) -> Result<impl Responder> {
-    delete_handler(id, service).await
+    accept_transfer_handler(user, id, service).await
+}
+
+// Decline or withdraw transfer route handler
+async fn close_transfer(
+    user: AuthenticatedUser,
+    id: web::Path<Uuid>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    close_transfer_handler(user, id, service).await
+}
+
+// Bulk update URLs route handler
+async fn bulk_update_urls(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    dto: web::Json<BulkUpdateUrlsDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    bulk_update_handler(user, tenant, dto, service).await
+}
+
+// Bulk delete URLs route handler
+async fn bulk_delete_urls(
+    user: OptionalUser,
+    tenant: CurrentTenant,
+    dto: web::Json<BulkDeleteUrlsDto>,
+    service: web::Data<ShortenedUrlServiceType>,
+) -> Result<impl Responder> {
+    bulk_delete_handler(user, tenant, dto, service).await
 }
 
 // Configure all routes function
@@ -66,10 +354,36 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
         web::scope("/api/urls")
             .route("", web::post().to(create_url))
             .route("", web::get().to(get_all_url))
-            .route("", web::patch().to(update_url))
-            .route("", web::delete().to(delete_url))
             .route("/search", web::get().to(get_all_url_by_query))
-            .route("/{id}", web::get().to(get_url_by_id)),
+            .route("/count", web::get().to(count_urls))
+            .route("/export", web::get().to(export_urls))
+            .route("/resolve", web::post().to(resolve_codes))
+            .route("/claim", web::post().to(claim_url))
+            .route("/transfers", web::get().to(list_pending_transfers))
+            .route("/transfers/{id}/accept", web::post().to(accept_transfer))
+            .route("/transfers/{id}", web::delete().to(close_transfer))
+            .route("/expiring", web::get().to(get_expiring))
+            .route("/trash", web::get().to(get_trash))
+            .route("/bulk", web::patch().to(bulk_update_urls))
+            .route("/bulk", web::delete().to(bulk_delete_urls))
+            .route("/presets", web::get().to(list_saved_searches))
+            .route("/presets", web::post().to(create_saved_search))
+            .route("/presets/{id}", web::get().to(get_saved_search))
+            .route("/presets/{id}", web::put().to(update_saved_search))
+            .route("/presets/{id}", web::delete().to(delete_saved_search))
+            .route("/{id}", web::get().to(get_url_by_id))
+            .route("/{id}", web::patch().to(update_url))
+            .route("/{id}", web::delete().to(delete_url))
+            .route("/{id}/history", web::get().to(get_url_history))
+            .route("/{id}/revert/{revision}", web::post().to(revert_url))
+            .route("/{id}/duplicate", web::post().to(duplicate_url))
+            .route("/{id}/restore", web::post().to(restore_url))
+            .route("/{id}/archive", web::post().to(archive_url))
+            .route("/{id}/unarchive", web::post().to(unarchive_url))
+            .route("/{id}/favorite", web::post().to(favorite_url))
+            .route("/{id}/favorite", web::delete().to(unfavorite_url))
+            .route("/{id}/transfer", web::post().to(transfer_url))
+            .route("/{id}/transfers", web::get().to(get_url_transfers)),
         // add more routes here
     );
 }
@@ -0,0 +1,39 @@
+use actix_web::{web, Responder};
+
+use crate::{
+    config::Config,
+    extractors::{CurrentTenant, ValidatedQuery},
+    handlers::{group_by_handler, top_links_handler, StatsServiceType},
+    models::{GroupByQueryParams, TopLinksQueryParams},
+    types::Result,
+};
+
+// Grouped link stats route handler
+async fn group_by(
+    tenant: CurrentTenant,
+    query: ValidatedQuery<GroupByQueryParams>,
+    service: web::Data<StatsServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    group_by_handler(tenant, query, service, config).await
+}
+
+// Top links leaderboard route handler
+async fn top_links(
+    tenant: CurrentTenant,
+    query: ValidatedQuery<TopLinksQueryParams>,
+    service: web::Data<StatsServiceType>,
+    config: web::Data<Config>,
+) -> Result<impl Responder> {
+    top_links_handler(tenant, query, service, config).await
+}
+
+// Configure all routes function
+pub fn configure_routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope("/api/stats")
+            .route("/group-by", web::get().to(group_by))
+            .route("/top", web::get().to(top_links)),
+        // add more routes here
+    );
+}
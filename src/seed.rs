@@ -0,0 +1,157 @@
+// src/seed.rs - Sample data loaded at startup for local development
+use std::collections::HashMap;
+use std::fs;
+use std::sync::Arc;
+
+use log::{debug, info, warn};
+use serde::Deserialize;
+use validator::Validate;
+
+use crate::{
+    cache::{AccessBacklog, LinkCache},
+    config::{Config, Environment},
+    db::Database,
+    errors::AppError,
+    events::EventBus,
+    models::{CreateShortenedUrlDto, CreateUserDto},
+    repositories::{ShortenedUrlRepository, UserRepository, UserRepositoryTrait},
+    services::{ShortenedUrlService, ShortenedUrlServiceTrait},
+    types::Result,
+    utils::hash::sha256_hex,
+};
+
+/// Users and links to create, as read from the seed file
+#[derive(Debug, Default, Deserialize)]
+pub struct Fixture {
+    #[serde(default)]
+    pub users: Vec<SeedUser>,
+
+    #[serde(default)]
+    pub links: Vec<SeedLink>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SeedUser {
+    #[serde(flatten)]
+    pub user: CreateUserDto,
+
+    /// A fixed API key secret, so local setups can hardcode it
+    pub api_key: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SeedLink {
+    #[serde(flatten)]
+    pub link: CreateShortenedUrlDto,
+
+    /// Email of the seeded user owning the link
+    pub owner: Option<String>,
+}
+
+/// Load the seed file into the database, skipping users and links that already exist
+///
+/// Links are matched on their alias, so every seeded link needs a `custom_alias` for
+/// restarts to leave the data as it was. Seeding is refused in production.
+pub async fn run(db: &Database, config: &Config) -> Result<()> {
+    if config.app.environment == Environment::Production {
+        warn!("SEED_DATA is ignored in production");
+        return Ok(());
+    }
+
+    let fixture = load(&config.seed.file)?;
+    let users = UserRepository::new(db.clone());
+    let links = ShortenedUrlService::new(
+        Arc::new(ShortenedUrlRepository::new(db.clone())),
+        config.short_codes.clone(),
+        config.pagination.clone(),
+        config.milestones.clone(),
+        Arc::new(LinkCache::disabled()),
+        Arc::new(AccessBacklog::new(0)),
+        Arc::new(EventBus::disabled()),
+    );
+
+    let mut owners = HashMap::new();
+    let mut created_users = 0;
+    for seed in fixture.users {
+        let user = match users.find_by_email(&seed.user.email).await? {
+            Some(user) => user,
+            None => {
+                seed.user.validate()?;
+                let user = users.create(&seed.user).await?;
+                if let Some(secret) = &seed.api_key {
+                    users
+                        .create_api_key(&user.id, "seed", &sha256_hex(secret))
+                        .await?;
+                }
+                created_users += 1;
+                user
+            }
+        };
+        owners.insert(user.email, user.id);
+    }
+
+    let mut created_links = 0;
+    for seed in fixture.links {
+        let Some(alias) = seed.link.custom_alias.clone() else {
+            warn!(
+                "Skipping seed link to '{}' without a custom_alias",
+                seed.link.original_url
+            );
+            continue;
+        };
+
+        let namespace = seed.link.namespace.as_deref();
+        if links.get_by_code(None, namespace, &alias).await.is_ok() {
+            debug!("Seed link '{}' already exists", alias);
+            continue;
+        }
+
+        let owner = match &seed.owner {
+            Some(email) => Some(*owners.get(email).ok_or_else(|| {
+                AppError::Config(format!(
+                    "Seed link '{}' is owned by '{}', who is not a seed user",
+                    alias, email
+                ))
+            })?),
+            None => None,
+        };
+        links.create(seed.link, owner, None).await?;
+        created_links += 1;
+    }
+
+    info!(
+        "Seeded {} users and {} links from {}",
+        created_users, created_links, config.seed.file
+    );
+    Ok(())
+}
+
+fn load(path: &str) -> Result<Fixture> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| AppError::Config(format!("Could not read seed file {}: {}", path, e)))?;
+    serde_json::from_str(&content)
+        .map_err(|e| AppError::Config(format!("Invalid seed file {}: {}", path, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_fixture_is_valid() {
+        let fixture: Fixture = serde_json::from_str(include_str!("../seeds/dev.json")).unwrap();
+        assert!(!fixture.users.is_empty());
+        assert!(!fixture.links.is_empty());
+
+        for seed in &fixture.users {
+            assert!(seed.user.validate().is_ok(), "{:?}", seed.user);
+        }
+        for seed in &fixture.links {
+            assert!(seed.link.validate().is_ok(), "{:?}", seed.link);
+            assert!(seed.link.custom_alias.is_some());
+            if let Some(owner) = &seed.owner {
+                assert!(fixture.users.iter().any(|u| &u.user.email == owner));
+            }
+        }
+    }
+}
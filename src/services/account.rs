@@ -0,0 +1,321 @@
+// src/services/account.rs - Account data export and erasure (GDPR)
+use std::io::Write;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use flate2::{write::GzEncoder, Compression};
+use log::{error, info};
+use uuid::Uuid;
+
+use crate::{
+    errors::AppError,
+    models::{
+        AccountArchive, AccountDeletionReport, AccountExport, DeletionMode, ExportStatus,
+        ShortenedUrlResponseDto, User,
+    },
+    repositories::AccountRepositoryTrait,
+    types::Result,
+};
+
+#[async_trait]
+pub trait AccountServiceTrait {
+    /// Queue an export of everything stored about the user; it is built in the background
+    async fn start_export(&self, user: &User) -> Result<AccountExport>;
+    async fn get_export(&self, user: &User, id: &Uuid) -> Result<AccountExport>;
+    async fn download_export(&self, user: &User, id: &Uuid) -> Result<Vec<u8>>;
+    async fn delete_account(
+        &self,
+        user: &User,
+        mode: DeletionMode,
+    ) -> Result<AccountDeletionReport>;
+}
+
+pub struct AccountService<T: AccountRepositoryTrait> {
+    repository: Arc<T>,
+}
+
+impl<T: AccountRepositoryTrait> AccountService<T> {
+    pub fn new(repository: Arc<T>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl<T: AccountRepositoryTrait + Send + Sync + 'static> AccountServiceTrait for AccountService<T> {
+    async fn start_export(&self, user: &User) -> Result<AccountExport> {
+        let export = self.repository.create_export(&user.id).await?;
+
+        // Exports may be large, so build them outside the request
+        let repository = self.repository.clone();
+        let user = user.clone();
+        let export_id = export.id;
+        tokio::spawn(async move {
+            if let Err(e) = repository
+                .update_export(&export_id, ExportStatus::Running, None, None)
+                .await
+            {
+                error!("Failed to start account export {}: {}", export_id, e);
+                return;
+            }
+
+            let result = match build_archive(repository.as_ref(), user).await {
+                Ok(archive) => {
+                    repository
+                        .update_export(&export_id, ExportStatus::Completed, Some(archive), None)
+                        .await
+                }
+                Err(e) => {
+                    error!("Account export {} failed: {}", export_id, e);
+                    repository
+                        .update_export(&export_id, ExportStatus::Failed, None, Some(e.to_string()))
+                        .await
+                }
+            };
+
+            if let Err(e) = result {
+                error!(
+                    "Failed to record outcome of account export {}: {}",
+                    export_id, e
+                );
+            }
+        });
+
+        Ok(export)
+    }
+
+    async fn get_export(&self, user: &User, id: &Uuid) -> Result<AccountExport> {
+        match self.repository.find_export(&user.id, id).await? {
+            Some(export) => Ok(export),
+            None => Err(AppError::NotFound(format!(
+                "Export with ID '{}' not found",
+                id
+            ))),
+        }
+    }
+
+    async fn download_export(&self, user: &User, id: &Uuid) -> Result<Vec<u8>> {
+        let export = self.get_export(user, id).await?;
+        if export.status != ExportStatus::Completed {
+            return Err(AppError::Conflict(format!(
+                "Export with ID '{}' is not ready yet",
+                id
+            )));
+        }
+
+        match self.repository.find_export_archive(&user.id, id).await? {
+            Some(archive) => Ok(archive),
+            None => Err(AppError::NotFound(format!(
+                "Export with ID '{}' not found",
+                id
+            ))),
+        }
+    }
+
+    async fn delete_account(
+        &self,
+        user: &User,
+        mode: DeletionMode,
+    ) -> Result<AccountDeletionReport> {
+        let links_affected = match mode {
+            DeletionMode::Erase => self.repository.erase_account(&user.id).await?,
+            DeletionMode::Anonymize => self.repository.anonymize_account(&user.id).await?,
+        };
+
+        info!(
+            "Deleted account {} ({:?}, {} links affected)",
+            user.id, mode, links_affected
+        );
+
+        Ok(AccountDeletionReport {
+            user_id: user.id,
+            mode,
+            links_affected,
+        })
+    }
+}
+
+// Collect the user's data and encode it as gzip-compressed JSON
+async fn build_archive<T: AccountRepositoryTrait>(repository: &T, user: User) -> Result<Vec<u8>> {
+    let links = repository.find_links_by_owner(&user.id).await?;
+    let visit_aggregates = repository.find_visit_aggregates_by_owner(&user.id).await?;
+
+    let archive = AccountArchive {
+        exported_at: Utc::now(),
+        user,
+        links: links
+            .into_iter()
+            .map(ShortenedUrlResponseDto::from)
+            .collect(),
+        visit_aggregates,
+    };
+
+    let json = serde_json::to_vec_pretty(&archive)
+        .map_err(|e| AppError::Internal(format!("Failed to serialize export: {}", e)))?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&json)?;
+
+    Ok(encoder.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use flate2::read::GzDecoder;
+    use serde_json::{json, Value as JsonValue};
+
+    use super::*;
+    use crate::errors::RepositoryError;
+    use crate::models::{DailyVisitAggregate, ShortenedUrl};
+
+    type RepoResult<T> = std::result::Result<T, RepositoryError>;
+
+    // One export at a time is enough, its archive is kept next to it
+    #[derive(Default)]
+    struct FakeRepository {
+        export: Mutex<Option<(AccountExport, Option<Vec<u8>>)>>,
+        links: Vec<ShortenedUrl>,
+    }
+
+    #[async_trait]
+    impl AccountRepositoryTrait for FakeRepository {
+        async fn create_export(&self, user_id: &Uuid) -> RepoResult<AccountExport> {
+            let export = AccountExport {
+                id: Uuid::new_v4(),
+                user_id: *user_id,
+                status: ExportStatus::Pending,
+                error: None,
+                created_at: Utc::now(),
+                completed_at: None,
+            };
+            *self.export.lock().unwrap() = Some((export.clone(), None));
+            Ok(export)
+        }
+        async fn find_export(
+            &self,
+            user_id: &Uuid,
+            id: &Uuid,
+        ) -> RepoResult<Option<AccountExport>> {
+            let export = self.export.lock().unwrap();
+            Ok(export
+                .as_ref()
+                .map(|(export, _)| export.clone())
+                .filter(|export| export.user_id == *user_id && export.id == *id))
+        }
+        async fn find_export_archive(
+            &self,
+            user_id: &Uuid,
+            id: &Uuid,
+        ) -> RepoResult<Option<Vec<u8>>> {
+            let export = self.export.lock().unwrap();
+            Ok(export
+                .as_ref()
+                .filter(|(export, _)| export.user_id == *user_id && export.id == *id)
+                .and_then(|(_, archive)| archive.clone()))
+        }
+        async fn update_export(
+            &self,
+            _: &Uuid,
+            status: ExportStatus,
+            archive: Option<Vec<u8>>,
+            error: Option<String>,
+        ) -> RepoResult<()> {
+            if let Some((export, stored)) = self.export.lock().unwrap().as_mut() {
+                export.status = status;
+                export.error = error;
+                *stored = archive;
+            }
+            Ok(())
+        }
+        async fn find_links_by_owner(&self, _: &Uuid) -> RepoResult<Vec<ShortenedUrl>> {
+            Ok(self.links.clone())
+        }
+        async fn find_visit_aggregates_by_owner(
+            &self,
+            _: &Uuid,
+        ) -> RepoResult<Vec<DailyVisitAggregate>> {
+            Ok(Vec::new())
+        }
+        async fn erase_account(&self, _: &Uuid) -> RepoResult<u64> {
+            Ok(self.links.len() as u64)
+        }
+        async fn anonymize_account(&self, _: &Uuid) -> RepoResult<u64> {
+            Ok(self.links.len() as u64)
+        }
+    }
+
+    fn user() -> User {
+        User {
+            id: Uuid::new_v4(),
+            email: "ada@example.com".to_string(),
+            display_name: None,
+            settings: json!({}),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_is_downloadable_once_built() {
+        let link = ShortenedUrl {
+            short_code: "docs".to_string(),
+            ..Default::default()
+        };
+        let service = AccountService::new(Arc::new(FakeRepository {
+            links: vec![link],
+            ..Default::default()
+        }));
+        let owner = user();
+
+        let export = service.start_export(&owner).await.unwrap();
+        let stranger = user();
+        assert!(matches!(
+            service.get_export(&stranger, &export.id).await,
+            Err(AppError::NotFound(_))
+        ));
+
+        // The archive is built by a background task
+        let mut status = export.status;
+        for _ in 0..50 {
+            status = service.get_export(&owner, &export.id).await.unwrap().status;
+            if status == ExportStatus::Completed {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(status, ExportStatus::Completed);
+
+        let archive = service.download_export(&owner, &export.id).await.unwrap();
+        let mut json = String::new();
+        GzDecoder::new(&archive[..])
+            .read_to_string(&mut json)
+            .unwrap();
+        let archive: JsonValue = serde_json::from_str(&json).unwrap();
+        assert_eq!(archive["user"]["email"], "ada@example.com");
+        assert_eq!(archive["links"][0]["short_code"], "docs");
+
+        let report = service
+            .delete_account(&owner, DeletionMode::Anonymize)
+            .await
+            .unwrap();
+        assert_eq!(
+            (report.mode, report.links_affected),
+            (DeletionMode::Anonymize, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pending_export_cant_be_downloaded() {
+        let repository = Arc::new(FakeRepository::default());
+        let owner = user();
+        let export = repository.create_export(&owner.id).await.unwrap();
+
+        let service = AccountService::new(repository);
+        assert!(matches!(
+            service.download_export(&owner, &export.id).await,
+            Err(AppError::Conflict(_))
+        ));
+    }
+}
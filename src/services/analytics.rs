@@ -0,0 +1,82 @@
+// src/services/analytics.rs - Visit tracking logic
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    models::{ExportedVisit, NewUrlVisit},
+    repositories::AnalyticsRepositoryTrait,
+    types::Result,
+    warehouse::VisitExporter,
+};
+
+#[async_trait]
+pub trait AnalyticsServiceTrait {
+    async fn record_visit(&self, visit: NewUrlVisit) -> Result<()>;
+    async fn record_untracked_visit(&self, url_id: &Uuid) -> Result<()>;
+}
+
+pub struct AnalyticsService<T: AnalyticsRepositoryTrait> {
+    repository: Arc<T>,
+    exporter: Option<Arc<VisitExporter>>,
+    store_raw_visits: bool,
+}
+
+impl<T: AnalyticsRepositoryTrait> AnalyticsService<T> {
+    pub fn new(
+        repository: Arc<T>,
+        exporter: Option<Arc<VisitExporter>>,
+        store_raw_visits: bool,
+    ) -> Self {
+        Self {
+            repository,
+            exporter,
+            store_raw_visits,
+        }
+    }
+}
+
+#[async_trait]
+impl<T: AnalyticsRepositoryTrait + Send + Sync> AnalyticsServiceTrait for AnalyticsService<T> {
+    async fn record_visit(&self, visit: NewUrlVisit) -> Result<()> {
+        if !self.store_raw_visits {
+            // The warehouse keeps the details, Postgres only the daily counts
+            if let Some(exporter) = &self.exporter {
+                exporter.record(ExportedVisit {
+                    url_id: visit.url_id,
+                    visited_at: Utc::now(),
+                    ip_address: visit.ip_address,
+                    user_agent: visit.user_agent,
+                    referer: visit.referer,
+                });
+            }
+            self.repository
+                .increment_daily_visits(&visit.url_id)
+                .await?;
+            return Ok(());
+        }
+
+        let record = self.repository.record_visit(&visit).await?;
+        if let Some(exporter) = &self.exporter {
+            exporter.record(ExportedVisit::from(&record));
+        }
+        Ok(())
+    }
+
+    async fn record_untracked_visit(&self, url_id: &Uuid) -> Result<()> {
+        // Exported without any details, so warehouse totals match the daily counts
+        if let Some(exporter) = &self.exporter {
+            exporter.record(ExportedVisit {
+                url_id: *url_id,
+                visited_at: Utc::now(),
+                ip_address: None,
+                user_agent: None,
+                referer: None,
+            });
+        }
+        self.repository.increment_daily_visits(url_id).await?;
+        Ok(())
+    }
+}
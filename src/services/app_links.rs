@@ -0,0 +1,50 @@
+// src/services/app_links.rs - Per-tenant mobile apps opening short links
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    errors::AppError,
+    models::{AppLinks, UpdateAppLinksDto},
+    repositories::AppLinksRepositoryTrait,
+    types::Result,
+};
+
+#[async_trait]
+pub trait AppLinksServiceTrait {
+    /// The tenant's app links, none when it never set them up
+    async fn get(&self, tenant: Option<Uuid>) -> Result<AppLinks>;
+    async fn update(&self, tenant: Option<Uuid>, dto: UpdateAppLinksDto) -> Result<AppLinks>;
+}
+
+pub struct AppLinksService<T: AppLinksRepositoryTrait> {
+    repository: Arc<T>,
+}
+
+impl<T: AppLinksRepositoryTrait> AppLinksService<T> {
+    pub fn new(repository: Arc<T>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl<T: AppLinksRepositoryTrait + Send + Sync> AppLinksServiceTrait for AppLinksService<T> {
+    async fn get(&self, tenant: Option<Uuid>) -> Result<AppLinks> {
+        let app_links = self.repository.find(tenant.as_ref()).await?;
+        Ok(app_links.unwrap_or_default())
+    }
+
+    async fn update(&self, tenant: Option<Uuid>, dto: UpdateAppLinksDto) -> Result<AppLinks> {
+        dto.validate()?;
+        // Android verifies the package against the certificates, one is useless alone
+        if dto.android_package.is_none() != dto.android_cert_fingerprints.is_empty() {
+            return Err(AppError::Validation(
+                "android_package and android_cert_fingerprints must be set together".to_string(),
+            ));
+        }
+        let app_links = self.repository.upsert(tenant.as_ref(), &dto).await?;
+        Ok(app_links)
+    }
+}
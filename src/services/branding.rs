@@ -0,0 +1,43 @@
+// src/services/branding.rs - Per-tenant look of visitor pages
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    models::{Branding, UpdateBrandingDto},
+    repositories::BrandingRepositoryTrait,
+    types::Result,
+};
+
+#[async_trait]
+pub trait BrandingServiceTrait {
+    /// The tenant's branding, the default look when it never set one
+    async fn get(&self, tenant: Option<Uuid>) -> Result<Branding>;
+    async fn update(&self, tenant: Option<Uuid>, dto: UpdateBrandingDto) -> Result<Branding>;
+}
+
+pub struct BrandingService<T: BrandingRepositoryTrait> {
+    repository: Arc<T>,
+}
+
+impl<T: BrandingRepositoryTrait> BrandingService<T> {
+    pub fn new(repository: Arc<T>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl<T: BrandingRepositoryTrait + Send + Sync> BrandingServiceTrait for BrandingService<T> {
+    async fn get(&self, tenant: Option<Uuid>) -> Result<Branding> {
+        let branding = self.repository.find(tenant.as_ref()).await?;
+        Ok(branding.unwrap_or_default())
+    }
+
+    async fn update(&self, tenant: Option<Uuid>, dto: UpdateBrandingDto) -> Result<Branding> {
+        dto.validate()?;
+        let branding = self.repository.upsert(tenant.as_ref(), &dto).await?;
+        Ok(branding)
+    }
+}
@@ -0,0 +1,99 @@
+// src/services/captcha.rs - CAPTCHA verification for anonymous link creation
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::warn;
+use serde::Deserialize;
+
+use crate::{
+    config::CaptchaConfig, errors::AppError, types::Result, utils::http_client::HttpClient,
+};
+
+// Verification sits in the create request, so a slow provider fails it rather than stalling
+const VERIFY_TIMEOUT: Duration = Duration::from_secs(5);
+
+// hCaptcha and reCAPTCHA answer in the same shape
+#[derive(Deserialize)]
+struct VerifyResponse {
+    success: bool,
+    #[serde(default, rename = "error-codes")]
+    error_codes: Vec<String>,
+}
+
+/// Checks the response token of a solved hCaptcha or reCAPTCHA widget with the provider
+pub struct CaptchaService {
+    config: CaptchaConfig,
+    client: Arc<HttpClient>,
+}
+
+impl CaptchaService {
+    pub fn new(config: CaptchaConfig, client: Arc<HttpClient>) -> Self {
+        Self { config, client }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Verifies a response token, passing the client address along for the provider's checks
+    ///
+    /// ### Errors
+    /// * `AppError::Validation` - If no token was given
+    /// * `AppError::Forbidden` - If the provider rejected the token
+    /// * `AppError::Unavailable` - If the provider couldn't be asked
+    pub async fn verify(&self, token: Option<&str>, remote_ip: Option<&str>) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let token = token
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| {
+                AppError::Validation(
+                    "Solve the CAPTCHA and send its response in X-Captcha-Token to create links \
+                 without an API key"
+                        .to_string(),
+                )
+            })?;
+
+        let mut form = vec![("secret", self.config.secret.as_str()), ("response", token)];
+        if let Some(ip) = remote_ip {
+            form.push(("remoteip", ip));
+        }
+        let request = self
+            .client
+            .post(&self.config.verify_url)
+            .timeout(VERIFY_TIMEOUT)
+            .form(&form);
+
+        let unavailable = |e: String| {
+            warn!("CAPTCHA verification failed: {}", e);
+            AppError::Unavailable("CAPTCHA verification is unavailable, try again".to_string())
+        };
+        let response = self
+            .client
+            .send(request)
+            .await
+            .map_err(|e| unavailable(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(unavailable(format!(
+                "provider responded with {}",
+                response.status()
+            )));
+        }
+        let verdict: VerifyResponse = response
+            .json()
+            .await
+            .map_err(|e| unavailable(e.to_string()))?;
+
+        if verdict.success {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(format!(
+                "CAPTCHA verification failed ({})",
+                verdict.error_codes.join(", ")
+            )))
+        }
+    }
+}
@@ -0,0 +1,50 @@
+// src/services/link_flag.rs - Review of links flagged for abnormal traffic
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use validator::Validate;
+
+use crate::{
+    config::PaginationConfig,
+    models::{LinkFlag, LinkFlagQueryParams, Page},
+    repositories::LinkFlagRepositoryTrait,
+    types::Result,
+};
+
+#[async_trait]
+pub trait LinkFlagServiceTrait {
+    async fn list_flags(&self, params: LinkFlagQueryParams) -> Result<Page<LinkFlag>>;
+    /// Close a flag, lifting any throttle it caused
+    async fn resolve_flag(&self, id: i64) -> Result<LinkFlag>;
+}
+
+pub struct LinkFlagService<T: LinkFlagRepositoryTrait> {
+    repository: Arc<T>,
+    pages: PaginationConfig,
+}
+
+impl<T: LinkFlagRepositoryTrait> LinkFlagService<T> {
+    pub fn new(repository: Arc<T>, pages: PaginationConfig) -> Self {
+        Self { repository, pages }
+    }
+}
+
+#[async_trait]
+impl<T: LinkFlagRepositoryTrait + Send + Sync> LinkFlagServiceTrait for LinkFlagService<T> {
+    async fn list_flags(&self, mut params: LinkFlagQueryParams) -> Result<Page<LinkFlag>> {
+        params.validate()?;
+
+        let limit = self.pages.page_size(params.limit);
+        params.limit = Some(limit);
+        let flags = self.repository.find_flags(&params).await?;
+        Ok(Page {
+            items: flags,
+            limit,
+        })
+    }
+
+    async fn resolve_flag(&self, id: i64) -> Result<LinkFlag> {
+        let flag = self.repository.resolve_flag(id).await?;
+        Ok(flag)
+    }
+}
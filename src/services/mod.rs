@@ -2,16 +2,127 @@ use std::sync::Arc;
 
 use actix_web::web;
 
+mod account;
+mod analytics;
+mod app_links;
+mod backup;
+mod branding;
+mod captcha;
+mod link_flag;
+mod notification;
+mod oauth;
+mod saved_search;
+mod scanner_guard;
+mod service_token;
 mod shortened_url;
+mod snapshot;
+mod stats;
+mod tenant;
+mod user;
 
-pub use shortened_url::{ShortenedUrlService, ShortenedUrlServiceTrait};
+pub use account::{AccountService, AccountServiceTrait};
+pub use analytics::{AnalyticsService, AnalyticsServiceTrait};
+pub use app_links::{AppLinksService, AppLinksServiceTrait};
+pub use backup::BackupService;
+pub use branding::{BrandingService, BrandingServiceTrait};
+pub use captcha::CaptchaService;
+pub use link_flag::{LinkFlagService, LinkFlagServiceTrait};
+pub use notification::{NotificationService, NotificationServiceTrait};
+pub use oauth::{OAuthService, OAuthServiceTrait};
+pub use saved_search::{SavedSearchService, SavedSearchServiceTrait};
+pub use scanner_guard::ScannerGuardService;
+pub use service_token::{ServiceTokenService, ServiceTokenServiceTrait};
+pub use shortened_url::{CodeGenerationStats, ShortenedUrlService, ShortenedUrlServiceTrait};
+pub use snapshot::SnapshotService;
+pub use stats::{StatsService, StatsServiceTrait};
+pub use tenant::TenantService;
+pub use user::{UserService, UserServiceTrait};
 
-use crate::{db::Database, repositories::ShortenedUrlRepository};
+use crate::{
+    config::Config,
+    db::Database,
+    repositories::{
+        AccountRepository, AnalyticsRepository, AppLinksRepository, BrandingRepository,
+        LinkFlagRepository, NotificationRepository, OAuthRepository, SavedSearchRepository,
+        ServiceTokenRepository, SnapshotRepository, StatsRepository, UserRepository,
+    },
+    utils::http_client::HttpClient,
+    warehouse::VisitExporter,
+};
 
 /// Service Register
-pub fn register(db: Database, cfg: &mut web::ServiceConfig) {
-    // create repository
-    let shortened_url_repository = ShortenedUrlRepository::new(db.clone());
-    let shortened_url_service = ShortenedUrlService::new(Arc::new(shortened_url_repository));
-    cfg.app_data(web::Data::new(shortened_url_service));
+///
+/// The shortened URL and tenant services are created once in `app` instead, so their
+/// in-memory state is shared by all workers, as are the HTTP client and the visit exporter.
+pub fn register(
+    db: Database,
+    config: &Config,
+    http_client: Arc<HttpClient>,
+    visit_exporter: Option<Arc<VisitExporter>>,
+    cfg: &mut web::ServiceConfig,
+) {
+    let analytics_repository = AnalyticsRepository::new(db.clone());
+    let analytics_service = AnalyticsService::new(
+        Arc::new(analytics_repository),
+        visit_exporter,
+        config.analytics.store_raw_visits,
+    );
+    cfg.app_data(web::Data::new(analytics_service));
+
+    let user_repository = UserRepository::new(db.clone());
+    let user_service = UserService::new(Arc::new(user_repository));
+    cfg.app_data(web::Data::new(user_service));
+
+    let service_token_repository = ServiceTokenRepository::new(db.clone());
+    let service_token_service = ServiceTokenService::new(Arc::new(service_token_repository));
+    cfg.app_data(web::Data::new(service_token_service));
+
+    let oauth_repository = OAuthRepository::new(db.clone());
+    let oauth_service = OAuthService::new(
+        Arc::new(oauth_repository),
+        Arc::new(UserRepository::new(db.clone())),
+        config.oauth.clone(),
+        http_client.clone(),
+    );
+    cfg.app_data(web::Data::new(oauth_service));
+
+    let account_repository = AccountRepository::new(db.clone());
+    let account_service = AccountService::new(Arc::new(account_repository));
+    cfg.app_data(web::Data::new(account_service));
+
+    let captcha_service = CaptchaService::new(config.captcha.clone(), http_client.clone());
+    cfg.app_data(web::Data::new(captcha_service));
+
+    let notification_repository = NotificationRepository::new(db.clone());
+    let notification_service = NotificationService::new(
+        Arc::new(notification_repository),
+        config.notifications.expiry_notice_days,
+        http_client,
+    );
+    cfg.app_data(web::Data::new(notification_service));
+
+    let link_flag_repository = LinkFlagRepository::new(db.clone());
+    let link_flag_service =
+        LinkFlagService::new(Arc::new(link_flag_repository), config.pagination.clone());
+    cfg.app_data(web::Data::new(link_flag_service));
+
+    let stats_repository = StatsRepository::new(db.clone());
+    let stats_service = StatsService::new(Arc::new(stats_repository), config.pagination.clone());
+    cfg.app_data(web::Data::new(stats_service));
+
+    let saved_search_repository = SavedSearchRepository::new(db.clone());
+    let saved_search_service = SavedSearchService::new(Arc::new(saved_search_repository));
+    cfg.app_data(web::Data::new(saved_search_service));
+
+    let branding_repository = BrandingRepository::new(db.clone());
+    let branding_service = BrandingService::new(Arc::new(branding_repository));
+    cfg.app_data(web::Data::new(branding_service));
+
+    let app_links_repository = AppLinksRepository::new(db.clone());
+    let app_links_service = AppLinksService::new(Arc::new(app_links_repository));
+    cfg.app_data(web::Data::new(app_links_service));
+
+    let snapshot_repository = SnapshotRepository::new(db.clone());
+    let snapshot_service = SnapshotService::new(Arc::new(snapshot_repository));
+    cfg.app_data(web::Data::new(snapshot_service));
 }
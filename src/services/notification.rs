@@ -0,0 +1,123 @@
+// src/services/notification.rs - Notification preferences and channels
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    errors::AppError,
+    models::{
+        CreateNotificationChannelDto, DigestFrequency, NotificationChannel,
+        NotificationPreferences, UpdateNotificationPreferencesDto, User,
+    },
+    notifications::{ChannelDispatcher, NotificationEvent},
+    repositories::NotificationRepositoryTrait,
+    types::Result,
+    utils::http_client::HttpClient,
+};
+
+#[async_trait]
+pub trait NotificationServiceTrait {
+    /// The user's preferences, falling back to the defaults if never saved
+    async fn get_preferences(&self, user: &User) -> Result<NotificationPreferences>;
+    async fn update_preferences(
+        &self,
+        user: &User,
+        dto: UpdateNotificationPreferencesDto,
+    ) -> Result<NotificationPreferences>;
+    async fn list_channels(&self, user: &User) -> Result<Vec<NotificationChannel>>;
+    async fn create_channel(
+        &self,
+        user: &User,
+        dto: CreateNotificationChannelDto,
+    ) -> Result<NotificationChannel>;
+    async fn delete_channel(&self, user: &User, id: &Uuid) -> Result<()>;
+    /// Send a test event to one of the user's channels
+    async fn test_channel(&self, user: &User, id: &Uuid) -> Result<()>;
+}
+
+pub struct NotificationService<T: NotificationRepositoryTrait> {
+    repository: Arc<T>,
+    dispatcher: ChannelDispatcher<T>,
+    default_notice_days: i32,
+}
+
+impl<T: NotificationRepositoryTrait + Send + Sync> NotificationService<T> {
+    pub fn new(repository: Arc<T>, default_notice_days: i32, http_client: Arc<HttpClient>) -> Self {
+        Self {
+            dispatcher: ChannelDispatcher::new(repository.clone(), http_client),
+            repository,
+            default_notice_days,
+        }
+    }
+}
+
+#[async_trait]
+impl<T: NotificationRepositoryTrait + Send + Sync> NotificationServiceTrait
+    for NotificationService<T>
+{
+    async fn get_preferences(&self, user: &User) -> Result<NotificationPreferences> {
+        let preferences = self.repository.find_preferences(&user.id).await?;
+        Ok(preferences.unwrap_or(NotificationPreferences {
+            user_id: user.id,
+            expiry_emails: true,
+            expiry_notice_days: self.default_notice_days,
+            digest_frequency: DigestFrequency::default(),
+            digest_unsubscribed: false,
+            updated_at: user.created_at,
+        }))
+    }
+
+    async fn update_preferences(
+        &self,
+        user: &User,
+        dto: UpdateNotificationPreferencesDto,
+    ) -> Result<NotificationPreferences> {
+        dto.validate()?;
+
+        let preferences = self
+            .repository
+            .upsert_preferences(&user.id, &dto, self.default_notice_days)
+            .await?;
+        Ok(preferences)
+    }
+
+    async fn list_channels(&self, user: &User) -> Result<Vec<NotificationChannel>> {
+        let channels = self.repository.find_channels_by_user(&user.id).await?;
+        Ok(channels)
+    }
+
+    async fn create_channel(
+        &self,
+        user: &User,
+        dto: CreateNotificationChannelDto,
+    ) -> Result<NotificationChannel> {
+        dto.validate()?;
+
+        let channel = self.repository.create_channel(&user.id, &dto).await?;
+        Ok(channel)
+    }
+
+    async fn delete_channel(&self, user: &User, id: &Uuid) -> Result<()> {
+        self.repository.delete_channel(id, &user.id).await?;
+        Ok(())
+    }
+
+    async fn test_channel(&self, user: &User, id: &Uuid) -> Result<()> {
+        let channel = self
+            .repository
+            .find_channels_by_user(&user.id)
+            .await?
+            .into_iter()
+            .find(|c| &c.id == id)
+            .ok_or_else(|| {
+                AppError::NotFound(format!("Notification channel with ID '{}' not found", id))
+            })?;
+
+        self.dispatcher
+            .send(&channel, &NotificationEvent::Test)
+            .await
+            .map_err(|e| AppError::Validation(format!("Channel test failed: {}", e)))
+    }
+}
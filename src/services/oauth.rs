@@ -0,0 +1,479 @@
+// src/services/oauth.rs - Sign in with GitHub or Google
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use log::info;
+use reqwest::header::USER_AGENT;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    config::{OAuthClientConfig, OAuthConfig},
+    errors::AppError,
+    models::{
+        ApiKey, CreateUserDto, OAuthCallbackParams, OAuthProfile, OAuthProvider, PendingLogin, User,
+    },
+    repositories::{OAuthRepositoryTrait, UserRepositoryTrait},
+    types::Result,
+    utils::{
+        hash::sha256_hex,
+        http_client::HttpClient,
+        id_generator::{self, BASE62_ALPHABET},
+    },
+};
+
+// Providers answer quickly; don't keep the user waiting on a dead one
+const PROVIDER_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[async_trait]
+pub trait OAuthServiceTrait {
+    /// Start a login, returning the provider page to send the user to and a nonce the
+    /// browser has to bring back
+    ///
+    /// When `link_user` is given, the identity is linked to that user instead of signing in.
+    async fn start(
+        &self,
+        provider: OAuthProvider,
+        link_user: Option<Uuid>,
+    ) -> Result<(String, String)>;
+    /// Complete a login the provider sent back, returning a new API key secret once
+    ///
+    /// Only the browser holding the `nonce` of the login can complete it.
+    async fn complete(
+        &self,
+        provider: OAuthProvider,
+        params: OAuthCallbackParams,
+        nonce: Option<&str>,
+    ) -> Result<(User, ApiKey, String)>;
+}
+
+pub struct OAuthService<O: OAuthRepositoryTrait, U: UserRepositoryTrait> {
+    repository: Arc<O>,
+    users: Arc<U>,
+    config: OAuthConfig,
+    client: Arc<HttpClient>,
+}
+
+impl<O: OAuthRepositoryTrait, U: UserRepositoryTrait> OAuthService<O, U> {
+    pub fn new(
+        repository: Arc<O>,
+        users: Arc<U>,
+        config: OAuthConfig,
+        client: Arc<HttpClient>,
+    ) -> Self {
+        Self {
+            repository,
+            users,
+            config,
+            client,
+        }
+    }
+
+    fn credentials(&self, provider: OAuthProvider) -> Result<&OAuthClientConfig> {
+        let credentials = match provider {
+            OAuthProvider::GitHub => self.config.github.as_ref(),
+            OAuthProvider::Google => self.config.google.as_ref(),
+        };
+        credentials.ok_or_else(|| AppError::NotFound(format!("{} login is not enabled", provider)))
+    }
+
+    fn redirect_uri(&self, provider: OAuthProvider) -> String {
+        format!(
+            "{}/api/auth/{}/callback",
+            self.config.redirect_base_url.trim_end_matches('/'),
+            provider.as_str()
+        )
+    }
+
+    // Trade the authorization code for an access token
+    async fn exchange_code(
+        &self,
+        provider: OAuthProvider,
+        credentials: &OAuthClientConfig,
+        code: &str,
+        code_verifier: &str,
+    ) -> Result<String> {
+        let redirect_uri = self.redirect_uri(provider);
+        let form = [
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", redirect_uri.as_str()),
+            ("client_id", credentials.client_id.as_str()),
+            ("client_secret", credentials.client_secret.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+
+        // GitHub reports errors with a 200, so the body decides either way
+        let request = self
+            .client
+            .post(token_endpoint(provider))
+            .timeout(PROVIDER_TIMEOUT)
+            .header("Accept", "application/json")
+            .form(&form);
+        let token: TokenResponse = self
+            .client
+            .send(request)
+            .await
+            .map_err(|e| unreachable_provider(provider, e))?
+            .json()
+            .await
+            .map_err(|e| unreachable_provider(provider, e))?;
+
+        token.access_token.ok_or_else(|| {
+            AppError::Unauthorized(format!(
+                "{} refused the login: {}",
+                provider,
+                token
+                    .error_description
+                    .or(token.error)
+                    .unwrap_or_else(|| "no access token".to_string())
+            ))
+        })
+    }
+
+    async fn fetch_profile(&self, provider: OAuthProvider, token: &str) -> Result<OAuthProfile> {
+        match provider {
+            OAuthProvider::GitHub => {
+                let user: GitHubUser = self.get_json(provider, GITHUB_USER_URL, token).await?;
+                // The profile email is optional and unverified, the emails list tells
+                let emails: Vec<GitHubEmail> =
+                    self.get_json(provider, GITHUB_EMAILS_URL, token).await?;
+                let email = emails.into_iter().find(|e| e.primary && e.verified);
+
+                Ok(OAuthProfile {
+                    subject: user.id.to_string(),
+                    email_verified: email.is_some(),
+                    email: email.map(|e| e.email),
+                    name: user.name.or(Some(user.login)),
+                })
+            }
+            OAuthProvider::Google => {
+                let user: GoogleUser = self.get_json(provider, GOOGLE_USERINFO_URL, token).await?;
+                Ok(OAuthProfile {
+                    subject: user.sub,
+                    email: user.email,
+                    email_verified: user.email_verified.unwrap_or(false),
+                    name: user.name,
+                })
+            }
+        }
+    }
+
+    async fn get_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        provider: OAuthProvider,
+        url: &str,
+        token: &str,
+    ) -> Result<T> {
+        let request = self
+            .client
+            .get(url)
+            .timeout(PROVIDER_TIMEOUT)
+            .bearer_auth(token)
+            // GitHub rejects requests without one
+            .header(USER_AGENT, "url-shortener");
+        self.client
+            .send(request)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| unreachable_provider(provider, e))?
+            .json()
+            .await
+            .map_err(|e| unreachable_provider(provider, e))
+    }
+
+    // The user a new identity signs in as: the one with its verified email, or a new one
+    async fn user_for(&self, provider: OAuthProvider, profile: &OAuthProfile) -> Result<User> {
+        let email = match (&profile.email, profile.email_verified) {
+            (Some(email), true) => email.to_lowercase(),
+            _ => {
+                return Err(AppError::Forbidden(format!(
+                    "Your {} account has no verified email address",
+                    provider
+                )))
+            }
+        };
+
+        if let Some(user) = self.users.find_by_email(&email).await? {
+            return Ok(user);
+        }
+
+        let display_name = profile
+            .name
+            .as_ref()
+            .map(|name| name.chars().take(100).collect::<String>())
+            .filter(|name| !name.trim().is_empty());
+        let user = self
+            .users
+            .create(&CreateUserDto {
+                email,
+                display_name,
+                settings: None,
+            })
+            .await?;
+        info!("Created user {} from a {} login", user.id, provider);
+        Ok(user)
+    }
+}
+
+#[async_trait]
+impl<O, U> OAuthServiceTrait for OAuthService<O, U>
+where
+    O: OAuthRepositoryTrait + Send + Sync,
+    U: UserRepositoryTrait + Send + Sync,
+{
+    async fn start(
+        &self,
+        provider: OAuthProvider,
+        link_user: Option<Uuid>,
+    ) -> Result<(String, String)> {
+        let credentials = self.credentials(provider)?;
+
+        // The state travels through the provider and may leak on the way, the nonce stays
+        // in the browser, so a callback sent to someone else's browser goes nowhere
+        let state = id_generator::generate_short_id(32, BASE62_ALPHABET);
+        let nonce = id_generator::generate_short_id(32, BASE62_ALPHABET);
+        let login = PendingLogin {
+            code_verifier: id_generator::generate_short_id(64, BASE62_ALPHABET),
+            nonce_hash: sha256_hex(&nonce),
+            link_user_id: link_user,
+        };
+        self.repository
+            .save_login(&state, provider, &login, self.config.state_ttl_seconds)
+            .await?;
+
+        let location = authorize_url(
+            provider,
+            credentials,
+            &self.redirect_uri(provider),
+            &state,
+            &login.code_verifier,
+        );
+        Ok((location, nonce))
+    }
+
+    async fn complete(
+        &self,
+        provider: OAuthProvider,
+        params: OAuthCallbackParams,
+        nonce: Option<&str>,
+    ) -> Result<(User, ApiKey, String)> {
+        if let Some(error) = params.error {
+            return Err(AppError::Unauthorized(format!(
+                "The {} login was not completed: {}",
+                provider, error
+            )));
+        }
+        let code = params
+            .code
+            .ok_or_else(|| AppError::Validation("The authorization code is missing".to_string()))?;
+        let credentials = self.credentials(provider)?;
+        let nonce = nonce.ok_or_else(|| {
+            AppError::Unauthorized(
+                "This login was started in another browser, please start again".to_string(),
+            )
+        })?;
+
+        let login = self
+            .repository
+            .take_login(
+                &params.state,
+                &sha256_hex(nonce),
+                provider,
+                self.config.state_ttl_seconds,
+            )
+            .await?
+            .ok_or_else(|| {
+                AppError::Unauthorized(
+                    "This login has expired, was already used or was started in another browser, \
+                     please start again"
+                        .to_string(),
+                )
+            })?;
+
+        let token = self
+            .exchange_code(provider, credentials, &code, &login.code_verifier)
+            .await?;
+        let profile = self.fetch_profile(provider, &token).await?;
+
+        let user = match self
+            .repository
+            .find_user(provider, &profile.subject)
+            .await?
+        {
+            Some(user) if login.link_user_id.is_none_or(|id| id == user.id) => user,
+            Some(_) => {
+                return Err(AppError::Conflict(format!(
+                    "This {} account is already linked to another user",
+                    provider
+                )))
+            }
+            None => {
+                let user_id = match login.link_user_id {
+                    Some(user_id) => user_id,
+                    None => self.user_for(provider, &profile).await?.id,
+                };
+                self.repository
+                    .link_identity(
+                        provider,
+                        &profile.subject,
+                        &user_id,
+                        profile.email.as_deref(),
+                    )
+                    .await?;
+
+                // Re-read, a concurrent login may have linked the identity first
+                self.repository
+                    .find_user(provider, &profile.subject)
+                    .await?
+                    .filter(|user| user.id == user_id)
+                    .ok_or_else(|| {
+                        AppError::Conflict(format!(
+                            "This {} account is already linked to another user",
+                            provider
+                        ))
+                    })?
+            }
+        };
+
+        // Only the hash is stored; the caller must keep the secret
+        let secret = id_generator::generate_api_key();
+        let api_key = self
+            .users
+            .create_api_key(
+                &user.id,
+                &format!("{} login", provider.as_str()),
+                &sha256_hex(&secret),
+            )
+            .await?;
+
+        Ok((user, api_key, secret))
+    }
+}
+
+const GITHUB_USER_URL: &str = "https://api.github.com/user";
+const GITHUB_EMAILS_URL: &str = "https://api.github.com/user/emails";
+const GOOGLE_USERINFO_URL: &str = "https://openidconnect.googleapis.com/v1/userinfo";
+
+fn token_endpoint(provider: OAuthProvider) -> &'static str {
+    match provider {
+        OAuthProvider::GitHub => "https://github.com/login/oauth/access_token",
+        OAuthProvider::Google => "https://oauth2.googleapis.com/token",
+    }
+}
+
+// The provider page asking the user to allow the login, bound to this login by PKCE
+fn authorize_url(
+    provider: OAuthProvider,
+    credentials: &OAuthClientConfig,
+    redirect_uri: &str,
+    state: &str,
+    code_verifier: &str,
+) -> String {
+    let (endpoint, scope) = match provider {
+        OAuthProvider::GitHub => (
+            "https://github.com/login/oauth/authorize",
+            "read:user user:email",
+        ),
+        OAuthProvider::Google => (
+            "https://accounts.google.com/o/oauth2/v2/auth",
+            "openid email profile",
+        ),
+    };
+    let challenge = pkce_challenge(code_verifier);
+
+    let mut url = Url::parse(endpoint).expect("provider endpoints are valid URLs");
+    url.query_pairs_mut()
+        .append_pair("client_id", &credentials.client_id)
+        .append_pair("redirect_uri", redirect_uri)
+        .append_pair("response_type", "code")
+        .append_pair("scope", scope)
+        .append_pair("state", state)
+        .append_pair("code_challenge", &challenge)
+        .append_pair("code_challenge_method", "S256");
+    url.to_string()
+}
+
+// S256 code challenge: the unpadded base64url SHA-256 of the verifier
+fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+fn unreachable_provider(provider: OAuthProvider, e: reqwest::Error) -> AppError {
+    AppError::Unavailable(format!("{} could not be reached: {}", provider, e))
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubUser {
+    id: i64,
+    login: String,
+    name: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GitHubEmail {
+    email: String,
+    primary: bool,
+    verified: bool,
+}
+
+#[derive(Deserialize)]
+struct GoogleUser {
+    sub: String,
+    email: Option<String>,
+    email_verified: Option<bool>,
+    name: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_challenge_matches_rfc_7636_example() {
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_authorize_url_carries_state_and_challenge() {
+        let credentials = OAuthClientConfig {
+            client_id: "client-1".to_string(),
+            client_secret: "secret".to_string(),
+        };
+        let url = authorize_url(
+            OAuthProvider::GitHub,
+            &credentials,
+            "https://sho.rt/api/auth/github/callback",
+            "state-1",
+            "verifier",
+        );
+        let url = Url::parse(&url).unwrap();
+        let query = url
+            .query_pairs()
+            .collect::<std::collections::HashMap<_, _>>();
+
+        assert_eq!(url.host_str(), Some("github.com"));
+        assert_eq!(query["client_id"], "client-1");
+        assert_eq!(
+            query["redirect_uri"],
+            "https://sho.rt/api/auth/github/callback"
+        );
+        assert_eq!(query["state"], "state-1");
+        assert_eq!(query["code_challenge"], pkce_challenge("verifier"));
+        assert_eq!(query["code_challenge_method"], "S256");
+    }
+}
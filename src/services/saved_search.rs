@@ -0,0 +1,197 @@
+// src/services/saved_search.rs - Named URL filters, per tenant
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    errors::{AppError, RepositoryError},
+    models::{SaveSearchDto, SavedSearch, ShortenedUrlQueryParams},
+    repositories::SavedSearchRepositoryTrait,
+    types::Result,
+};
+
+#[async_trait]
+pub trait SavedSearchServiceTrait {
+    async fn list(&self, tenant: Option<Uuid>) -> Result<Vec<SavedSearch>>;
+    async fn get(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<SavedSearch>;
+    async fn create(&self, tenant: Option<Uuid>, dto: SaveSearchDto) -> Result<SavedSearch>;
+    async fn update(
+        &self,
+        id: &Uuid,
+        tenant: Option<Uuid>,
+        dto: SaveSearchDto,
+    ) -> Result<SavedSearch>;
+    async fn delete(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<()>;
+    /// The query with the filters of the preset it names, if any, filling in the ones it
+    /// leaves unset
+    async fn apply(
+        &self,
+        tenant: Option<Uuid>,
+        params: ShortenedUrlQueryParams,
+    ) -> Result<ShortenedUrlQueryParams>;
+}
+
+pub struct SavedSearchService<T: SavedSearchRepositoryTrait> {
+    repository: Arc<T>,
+}
+
+impl<T: SavedSearchRepositoryTrait> SavedSearchService<T> {
+    pub fn new(repository: Arc<T>) -> Self {
+        Self { repository }
+    }
+
+    fn not_found(id: &Uuid) -> AppError {
+        AppError::NotFound(format!("Saved search with ID '{}' not found", id))
+    }
+}
+
+#[async_trait]
+impl<T: SavedSearchRepositoryTrait + Send + Sync> SavedSearchServiceTrait
+    for SavedSearchService<T>
+{
+    async fn list(&self, tenant: Option<Uuid>) -> Result<Vec<SavedSearch>> {
+        let searches = self.repository.find_all(tenant.as_ref()).await?;
+        Ok(searches)
+    }
+
+    async fn get(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<SavedSearch> {
+        self.repository
+            .find_by_id(id, tenant.as_ref())
+            .await?
+            .ok_or_else(|| Self::not_found(id))
+    }
+
+    async fn create(&self, tenant: Option<Uuid>, dto: SaveSearchDto) -> Result<SavedSearch> {
+        dto.validate()?;
+        let params = JsonValue::Object(set_params(&dto.params));
+        self.repository
+            .create(tenant.as_ref(), &dto.name, &params)
+            .await
+            .map_err(|e| name_taken(e, &dto.name))
+    }
+
+    async fn update(
+        &self,
+        id: &Uuid,
+        tenant: Option<Uuid>,
+        dto: SaveSearchDto,
+    ) -> Result<SavedSearch> {
+        dto.validate()?;
+        let params = JsonValue::Object(set_params(&dto.params));
+        self.repository
+            .update(id, tenant.as_ref(), &dto.name, &params)
+            .await
+            .map_err(|e| name_taken(e, &dto.name))?
+            .ok_or_else(|| Self::not_found(id))
+    }
+
+    async fn delete(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<()> {
+        if !self.repository.delete(id, tenant.as_ref()).await? {
+            return Err(Self::not_found(id));
+        }
+        Ok(())
+    }
+
+    async fn apply(
+        &self,
+        tenant: Option<Uuid>,
+        params: ShortenedUrlQueryParams,
+    ) -> Result<ShortenedUrlQueryParams> {
+        let Some(name) = params.preset.as_deref() else {
+            return Ok(params);
+        };
+
+        let preset = self
+            .repository
+            .find_by_name(name, tenant.as_ref())
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("No saved search named '{}'", name)))?;
+        with_preset(params, &preset)
+    }
+}
+
+fn name_taken(err: RepositoryError, name: &str) -> AppError {
+    match err {
+        RepositoryError::Conflict(_) => {
+            AppError::Conflict(format!("A saved search named '{}' already exists", name))
+        }
+        err => err.into(),
+    }
+}
+
+// The parameters of a query that are set, as a JSON object
+fn set_params(params: &ShortenedUrlQueryParams) -> JsonMap<String, JsonValue> {
+    let mut fields = match serde_json::to_value(params) {
+        Ok(JsonValue::Object(fields)) => fields,
+        _ => JsonMap::new(),
+    };
+    fields.retain(|_, value| !value.is_null());
+    fields
+}
+
+// The query's parameters over those of a saved search
+fn with_preset(
+    params: ShortenedUrlQueryParams,
+    preset: &SavedSearch,
+) -> Result<ShortenedUrlQueryParams> {
+    let mut fields = preset.params.as_object().cloned().unwrap_or_default();
+    fields.extend(set_params(&params));
+    fields.remove("preset");
+
+    let mut merged: ShortenedUrlQueryParams = serde_json::from_value(JsonValue::Object(fields))
+        .map_err(|e| {
+            AppError::Internal(format!(
+                "Saved search '{}' has invalid parameters: {}",
+                preset.name, e
+            ))
+        })?;
+    merged.tenant = params.tenant;
+    merged.user = params.user;
+
+    // Filters from both sides may contradict each other, like dates out of order
+    merged.validate()?;
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TenantScope;
+    use chrono::Utc;
+    use serde_json::json;
+
+    #[test]
+    fn test_with_preset() {
+        let preset = SavedSearch {
+            id: Uuid::new_v4(),
+            name: "popular".to_string(),
+            params: json!({ "is_active": true, "order_by": "access_count:desc", "limit": 10 }),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let params = ShortenedUrlQueryParams {
+            limit: Some(5),
+            preset: Some("popular".to_string()),
+            tenant: TenantScope::Only(None),
+            ..Default::default()
+        };
+
+        let merged = with_preset(params, &preset).unwrap();
+        assert_eq!(merged.limit, Some(5));
+        assert_eq!(merged.is_active, Some(true));
+        assert_eq!(
+            merged.order_by.clone().map(String::from).as_deref(),
+            Some("access_count:desc")
+        );
+        assert_eq!(merged.preset, None);
+        assert_eq!(merged.tenant, TenantScope::Only(None));
+
+        assert_eq!(
+            JsonValue::Object(set_params(&merged)),
+            json!({ "is_active": true, "order_by": "access_count:desc", "limit": 5 })
+        );
+    }
+}
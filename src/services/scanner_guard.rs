@@ -0,0 +1,267 @@
+// src/services/scanner_guard.rs - Tarpit and blocklist for code enumeration
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use log::{debug, warn};
+use validator::Validate;
+
+use crate::{
+    config::ScannerGuardConfig,
+    errors::AppError,
+    models::{BlockIpDto, BlockSource, BlockedIp},
+    repositories::BlockedIpRepositoryTrait,
+    types::Result,
+};
+
+// Misses counted for one address in the current window
+struct MissWindow {
+    started: Instant,
+    misses: u32,
+}
+
+/// Tracks 404s per client address, slowing down and then blocking clients that enumerate codes
+///
+/// Miss counts live in memory per instance; blocks are stored in the database and every
+/// instance reloads them periodically, so a block applies across replicas.
+pub struct ScannerGuardService<R: BlockedIpRepositoryTrait> {
+    repository: Arc<R>,
+    config: RwLock<ScannerGuardConfig>,
+    misses: Mutex<HashMap<IpAddr, MissWindow>>,
+    blocked: RwLock<HashMap<IpAddr, Option<DateTime<Utc>>>>,
+}
+
+impl<R: BlockedIpRepositoryTrait + Send + Sync> ScannerGuardService<R> {
+    pub fn new(repository: Arc<R>, config: ScannerGuardConfig) -> Self {
+        Self {
+            repository,
+            config: RwLock::new(config),
+            misses: Mutex::new(HashMap::new()),
+            blocked: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config().enabled
+    }
+
+    /// Apply new thresholds and honeypot paths; whether the guard runs and how often it
+    /// syncs are kept until a restart
+    pub fn update_config(&self, config: ScannerGuardConfig) {
+        let mut current = self.config.write().unwrap_or_else(|e| e.into_inner());
+        *current = ScannerGuardConfig {
+            enabled: current.enabled,
+            sync_interval_seconds: current.sync_interval_seconds,
+            ..config
+        };
+    }
+
+    fn config(&self) -> RwLockReadGuard<'_, ScannerGuardConfig> {
+        self.config.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether requests from this address are refused
+    pub fn is_blocked(&self, ip: &IpAddr) -> bool {
+        let blocked = self.blocked.read().unwrap_or_else(|e| e.into_inner());
+        match blocked.get(ip) {
+            Some(until) => until.is_none_or(|until| Utc::now() < until),
+            None => false,
+        }
+    }
+
+    /// How long to hold the response to this address before answering
+    pub fn tarpit_delay(&self, ip: &IpAddr) -> Duration {
+        let config = self.config();
+        let window = Duration::from_secs(config.window_seconds);
+        let misses = self.misses.lock().unwrap_or_else(|e| e.into_inner());
+        let count = misses
+            .get(ip)
+            .filter(|w| w.started.elapsed() < window)
+            .map_or(0, |w| w.misses);
+
+        tarpit_delay(
+            count,
+            config.tarpit_threshold,
+            config.tarpit_base_delay_ms,
+            config.tarpit_max_delay_ms,
+        )
+    }
+
+    /// Whether only a scanner would request this path
+    pub fn is_honeypot(&self, path: &str) -> bool {
+        self.config().honeypot_paths.iter().any(|p| p == path)
+    }
+
+    /// Count a request that hit an unknown code, blocking the address past the threshold
+    pub async fn record_miss(&self, ip: IpAddr) -> Result<()> {
+        let (window_seconds, block_threshold) = {
+            let config = self.config();
+            (config.window_seconds, config.block_threshold)
+        };
+        let count = {
+            let window = Duration::from_secs(window_seconds);
+            let mut misses = self.misses.lock().unwrap_or_else(|e| e.into_inner());
+            let entry = misses.entry(ip).or_insert(MissWindow {
+                started: Instant::now(),
+                misses: 0,
+            });
+            if entry.started.elapsed() >= window {
+                entry.started = Instant::now();
+                entry.misses = 0;
+            }
+            entry.misses += 1;
+            entry.misses
+        };
+
+        // Block exactly once, when the threshold is crossed
+        if count != block_threshold {
+            return Ok(());
+        }
+
+        let reason = format!(
+            "{} unknown codes requested within {}s",
+            count, window_seconds
+        );
+        self.auto_block(ip, &reason).await
+    }
+
+    /// Block an address right away for requesting a honeypot path
+    pub async fn record_honeypot_hit(&self, ip: IpAddr, path: &str) -> Result<()> {
+        self.auto_block(ip, &format!("Requested honeypot path '{}'", path))
+            .await
+    }
+
+    async fn auto_block(&self, ip: IpAddr, reason: &str) -> Result<()> {
+        let until = Utc::now() + chrono::Duration::seconds(self.config().block_seconds);
+        warn!("Blocking {} until {}: {}", ip, until, reason);
+
+        if let Some(block) = self
+            .repository
+            .upsert(&ip.to_string(), reason, BlockSource::Auto, Some(until))
+            .await?
+        {
+            self.cache_block(ip, block.blocked_until);
+        }
+
+        Ok(())
+    }
+
+    /// Reload blocks from the database and drop state that ran out
+    pub async fn refresh(&self) -> Result<()> {
+        let removed = self.repository.delete_expired().await?;
+        let active = self.repository.find_active().await?;
+
+        let blocked = active
+            .iter()
+            .filter_map(|b| b.ip.parse::<IpAddr>().ok().map(|ip| (ip, b.blocked_until)))
+            .collect::<HashMap<_, _>>();
+        debug!(
+            "Scanner guard: {} active blocks, {} expired removed",
+            blocked.len(),
+            removed
+        );
+        *self.blocked.write().unwrap_or_else(|e| e.into_inner()) = blocked;
+
+        let window = Duration::from_secs(self.config().window_seconds);
+        self.misses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .retain(|_, w| w.started.elapsed() < window);
+
+        Ok(())
+    }
+
+    pub async fn list_blocks(&self) -> Result<Vec<BlockedIp>> {
+        let blocks = self.repository.find_active().await?;
+        Ok(blocks)
+    }
+
+    pub async fn block(&self, dto: BlockIpDto) -> Result<BlockedIp> {
+        dto.validate()?;
+
+        let ip = parse_ip(&dto.ip)?;
+        let until = dto
+            .duration_seconds
+            .map(|secs| Utc::now() + chrono::Duration::seconds(secs));
+        let reason = dto
+            .reason
+            .unwrap_or_else(|| "Blocked by an admin".to_string());
+
+        let block = self
+            .repository
+            .upsert(&ip.to_string(), &reason, BlockSource::Manual, until)
+            .await?
+            .ok_or_else(|| AppError::Internal(format!("Failed to block '{}'", ip)))?;
+
+        self.cache_block(ip, block.blocked_until);
+        Ok(block)
+    }
+
+    pub async fn unblock(&self, ip: &str) -> Result<()> {
+        let ip = parse_ip(ip)?;
+        self.repository.delete(&ip.to_string()).await?;
+
+        self.blocked
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&ip);
+        self.misses
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&ip);
+        Ok(())
+    }
+
+    fn cache_block(&self, ip: IpAddr, until: Option<DateTime<Utc>>) {
+        self.blocked
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(ip, until);
+    }
+}
+
+// Addresses are stored in canonical form so "::1" and "0:0::1" are the same block
+fn parse_ip(ip: &str) -> Result<IpAddr> {
+    ip.trim()
+        .parse::<IpAddr>()
+        .map_err(|_| AppError::Validation(format!("'{}' is not a valid IP address", ip)))
+}
+
+// Delay doubling with every miss past the threshold, capped at the maximum
+fn tarpit_delay(misses: u32, threshold: u32, base_ms: u64, max_ms: u64) -> Duration {
+    if misses <= threshold {
+        return Duration::ZERO;
+    }
+
+    let excess = (misses - threshold - 1).min(32);
+    let delay = base_ms.saturating_mul(1u64 << excess).min(max_ms);
+    Duration::from_millis(delay)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tarpit_delay() {
+        assert_eq!(tarpit_delay(10, 10, 250, 10_000), Duration::ZERO);
+        assert_eq!(
+            tarpit_delay(11, 10, 250, 10_000),
+            Duration::from_millis(250)
+        );
+        assert_eq!(
+            tarpit_delay(12, 10, 250, 10_000),
+            Duration::from_millis(500)
+        );
+        assert_eq!(
+            tarpit_delay(14, 10, 250, 10_000),
+            Duration::from_millis(2_000)
+        );
+        assert_eq!(
+            tarpit_delay(1_000, 10, 250, 10_000),
+            Duration::from_millis(10_000)
+        );
+    }
+}
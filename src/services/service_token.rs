@@ -0,0 +1,68 @@
+// src/services/service_token.rs - Machine credentials with scopes
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::{
+    errors::AppError,
+    models::{CreateServiceTokenDto, ServiceToken, ServiceTokenIdentity},
+    repositories::ServiceTokenRepositoryTrait,
+    types::Result,
+    utils::{hash::sha256_hex, id_generator},
+};
+
+#[async_trait]
+pub trait ServiceTokenServiceTrait {
+    /// Create a token, returning its secret once
+    async fn create(&self, dto: CreateServiceTokenDto) -> Result<(ServiceToken, String)>;
+    async fn list(&self) -> Result<Vec<ServiceToken>>;
+    async fn revoke(&self, id: &Uuid) -> Result<()>;
+    /// Resolve a token secret, `None` when unknown, expired or revoked
+    async fn authenticate(&self, secret: &str) -> Result<Option<ServiceTokenIdentity>>;
+}
+
+pub struct ServiceTokenService<T: ServiceTokenRepositoryTrait> {
+    repository: Arc<T>,
+}
+
+impl<T: ServiceTokenRepositoryTrait> ServiceTokenService<T> {
+    pub fn new(repository: Arc<T>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl<T: ServiceTokenRepositoryTrait + Send + Sync> ServiceTokenServiceTrait
+    for ServiceTokenService<T>
+{
+    async fn create(&self, dto: CreateServiceTokenDto) -> Result<(ServiceToken, String)> {
+        dto.validate()?;
+
+        // Only the hash is stored; the caller must keep the secret
+        let secret = id_generator::generate_service_token();
+        let token = self.repository.create(&dto, &sha256_hex(&secret)).await?;
+        Ok((token, secret))
+    }
+
+    async fn list(&self) -> Result<Vec<ServiceToken>> {
+        let tokens = self.repository.find_all().await?;
+        Ok(tokens)
+    }
+
+    async fn revoke(&self, id: &Uuid) -> Result<()> {
+        if !self.repository.revoke(id).await? {
+            return Err(AppError::NotFound(format!(
+                "No active service token with ID '{}'",
+                id
+            )));
+        }
+        Ok(())
+    }
+
+    async fn authenticate(&self, secret: &str) -> Result<Option<ServiceTokenIdentity>> {
+        let identity = self.repository.find_by_hash(&sha256_hex(secret)).await?;
+        Ok(identity)
+    }
+}
@@ -1,101 +1,547 @@
 // src/services/shortened_url.rs - Business logic
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use chrono::{Duration, Utc};
+use chrono::{DateTime, Duration, Utc};
+use futures::{stream::BoxStream, StreamExt};
+use ipnet::IpNet;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use url::Url;
 use uuid::Uuid;
 use validator::Validate;
 
 use crate::{
-    errors::AppError,
+    cache::{AccessBacklog, LinkCache, RecentCreates, SingleFlight},
+    config::{MilestoneConfig, PaginationConfig, ShortCodeConfig},
+    errors::{AppError, ErrorCode, FieldErrors, RepositoryError},
+    events::{DomainEvent, EventBus},
     models::{
-        CreateShortenedUrlDto, ShortenedUrl, ShortenedUrlQueryParams, ShortenedUrlResponseDto,
-        ShortenedUrlUpdateParams,
+        AliasConflict, BulkDeleteUrlsDto, BulkItemResult, BulkUpdateUrlsDto, ClaimLinkDto,
+        CodeStrategy, CreateShortenedUrlDto, DuplicateShortenedUrlDto, ExpiringQueryParams,
+        LinkHealth, LinkState, LinkStatus, LinkTransfer, NewShortenedUrl, Page, Reachability,
+        ResolveCodesDto, ResolvedCode, ShortenedUrl, ShortenedUrlDetailsDto,
+        ShortenedUrlQueryParams, ShortenedUrlResponseDto, ShortenedUrlUpdateParams, TenantScope,
+        TransferLinkDto, TransferStatus, TrashStats, TrashedUrl, UrlRevision, MAX_BULK_URLS,
     },
-    repositories::ShortenedUrlRepositoryTrait,
+    repositories::{commit, ShortenedUrlRepositoryTrait},
     types::Result,
-    utils::id_generator,
+    utils::{hash::sha256_hex, id_generator, network::parse_cidrs, unicode::nfc},
+    validations::validate_alias_length,
 };
 
 #[async_trait]
 pub trait ShortenedUrlServiceTrait {
-    async fn create(&self, dto: CreateShortenedUrlDto) -> Result<ShortenedUrlResponseDto>;
-    async fn get_by_id(&self, id: &Uuid) -> Result<ShortenedUrl>;
-    async fn get_by_query(&self, params: &ShortenedUrlQueryParams) -> Result<Vec<ShortenedUrl>>;
-    async fn get_all(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<ShortenedUrl>>;
-    async fn get_by_code(&self, code: &str) -> Result<ShortenedUrl>;
-    async fn update(&self, id: &Uuid, params: ShortenedUrlUpdateParams) -> Result<u64>;
-    async fn delete(&self, id: &Uuid) -> Result<bool>;
+    async fn create(
+        &self,
+        dto: CreateShortenedUrlDto,
+        created_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrlResponseDto>;
+    /// Run every check of `create` and return the URL it would create, without saving it
+    async fn preview_create(
+        &self,
+        dto: CreateShortenedUrlDto,
+        created_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrlResponseDto>;
+    /// A URL of the tenant by ID; URLs of other tenants are reported as not found
+    /// The id a link goes by now, for ids clients may have kept from before links moved to
+    /// time-ordered ids; any other id is returned as it is
+    async fn resolve_id(&self, id: Uuid) -> Result<Uuid>;
+    async fn get_by_id(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<ShortenedUrl>;
+    /// A URL of the tenant by ID along with its health, as shown on its detail response
+    async fn get_details(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<ShortenedUrlDetailsDto>;
+    async fn get_by_query(&self, params: &ShortenedUrlQueryParams) -> Result<Page<ShortenedUrl>>;
+    async fn get_all(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        tenant: Option<Uuid>,
+    ) -> Result<Page<ShortenedUrl>>;
+    /// Number of URLs matching params; `limit`, `offset` and ordering are ignored
+    async fn count(&self, params: &ShortenedUrlQueryParams) -> Result<i64>;
+    /// Every URL matching params, streamed rather than paged; `limit` is only applied if set
+    fn export(&self, params: ShortenedUrlQueryParams) -> BoxStream<'static, Result<ShortenedUrl>>;
+    async fn get_by_code(
+        &self,
+        tenant: Option<Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Result<ShortenedUrl>;
+    async fn get_by_path(
+        &self,
+        tenant: Option<Uuid>,
+        first: &str,
+        rest: &str,
+    ) -> Result<(ShortenedUrl, String)>;
+    async fn resolve_codes(
+        &self,
+        dto: ResolveCodesDto,
+        tenant: Option<Uuid>,
+    ) -> Result<BTreeMap<String, Option<ResolvedCode>>>;
+    async fn update(
+        &self,
+        id: &Uuid,
+        params: ShortenedUrlUpdateParams,
+        changed_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<u64>;
+    /// Run every check of `update` and return the URL as it would be, without changing it
+    async fn preview_update(
+        &self,
+        id: &Uuid,
+        params: ShortenedUrlUpdateParams,
+        user_id: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrlDetailsDto>;
+    async fn get_history(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<Vec<UrlRevision>>;
+    async fn revert(
+        &self,
+        id: &Uuid,
+        revision: i32,
+        changed_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl>;
+    /// Copy a URL of the tenant to a new link with a fresh code, or the alias given. The
+    /// copy keeps the destination, settings and metadata but starts with no visits
+    async fn duplicate(
+        &self,
+        id: &Uuid,
+        dto: DuplicateShortenedUrlDto,
+        created_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrlResponseDto>;
+    /// Move a URL of the tenant to the trash, from which it can be restored until purged
+    async fn delete(&self, id: &Uuid, user_id: Option<Uuid>, tenant: Option<Uuid>) -> Result<bool>;
+    /// Live URLs of the tenant expiring within `within_days`, 7 by default, soonest first
+    async fn get_expiring(
+        &self,
+        params: &ExpiringQueryParams,
+        tenant: Option<Uuid>,
+    ) -> Result<Page<ShortenedUrl>>;
+    /// URLs of the tenant in the trash, most recently deleted first
+    async fn get_trash(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        tenant: Option<Uuid>,
+    ) -> Result<Page<TrashedUrl>>;
+    /// Take a URL of the tenant out of the trash, with its code, settings and visits
+    async fn restore(
+        &self,
+        id: &Uuid,
+        user_id: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl>;
+    /// Pin a URL of the tenant for a user, who finds it with `favorites=true`
+    async fn add_favorite(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl>;
+    /// Unpin a URL for a user, returning whether it was pinned
+    async fn remove_favorite(&self, id: &Uuid, user_id: &Uuid) -> Result<bool>;
+    /// Make a user the owner of a link created without signing in, with the token its
+    /// creation was answered with. A token works once
+    async fn claim(
+        &self,
+        dto: ClaimLinkDto,
+        user_id: &Uuid,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl>;
+    /// Offer a URL of the tenant owned by the user to another user, who becomes its owner
+    /// once accepting
+    async fn offer_transfer(
+        &self,
+        id: &Uuid,
+        dto: TransferLinkDto,
+        user_id: &Uuid,
+        tenant: Option<Uuid>,
+    ) -> Result<LinkTransfer>;
+    /// Transfers offered to a user and waiting for an answer
+    async fn pending_transfers(&self, user_id: &Uuid) -> Result<Vec<LinkTransfer>>;
+    /// Every transfer of a URL of the tenant, newest first
+    async fn get_transfers(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<Vec<LinkTransfer>>;
+    /// Accept a transfer offered to the user, who becomes the owner of its URL
+    async fn accept_transfer(&self, id: &Uuid, user_id: &Uuid) -> Result<ShortenedUrl>;
+    /// Decline a transfer offered to the user, or withdraw one the user offered
+    async fn close_transfer(&self, id: &Uuid, user_id: &Uuid) -> Result<LinkTransfer>;
+    /// Move a URL of the tenant to another owner or tenant without asking, for admins
+    async fn force_transfer(
+        &self,
+        id: &Uuid,
+        to_user_id: Option<Uuid>,
+        to_tenant: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl>;
+    /// Archive a URL of the tenant, hiding it from listings while it keeps redirecting, or
+    /// take it out of the archive
+    async fn set_archived(
+        &self,
+        id: &Uuid,
+        archived: bool,
+        user_id: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl>;
+    /// URLs in the trash of every tenant, and how many were trashed over `retention_days` ago
+    async fn trash_stats(&self, retention_days: u32) -> Result<TrashStats>;
+    /// Apply one change to URLs of the tenant picked by ID or filter, in one transaction.
+    /// URLs that can't be changed get a failed result instead of failing the others
+    async fn bulk_update(
+        &self,
+        dto: BulkUpdateUrlsDto,
+        changed_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<Vec<BulkItemResult>>;
+    /// Delete URLs of the tenant picked by ID or filter, in one transaction
+    async fn bulk_delete(
+        &self,
+        dto: BulkDeleteUrlsDto,
+        user_id: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<Vec<BulkItemResult>>;
+    /// Move a link of any tenant to another moderation status, for admins
+    async fn set_status(&self, id: &Uuid, status: LinkStatus) -> Result<ShortenedUrl>;
+    async fn consume(&self, url: &ShortenedUrl) -> Result<()>;
+    async fn record_access(&self, url: &ShortenedUrl) -> Result<()>;
+    /// Count an access without touching the database, like during an outage, for
+    /// `replay_accesses` to write later
+    fn defer_access(&self, url: &ShortenedUrl);
+    async fn replay_accesses(&self) -> Result<usize>;
+    async fn warm_cache(&self, limit: usize) -> Result<usize>;
+}
+
+/// Short codes generated since startup, as reported by the health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeGenerationStats {
+    /// Links created with a generated code
+    pub generated: u64,
+    /// Codes drawn again because the previous one was taken
+    pub retries: u64,
+    /// Creations that gave up without finding a free code
+    pub exhausted: u64,
+}
+
+#[derive(Default)]
+struct CodeCounters {
+    generated: AtomicU64,
+    retries: AtomicU64,
+    exhausted: AtomicU64,
 }
 
 pub struct ShortenedUrlService<T: ShortenedUrlRepositoryTrait> {
     repository: Arc<T>,
+    codes: ShortCodeConfig,
+    pages: PaginationConfig,
+    milestones: MilestoneConfig,
+    cache: Arc<LinkCache>,
+    backlog: Arc<AccessBacklog>,
+    events: Arc<EventBus>,
+    lookups: SingleFlight<SharedLookup>,
+    code_counters: CodeCounters,
+    recent_creates: RecentCreates,
+    claim_ttl_days: u32,
+}
+
+// Random codes drawn before giving up on finding a free one
+const MAX_CODE_ATTEMPTS: usize = 5;
+
+// Highest number appended to a taken alias, `launch-100`, before giving up
+const MAX_ALIAS_SUFFIX: u32 = 100;
+
+// How the code of a prepared link was asked for, to pick another when it turns out taken
+struct CodeRequest {
+    strategy: CodeStrategy,
+    on_conflict: AliasConflict,
+    /// The custom alias asked for, before any number was appended
+    alias: Option<String>,
+    /// Number appended to the alias, 1 while it has none
+    suffix: u32,
 }
 
+// Days ahead the expiring-soon listing looks when not told
+const DEFAULT_EXPIRING_WITHIN_DAYS: u32 = 7;
+
+// A code lookup as shared between concurrent requests for the same code
+type SharedLookup = std::result::Result<Option<ShortenedUrl>, Arc<RepositoryError>>;
+
 impl<T: ShortenedUrlRepositoryTrait> ShortenedUrlService<T> {
-    pub fn new(repository: Arc<T>) -> Self {
-        Self { repository }
+    pub fn new(
+        repository: Arc<T>,
+        codes: ShortCodeConfig,
+        pages: PaginationConfig,
+        milestones: MilestoneConfig,
+        cache: Arc<LinkCache>,
+        backlog: Arc<AccessBacklog>,
+        events: Arc<EventBus>,
+    ) -> Self {
+        Self {
+            repository,
+            codes,
+            pages,
+            milestones,
+            cache,
+            backlog,
+            events,
+            lookups: SingleFlight::new(),
+            code_counters: CodeCounters::default(),
+            recent_creates: RecentCreates::disabled(),
+            claim_ttl_days: 0,
+        }
+    }
+
+    /// Answer create requests repeated by the same client with the link created first
+    pub fn with_recent_creates(mut self, recent_creates: RecentCreates) -> Self {
+        self.recent_creates = recent_creates;
+        self
+    }
+
+    /// Give links created without signing in a token to claim them with for `ttl_days`, 0
+    /// to issue none
+    pub fn with_link_claims(mut self, ttl_days: u32) -> Self {
+        self.claim_ttl_days = ttl_days;
+        self
+    }
+
+    pub fn code_stats(&self) -> CodeGenerationStats {
+        CodeGenerationStats {
+            generated: self.code_counters.generated.load(Ordering::Relaxed),
+            retries: self.code_counters.retries.load(Ordering::Relaxed),
+            exhausted: self.code_counters.exhausted.load(Ordering::Relaxed),
+        }
     }
 }
 
-#[async_trait]
-impl<T: ShortenedUrlRepositoryTrait + Send + Sync> ShortenedUrlServiceTrait
-    for ShortenedUrlService<T>
-{
-    async fn create(&self, dto: CreateShortenedUrlDto) -> Result<ShortenedUrlResponseDto> {
-        dto.validate()?;
+impl<T: ShortenedUrlRepositoryTrait + Send + Sync> ShortenedUrlService<T> {
+    // Record the milestones a link passed when its count went from `previous` to `current`.
+    // Only one caller can record a milestone, the owner is told by the notification job
+    async fn reach_milestones(&self, url_id: &Uuid, previous: i64, current: i64) {
+        let crossed = self.milestones.crossed(previous, current);
+        if crossed.is_empty() {
+            return;
+        }
 
-        // Generate or use custom short code
-        let (short_code, is_custom_code) = match dto.custom_alias {
-            Some(code) if !code.trim().is_empty() => {
-                // Check if custom code is already in use
-                if (self.repository.find_by_code(&code).await?).is_some() {
+        match self.repository.claim_milestones(url_id, &crossed).await {
+            Ok(claimed) => {
+                for milestone in claimed {
+                    info!("Link {} reached {} clicks", url_id, milestone);
+                }
+            }
+            Err(e) => warn!(
+                "Failed to record click milestones {:?} of {}: {}",
+                crossed, url_id, e
+            ),
+        }
+    }
+
+    // Look up a code within a tenant's namespace, ignoring case when codes are case-insensitive
+    async fn find_code(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> std::result::Result<Option<ShortenedUrl>, RepositoryError> {
+        if self.codes.case_insensitive {
+            self.repository
+                .find_by_code_ignore_case(tenant, namespace, code)
+                .await
+        } else {
+            self.repository.find_by_code(tenant, namespace, code).await
+        }
+    }
+
+    // Draw a random code. Case-insensitive instances skip codes that match an older one in
+    // another case; anything else taken is caught when saving
+    async fn generate_code(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+    ) -> Result<String> {
+        let alphabet = self.codes.generator_chars();
+        for _ in 0..MAX_CODE_ATTEMPTS {
+            let code = id_generator::generate_short_id(self.codes.length, &alphabet);
+            if !self.codes.case_insensitive
+                || self.find_code(tenant, namespace, &code).await?.is_none()
+            {
+                return Ok(code);
+            }
+            self.code_counters.retries.fetch_add(1, Ordering::Relaxed);
+        }
+
+        Err(self.codes_exhausted())
+    }
+
+    // The IDs a bulk change applies to, either as given or as matched by a filter within the
+    // tenant. They're sorted so concurrent bulk changes lock rows in the same order and can't
+    // deadlock
+    async fn bulk_selection(
+        &self,
+        ids: Option<Vec<Uuid>>,
+        filter: Option<ShortenedUrlQueryParams>,
+        tenant: Option<Uuid>,
+    ) -> Result<Vec<Uuid>> {
+        let mut ids = match (ids, filter) {
+            (Some(ids), _) => ids,
+            (None, Some(filter)) => {
+                // Read one past the cap to tell a selection that fits from one that doesn't
+                let params = ShortenedUrlQueryParams {
+                    limit: Some(MAX_BULK_URLS as i64 + 1),
+                    offset: None,
+                    tenant: TenantScope::Only(tenant),
+                    ..filter
+                };
+                let urls = self.repository.find(&params).await?;
+                if urls.len() as u64 > MAX_BULK_URLS {
                     return Err(AppError::Validation(format!(
-                        "Custom short code '{}' is already in use",
-                        code
+                        "The filter matches more than {} URLs, the most a bulk change may \
+                         apply to; narrow it down or change them in several requests",
+                        MAX_BULK_URLS
                     )));
                 }
-                (code, true)
+                urls.into_iter().map(|url| url.id).collect()
             }
-            _ => {
-                // Generate a unique short code
-                let mut code = id_generator::generate_short_id(6);
+            (None, None) => Vec::new(),
+        };
 
-                // Ensure the generated code is unique
-                let mut attempts = 0;
-                while (self.repository.find_by_code(&code).await?).is_some() {
-                    code = id_generator::generate_short_id(6);
-                    attempts += 1;
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
 
-                    if attempts >= 5 {
-                        return Err(AppError::Internal(
-                            "Failed to generate a unique short code after multiple attempts"
-                                .to_string(),
-                        ));
-                    }
-                }
+    // Create a link, or find the one a hashed code leads to, and whether it was created
+    async fn create_link(
+        &self,
+        dto: CreateShortenedUrlDto,
+        created_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<(ShortenedUrl, bool)> {
+        let (shortened_url, request) = self.prepare(dto, created_by, tenant).await?;
+        let (record, created) = self.insert(shortened_url, request, false).await?;
+        if !created {
+            return Ok((record, false));
+        }
+        if !record.is_custom_code {
+            self.code_counters.generated.fetch_add(1, Ordering::Relaxed);
+        }
+        self.cache.remove_missing(
+            record.tenant_id.as_ref(),
+            record.namespace.as_deref(),
+            &record.short_code,
+        );
+        self.events.publish(DomainEvent::url_created(&record));
+        Ok((record, true))
+    }
 
-                (code, false)
+    // Create a link and answer with it, along with a claim token when made without signing in
+    async fn create_answer(
+        &self,
+        dto: CreateShortenedUrlDto,
+        created_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrlResponseDto> {
+        let (record, created) = self.create_link(dto, created_by, tenant).await?;
+        let claim = if created && created_by.is_none() && self.claim_ttl_days > 0 {
+            self.issue_claim(&record.id).await
+        } else {
+            None
+        };
+        let mut answer = ShortenedUrlResponseDto::from(record);
+        if let Some((token, expires_at)) = claim {
+            answer.claim_token = Some(token);
+            answer.claim_expires_at = Some(expires_at);
+        }
+        Ok(answer)
+    }
+
+    // Store the hash of a fresh claim token for a link; the link is created either way, so
+    // failing to store it only costs its creator the claim
+    async fn issue_claim(&self, url_id: &Uuid) -> Option<(String, DateTime<Utc>)> {
+        let token = id_generator::generate_claim_token();
+        let expires_at = Utc::now() + Duration::days(self.claim_ttl_days as i64);
+        match self
+            .repository
+            .create_claim(url_id, &sha256_hex(&token), expires_at)
+            .await
+        {
+            Ok(()) => Some((token, expires_at)),
+            Err(e) => {
+                warn!("Failed to issue a claim token for URL {}: {}", url_id, e);
+                None
             }
+        }
+    }
+
+    // Check a new link and settle its code, expiry and settings, short of saving it
+    async fn prepare(
+        &self,
+        dto: CreateShortenedUrlDto,
+        created_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<(NewShortenedUrl, CodeRequest)> {
+        dto.validate()?;
+
+        let mut request = CodeRequest {
+            strategy: dto.code_strategy.unwrap_or(self.codes.strategy),
+            on_conflict: dto.on_conflict.unwrap_or_default(),
+            alias: None,
+            suffix: 1,
         };
+        let namespace = dto
+            .namespace
+            .as_deref()
+            .map(|namespace| self.codes.canonicalize(namespace));
 
-        // Create a new URL entity with basic info
-        let mut shortened_url = ShortenedUrl {
-            short_code,
-            is_custom_code,
-            original_url: dto.original_url,
-            ..Default::default()
+        // Generate or use custom short code
+        let (short_code, is_custom_code) = match dto.custom_alias {
+            Some(code) if !code.trim().is_empty() => {
+                let alias = self.codes.canonicalize(&code);
+                self.check_alias(&alias, created_by.is_some())?;
+
+                // Codes stored before case-insensitive lookups were enabled may differ only
+                // by case, which the unique index doesn't see
+                let mut code = alias.clone();
+                while self.codes.case_insensitive
+                    && (self
+                        .find_code(tenant.as_ref(), namespace.as_deref(), &code)
+                        .await?)
+                        .is_some()
+                {
+                    code =
+                        self.next_alias(&mut request, &alias, namespace.as_deref(), created_by)?;
+                }
+                request.alias = Some(alias);
+                (code, true)
+            }
+            _ if request.strategy == CodeStrategy::Hash => {
+                let code = self.hash_code(
+                    tenant.as_ref(),
+                    namespace.as_deref(),
+                    &dto.original_url,
+                    self.codes.length,
+                );
+                (code, false)
+            }
+            _ => (
+                self.generate_code(tenant.as_ref(), namespace.as_deref())
+                    .await?,
+                false,
+            ),
         };
 
         // Handle expiration logic (prioritize expires_at over expires_in_days)
-        if let Some(expires_at) = dto.expires_at {
+        let expires_at = if let Some(expires_at) = dto.expires_at {
             // Validate that expiration is in the future
             if expires_at <= Utc::now() {
                 return Err(AppError::Validation(
                     "Expiration date must be in the future".to_string(),
                 ));
             }
-            shortened_url.expires_at = Some(expires_at);
+            Some(expires_at)
         } else if let Some(days) = dto.expires_in_days {
             if days == 0 {
                 return Err(AppError::Validation(
@@ -104,59 +550,2246 @@ impl<T: ShortenedUrlRepositoryTrait + Send + Sync> ShortenedUrlServiceTrait
             }
 
             // Calculate expiration date based on days
-            shortened_url.expires_at = Some(Utc::now() + Duration::days(days as i64));
+            Some(Utc::now() + Duration::days(days as i64))
+        } else {
+            None
+        };
+
+        // The ID, creation time and counters are left to the database
+        let base = NewShortenedUrl::new(dto.original_url, short_code)
+            .with_namespace(namespace)
+            .with_tenant(tenant)
+            .with_creator(created_by)
+            .with_custom_code(is_custom_code)
+            .with_expiry(expires_at)
+            .with_metadata(dto.metadata);
+        let shortened_url = NewShortenedUrl {
+            allowed_cidrs: dto
+                .allowed_cidrs
+                .as_deref()
+                .map(parse_cidrs)
+                .unwrap_or_default(),
+            denied_cidrs: dto
+                .denied_cidrs
+                .as_deref()
+                .map(parse_cidrs)
+                .unwrap_or_default(),
+            single_use: dto.single_use.unwrap_or(false),
+            forward_path: dto.forward_path.unwrap_or(false),
+            forward_query: dto.forward_query.unwrap_or(false),
+            fallback_url: dto.fallback_url,
+            max_idle_days: dto.max_idle_days,
+            upgrade_https: dto.upgrade_https,
+            behavior: dto.behavior.unwrap_or_default(),
+            app_url: dto.app_url,
+            og_title: dto.og_title,
+            og_description: dto.og_description,
+            og_image: dto.og_image,
+            max_redirects_per_minute: dto.max_redirects_per_minute,
+            notes: dto.notes,
+            ..base
+        };
+        Ok((shortened_url, request))
+    }
+
+    // Refuse an alias breaking the instance's rules. Signed-in callers may get different
+    // length limits than anonymous ones
+    fn check_alias(&self, code: &str, signed_in: bool) -> Result<()> {
+        let limits = match signed_in {
+            true => self.codes.user_alias_length,
+            false => self.codes.alias_length,
+        };
+        if let Err(e) = validate_alias_length(code, limits.min, limits.max) {
+            let reason = e.message.map(|m| m.to_string()).unwrap_or_default();
+            return Err(custom_alias_error(reason));
+        }
+
+        if !code.is_ascii() && !self.codes.emoji_aliases {
+            return Err(custom_alias_error(
+                "Emoji aliases are not enabled on this instance".to_string(),
+            ));
+        }
+
+        if self.codes.strict_aliases && !self.codes.fits_alphabet(code) {
+            return Err(custom_alias_error(format!(
+                "Custom alias may only use the characters '{}'",
+                self.codes.generator_chars()
+            )));
+        }
+        Ok(())
+    }
+
+    // The alias to try once the current one turned out taken: the next numbered one when the
+    // request asked for it, otherwise the alias is refused
+    fn next_alias(
+        &self,
+        request: &mut CodeRequest,
+        alias: &str,
+        namespace: Option<&str>,
+        created_by: Option<Uuid>,
+    ) -> Result<String> {
+        let taken = match request.suffix {
+            1 => alias.to_string(),
+            suffix => format!("{}-{}", alias, suffix),
+        };
+        if request.on_conflict != AliasConflict::Suffix {
+            return Err(alias_in_use(namespace, &taken));
+        }
+        if request.suffix >= MAX_ALIAS_SUFFIX {
+            return Err(custom_alias_error(format!(
+                "Custom alias '{}' and its numbered variants up to '{}' are in use",
+                alias, taken
+            )));
         }
 
-        // Set optional metadata if provided
-        shortened_url.metadata = dto.metadata;
+        request.suffix += 1;
+        let code = format!("{}-{}", alias, request.suffix);
+        self.check_alias(&code, created_by.is_some())?;
+        Ok(code)
+    }
+
+    // The code a URL hashes to within a tenant's namespace, in the generator's alphabet
+    fn hash_code(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        original_url: &str,
+        length: usize,
+    ) -> String {
+        let tenant = tenant.map(Uuid::to_string).unwrap_or_default();
+        let input = format!(
+            "{}\n{}\n{}",
+            tenant,
+            namespace.unwrap_or_default(),
+            normalize_url(original_url)
+        );
+        id_generator::hash_short_id(&input, length, &self.codes.generator_chars())
+    }
 
-        // Save to repository
-        let record = self.repository.save(&shortened_url).await?;
-        let response_dto = ShortenedUrlResponseDto::from(record);
+    // Save a prepared link, drawing another generated code while the one drawn is taken.
+    // A hashed code taken by another URL or owner is extended instead, and one taken by the
+    // same URL and owner gives back that link, reported as not created. A taken alias is
+    // numbered when asked for. A dry run goes as far as inserting the link, but never commits
+    async fn insert(
+        &self,
+        mut shortened_url: NewShortenedUrl,
+        mut request: CodeRequest,
+        dry_run: bool,
+    ) -> Result<(ShortenedUrl, bool)> {
+        // The unique index on tenant, namespace and code decides between concurrent requests
+        // for the same code: a taken alias is refused, a taken generated code drawn again
+        let mut attempts = 0;
+        loop {
+            let saved = if dry_run {
+                // Inserted in a transaction that's dropped without committing, so the
+                // database still has the final say on the code
+                let mut tx = self.repository.begin().await?;
+                self.repository.save_in(&mut tx, &shortened_url).await
+            } else {
+                self.repository.save(&shortened_url).await
+            };
+            match saved {
+                Ok(record) => return Ok((record, true)),
+                Err(RepositoryError::Conflict(_)) if shortened_url.is_custom_code => {
+                    let alias = request.alias.clone().unwrap_or_default();
+                    shortened_url.short_code = self.next_alias(
+                        &mut request,
+                        &alias,
+                        shortened_url.namespace.as_deref(),
+                        shortened_url.created_by,
+                    )?;
+                }
+                Err(RepositoryError::Conflict(_)) if request.strategy == CodeStrategy::Hash => {
+                    let tenant = shortened_url.tenant_id;
+                    let namespace = shortened_url.namespace.as_deref();
+                    let existing = self
+                        .find_code(tenant.as_ref(), namespace, &shortened_url.short_code)
+                        .await?;
+                    // Links no longer working are skipped, like ones of other URLs or other
+                    // owners, whose settings and stats the caller has no business sharing
+                    if let Some(existing) = existing.filter(|existing| {
+                        existing.is_valid()
+                            && existing.created_by == shortened_url.created_by
+                            && normalize_url(&existing.original_url)
+                                == normalize_url(&shortened_url.original_url)
+                    }) {
+                        return Ok((existing, false));
+                    }
+                    if attempts >= MAX_CODE_ATTEMPTS {
+                        return Err(self.codes_exhausted());
+                    }
+                    attempts += 1;
+                    self.code_counters.retries.fetch_add(1, Ordering::Relaxed);
+                    debug!(
+                        "Hashed code '{}' is taken by another link, extending it",
+                        shortened_url.short_code
+                    );
+                    shortened_url.short_code = self.hash_code(
+                        tenant.as_ref(),
+                        namespace,
+                        &shortened_url.original_url,
+                        shortened_url.short_code.chars().count() + 1,
+                    );
+                }
+                Err(RepositoryError::Conflict(_)) if attempts < MAX_CODE_ATTEMPTS => {
+                    attempts += 1;
+                    self.code_counters.retries.fetch_add(1, Ordering::Relaxed);
+                    debug!(
+                        "Generated code '{}' is taken, drawing another",
+                        shortened_url.short_code
+                    );
+                    let tenant = shortened_url.tenant_id;
+                    shortened_url.short_code = self
+                        .generate_code(tenant.as_ref(), shortened_url.namespace.as_deref())
+                        .await?;
+                }
+                Err(RepositoryError::Conflict(_)) => return Err(self.codes_exhausted()),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn codes_exhausted(&self) -> AppError {
+        self.code_counters.exhausted.fetch_add(1, Ordering::Relaxed);
+        warn!(
+            "No free short code found in {} attempts, consider longer codes",
+            MAX_CODE_ATTEMPTS
+        );
+        AppError::Internal(
+            "Failed to generate a unique short code after multiple attempts".to_string(),
+        )
+    }
+
+    // Look up a code to redirect, preferring the cache, which also remembers codes that
+    // don't exist. Concurrent misses for the same code share one query. While the database
+    // is unreachable, links cached earlier keep working even once expired
+    async fn resolve_code(
+        &self,
+        tenant: Option<&Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Result<Option<ShortenedUrl>> {
+        // Codes are stored normalized, however the visitor's device spelled them
+        let code = &*nfc(code);
+        if let Some(url) = self.cache.get(tenant, namespace, code) {
+            return Ok(Some(url));
+        }
+        if self.cache.is_missing(tenant, namespace, code) {
+            return Ok(None);
+        }
+
+        let key = self.cache.key(tenant, namespace, code);
+        let found = self
+            .lookups
+            .run(&key, || async {
+                let url = self
+                    .find_code(tenant, namespace, code)
+                    .await
+                    .map_err(Arc::new)?;
+                match &url {
+                    Some(url) => self.cache.insert(url),
+                    None => self.cache.insert_missing(tenant, namespace, code),
+                }
+                Ok(url)
+            })
+            .await;
+
+        match found {
+            Ok(url) => Ok(url),
+            Err(e) if e.is_outage() => match self.cache.get_stale(tenant, namespace, code) {
+                Some(url) => {
+                    warn!(
+                        "Database unavailable, redirecting '{}' from cache",
+                        url.path()
+                    );
+                    Ok(Some(url))
+                }
+                None => Err(lookup_error(e)),
+            },
+            Err(e) => Err(lookup_error(e)),
+        }
+    }
+}
+
+#[async_trait]
+impl<T: ShortenedUrlRepositoryTrait + Send + Sync> ShortenedUrlServiceTrait
+    for ShortenedUrlService<T>
+{
+    async fn create(
+        &self,
+        dto: CreateShortenedUrlDto,
+        created_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrlResponseDto> {
+        if !self.recent_creates.is_enabled() {
+            return self.create_answer(dto, created_by, tenant).await;
+        }
+
+        // The same client sending the same request again gets the link created the first time
+        let key = create_key(&dto, created_by, tenant);
+        if let Some(answer) = self.recent_creates.get(&key) {
+            debug!(
+                "Answering a repeated create request with '{}'",
+                answer.short_code
+            );
+            return Ok(answer);
+        }
+
+        let mut failure = None;
+        let created = {
+            let (dto, failure) = (&dto, &mut failure);
+            self.recent_creates
+                .in_flight()
+                .run(&key, || async move {
+                    match self.create_answer(dto.clone(), created_by, tenant).await {
+                        Ok(answer) => Some(answer),
+                        Err(e) => {
+                            *failure = Some(e);
+                            None
+                        }
+                    }
+                })
+                .await
+        };
+        let answer = match (created, failure) {
+            (_, Some(e)) => return Err(e),
+            (Some(answer), None) => answer,
+            // The identical request this one waited on failed, which this one may not
+            (None, None) => self.create_answer(dto, created_by, tenant).await?,
+        };
+        self.recent_creates.insert(key, answer.clone());
+
+        Ok(answer)
+    }
+
+    async fn preview_create(
+        &self,
+        dto: CreateShortenedUrlDto,
+        created_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrlResponseDto> {
+        let (shortened_url, request) = self.prepare(dto, created_by, tenant).await?;
+        let (record, _) = self.insert(shortened_url, request, true).await?;
+        Ok(ShortenedUrlResponseDto::from(record))
+    }
 
-        Ok(response_dto)
+    async fn resolve_id(&self, id: Uuid) -> Result<Uuid> {
+        // Only random ids were remapped, time-ordered ones are current
+        if id.get_version_num() != 4 {
+            return Ok(id);
+        }
+        Ok(self.repository.find_remapped_id(&id).await?.unwrap_or(id))
     }
 
-    async fn get_by_id(&self, id: &Uuid) -> Result<ShortenedUrl> {
+    async fn get_by_id(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<ShortenedUrl> {
         match self.repository.find_by_id(id).await? {
-            Some(url) => Ok(url),
-            None => Err(AppError::NotFound(format!(
+            Some(url) if url.tenant_id == tenant => Ok(url),
+            _ => Err(AppError::NotFound(format!(
                 "URL with ID '{}' not found",
                 id
             ))),
         }
     }
 
-    async fn get_by_code(&self, code: &str) -> Result<ShortenedUrl> {
-        match self.repository.find_by_code(code).await? {
+    async fn get_details(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<ShortenedUrlDetailsDto> {
+        let url = self.get_by_id(id, tenant).await?;
+        let health = link_health(&url, Utc::now());
+        Ok(ShortenedUrlDetailsDto {
+            url: url.into(),
+            health,
+        })
+    }
+
+    async fn get_by_code(
+        &self,
+        tenant: Option<Uuid>,
+        namespace: Option<&str>,
+        code: &str,
+    ) -> Result<ShortenedUrl> {
+        match self.resolve_code(tenant.as_ref(), namespace, code).await? {
             Some(url) => Ok(url),
             None => Err(AppError::NotFound(format!(
                 "URL with code '{}' not found",
-                code
+                namespace.map_or_else(|| code.to_string(), |ns| format!("{}/{}", ns, code))
+            ))),
+        }
+    }
+
+    async fn get_by_path(
+        &self,
+        tenant: Option<Uuid>,
+        first: &str,
+        rest: &str,
+    ) -> Result<(ShortenedUrl, String)> {
+        // `/a/b...` is either code `b` in namespace `a`, or code `a` forwarding `b...`
+        let (code, tail) = rest.split_once('/').unwrap_or((rest, ""));
+        if !code.is_empty() {
+            if let Some(url) = self
+                .resolve_code(tenant.as_ref(), Some(first), code)
+                .await?
+            {
+                if tail.is_empty() || url.forward_path {
+                    return Ok((url, tail.to_string()));
+                }
+            }
+        }
+
+        match self.resolve_code(tenant.as_ref(), None, first).await? {
+            Some(url) if rest.is_empty() || url.forward_path => Ok((url, rest.to_string())),
+            _ => Err(AppError::NotFound(format!(
+                "URL with code '{}/{}' not found",
+                first, rest
             ))),
         }
     }
 
-    async fn get_by_query(&self, params: &ShortenedUrlQueryParams) -> Result<Vec<ShortenedUrl>> {
-        print!("params: {:?}", params);
-        let urls = self.repository.find(params).await?;
-        Ok(urls)
+    async fn get_by_query(&self, params: &ShortenedUrlQueryParams) -> Result<Page<ShortenedUrl>> {
+        let limit = self.pages.page_size(params.limit);
+        let params = ShortenedUrlQueryParams {
+            limit: Some(limit),
+            ..params.clone()
+        };
+        let urls = self.repository.find(&params).await?;
+        Ok(Page { items: urls, limit })
+    }
+
+    async fn get_all(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        tenant: Option<Uuid>,
+    ) -> Result<Page<ShortenedUrl>> {
+        let limit = self.pages.page_size(limit);
+        let urls = self
+            .repository
+            .find_all(tenant.as_ref(), Some(limit), offset)
+            .await?;
+        Ok(Page { items: urls, limit })
     }
 
-    async fn get_all(&self, limit: Option<i64>, offset: Option<i64>) -> Result<Vec<ShortenedUrl>> {
-        let urls = self.repository.find_all(limit, offset).await?;
-        Ok(urls)
+    async fn count(&self, params: &ShortenedUrlQueryParams) -> Result<i64> {
+        let count = self.repository.count(params).await?;
+        Ok(count)
     }
 
-    async fn update(&self, id: &Uuid, dto: ShortenedUrlUpdateParams) -> Result<u64> {
+    fn export(&self, params: ShortenedUrlQueryParams) -> BoxStream<'static, Result<ShortenedUrl>> {
+        self.repository
+            .stream(params)
+            .map(|row| row.map_err(AppError::from))
+            .boxed()
+    }
+
+    async fn resolve_codes(
+        &self,
+        dto: ResolveCodesDto,
+        tenant: Option<Uuid>,
+    ) -> Result<BTreeMap<String, Option<ResolvedCode>>> {
         dto.validate()?;
 
-        let rows = self.repository.update(id, &dto).await?;
+        // Every requested code gets an entry, null when it doesn't exist
+        let mut resolved = dto
+            .codes
+            .into_iter()
+            .map(|code| (code, None))
+            .collect::<BTreeMap<_, _>>();
+
+        let ignore_case = self.codes.case_insensitive;
+        // Codes may be namespaced, as in `docs/setup`
+        let paths = resolved
+            .keys()
+            .map(|code| self.codes.canonicalize(code))
+            .collect::<Vec<_>>();
+        let found = self
+            .repository
+            .find_by_codes(tenant.as_ref(), &paths, ignore_case)
+            .await?;
+
+        for (code, entry) in resolved.iter_mut() {
+            // Prefer the exact spelling when several differ only by case
+            *entry = found
+                .iter()
+                .find(|url| url.path() == *code)
+                .or_else(|| {
+                    found
+                        .iter()
+                        .find(|url| ignore_case && url.path().eq_ignore_ascii_case(code))
+                })
+                .map(ResolvedCode::from);
+        }
+
+        Ok(resolved)
+    }
+
+    async fn update(
+        &self,
+        id: &Uuid,
+        dto: ShortenedUrlUpdateParams,
+        changed_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<u64> {
+        validate_update(&dto)?;
+
+        // Links never move between tenants, so checking first is enough
+        let url = self.get_by_id(id, tenant).await?;
+        check_owner(&url, changed_by.as_ref())?;
+
+        let rows = self
+            .repository
+            .update(id, &dto, changed_by.as_ref())
+            .await?;
+        self.cache.invalidate(id);
+        if rows == 0 {
+            return Err(AppError::NotFound(format!(
+                "URL with ID '{}' not found",
+                id
+            )));
+        }
         Ok(rows)
     }
 
-    async fn delete(&self, id: &Uuid) -> Result<bool> {
+    async fn preview_update(
+        &self,
+        id: &Uuid,
+        dto: ShortenedUrlUpdateParams,
+        user_id: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrlDetailsDto> {
+        validate_update(&dto)?;
+
+        // The change is made in a transaction that's dropped without committing, so the
+        // version check and the database constraints apply as they would for real
+        let mut tx = self.repository.begin().await?;
+        let Some(found) = self
+            .repository
+            .lock_by_id_in(&mut tx, id)
+            .await?
+            .filter(|url| url.tenant_id == tenant)
+        else {
+            return Err(AppError::NotFound(format!(
+                "URL with ID '{}' not found",
+                id
+            )));
+        };
+        check_owner(&found, user_id.as_ref())?;
+
+        self.repository.update_in(&mut tx, id, &dto, None).await?;
+        let Some(url) = self.repository.lock_by_id_in(&mut tx, id).await? else {
+            return Err(AppError::NotFound(format!(
+                "URL with ID '{}' not found",
+                id
+            )));
+        };
+        let health = link_health(&url, Utc::now());
+        Ok(ShortenedUrlDetailsDto {
+            url: url.into(),
+            health,
+        })
+    }
+
+    async fn get_history(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<Vec<UrlRevision>> {
+        // Tell a missing URL apart from one that never changed
+        self.get_by_id(id, tenant).await?;
+
+        let revisions = self.repository.find_revisions(id).await?;
+        Ok(revisions)
+    }
+
+    async fn duplicate(
+        &self,
+        id: &Uuid,
+        dto: DuplicateShortenedUrlDto,
+        created_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrlResponseDto> {
+        dto.validate()?;
+
+        let source = self.get_by_id(id, tenant).await?;
+        check_owner(&source, created_by.as_ref())?;
+        // A copy would start out active, getting around moderation of the original
+        if source.status != LinkStatus::Active {
+            return Err(AppError::Forbidden(format!(
+                "URL with code '{}' can't be duplicated until it passes moderation",
+                source.path()
+            )));
+        }
+
+        // An expiry already past is dropped rather than refused, as the copy is usually
+        // meant to outlive the original
+        let expires_at = match (dto.expires_at, dto.expires_in_days) {
+            (None, None) => source.expires_at.filter(|expiry| *expiry > Utc::now()),
+            (expires_at, _) => expires_at,
+        };
+        let cidrs = |cidrs: &[IpNet]| Some(cidrs.iter().map(ToString::to_string).collect());
+        let copy = CreateShortenedUrlDto {
+            custom_alias: dto.custom_alias,
+            // A hashed code would lead back to the original
+            code_strategy: Some(CodeStrategy::Random),
+            on_conflict: None,
+            namespace: source.namespace,
+            expires_at,
+            expires_in_days: dto.expires_in_days,
+            metadata: source.metadata,
+            allowed_cidrs: cidrs(&source.allowed_cidrs),
+            denied_cidrs: cidrs(&source.denied_cidrs),
+            single_use: Some(source.single_use),
+            forward_path: Some(source.forward_path),
+            forward_query: Some(source.forward_query),
+            fallback_url: source.fallback_url,
+            max_idle_days: source.max_idle_days,
+            upgrade_https: source.upgrade_https,
+            behavior: Some(source.behavior),
+            app_url: source.app_url,
+            og_title: source.og_title,
+            og_description: source.og_description,
+            og_image: source.og_image,
+            max_redirects_per_minute: source.max_redirects_per_minute,
+            notes: source.notes,
+            original_url: source.original_url,
+            client_ip: None,
+        };
+
+        let (record, _) = self.create_link(copy, created_by, tenant).await?;
+        Ok(ShortenedUrlResponseDto::from(record))
+    }
+
+    async fn revert(
+        &self,
+        id: &Uuid,
+        revision: i32,
+        changed_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl> {
+        // Read and change the URL in one transaction, so a concurrent edit can't
+        // slip in between and fail the revert with a conflict
+        let mut tx = self.repository.begin().await?;
+
+        let Some(url) = self
+            .repository
+            .lock_by_id_in(&mut tx, id)
+            .await?
+            .filter(|url| url.tenant_id == tenant)
+        else {
+            return Err(AppError::NotFound(format!(
+                "URL with ID '{}' not found",
+                id
+            )));
+        };
+        check_owner(&url, changed_by.as_ref())?;
+        let Some(target) = self.repository.find_revision(id, revision).await? else {
+            return Err(AppError::NotFound(format!(
+                "Revision {} of URL with ID '{}' not found",
+                revision, id
+            )));
+        };
+
+        // Reverting is a change like any other, so it can be undone the same way
+        let params = ShortenedUrlUpdateParams {
+            original_url: Some(target.original_url),
+            change_comment: Some(format!("Reverted to revision {}", revision)),
+            version: Some(url.version),
+            ..Default::default()
+        };
+        self.repository
+            .update_in(&mut tx, id, &params, changed_by.as_ref())
+            .await?;
+        let reverted = self.repository.lock_by_id_in(&mut tx, id).await?;
+        commit(tx).await?;
+        self.cache.invalidate(id);
+
+        reverted.ok_or_else(|| AppError::NotFound(format!("URL with ID '{}' not found", id)))
+    }
+
+    async fn delete(&self, id: &Uuid, user_id: Option<Uuid>, tenant: Option<Uuid>) -> Result<bool> {
+        // A URL of another tenant is left alone, as if it didn't exist
+        match self.repository.find_by_id(id).await? {
+            Some(url) if url.tenant_id == tenant => check_owner(&url, user_id.as_ref())?,
+            _ => return Ok(false),
+        }
+
         let is_rows_deleted = self.repository.delete(id, false).await?;
+        self.cache.invalidate(id);
         Ok(is_rows_deleted)
     }
+
+    async fn get_expiring(
+        &self,
+        params: &ExpiringQueryParams,
+        tenant: Option<Uuid>,
+    ) -> Result<Page<ShortenedUrl>> {
+        params.validate()?;
+        let limit = self.pages.page_size(params.limit);
+        let within_days = params.within_days.unwrap_or(DEFAULT_EXPIRING_WITHIN_DAYS);
+        let urls = self
+            .repository
+            .find_expiring(
+                tenant.as_ref(),
+                within_days as i32,
+                limit,
+                params.offset.unwrap_or(0),
+            )
+            .await?;
+        Ok(Page { items: urls, limit })
+    }
+
+    async fn get_trash(
+        &self,
+        limit: Option<i64>,
+        offset: Option<i64>,
+        tenant: Option<Uuid>,
+    ) -> Result<Page<TrashedUrl>> {
+        let limit = self.pages.page_size(limit);
+        let urls = self
+            .repository
+            .find_trash(tenant.as_ref(), limit, offset.unwrap_or(0))
+            .await?;
+        Ok(Page { items: urls, limit })
+    }
+
+    async fn restore(
+        &self,
+        id: &Uuid,
+        user_id: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl> {
+        let not_found =
+            || AppError::NotFound(format!("URL with ID '{}' not found in the trash", id));
+        let trashed = self
+            .repository
+            .find_in_trash(id, tenant.as_ref())
+            .await?
+            .ok_or_else(not_found)?;
+        check_owner(&trashed, user_id.as_ref())?;
+        let url = self
+            .repository
+            .restore(id, tenant.as_ref())
+            .await?
+            .ok_or_else(not_found)?;
+
+        // Visitors may have been told the code doesn't exist while it was in the trash
+        self.cache.remove_missing(
+            url.tenant_id.as_ref(),
+            url.namespace.as_deref(),
+            &url.short_code,
+        );
+        info!("URL with code '{}' restored from the trash", url.path());
+        Ok(url)
+    }
+
+    async fn add_favorite(
+        &self,
+        id: &Uuid,
+        user_id: &Uuid,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl> {
+        let url = self.get_by_id(id, tenant).await?;
+        self.repository.add_favorite(user_id, id).await?;
+        Ok(url)
+    }
+
+    async fn remove_favorite(&self, id: &Uuid, user_id: &Uuid) -> Result<bool> {
+        // Favorites only ever point to the user's own pins, so any tenant's can go
+        Ok(self.repository.remove_favorite(user_id, id).await?)
+    }
+
+    async fn claim(
+        &self,
+        dto: ClaimLinkDto,
+        user_id: &Uuid,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl> {
+        dto.validate()?;
+        let Some(url) = self
+            .repository
+            .claim(&sha256_hex(&dto.token), user_id, tenant.as_ref())
+            .await?
+        else {
+            return Err(AppError::NotFound(
+                "Claim token is invalid or expired".to_string(),
+            ));
+        };
+
+        self.cache.invalidate(&url.id);
+        info!("URL with code '{}' claimed by user {}", url.path(), user_id);
+        Ok(url)
+    }
+
+    async fn offer_transfer(
+        &self,
+        id: &Uuid,
+        dto: TransferLinkDto,
+        user_id: &Uuid,
+        tenant: Option<Uuid>,
+    ) -> Result<LinkTransfer> {
+        dto.validate()?;
+        if dto.to_tenant.is_some() {
+            return Err(AppError::Forbidden(
+                "Moving a URL to another tenant requires the admin token".to_string(),
+            ));
+        }
+        let Some(to_user_id) = dto.to_user_id else {
+            return Err(AppError::Validation(
+                "The user to transfer the URL to is required, as 'to_user_id'".to_string(),
+            ));
+        };
+
+        let url = self.get_by_id(id, tenant).await?;
+        if url.created_by != Some(*user_id) {
+            return Err(AppError::Forbidden(
+                "Only the owner of a URL can transfer it".to_string(),
+            ));
+        }
+        if to_user_id == *user_id {
+            return Err(AppError::Validation(
+                "The URL already belongs to this user".to_string(),
+            ));
+        }
+
+        let transfer = self
+            .repository
+            .offer_transfer(id, user_id, &to_user_id)
+            .await
+            .map_err(|e| match e {
+                RepositoryError::InvalidData(_) => {
+                    AppError::NotFound(format!("User with ID '{}' not found", to_user_id))
+                }
+                e => e.into(),
+            })?
+            .ok_or_else(|| {
+                AppError::Conflict(format!(
+                    "URL with ID '{}' already has a pending transfer",
+                    id
+                ))
+            })?;
+        info!(
+            "Transfer of URL with code '{}' offered to user {}",
+            url.path(),
+            to_user_id
+        );
+        Ok(transfer)
+    }
+
+    async fn pending_transfers(&self, user_id: &Uuid) -> Result<Vec<LinkTransfer>> {
+        Ok(self.repository.find_pending_transfers(user_id).await?)
+    }
+
+    async fn get_transfers(&self, id: &Uuid, tenant: Option<Uuid>) -> Result<Vec<LinkTransfer>> {
+        self.get_by_id(id, tenant).await?;
+        Ok(self.repository.find_transfers(id).await?)
+    }
+
+    async fn accept_transfer(&self, id: &Uuid, user_id: &Uuid) -> Result<ShortenedUrl> {
+        if let Some(url) = self.repository.accept_transfer(id, user_id).await? {
+            self.cache.invalidate(&url.id);
+            info!(
+                "URL with code '{}' transferred to user {}",
+                url.path(),
+                user_id
+            );
+            return Ok(url);
+        }
+
+        // Tell the recipient why, other users don't learn the transfer exists
+        match self.repository.find_transfer(id).await? {
+            Some(transfer) if transfer.to_user_id == Some(*user_id) => {
+                if transfer.status == TransferStatus::Pending {
+                    // The link went to the trash or to someone else since, the offer is void
+                    self.repository
+                        .close_transfer(id, TransferStatus::Cancelled)
+                        .await?;
+                    return Err(AppError::Conflict(
+                        "The URL changed owner or was deleted since the transfer was offered"
+                            .to_string(),
+                    ));
+                }
+                Err(AppError::Conflict(format!(
+                    "Transfer with ID '{}' is no longer pending",
+                    id
+                )))
+            }
+            _ => Err(AppError::NotFound(format!(
+                "Transfer with ID '{}' not found",
+                id
+            ))),
+        }
+    }
+
+    async fn close_transfer(&self, id: &Uuid, user_id: &Uuid) -> Result<LinkTransfer> {
+        let status = match self.repository.find_transfer(id).await? {
+            Some(transfer) if transfer.to_user_id == Some(*user_id) => TransferStatus::Declined,
+            Some(transfer) if transfer.from_user_id == Some(*user_id) => TransferStatus::Cancelled,
+            _ => {
+                return Err(AppError::NotFound(format!(
+                    "Transfer with ID '{}' not found",
+                    id
+                )))
+            }
+        };
+
+        self.repository
+            .close_transfer(id, status)
+            .await?
+            .ok_or_else(|| {
+                AppError::Conflict(format!("Transfer with ID '{}' is no longer pending", id))
+            })
+    }
+
+    async fn force_transfer(
+        &self,
+        id: &Uuid,
+        to_user_id: Option<Uuid>,
+        to_tenant: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl> {
+        if to_user_id.is_none() && to_tenant.is_none() {
+            return Err(AppError::Validation(
+                "Either 'to_user_id' or 'to_tenant' is required".to_string(),
+            ));
+        }
+
+        let url = self.get_by_id(id, tenant).await?;
+        let moved = self
+            .repository
+            .force_transfer(id, to_user_id.as_ref(), to_tenant.as_ref())
+            .await
+            .map_err(|e| match e {
+                RepositoryError::Conflict(_) => AppError::Conflict(format!(
+                    "Code '{}' is already taken in the target tenant",
+                    url.path()
+                )),
+                RepositoryError::InvalidData(_) => AppError::NotFound(format!(
+                    "User with ID '{}' not found",
+                    to_user_id.unwrap_or_default()
+                )),
+                e => e.into(),
+            })?
+            .ok_or_else(|| AppError::NotFound(format!("URL with ID '{}' not found", id)))?;
+
+        // Visitors of the target tenant may have been told the code doesn't exist
+        self.cache.invalidate(id);
+        self.cache.remove_missing(
+            moved.tenant_id.as_ref(),
+            moved.namespace.as_deref(),
+            &moved.short_code,
+        );
+        info!("URL with code '{}' transferred by an admin", moved.path());
+        Ok(moved)
+    }
+
+    async fn set_archived(
+        &self,
+        id: &Uuid,
+        archived: bool,
+        user_id: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<ShortenedUrl> {
+        let url = self.get_by_id(id, tenant).await?;
+        check_owner(&url, user_id.as_ref())?;
+        let url = self
+            .repository
+            .set_archived(id, archived)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("URL with ID '{}' not found", id)))?;
+        self.cache.invalidate(id);
+        info!(
+            "URL with code '{}' {} the archive",
+            url.path(),
+            if archived { "moved to" } else { "taken out of" }
+        );
+        Ok(url)
+    }
+
+    async fn trash_stats(&self, retention_days: u32) -> Result<TrashStats> {
+        let cutoff = Utc::now() - Duration::days(retention_days.into());
+        let stats = self.repository.count_trash(cutoff).await?;
+        Ok(stats)
+    }
+
+    async fn bulk_update(
+        &self,
+        dto: BulkUpdateUrlsDto,
+        changed_by: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<Vec<BulkItemResult>> {
+        dto.validate()?;
+
+        let ids = self.bulk_selection(dto.ids, dto.filter, tenant).await?;
+        let mut tx = self.repository.begin().await?;
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            let Some(url) = self
+                .repository
+                .lock_by_id_in(&mut tx, &id)
+                .await?
+                .filter(|url| url.tenant_id == tenant)
+            else {
+                results.push(BulkItemResult::failed(
+                    id,
+                    format!("URL with ID '{}' not found", id),
+                ));
+                continue;
+            };
+            if let Err(e) = check_owner(&url, changed_by.as_ref()) {
+                results.push(BulkItemResult::failed(id, e.to_string()));
+                continue;
+            }
+
+            let metadata = match &dto.add_tag {
+                Some(tag) => match with_tag(url.metadata, tag) {
+                    Ok(metadata) => Some(metadata),
+                    Err(reason) => {
+                        results.push(BulkItemResult::failed(id, reason));
+                        continue;
+                    }
+                },
+                None => None,
+            };
+            let params = ShortenedUrlUpdateParams {
+                is_active: dto.is_active,
+                expires_at: dto.expires_at,
+                metadata,
+                ..Default::default()
+            };
+            self.repository
+                .update_in(&mut tx, &id, &params, changed_by.as_ref())
+                .await?;
+            results.push(BulkItemResult::done(id));
+        }
+        commit(tx).await?;
+
+        for result in results.iter().filter(|result| result.success) {
+            self.cache.invalidate(&result.id);
+        }
+        Ok(results)
+    }
+
+    async fn bulk_delete(
+        &self,
+        dto: BulkDeleteUrlsDto,
+        user_id: Option<Uuid>,
+        tenant: Option<Uuid>,
+    ) -> Result<Vec<BulkItemResult>> {
+        dto.validate()?;
+
+        let ids = self.bulk_selection(dto.ids, dto.filter, tenant).await?;
+        let mut tx = self.repository.begin().await?;
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            // A URL of another tenant is reported like one that doesn't exist
+            let found = self
+                .repository
+                .lock_by_id_in(&mut tx, &id)
+                .await?
+                .filter(|url| url.tenant_id == tenant);
+            if let Some(Err(e)) = found.as_ref().map(|url| check_owner(url, user_id.as_ref())) {
+                results.push(BulkItemResult::failed(id, e.to_string()));
+                continue;
+            }
+            if found.is_some() && self.repository.delete_in(&mut tx, &id).await? {
+                results.push(BulkItemResult::done(id));
+            } else {
+                results.push(BulkItemResult::failed(
+                    id,
+                    format!("URL with ID '{}' not found", id),
+                ));
+            }
+        }
+        commit(tx).await?;
+
+        for result in results.iter().filter(|result| result.success) {
+            self.cache.invalidate(&result.id);
+        }
+        Ok(results)
+    }
+
+    async fn set_status(&self, id: &Uuid, status: LinkStatus) -> Result<ShortenedUrl> {
+        let url = self
+            .repository
+            .update_status(id, status)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("URL with ID '{}' not found", id)))?;
+        self.cache.invalidate(id);
+        info!("URL with code '{}' is now {:?}", url.path(), status);
+        Ok(url)
+    }
+
+    async fn consume(&self, url: &ShortenedUrl) -> Result<()> {
+        if !url.single_use {
+            return Ok(());
+        }
+
+        // The loaded row may be stale, only the conditional update decides who gets the link
+        self.cache.invalidate(&url.id);
+        if url.is_consumed() || !self.repository.consume(&url.id).await? {
+            return Err(AppError::Gone(format!(
+                "URL with code '{}' has already been used",
+                url.path()
+            ))
+            .with_code(ErrorCode::UrlConsumed));
+        }
+
+        Ok(())
+    }
+
+    async fn record_access(&self, url: &ShortenedUrl) -> Result<()> {
+        self.events.publish(DomainEvent::url_clicked(url));
+        match self.repository.record_access(&url.id).await {
+            Ok(count) => {
+                self.reach_milestones(&url.id, count - 1, count).await;
+                Ok(())
+            }
+            // Redirects served from the cache during an outage are counted once it's over
+            Err(e) if e.is_outage() => {
+                debug!("Deferring access count of '{}': {}", url.path(), e);
+                self.backlog.record(url.id, Utc::now());
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn defer_access(&self, url: &ShortenedUrl) {
+        self.events.publish(DomainEvent::url_clicked(url));
+        self.backlog.record(url.id, Utc::now());
+    }
+
+    async fn replay_accesses(&self) -> Result<usize> {
+        let mut pending = self.backlog.drain().into_iter();
+        let mut replayed = 0;
+
+        while let Some(access) = pending.next() {
+            let result = self
+                .repository
+                .add_accesses(&access.url_id, access.count, access.last_accessed)
+                .await;
+
+            match result {
+                Ok(count) => {
+                    self.reach_milestones(&access.url_id, count - access.count, count)
+                        .await;
+                    replayed += 1;
+                }
+                // Still down, keep everything not written yet for the next attempt
+                Err(e) if e.is_outage() => {
+                    self.backlog.restore(std::iter::once(access).chain(pending));
+                    return Err(e.into());
+                }
+                Err(e) => warn!(
+                    "Dropping {} deferred access(es) to {}: {}",
+                    access.count, access.url_id, e
+                ),
+            }
+        }
+
+        Ok(replayed)
+    }
+
+    async fn warm_cache(&self, limit: usize) -> Result<usize> {
+        let urls = self.repository.find_most_accessed(limit as i64).await?;
+        for url in &urls {
+            self.cache.insert(url);
+        }
+        Ok(urls.len())
+    }
+}
+
+// Convert the error of a lookup that other requests waiting on it may still hold
+fn lookup_error(err: Arc<RepositoryError>) -> AppError {
+    match Arc::try_unwrap(err) {
+        Ok(err) => err.into(),
+        Err(err) if err.is_outage() => AppError::Unavailable(err.to_string()),
+        Err(err) => AppError::Internal(err.to_string()),
+    }
+}
+
+// The error for an alias some other link already has
+fn alias_in_use(namespace: Option<&str>, code: &str) -> AppError {
+    let path = match namespace {
+        Some(namespace) => format!("{}/{}", namespace, code),
+        None => code.to_string(),
+    };
+    AppError::Validation(format!("Custom short code '{}' is already in use", path))
+        .with_code(ErrorCode::AliasTaken)
+}
+
+// What tells a create request apart from others: the client sending it and its body
+fn create_key(
+    dto: &CreateShortenedUrlDto,
+    created_by: Option<Uuid>,
+    tenant: Option<Uuid>,
+) -> String {
+    let request =
+        serde_json::to_string(&(tenant, created_by, dto.client_ip, dto)).unwrap_or_default();
+    sha256_hex(&request)
+}
+
+// The form of a URL its hashed code is derived from, so trivially different spellings, like
+// an uppercase host or an explicit default port, share a code
+fn normalize_url(url: &str) -> String {
+    Url::parse(url).map_or_else(|_| url.to_string(), String::from)
+}
+
+// Without a base version, concurrent edits would silently overwrite each other
+fn validate_update(dto: &ShortenedUrlUpdateParams) -> Result<()> {
+    dto.validate()?;
+    if dto.version.is_none() {
+        return Err(AppError::Validation(
+            "The current version of the URL is required, as 'version' or an If-Match header"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
+// A link with an owner is only changed by them, one created without signing in by anyone who
+// can reach it
+fn check_owner(url: &ShortenedUrl, user_id: Option<&Uuid>) -> Result<()> {
+    match url.created_by {
+        Some(owner) if user_id != Some(&owner) => Err(AppError::Forbidden(
+            "Only the owner of a URL can change it".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+// Report a rejected alias under its field, like DTO validation does
+fn custom_alias_error(reason: String) -> AppError {
+    AppError::InvalidFields(FieldErrors::from([(
+        "custom_alias".to_string(),
+        vec![reason],
+    )]))
+}
+
+// Metadata with a tag added to its `tags` array, unless the tag is there already
+fn with_tag(metadata: Option<JsonValue>, tag: &str) -> std::result::Result<JsonValue, String> {
+    let mut metadata = match metadata {
+        None | Some(JsonValue::Null) => JsonValue::Object(Default::default()),
+        Some(metadata) => metadata,
+    };
+    let JsonValue::Object(fields) = &mut metadata else {
+        return Err("Metadata isn't an object, so it can't hold tags".to_string());
+    };
+    let JsonValue::Array(tags) = fields
+        .entry("tags")
+        .or_insert_with(|| JsonValue::Array(vec![]))
+    else {
+        return Err("Metadata 'tags' isn't an array".to_string());
+    };
+    if !tags.iter().any(|existing| existing.as_str() == Some(tag)) {
+        tags.push(JsonValue::from(tag));
+    }
+    Ok(metadata)
+}
+
+// The state of a link as redirects would find it at `now`, checked in the same order
+fn link_health(url: &ShortenedUrl, now: DateTime<Utc>) -> LinkHealth {
+    let is_expired = url.is_expired();
+    let state = match url.status {
+        LinkStatus::Blocked => LinkState::Blocked,
+        LinkStatus::PendingReview => LinkState::PendingReview,
+        _ if is_expired => LinkState::Expired,
+        _ if !url.is_active => LinkState::Deactivated,
+        _ if url.is_throttled() => LinkState::Throttled,
+        _ if url.is_consumed() => LinkState::Consumed,
+        LinkStatus::Flagged => LinkState::Flagged,
+        LinkStatus::Active => LinkState::Active,
+    };
+
+    // The earlier of the expiry date and the end of the idle limit
+    let expiry = match (url.expires_at, url.idle_expires_at()) {
+        (Some(date), Some(idle)) => Some(date.min(idle)),
+        (date, idle) => date.or(idle),
+    };
+    let reachability = match (url.last_checked_at, url.is_broken) {
+        (None, _) => Reachability::Unchecked,
+        (Some(_), true) => Reachability::Broken,
+        (Some(_), false) => Reachability::Reachable,
+    };
+
+    LinkHealth {
+        state,
+        is_expired,
+        days_until_expiry: expiry.map(|expiry| (expiry - now).num_days().max(0)),
+        reachability,
+        last_checked_at: url.last_checked_at,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::config::CodeAlphabet;
+    use crate::db::{
+        testing::{insert_link, insert_user, url_service},
+        Database,
+    };
+    use crate::errors::RepositoryError;
+    use crate::repositories::{DbTransaction, ShortenedUrlRepository};
+
+    type RepoResult<T> = std::result::Result<T, RepositoryError>;
+
+    // Only saving and consumption are exercised; the set stands in for the unique index on
+    // codes, the flag for the conditional UPDATE
+    #[derive(Default)]
+    struct FakeRepository {
+        codes: Mutex<HashSet<String>>,
+        consumed: AtomicBool,
+    }
+
+    #[async_trait]
+    impl ShortenedUrlRepositoryTrait for FakeRepository {
+        async fn save(&self, url: &NewShortenedUrl) -> RepoResult<ShortenedUrl> {
+            tokio::task::yield_now().await;
+            let mut codes = self.codes.lock().unwrap();
+            if !codes.insert(url.short_code.clone()) {
+                return Err(RepositoryError::Conflict(
+                    "Resource already exists".to_string(),
+                ));
+            }
+            Ok(ShortenedUrl {
+                id: Uuid::new_v4(),
+                created_at: Utc::now(),
+                original_url: url.original_url.clone(),
+                short_code: url.short_code.clone(),
+                is_custom_code: url.is_custom_code,
+                is_active: true,
+                ..Default::default()
+            })
+        }
+        async fn find(&self, _: &ShortenedUrlQueryParams) -> RepoResult<Vec<ShortenedUrl>> {
+            unimplemented!()
+        }
+        fn stream(
+            &self,
+            _: ShortenedUrlQueryParams,
+        ) -> BoxStream<'static, RepoResult<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn count(&self, _: &ShortenedUrlQueryParams) -> RepoResult<i64> {
+            unimplemented!()
+        }
+        async fn find_by_id(&self, _: &Uuid) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn find_remapped_id(&self, _: &Uuid) -> RepoResult<Option<Uuid>> {
+            unimplemented!()
+        }
+        async fn find_by_code(
+            &self,
+            _: Option<&Uuid>,
+            _: Option<&str>,
+            _: &str,
+        ) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn find_all(
+            &self,
+            _: Option<&Uuid>,
+            _: Option<i64>,
+            _: Option<i64>,
+        ) -> RepoResult<Vec<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn find_by_codes(
+            &self,
+            _: Option<&Uuid>,
+            _: &[String],
+            _: bool,
+        ) -> RepoResult<Vec<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn find_by_code_ignore_case(
+            &self,
+            _: Option<&Uuid>,
+            _: Option<&str>,
+            _: &str,
+        ) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn update(
+            &self,
+            _: &Uuid,
+            _: &ShortenedUrlUpdateParams,
+            _: Option<&Uuid>,
+        ) -> RepoResult<u64> {
+            unimplemented!()
+        }
+        async fn update_in(
+            &self,
+            _: &mut DbTransaction,
+            _: &Uuid,
+            _: &ShortenedUrlUpdateParams,
+            _: Option<&Uuid>,
+        ) -> RepoResult<u64> {
+            unimplemented!()
+        }
+        async fn begin(&self) -> RepoResult<DbTransaction> {
+            unimplemented!()
+        }
+        async fn save_in(
+            &self,
+            _: &mut DbTransaction,
+            _: &NewShortenedUrl,
+        ) -> RepoResult<ShortenedUrl> {
+            unimplemented!()
+        }
+        async fn lock_by_id_in(
+            &self,
+            _: &mut DbTransaction,
+            _: &Uuid,
+        ) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn delete_in(&self, _: &mut DbTransaction, _: &Uuid) -> RepoResult<bool> {
+            unimplemented!()
+        }
+        async fn find_expiring(
+            &self,
+            _: Option<&Uuid>,
+            _: i32,
+            _: i64,
+            _: i64,
+        ) -> RepoResult<Vec<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn find_trash(
+            &self,
+            _: Option<&Uuid>,
+            _: i64,
+            _: i64,
+        ) -> RepoResult<Vec<TrashedUrl>> {
+            unimplemented!()
+        }
+        async fn find_in_trash(
+            &self,
+            _: &Uuid,
+            _: Option<&Uuid>,
+        ) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn restore(&self, _: &Uuid, _: Option<&Uuid>) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn purge_trash(&self, _: DateTime<Utc>, _: i64) -> RepoResult<u64> {
+            unimplemented!()
+        }
+        async fn count_trash(&self, _: DateTime<Utc>) -> RepoResult<TrashStats> {
+            unimplemented!()
+        }
+        async fn find_revisions(&self, _: &Uuid) -> RepoResult<Vec<UrlRevision>> {
+            unimplemented!()
+        }
+        async fn find_revision(&self, _: &Uuid, _: i32) -> RepoResult<Option<UrlRevision>> {
+            unimplemented!()
+        }
+        async fn delete(&self, _: &Uuid, _: bool) -> RepoResult<bool> {
+            unimplemented!()
+        }
+        async fn record_access(&self, _: &Uuid) -> RepoResult<i64> {
+            unimplemented!()
+        }
+        async fn add_accesses(&self, _: &Uuid, _: i64, _: DateTime<Utc>) -> RepoResult<i64> {
+            unimplemented!()
+        }
+        async fn claim_milestones(&self, _: &Uuid, _: &[i64]) -> RepoResult<Vec<i64>> {
+            unimplemented!()
+        }
+        async fn find_most_accessed(&self, _: i64) -> RepoResult<Vec<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn dump_rows(&self, _: i64, _: i64) -> RepoResult<Vec<serde_json::Value>> {
+            unimplemented!()
+        }
+        async fn deactivate_idle(&self) -> RepoResult<u64> {
+            unimplemented!()
+        }
+        async fn archive_unused(&self, _: DateTime<Utc>) -> RepoResult<u64> {
+            unimplemented!()
+        }
+        async fn claim_expired(&self, _: DateTime<Utc>) -> RepoResult<Vec<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn update_status(&self, _: &Uuid, _: LinkStatus) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn set_archived(&self, _: &Uuid, _: bool) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn find_http_destinations(
+            &self,
+            _: bool,
+            _: Option<Uuid>,
+            _: i64,
+        ) -> RepoResult<Vec<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn find_links_to_check(&self, _: i64) -> RepoResult<Vec<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn record_check(&self, _: &Uuid, _: Option<i32>, _: bool) -> RepoResult<()> {
+            unimplemented!()
+        }
+        async fn add_favorite(&self, _: &Uuid, _: &Uuid) -> RepoResult<()> {
+            unimplemented!()
+        }
+        async fn remove_favorite(&self, _: &Uuid, _: &Uuid) -> RepoResult<bool> {
+            unimplemented!()
+        }
+        async fn create_claim(&self, _: &Uuid, _: &str, _: DateTime<Utc>) -> RepoResult<()> {
+            unimplemented!()
+        }
+        async fn claim(
+            &self,
+            _: &str,
+            _: &Uuid,
+            _: Option<&Uuid>,
+        ) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn purge_expired_claims(&self) -> RepoResult<u64> {
+            unimplemented!()
+        }
+        async fn offer_transfer(
+            &self,
+            _: &Uuid,
+            _: &Uuid,
+            _: &Uuid,
+        ) -> RepoResult<Option<LinkTransfer>> {
+            unimplemented!()
+        }
+        async fn find_transfer(&self, _: &Uuid) -> RepoResult<Option<LinkTransfer>> {
+            unimplemented!()
+        }
+        async fn find_pending_transfers(&self, _: &Uuid) -> RepoResult<Vec<LinkTransfer>> {
+            unimplemented!()
+        }
+        async fn find_transfers(&self, _: &Uuid) -> RepoResult<Vec<LinkTransfer>> {
+            unimplemented!()
+        }
+        async fn accept_transfer(&self, _: &Uuid, _: &Uuid) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn close_transfer(
+            &self,
+            _: &Uuid,
+            _: TransferStatus,
+        ) -> RepoResult<Option<LinkTransfer>> {
+            unimplemented!()
+        }
+        async fn force_transfer(
+            &self,
+            _: &Uuid,
+            _: Option<&Uuid>,
+            _: Option<&Uuid>,
+        ) -> RepoResult<Option<ShortenedUrl>> {
+            unimplemented!()
+        }
+        async fn consume(&self, _: &Uuid) -> RepoResult<bool> {
+            tokio::task::yield_now().await;
+            Ok(self
+                .consumed
+                .compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok())
+        }
+    }
+
+    fn service(
+        repository: FakeRepository,
+        codes: ShortCodeConfig,
+    ) -> Arc<ShortenedUrlService<FakeRepository>> {
+        Arc::new(ShortenedUrlService::new(
+            Arc::new(repository),
+            codes,
+            PaginationConfig::default(),
+            MilestoneConfig::default(),
+            Arc::new(LinkCache::disabled()),
+            Arc::new(AccessBacklog::new(0)),
+            Arc::new(EventBus::disabled()),
+        ))
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_creates_share_an_alias_once() {
+        let service = service(FakeRepository::default(), ShortCodeConfig::default());
+
+        // None of the requests finds the alias taken before saving
+        let attempts = (0..8).map(|_| {
+            let service = service.clone();
+            tokio::spawn(async move {
+                let dto = CreateShortenedUrlDto {
+                    original_url: "https://example.com".to_string(),
+                    custom_alias: Some("launch".to_string()),
+                    ..Default::default()
+                };
+                service.create(dto, None, None).await
+            })
+        });
+        let results = futures::future::join_all(attempts).await;
+
+        let created = results.iter().filter(|r| matches!(r, Ok(Ok(_)))).count();
+        let refused = results
+            .iter()
+            .filter(|r| {
+                matches!(r, Ok(Err(e)) if e.code() == ErrorCode::AliasTaken
+                    && e.message() == "Custom short code 'launch' is already in use")
+            })
+            .count();
+        assert_eq!((created, refused), (1, 7));
+    }
+
+    #[tokio::test]
+    async fn test_repeated_create_gets_the_first_link() {
+        let service = Arc::new(
+            Arc::into_inner(service(
+                FakeRepository::default(),
+                ShortCodeConfig::default(),
+            ))
+            .unwrap()
+            .with_recent_creates(RecentCreates::new(std::time::Duration::from_secs(10))),
+        );
+        let dto = |ip: &str| CreateShortenedUrlDto {
+            original_url: "https://example.com".to_string(),
+            client_ip: ip.parse().ok(),
+            ..Default::default()
+        };
+
+        // A double click sends both requests at once
+        let attempts = (0..4).map(|_| {
+            let (service, dto) = (service.clone(), dto("203.0.113.7"));
+            tokio::spawn(async move { service.create(dto, None, None).await.unwrap() })
+        });
+        let mut codes = futures::future::join_all(attempts)
+            .await
+            .into_iter()
+            .map(|created| created.unwrap().short_code)
+            .collect::<Vec<_>>();
+        let again = service
+            .create(dto("203.0.113.7"), None, None)
+            .await
+            .unwrap();
+        codes.push(again.short_code);
+        codes.dedup();
+        assert_eq!(codes.len(), 1);
+
+        let other_client = service
+            .create(dto("203.0.113.8"), None, None)
+            .await
+            .unwrap();
+        assert_ne!(other_client.short_code, codes[0]);
+    }
+
+    #[tokio::test]
+    async fn test_taken_alias_is_numbered_when_asked() {
+        let service = service(FakeRepository::default(), ShortCodeConfig::default());
+        let create = |on_conflict| {
+            service.create(
+                CreateShortenedUrlDto {
+                    original_url: "https://example.com".to_string(),
+                    custom_alias: Some("launch".to_string()),
+                    on_conflict,
+                    ..Default::default()
+                },
+                None,
+                None,
+            )
+        };
+
+        let mut codes = Vec::new();
+        for _ in 0..3 {
+            codes.push(
+                create(Some(AliasConflict::Suffix))
+                    .await
+                    .unwrap()
+                    .short_code,
+            );
+        }
+        assert_eq!(codes, ["launch", "launch-2", "launch-3"]);
+        assert!(matches!(create(None).await, Err(e) if e.code() == ErrorCode::AliasTaken));
+    }
+
+    #[tokio::test]
+    async fn test_generated_code_retries_are_bounded() {
+        // Both codes the alphabet allows are taken
+        let repository = FakeRepository {
+            codes: Mutex::new(HashSet::from(["a".to_string(), "b".to_string()])),
+            ..Default::default()
+        };
+        let codes = ShortCodeConfig {
+            alphabet: CodeAlphabet::Custom("ab".to_string()),
+            length: 1,
+            ..Default::default()
+        };
+        let service = service(repository, codes);
+
+        let dto = CreateShortenedUrlDto {
+            original_url: "https://example.com".to_string(),
+            ..Default::default()
+        };
+        let result = service.create(dto, None, None).await;
+        assert!(matches!(result, Err(AppError::Internal(_))));
+
+        let stats = service.code_stats();
+        assert_eq!(
+            (stats.generated, stats.retries, stats.exhausted),
+            (0, MAX_CODE_ATTEMPTS as u64, 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_consume_single_use_once() {
+        let service = service(FakeRepository::default(), ShortCodeConfig::default());
+        let url = ShortenedUrl {
+            short_code: "secret".to_string(),
+            single_use: true,
+            ..Default::default()
+        };
+
+        // Every request loaded the link before any of them consumed it
+        let attempts = (0..8).map(|_| {
+            let service = service.clone();
+            let url = url.clone();
+            tokio::spawn(async move { service.consume(&url).await })
+        });
+        let results = futures::future::join_all(attempts).await;
+
+        let won = results.iter().filter(|r| matches!(r, Ok(Ok(())))).count();
+        let gone = results
+            .iter()
+            .filter(|r| matches!(r, Ok(Err(e)) if e.code() == ErrorCode::UrlConsumed))
+            .count();
+        assert_eq!((won, gone), (1, 7));
+
+        // A reload after consumption is refused without touching the repository again
+        let reloaded = ShortenedUrl {
+            consumed_at: Some(Utc::now()),
+            ..url
+        };
+        assert!(matches!(
+            service.consume(&reloaded).await,
+            Err(e) if e.code() == ErrorCode::UrlConsumed
+        ));
+
+        // Regular links are never consumed
+        let regular = ShortenedUrl::default();
+        assert!(service.consume(&regular).await.is_ok());
+        assert!(service.consume(&regular).await.is_ok());
+    }
+
+    #[test]
+    fn test_link_health() {
+        let now = Utc::now();
+        let mut url = ShortenedUrl {
+            created_at: now - Duration::days(10),
+            is_active: true,
+            expires_at: Some(now + Duration::days(20) + Duration::hours(1)),
+            max_idle_days: Some(15),
+            ..Default::default()
+        };
+
+        // The idle limit runs out before the expiry date
+        let health = link_health(&url, now);
+        assert_eq!(health.state, LinkState::Active);
+        assert_eq!(health.days_until_expiry, Some(5));
+        assert_eq!(health.reachability, Reachability::Unchecked);
+
+        url.status = LinkStatus::Flagged;
+        url.last_checked_at = Some(now);
+        url.is_broken = true;
+        let health = link_health(&url, now);
+        assert_eq!(health.state, LinkState::Flagged);
+        assert_eq!(health.reachability, Reachability::Broken);
+
+        url.created_at = now - Duration::days(30);
+        let health = link_health(&url, now);
+        assert!(health.is_expired);
+        assert_eq!(health.state, LinkState::Expired);
+        assert_eq!(health.days_until_expiry, Some(0));
+
+        url.status = LinkStatus::Blocked;
+        assert_eq!(link_health(&url, now).state, LinkState::Blocked);
+    }
+
+    #[test]
+    fn test_with_tag() {
+        let tagged = serde_json::json!({ "title": "Launch", "tags": ["promo"] });
+        assert_eq!(
+            with_tag(Some(tagged.clone()), "spring").unwrap(),
+            serde_json::json!({ "title": "Launch", "tags": ["promo", "spring"] })
+        );
+        assert_eq!(with_tag(Some(tagged.clone()), "promo").unwrap(), tagged);
+        assert_eq!(
+            with_tag(None, "promo").unwrap(),
+            serde_json::json!({ "tags": ["promo"] })
+        );
+
+        assert!(with_tag(Some(serde_json::json!(["promo"])), "promo").is_err());
+        assert!(with_tag(Some(serde_json::json!({ "tags": "promo" })), "promo").is_err());
+    }
+
+    #[sqlx::test]
+    async fn test_concurrent_visits_consume_a_single_use_link_once(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let id = insert_link(&pool, "secret").await;
+        sqlx::query("UPDATE shortened_urls SET single_use = TRUE WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let url = service.get_by_id(&id, None).await.unwrap();
+
+        // Every visit loaded the link before any of them consumed it, the database decides
+        let attempts = (0..8).map(|_| {
+            let (service, url) = (service.clone(), url.clone());
+            tokio::spawn(async move { service.consume(&url).await })
+        });
+        let results = futures::future::join_all(attempts).await;
+
+        let won = results.iter().filter(|r| matches!(r, Ok(Ok(())))).count();
+        let gone = results
+            .iter()
+            .filter(|r| matches!(r, Ok(Err(e)) if e.code() == ErrorCode::UrlConsumed))
+            .count();
+        assert_eq!((won, gone), (1, 7));
+        assert!(service.get_by_id(&id, None).await.unwrap().is_consumed());
+    }
+
+    #[sqlx::test]
+    async fn test_resolve_codes_in_one_batch(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        insert_link(&pool, "live").await;
+        let stale = insert_link(&pool, "stale").await;
+        sqlx::query("UPDATE shortened_urls SET expires_at = NOW() - INTERVAL '1 day' WHERE id = $1")
+            .bind(stale)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let resolve = |codes: Vec<String>| service.resolve_codes(ResolveCodesDto { codes }, None);
+
+        let codes = ["live", "stale", "gone"].map(String::from).to_vec();
+        let resolved = resolve(codes).await.unwrap();
+        let found = |expired| {
+            Some(ResolvedCode {
+                original_url: "https://example.com".to_string(),
+                active: true,
+                expired,
+            })
+        };
+        assert_eq!(resolved.len(), 3);
+        assert_eq!(resolved["live"], found(false));
+        assert_eq!(resolved["stale"], found(true));
+        assert_eq!(resolved["gone"], None);
+
+        // Up to 100 codes at once, and at least one
+        let batch = |size: usize| (0..size).map(|i| format!("code-{i}")).collect::<Vec<_>>();
+        assert_eq!(resolve(batch(100)).await.unwrap().len(), 100);
+        for size in [0, 101] {
+            let err = resolve(batch(size)).await.unwrap_err();
+            assert!(matches!(err, AppError::InvalidFields(_)), "{:?}", err);
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_revert_waits_for_concurrent_edits(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let id = insert_link(&pool, "reverted").await;
+        let dto = ShortenedUrlUpdateParams {
+            original_url: Some("https://example.org".to_string()),
+            version: Some(service.get_by_id(&id, None).await.unwrap().version),
+            ..Default::default()
+        };
+        service.update(&id, dto, None, None).await.unwrap();
+
+        // Another editor holds the row while changing it
+        let mut edit = pool.begin().await.unwrap();
+        sqlx::query("UPDATE shortened_urls SET version = version + 1 WHERE id = $1")
+            .bind(id)
+            .execute(&mut *edit)
+            .await
+            .unwrap();
+
+        let revert = tokio::spawn({
+            let service = service.clone();
+            async move { service.revert(&id, 1, None, None).await }
+        });
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert!(
+            !revert.is_finished(),
+            "revert read the row while it was locked"
+        );
+
+        // Having read the row only once the edit is done, the revert applies on top of it
+        edit.commit().await.unwrap();
+        let reverted = revert.await.unwrap().unwrap();
+        assert_eq!(reverted.original_url, "https://example.com");
+    }
+
+    #[test]
+    fn test_update_needs_a_base_version() {
+        let mut dto = ShortenedUrlUpdateParams {
+            original_url: Some("https://example.org".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            validate_update(&dto),
+            Err(AppError::Validation(_))
+        ));
+
+        dto.version = Some(3);
+        assert!(validate_update(&dto).is_ok());
+    }
+
+    #[sqlx::test]
+    async fn test_claim_tokens_work_once_until_they_expire(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let repository = ShortenedUrlRepository::new(Database::from_pool(pool.clone()));
+        let alice = insert_user(&pool, "alice@example.com").await;
+        let bob = insert_user(&pool, "bob@example.com").await;
+        let claim = |token: &str| ClaimLinkDto {
+            token: token.to_string(),
+        };
+
+        let expired = insert_link(&pool, "expired").await;
+        let yesterday = Utc::now() - Duration::days(1);
+        repository
+            .create_claim(&expired, &sha256_hex("old"), yesterday)
+            .await
+            .unwrap();
+        let err = service.claim(claim("old"), &alice, None).await.unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)), "{:?}", err);
+
+        let id = insert_link(&pool, "anonymous").await;
+        let tomorrow = Utc::now() + Duration::days(1);
+        repository
+            .create_claim(&id, &sha256_hex("fresh"), tomorrow)
+            .await
+            .unwrap();
+        let claimed = service.claim(claim("fresh"), &alice, None).await.unwrap();
+        assert_eq!(claimed.created_by, Some(alice));
+
+        // The token is used up, and the link keeps its owner
+        let err = service.claim(claim("fresh"), &bob, None).await.unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)), "{:?}", err);
+        assert_eq!(
+            service.get_by_id(&id, None).await.unwrap().created_by,
+            Some(alice)
+        );
+    }
+
+    async fn owned_link(pool: &sqlx::PgPool, short_code: &str, owner: Uuid) -> Uuid {
+        let id = insert_link(pool, short_code).await;
+        sqlx::query("UPDATE shortened_urls SET created_by = $1 WHERE id = $2")
+            .bind(owner)
+            .bind(id)
+            .execute(pool)
+            .await
+            .unwrap();
+        id
+    }
+
+    fn offer_to(user_id: Uuid) -> TransferLinkDto {
+        TransferLinkDto {
+            to_user_id: Some(user_id),
+            ..Default::default()
+        }
+    }
+
+    #[sqlx::test]
+    async fn test_only_the_owner_changes_a_link(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let alice = insert_user(&pool, "alice@example.com").await;
+        let bob = insert_user(&pool, "bob@example.com").await;
+        let id = owned_link(&pool, "owned", alice).await;
+        let version = service.get_by_id(&id, None).await.unwrap().version;
+        let edit = |version| ShortenedUrlUpdateParams {
+            is_active: Some(false),
+            version: Some(version),
+            ..Default::default()
+        };
+
+        for user in [Some(bob), None] {
+            let err = service
+                .update(&id, edit(version), user, None)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::Forbidden(_)), "{:?}", err);
+            let err = service.delete(&id, user, None).await.unwrap_err();
+            assert!(matches!(err, AppError::Forbidden(_)), "{:?}", err);
+        }
+        service
+            .update(&id, edit(version), Some(alice), None)
+            .await
+            .unwrap();
+        assert!(service.delete(&id, Some(alice), None).await.unwrap());
+
+        // Nobody owns a link created without signing in
+        let anonymous = insert_link(&pool, "anonymous").await;
+        let version = service.get_by_id(&anonymous, None).await.unwrap().version;
+        service
+            .update(&anonymous, edit(version), Some(bob), None)
+            .await
+            .unwrap();
+    }
+
+    #[sqlx::test]
+    async fn test_only_the_owner_archives_restores_or_copies_a_link(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let alice = insert_user(&pool, "alice@example.com").await;
+        let bob = insert_user(&pool, "bob@example.com").await;
+        let id = owned_link(&pool, "owned", alice).await;
+
+        for user in [Some(bob), None] {
+            let err = service
+                .set_archived(&id, true, user, None)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::Forbidden(_)), "{:?}", err);
+            let err = service
+                .duplicate(&id, DuplicateShortenedUrlDto::default(), user, None)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::Forbidden(_)), "{:?}", err);
+        }
+        let archived = service
+            .set_archived(&id, true, Some(alice), None)
+            .await
+            .unwrap();
+        assert!(archived.archived_at.is_some());
+
+        assert!(service.delete(&id, Some(alice), None).await.unwrap());
+        for user in [Some(bob), None] {
+            let err = service.restore(&id, user, None).await.unwrap_err();
+            assert!(matches!(err, AppError::Forbidden(_)), "{:?}", err);
+        }
+        service.restore(&id, Some(alice), None).await.unwrap();
+    }
+
+    #[sqlx::test]
+    async fn test_hashed_codes_are_only_shared_with_the_same_owner(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let alice = insert_user(&pool, "alice@example.com").await;
+        let bob = insert_user(&pool, "bob@example.com").await;
+        let create = |owner| {
+            let dto = CreateShortenedUrlDto {
+                original_url: "https://example.com/launch".to_string(),
+                code_strategy: Some(CodeStrategy::Hash),
+                ..Default::default()
+            };
+            service.create(dto, owner, None)
+        };
+
+        let first = create(Some(alice)).await.unwrap();
+        assert_eq!(create(Some(alice)).await.unwrap().id, first.id);
+
+        let others = [create(Some(bob)).await.unwrap(), create(None).await.unwrap()];
+        for other in &others {
+            assert_ne!(other.id, first.id);
+            assert!(other.short_code.starts_with(&first.short_code));
+        }
+        assert_eq!(create(Some(bob)).await.unwrap().id, others[0].id);
+    }
+
+    #[sqlx::test]
+    async fn test_duplicate_makes_a_fresh_link_for_the_caller(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let alice = insert_user(&pool, "alice@example.com").await;
+        let id = insert_link(&pool, "campaign").await;
+        sqlx::query("UPDATE shortened_urls SET access_count = 42, notes = 'Spring' WHERE id = $1")
+            .bind(id)
+            .execute(&pool)
+            .await
+            .unwrap();
+        let source = service.get_by_id(&id, None).await.unwrap();
+
+        let copy = service
+            .duplicate(&id, DuplicateShortenedUrlDto::default(), Some(alice), None)
+            .await
+            .unwrap();
+        assert_ne!(copy.id, Some(id));
+        assert_ne!(copy.short_code, source.short_code);
+        assert!(!copy.is_custom_code);
+        assert_eq!(copy.original_url, source.original_url);
+        assert_eq!(copy.access_count, 0);
+        let copy = service.get_by_id(&copy.id.unwrap(), None).await.unwrap();
+        assert_eq!(copy.created_by, Some(alice));
+        assert_eq!(copy.notes.as_deref(), Some("Spring"));
+
+        // The original is left as it was
+        let after = service.get_by_id(&id, None).await.unwrap();
+        assert_eq!(
+            (after.short_code, after.access_count, after.created_by),
+            (source.short_code, 42, None)
+        );
+        assert_eq!(after.version, source.version);
+
+        let named = service
+            .duplicate(
+                &id,
+                DuplicateShortenedUrlDto {
+                    custom_alias: Some("campaign-2".to_string()),
+                    ..Default::default()
+                },
+                Some(alice),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(named.short_code, "campaign-2");
+    }
+
+    #[sqlx::test]
+    async fn test_archived_links_leave_listings_but_keep_redirecting(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let alice = insert_user(&pool, "alice@example.com").await;
+        let bob = insert_user(&pool, "bob@example.com").await;
+        let id = owned_link(&pool, "spring", alice).await;
+        insert_link(&pool, "summer").await;
+        let listed = |page: Page<ShortenedUrl>| {
+            page.items
+                .into_iter()
+                .map(|url| url.short_code)
+                .collect::<Vec<_>>()
+        };
+        let with_archived = ShortenedUrlQueryParams {
+            include_archived: Some(true),
+            ..Default::default()
+        };
+
+        service
+            .set_archived(&id, true, Some(alice), None)
+            .await
+            .unwrap();
+        let all = service.get_all(None, None, None).await.unwrap();
+        assert_eq!(listed(all), ["summer"]);
+        assert_eq!(service.count(&Default::default()).await.unwrap(), 1);
+        let mut everything = listed(service.get_by_query(&with_archived).await.unwrap());
+        everything.sort();
+        assert_eq!(everything, ["spring", "summer"]);
+        let url = service.get_by_code(None, None, "spring").await.unwrap();
+        assert!(url.archived_at.is_some() && url.is_valid());
+
+        // Only the owner takes it back out
+        for user in [Some(bob), None] {
+            let err = service
+                .set_archived(&id, false, user, None)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, AppError::Forbidden(_)), "{:?}", err);
+        }
+        let url = service
+            .set_archived(&id, false, Some(alice), None)
+            .await
+            .unwrap();
+        assert!(url.archived_at.is_none());
+        let mut all = listed(service.get_all(None, None, None).await.unwrap());
+        all.sort();
+        assert_eq!(all, ["spring", "summer"]);
+    }
+
+    #[sqlx::test]
+    async fn test_transfer_offers(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let alice = insert_user(&pool, "alice@example.com").await;
+        let bob = insert_user(&pool, "bob@example.com").await;
+        let id = owned_link(&pool, "moving", alice).await;
+
+        // Declined by the recipient
+        let offer = service
+            .offer_transfer(&id, offer_to(bob), &alice, None)
+            .await
+            .unwrap();
+        let err = service
+            .accept_transfer(&offer.id, &alice)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::NotFound(_)), "{:?}", err);
+        let closed = service.close_transfer(&offer.id, &bob).await.unwrap();
+        assert_eq!(closed.status, TransferStatus::Declined);
+
+        // Withdrawn by the owner
+        let offer = service
+            .offer_transfer(&id, offer_to(bob), &alice, None)
+            .await
+            .unwrap();
+        let closed = service.close_transfer(&offer.id, &alice).await.unwrap();
+        assert_eq!(closed.status, TransferStatus::Cancelled);
+        let err = service.accept_transfer(&offer.id, &bob).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "{:?}", err);
+        assert_eq!(
+            service.get_by_id(&id, None).await.unwrap().created_by,
+            Some(alice)
+        );
+
+        // Accepted
+        let offer = service
+            .offer_transfer(&id, offer_to(bob), &alice, None)
+            .await
+            .unwrap();
+        let moved = service.accept_transfer(&offer.id, &bob).await.unwrap();
+        assert_eq!(moved.created_by, Some(bob));
+        let err = service
+            .offer_transfer(&id, offer_to(bob), &alice, None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Forbidden(_)), "{:?}", err);
+    }
+
+    #[sqlx::test]
+    async fn test_transfer_is_void_once_the_owner_changed(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let alice = insert_user(&pool, "alice@example.com").await;
+        let bob = insert_user(&pool, "bob@example.com").await;
+        let carol = insert_user(&pool, "carol@example.com").await;
+        let id = owned_link(&pool, "raced", alice).await;
+
+        let offer = service
+            .offer_transfer(&id, offer_to(bob), &alice, None)
+            .await
+            .unwrap();
+        // An admin hands the link to someone else before bob answers
+        service
+            .force_transfer(&id, Some(carol), None, None)
+            .await
+            .unwrap();
+
+        let err = service.accept_transfer(&offer.id, &bob).await.unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "{:?}", err);
+        assert_eq!(
+            service.get_by_id(&id, None).await.unwrap().created_by,
+            Some(carol)
+        );
+        let transfers = service.get_transfers(&id, None).await.unwrap();
+        let offered = transfers.iter().find(|t| t.id == offer.id).unwrap();
+        assert_eq!(offered.status, TransferStatus::Cancelled);
+    }
+
+    #[sqlx::test]
+    async fn test_admin_transfer_into_a_tenant_using_the_code(pool: sqlx::PgPool) {
+        let service = url_service(&pool);
+        let tenant: Uuid = sqlx::query_scalar(
+            "INSERT INTO tenants (slug, name) VALUES ('acme', 'Acme') RETURNING id",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        let id = insert_link(&pool, "launch").await;
+        let taken = insert_link(&pool, "launch-acme").await;
+        sqlx::query(
+            "UPDATE shortened_urls SET short_code = 'launch', tenant_id = $1 WHERE id = $2",
+        )
+        .bind(tenant)
+        .bind(taken)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let err = service
+            .force_transfer(&id, None, Some(tenant), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AppError::Conflict(_)), "{:?}", err);
+        assert_eq!(service.get_by_id(&id, None).await.unwrap().tenant_id, None);
+    }
 }
@@ -0,0 +1,121 @@
+// src/services/snapshot.rs - Moving links and settings from one instance to another
+use std::sync::Arc;
+
+use log::info;
+
+use crate::{
+    errors::AppError,
+    models::{RestoreReport, Snapshot, SNAPSHOT_FORMAT_VERSION},
+    repositories::SnapshotRepositoryTrait,
+    types::Result,
+};
+
+pub struct SnapshotService<R: SnapshotRepositoryTrait> {
+    repository: Arc<R>,
+}
+
+impl<R: SnapshotRepositoryTrait + Send + Sync> SnapshotService<R> {
+    pub fn new(repository: Arc<R>) -> Self {
+        Self { repository }
+    }
+
+    /// Links and settings as of now
+    pub async fn take(&self) -> Result<Snapshot> {
+        let snapshot = self.repository.take().await?;
+        info!(
+            "Snapshot taken: {} links, {} tenants",
+            snapshot.links.len(),
+            snapshot.tenants.len()
+        );
+        Ok(snapshot)
+    }
+
+    /// Add the links and settings of a snapshot missing here, or only check what that would do
+    ///
+    /// ### Errors
+    /// - `AppError::Validation` if the snapshot is from another release, or its rows don't fit
+    /// - `AppError::Conflict` if tenants or links clash with different ones here, unless it's
+    ///   a dry run, whose report lists them
+    pub async fn restore(&self, snapshot: Snapshot, dry_run: bool) -> Result<RestoreReport> {
+        if snapshot.format_version != SNAPSHOT_FORMAT_VERSION {
+            return Err(AppError::Validation(format!(
+                "Snapshot format version {} isn't supported, expected {}",
+                snapshot.format_version, SNAPSHOT_FORMAT_VERSION
+            )));
+        }
+        let schema_version = self.repository.schema_version().await?;
+        if snapshot.schema_version != schema_version {
+            return Err(AppError::Validation(format!(
+                "Snapshot was taken at schema version {} but this instance is at {}, restore it \
+                 on an instance running the same release",
+                snapshot.schema_version, schema_version
+            )));
+        }
+
+        let report = self.repository.restore(&snapshot, dry_run).await?;
+        if !dry_run {
+            if !report.conflicts.is_empty() {
+                return Err(AppError::Conflict(format!(
+                    "Snapshot clashes with data of this instance: {}",
+                    report.conflicts.join("; ")
+                )));
+            }
+            let inserted: u64 = report.tables.iter().map(|t| t.inserted).sum();
+            info!(
+                "Snapshot taken at {} restored: {} rows inserted",
+                snapshot.taken_at, inserted
+            );
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::PgPool;
+
+    use super::*;
+    use crate::db::{testing::insert_link, Database};
+    use crate::repositories::SnapshotRepository;
+
+    #[sqlx::test]
+    async fn test_restore_checks_the_snapshot_first(pool: PgPool) {
+        let repository = SnapshotRepository::new(Database::from_pool(pool.clone()));
+        let service = SnapshotService::new(Arc::new(repository));
+        insert_link(&pool, "launch").await;
+        let snapshot = service.take().await.unwrap();
+
+        // Snapshots of another release are refused, dry run or not
+        let mut other_release = snapshot.clone();
+        other_release.schema_version -= 1;
+        let refused = service.restore(other_release, true).await;
+        assert!(
+            matches!(refused, Err(AppError::Validation(_))),
+            "{:?}",
+            refused
+        );
+        let mut other_format = snapshot.clone();
+        other_format.format_version += 1;
+        let refused = service.restore(other_format, true).await;
+        assert!(
+            matches!(refused, Err(AppError::Validation(_))),
+            "{:?}",
+            refused
+        );
+
+        // A clash is listed by a dry run and fails the actual restore
+        sqlx::query("DELETE FROM shortened_urls")
+            .execute(&pool)
+            .await
+            .unwrap();
+        insert_link(&pool, "launch").await;
+        let report = service.restore(snapshot.clone(), true).await.unwrap();
+        assert_eq!(report.conflicts.len(), 1);
+        let refused = service.restore(snapshot, false).await;
+        assert!(
+            matches!(refused, Err(AppError::Conflict(_))),
+            "{:?}",
+            refused
+        );
+    }
+}
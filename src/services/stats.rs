@@ -0,0 +1,119 @@
+// src/services/stats.rs - Aggregate link statistics for reporting
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use futures::{stream::BoxStream, StreamExt};
+use uuid::Uuid;
+
+use crate::{
+    config::PaginationConfig,
+    errors::AppError,
+    models::{GroupByQueryParams, GroupStats, Page, TopLink, TopLinksQueryParams},
+    repositories::StatsRepositoryTrait,
+    types::Result,
+};
+
+#[async_trait]
+pub trait StatsServiceTrait {
+    /// Links and clicks per group, days bucketed in UTC unless a time zone is given
+    async fn group_by(
+        &self,
+        params: &GroupByQueryParams,
+        tenant: Option<Uuid>,
+    ) -> Result<Page<GroupStats>>;
+    /// Most clicked links within the window, 24 hours unless given
+    async fn top_links(
+        &self,
+        params: &TopLinksQueryParams,
+        tenant: Option<Uuid>,
+    ) -> Result<Page<TopLink>>;
+    /// Every group for a report, or as many as the limit asks for
+    fn export_group_by(
+        &self,
+        params: &GroupByQueryParams,
+        tenant: Option<Uuid>,
+    ) -> BoxStream<'static, Result<GroupStats>>;
+    /// Every link clicked within the window for a report, or as many as the limit asks for
+    fn export_top_links(
+        &self,
+        params: &TopLinksQueryParams,
+        tenant: Option<Uuid>,
+    ) -> BoxStream<'static, Result<TopLink>>;
+}
+
+pub struct StatsService<T: StatsRepositoryTrait> {
+    repository: Arc<T>,
+    pages: PaginationConfig,
+}
+
+impl<T: StatsRepositoryTrait> StatsService<T> {
+    pub fn new(repository: Arc<T>, pages: PaginationConfig) -> Self {
+        Self { repository, pages }
+    }
+}
+
+#[async_trait]
+impl<T: StatsRepositoryTrait + Send + Sync> StatsServiceTrait for StatsService<T> {
+    async fn group_by(
+        &self,
+        params: &GroupByQueryParams,
+        tenant: Option<Uuid>,
+    ) -> Result<Page<GroupStats>> {
+        let limit = self.pages.page_size(params.limit);
+        let groups = self
+            .repository
+            .group_by(
+                tenant.as_ref(),
+                params.dimension,
+                params.tz.as_deref().unwrap_or("UTC"),
+                limit,
+            )
+            .await?;
+        Ok(Page {
+            items: groups,
+            limit,
+        })
+    }
+
+    async fn top_links(
+        &self,
+        params: &TopLinksQueryParams,
+        tenant: Option<Uuid>,
+    ) -> Result<Page<TopLink>> {
+        let limit = self.pages.page_size(params.limit);
+        let since = Utc::now() - params.window.unwrap_or_default().duration();
+        let links = self
+            .repository
+            .find_top_links(tenant.as_ref(), since, limit)
+            .await?;
+        Ok(Page {
+            items: links,
+            limit,
+        })
+    }
+
+    fn export_group_by(
+        &self,
+        params: &GroupByQueryParams,
+        tenant: Option<Uuid>,
+    ) -> BoxStream<'static, Result<GroupStats>> {
+        let tz = params.tz.clone().unwrap_or_else(|| "UTC".to_string());
+        self.repository
+            .stream_group_by(tenant, params.dimension, tz, params.limit)
+            .map(|row| row.map_err(AppError::from))
+            .boxed()
+    }
+
+    fn export_top_links(
+        &self,
+        params: &TopLinksQueryParams,
+        tenant: Option<Uuid>,
+    ) -> BoxStream<'static, Result<TopLink>> {
+        let since = Utc::now() - params.window.unwrap_or_default().duration();
+        self.repository
+            .stream_top_links(tenant, since, params.limit)
+            .map(|row| row.map_err(AppError::from))
+            .boxed()
+    }
+}
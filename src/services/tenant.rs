@@ -0,0 +1,153 @@
+// src/services/tenant.rs - Tenant lookup for incoming requests
+use std::sync::{Arc, RwLock};
+
+use log::debug;
+use validator::Validate;
+
+use crate::{
+    errors::AppError,
+    models::{CreateTenantDto, Tenant},
+    repositories::TenantRepositoryTrait,
+    types::Result,
+};
+
+/// Resolves the tenant of each request from memory
+///
+/// Tenants are few and rarely change, so every instance keeps all of them in memory and
+/// reloads them periodically, picking up tenants added through other instances.
+pub struct TenantService<R: TenantRepositoryTrait> {
+    repository: Arc<R>,
+    tenants: RwLock<Vec<Tenant>>,
+}
+
+impl<R: TenantRepositoryTrait + Send + Sync> TenantService<R> {
+    pub fn new(repository: Arc<R>) -> Self {
+        Self {
+            repository,
+            tenants: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Reload tenants from the database
+    pub async fn refresh(&self) -> Result<()> {
+        let tenants = self.repository.find_all().await?;
+        debug!("Tenants: {} loaded", tenants.len());
+        *self.tenants.write().unwrap_or_else(|e| e.into_inner()) = tenants;
+        Ok(())
+    }
+
+    /// The tenant a request belongs to, `None` for the default tenant
+    ///
+    /// A tenant named by slug wins over the one owning the request's host; naming an
+    /// unknown tenant is an error rather than falling back to the default.
+    pub fn resolve(&self, slug: Option<&str>, host: Option<&str>) -> Result<Option<Tenant>> {
+        let tenants = self.tenants.read().unwrap_or_else(|e| e.into_inner());
+        resolve_in(&tenants, slug, host)
+    }
+
+    pub async fn list(&self) -> Result<Vec<Tenant>> {
+        let tenants = self.repository.find_all().await?;
+        Ok(tenants)
+    }
+
+    pub async fn create(&self, dto: CreateTenantDto) -> Result<Tenant> {
+        dto.validate()?;
+
+        let tenant = self
+            .repository
+            .create(&dto)
+            .await
+            .map_err(|e| match AppError::from(e) {
+                AppError::Conflict(_) => AppError::Conflict(
+                    "A tenant with this slug or domain already exists".to_string(),
+                ),
+                e => e,
+            })?;
+
+        self.tenants
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(tenant.clone());
+        Ok(tenant)
+    }
+}
+
+fn resolve_in(
+    tenants: &[Tenant],
+    slug: Option<&str>,
+    host: Option<&str>,
+) -> Result<Option<Tenant>> {
+    if let Some(slug) = slug.map(str::trim).filter(|s| !s.is_empty()) {
+        return tenants
+            .iter()
+            .find(|t| t.slug.eq_ignore_ascii_case(slug))
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| AppError::NotFound(format!("Tenant '{}' not found", slug)));
+    }
+
+    let Some(host) = host.map(host_name) else {
+        return Ok(None);
+    };
+    Ok(tenants
+        .iter()
+        .find(|t| {
+            t.domain
+                .as_deref()
+                .is_some_and(|d| d.eq_ignore_ascii_case(host))
+        })
+        .cloned())
+}
+
+// The host name of a Host header value, without the port
+fn host_name(host: &str) -> &str {
+    match host.rsplit_once(':') {
+        Some((name, port)) if !name.ends_with(':') && port.chars().all(|c| c.is_ascii_digit()) => {
+            name
+        }
+        _ => host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+
+    fn tenant(slug: &str, domain: Option<&str>) -> Tenant {
+        Tenant {
+            id: Uuid::new_v4(),
+            slug: slug.to_string(),
+            name: slug.to_string(),
+            domain: domain.map(str::to_string),
+            base_url: None,
+            fallback_url: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn resolves_by_slug_then_by_host() {
+        let tenants = [tenant("acme", Some("go.acme.com")), tenant("globex", None)];
+        let slug = |slug, host| resolve_in(&tenants, slug, host).map(|t| t.map(|t| t.slug));
+
+        assert_eq!(
+            slug(Some("globex"), Some("go.acme.com"))
+                .unwrap()
+                .as_deref(),
+            Some("globex")
+        );
+        assert_eq!(
+            slug(None, Some("GO.acme.com:8443")).unwrap().as_deref(),
+            Some("acme")
+        );
+        assert_eq!(slug(None, Some("localhost:8000")).unwrap(), None);
+        assert_eq!(slug(Some(""), None).unwrap(), None);
+        assert!(matches!(
+            slug(Some("initech"), None),
+            Err(AppError::NotFound(_))
+        ));
+    }
+}
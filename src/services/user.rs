@@ -0,0 +1,56 @@
+// src/services/user.rs - Users and API key authentication
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use validator::Validate;
+
+use crate::{
+    models::{ApiKey, CreateUserDto, User},
+    repositories::UserRepositoryTrait,
+    types::Result,
+    utils::{hash::sha256_hex, id_generator},
+};
+
+#[async_trait]
+pub trait UserServiceTrait {
+    /// Create a user and an initial API key, returning the key secret once
+    async fn create(&self, dto: CreateUserDto) -> Result<(User, ApiKey, String)>;
+    /// Resolve the owner of an API key secret
+    async fn authenticate(&self, api_key: &str) -> Result<Option<User>>;
+}
+
+pub struct UserService<T: UserRepositoryTrait> {
+    repository: Arc<T>,
+}
+
+impl<T: UserRepositoryTrait> UserService<T> {
+    pub fn new(repository: Arc<T>) -> Self {
+        Self { repository }
+    }
+}
+
+#[async_trait]
+impl<T: UserRepositoryTrait + Send + Sync> UserServiceTrait for UserService<T> {
+    async fn create(&self, dto: CreateUserDto) -> Result<(User, ApiKey, String)> {
+        dto.validate()?;
+
+        let user = self.repository.create(&dto).await?;
+
+        // Only the hash is stored; the caller must keep the secret
+        let secret = id_generator::generate_api_key();
+        let api_key = self
+            .repository
+            .create_api_key(&user.id, "default", &sha256_hex(&secret))
+            .await?;
+
+        Ok((user, api_key, secret))
+    }
+
+    async fn authenticate(&self, api_key: &str) -> Result<Option<User>> {
+        let user = self
+            .repository
+            .find_by_api_key_hash(&sha256_hex(api_key))
+            .await?;
+        Ok(user)
+    }
+}
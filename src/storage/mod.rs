@@ -0,0 +1,23 @@
+use async_trait::async_trait;
+
+mod s3;
+
+pub use s3::S3ObjectStore;
+
+use crate::types::Result;
+
+/// Minimal write-only object storage used for backups and archives
+#[async_trait]
+pub trait ObjectStore: Send + Sync {
+    /// Human readable location of the store, used in logs and reports
+    fn location(&self) -> String;
+
+    /// Uploads an object, replacing any existing object with the same key
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        content_type: &str,
+        content_encoding: Option<&str>,
+    ) -> Result<()>;
+}
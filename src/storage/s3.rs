@@ -0,0 +1,220 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use log::debug;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use super::ObjectStore;
+use crate::{config::S3Config, errors::AppError, types::Result, utils::http_client::HttpClient};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Object store speaking the S3 REST API (AWS, MinIO, R2, ...), signed with SigV4
+pub struct S3ObjectStore {
+    client: Arc<HttpClient>,
+    config: S3Config,
+}
+
+impl S3ObjectStore {
+    pub fn new(config: S3Config, client: Arc<HttpClient>) -> Result<Self> {
+        // Fail at startup rather than on the first upload
+        Url::parse(&config.endpoint).map_err(|e| {
+            AppError::Config(format!("Invalid S3 endpoint '{}': {}", config.endpoint, e))
+        })?;
+
+        Ok(Self { client, config })
+    }
+
+    // Build the object URL, path-style (endpoint/bucket/key) or virtual-hosted (bucket.endpoint/key)
+    fn object_url(&self, key: &str) -> Result<Url> {
+        let mut url = Url::parse(&self.config.endpoint)
+            .map_err(|e| AppError::Config(format!("Invalid S3 endpoint: {}", e)))?;
+
+        let path = if self.config.path_style {
+            format!("/{}/{}", self.config.bucket, uri_encode(key))
+        } else {
+            let host = url.host_str().unwrap_or_default().to_string();
+            url.set_host(Some(&format!("{}.{}", self.config.bucket, host)))
+                .map_err(|e| AppError::Config(format!("Invalid S3 bucket host: {}", e)))?;
+            format!("/{}", uri_encode(key))
+        };
+        url.set_path(&path);
+
+        Ok(url)
+    }
+}
+
+#[async_trait]
+impl ObjectStore for S3ObjectStore {
+    fn location(&self) -> String {
+        format!("s3://{}/{}", self.config.bucket, self.config.prefix)
+    }
+
+    async fn put_object(
+        &self,
+        key: &str,
+        body: Vec<u8>,
+        content_type: &str,
+        content_encoding: Option<&str>,
+    ) -> Result<()> {
+        let key = format!("{}{}", self.config.prefix, key);
+        let url = self.object_url(&key)?;
+        let payload_hash = hex::encode(Sha256::digest(&body));
+        let now = Utc::now();
+
+        let authorization = sign_request(
+            &SigningParams {
+                method: "PUT",
+                url: &url,
+                payload_hash: &payload_hash,
+                region: &self.config.region,
+                access_key: &self.config.access_key,
+                secret_key: &self.config.secret_key,
+            },
+            now,
+        );
+
+        let mut request = self
+            .client
+            .put(url.as_str())
+            .header("x-amz-date", amz_date(now))
+            .header("x-amz-content-sha256", &payload_hash)
+            .header("authorization", authorization)
+            .header("content-type", content_type);
+
+        if let Some(encoding) = content_encoding {
+            request = request.header("content-encoding", encoding);
+        }
+
+        let response = self
+            .client
+            .send(request.body(body))
+            .await
+            .map_err(|e| AppError::Internal(format!("S3 upload of '{}' failed: {}", key, e)))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let detail = response.text().await.unwrap_or_default();
+            return Err(AppError::Internal(format!(
+                "S3 upload of '{}' rejected with {}: {}",
+                key, status, detail
+            )));
+        }
+
+        debug!("Uploaded '{}' to {}", key, url);
+        Ok(())
+    }
+}
+
+// Inputs of a SigV4 signature for a request without query parameters
+struct SigningParams<'a> {
+    method: &'a str,
+    url: &'a Url,
+    payload_hash: &'a str,
+    region: &'a str,
+    access_key: &'a str,
+    secret_key: &'a str,
+}
+
+// Compute the SigV4 Authorization header value (signed headers: host, x-amz-content-sha256, x-amz-date)
+fn sign_request(params: &SigningParams, now: DateTime<Utc>) -> String {
+    let amz_date = amz_date(now);
+    let date_stamp = now.format("%Y%m%d").to_string();
+    let host = match params.url.port() {
+        Some(port) => format!("{}:{}", params.url.host_str().unwrap_or_default(), port),
+        None => params.url.host_str().unwrap_or_default().to_string(),
+    };
+
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+        params.method,
+        params.url.path(),
+        host,
+        params.payload_hash,
+        amz_date,
+        signed_headers,
+        params.payload_hash
+    );
+
+    let scope = format!("{}/{}/s3/aws4_request", date_stamp, params.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let key = signing_key(params.secret_key, &date_stamp, params.region, "s3");
+    let signature = hex::encode(hmac(&key, string_to_sign.as_bytes()));
+
+    format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        params.access_key, scope, signed_headers, signature
+    )
+}
+
+// Derive the per-day, per-region, per-service SigV4 signing key
+fn signing_key(secret_key: &str, date_stamp: &str, region: &str, service: &str) -> Vec<u8> {
+    let k_date = hmac(
+        format!("AWS4{}", secret_key).as_bytes(),
+        date_stamp.as_bytes(),
+    );
+    let k_region = hmac(&k_date, region.as_bytes());
+    let k_service = hmac(&k_region, service.as_bytes());
+    hmac(&k_service, b"aws4_request")
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn amz_date(now: DateTime<Utc>) -> String {
+    now.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+// Percent-encode an object key as S3 expects, leaving '/' separators intact
+fn uri_encode(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_key() {
+        // Example from the AWS SigV4 documentation
+        let key = signing_key(
+            "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY",
+            "20120215",
+            "us-east-1",
+            "iam",
+        );
+        assert_eq!(
+            hex::encode(key),
+            "f4780e2d9f65fa895f9c67b32ce1baf0b0d8a43505a000a1a9e090d414db404d"
+        );
+    }
+
+    #[test]
+    fn test_uri_encode() {
+        assert_eq!(
+            uri_encode("backups/urls.ndjson.gz"),
+            "backups/urls.ndjson.gz"
+        );
+        assert_eq!(uri_encode("a b+c"), "a%20b%2Bc");
+    }
+}
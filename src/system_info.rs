@@ -0,0 +1,211 @@
+// src/system_info.rs - What an instance runs and how it's set up, for debugging deployments
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value as JsonValue};
+use url::Url;
+
+use crate::{
+    cache::LinkCacheStats,
+    config::{CodeAlphabet, Config},
+    db::PoolStats,
+    models::JobRecord,
+};
+
+// Shown instead of secrets
+const REDACTED: &str = "<redacted>";
+
+/// The build an instance runs, stamped in by `build.rs`
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub version: String,
+    /// Commit built from, `unknown` when built outside the repository without `GIT_SHA`
+    pub git_sha: &'static str,
+    pub build_time: Option<DateTime<Utc>>,
+    /// Cargo profile, `debug` or `release`
+    pub profile: &'static str,
+}
+
+impl BuildInfo {
+    pub fn current(version: &str) -> Self {
+        Self {
+            version: version.to_string(),
+            git_sha: env!("GIT_SHA"),
+            build_time: env!("BUILD_TIMESTAMP")
+                .parse()
+                .ok()
+                .and_then(|secs| DateTime::from_timestamp(secs, 0)),
+            profile: env!("BUILD_PROFILE"),
+        }
+    }
+}
+
+/// Everything the admin system info reports
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    pub build: BuildInfo,
+    /// Settings worth knowing when debugging, secrets redacted
+    pub config: JsonValue,
+    pub features: BTreeMap<&'static str, bool>,
+    pub db_pool: PoolStats,
+    pub link_cache: LinkCacheStats,
+    pub jobs: Vec<JobRecord>,
+    pub uptime_seconds: u64,
+}
+
+/// The settings of an instance, with secrets replaced by `<redacted>` when set
+pub fn config_summary(config: &Config) -> JsonValue {
+    let alphabet = match &config.short_codes.alphabet {
+        CodeAlphabet::Base62 => "base62",
+        CodeAlphabet::Unambiguous => "unambiguous",
+        CodeAlphabet::Custom(chars) => chars,
+    };
+
+    json!({
+        "app": {
+            "name": config.app.name,
+            "environment": format!("{:?}", config.app.environment).to_lowercase(),
+            "log_level": config.app.log_level,
+            "fallback_url": config.app.fallback_url,
+        },
+        "server": {
+            "host": config.server.host,
+            "port": config.server.port,
+            "workers": config.server.workers,
+        },
+        "db": {
+            "url": redact_url(&config.db.url),
+            "max_connections": config.db.max_connections,
+            "min_connections": config.db.min_connections,
+            "connect_timeout_seconds": config.db.connect_timeout_seconds,
+            "use_migrations": config.db.use_migrations,
+            "schema": config.db.schema,
+        },
+        "auth": {
+            "admin_token": redact(config.auth.admin_token.as_deref()),
+            "signing_keys": config.request_signing.keys.keys().collect::<Vec<_>>(),
+            "signing_required": config.request_signing.required,
+        },
+        "short_codes": {
+            "alphabet": alphabet,
+            "length": config.short_codes.length,
+            "case_insensitive": config.short_codes.case_insensitive,
+        },
+        "cors": {
+            "allowed_origins": config.cors.allowed_origins,
+        },
+        "anonymous_create": {
+            "rate_limit": config.anonymous_create.rate_limit,
+            "window_seconds": config.anonymous_create.window_seconds,
+        },
+        "scanner_guard": {
+            "window_seconds": config.scanner_guard.window_seconds,
+            "tarpit_threshold": config.scanner_guard.tarpit_threshold,
+            "block_threshold": config.scanner_guard.block_threshold,
+            "block_seconds": config.scanner_guard.block_seconds,
+            "honeypot_paths": config.scanner_guard.honeypot_paths,
+        },
+        "link_cache": {
+            "ttl_seconds": config.link_cache.ttl_seconds,
+            "capacity": config.link_cache.capacity,
+        },
+        "pagination": {
+            "default_page_size": config.pagination.default_page_size,
+            "max_page_size": config.pagination.max_page_size,
+        },
+        "trash": {
+            "retention_days": config.trash.retention_days,
+        },
+        "request_timeouts": {
+            "redirect_ms": config.request_timeouts.redirect_ms,
+            "api_ms": config.request_timeouts.api_ms,
+            "admin_ms": config.request_timeouts.admin_ms,
+        },
+        "events": {
+            "publisher": format!("{:?}", config.events.publisher).to_lowercase(),
+            "nats_url": config.events.nats_url.as_deref().map(redact_url),
+            "kafka_rest_url": config.events.kafka_rest_url.as_deref().map(redact_url),
+        },
+        "visit_export": {
+            "sink": format!("{:?}", config.analytics.export.sink).to_lowercase(),
+            "url": config.analytics.export.url.as_deref().map(redact_url),
+            "password": redact(config.analytics.export.password.as_deref()),
+        },
+        "s3": config.s3.as_ref().map(|s3| json!({
+            "endpoint": s3.endpoint,
+            "bucket": s3.bucket,
+            "access_key": s3.access_key,
+            "secret_key": REDACTED,
+        })),
+        "smtp": config.smtp.as_ref().map(|smtp| json!({
+            "host": smtp.host,
+            "port": smtp.port,
+            "username": smtp.username,
+            "password": redact(smtp.password.as_deref()),
+        })),
+        "captcha": {
+            "provider": format!("{:?}", config.captcha.provider).to_lowercase(),
+            "secret": redact(Some(config.captcha.secret.as_str()).filter(|s| !s.is_empty())),
+        },
+        "job_schedules": config.jobs.schedules,
+    })
+}
+
+/// Which optional parts of the service are switched on
+pub fn feature_flags(config: &Config) -> BTreeMap<&'static str, bool> {
+    BTreeMap::from([
+        ("admin_dashboard", config.auth.admin_dashboard),
+        ("anomaly_detection", config.anomaly.enabled),
+        ("backups", config.backup.enabled),
+        ("captcha", config.captcha.enabled),
+        ("csrf_protection", config.csrf.enabled),
+        ("digests", config.notifications.digest_enabled),
+        ("expiry_notifications", config.notifications.expiry_enabled),
+        ("https_upgrade", config.https_upgrade.enabled),
+        ("link_archive", config.link_archive.enabled),
+        ("link_cache", config.link_cache.enabled),
+        ("link_claims", config.link_claims.ttl_days > 0),
+        ("link_cleanup", config.link_cleanup.enabled),
+        ("link_rot_check", config.link_rot.enabled),
+        ("oauth_github", config.oauth.github.is_some()),
+        ("oauth_google", config.oauth.google.is_some()),
+        ("raw_visits", config.analytics.store_raw_visits),
+        ("reverse_proxy", config.reverse_proxy.enabled),
+        ("scanner_guard", config.scanner_guard.enabled),
+        ("trash_purge", config.trash.purge_enabled),
+        ("visit_retention", config.analytics.retention_enabled),
+    ])
+}
+
+fn redact(secret: Option<&str>) -> Option<&'static str> {
+    secret.map(|_| REDACTED)
+}
+
+// A URL without the password it may carry, like a database URL
+fn redact_url(raw: &str) -> String {
+    match Url::parse(raw) {
+        Ok(url) if url.password().is_some() => {
+            let password = url.password().unwrap_or_default();
+            raw.replacen(&format!(":{}@", password), &format!(":{}@", REDACTED), 1)
+        }
+        Ok(_) => raw.to_string(),
+        // Not worth the risk of showing something unparsed
+        Err(_) => REDACTED.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_url() {
+        assert_eq!(
+            redact_url("postgres://app:s3cret@db:5432/links"),
+            "postgres://app:<redacted>@db:5432/links"
+        );
+        assert_eq!(redact_url("nats://nats:4222"), "nats://nats:4222");
+        assert_eq!(redact_url("//app:s3cret@db"), REDACTED);
+    }
+}
@@ -1,7 +1,22 @@
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 use std::time::Instant;
 
-use crate::{db::{Database, DatabaseHealth}, errors::AppError};
+use crate::{
+    cache::{LinkCache, LinkCacheStats},
+    db::{Database, DatabaseHealth},
+    errors::AppError,
+    events::{EventBus, EventBusStats},
+    handlers::{JobSchedulerType, ShortenedUrlServiceType},
+    jobs::JobLockStats,
+    services::CodeGenerationStats,
+    utils::{
+        circuit_breaker::{BreakerStatus, CircuitBreaker},
+        http_client::{HttpClient, HttpClientStats},
+        maintenance::MaintenanceMode,
+    },
+    warehouse::{VisitExportStats, VisitExporter},
+};
 
 #[derive(Serialize, Deserialize)]
 pub struct ResponsePayload {
@@ -14,12 +29,28 @@ pub struct HealthStatus {
     pub status: String,
     pub version: String,
     pub db_health: Option<DatabaseHealth>,
+    pub db_circuit_breaker: BreakerStatus,
+    pub link_cache: LinkCacheStats,
+    pub http_client: HttpClientStats,
+    pub short_codes: CodeGenerationStats,
+    pub job_locks: JobLockStats,
+    pub events: EventBusStats,
+    /// Only reported while visits are exported to an analytics warehouse
+    pub visit_export: Option<VisitExportStats>,
     pub uptime_seconds: u64,
 }
 
 pub struct AppState {
     pub start_time: Instant,
     pub db: Database,
+    pub db_breaker: Arc<CircuitBreaker>,
+    pub link_cache: Arc<LinkCache>,
+    pub http_client: Arc<HttpClient>,
+    pub shortened_urls: Arc<ShortenedUrlServiceType>,
+    pub job_scheduler: Arc<JobSchedulerType>,
+    pub event_bus: Arc<EventBus>,
+    pub visit_exporter: Option<Arc<VisitExporter>>,
+    pub maintenance: Arc<MaintenanceMode>,
     pub version: String,
 }
 
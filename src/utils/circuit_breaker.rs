@@ -0,0 +1,173 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+
+/// Whether calls are let through to the protected dependency
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BreakerState {
+    /// Calls go through, failures are counted
+    Closed,
+    /// Calls are refused until the cool-down ends
+    Open,
+    /// One probe call is let through to test whether the dependency recovered
+    HalfOpen,
+}
+
+/// Breaker state as reported by the health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakerStatus {
+    pub state: BreakerState,
+    pub consecutive_failures: u32,
+    pub opened_at: Option<DateTime<Utc>>,
+}
+
+struct BreakerInner {
+    state: BreakerState,
+    failures: u32,
+    opened: Option<(Instant, DateTime<Utc>)>,
+    probe_started: Option<Instant>,
+}
+
+/// Stops calling a dependency after repeated failures, so callers fail fast instead of
+/// piling up on timeouts
+///
+/// After `failure_threshold` consecutive failures the breaker opens for `open_for`.
+/// Then a single probe is let through: success closes the breaker, failure opens it again.
+pub struct CircuitBreaker {
+    name: &'static str,
+    failure_threshold: u32,
+    open_for: Duration,
+    inner: Mutex<BreakerInner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            name,
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+            inner: Mutex::new(BreakerInner {
+                state: BreakerState::Closed,
+                failures: 0,
+                opened: None,
+                probe_started: None,
+            }),
+        }
+    }
+
+    /// Whether a call may go through now; callers must report its outcome
+    pub fn try_acquire(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        match inner.state {
+            BreakerState::Closed => true,
+            BreakerState::Open => {
+                let cooled_down = inner
+                    .opened
+                    .is_some_and(|(at, _)| at.elapsed() >= self.open_for);
+                if cooled_down {
+                    inner.state = BreakerState::HalfOpen;
+                    inner.probe_started = Some(Instant::now());
+                }
+                cooled_down
+            }
+            // A probe that never reported back (e.g. a cancelled request) is replaced
+            BreakerState::HalfOpen => match inner.probe_started {
+                Some(started) if started.elapsed() < self.open_for => false,
+                _ => {
+                    inner.probe_started = Some(Instant::now());
+                    true
+                }
+            },
+        }
+    }
+
+    /// Reports a call that reached the dependency
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        if inner.state != BreakerState::Closed {
+            info!("{} circuit breaker closed, calls resume", self.name);
+        }
+        inner.state = BreakerState::Closed;
+        inner.failures = 0;
+        inner.opened = None;
+        inner.probe_started = None;
+    }
+
+    /// Reports a call that failed because the dependency is unreachable
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        inner.failures = inner.failures.saturating_add(1);
+        inner.probe_started = None;
+
+        let trips = match inner.state {
+            BreakerState::Closed => inner.failures >= self.failure_threshold,
+            BreakerState::HalfOpen => true,
+            BreakerState::Open => false,
+        };
+        if trips {
+            warn!(
+                "{} circuit breaker opened after {} consecutive failures, refusing calls for {:?}",
+                self.name, inner.failures, self.open_for
+            );
+            inner.state = BreakerState::Open;
+            inner.opened = Some((Instant::now(), Utc::now()));
+        }
+    }
+
+    pub fn status(&self) -> BreakerStatus {
+        let inner = self.inner.lock().unwrap_or_else(|e| e.into_inner());
+        BreakerStatus {
+            state: inner.state,
+            consecutive_failures: inner.failures,
+            opened_at: inner.opened.map(|(_, at)| at),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.status().state != BreakerState::Closed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_and_recovers_through_a_probe() {
+        let breaker = CircuitBreaker::new("test", 2, Duration::ZERO);
+
+        assert!(breaker.try_acquire());
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, BreakerState::Closed);
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, BreakerState::Open);
+
+        // The cool-down is over, so one probe goes through
+        assert!(breaker.try_acquire());
+        assert_eq!(breaker.status().state, BreakerState::HalfOpen);
+
+        // A failed probe opens the breaker again
+        breaker.record_failure();
+        assert_eq!(breaker.status().state, BreakerState::Open);
+
+        assert!(breaker.try_acquire());
+        breaker.record_success();
+        let status = breaker.status();
+        assert_eq!(status.state, BreakerState::Closed);
+        assert_eq!(status.consecutive_failures, 0);
+        assert!(status.opened_at.is_none());
+    }
+
+    #[test]
+    fn refuses_calls_while_open() {
+        let breaker = CircuitBreaker::new("test", 1, Duration::from_secs(60));
+
+        breaker.record_failure();
+        assert!(!breaker.try_acquire());
+        assert!(breaker.is_open());
+    }
+}
@@ -0,0 +1,354 @@
+use std::time::Duration;
+
+use actix_web::web::Bytes;
+use futures::{future, Stream, StreamExt};
+use log::debug;
+use reqwest::{
+    header::{CONTENT_DISPOSITION, CONTENT_LENGTH, CONTENT_TYPE},
+    Response,
+};
+use url::{Host, Url};
+
+use crate::{
+    config::{FileProxyConfig, HttpClientConfig},
+    types::Result,
+    utils::{http_client::HttpClient, network::is_public},
+};
+
+// Headers of the destination's answer passed on to visitors of proxied links, besides
+// the length which the response is sized with
+const PASSED_HEADERS: [&str; 3] = ["content-type", "last-modified", "etag"];
+
+/// What is known about a destination file before showing it
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileInfo {
+    pub file_name: String,
+    /// Media type without parameters, like `application/pdf`
+    pub content_type: Option<String>,
+    pub size: Option<u64>,
+}
+
+impl FileInfo {
+    /// Whether the destination is a web page rather than a file
+    pub fn is_page(&self) -> bool {
+        matches!(
+            self.content_type.as_deref(),
+            Some("text/html") | Some("application/xhtml+xml")
+        )
+    }
+}
+
+/// Serves link destinations that are files from the short URL itself
+///
+/// Proxied files are streamed through as they arrive, and only when the destination
+/// announces a size under the limit. Anything else, web pages included, is left to a
+/// regular redirect.
+///
+/// Anyone can set up a link, so only public addresses are requested and redirects aren't
+/// followed: a link can't be used to reach this host or its private network.
+pub struct FileProxy {
+    client: HttpClient,
+    max_bytes: u64,
+    timeout: Duration,
+}
+
+impl FileProxy {
+    pub fn new(config: &FileProxyConfig, client_config: &HttpClientConfig) -> Result<Self> {
+        Ok(Self {
+            client: HttpClient::public_only(client_config)?,
+            max_bytes: config.max_bytes,
+            timeout: Duration::from_millis(config.timeout_ms),
+        })
+    }
+
+    /// Whether a destination may be requested at all: an http(s) URL not naming an
+    /// internal address. Host names are checked as they're resolved.
+    pub fn allows(&self, destination: &str) -> bool {
+        let Ok(url) = Url::parse(destination) else {
+            return false;
+        };
+        let public = match url.host() {
+            Some(Host::Ipv4(ip)) => is_public(ip.into()),
+            Some(Host::Ipv6(ip)) => is_public(ip.into()),
+            Some(Host::Domain(_)) => true,
+            None => false,
+        };
+        public && matches!(url.scheme(), "http" | "https")
+    }
+
+    /// Request the destination file, if it can be proxied, along with its size
+    ///
+    /// Returns `None` when the destination fails, is a web page, doesn't say how large it
+    /// is or is larger than allowed.
+    pub async fn fetch(&self, destination: &str) -> Option<(Response, u64)> {
+        if !self.allows(destination) {
+            debug!("Not proxying '{}': not a public address", destination);
+            return None;
+        }
+        let request = self.client.get(destination).timeout(self.timeout);
+        let response = match self.client.send_with_retries(request, 0).await {
+            Ok(response) if response.status().is_success() => response,
+            Ok(response) => {
+                debug!(
+                    "Not proxying '{}': answered {}",
+                    destination,
+                    response.status()
+                );
+                return None;
+            }
+            Err(e) => {
+                debug!("Not proxying '{}': {}", destination, e);
+                return None;
+            }
+        };
+
+        let info = file_info(destination, &response);
+        match info.size {
+            _ if info.is_page() => None,
+            Some(size) if size <= self.max_bytes => Some((response, size)),
+            size => {
+                debug!(
+                    "Not proxying '{}': size {:?} over the limit",
+                    destination, size
+                );
+                None
+            }
+        }
+    }
+
+    /// The body of a proxied file, cut off once it goes past the size limit
+    ///
+    /// Guards against destinations sending more than the size they announced.
+    pub fn body(&self, response: Response) -> impl Stream<Item = reqwest::Result<Bytes>> {
+        capped_body(response, self.max_bytes)
+    }
+
+    /// Look up the type and size of the destination file without downloading it
+    ///
+    /// Destinations that don't answer HEAD are described from their URL alone.
+    pub async fn probe(&self, destination: &str) -> FileInfo {
+        let request = self.client.head(destination).timeout(self.timeout);
+        let response = if self.allows(destination) {
+            self.client.send_with_retries(request, 0).await.ok()
+        } else {
+            None
+        };
+        match response {
+            Some(response) if response.status().is_success() => file_info(destination, &response),
+            _ => FileInfo {
+                file_name: file_name(destination),
+                content_type: guess_content_type(destination).map(String::from),
+                size: None,
+            },
+        }
+    }
+}
+
+/// The body of a response, ending once more than `max_bytes` have been read
+pub fn capped_body(
+    response: Response,
+    max_bytes: u64,
+) -> impl Stream<Item = reqwest::Result<Bytes>> {
+    let mut read = 0u64;
+    response.bytes_stream().take_while(move |chunk| {
+        if let Ok(chunk) = chunk {
+            read += chunk.len() as u64;
+        }
+        future::ready(read <= max_bytes)
+    })
+}
+
+/// Headers to answer a proxied file with: the destination's own, plus a
+/// `Content-Disposition` naming the file when the destination didn't send one
+pub fn proxied_headers(destination: &str, response: &Response) -> Vec<(String, String)> {
+    let mut headers = PASSED_HEADERS
+        .iter()
+        .filter_map(|name| {
+            let value = response.headers().get(*name)?.to_str().ok()?;
+            Some((name.to_string(), value.to_string()))
+        })
+        .collect::<Vec<_>>();
+
+    let disposition = match response
+        .headers()
+        .get(CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some(disposition) => disposition.to_string(),
+        None => format!("inline; filename=\"{}\"", file_name(destination)),
+    };
+    headers.push((CONTENT_DISPOSITION.to_string(), disposition));
+    // The file is served from the short link's origin, so nothing in it may run there
+    headers.push(("content-security-policy".to_string(), "sandbox".to_string()));
+    headers.push(("x-content-type-options".to_string(), "nosniff".to_string()));
+    headers
+}
+
+fn file_info(destination: &str, response: &Response) -> FileInfo {
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .map(|v| v.trim().to_ascii_lowercase())
+        .filter(|v| !v.is_empty() && v != "application/octet-stream")
+        .or_else(|| guess_content_type(destination).map(String::from));
+    let size = response
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok());
+
+    FileInfo {
+        file_name: file_name(destination),
+        content_type,
+        size,
+    }
+}
+
+/// The name of the file a URL points to, taken from the last segment of its path
+///
+/// Characters that would break out of a quoted header value are dropped.
+pub fn file_name(destination: &str) -> String {
+    let name = Url::parse(destination)
+        .ok()
+        .and_then(|url| {
+            url.path_segments()?
+                .rfind(|segment| !segment.is_empty())
+                .map(String::from)
+        })
+        .unwrap_or_default();
+    let name = name
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"' && *c != '\\')
+        .collect::<String>();
+
+    if name.is_empty() {
+        "download".to_string()
+    } else {
+        name
+    }
+}
+
+// Media type of common file formats, from the extension in the URL's path
+fn guess_content_type(destination: &str) -> Option<&'static str> {
+    let path = Url::parse(destination).ok()?.path().to_ascii_lowercase();
+    let (_, extension) = path.rsplit('/').next()?.rsplit_once('.')?;
+    let content_type = match extension {
+        "pdf" => "application/pdf",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "txt" => "text/plain",
+        "csv" => "text/csv",
+        "zip" => "application/zip",
+        "html" | "htm" => "text/html",
+        _ => return None,
+    };
+    Some(content_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    fn client_config() -> HttpClientConfig {
+        HttpClientConfig {
+            timeout_ms: 1000,
+            connect_timeout_ms: 1000,
+            pool_max_idle_per_host: 1,
+            max_retries: 0,
+            retry_base_delay_ms: 10,
+            retry_max_delay_ms: 10,
+        }
+    }
+
+    // A server on this host answering every request with a small PDF
+    async fn local_file_server() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0; 1024];
+                let _ = socket.read(&mut request).await;
+                let _ = socket
+                    .write_all(
+                        b"HTTP/1.1 200 OK\r\ncontent-type: application/pdf\r\n\
+                          content-length: 4\r\nconnection: close\r\n\r\n%PDF",
+                    )
+                    .await;
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn test_internal_destinations_are_refused() {
+        let config = FileProxyConfig {
+            max_bytes: 1024,
+            timeout_ms: 1000,
+        };
+        let proxy = FileProxy::new(&config, &client_config()).unwrap();
+        let port = local_file_server().await;
+
+        // The server answers anyone else
+        let file = format!("http://127.0.0.1:{}/report.pdf", port);
+        let open = HttpClient::new(&client_config()).unwrap();
+        assert!(open
+            .send(open.get(&file))
+            .await
+            .unwrap()
+            .status()
+            .is_success());
+
+        for destination in [
+            file,
+            format!("http://localhost:{}/report.pdf", port),
+            format!("http://[::ffff:127.0.0.1]:{}/report.pdf", port),
+            "http://169.254.169.254/latest/meta-data/".to_string(),
+            "http://10.0.0.1/report.pdf".to_string(),
+        ] {
+            assert!(proxy.fetch(&destination).await.is_none(), "{}", destination);
+            assert_eq!(
+                proxy.probe(&destination).await.size,
+                None,
+                "{}",
+                destination
+            );
+        }
+
+        assert!(proxy.allows("https://cdn.example.com/report.pdf"));
+        assert!(!proxy.allows("http://[::1]/report.pdf"));
+        assert!(!proxy.allows("file:///etc/passwd"));
+    }
+
+    #[test]
+    fn test_file_name_and_type_from_url() {
+        assert_eq!(
+            file_name("https://cdn.example.com/docs/report.pdf?v=2"),
+            "report.pdf"
+        );
+        assert_eq!(file_name("https://cdn.example.com/a/b/"), "b");
+        assert_eq!(file_name("https://example.com/"), "download");
+
+        assert_eq!(
+            guess_content_type("https://cdn.example.com/img/logo.PNG"),
+            Some("image/png")
+        );
+        assert_eq!(
+            guess_content_type("https://example.com/archive.tar.xz"),
+            None
+        );
+        assert_eq!(guess_content_type("https://example.com/v1.2/about"), None);
+    }
+}
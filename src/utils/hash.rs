@@ -1,25 +1,5 @@
 use rand::{rng, Rng};
-
-/// Converts a number to base62 representation (0-9, A-Z, a-z)
-pub fn encode_base62(mut num: u64) -> String {
-    const CHARSET: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
-    const BASE: u64 = 62;
-
-    if num == 0 {
-        return "0".to_string();
-    }
-
-    let mut result = Vec::new();
-
-    while num > 0 {
-        result.push(CHARSET[(num % BASE) as usize]);
-        num /= BASE;
-    }
-
-    // Reverse and convert to string
-    result.reverse();
-    String::from_utf8(result).unwrap()
-}
+use sha2::{Digest, Sha256};
 
 /// Generates a random base62 character
 pub fn random_base62_char() -> char {
@@ -27,3 +7,8 @@ pub fn random_base62_char() -> char {
     let idx = rng().random_range(0..CHARSET.len());
     CHARSET[idx] as char
 }
+
+/// Hex-encoded SHA-256 digest, used to store secrets such as API keys
+pub fn sha256_hex(input: &str) -> String {
+    hex::encode(Sha256::digest(input.as_bytes()))
+}
@@ -0,0 +1,257 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use std::time::{Duration, Instant};
+
+use log::debug;
+use rand::Rng;
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    header::RETRY_AFTER,
+    redirect::Policy,
+    Client, ClientBuilder, Method, RequestBuilder, Response, StatusCode,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::HttpClientConfig, errors::AppError, utils::network::is_public};
+
+/// Outbound request counts since startup, as reported by the health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpClientStats {
+    pub requests: u64,
+    /// Attempts repeated after a transient failure
+    pub retries: u64,
+    /// Requests that failed without any response, after their retries
+    pub failures: u64,
+    pub average_latency_ms: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    requests: AtomicU64,
+    retries: AtomicU64,
+    failures: AtomicU64,
+    latency_ms: AtomicU64,
+}
+
+/// Shared client for calls to other services (OAuth providers, webhooks, S3, ...)
+///
+/// Connections are pooled across all callers. Requests that fail to connect or time out are
+/// retried with exponential backoff and jitter; idempotent requests are also retried on 429,
+/// 502, 503 and 504, waiting for `Retry-After` when given. Callers set per-call timeouts on
+/// the request builder.
+pub struct HttpClient {
+    client: Client,
+    config: HttpClientConfig,
+    counters: Counters,
+}
+
+impl HttpClient {
+    pub fn new(config: &HttpClientConfig) -> Result<Self, AppError> {
+        Self::build(config, Client::builder().redirect(Policy::default()))
+    }
+
+    /// A client handing redirects back to the caller instead of following them
+    pub fn without_redirects(config: &HttpClientConfig) -> Result<Self, AppError> {
+        Self::build(config, Client::builder().redirect(Policy::none()))
+    }
+
+    /// A client for URLs anyone can choose, which only connects to public addresses
+    ///
+    /// Host names resolving to this host, a private network or a link-local address fail
+    /// to resolve, and redirects aren't followed. Callers still have to refuse URLs naming
+    /// such an address directly, see [`is_public`].
+    pub fn public_only(config: &HttpClientConfig) -> Result<Self, AppError> {
+        let builder = Client::builder()
+            .redirect(Policy::none())
+            .dns_resolver(Arc::new(PublicResolver));
+        Self::build(config, builder)
+    }
+
+    fn build(config: &HttpClientConfig, builder: ClientBuilder) -> Result<Self, AppError> {
+        let client = builder
+            .timeout(Duration::from_millis(config.timeout_ms))
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms))
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .user_agent(concat!("url-shortener/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .map_err(|e| AppError::Config(format!("Could not build the HTTP client: {}", e)))?;
+
+        Ok(Self {
+            client,
+            config: config.clone(),
+            counters: Counters::default(),
+        })
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder {
+        self.client.get(url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder {
+        self.client.post(url)
+    }
+
+    pub fn put(&self, url: &str) -> RequestBuilder {
+        self.client.put(url)
+    }
+
+    pub fn head(&self, url: &str) -> RequestBuilder {
+        self.client.head(url)
+    }
+
+    /// Send a request, retrying as many times as configured
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        self.send_with_retries(request, self.config.max_retries)
+            .await
+    }
+
+    /// Send a request, retrying transient failures up to `max_retries` times
+    ///
+    /// Returns the last response or error once retries are exhausted. Requests with a
+    /// streaming body can't be repeated and are sent once.
+    pub async fn send_with_retries(
+        &self,
+        request: RequestBuilder,
+        max_retries: u32,
+    ) -> reqwest::Result<Response> {
+        let request = request.build()?;
+        let idempotent = matches!(
+            *request.method(),
+            Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS
+        );
+        let started = Instant::now();
+        self.counters.requests.fetch_add(1, Ordering::Relaxed);
+
+        let mut attempt = 0;
+        let mut pending = Some(request);
+        let result = loop {
+            let request = pending
+                .take()
+                .expect("a request is pending on every attempt");
+            let retry = if attempt < max_retries {
+                request.try_clone()
+            } else {
+                None
+            };
+            let url = request.url().clone();
+
+            let result = self.client.execute(request).await;
+            let delay = match (&result, retry.is_some()) {
+                (_, false) => None,
+                (Err(e), true) if e.is_connect() || e.is_timeout() => Some(self.backoff(attempt)),
+                (Ok(response), true) if idempotent && is_transient(response.status()) => {
+                    Some(retry_after(response).unwrap_or_else(|| self.backoff(attempt)))
+                }
+                _ => None,
+            };
+
+            let Some(delay) = delay else {
+                break result;
+            };
+            debug!("Retrying {} in {:?} (attempt {})", url, delay, attempt + 1);
+            self.counters.retries.fetch_add(1, Ordering::Relaxed);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+            pending = retry;
+        };
+
+        if result.is_err() {
+            self.counters.failures.fetch_add(1, Ordering::Relaxed);
+        }
+        self.counters
+            .latency_ms
+            .fetch_add(started.elapsed().as_millis() as u64, Ordering::Relaxed);
+        result
+    }
+
+    pub fn stats(&self) -> HttpClientStats {
+        let requests = self.counters.requests.load(Ordering::Relaxed);
+        HttpClientStats {
+            requests,
+            retries: self.counters.retries.load(Ordering::Relaxed),
+            failures: self.counters.failures.load(Ordering::Relaxed),
+            average_latency_ms: self
+                .counters
+                .latency_ms
+                .load(Ordering::Relaxed)
+                .checked_div(requests)
+                .unwrap_or(0),
+        }
+    }
+
+    // Exponential backoff with full jitter, capped at the configured maximum
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .config
+            .retry_base_delay_ms
+            .saturating_mul(1 << attempt.min(16))
+            .min(self.config.retry_max_delay_ms);
+        Duration::from_millis(rand::rng().random_range(0..=ceiling))
+    }
+}
+
+fn is_transient(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+// Honor a `Retry-After` given in seconds, as long as it's reasonably short
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|seconds| *seconds <= 30)
+        .map(Duration::from_secs)
+}
+
+// Resolves host names with the system resolver, leaving out every address that isn't public
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let host = name.as_str().to_string();
+            let addrs = tokio::net::lookup_host((host.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect::<Vec<_>>();
+            if addrs.is_empty() {
+                return Err(format!("'{}' has no public address", host).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_stays_within_bounds() {
+        let client = HttpClient::new(&HttpClientConfig {
+            timeout_ms: 1000,
+            connect_timeout_ms: 1000,
+            pool_max_idle_per_host: 4,
+            max_retries: 2,
+            retry_base_delay_ms: 100,
+            retry_max_delay_ms: 1000,
+        })
+        .unwrap();
+
+        for attempt in 0..40 {
+            let ceiling = (100u64 << attempt.min(16)).min(1000);
+            assert!(client.backoff(attempt) <= Duration::from_millis(ceiling));
+        }
+        assert_eq!(client.stats().requests, 0);
+    }
+}
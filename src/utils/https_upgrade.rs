@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::debug;
+use url::Url;
+
+use crate::{config::HttpsUpgradeConfig, utils::http_client::HttpClient};
+
+// Hosts remembered before outcomes past their TTL are swept out
+const SWEEP_THRESHOLD: usize = 10_000;
+
+/// Rewrites http:// destinations to https:// for hosts that answer over HTTPS
+///
+/// A host is checked with a single HEAD request to its root; any response counts as
+/// support, since it took a working TLS handshake to get one. The outcome is kept in
+/// memory for the configured TTL, so links to the same host only cost one check.
+pub struct HttpsUpgrader {
+    client: Arc<HttpClient>,
+    by_default: bool,
+    timeout: Duration,
+    ttl: Duration,
+    hosts: Mutex<HashMap<String, (bool, Instant)>>,
+}
+
+impl HttpsUpgrader {
+    pub fn new(config: &HttpsUpgradeConfig, client: Arc<HttpClient>) -> Self {
+        Self {
+            client,
+            by_default: config.enabled,
+            timeout: Duration::from_millis(config.check_timeout_ms),
+            ttl: Duration::from_secs(config.cache_ttl_seconds),
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether links that didn't choose with `upgrade_https` are upgraded
+    pub fn by_default(&self) -> bool {
+        self.by_default
+    }
+
+    /// The https:// form of an http:// URL, if its host supports HTTPS
+    ///
+    /// URLs already on HTTPS, and ones with an explicit port, are left alone.
+    pub async fn upgrade(&self, url: &str) -> Option<String> {
+        let upgraded = https_form(url)?;
+        let host = upgraded.host_str()?.to_ascii_lowercase();
+        self.supports_https(&host)
+            .await
+            .then(|| upgraded.to_string())
+    }
+
+    async fn supports_https(&self, host: &str) -> bool {
+        if let Some(supported) = self.cached(host) {
+            return supported;
+        }
+
+        let request = self
+            .client
+            .head(&format!("https://{}/", host))
+            .timeout(self.timeout);
+        let supported = match self.client.send_with_retries(request, 0).await {
+            Ok(_) => true,
+            Err(e) => {
+                debug!("{} doesn't look like it serves HTTPS: {}", host, e);
+                false
+            }
+        };
+
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        if hosts.len() >= SWEEP_THRESHOLD {
+            hosts.retain(|_, (_, checked)| checked.elapsed() < self.ttl);
+        }
+        hosts.insert(host.to_string(), (supported, Instant::now()));
+        supported
+    }
+
+    fn cached(&self, host: &str) -> Option<bool> {
+        let hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        hosts
+            .get(host)
+            .filter(|(_, checked)| checked.elapsed() < self.ttl)
+            .map(|(supported, _)| *supported)
+    }
+}
+
+// The URL with its scheme switched to https, for plain http URLs on the default port
+fn https_form(url: &str) -> Option<Url> {
+    let mut parsed = Url::parse(url).ok()?;
+    if parsed.scheme() != "http" || parsed.port().is_some() {
+        return None;
+    }
+    parsed.set_scheme("https").ok()?;
+    Some(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_https_form() {
+        let upgraded = |url: &str| https_form(url).map(|u| u.to_string());
+
+        assert_eq!(
+            upgraded("http://Example.com/a?b=1#c").as_deref(),
+            Some("https://example.com/a?b=1#c")
+        );
+        // The default port isn't kept by the parser, so it doesn't count as explicit
+        assert_eq!(
+            upgraded("http://example.com:80/").as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(upgraded("http://example.com:8080/"), None);
+        assert_eq!(upgraded("https://example.com/"), None);
+        assert_eq!(upgraded("not a url"), None);
+    }
+}
@@ -1,25 +1,91 @@
 use rand::{rng, Rng};
+use sha2::{Digest, Sha256};
 
-use super::hash::{encode_base62, random_base62_char};
+use super::hash::random_base62_char;
 
-/// Generates a unique short ID for URL shortening using base62 encoding of random values
-pub fn generate_short_id(length: usize) -> String {
-    // Generate a random 64-bit number
-    let random_id: u64 = rng().random();
+/// Digits and ASCII letters in both cases
+pub const BASE62_ALPHABET: &str = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
 
-    // Encode it using base62
-    let mut encoded = encode_base62(random_id);
+/// Base62 without characters easily mistaken for one another (0/O/o, 1/l/I/i)
+pub const UNAMBIGUOUS_ALPHABET: &str = "23456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghjkmnpqrstuvwxyz";
 
-    // Ensure the ID is of desired length
-    // If too short, pad with additional random characters
-    while encoded.len() < length {
-        encoded.push(random_base62_char());
-    }
+/// Generates a random short ID for URL shortening from the given alphabet
+pub fn generate_short_id(length: usize, alphabet: &str) -> String {
+    let chars = alphabet.chars().collect::<Vec<_>>();
+    let mut rng = rng();
+    (0..length)
+        .map(|_| chars[rng.random_range(0..chars.len())])
+        .collect()
+}
 
-    // If too long, truncate
-    if encoded.len() > length {
-        encoded.truncate(length);
-    }
+/// Derives a short ID from the SHA-256 digest of `input`, written with the given alphabet
+///
+/// The same input always gives the same ID, and a longer ID starts with the shorter one, so a
+/// taken ID can be extended a character at a time.
+pub fn hash_short_id(input: &str, length: usize, alphabet: &str) -> String {
+    let chars = alphabet.chars().collect::<Vec<_>>();
+    let base = chars.len() as u32;
+    let mut digest = Sha256::digest(input.as_bytes());
+    (0..length)
+        .map(|_| {
+            // Divide the digest, read as a big-endian number, by the base; the remainder is
+            // the next digit
+            let mut remainder = 0u32;
+            for byte in digest.iter_mut() {
+                let value = (remainder << 8) | *byte as u32;
+                *byte = (value / base) as u8;
+                remainder = value % base;
+            }
+            chars[remainder as usize]
+        })
+        .collect()
+}
 
-    encoded
+/// Prefix of service token secrets, telling them apart from API keys
+pub const SERVICE_TOKEN_PREFIX: &str = "ust_";
+
+/// Generates a new API key secret (prefixed so leaked keys are easy to recognise)
+pub fn generate_api_key() -> String {
+    let secret: String = (0..40).map(|_| random_base62_char()).collect();
+    format!("usk_{}", secret)
+}
+
+/// Generates a new link claim token, handed to whoever created a link without signing in
+pub fn generate_claim_token() -> String {
+    let secret: String = (0..40).map(|_| random_base62_char()).collect();
+    format!("ucl_{}", secret)
+}
+
+/// Generates a new CSRF token for a browser session
+pub fn generate_csrf_token() -> String {
+    (0..32).map(|_| random_base62_char()).collect()
+}
+
+/// Generates a new service token secret
+pub fn generate_service_token() -> String {
+    let secret: String = (0..40).map(|_| random_base62_char()).collect();
+    format!("{}{}", SERVICE_TOKEN_PREFIX, secret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_short_id_extends_itself() {
+        let short = hash_short_id("https://example.com/", 6, BASE62_ALPHABET);
+        let long = hash_short_id("https://example.com/", 8, BASE62_ALPHABET);
+        assert_eq!(
+            short,
+            hash_short_id("https://example.com/", 6, BASE62_ALPHABET)
+        );
+        assert!(long.starts_with(&short));
+        assert_ne!(
+            short,
+            hash_short_id("https://example.org/", 6, BASE62_ALPHABET)
+        );
+        assert!(hash_short_id("https://example.com/", 32, "ab")
+            .chars()
+            .all(|c| c == 'a' || c == 'b'));
+    }
 }
@@ -0,0 +1,65 @@
+// src/utils/logger.rs - A logger whose filter can change while the server runs
+use std::sync::RwLock;
+
+use env_logger::{Builder, Env, Logger};
+use log::{Log, Metadata, Record, SetLoggerError};
+
+use crate::config::{Config, Environment};
+
+/// The global logger, wrapping `env_logger`, which keeps its filter once installed
+pub struct ReloadableLogger {
+    inner: RwLock<Logger>,
+}
+
+impl ReloadableLogger {
+    /// Install as the global logger, filtering as `config` asks
+    pub fn install(config: &Config) -> Result<&'static Self, SetLoggerError> {
+        let logger = build(config);
+        let filter = logger.filter();
+        let reloadable: &'static Self = Box::leak(Box::new(Self {
+            inner: RwLock::new(logger),
+        }));
+        log::set_logger(reloadable)?;
+        log::set_max_level(filter);
+        Ok(reloadable)
+    }
+
+    /// Filter as `config` asks from now on
+    pub fn reload(&self, config: &Config) {
+        let logger = build(config);
+        log::set_max_level(logger.filter());
+        *self.inner.write().unwrap_or_else(|e| e.into_inner()) = logger;
+    }
+
+    fn logger(&self) -> std::sync::RwLockReadGuard<'_, Logger> {
+        self.inner.read().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Log for ReloadableLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.logger().enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        self.logger().log(record)
+    }
+
+    fn flush(&self) {
+        self.logger().flush()
+    }
+}
+
+fn build(config: &Config) -> Logger {
+    // Configure log level based on environment and config
+    let log_level = match config.app.environment {
+        Environment::Development => config.app.log_level.clone(),
+        Environment::Testing => "debug,actix_web=info".to_string(),
+        Environment::Production => "info,actix_web=warn".to_string(),
+    };
+
+    let env = Env::default()
+        .filter_or("RUST_LOG", log_level)
+        .write_style_or("RUST_LOG_STYLE", "always");
+    Builder::from_env(env).build()
+}
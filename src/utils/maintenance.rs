@@ -0,0 +1,97 @@
+// src/utils/maintenance.rs - Switching an instance to maintenance while operators work on the
+// database
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::config::MaintenanceConfig;
+
+/// Whether an instance is in maintenance, and what API clients are told meanwhile
+#[derive(Debug, Clone, Serialize)]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    pub message: String,
+    /// When maintenance started, `None` while it's off
+    pub since: Option<DateTime<Utc>>,
+}
+
+/// Maintenance mode of an instance, shared by its workers
+///
+/// While it's on, the API answers 503 except for admin endpoints, redirects keep serving
+/// without writing to the database and scheduled jobs wait. It's kept in memory, so every
+/// instance is switched on its own, and a restart goes back to `MAINTENANCE_MODE`.
+pub struct MaintenanceMode {
+    default_message: String,
+    status: RwLock<MaintenanceStatus>,
+}
+
+impl MaintenanceMode {
+    pub fn new(config: &MaintenanceConfig) -> Self {
+        Self {
+            default_message: config.message.clone(),
+            status: RwLock::new(MaintenanceStatus {
+                enabled: config.enabled,
+                message: config.message.clone(),
+                since: config.enabled.then(Utc::now),
+            }),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.status
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .enabled
+    }
+
+    pub fn status(&self) -> MaintenanceStatus {
+        self.status
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Switch maintenance on or off, with the message to show instead of the default one
+    pub fn set(&self, enabled: bool, message: Option<String>) -> MaintenanceStatus {
+        let mut status = self.status.write().unwrap_or_else(|e| e.into_inner());
+        // Switching it on again only changes the message
+        let since = match (enabled, status.since) {
+            (true, Some(since)) => Some(since),
+            (true, None) => Some(Utc::now()),
+            (false, _) => None,
+        };
+        *status = MaintenanceStatus {
+            enabled,
+            message: message.unwrap_or_else(|| self.default_message.clone()),
+            since,
+        };
+        status.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_keeps_start_while_enabled() {
+        let mode = MaintenanceMode::new(&MaintenanceConfig {
+            enabled: false,
+            message: "Back soon".to_string(),
+        });
+        assert!(!mode.is_enabled());
+
+        let started = mode.set(true, Some("Upgrading the database".to_string()));
+        assert!(mode.is_enabled());
+        assert_eq!(started.message, "Upgrading the database");
+
+        let updated = mode.set(true, None);
+        assert_eq!(updated.since, started.since);
+        assert_eq!(updated.message, "Back soon");
+
+        let stopped = mode.set(false, None);
+        assert!(!mode.is_enabled());
+        assert_eq!(stopped.since, None);
+    }
+}
@@ -1,3 +1,20 @@
+pub mod circuit_breaker;
+pub mod file_proxy;
 pub mod hash;
+pub mod http_client;
+pub mod https_upgrade;
+pub mod id_generator;
+pub mod logger;
+pub mod maintenance;
+pub mod network;
+pub mod query_parser;
+pub mod rate_limiter;
+pub mod redirect_loop;
+pub mod report;
+pub mod reverse_proxy;
+pub mod signing;
+pub mod template;
+pub mod unicode;
+pub mod url_rewrite;
+pub mod user_agent;
 pub mod validation;
-pub mod id_generator;
\ No newline at end of file
@@ -0,0 +1,221 @@
+use std::net::{IpAddr, SocketAddr};
+
+use actix_web::{
+    http::header::{HeaderMap, FORWARDED, X_FORWARDED_FOR},
+    web, HttpRequest,
+};
+use ipnet::IpNet;
+
+use crate::config::Config;
+
+/// The address of the client behind a request
+///
+/// Forwarding headers are only believed from the proxies in `TRUSTED_PROXIES`, as anyone
+/// else can send whatever they like in them. See [`forwarded_client`].
+pub fn client_ip(req: &HttpRequest) -> Option<IpAddr> {
+    let config = req.app_data::<web::Data<Config>>();
+    let trusted = config.map_or(&[][..], |config| &config.server.trusted_proxies);
+    forwarded_client(
+        req.peer_addr().map(|addr| addr.ip()),
+        req.headers(),
+        trusted,
+    )
+}
+
+/// The client address behind a connection from `peer`
+///
+/// The hops named in `Forwarded` (or, without it, `X-Forwarded-For`) are walked back from
+/// the peer for as long as the address they were received from is a trusted proxy; the
+/// first address that isn't is the client. A hop that can't be read ends the walk at the
+/// proxy that sent it. IPv4-mapped IPv6 addresses are returned as plain IPv4 so they match
+/// IPv4 rules.
+pub fn forwarded_client(
+    peer: Option<IpAddr>,
+    headers: &HeaderMap,
+    trusted: &[IpNet],
+) -> Option<IpAddr> {
+    let mut client = peer?.to_canonical();
+    let hops = forwarded_hops(headers);
+    for hop in hops.iter().rev() {
+        if !trusted.iter().any(|net| net.contains(&client)) {
+            break;
+        }
+        match parse_hop(hop) {
+            Some(ip) => client = ip.to_canonical(),
+            None => break,
+        }
+    }
+    Some(client)
+}
+
+// The addresses a request was forwarded for, the client's first
+fn forwarded_hops(headers: &HeaderMap) -> Vec<String> {
+    let values = |name| {
+        headers
+            .get_all(name)
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect::<Vec<_>>()
+    };
+
+    let forwarded = values(FORWARDED);
+    if !forwarded.is_empty() {
+        // Each element is a list of pairs such as `for=192.0.2.1;proto=https`, where a
+        // missing `for` leaves the hop unknown
+        return forwarded
+            .into_iter()
+            .map(|element| {
+                element
+                    .split(';')
+                    .filter_map(|pair| pair.trim().split_once('='))
+                    .find(|(key, _)| key.trim().eq_ignore_ascii_case("for"))
+                    .map_or_else(String::new, |(_, value)| value.trim().to_string())
+            })
+            .collect();
+    }
+    values(X_FORWARDED_FOR)
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+// An address as forwarding headers give it: bare, with a port, in brackets or quoted
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    let hop = hop.trim().trim_matches('"');
+    hop.parse::<IpAddr>()
+        .or_else(|_| hop.parse::<SocketAddr>().map(|addr| addr.ip()))
+        .or_else(|_| {
+            hop.trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+        })
+        .ok()
+}
+
+/// Whether an address is reachable on the public internet, rather than this host, a private
+/// network or a link-local service such as cloud metadata endpoints
+///
+/// IPv4-mapped IPv6 addresses are judged as the IPv4 address they carry.
+pub fn is_public(ip: IpAddr) -> bool {
+    match ip.to_canonical() {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                // "This network" and carrier-grade NAT ranges
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)))
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            !(ip.is_loopback()
+                || ip.is_unspecified()
+                || ip.is_multicast()
+                // Unique local (fc00::/7) and link-local (fe80::/10) ranges
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80)
+        }
+    }
+}
+
+/// Parses a CIDR range, accepting a bare address as a single-host range
+///
+/// Ranges with host bits set (e.g. `10.0.0.1/8`) are rejected, as Postgres does.
+pub fn parse_cidr(value: &str) -> Option<IpNet> {
+    let value = value.trim();
+    let net = match value.parse::<IpNet>() {
+        Ok(net) => net,
+        Err(_) => IpNet::from(value.parse::<IpAddr>().ok()?),
+    };
+
+    (net.trunc() == net).then_some(net)
+}
+
+/// Parses validated CIDR strings, skipping any that don't parse
+pub fn parse_cidrs(values: &[String]) -> Vec<IpNet> {
+    values.iter().filter_map(|v| parse_cidr(v)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cidr() {
+        assert_eq!(parse_cidr("10.0.0.0/8"), "10.0.0.0/8".parse().ok());
+        assert_eq!(parse_cidr(" 192.168.1.7 "), "192.168.1.7/32".parse().ok());
+        assert_eq!(parse_cidr("2001:db8::/32"), "2001:db8::/32".parse().ok());
+        assert_eq!(parse_cidr("10.0.0.1/8"), None);
+        assert_eq!(parse_cidr("10.0.0.0/33"), None);
+        assert_eq!(parse_cidr("internal"), None);
+    }
+
+    #[test]
+    fn test_forwarding_headers_need_a_trusted_proxy() {
+        let trusted = [parse_cidr("10.0.0.0/8").unwrap()];
+        let peer = |s: &str| Some(s.parse::<IpAddr>().unwrap());
+        let ip = |s: &str| s.parse::<IpAddr>().ok();
+        let headers = |name, value: &str| {
+            let mut headers = HeaderMap::new();
+            headers.insert(name, value.parse().unwrap());
+            headers
+        };
+        let spoofed = headers(X_FORWARDED_FOR, "203.0.113.9");
+
+        // Anyone connecting directly is who they are, whatever they claim
+        let client = forwarded_client(peer("198.51.100.7"), &spoofed, &trusted);
+        assert_eq!(client, ip("198.51.100.7"));
+        let client = forwarded_client(peer("10.0.0.2"), &spoofed, &[]);
+        assert_eq!(client, ip("10.0.0.2"));
+
+        // Through a trusted proxy, the right-most address it didn't add itself
+        let chain = headers(X_FORWARDED_FOR, "1.1.1.1, 203.0.113.9, 10.0.0.3");
+        let client = forwarded_client(peer("10.0.0.2"), &chain, &trusted);
+        assert_eq!(client, ip("203.0.113.9"));
+        let client = forwarded_client(peer("::ffff:10.0.0.2"), &spoofed, &trusted);
+        assert_eq!(client, ip("203.0.113.9"));
+
+        let forwarded = headers(
+            FORWARDED,
+            r#"for=1.1.1.1, for="[2001:db8::7]:4711";proto=https, for=10.0.0.3"#,
+        );
+        let client = forwarded_client(peer("10.0.0.2"), &forwarded, &trusted);
+        assert_eq!(client, ip("2001:db8::7"));
+
+        // A hop that can't be read stops at the proxy that sent it
+        let hidden = headers(FORWARDED, "for=_hidden, for=10.0.0.3");
+        let client = forwarded_client(peer("10.0.0.2"), &hidden, &trusted);
+        assert_eq!(client, ip("10.0.0.3"));
+    }
+
+    #[test]
+    fn test_is_public() {
+        let public = |s: &str| is_public(s.parse().unwrap());
+        assert!(public("93.184.216.34"));
+        assert!(public("2606:2800:220:1::1"));
+
+        for internal in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "0.0.0.0",
+            "100.64.0.1",
+            "::1",
+            "::",
+            "fd00::1",
+            "fe80::1",
+            "::ffff:127.0.0.1",
+            "::ffff:169.254.169.254",
+        ] {
+            assert!(!public(internal), "{} counted as public", internal);
+        }
+    }
+}
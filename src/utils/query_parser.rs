@@ -0,0 +1,452 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use chrono::{DateTime, Months, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::LinkStatus;
+
+// Longest query accepted, and most terms in one
+const MAX_QUERY_LENGTH: usize = 500;
+const MAX_TERMS: usize = 20;
+
+const KEYS: &str = "domain, clicks, created, expires, tag, code, namespace, status, is";
+
+/// A search in the `q` mini-language, e.g.
+/// `domain:example.com clicks:>100 created:2024-01..2024-03 tag:launch`
+///
+/// Terms are separated by spaces and must all match. Each is a `key:value` filter or a bare
+/// word the destination must contain; a leading `-` negates a term and double quotes keep
+/// spaces in a value, as in `-tag:"spring sale"`.
+///
+/// * `domain:example.com` - destination host, subdomains included
+/// * `clicks:100`, `clicks:>100`, `clicks:<=5`, `clicks:10..100` - access count
+/// * `created:2024`, `created:2024-01..2024-03`, `created:>=2024-06-15`, `created:..2024-01` -
+///   creation date in UTC, a year, month or day matching the whole period; `expires:` alike
+/// * `tag:launch` - entry of the `tags` metadata array
+/// * `code:abc`, `namespace:docs`, `status:flagged`
+/// * `is:active`, `is:expired`, `is:custom`, `is:broken`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct SearchQuery {
+    source: String,
+    terms: Vec<SearchTerm>,
+}
+
+impl SearchQuery {
+    pub fn terms(&self) -> &[SearchTerm] {
+        &self.terms
+    }
+}
+
+/// One condition of a search, see [`SearchQuery`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchTerm {
+    pub negated: bool,
+    pub filter: SearchFilter,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchFilter {
+    /// Text the destination contains
+    Text(String),
+    /// Lowercase host the destination is on, or a parent domain of it
+    Domain(String),
+    Clicks(CountRange),
+    Created(DateRange),
+    Expires(DateRange),
+    Tag(String),
+    Code(String),
+    Namespace(String),
+    Status(LinkStatus),
+    Is(LinkFlag),
+}
+
+/// Link properties matched by `is:`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkFlag {
+    Active,
+    Expired,
+    Custom,
+    Broken,
+}
+
+/// Counts from `min` to `max`, both included
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CountRange {
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+}
+
+/// Times from `from`, included, until `until`, excluded
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateRange {
+    pub from: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl FromStr for SearchQuery {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() > MAX_QUERY_LENGTH {
+            return Err(format!(
+                "Search query must be at most {} characters",
+                MAX_QUERY_LENGTH
+            ));
+        }
+
+        let terms = tokenize(s)?
+            .into_iter()
+            .map(|token| parse_term(&token))
+            .collect::<Result<Vec<_>, _>>()?;
+        if terms.len() > MAX_TERMS {
+            return Err(format!(
+                "Search query must have at most {} terms",
+                MAX_TERMS
+            ));
+        }
+
+        Ok(SearchQuery {
+            source: s.to_string(),
+            terms,
+        })
+    }
+}
+
+impl TryFrom<String> for SearchQuery {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<SearchQuery> for String {
+    fn from(query: SearchQuery) -> Self {
+        query.source
+    }
+}
+
+impl Display for SearchQuery {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+// A term as written, before its key and value are told apart
+struct Token {
+    negated: bool,
+    key: Option<String>,
+    value: String,
+}
+
+// Split a query on spaces outside double quotes. Quotes only group, so `tag:"a b"` and
+// `"tag:a b"` differ: the second is a bare phrase
+fn tokenize(s: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return Ok(tokens);
+        }
+
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut key = None;
+        let mut value = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.next_if(|c| quoted || !c.is_whitespace()) {
+            match c {
+                '"' => quoted = !quoted,
+                ':' if key.is_none() && !quoted && !value.is_empty() => {
+                    key = Some(std::mem::take(&mut value).to_lowercase());
+                }
+                c => value.push(c),
+            }
+        }
+        if quoted {
+            return Err("Search query has an unclosed quote".to_string());
+        }
+        if value.is_empty() {
+            return Err(match key {
+                Some(key) => format!("Search term '{}:' needs a value", key),
+                None => "Search query has an empty term".to_string(),
+            });
+        }
+
+        tokens.push(Token {
+            negated,
+            key,
+            value,
+        });
+    }
+}
+
+fn parse_term(token: &Token) -> Result<SearchTerm, String> {
+    let value = token.value.as_str();
+    let filter = match token.key.as_deref() {
+        None => SearchFilter::Text(value.to_string()),
+        Some("domain") => SearchFilter::Domain(parse_domain(value)?),
+        Some("clicks") => SearchFilter::Clicks(parse_counts(value)?),
+        Some("created") => SearchFilter::Created(parse_dates(value)?),
+        Some("expires") => SearchFilter::Expires(parse_dates(value)?),
+        Some("tag") => SearchFilter::Tag(value.to_string()),
+        Some("code") => SearchFilter::Code(value.to_string()),
+        Some("namespace") => SearchFilter::Namespace(value.to_string()),
+        Some("status") => SearchFilter::Status(value.parse()?),
+        Some("is") => SearchFilter::Is(match value.to_lowercase().as_str() {
+            "active" => LinkFlag::Active,
+            "expired" => LinkFlag::Expired,
+            "custom" => LinkFlag::Custom,
+            "broken" => LinkFlag::Broken,
+            _ => {
+                return Err(format!(
+                    "Unknown 'is:{}', expected one of: active, expired, custom, broken",
+                    value
+                ))
+            }
+        }),
+        Some(key) => {
+            return Err(format!(
+                "Unknown search key '{}', expected one of: {}",
+                key, KEYS
+            ));
+        }
+    };
+
+    Ok(SearchTerm {
+        negated: token.negated,
+        filter,
+    })
+}
+
+// Hosts are matched as written in lowercase, so only host characters are taken
+fn parse_domain(value: &str) -> Result<String, String> {
+    let domain = value.trim_matches('.').to_lowercase();
+    let valid = domain
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '.' || c == '-');
+    if domain.is_empty() || !valid {
+        return Err(format!("'{}' isn't a domain", value));
+    }
+    Ok(domain)
+}
+
+fn parse_counts(value: &str) -> Result<CountRange, String> {
+    let count = |s: &str| {
+        s.parse::<i64>()
+            .map_err(|_| format!("'{}' isn't a number of clicks", s))
+    };
+
+    if let Some((min, max)) = value.split_once("..") {
+        let min = (!min.is_empty()).then(|| count(min)).transpose()?;
+        let max = (!max.is_empty()).then(|| count(max)).transpose()?;
+        return Ok(CountRange { min, max });
+    }
+
+    let range = match comparison(value) {
+        (">=", n) => CountRange {
+            min: Some(count(n)?),
+            max: None,
+        },
+        (">", n) => CountRange {
+            min: Some(count(n)?.saturating_add(1)),
+            max: None,
+        },
+        ("<=", n) => CountRange {
+            min: None,
+            max: Some(count(n)?),
+        },
+        ("<", n) => CountRange {
+            min: None,
+            max: Some(count(n)?.saturating_sub(1)),
+        },
+        (_, n) => {
+            let n = count(n)?;
+            CountRange {
+                min: Some(n),
+                max: Some(n),
+            }
+        }
+    };
+    Ok(range)
+}
+
+fn parse_dates(value: &str) -> Result<DateRange, String> {
+    if let Some((from, until)) = value.split_once("..") {
+        let from = (!from.is_empty()).then(|| period(from)).transpose()?;
+        let until = (!until.is_empty()).then(|| period(until)).transpose()?;
+        return Ok(DateRange {
+            from: from.map(|(start, _)| start),
+            until: until.map(|(_, end)| end),
+        });
+    }
+
+    let range = match comparison(value) {
+        (">=", date) => DateRange {
+            from: Some(period(date)?.0),
+            until: None,
+        },
+        (">", date) => DateRange {
+            from: Some(period(date)?.1),
+            until: None,
+        },
+        ("<=", date) => DateRange {
+            from: None,
+            until: Some(period(date)?.1),
+        },
+        ("<", date) => DateRange {
+            from: None,
+            until: Some(period(date)?.0),
+        },
+        (_, date) => {
+            let (start, end) = period(date)?;
+            DateRange {
+                from: Some(start),
+                until: Some(end),
+            }
+        }
+    };
+    Ok(range)
+}
+
+// Split a leading comparison operator off a value
+fn comparison(value: &str) -> (&str, &str) {
+    [">=", "<=", ">", "<"]
+        .into_iter()
+        .find_map(|op| value.strip_prefix(op).map(|rest| (op, rest)))
+        .unwrap_or(("", value))
+}
+
+// The start and end of the year, month or day a date like `2024`, `2024-03` or `2024-03-15`
+// names, at midnight UTC
+fn period(date: &str) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let invalid = || format!("'{}' isn't a date like 2024, 2024-03 or 2024-03-15", date);
+    let parts = date
+        .split('-')
+        .map(|part| part.parse::<u32>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let (start, end) = match parts[..] {
+        [year] => {
+            let start = NaiveDate::from_ymd_opt(year as i32, 1, 1).ok_or_else(invalid)?;
+            (start, start.checked_add_months(Months::new(12)))
+        }
+        [year, month] => {
+            let start = NaiveDate::from_ymd_opt(year as i32, month, 1).ok_or_else(invalid)?;
+            (start, start.checked_add_months(Months::new(1)))
+        }
+        [year, month, day] => {
+            let start = NaiveDate::from_ymd_opt(year as i32, month, day).ok_or_else(invalid)?;
+            (start, start.succ_opt())
+        }
+        _ => return Err(invalid()),
+    };
+
+    let midnight = |day: NaiveDate| day.and_hms_opt(0, 0, 0).map(|time| time.and_utc());
+    Ok((
+        midnight(start).ok_or_else(invalid)?,
+        end.and_then(midnight).ok_or_else(invalid)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(y: i32, m: u32, d: u32) -> Option<DateTime<Utc>> {
+        NaiveDate::from_ymd_opt(y, m, d)
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|time| time.and_utc())
+    }
+
+    #[test]
+    fn test_parses_terms() {
+        let query: SearchQuery =
+            "domain:Example.com clicks:>100 created:2024-01..2024-03 -tag:\"spring sale\" docs"
+                .parse()
+                .unwrap();
+        let filters = query
+            .terms()
+            .iter()
+            .map(|t| (t.negated, &t.filter))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            filters,
+            [
+                (false, &SearchFilter::Domain("example.com".to_string())),
+                (
+                    false,
+                    &SearchFilter::Clicks(CountRange {
+                        min: Some(101),
+                        max: None
+                    })
+                ),
+                (
+                    false,
+                    &SearchFilter::Created(DateRange {
+                        from: day(2024, 1, 1),
+                        until: day(2024, 4, 1)
+                    })
+                ),
+                (true, &SearchFilter::Tag("spring sale".to_string())),
+                (false, &SearchFilter::Text("docs".to_string())),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parses_ranges() {
+        assert_eq!(
+            parse_dates("2024").unwrap(),
+            DateRange {
+                from: day(2024, 1, 1),
+                until: day(2025, 1, 1)
+            }
+        );
+        assert_eq!(
+            parse_dates("<=2024-02-29").unwrap(),
+            DateRange {
+                from: None,
+                until: day(2024, 3, 1)
+            }
+        );
+        assert_eq!(
+            parse_dates("2023-12..").unwrap(),
+            DateRange {
+                from: day(2023, 12, 1),
+                until: None
+            }
+        );
+        assert_eq!(
+            parse_counts("10..20").unwrap(),
+            CountRange {
+                min: Some(10),
+                max: Some(20)
+            }
+        );
+        assert_eq!(
+            parse_counts("<5").unwrap(),
+            CountRange {
+                min: None,
+                max: Some(4)
+            }
+        );
+
+        assert!(parse_dates("2024-02-30").is_err());
+        assert!(parse_dates("2024-13").is_err());
+        assert!(parse_counts("many").is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_queries() {
+        assert!("size:large".parse::<SearchQuery>().is_err());
+        assert!("tag:".parse::<SearchQuery>().is_err());
+        assert!("tag:\"spring".parse::<SearchQuery>().is_err());
+        assert!("is:popular".parse::<SearchQuery>().is_err());
+        assert!("domain:exa%mple.com".parse::<SearchQuery>().is_err());
+        assert!("status:hidden".parse::<SearchQuery>().is_err());
+    }
+}
@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+// Clients tracked before windows that already ended are swept out
+const SWEEP_THRESHOLD: usize = 10_000;
+
+struct Window {
+    started: Instant,
+    hits: u32,
+}
+
+/// Where a client stands within its window, as told in the `X-RateLimit-*` headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quota {
+    pub limit: u32,
+    /// Requests left in the window
+    pub remaining: u32,
+    /// Until the window ends and the count starts over
+    pub reset: Duration,
+}
+
+/// Allows each client address `limit` requests per fixed window
+///
+/// Counts live in memory, so every instance applies the limit on its own. A limit of 0
+/// lets everything through. The limit can be changed while running, windows in progress
+/// keep their count.
+pub struct RateLimiter {
+    limits: RwLock<(u32, Duration)>,
+    windows: Mutex<HashMap<IpAddr, Window>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32, window: Duration) -> Self {
+        Self {
+            limits: RwLock::new((limit, window)),
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Apply another limit from now on
+    pub fn set_limit(&self, limit: u32, window: Duration) {
+        *self.limits.write().unwrap_or_else(|e| e.into_inner()) = (limit, window);
+    }
+
+    /// Counts a request from `ip`
+    ///
+    /// ### Returns
+    /// * `Ok(Option<Quota>)` - The request is within the limit, and what is left of it; `None`
+    ///   when there is no limit
+    /// * `Err(Quota)` - The client is over the limit, and may try again once `reset` passed
+    pub fn check(&self, ip: IpAddr) -> Result<Option<Quota>, Quota> {
+        let (limit, window) = *self.limits.read().unwrap_or_else(|e| e.into_inner());
+        if limit == 0 {
+            return Ok(None);
+        }
+
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+        if windows.len() >= SWEEP_THRESHOLD {
+            windows.retain(|_, w| w.started.elapsed() < window);
+        }
+
+        let entry = windows.entry(ip).or_insert(Window {
+            started: Instant::now(),
+            hits: 0,
+        });
+        if entry.started.elapsed() >= window {
+            entry.started = Instant::now();
+            entry.hits = 0;
+        }
+        let reset = window.saturating_sub(entry.started.elapsed());
+        if entry.hits >= limit {
+            return Err(Quota {
+                limit,
+                remaining: 0,
+                reset,
+            });
+        }
+        entry.hits += 1;
+        Ok(Some(Quota {
+            limit,
+            remaining: limit - entry.hits,
+            reset,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_each_client_separately() {
+        let limiter = RateLimiter::new(2, Duration::from_secs(60));
+        let first: IpAddr = "203.0.113.1".parse().unwrap();
+        let second: IpAddr = "203.0.113.2".parse().unwrap();
+
+        assert_eq!(limiter.check(first).unwrap().map(|q| q.remaining), Some(1));
+        assert_eq!(limiter.check(first).unwrap().map(|q| q.remaining), Some(0));
+        let quota = limiter.check(first).unwrap_err();
+        assert_eq!((quota.limit, quota.remaining), (2, 0));
+        assert!(quota.reset > Duration::from_secs(55) && quota.reset <= Duration::from_secs(60));
+        assert!(limiter.check(second).is_ok());
+
+        // A raised limit lets the client through within the same window
+        limiter.set_limit(3, Duration::from_secs(60));
+        assert!(limiter.check(first).is_ok());
+        assert!(limiter.check(first).is_err());
+
+        let unlimited = RateLimiter::new(0, Duration::from_secs(60));
+        assert!((0..100).all(|_| unlimited.check(first) == Ok(None)));
+    }
+}
@@ -0,0 +1,92 @@
+use percent_encoding::percent_decode_str;
+use url::Url;
+
+use crate::{config::RedirectLoopConfig, errors::AppError, types::Result};
+
+/// Tells destinations pointing back at this shortener from the rest
+///
+/// A link sending visitors to another short link of this instance redirects in a loop as
+/// soon as the chain comes back to it, so such destinations are refused when links are set
+/// up, and chains left over from before are followed at most `max_depth` links deep.
+pub struct LoopGuard {
+    hosts: Vec<String>,
+    max_depth: usize,
+}
+
+impl LoopGuard {
+    pub fn new(config: &RedirectLoopConfig) -> Self {
+        Self {
+            hosts: config.hosts.clone(),
+            max_depth: config.max_depth,
+        }
+    }
+
+    /// Short links followed through at most before a chain counts as a loop
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Refuse a destination on one of this shortener's hosts, when a link is set up
+    pub fn check(&self, destination: &str) -> Result<()> {
+        if self.is_own(destination) {
+            return Err(AppError::Validation(format!(
+                "Destination '{}' points back at this shortener, which would redirect in a loop",
+                destination
+            )));
+        }
+        Ok(())
+    }
+
+    /// The decoded path of a destination on one of this shortener's hosts, like `docs/setup`
+    ///
+    /// `None` for other hosts and for the home page, which never redirects.
+    pub fn own_path(&self, destination: &str) -> Option<String> {
+        let url = Url::parse(destination).ok()?;
+        if !self.is_own_url(&url) {
+            return None;
+        }
+        let path = percent_decode_str(url.path().trim_matches('/'))
+            .decode_utf8()
+            .ok()?;
+        (!path.is_empty()).then(|| path.into_owned())
+    }
+
+    fn is_own(&self, destination: &str) -> bool {
+        Url::parse(destination).is_ok_and(|url| self.is_own_url(&url))
+    }
+
+    fn is_own_url(&self, url: &Url) -> bool {
+        url.host_str()
+            .map(|host| host.trim_end_matches('.').to_ascii_lowercase())
+            .is_some_and(|host| self.hosts.contains(&host))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recognizes_own_destinations() {
+        let guard = LoopGuard::new(&RedirectLoopConfig {
+            hosts: vec!["sho.rt".to_string(), "links.example.com".to_string()],
+            max_depth: 5,
+        });
+
+        assert!(guard.check("https://example.com/sho.rt").is_ok());
+        assert!(guard.check("https://sub.sho.rt/abc").is_ok());
+        assert!(guard.check("https://SHO.RT./abc").is_err());
+        assert!(guard.check("http://links.example.com:8080/").is_err());
+
+        assert_eq!(
+            guard.own_path("https://sho.rt/docs/setup?x=1").as_deref(),
+            Some("docs/setup")
+        );
+        assert_eq!(
+            guard.own_path("https://sho.rt/%F0%9F%8D%95").as_deref(),
+            Some("🍕")
+        );
+        assert_eq!(guard.own_path("https://sho.rt/"), None);
+        assert_eq!(guard.own_path("https://example.com/abc"), None);
+    }
+}
@@ -0,0 +1,112 @@
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+
+/// A value in a report row
+pub enum Cell {
+    Text(String),
+    Number(i64),
+    Empty,
+}
+
+/// Rows that can be downloaded as a CSV or XLSX report
+pub trait ReportRow {
+    /// Column titles, in the order of `cells`
+    const COLUMNS: &'static [&'static str];
+
+    fn cells(&self) -> Vec<Cell>;
+}
+
+/// The header line of a CSV report
+pub fn csv_header<T: ReportRow>() -> String {
+    let mut line = T::COLUMNS.join(",");
+    line.push('\n');
+    line
+}
+
+/// One line of a CSV report
+///
+/// Text that spreadsheets would read as a formula is prefixed with `'`, so opening a report
+/// never runs something a link owner typed in.
+pub fn csv_line<T: ReportRow>(row: &T) -> String {
+    let fields = row
+        .cells()
+        .into_iter()
+        .map(|cell| match cell {
+            Cell::Text(text) if text.starts_with(['=', '+', '-', '@', '\t', '\r']) => {
+                csv_field(&format!("'{}", text))
+            }
+            Cell::Text(text) => csv_field(&text),
+            Cell::Number(number) => number.to_string(),
+            Cell::Empty => String::new(),
+        })
+        .collect::<Vec<_>>();
+    let mut line = fields.join(",");
+    line.push('\n');
+    line
+}
+
+/// Quote a CSV field when it contains a delimiter, quote or line break
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// A workbook with the rows on a single sheet, below a bold header row
+pub fn xlsx_report<T: ReportRow>(sheet_name: &str, rows: &[T]) -> Result<Vec<u8>, XlsxError> {
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(sheet_name)?;
+
+    let bold = Format::new().set_bold();
+    for (col, title) in T::COLUMNS.iter().enumerate() {
+        sheet.write_string_with_format(0, col as u16, *title, &bold)?;
+    }
+    for (i, row) in rows.iter().enumerate() {
+        let line = i as u32 + 1;
+        for (col, cell) in row.cells().into_iter().enumerate() {
+            match cell {
+                // Written as strings, so text is never taken for a formula
+                Cell::Text(text) => sheet.write_string(line, col as u16, text)?,
+                Cell::Number(number) => sheet.write_number(line, col as u16, number as f64)?,
+                Cell::Empty => continue,
+            };
+        }
+    }
+    sheet.set_freeze_panes(1, 0)?;
+
+    workbook.save_to_buffer()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Row(&'static str, i64);
+
+    impl ReportRow for Row {
+        const COLUMNS: &'static [&'static str] = &["name", "count"];
+
+        fn cells(&self) -> Vec<Cell> {
+            vec![Cell::Text(self.0.to_string()), Cell::Number(self.1)]
+        }
+    }
+
+    #[test]
+    fn test_csv_lines() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+
+        assert_eq!(csv_header::<Row>(), "name,count\n");
+        assert_eq!(csv_line(&Row("a,b", 3)), "\"a,b\",3\n");
+        assert_eq!(csv_line(&Row("=1+2", -1)), "'=1+2,-1\n");
+    }
+
+    #[test]
+    fn test_xlsx_report_is_a_zip() {
+        let report = xlsx_report("Stats", &[Row("a", 1), Row("b", 2)]).unwrap();
+        assert!(report.starts_with(b"PK"));
+    }
+}
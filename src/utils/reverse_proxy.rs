@@ -0,0 +1,246 @@
+use std::time::Duration;
+
+use futures::Stream;
+use log::warn;
+use reqwest::{header::HeaderMap, Response};
+use url::Url;
+
+use crate::{
+    config::{HttpClientConfig, ReverseProxyConfig},
+    errors::AppError,
+    types::Result,
+    utils::{file_proxy::capped_body, http_client::HttpClient},
+};
+
+// Request headers passed on to destinations; cookies, credentials and anything naming
+// this service stay behind
+const FORWARDED_REQUEST_HEADERS: [&str; 8] = [
+    "accept",
+    "accept-encoding",
+    "accept-language",
+    "if-modified-since",
+    "if-none-match",
+    "range",
+    "if-range",
+    "user-agent",
+];
+
+// Response headers passed back to visitors, besides the length which the response is
+// sized with. Cookies, redirects and headers describing the destination's servers stay
+// behind
+const FORWARDED_RESPONSE_HEADERS: [&str; 10] = [
+    "content-type",
+    "content-encoding",
+    "content-language",
+    "content-range",
+    "accept-ranges",
+    "cache-control",
+    "expires",
+    "etag",
+    "last-modified",
+    "vary",
+];
+
+/// Serves the destinations of `reverse_proxy` links in place of redirecting to them
+///
+/// Only destinations on an allowed domain are requested, and redirects they answer with
+/// aren't followed, so a link can't be used to reach any other host from this service.
+pub struct ReverseProxy {
+    client: HttpClient,
+    enabled: bool,
+    allowed_domains: Vec<String>,
+    max_bytes: u64,
+    timeout: Duration,
+}
+
+/// A destination's answer, ready to be streamed back to the visitor
+pub struct ProxiedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Size announced by the destination, if any
+    pub size: Option<u64>,
+    pub response: Response,
+}
+
+impl ReverseProxy {
+    pub fn new(config: &ReverseProxyConfig, client_config: &HttpClientConfig) -> Result<Self> {
+        Ok(Self {
+            client: HttpClient::without_redirects(client_config)?,
+            enabled: config.enabled,
+            allowed_domains: config.allowed_domains.clone(),
+            max_bytes: config.max_bytes,
+            timeout: Duration::from_millis(config.timeout_ms),
+        })
+    }
+
+    /// Refuse destinations that can't be reverse proxied, when a link is set up
+    pub fn check(&self, destination: &str) -> Result<()> {
+        if !self.enabled {
+            return Err(AppError::Validation(
+                "The reverse_proxy behavior is not enabled on this instance".to_string(),
+            ));
+        }
+        if !self.allows(destination) {
+            return Err(AppError::Validation(format!(
+                "'{}' is not on a domain allowed for reverse_proxy links",
+                destination
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether a destination is on one of the allowed domains
+    pub fn allows(&self, destination: &str) -> bool {
+        let Ok(url) = Url::parse(destination) else {
+            return false;
+        };
+        let Some(host) = url.host_str().map(|h| h.to_ascii_lowercase()) else {
+            return false;
+        };
+        matches!(url.scheme(), "http" | "https")
+            && self.allowed_domains.iter().any(|domain| {
+                host == *domain
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|sub| sub.ends_with('.'))
+            })
+    }
+
+    /// Request a destination on behalf of a visitor
+    ///
+    /// Errors never name the destination, as it's meant to stay hidden from visitors.
+    ///
+    /// ### Arguments
+    /// * `short_code` - The link being followed, for messages
+    /// * `destination` - The URL to request
+    /// * `headers` - The visitor's request headers, of which only some are passed on
+    ///
+    /// ### Errors
+    /// * `AppError::Forbidden` - If the destination isn't (or no longer) allowed
+    /// * `AppError::Timeout` - If the destination took too long to answer
+    /// * `AppError::BadGateway` - If the destination failed, redirected elsewhere or
+    ///   announced a response over the size limit
+    pub async fn fetch(
+        &self,
+        short_code: &str,
+        destination: &str,
+        headers: impl Iterator<Item = (String, String)>,
+    ) -> Result<ProxiedResponse> {
+        if !self.enabled || !self.allows(destination) {
+            warn!(
+                "Refusing to reverse proxy '{}' to '{}'",
+                short_code, destination
+            );
+            return Err(AppError::Forbidden(format!(
+                "URL with code '{}' can't be served",
+                short_code
+            )));
+        }
+
+        let mut request = self.client.get(destination).timeout(self.timeout);
+        for (name, value) in headers {
+            if FORWARDED_REQUEST_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+                request = request.header(name, value);
+            }
+        }
+        let unreachable = || {
+            AppError::BadGateway(format!(
+                "The destination of URL with code '{}' couldn't be reached",
+                short_code
+            ))
+        };
+        let response = self
+            .client
+            .send_with_retries(request, 0)
+            .await
+            .map_err(|e| {
+                warn!("Reverse proxying '{}' failed: {}", short_code, e);
+                if e.is_timeout() {
+                    AppError::Timeout(format!(
+                        "The destination of URL with code '{}' took too long to answer",
+                        short_code
+                    ))
+                } else {
+                    unreachable()
+                }
+            })?;
+
+        let status = response.status();
+        if status.is_redirection() && status.as_u16() != 304 {
+            warn!("Not following redirect of '{}' ({})", short_code, status);
+            return Err(unreachable());
+        }
+        let size = response.content_length();
+        if size.is_some_and(|size| size > self.max_bytes) {
+            warn!("Response for '{}' is over the size limit", short_code);
+            return Err(AppError::BadGateway(format!(
+                "The destination of URL with code '{}' answered with too much data",
+                short_code
+            )));
+        }
+
+        Ok(ProxiedResponse {
+            status: status.as_u16(),
+            headers: forwarded_response_headers(response.headers()),
+            size,
+            response,
+        })
+    }
+
+    /// The body of a destination's answer, cut off once it goes past the size limit
+    pub fn body(
+        &self,
+        response: Response,
+    ) -> impl Stream<Item = reqwest::Result<actix_web::web::Bytes>> {
+        capped_body(response, self.max_bytes)
+    }
+}
+
+fn forwarded_response_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter(|(name, _)| FORWARDED_RESPONSE_HEADERS.contains(&name.as_str()))
+        .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn proxy(enabled: bool) -> ReverseProxy {
+        let config = ReverseProxyConfig {
+            enabled,
+            allowed_domains: vec!["intranet.example".to_string()],
+            max_bytes: 1024,
+            timeout_ms: 1000,
+        };
+        let client = HttpClientConfig {
+            timeout_ms: 1000,
+            connect_timeout_ms: 1000,
+            pool_max_idle_per_host: 1,
+            max_retries: 0,
+            retry_base_delay_ms: 10,
+            retry_max_delay_ms: 10,
+        };
+        ReverseProxy::new(&config, &client).unwrap()
+    }
+
+    #[test]
+    fn test_only_allowed_domains_are_proxied() {
+        let proxy = proxy(true);
+        assert!(proxy.allows("https://intranet.example/wiki"));
+        assert!(proxy.allows("http://docs.intranet.example:8080/a?b=c"));
+
+        assert!(!proxy.allows("https://evilintranet.example/"));
+        assert!(!proxy.allows("https://intranet.example.evil.test/"));
+        assert!(!proxy.allows("ftp://intranet.example/file"));
+        assert!(!proxy.allows("not a url"));
+
+        assert!(proxy.check("https://intranet.example/").is_ok());
+        assert!(proxy.check("https://example.com/").is_err());
+        assert!(self::proxy(false)
+            .check("https://intranet.example/")
+            .is_err());
+    }
+}
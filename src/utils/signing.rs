@@ -0,0 +1,216 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::{config::RequestSigningConfig, errors::AppError};
+
+pub const SIGNATURE_HEADER: &str = "x-signature";
+pub const SIGNATURE_KEY_HEADER: &str = "x-signature-key";
+pub const SIGNATURE_TIMESTAMP_HEADER: &str = "x-signature-timestamp";
+pub const SIGNATURE_NONCE_HEADER: &str = "x-signature-nonce";
+
+const MAX_NONCE_LENGTH: usize = 128;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Signature headers sent along with a signed request
+#[derive(Debug, Clone)]
+pub struct SignatureParts {
+    pub key_id: String,
+    pub signature: String,
+    pub timestamp: i64,
+    pub nonce: String,
+}
+
+/// Sign a request the way partners are expected to
+///
+/// The signature is the hex encoded HMAC-SHA256 of the method, the path with its query, the
+/// unix timestamp, the nonce and the raw body, joined by newlines.
+pub fn sign(
+    secret: &str,
+    method: &str,
+    path: &str,
+    timestamp: i64,
+    nonce: &str,
+    body: &[u8],
+) -> String {
+    hex::encode(
+        mac(secret, method, path, timestamp, nonce, body)
+            .finalize()
+            .into_bytes(),
+    )
+}
+
+fn mac(
+    secret: &str,
+    method: &str,
+    path: &str,
+    timestamp: i64,
+    nonce: &str,
+    body: &[u8],
+) -> HmacSha256 {
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}\n{}\n{}\n{}\n", method, path, timestamp, nonce).as_bytes());
+    mac.update(body);
+    mac
+}
+
+/// Checks signed requests against the configured partner keys
+///
+/// Nonces are remembered for as long as their timestamp is accepted, so a captured request
+/// can't be sent again. They are kept in memory, per instance.
+pub struct RequestSigner {
+    config: RequestSigningConfig,
+    seen_nonces: Mutex<HashMap<(String, String), i64>>,
+}
+
+impl RequestSigner {
+    pub fn new(config: RequestSigningConfig) -> Self {
+        Self {
+            config,
+            seen_nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether any signing keys are configured
+    pub fn is_enabled(&self) -> bool {
+        !self.config.keys.is_empty()
+    }
+
+    /// Whether service token requests must be signed
+    pub fn is_required(&self) -> bool {
+        self.is_enabled() && self.config.required
+    }
+
+    /// Verify the signature of a request
+    ///
+    /// ### Arguments
+    /// - `parts`: The signature headers of the request
+    /// - `method`: The request method
+    /// - `path`: The request path including its query
+    /// - `body`: The raw request body
+    /// - `now`: The current unix timestamp
+    ///
+    /// ### Errors
+    /// - `AppError::Unauthorized` if the key is unknown, the timestamp is outside the
+    ///   tolerance, the signature doesn't match or the nonce was used before
+    pub fn verify(
+        &self,
+        parts: &SignatureParts,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        now: i64,
+    ) -> Result<(), AppError> {
+        let secret = self
+            .config
+            .keys
+            .get(&parts.key_id)
+            .ok_or_else(|| AppError::Unauthorized("Unknown signing key".to_string()))?;
+
+        // Subtracting could overflow on a timestamp picked by the client
+        if now.abs_diff(parts.timestamp) > self.config.tolerance_seconds.max(0) as u64 {
+            return Err(AppError::Unauthorized(
+                "Signature timestamp is outside the allowed window".to_string(),
+            ));
+        }
+        if parts.nonce.is_empty() || parts.nonce.len() > MAX_NONCE_LENGTH {
+            return Err(AppError::Unauthorized(format!(
+                "Signature nonce must be 1 to {} characters",
+                MAX_NONCE_LENGTH
+            )));
+        }
+
+        let signature = hex::decode(&parts.signature)
+            .map_err(|_| AppError::Unauthorized("Signature must be hex encoded".to_string()))?;
+        mac(secret, method, path, parts.timestamp, &parts.nonce, body)
+            .verify_slice(&signature)
+            .map_err(|_| AppError::Unauthorized("Invalid request signature".to_string()))?;
+
+        // Only remember nonces of genuine requests, so others can't burn them
+        let mut seen = self.seen_nonces.lock().unwrap();
+        seen.retain(|_, expires_at| *expires_at >= now);
+        let key = (parts.key_id.clone(), parts.nonce.clone());
+        if seen.contains_key(&key) {
+            return Err(AppError::Unauthorized(
+                "Signature nonce was already used".to_string(),
+            ));
+        }
+        seen.insert(key, parts.timestamp + self.config.tolerance_seconds);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signer() -> RequestSigner {
+        RequestSigner::new(RequestSigningConfig {
+            keys: HashMap::from([("partner".to_string(), "s3cret".to_string())]),
+            tolerance_seconds: 300,
+            required: false,
+        })
+    }
+
+    fn parts(body: &[u8], timestamp: i64, nonce: &str) -> SignatureParts {
+        SignatureParts {
+            key_id: "partner".to_string(),
+            signature: sign("s3cret", "POST", "/api/urls", timestamp, nonce, body),
+            timestamp,
+            nonce: nonce.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_verify_signed_requests() {
+        let signer = signer();
+        let now = 1_700_000_000;
+        let body = br#"{"original_url":"https://example.com"}"#;
+
+        let signed = parts(body, now, "n1");
+        assert!(signer
+            .verify(&signed, "POST", "/api/urls", body, now)
+            .is_ok());
+        // Replayed nonce
+        assert!(signer
+            .verify(&signed, "POST", "/api/urls", body, now)
+            .is_err());
+        // Tampered body and path
+        let signed = parts(body, now, "n2");
+        assert!(signer
+            .verify(&signed, "POST", "/api/urls", b"{}", now)
+            .is_err());
+        assert!(signer
+            .verify(&signed, "POST", "/api/tokens", body, now)
+            .is_err());
+        // Stale timestamp
+        let signed = parts(body, now - 301, "n3");
+        assert!(signer
+            .verify(&signed, "POST", "/api/urls", body, now)
+            .is_err());
+        // Unknown key
+        let signed = SignatureParts {
+            key_id: "other".to_string(),
+            ..parts(body, now, "n4")
+        };
+        assert!(signer
+            .verify(&signed, "POST", "/api/urls", body, now)
+            .is_err());
+    }
+
+    #[test]
+    fn test_extreme_timestamps_are_rejected() {
+        let signer = signer();
+        let now = 1_700_000_000;
+        for timestamp in [i64::MIN, i64::MAX] {
+            let signed = parts(b"", timestamp, "n1");
+            assert!(signer
+                .verify(&signed, "POST", "/api/urls", b"", now)
+                .is_err());
+        }
+    }
+}
@@ -0,0 +1,21 @@
+/// Replaces `{{name}}` placeholders, HTML-escaping values when rendering markup
+pub fn render(template: &str, vars: &[(&str, &str)], escape: bool) -> String {
+    vars.iter().fold(template.to_string(), |out, (key, value)| {
+        let value = if escape {
+            html_escape(value)
+        } else {
+            value.to_string()
+        };
+        out.replace(&format!("{{{{{}}}}}", key), &value)
+    })
+}
+
+/// Escapes text for use in HTML content and attribute values
+pub fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
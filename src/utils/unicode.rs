@@ -0,0 +1,97 @@
+use std::borrow::Cow;
+
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Joins emoji into one, as in a family or a profession
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+// Turns the preceding digit, `#` or `*` into a keycap emoji
+const COMBINING_KEYCAP: char = '\u{20E3}';
+
+/// The NFC form of a text, so the same emoji or accented letter typed differently is
+/// stored and looked up alike
+pub fn nfc(text: &str) -> Cow<'_, str> {
+    if text.is_ascii() || is_nfc_quick(text.chars()) == IsNormalized::Yes {
+        Cow::Borrowed(text)
+    } else {
+        Cow::Owned(text.nfc().collect())
+    }
+}
+
+/// Number of characters as a reader counts them, a multi-codepoint emoji being one
+pub fn grapheme_count(text: &str) -> usize {
+    text.graphemes(true).count()
+}
+
+/// Whether a text is made of one emoji, including sequences such as flags, skin tones,
+/// keycaps and joined emoji like 👨‍👩‍👧
+pub fn is_emoji(grapheme: &str) -> bool {
+    let mut pictographic = false;
+    for c in grapheme.chars() {
+        if is_pictographic(c) {
+            pictographic = true;
+        } else if !(is_emoji_modifier(c) || c.is_ascii_digit() || c == '#' || c == '*') {
+            return false;
+        }
+    }
+    pictographic || grapheme.ends_with(COMBINING_KEYCAP)
+}
+
+// Emoji that stand on their own
+fn is_pictographic(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00A9}'
+            | '\u{00AE}'
+            | '\u{203C}'
+            | '\u{2049}'
+            | '\u{2122}'
+            | '\u{2139}'
+            | '\u{2194}'..='\u{21AA}'
+            | '\u{231A}'..='\u{23FF}'
+            | '\u{24C2}'
+            | '\u{25AA}'..='\u{25FE}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2934}'..='\u{2935}'
+            | '\u{2B05}'..='\u{2B55}'
+            | '\u{3030}'
+            | '\u{303D}'
+            | '\u{3297}'
+            | '\u{3299}'
+            | '\u{1F000}'..='\u{1FAFF}'
+    )
+}
+
+// Characters changing the emoji they follow: joiners, presentation selectors, keycaps
+// and the tags of subdivision flags
+fn is_emoji_modifier(c: char) -> bool {
+    matches!(
+        c,
+        ZERO_WIDTH_JOINER | COMBINING_KEYCAP | '\u{FE0E}' | '\u{FE0F}' | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_multi_codepoint_emoji() {
+        // Family, skin tone, flag, keycap and subdivision flag sequences
+        for emoji in ["😀", "🚀", "👨‍👩‍👧", "👍🏽", "🇫🇷", "1️⃣", "🏴󠁧󠁢󠁳󠁣󠁴󠁿", "❤️"]
+        {
+            assert!(is_emoji(emoji), "{} should be an emoji", emoji);
+            assert_eq!(grapheme_count(emoji), 1, "{} is one character", emoji);
+        }
+        assert_eq!(grapheme_count("😀🚀go"), 4);
+
+        assert!(!is_emoji("a"));
+        assert!(!is_emoji("1"));
+        assert!(!is_emoji("é"));
+        assert!(!is_emoji("\u{200D}"));
+
+        // A decomposed accent is composed, text already in NFC is left alone
+        assert_eq!(nfc("cafe\u{301}"), "caf\u{E9}");
+        assert!(matches!(nfc("👨‍👩‍👧"), Cow::Borrowed(_)));
+    }
+}
@@ -0,0 +1,143 @@
+use std::collections::HashSet;
+
+use url::{form_urlencoded, Url};
+
+/// Appends a trailing path and query from a short link request to its destination
+///
+/// The path is joined under the destination path and the query is merged as by
+/// [`merge_query`]. Returns `None` when the destination can't carry
+/// a path or the trailing path tries to climb out with `.` or `..` segments.
+pub fn append_path(destination: &str, tail: &str, query: &str) -> Option<String> {
+    let mut url = Url::parse(destination).ok()?;
+    if url.cannot_be_a_base() {
+        return None;
+    }
+
+    let segments = tail
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<_>>();
+    if segments.iter().any(|s| is_dot_segment(s)) {
+        return None;
+    }
+
+    if !segments.is_empty() {
+        let mut path = url.path().trim_end_matches('/').to_string();
+        for segment in segments {
+            path.push('/');
+            path.push_str(segment);
+        }
+        if tail.ends_with('/') {
+            path.push('/');
+        }
+        url.set_path(&path);
+    }
+
+    merge_into(&mut url, query);
+    Some(url.into())
+}
+
+/// Merges the query parameters of a short link request into its destination
+///
+/// Incoming parameters replace destination parameters of the same name, the others
+/// are kept in place, as are the fragment and the original encoding. Returns `None`
+/// when the destination isn't a valid URL.
+pub fn merge_query(destination: &str, query: &str) -> Option<String> {
+    let mut url = Url::parse(destination).ok()?;
+    merge_into(&mut url, query);
+    Some(url.into())
+}
+
+fn merge_into(url: &mut Url, query: &str) {
+    let incoming = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .collect::<Vec<_>>();
+    if incoming.is_empty() {
+        return;
+    }
+
+    // Compare names decoded, so `utm%5Fsource` replaces `utm_source`
+    let replaced = incoming
+        .iter()
+        .map(|p| param_name(p))
+        .collect::<HashSet<_>>();
+    let mut pairs = url
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|p| !p.is_empty() && !replaced.contains(&param_name(p)))
+        .map(String::from)
+        .collect::<Vec<_>>();
+    pairs.extend(incoming.into_iter().map(String::from));
+
+    url.set_query(Some(&pairs.join("&")));
+}
+
+fn param_name(pair: &str) -> String {
+    form_urlencoded::parse(pair.as_bytes())
+        .next()
+        .map(|(name, _)| name.into_owned())
+        .unwrap_or_default()
+}
+
+// URL parsers resolve these, including their percent-encoded forms
+fn is_dot_segment(segment: &str) -> bool {
+    let segment = segment.to_ascii_lowercase().replace("%2e", ".");
+    segment == "." || segment == ".."
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_append_path() {
+        let docs = "https://docs.example.com/v2/";
+        assert_eq!(
+            append_path(docs, "guide/install", "").as_deref(),
+            Some("https://docs.example.com/v2/guide/install")
+        );
+        assert_eq!(
+            append_path("https://docs.example.com/v2", "guide/", "lang=en").as_deref(),
+            Some("https://docs.example.com/v2/guide/?lang=en")
+        );
+        assert_eq!(
+            append_path("https://example.com/?ref=short#top", "a b", "x=1").as_deref(),
+            Some("https://example.com/a%20b?ref=short&x=1#top")
+        );
+        assert_eq!(append_path(docs, "", "").as_deref(), Some(docs));
+        assert_eq!(append_path(docs, "guide/../../admin", ""), None);
+        assert_eq!(append_path(docs, "%2E%2e/admin", ""), None);
+        assert_eq!(append_path("mailto:team@example.com", "x", ""), None);
+    }
+
+    #[test]
+    fn test_merge_query() {
+        assert_eq!(
+            merge_query("https://example.com/p", "utm_source=tw").as_deref(),
+            Some("https://example.com/p?utm_source=tw")
+        );
+        assert_eq!(
+            merge_query(
+                "https://example.com/p?ref=a&utm_source=site#top",
+                "utm%5Fsource=tw&x=1"
+            )
+            .as_deref(),
+            Some("https://example.com/p?ref=a&utm%5Fsource=tw&x=1#top")
+        );
+        assert_eq!(
+            merge_query(
+                "https://example.com/p?tag=a&tag=b&q=hello%20world",
+                "tag=c&tag=d"
+            )
+            .as_deref(),
+            Some("https://example.com/p?q=hello%20world&tag=c&tag=d")
+        );
+        assert_eq!(
+            merge_query("https://example.com/p?ref=a", "").as_deref(),
+            Some("https://example.com/p?ref=a")
+        );
+        assert_eq!(merge_query("not a url", "x=1"), None);
+    }
+}
@@ -0,0 +1,55 @@
+// Markers found in the user agents of link preview crawlers, lowercased
+const CRAWLER_MARKERS: [&str; 18] = [
+    "facebookexternalhit",
+    "facebot",
+    "twitterbot",
+    "linkedinbot",
+    "slackbot",
+    "slack-imgproxy",
+    "discordbot",
+    "whatsapp",
+    "telegrambot",
+    "pinterest",
+    "redditbot",
+    "applebot",
+    "skypeuripreview",
+    "embedly",
+    "vkshare",
+    "iframely",
+    "mastodon",
+    "googlebot",
+];
+
+/// Whether a user agent belongs to a crawler fetching a link to preview it, like the
+/// ones of social platforms and chat apps
+pub fn is_link_preview_crawler(user_agent: &str) -> bool {
+    let user_agent = user_agent.to_ascii_lowercase();
+    CRAWLER_MARKERS
+        .iter()
+        .any(|marker| user_agent.contains(marker))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_preview_crawlers() {
+        assert!(is_link_preview_crawler(
+            "facebookexternalhit/1.1 (+http://www.facebook.com/externalhit_uatext.php)"
+        ));
+        assert!(is_link_preview_crawler("Twitterbot/1.0"));
+        assert!(is_link_preview_crawler(
+            "Slackbot-LinkExpanding 1.0 (+https://api.slack.com/robots)"
+        ));
+        assert!(is_link_preview_crawler(
+            "Mozilla/5.0 (compatible; Discordbot/2.0; +https://discordapp.com)"
+        ));
+        assert!(is_link_preview_crawler("WhatsApp/2.23.20.0"));
+
+        assert!(!is_link_preview_crawler(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 17_0 like Mac OS X) Safari/604.1"
+        ));
+        assert!(!is_link_preview_crawler("curl/8.5.0"));
+    }
+}
@@ -0,0 +1,114 @@
+use validator::ValidationError;
+
+const MAX_APPS: usize = 20;
+
+/// Validates iOS app identifiers, a 10 character team ID and a bundle ID like
+/// `ABCDE12345.com.example.app`
+pub fn validate_ios_app_ids(ids: &[String]) -> Result<(), ValidationError> {
+    if ids.len() > MAX_APPS {
+        let mut err = ValidationError::new("ios_app_ids");
+        err.message = Some(format!("At most {} apps are allowed", MAX_APPS).into());
+        return Err(err);
+    }
+
+    let valid = |id: &String| {
+        id.split_once('.').is_some_and(|(team, bundle)| {
+            team.len() == 10
+                && team
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+                && is_reverse_domain(bundle)
+        })
+    };
+    if let Some(invalid) = ids.iter().find(|id| !valid(id)) {
+        let mut err = ValidationError::new("ios_app_id");
+        err.message = Some(
+            format!(
+                "'{}' is not an app ID made of a team ID and a bundle ID, like ABCDE12345.com.example.app",
+                invalid
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Validates an Android application ID like `com.example.app`
+pub fn validate_android_package(package: &str) -> Result<(), ValidationError> {
+    let valid = package.contains('.')
+        && package.split('.').all(|part| {
+            part.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+
+    if !valid {
+        let mut err = ValidationError::new("android_package");
+        err.message = Some("Android package must be an application ID like com.example.app".into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Validates SHA-256 certificate fingerprints, 32 hex bytes separated by colons
+pub fn validate_cert_fingerprints(fingerprints: &[String]) -> Result<(), ValidationError> {
+    if fingerprints.len() > MAX_APPS {
+        let mut err = ValidationError::new("cert_fingerprints");
+        err.message = Some(format!("At most {} fingerprints are allowed", MAX_APPS).into());
+        return Err(err);
+    }
+
+    let valid = |fingerprint: &String| {
+        let bytes = fingerprint.split(':').collect::<Vec<_>>();
+        bytes.len() == 32
+            && bytes
+                .iter()
+                .all(|b| b.len() == 2 && b.chars().all(|c| c.is_ascii_hexdigit()))
+    };
+    if let Some(invalid) = fingerprints.iter().find(|f| !valid(f)) {
+        let mut err = ValidationError::new("cert_fingerprint");
+        err.message = Some(
+            format!(
+                "'{}' is not a SHA-256 fingerprint like 14:6D:E9:...:5B (32 bytes)",
+                invalid
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+fn is_reverse_domain(id: &str) -> bool {
+    !id.is_empty()
+        && id.split('.').all(|part| {
+            !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_app_identifiers() {
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        assert!(validate_ios_app_ids(&ids(&["ABCDE12345.com.example.app"])).is_ok());
+        assert!(validate_ios_app_ids(&ids(&["abcde12345.com.example.app"])).is_err());
+        assert!(validate_ios_app_ids(&ids(&["ABCDE12345"])).is_err());
+        assert!(validate_ios_app_ids(&ids(&["ABCDE1234.com.example"])).is_err());
+
+        assert!(validate_android_package("com.example.app").is_ok());
+        assert!(validate_android_package("com.example.my_app2").is_ok());
+        assert!(validate_android_package("app").is_err());
+        assert!(validate_android_package("com.1example").is_err());
+
+        let fingerprint = vec!["14:6D"; 16].join(":");
+        assert!(validate_cert_fingerprints(&ids(&[&fingerprint])).is_ok());
+        assert!(validate_cert_fingerprints(&ids(&["14:6D:E9"])).is_err());
+        assert!(validate_cert_fingerprints(&ids(&[&fingerprint.replace('D', "G")])).is_err());
+    }
+}
@@ -0,0 +1,30 @@
+use validator::ValidationError;
+
+/// Validates a hex color as used in CSS, like `#1a73e8` or `#fff`
+pub fn validate_color(color: &str) -> Result<(), ValidationError> {
+    let valid = color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    });
+
+    if !valid {
+        let mut err = ValidationError::new("color");
+        err.message = Some("Color must be a hex color like '#1a73e8' or '#fff'".into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_color() {
+        assert!(validate_color("#1a73e8").is_ok());
+        assert!(validate_color("#FFF").is_ok());
+        assert!(validate_color("1a73e8").is_err());
+        assert!(validate_color("#12345").is_err());
+        assert!(validate_color("red;}body{").is_err());
+    }
+}
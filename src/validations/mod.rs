@@ -1,3 +1,23 @@
+pub mod app_links;
+pub mod branding;
+pub mod network;
+pub mod notification;
+pub mod saved_search;
+pub mod service_token;
 pub mod shortened_url;
+pub mod stats;
+pub mod tenant;
 
-pub use shortened_url::{validate_custom_alias, validate_date, validate_url};
+pub use app_links::{validate_android_package, validate_cert_fingerprints, validate_ios_app_ids};
+pub use branding::validate_color;
+pub use network::{validate_cidr_list, validate_ip_address};
+pub use notification::validate_channel_target;
+pub use saved_search::validate_saved_search;
+pub use service_token::validate_scopes;
+pub use shortened_url::{
+    validate_alias_length, validate_app_url, validate_bulk_delete, validate_bulk_update,
+    validate_custom_alias, validate_date, validate_namespace, validate_query_params, validate_url,
+    MAX_ALIAS_LENGTH,
+};
+pub use stats::validate_timezone;
+pub use tenant::{validate_domain, validate_tenant_slug};
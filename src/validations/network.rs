@@ -0,0 +1,41 @@
+use std::net::IpAddr;
+
+use validator::ValidationError;
+
+use crate::utils::network::parse_cidr;
+
+const MAX_CIDRS_PER_LIST: usize = 50;
+
+/// Validates that a string is a single IPv4 or IPv6 address
+pub fn validate_ip_address(ip: &str) -> Result<(), ValidationError> {
+    if ip.parse::<IpAddr>().is_err() {
+        let mut err = ValidationError::new("ip_address");
+        err.message = Some("Must be a valid IPv4 or IPv6 address".into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Validates a list of CIDR ranges (or bare addresses) for link access rules
+pub fn validate_cidr_list(cidrs: &[String]) -> Result<(), ValidationError> {
+    if cidrs.len() > MAX_CIDRS_PER_LIST {
+        let mut err = ValidationError::new("cidr_list");
+        err.message = Some(format!("At most {} ranges are allowed", MAX_CIDRS_PER_LIST).into());
+        return Err(err);
+    }
+
+    if let Some(invalid) = cidrs.iter().find(|c| parse_cidr(c).is_none()) {
+        let mut err = ValidationError::new("cidr");
+        err.message = Some(
+            format!(
+                "'{}' is not a valid CIDR range (e.g. 10.0.0.0/8) with no host bits set",
+                invalid
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+
+    Ok(())
+}
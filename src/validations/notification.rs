@@ -0,0 +1,82 @@
+use url::Url;
+use validator::ValidationError;
+
+use crate::models::{ChannelKind, CreateNotificationChannelDto};
+
+/// Validates that a channel's target URL fits its kind:
+/// - Slack and Discord only accept their own HTTPS incoming webhook URLs
+/// - Generic webhooks accept any http/https URL
+pub fn validate_channel_target(dto: &CreateNotificationChannelDto) -> Result<(), ValidationError> {
+    let invalid = |message: &'static str| {
+        let mut err = ValidationError::new("target_url");
+        err.message = Some(message.into());
+        err
+    };
+
+    if dto.target_url.len() > 2048 {
+        return Err(invalid("Target URL must be at most 2048 characters"));
+    }
+
+    let url = Url::parse(&dto.target_url).map_err(|_| invalid("Target URL is not a valid URL"))?;
+    let host = url.host_str().unwrap_or_default();
+
+    match dto.kind {
+        ChannelKind::Slack if url.scheme() != "https" || host != "hooks.slack.com" => Err(invalid(
+            "Slack channels need an https://hooks.slack.com/ incoming webhook URL",
+        )),
+        ChannelKind::Discord
+            if url.scheme() != "https"
+                || !matches!(host, "discord.com" | "discordapp.com")
+                || !url.path().starts_with("/api/webhooks/") =>
+        {
+            Err(invalid(
+                "Discord channels need an https://discord.com/api/webhooks/ URL",
+            ))
+        }
+        ChannelKind::Webhook if !matches!(url.scheme(), "http" | "https") || host.is_empty() => {
+            Err(invalid("Webhook URL must use http or https"))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dto(kind: ChannelKind, target_url: &str) -> CreateNotificationChannelDto {
+        CreateNotificationChannelDto {
+            kind,
+            target_url: target_url.to_string(),
+            events: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_channel_target() {
+        assert!(validate_channel_target(&dto(
+            ChannelKind::Slack,
+            "https://hooks.slack.com/services/T/B/x"
+        ))
+        .is_ok());
+        assert!(
+            validate_channel_target(&dto(ChannelKind::Slack, "https://example.com/hook")).is_err()
+        );
+        assert!(validate_channel_target(&dto(
+            ChannelKind::Discord,
+            "https://discord.com/api/webhooks/1/abc"
+        ))
+        .is_ok());
+        assert!(validate_channel_target(&dto(
+            ChannelKind::Discord,
+            "http://discord.com/api/webhooks/1/abc"
+        ))
+        .is_err());
+        assert!(
+            validate_channel_target(&dto(ChannelKind::Webhook, "https://example.com/hook")).is_ok()
+        );
+        assert!(
+            validate_channel_target(&dto(ChannelKind::Webhook, "ftp://example.com/hook")).is_err()
+        );
+    }
+}
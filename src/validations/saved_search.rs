@@ -0,0 +1,14 @@
+use validator::ValidationError;
+
+use crate::models::SaveSearchDto;
+
+/// Validates that a saved search doesn't apply another one, which could go round in circles
+pub fn validate_saved_search(dto: &SaveSearchDto) -> Result<(), ValidationError> {
+    if dto.params.preset.is_some() {
+        let mut err = ValidationError::new("preset");
+        err.message = Some("A saved search can't apply another preset".into());
+        return Err(err);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,35 @@
+use validator::ValidationError;
+
+use crate::models::service_token::SCOPES;
+
+/// Validates the scopes granted to a service token: known scopes or `family:*`
+pub fn validate_scopes(scopes: &[String]) -> Result<(), ValidationError> {
+    if scopes.is_empty() {
+        let mut err = ValidationError::new("scopes");
+        err.message = Some("At least one scope is required".into());
+        return Err(err);
+    }
+
+    let known = |scope: &str| {
+        SCOPES.contains(&scope)
+            || scope.strip_suffix(":*").is_some_and(|family| {
+                SCOPES
+                    .iter()
+                    .any(|s| s.split_once(':').is_some_and(|(f, _)| f == family))
+            })
+    };
+    if let Some(scope) = scopes.iter().find(|s| !known(s)) {
+        let mut err = ValidationError::new("scopes");
+        err.message = Some(
+            format!(
+                "Unknown scope '{}', expected one of {} or a family like 'urls:*'",
+                scope,
+                SCOPES.join(", ")
+            )
+            .into(),
+        );
+        return Err(err);
+    }
+
+    Ok(())
+}
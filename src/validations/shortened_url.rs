@@ -1,8 +1,18 @@
 use url::Url;
 use chrono::{DateTime, Utc};
 
+use unicode_segmentation::UnicodeSegmentation;
 use validator::ValidationError;
 
+use crate::models::{BulkDeleteUrlsDto, BulkUpdateUrlsDto, ShortenedUrlQueryParams};
+use crate::utils::unicode::{grapheme_count, is_emoji};
+
+/// Longest alias a short code column can hold
+pub const MAX_ALIAS_LENGTH: usize = 100;
+
+// First path segments taken by the API itself
+const RESERVED_NAMESPACES: &[&str] = &["admin", "api", "health"];
+
 /// Validates that a URL string is properly formatted and uses http/https
 pub fn validate_url(url_str: &str) -> Result<(), ValidationError> {
     match Url::parse(url_str) {
@@ -23,25 +33,99 @@ pub fn validate_url(url_str: &str) -> Result<(), ValidationError> {
     }
 }
 
+/// Validates an app link: an absolute URI in a custom scheme like `myapp://item/42`, or
+/// an http(s) universal link, but never a scheme that runs or reads anything locally
+pub fn validate_app_url(url_str: &str) -> Result<(), ValidationError> {
+    let Ok(url) = Url::parse(url_str) else {
+        let mut err = ValidationError::new("app_url");
+        err.message = Some("App URL must be an absolute URI like myapp://path".into());
+        return Err(err);
+    };
+
+    if matches!(
+        url.scheme(),
+        "javascript" | "data" | "vbscript" | "file" | "blob" | "about"
+    ) {
+        let mut err = ValidationError::new("app_url_scheme");
+        err.message = Some(format!("App URL scheme '{}' is not allowed", url.scheme()).into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
 /// Validates that a custom alias (if provided) meets requirements:
-/// - Between 1-100 characters
-/// - Only contains URL-safe characters
+/// - Between 1-100 characters, the most a short code column holds
+/// - Only contains URL-safe characters, or emoji
+///
+/// Deployments can narrow the length further, see [`validate_alias_length`], and decide
+/// whether emoji are accepted at all.
 pub fn validate_custom_alias(alias: &str) -> Result<(), ValidationError> {
-    // Check length
-    if alias.is_empty() || alias.len() > 10 {
+    validate_alias_length(alias, 1, MAX_ALIAS_LENGTH)?;
+    // Emoji take several characters of the column each
+    if alias.chars().count() > MAX_ALIAS_LENGTH {
         let mut err = ValidationError::new("custom_alias_length");
-        err.message = Some("Custom alias must be between 1 and 10 characters".into());
+        err.message = Some("Custom alias is too long".into());
         return Err(err);
     }
 
     // Ensure it only contains URL-safe characters
-    if !alias
-        .chars()
-        .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    let allowed = |grapheme: &str| {
+        grapheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+            || is_emoji(grapheme)
+    };
+    if !alias.graphemes(true).all(allowed) {
+        let mut err = ValidationError::new("custom_alias_chars");
+        err.message = Some(
+            "Custom alias can only contain letters, digits, hyphens, underscores and emoji".into(),
+        );
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Validates a link namespace: 1-50 URL-safe characters, not a reserved top-level path
+pub fn validate_namespace(namespace: &str) -> Result<(), ValidationError> {
+    if namespace.is_empty()
+        || namespace.len() > 50
+        || !namespace
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
     {
-        return Err(ValidationError::new(
-            "Custom alias can only contain alphanumeric characters, hyphens, and underscores",
-        ));
+        let mut err = ValidationError::new("namespace");
+        err.message =
+            Some("Namespace must be 1 to 50 letters, digits, hyphens and underscores".into());
+        return Err(err);
+    }
+
+    if RESERVED_NAMESPACES.contains(&namespace.to_lowercase().as_str()) {
+        let mut err = ValidationError::new("namespace_reserved");
+        err.message = Some(format!("Namespace '{}' is reserved", namespace).into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Validates that a custom alias is between `min` and `max` characters long, counting
+/// each emoji as one
+pub fn validate_alias_length(alias: &str, min: usize, max: usize) -> Result<(), ValidationError> {
+    let len = grapheme_count(alias);
+    if len < min || len > max {
+        let mut err = ValidationError::new("custom_alias_length");
+        err.message = Some(if min == max {
+            format!("Custom alias must be exactly {} characters", min).into()
+        } else {
+            format!(
+                "Custom alias must be between {} and {} characters",
+                min, max
+            )
+            .into()
+        });
+        return Err(err);
     }
 
     Ok(())
@@ -59,6 +143,49 @@ pub fn validate_date(date_str: &DateTime<Utc>) -> Result<(), ValidationError> {
 
 }
 
+/// Validates cross-field constraints of the list/search query parameters:
+/// - `created_after` must not be later than `created_before`
+pub fn validate_query_params(params: &ShortenedUrlQueryParams) -> Result<(), ValidationError> {
+    if let (Some(after), Some(before)) = (params.created_after, params.created_before) {
+        if after > before {
+            let mut err = ValidationError::new("created_range");
+            err.message = Some("created_after must be before created_before".into());
+            return Err(err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Validates that a bulk change picks its URLs either by ID or by filter, and changes
+/// something
+pub fn validate_bulk_update(dto: &BulkUpdateUrlsDto) -> Result<(), ValidationError> {
+    validate_bulk_selection(dto.ids.is_some(), dto.filter.is_some())?;
+
+    if dto.is_active.is_none() && dto.expires_at.is_none() && dto.add_tag.is_none() {
+        let mut err = ValidationError::new("bulk_change");
+        err.message = Some("One of is_active, expires_at or add_tag is required".into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Validates that a bulk deletion picks its URLs either by ID or by filter
+pub fn validate_bulk_delete(dto: &BulkDeleteUrlsDto) -> Result<(), ValidationError> {
+    validate_bulk_selection(dto.ids.is_some(), dto.filter.is_some())
+}
+
+fn validate_bulk_selection(has_ids: bool, has_filter: bool) -> Result<(), ValidationError> {
+    if has_ids == has_filter {
+        let mut err = ValidationError::new("bulk_selection");
+        err.message = Some("Exactly one of ids or filter is required".into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -74,16 +201,100 @@ mod tests {
         assert!(validate_url("ftp://example.com").is_err()); // Not http/https
     }
 
+    #[test]
+    fn test_validate_app_url() {
+        assert!(validate_app_url("myapp://item/42").is_ok());
+        assert!(validate_app_url("fb://profile/33138223345").is_ok());
+        assert!(validate_app_url("https://app.example.com/item/42").is_ok());
+
+        assert!(validate_app_url("item/42").is_err());
+        assert!(validate_app_url("javascript:alert(1)").is_err());
+        assert!(validate_app_url("JavaScript:alert(1)").is_err());
+        assert!(validate_app_url("data:text/html,hi").is_err());
+    }
+
     #[test]
     fn test_validate_custom_alias() {
         // Valid aliases
         assert!(validate_custom_alias("valid-alias").is_ok());
         assert!(validate_custom_alias("valid_alias123").is_ok());
 
+        assert!(validate_custom_alias("a").is_ok());
+        assert!(validate_custom_alias(&"a".repeat(100)).is_ok());
+
         // Invalid aliases
         let too_long = "a".repeat(101);
         assert!(validate_custom_alias(&too_long).is_err());
+        assert!(validate_custom_alias("").is_err());
         assert!(validate_custom_alias("invalid/alias").is_err());
+        assert!(validate_custom_alias("café").is_err());
+
+        let err = validate_custom_alias("has space").unwrap_err();
+        assert_eq!(err.code, "custom_alias_chars");
+        assert!(err.message.is_some());
+
+        // Emoji, including multi-codepoint ones, count as one character each
+        assert!(validate_custom_alias("😀🚀").is_ok());
+        assert!(validate_custom_alias("go-👨‍👩‍👧").is_ok());
+        assert!(validate_custom_alias("🇫🇷1️⃣👍🏽").is_ok());
+        assert!(validate_alias_length("👨‍👩‍👧🚀", 2, 2).is_ok());
+        assert!(validate_custom_alias(&"👨‍👩‍👧".repeat(20)).is_ok());
+        assert!(validate_custom_alias(&"👨‍👩‍👧".repeat(21)).is_err());
+        assert!(validate_custom_alias("\u{200D}").is_err());
+        assert!(validate_custom_alias("😀 🚀").is_err());
+    }
+
+    #[test]
+    fn test_validate_namespace() {
+        assert!(validate_namespace("docs").is_ok());
+        assert!(validate_namespace("team_a-1").is_ok());
+
+        assert!(validate_namespace("").is_err());
+        assert!(validate_namespace(&"a".repeat(51)).is_err());
+        assert!(validate_namespace("docs/setup").is_err());
+        assert!(validate_namespace("API").is_err());
+        assert!(validate_namespace("health").is_err());
+    }
+
+    #[test]
+    fn test_validate_alias_length() {
+        assert!(validate_alias_length("abcd", 4, 10).is_ok());
+        assert!(validate_alias_length("abcdefghij", 4, 10).is_ok());
+        assert!(validate_alias_length("abc", 4, 10).is_err());
+        assert!(validate_alias_length("abcdefghijk", 4, 10).is_err());
+
+        let err = validate_alias_length("abc", 4, 10).unwrap_err();
+        assert_eq!(
+            err.message.as_deref(),
+            Some("Custom alias must be between 4 and 10 characters")
+        );
+        let err = validate_alias_length("abc", 5, 5).unwrap_err();
+        assert_eq!(
+            err.message.as_deref(),
+            Some("Custom alias must be exactly 5 characters")
+        );
+    }
+
+    #[test]
+    fn test_validate_query_params() {
+        let now = Utc::now();
+
+        // Valid ranges
+        assert!(validate_query_params(&ShortenedUrlQueryParams::default()).is_ok());
+        let params = ShortenedUrlQueryParams {
+            created_after: Some(now - chrono::Duration::days(1)),
+            created_before: Some(now),
+            ..Default::default()
+        };
+        assert!(validate_query_params(&params).is_ok());
+
+        // Inverted range
+        let params = ShortenedUrlQueryParams {
+            created_after: Some(now),
+            created_before: Some(now - chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        assert!(validate_query_params(&params).is_err());
     }
 
     #[test]
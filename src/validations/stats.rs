@@ -0,0 +1,29 @@
+use chrono_tz::Tz;
+use validator::ValidationError;
+
+/// Validates a time zone given by its IANA name, like `Europe/Paris`
+pub fn validate_timezone(tz: &str) -> Result<(), ValidationError> {
+    if tz.parse::<Tz>().is_err() {
+        let mut err = ValidationError::new("timezone");
+        err.message = Some("Unknown time zone, expected an IANA name like 'Europe/Paris'".into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_timezone() {
+        assert!(validate_timezone("UTC").is_ok());
+        assert!(validate_timezone("Europe/Paris").is_ok());
+        assert!(validate_timezone("America/Argentina/Buenos_Aires").is_ok());
+        assert!(validate_timezone("europe/paris").is_err());
+        assert!(validate_timezone("Mars/Olympus_Mons").is_err());
+        assert!(validate_timezone("+02:00").is_err());
+        assert!(validate_timezone("").is_err());
+    }
+}
@@ -0,0 +1,60 @@
+use validator::ValidationError;
+
+/// Validates a tenant slug, as sent in the tenant header: 1-50 lowercase letters, digits,
+/// hyphens and underscores
+pub fn validate_tenant_slug(slug: &str) -> Result<(), ValidationError> {
+    if slug.is_empty()
+        || slug.len() > 50
+        || !slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    {
+        let mut err = ValidationError::new("slug");
+        err.message =
+            Some("Slug must be 1 to 50 lowercase letters, digits, hyphens and underscores".into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+/// Validates a tenant domain: a lowercase host name without scheme, port or path
+pub fn validate_domain(domain: &str) -> Result<(), ValidationError> {
+    let valid_label = |label: &str| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    };
+
+    if domain.len() > 253 || !domain.split('.').all(valid_label) {
+        let mut err = ValidationError::new("domain");
+        err.message = Some("Domain must be a lowercase host name, like 'go.example.com'".into());
+        return Err(err);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_tenant_fields() {
+        assert!(validate_tenant_slug("acme").is_ok());
+        assert!(validate_tenant_slug("acme_eu-2").is_ok());
+        assert!(validate_tenant_slug("").is_err());
+        assert!(validate_tenant_slug("Acme").is_err());
+
+        assert!(validate_domain("go.acme.com").is_ok());
+        assert!(validate_domain("localhost").is_ok());
+        assert!(validate_domain("https://go.acme.com").is_err());
+        assert!(validate_domain("go.acme.com:8080").is_err());
+        assert!(validate_domain("Go.Acme.com").is_err());
+        assert!(validate_domain("-acme.com").is_err());
+    }
+}
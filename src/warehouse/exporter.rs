@@ -0,0 +1,212 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
+
+use super::VisitExportSink;
+use crate::{models::ExportedVisit, types::Result};
+
+/// Export counts since startup, as reported by the health check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VisitExportStats {
+    pub sink: String,
+    /// Visits waiting for the next flush
+    pub pending: usize,
+    pub exported: u64,
+    /// Batches the sink rejected or couldn't be reached for, to be sent again
+    pub failed_batches: u64,
+    /// Visits dropped because too many were pending
+    pub dropped: u64,
+}
+
+#[derive(Default)]
+struct Counters {
+    exported: AtomicU64,
+    failed_batches: AtomicU64,
+    dropped: AtomicU64,
+}
+
+/// Visits waiting to be written to the analytics warehouse
+///
+/// Redirects only add to an in-memory queue; a background task sends it to the sink in
+/// batches. Batches that fail are put back at the front of the queue, and once
+/// `max_pending` visits are waiting new ones are dropped, so an unreachable warehouse
+/// costs memory up to that bound rather than slowing down redirects.
+pub struct VisitExporter {
+    sink: Arc<dyn VisitExportSink>,
+    batch_size: usize,
+    max_pending: usize,
+    pending: Mutex<VecDeque<ExportedVisit>>,
+    batch_ready: Notify,
+    counters: Counters,
+}
+
+impl VisitExporter {
+    pub fn new(sink: Arc<dyn VisitExportSink>, batch_size: usize, max_pending: usize) -> Self {
+        Self {
+            sink,
+            batch_size: batch_size.max(1),
+            max_pending,
+            pending: Mutex::new(VecDeque::new()),
+            batch_ready: Notify::new(),
+            counters: Counters::default(),
+        }
+    }
+
+    pub fn sink_name(&self) -> &'static str {
+        self.sink.name()
+    }
+
+    /// Queues a visit for the next flush
+    pub fn record(&self, visit: ExportedVisit) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.len() >= self.max_pending {
+            self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        pending.push_back(visit);
+        if pending.len() == self.batch_size {
+            self.batch_ready.notify_one();
+        }
+    }
+
+    /// Waits until a full batch is pending
+    pub async fn batch_ready(&self) {
+        self.batch_ready.notified().await
+    }
+
+    /// Sends every pending visit to the sink, one batch at a time
+    ///
+    /// Stops at the first failing batch, which stays queued along with the rest.
+    ///
+    /// ### Returns
+    /// * `Result<usize>` - Number of visits exported
+    pub async fn flush(&self) -> Result<usize> {
+        let mut exported = 0;
+        loop {
+            let batch = self.take_batch();
+            if batch.is_empty() {
+                return Ok(exported);
+            }
+
+            if let Err(e) = self.sink.export(&batch).await {
+                self.counters.failed_batches.fetch_add(1, Ordering::Relaxed);
+                self.restore(batch);
+                return Err(e);
+            }
+            self.counters
+                .exported
+                .fetch_add(batch.len() as u64, Ordering::Relaxed);
+            exported += batch.len();
+        }
+    }
+
+    fn take_batch(&self) -> Vec<ExportedVisit> {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        let count = pending.len().min(self.batch_size);
+        pending.drain(..count).collect()
+    }
+
+    // Puts a failed batch back ahead of the visits recorded since, keeping the oldest
+    fn restore(&self, batch: Vec<ExportedVisit>) {
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        for visit in batch.into_iter().rev() {
+            pending.push_front(visit);
+        }
+        if pending.len() > self.max_pending {
+            let excess = pending.len() - self.max_pending;
+            pending.truncate(self.max_pending);
+            self.counters
+                .dropped
+                .fetch_add(excess as u64, Ordering::Relaxed);
+        }
+    }
+
+    pub fn stats(&self) -> VisitExportStats {
+        VisitExportStats {
+            sink: self.sink.name().to_string(),
+            pending: self.pending.lock().unwrap_or_else(|e| e.into_inner()).len(),
+            exported: self.counters.exported.load(Ordering::Relaxed),
+            failed_batches: self.counters.failed_batches.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    use super::*;
+    use crate::errors::AppError;
+
+    #[derive(Default)]
+    struct Recording {
+        down: AtomicBool,
+        batches: Mutex<Vec<Vec<Uuid>>>,
+    }
+
+    #[async_trait]
+    impl VisitExportSink for Recording {
+        fn name(&self) -> &'static str {
+            "recording"
+        }
+
+        async fn export(&self, visits: &[ExportedVisit]) -> Result<()> {
+            if self.down.load(Ordering::Relaxed) {
+                return Err(AppError::Internal("down".to_string()));
+            }
+            let ids = visits.iter().map(|visit| visit.url_id).collect();
+            self.batches.lock().unwrap().push(ids);
+            Ok(())
+        }
+    }
+
+    fn visit(url_id: Uuid) -> ExportedVisit {
+        ExportedVisit {
+            url_id,
+            visited_at: Utc::now(),
+            ip_address: None,
+            user_agent: None,
+            referer: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_batches_are_sent_again_in_order() {
+        let sink = Arc::new(Recording::default());
+        let exporter = VisitExporter::new(sink.clone(), 2, 4);
+        let ids = (0..5).map(|_| Uuid::new_v4()).collect::<Vec<_>>();
+
+        sink.down.store(true, Ordering::Relaxed);
+        for id in &ids[..3] {
+            exporter.record(visit(*id));
+        }
+        assert!(exporter.flush().await.is_err());
+        assert_eq!(exporter.stats().pending, 3);
+
+        // Past the bound, newer visits are dropped
+        exporter.record(visit(ids[3]));
+        exporter.record(visit(ids[4]));
+        assert_eq!(exporter.stats().dropped, 1);
+
+        sink.down.store(false, Ordering::Relaxed);
+        assert_eq!(exporter.flush().await.unwrap(), 4);
+        assert_eq!(
+            *sink.batches.lock().unwrap(),
+            vec![vec![ids[0], ids[1]], vec![ids[2], ids[3]]]
+        );
+
+        let stats = exporter.stats();
+        assert_eq!(
+            (stats.pending, stats.exported, stats.failed_batches),
+            (0, 4, 1)
+        );
+    }
+}
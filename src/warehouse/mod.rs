@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+mod exporter;
+mod sinks;
+
+pub use exporter::{VisitExportStats, VisitExporter};
+pub use sinks::{ClickHouseSink, NdjsonHttpSink, VisitExportSink};
+
+use crate::{
+    config::{Config, VisitExportKind},
+    errors::AppError,
+    types::Result,
+    utils::http_client::HttpClient,
+};
+
+/// Build the exporter for the analytics warehouse selected in configuration, if any
+pub fn build_visit_exporter(
+    config: &Config,
+    client: Arc<HttpClient>,
+) -> Result<Option<Arc<VisitExporter>>> {
+    let export = &config.analytics.export;
+    let url = || {
+        export.url.as_deref().ok_or_else(|| {
+            AppError::Config(
+                "VISIT_EXPORT_SINK requires VISIT_EXPORT_URL to be configured".to_string(),
+            )
+        })
+    };
+    let credentials = export
+        .user
+        .clone()
+        .map(|user| (user, export.password.clone()));
+
+    let sink: Arc<dyn VisitExportSink> = match export.sink {
+        VisitExportKind::None => return Ok(None),
+        VisitExportKind::ClickHouse => {
+            // The table name ends up in the insert statement
+            let table = &export.clickhouse_table;
+            if table.is_empty()
+                || !table
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+            {
+                return Err(AppError::Config(format!(
+                    "Invalid VISIT_EXPORT_CLICKHOUSE_TABLE '{}'",
+                    table
+                )));
+            }
+            Arc::new(ClickHouseSink::new(url()?, table, credentials, client))
+        }
+        VisitExportKind::Http => Arc::new(NdjsonHttpSink::new(url()?, credentials, client)),
+    };
+
+    Ok(Some(Arc::new(VisitExporter::new(
+        sink,
+        export.batch_size,
+        export.max_pending,
+    ))))
+}
@@ -0,0 +1,191 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use reqwest::{header::CONTENT_TYPE, RequestBuilder};
+
+use crate::{
+    errors::AppError, models::ExportedVisit, types::Result, utils::http_client::HttpClient,
+};
+
+// An insert of a full batch is quick; one taking longer than this is retried with the next flush
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Destination receiving batches of visit events
+#[async_trait]
+pub trait VisitExportSink: Send + Sync {
+    /// Short name used in logs and the health check
+    fn name(&self) -> &'static str;
+
+    /// Writes a batch of visits, failing unless every one of them was accepted
+    async fn export(&self, visits: &[ExportedVisit]) -> Result<()>;
+}
+
+/// Inserts visits into a ClickHouse table through its HTTP interface
+///
+/// The table needs the columns of `ExportedVisit`, for example:
+///
+/// ```sql
+/// CREATE TABLE url_visits (
+///     url_id UUID,
+///     visited_at DateTime64(6, 'UTC'),
+///     ip_address Nullable(String),
+///     user_agent Nullable(String),
+///     referer Nullable(String)
+/// ) ENGINE = MergeTree ORDER BY (url_id, visited_at)
+/// ```
+pub struct ClickHouseSink {
+    client: Arc<HttpClient>,
+    url: String,
+    query: String,
+    credentials: Option<(String, Option<String>)>,
+}
+
+impl ClickHouseSink {
+    pub fn new(
+        url: &str,
+        table: &str,
+        credentials: Option<(String, Option<String>)>,
+        client: Arc<HttpClient>,
+    ) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            query: format!("INSERT INTO {} FORMAT JSONEachRow", table),
+            credentials,
+        }
+    }
+}
+
+#[async_trait]
+impl VisitExportSink for ClickHouseSink {
+    fn name(&self) -> &'static str {
+        "clickhouse"
+    }
+
+    async fn export(&self, visits: &[ExportedVisit]) -> Result<()> {
+        let request = self
+            .client
+            .post(&self.url)
+            // Timestamps are serialized as RFC 3339, which ClickHouse only parses on request
+            .query(&[
+                ("query", self.query.as_str()),
+                ("date_time_input_format", "best_effort"),
+            ])
+            .body(ndjson(visits)?);
+        send(&self.client, request, &self.credentials, "ClickHouse").await
+    }
+}
+
+/// Posts visits as newline-delimited JSON to an HTTP endpoint, such as a log collector
+pub struct NdjsonHttpSink {
+    client: Arc<HttpClient>,
+    url: String,
+    credentials: Option<(String, Option<String>)>,
+}
+
+impl NdjsonHttpSink {
+    pub fn new(
+        url: &str,
+        credentials: Option<(String, Option<String>)>,
+        client: Arc<HttpClient>,
+    ) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            credentials,
+        }
+    }
+}
+
+#[async_trait]
+impl VisitExportSink for NdjsonHttpSink {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn export(&self, visits: &[ExportedVisit]) -> Result<()> {
+        let request = self
+            .client
+            .post(&self.url)
+            .header(CONTENT_TYPE, "application/x-ndjson")
+            .body(ndjson(visits)?);
+        send(
+            &self.client,
+            request,
+            &self.credentials,
+            "Visit export endpoint",
+        )
+        .await
+    }
+}
+
+// One JSON object per line, including the last
+fn ndjson(visits: &[ExportedVisit]) -> Result<Vec<u8>> {
+    let mut body = Vec::new();
+    for visit in visits {
+        serde_json::to_writer(&mut body, visit).map_err(|e| AppError::Internal(e.to_string()))?;
+        body.push(b'\n');
+    }
+    Ok(body)
+}
+
+async fn send(
+    client: &HttpClient,
+    request: RequestBuilder,
+    credentials: &Option<(String, Option<String>)>,
+    target: &str,
+) -> Result<()> {
+    let request = match credentials {
+        Some((user, password)) => request.basic_auth(user, password.as_ref()),
+        None => request,
+    };
+    let response = client
+        .send(request.timeout(EXPORT_TIMEOUT))
+        .await
+        .map_err(|e| AppError::Internal(format!("Exporting visits failed: {}", e)))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        // ClickHouse explains rejected inserts in the body
+        let body = response.text().await.unwrap_or_default();
+        return Err(AppError::Internal(format!(
+            "{} responded with {}: {}",
+            target,
+            status,
+            body.trim()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+    use uuid::Uuid;
+
+    use super::*;
+
+    #[test]
+    fn test_ndjson_body() {
+        let visit = ExportedVisit {
+            url_id: Uuid::nil(),
+            visited_at: Utc.with_ymd_and_hms(2025, 10, 28, 9, 30, 0).unwrap(),
+            ip_address: Some("203.0.113.7".to_string()),
+            user_agent: None,
+            referer: None,
+        };
+
+        let body = String::from_utf8(ndjson(&[visit.clone(), visit]).unwrap()).unwrap();
+        let lines = body.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(body.ends_with('\n'));
+        assert_eq!(
+            lines[0],
+            "{\"url_id\":\"00000000-0000-0000-0000-000000000000\",\
+             \"visited_at\":\"2025-10-28T09:30:00Z\",\"ip_address\":\"203.0.113.7\",\
+             \"user_agent\":null,\"referer\":null}"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+// Admin dashboard: a thin client of the JSON API, signed in with the admin token
+"use strict";
+
+const PAGE_SIZE = 25;
+const TOKEN_KEY = "admin_token";
+const SVG_NS = "http://www.w3.org/2000/svg";
+
+const $ = (id) => document.getElementById(id);
+let offset = 0;
+
+function token() {
+  return sessionStorage.getItem(TOKEN_KEY);
+}
+
+// The CSRF cookie is handed out with this page; requests without an Authorization header
+// echo it, since the server can't tell them apart from forged ones otherwise
+function csrfToken() {
+  const name = document.querySelector('meta[name="csrf-cookie"]').content;
+  const cookie = document.cookie.split("; ").find((c) => c.startsWith(`${name}=`));
+  return cookie ? decodeURIComponent(cookie.split("=")[1]) : "";
+}
+
+async function api(path, { admin = false, method = "GET", body } = {}) {
+  const headers = { Accept: "application/json" };
+  if (admin) {
+    headers.Authorization = `Bearer ${token()}`;
+  } else {
+    // Links created with the admin token skip the limits and CAPTCHA of anonymous ones
+    headers["X-Admin-Token"] = token();
+    headers["X-CSRF-Token"] = csrfToken();
+  }
+  if (body !== undefined) {
+    headers["Content-Type"] = "application/json";
+  }
+
+  const response = await fetch(path, {
+    method,
+    headers,
+    body: body === undefined ? undefined : JSON.stringify(body),
+    credentials: "same-origin",
+  });
+  const payload = await response.json().catch(() => ({}));
+  if (response.status === 401 && admin) {
+    signOut();
+  }
+  if (!response.ok) {
+    throw new Error(payload.message || `${response.status} ${response.statusText}`);
+  }
+  return payload.data;
+}
+
+function showError(message, target = "error") {
+  $(target).textContent = message || "";
+}
+
+function shortUrl(link) {
+  const code = link.namespace ? `${link.namespace}/${link.short_code}` : link.short_code;
+  return `${location.origin}/${code}`;
+}
+
+function formatDate(value) {
+  return value ? new Date(value).toLocaleString() : "";
+}
+
+// Sign in
+
+async function signIn(event) {
+  event.preventDefault();
+  sessionStorage.setItem(TOKEN_KEY, $("token").value);
+  try {
+    await api("/api/admin/jobs", { admin: true });
+    showError("", "sign-in-error");
+    showDashboard();
+  } catch (e) {
+    sessionStorage.removeItem(TOKEN_KEY);
+    showError("That token was not accepted", "sign-in-error");
+  }
+}
+
+function signOut() {
+  sessionStorage.removeItem(TOKEN_KEY);
+  $("dashboard").hidden = true;
+  $("sign-out").hidden = true;
+  $("sign-in").hidden = false;
+}
+
+function showDashboard() {
+  $("sign-in").hidden = true;
+  $("dashboard").hidden = false;
+  $("sign-out").hidden = false;
+  loadLinks();
+}
+
+function showTab(name) {
+  document.querySelectorAll("nav button").forEach((button) => {
+    button.classList.toggle("active", button.dataset.tab === name);
+  });
+  document.querySelectorAll(".tab").forEach((tab) => {
+    tab.hidden = tab.id !== name;
+  });
+  showError("");
+  if (name === "stats") {
+    loadStats();
+  }
+}
+
+// Links
+
+async function loadLinks() {
+  const params = new URLSearchParams({ limit: PAGE_SIZE, offset });
+  const code = $("search-code").value.trim();
+  const url = $("search-url").value.trim();
+  if (code) {
+    params.set("short_code", code);
+  }
+  if (url) {
+    params.set("original_url", url);
+  }
+  const path = code || url ? "/api/urls/search" : "/api/urls";
+
+  try {
+    const links = await api(`${path}?${params}`);
+    renderLinks(links);
+    $("page-number").textContent = `Page ${offset / PAGE_SIZE + 1}`;
+    $("previous-page").disabled = offset === 0;
+    $("next-page").disabled = links.length < PAGE_SIZE;
+    showError("");
+  } catch (e) {
+    showError(e.message);
+  }
+}
+
+function renderLinks(links) {
+  const body = $("links-body");
+  body.replaceChildren();
+  for (const link of links) {
+    const row = body.insertRow();
+    const anchor = document.createElement("a");
+    anchor.href = shortUrl(link);
+    anchor.textContent = link.namespace ? `${link.namespace}/${link.short_code}` : link.short_code;
+    row.insertCell().append(anchor);
+
+    const destination = row.insertCell();
+    destination.className = "url";
+    destination.textContent = link.original_url;
+    destination.title = link.original_url;
+
+    row.insertCell().textContent = link.access_count;
+    row.insertCell().textContent = formatDate(link.created_at);
+    row.insertCell().textContent = formatDate(link.expires_at);
+  }
+}
+
+// Create
+
+async function createLink(event) {
+  event.preventDefault();
+  const body = { original_url: $("original-url").value.trim() };
+  const alias = $("custom-alias").value.trim();
+  const expiresAt = $("expires-at").value;
+  if (alias) {
+    body.custom_alias = alias;
+  }
+  if (expiresAt) {
+    body.expires_at = new Date(expiresAt).toISOString();
+  }
+
+  try {
+    const link = await api("/api/urls", { method: "POST", body });
+    const anchor = document.createElement("a");
+    anchor.href = shortUrl(link);
+    anchor.textContent = shortUrl(link);
+    $("create-result").replaceChildren("Created ", anchor);
+    $("create-form").reset();
+    showError("");
+  } catch (e) {
+    $("create-result").replaceChildren();
+    showError(e.message);
+  }
+}
+
+// Stats
+
+async function loadStats() {
+  try {
+    const [days, top] = await Promise.all([
+      api("/api/stats/group-by?dimension=day&limit=30"),
+      api(`/api/stats/top?window=${$("top-window").value}&limit=10`),
+    ]);
+    barChart(
+      $("daily-chart"),
+      days.reverse().map((day) => [day.group || "", day.clicks]),
+    );
+    barChart(
+      $("top-chart"),
+      top.map((link) => [link.short_code, link.clicks]),
+    );
+    showError("");
+  } catch (e) {
+    showError(e.message);
+  }
+}
+
+// Horizontal bars, one per [label, value] pair
+function barChart(container, rows) {
+  const rowHeight = 22;
+  const labelWidth = 140;
+  const width = 640;
+  const max = Math.max(1, ...rows.map(([, value]) => value));
+
+  const svg = document.createElementNS(SVG_NS, "svg");
+  svg.setAttribute("viewBox", `0 0 ${width} ${Math.max(1, rows.length) * rowHeight}`);
+  rows.forEach(([label, value], index) => {
+    const y = index * rowHeight;
+    const barWidth = ((width - labelWidth - 60) * value) / max;
+
+    const name = document.createElementNS(SVG_NS, "text");
+    name.setAttribute("x", 0);
+    name.setAttribute("y", y + 15);
+    name.textContent = label;
+
+    const bar = document.createElementNS(SVG_NS, "rect");
+    bar.setAttribute("x", labelWidth);
+    bar.setAttribute("y", y + 4);
+    bar.setAttribute("width", Math.max(1, barWidth));
+    bar.setAttribute("height", rowHeight - 8);
+
+    const count = document.createElementNS(SVG_NS, "text");
+    count.setAttribute("x", labelWidth + barWidth + 6);
+    count.setAttribute("y", y + 15);
+    count.textContent = value;
+
+    svg.append(name, bar, count);
+  });
+
+  container.replaceChildren(rows.length ? svg : "No clicks yet");
+}
+
+document.addEventListener("DOMContentLoaded", () => {
+  $("sign-in-form").addEventListener("submit", signIn);
+  $("sign-out").addEventListener("click", signOut);
+  $("create-form").addEventListener("submit", createLink);
+  $("top-window").addEventListener("change", loadStats);
+  $("search-form").addEventListener("submit", (event) => {
+    event.preventDefault();
+    offset = 0;
+    loadLinks();
+  });
+  $("previous-page").addEventListener("click", () => {
+    offset = Math.max(0, offset - PAGE_SIZE);
+    loadLinks();
+  });
+  $("next-page").addEventListener("click", () => {
+    offset += PAGE_SIZE;
+    loadLinks();
+  });
+  document.querySelectorAll("nav button").forEach((button) => {
+    button.addEventListener("click", () => showTab(button.dataset.tab));
+  });
+
+  if (token()) {
+    showDashboard();
+  } else {
+    signOut();
+  }
+});